@@ -1,5 +1,5 @@
 use num_rational::Ratio;
-use penumbra_crypto::{asset, stake::Penalty, Amount};
+use penumbra_crypto::{asset, proofs::ProofPolicy, signing::SigningPolicy, stake::Penalty, Amount};
 use penumbra_proto::client::v1alpha1 as pb_client;
 use penumbra_proto::core::chain::v1alpha1 as pb_chain;
 use penumbra_proto::core::crypto::v1alpha1 as pb_crypto;
@@ -83,6 +83,25 @@ pub struct ChainParameters {
     /// The threshold for a proposal to be vetoed, regardless of whether the "yes" and "no" votes
     /// would have passed it, as a ratio of "no with veto" votes over all total votes.
     pub proposal_veto_threshold: Ratio<u64>,
+
+    /// The epoch from which only Groth16 proofs are accepted, if one has been scheduled.
+    pub groth16_activation_epoch: Option<u64>,
+
+    /// The height from which signatures must be made in their signing context, if one has been
+    /// scheduled.
+    pub signing_context_activation_height: Option<u64>,
+}
+
+impl ChainParameters {
+    /// The [`ProofPolicy`] in force during `epoch`.
+    pub fn proof_policy(&self, epoch: u64) -> ProofPolicy {
+        ProofPolicy::at_epoch(self.groth16_activation_epoch, epoch)
+    }
+
+    /// The [`SigningPolicy`] in force at `height`.
+    pub fn signing_policy(&self, height: u64) -> SigningPolicy {
+        SigningPolicy::at_height(self.signing_context_activation_height, height)
+    }
 }
 
 impl Protobuf<pb_chain::ChainParameters> for ChainParameters {}
@@ -135,6 +154,8 @@ impl TryFrom<pb_chain::ChainParameters> for ChainParameters {
                 .proposal_veto_threshold
                 .ok_or_else(|| anyhow::anyhow!("missing `proposal_veto_threshold`"))?
                 .into(),
+            groth16_activation_epoch: msg.groth16_activation_epoch,
+            signing_context_activation_height: msg.signing_context_activation_height,
         })
     }
 }
@@ -181,6 +202,8 @@ impl From<ChainParameters> for pb_chain::ChainParameters {
             proposal_valid_quorum: Some(params.proposal_valid_quorum.into()),
             proposal_pass_threshold: Some(params.proposal_pass_threshold.into()),
             proposal_veto_threshold: Some(params.proposal_veto_threshold.into()),
+            groth16_activation_epoch: params.groth16_activation_epoch,
+            signing_context_activation_height: params.signing_context_activation_height,
         }
     }
 }
@@ -213,6 +236,10 @@ impl Default for ChainParameters {
             proposal_valid_quorum: Ratio::new(2, 5),
             proposal_pass_threshold: Ratio::new(1, 2),
             proposal_veto_threshold: Ratio::new(1, 3),
+            // transparent proofs are accepted until an activation epoch is scheduled
+            groth16_activation_epoch: None,
+            // signatures over the raw message are accepted until an activation height is scheduled
+            signing_context_activation_height: None,
         }
     }
 }
@@ -77,7 +77,16 @@ impl ActionHandler for Delegate {
         //
         // should give approximately the same results, they may not give
         // exactly the same results.
-        let expected_delegation_amount = next_rate_data.delegation_amount(d.unbonded_amount.into());
+        let expected_delegation_amount = next_rate_data
+            .delegation_amount(d.unbonded_amount.into())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "can't delegate {} unbonded stake to {} at exchange rate {}",
+                    d.unbonded_amount,
+                    d.validator_identity,
+                    next_rate_data.validator_exchange_rate
+                )
+            })?;
 
         if expected_delegation_amount != u64::from(d.delegation_amount) {
             return Err(anyhow::anyhow!(
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use penumbra_chain::sync::StatePayload;
 use penumbra_storage::{State, StateRead, StateTransaction};
-use penumbra_transaction::{action::Output, Transaction};
+use penumbra_transaction::{
+    action::{stateless_checks, Output},
+    Transaction,
+};
 use tracing::instrument;
 
 use crate::{action_handler::ActionHandler, shielded_pool::NoteManager};
@@ -15,6 +18,8 @@ impl ActionHandler for Output {
     async fn check_stateless(&self, _context: Arc<Transaction>) -> Result<()> {
         let output = self;
 
+        stateless_checks::check_output_body(&output.body).context("malformed output body")?;
+
         output.proof.verify(
             output.body.balance_commitment,
             output.body.note_payload.note_commitment,
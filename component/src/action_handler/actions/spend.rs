@@ -2,8 +2,12 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use penumbra_crypto::signing::{self, SigningPolicy, SpendAuthorization};
 use penumbra_storage::{State, StateRead, StateTransaction, StateWrite};
-use penumbra_transaction::{action::Spend, Transaction};
+use penumbra_transaction::{
+    action::{stateless_checks, Spend},
+    Transaction,
+};
 use tracing::instrument;
 
 use crate::{
@@ -19,12 +23,18 @@ impl ActionHandler for Spend {
         let effect_hash = context.transaction_body().effect_hash();
         let anchor = context.anchor;
 
-        // 2. Check spend auth signature using provided spend auth key.
-        spend
-            .body
-            .rk
-            .verify(effect_hash.as_ref(), &spend.auth_sig)
-            .context("spend auth signature failed to verify")?;
+        // 1. Check that the spend body is well-formed, before any more expensive checks.
+        stateless_checks::check_spend_body(&spend.body).context("malformed spend body")?;
+
+        // 2. Check spend auth signature using provided spend auth key. Whether it must be made in
+        // its signing context depends on the chain parameters, and is checked statefully.
+        signing::verify_under::<SpendAuthorization>(
+            SigningPolicy::AllowLegacy,
+            &spend.body.rk,
+            effect_hash.as_ref(),
+            &spend.auth_sig,
+        )
+        .context("spend auth signature failed to verify")?;
 
         // 3. Check that the proof verifies.
         spend
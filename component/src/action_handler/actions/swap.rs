@@ -4,14 +4,17 @@ use crate::{
     shielded_pool::NoteManager,
     stubdex::{StateReadExt as _, StateWriteExt as _},
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use async_trait::async_trait;
 
 use penumbra_chain::sync::StatePayload;
 use penumbra_crypto::MockFlowCiphertext;
 use penumbra_storage::{State, StateRead, StateTransaction};
-use penumbra_transaction::{action::Swap, IsAction, Transaction};
+use penumbra_transaction::{
+    action::{stateless_checks, Swap},
+    IsAction, Transaction,
+};
 use tracing::instrument;
 
 use crate::action_handler::ActionHandler;
@@ -20,6 +23,8 @@ use crate::action_handler::ActionHandler;
 impl ActionHandler for Swap {
     #[instrument(name = "swap", skip(self, _context))]
     async fn check_stateless(&self, _context: Arc<Transaction>) -> Result<()> {
+        stateless_checks::check_swap_body(&self.body).context("malformed swap body")?;
+
         self.proof.verify(
             self.body.fee_commitment,
             self.body.payload.commitment,
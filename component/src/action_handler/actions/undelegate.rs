@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
+use penumbra_crypto::{asset, Value, STAKING_TOKEN_ASSET_ID};
 use penumbra_storage::{State, StateTransaction};
 use penumbra_transaction::{action::Undelegate, Transaction};
 use tracing::instrument;
@@ -72,11 +73,18 @@ impl ActionHandler for Undelegate {
         // exactly the same results.
         let expected_unbonded_amount = rate_data.unbonded_amount(u.delegation_amount.into());
 
+        let format_unbonded = |amount| {
+            Value {
+                amount,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            }
+            .format(&asset::Cache::with_known_assets())
+        };
         ensure!(
             u64::from(u.unbonded_amount) == expected_unbonded_amount,
             "undelegation amount {} does not match expected amount {}",
-            u.unbonded_amount,
-            expected_unbonded_amount,
+            format_unbonded(u.unbonded_amount),
+            format_unbonded(expected_unbonded_amount.into()),
         );
 
         Ok(())
@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use penumbra_chain::StateReadExt as _;
+use penumbra_crypto::signing::{
+    self, SigningPolicy, ValidatorDefinition as ValidatorDefinitionContext,
+};
 use penumbra_storage::{State, StateTransaction};
 use penumbra_transaction::Transaction;
 use std::sync::Arc;
@@ -20,14 +23,16 @@ impl ActionHandler for ValidatorDefinition {
         // Check that validator definition is correctly signed and well-formed:
         let definition = validator::Definition::try_from(self.clone())
             .context("supplied proto is not a valid definition")?;
-        // First, check the signature:
+        // First, check the signature (whether it must be made in its signing context depends on
+        // the chain parameters, and is checked statefully):
         let definition_bytes = definition.validator.encode_to_vec();
-        definition
-            .validator
-            .identity_key
-            .0
-            .verify(&definition_bytes, &definition.auth_sig)
-            .context("validator definition signature failed to verify")?;
+        signing::verify_under::<ValidatorDefinitionContext>(
+            SigningPolicy::AllowLegacy,
+            &definition.validator.identity_key.0,
+            &definition_bytes,
+            &definition.auth_sig,
+        )
+        .context("validator definition signature failed to verify")?;
 
         // TODO(hdevalence) -- is this duplicated by the check during parsing?
         // Check that the funding streams do not exceed 100% commission (10000bps)
@@ -8,7 +8,9 @@ use penumbra_transaction::Transaction;
 
 use crate::shielded_pool::consensus_rules;
 
-use self::stateful::{claimed_anchor_is_valid, fmd_parameters_valid};
+use self::stateful::{
+    claimed_anchor_is_valid, fmd_parameters_valid, signatures_match_signing_policy,
+};
 
 use super::ActionHandler;
 
@@ -40,6 +42,7 @@ impl ActionHandler for Transaction {
     async fn check_stateful(&self, state: Arc<State>) -> Result<()> {
         claimed_anchor_is_valid(state.clone(), self).await?;
         fmd_parameters_valid(state.clone(), self).await?;
+        signatures_match_signing_policy(state.clone(), self).await?;
 
         // TODO: these can all be parallel tasks
         for action in self.actions() {
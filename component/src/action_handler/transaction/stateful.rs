@@ -1,11 +1,16 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use penumbra_chain::StateReadExt as _;
+use penumbra_crypto::signing::{self, GovernanceVote, SigningPolicy, SpendAuthorization};
+use penumbra_proto::Protobuf;
 use penumbra_storage::State;
-use penumbra_transaction::Transaction;
+use penumbra_transaction::{Action, Transaction};
 
-use crate::shielded_pool::{consensus_rules, StateReadExt as _};
+use crate::{
+    shielded_pool::{consensus_rules, StateReadExt as _},
+    stake::validator,
+};
 
 pub(super) async fn claimed_anchor_is_valid(
     state: Arc<State>,
@@ -34,3 +39,53 @@ pub(super) async fn fmd_parameters_valid(
         height,
     )
 }
+
+pub(super) async fn signatures_match_signing_policy(
+    state: Arc<State>,
+    transaction: &Transaction,
+) -> Result<()> {
+    let height = state.get_block_height().await?;
+    let policy = state.get_chain_params().await?.signing_policy(height);
+    if policy == SigningPolicy::AllowLegacy {
+        // Every signature was already checked statelessly under this policy.
+        return Ok(());
+    }
+
+    transaction
+        .verify_balance(policy)
+        .context("binding signature must be made in its signing context")?;
+
+    let effect_hash = transaction.transaction_body().effect_hash();
+    for action in transaction.actions() {
+        match action {
+            Action::Spend(spend) => signing::verify_under::<SpendAuthorization>(
+                policy,
+                &spend.body.rk,
+                effect_hash.as_ref(),
+                &spend.auth_sig,
+            )
+            .context("spend auth signature must be made in its signing context")?,
+            Action::ValidatorDefinition(definition) => {
+                let definition = validator::Definition::try_from(definition.clone())
+                    .context("supplied proto is not a valid definition")?;
+                signing::verify_under::<signing::ValidatorDefinition>(
+                    policy,
+                    &definition.validator.identity_key.0,
+                    &definition.validator.encode_to_vec(),
+                    &definition.auth_sig,
+                )
+                .context("validator definition signature must be made in its signing context")?
+            }
+            Action::ValidatorVote(vote) => signing::verify_under::<GovernanceVote>(
+                policy,
+                &vote.body.governance_key.0,
+                &vote.body.encode_to_vec(),
+                &vote.auth_sig,
+            )
+            .context("validator vote signature must be made in its signing context")?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -1,17 +1,16 @@
 use std::collections::BTreeSet;
 
 use anyhow::{Context, Result};
+use penumbra_crypto::signing::SigningPolicy;
 use penumbra_transaction::Transaction;
 
 #[tracing::instrument(skip(tx))]
 pub(super) fn valid_binding_signature(tx: &Transaction) -> Result<()> {
-    let effect_hash = tx.transaction_body().effect_hash();
+    tracing::debug!(bvk = ?tx.binding_verification_key(), effect_hash = ?tx.transaction_body().effect_hash());
 
-    tracing::debug!(bvk = ?tx.binding_verification_key(), effect_hash = ?effect_hash);
-
-    // Check binding signature.
-    tx.binding_verification_key()
-        .verify(effect_hash.as_ref(), tx.binding_sig())
+    // Check that the actions and fee balance. Whether the binding signature must be made in its
+    // signing context depends on the chain parameters, and is checked statefully.
+    tx.verify_balance(SigningPolicy::AllowLegacy)
         .context("binding signature failed to verify")
 }
 
@@ -1,5 +1,7 @@
 use anyhow::{Context as _, Result};
 
+use penumbra_crypto::signing::{self, GovernanceVote, SigningPolicy};
+
 use super::proposal::{self, chain_params};
 use penumbra_transaction::action::{
     ProposalSubmit, ProposalWithdraw, ValidatorVote, ValidatorVoteBody,
@@ -74,12 +76,16 @@ pub mod stateless {
     }
 
     pub fn validator_vote(ValidatorVote { body, auth_sig }: &ValidatorVote) -> Result<()> {
-        // Check the signature using the GOVERNANCE KEY:
+        // Check the signature using the GOVERNANCE KEY (whether it must be made in its signing
+        // context depends on the chain parameters, and is checked statefully):
         let body_bytes = body.encode_to_vec();
-        body.governance_key
-            .0
-            .verify(&body_bytes, auth_sig)
-            .context("validator vote signature failed to verify")?;
+        signing::verify_under::<GovernanceVote>(
+            SigningPolicy::AllowLegacy,
+            &body.governance_key.0,
+            &body_bytes,
+            auth_sig,
+        )
+        .context("validator vote signature failed to verify")?;
 
         // This is stateless verification, so we still need to check that the proposal being voted
         // on exists, and that this validator hasn't voted on it already.
@@ -105,7 +111,9 @@ pub mod stateful {
     use super::*;
     use crate::stake::StateReadExt as _;
     use penumbra_chain::StateReadExt as _;
-    use penumbra_crypto::{stake::IdentityKey, Amount, GovernanceKey, STAKING_TOKEN_DENOM};
+    use penumbra_crypto::{
+        asset, stake::IdentityKey, Amount, GovernanceKey, Value, STAKING_TOKEN_ASSET_ID,
+    };
     use penumbra_storage::State;
     use penumbra_transaction::action::{ProposalDepositClaim, ProposalPayload};
 
@@ -120,11 +128,9 @@ pub mod stateful {
         let chain_parameters = state.get_chain_params().await?;
         if *deposit_amount != chain_parameters.proposal_deposit_amount {
             anyhow::bail!(
-                "submitted proposal deposit of {}{} does not match required proposal deposit of {}{}",
-                deposit_amount,
-                *STAKING_TOKEN_DENOM,
-                chain_parameters.proposal_deposit_amount,
-                *STAKING_TOKEN_DENOM,
+                "submitted proposal deposit of {} does not match required proposal deposit of {}",
+                format_staking_amount(*deposit_amount),
+                format_staking_amount(chain_parameters.proposal_deposit_amount),
             );
         }
 
@@ -317,6 +323,15 @@ pub mod stateful {
         Ok(())
     }
 
+    /// Format an amount of the staking token in its display units, for error messages.
+    fn format_staking_amount(amount: Amount) -> String {
+        Value {
+            amount,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        }
+        .format(&asset::Cache::with_known_assets())
+    }
+
     async fn proposal_claim_valid_deposit(
         state: &State,
         proposal_id: u64,
@@ -325,11 +340,9 @@ pub mod stateful {
         if let Some(proposal_deposit_amount) = state.proposal_deposit_amount(proposal_id).await? {
             if claim_deposit_amount != proposal_deposit_amount {
                 anyhow::bail!(
-                    "proposal deposit claim for {}{} does not match proposal deposit of {}{}",
-                    claim_deposit_amount,
-                    *STAKING_TOKEN_DENOM,
-                    proposal_deposit_amount,
-                    *STAKING_TOKEN_DENOM,
+                    "proposal deposit claim for {} does not match proposal deposit of {}",
+                    format_staking_amount(claim_deposit_amount),
+                    format_staking_amount(proposal_deposit_amount),
                 );
             }
         } else {
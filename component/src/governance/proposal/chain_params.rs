@@ -21,6 +21,8 @@ pub enum MutableParam {
     SlashingPenaltyDowntime,
     SignedBlocksWindowLen,
     MissedBlocksMaximum,
+    Groth16ActivationEpoch,
+    SigningContextActivationHeight,
 }
 
 impl Protobuf<pb::MutableChainParameter> for MutableParam {}
@@ -53,7 +55,7 @@ impl TryFrom<MutableParametersResponse> for MutableParam {
 }
 impl MutableParam {
     // TODO: would be nicer as a macro but after a bit of fiddling i couldn't get it right
-    pub const fn iter() -> [MutableParam; 7] {
+    pub const fn iter() -> [MutableParam; 9] {
         [
             MutableParam::UnbondingEpochs,
             MutableParam::ActiveValidatorLimit,
@@ -62,6 +64,8 @@ impl MutableParam {
             MutableParam::SlashingPenaltyDowntime,
             MutableParam::SignedBlocksWindowLen,
             MutableParam::MissedBlocksMaximum,
+            MutableParam::Groth16ActivationEpoch,
+            MutableParam::SigningContextActivationHeight,
         ]
     }
 
@@ -76,6 +80,8 @@ impl MutableParam {
             MutableParam::SlashingPenaltyDowntime => "Slashing penalty specified in basis points applied to validator reward rates as punishment for downtime. Must be at least 1.",
             MutableParam::SignedBlocksWindowLen => "Number of blocks to use as the window for detecting validator downtime. Must be at least 2 and greater than or equal to missed_blocks_maximum.",
             MutableParam::MissedBlocksMaximum => "The maximum number of blocks a validator may miss in the signed_blocks_window_len before being slashed for downtime. Must be at least 1 and less than or equal to signed_blocks_window_len.",
+            MutableParam::Groth16ActivationEpoch => "The epoch from which only Groth16 proofs are accepted.",
+            MutableParam::SigningContextActivationHeight => "The height from which signatures must be made in their signing context.",
         }
     }
 }
@@ -92,6 +98,10 @@ impl std::str::FromStr for MutableParam {
             "slashing_penalty_downtime" => Result::Ok(MutableParam::SlashingPenaltyDowntime),
             "signed_blocks_window_len" => Result::Ok(MutableParam::SignedBlocksWindowLen),
             "missed_blocks_maximum" => Result::Ok(MutableParam::MissedBlocksMaximum),
+            "groth16_activation_epoch" => Result::Ok(MutableParam::Groth16ActivationEpoch),
+            "signing_context_activation_height" => {
+                Result::Ok(MutableParam::SigningContextActivationHeight)
+            }
             _ => Err(anyhow::anyhow!("mutable parameter not found")),
         }
     }
@@ -109,6 +119,10 @@ impl fmt::Display for MutableParam {
             MutableParam::SlashingPenaltyDowntime => write!(f, "slashing_penalty_downtime"),
             MutableParam::SignedBlocksWindowLen => write!(f, "signed_blocks_window_len"),
             MutableParam::MissedBlocksMaximum => write!(f, "missed_blocks_maximum"),
+            MutableParam::Groth16ActivationEpoch => write!(f, "groth16_activation_epoch"),
+            MutableParam::SigningContextActivationHeight => {
+                write!(f, "signing_context_activation_height")
+            }
         }
     }
 }
@@ -221,6 +235,14 @@ pub fn resolve_parameters(
             MutableParam::MissedBlocksMaximum => {
                 new_chain_params.missed_blocks_maximum = value.parse().context("invalid value")?
             }
+            MutableParam::Groth16ActivationEpoch => {
+                new_chain_params.groth16_activation_epoch =
+                    Some(value.parse().context("invalid value")?)
+            }
+            MutableParam::SigningContextActivationHeight => {
+                new_chain_params.signing_context_activation_height =
+                    Some(value.parse().context("invalid value")?)
+            }
         }
     }
 
@@ -295,5 +317,14 @@ fn param_value_is_valid(param: &MutableParam, value: &str) -> bool {
             // Missed blocks maximum must be at least 1.
             value >= 1
         }
+        MutableParam::Groth16ActivationEpoch => {
+            // Any epoch is valid; one in the past takes effect at the next epoch boundary.
+            value.parse::<u64>().is_ok()
+        }
+        MutableParam::SigningContextActivationHeight => {
+            // Any height is valid; one in the past takes effect immediately, which rejects
+            // transactions signed by clients that don't yet use signing contexts.
+            value.parse::<u64>().is_ok()
+        }
     }
 }
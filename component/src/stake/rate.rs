@@ -1,6 +1,9 @@
 //! Staking reward and delegation token exchange rates.
 
-use penumbra_crypto::{stake::Penalty, Amount};
+use penumbra_crypto::{
+    stake::{ExchangeRate, Penalty},
+    Amount,
+};
 use penumbra_proto::{
     client::v1alpha1::NextValidatorRateResponse, core::stake::v1alpha1 as pb, Protobuf,
 };
@@ -88,26 +91,21 @@ impl RateData {
     /// unbonded_amount == rate_data.unbonded_amount(delegation_amount)
     /// ```
     /// but in general *not both*, because the computation involves rounding.
-    pub fn delegation_amount(&self, unbonded_amount: u64) -> u64 {
-        // validator_exchange_rate fits in 32 bits, but unbonded_amount is 64-bit;
-        // upconvert to u128 intermediates and panic if the result is too large (unlikely)
-        ((unbonded_amount as u128 * 1_0000_0000) / self.validator_exchange_rate as u128)
-            .try_into()
-            .unwrap()
+    ///
+    /// Returns `None` if the validator's exchange rate is zero, e.g. after it was slashed by a full
+    /// penalty, or if the result doesn't fit in a `u64`.
+    pub fn delegation_amount(&self, unbonded_amount: u64) -> Option<u64> {
+        u128::from(
+            ExchangeRate(self.validator_exchange_rate).delegation_amount(unbonded_amount.into())?,
+        )
+        .try_into()
+        .ok()
     }
 
     pub fn slash(&self, penalty: Penalty) -> Self {
         let mut slashed = self.clone();
-        // (1 - penalty) * exchange_rate
-        slashed.validator_exchange_rate = self
-            .validator_exchange_rate
-            // Slashing penalty is in bps^2, so we divide by 1e8
-            .saturating_sub(
-                u64::try_from(
-                    (self.validator_exchange_rate as u128 * penalty.0 as u128) / 1_0000_0000,
-                )
-                .unwrap(),
-            );
+        slashed.validator_exchange_rate =
+            ExchangeRate(self.validator_exchange_rate).slash(penalty).0;
 
         slashed
     }
@@ -126,11 +124,14 @@ impl RateData {
     /// ```
     /// but in general *not both*, because the computation involves rounding.
     pub fn unbonded_amount(&self, delegation_amount: u64) -> u64 {
-        // validator_exchange_rate fits in 32 bits, but unbonded_amount is 64-bit;
-        // upconvert to u128 intermediates and panic if the result is too large (unlikely)
-        ((delegation_amount as u128 * self.validator_exchange_rate as u128) / 1_0000_0000)
-            .try_into()
-            .unwrap()
+        // panic if the result is too large (unlikely)
+        u128::from(
+            ExchangeRate(self.validator_exchange_rate)
+                .unbonded_amount(delegation_amount.into())
+                .expect("a u64 amount times a u64 rate fits in a u128"),
+        )
+        .try_into()
+        .unwrap()
     }
 
     /// Computes the validator's voting power at this epoch given the total supply of the
@@ -144,13 +145,23 @@ impl RateData {
 
     /// Uses this `RateData` to build a `Delegate` transaction action that
     /// delegates `unbonded_amount` of the staking token.
-    pub fn build_delegate(&self, unbonded_amount: u64) -> Delegate {
-        Delegate {
-            delegation_amount: self.delegation_amount(unbonded_amount).into(),
+    ///
+    /// Errors if nothing can be delegated at this rate, as for [`RateData::delegation_amount`].
+    pub fn build_delegate(&self, unbonded_amount: u64) -> anyhow::Result<Delegate> {
+        let delegation_amount = self.delegation_amount(unbonded_amount).ok_or_else(|| {
+            anyhow::anyhow!(
+                "can't delegate {} to {} at exchange rate {}",
+                unbonded_amount,
+                self.identity_key,
+                self.validator_exchange_rate
+            )
+        })?;
+        Ok(Delegate {
+            delegation_amount: delegation_amount.into(),
             epoch_index: self.epoch_index,
             unbonded_amount: unbonded_amount.into(),
             validator_identity: self.identity_key.clone(),
-        }
+        })
     }
 
     /// Uses this `RateData` to build an `Undelegate` transaction action that
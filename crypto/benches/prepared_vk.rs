@@ -0,0 +1,28 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use penumbra_crypto::proofs::groth16::{
+    prepared_verifying_key_from_bytes, prepared_verifying_key_to_bytes, OutputCircuit,
+    ParameterSetup, SpendCircuit,
+};
+
+fn bench(c: &mut Criterion) {
+    let (_spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+    let (_output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+
+    let mut group = c.benchmark_group("prepared-verifying-key");
+    for (name, vk) in [("spend", spend_vk), ("output", output_vk)] {
+        let bytes = prepared_verifying_key_to_bytes(&ark_groth16::prepare_verifying_key(&vk))
+            .expect("can serialize prepared verifying key");
+
+        group.bench_function(format!("{}_prepare", name).as_str(), |b| {
+            b.iter(|| ark_groth16::prepare_verifying_key(&vk))
+        });
+        group.bench_function(format!("{}_load", name).as_str(), |b| {
+            b.iter(|| prepared_verifying_key_from_bytes(&bytes).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,153 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use decaf377::Fr;
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+use penumbra_crypto::{
+    asset,
+    keys::{RandomizedVerificationKey, SeedPhrase, SpendKey},
+    proofs::groth16::{
+        metrics::all_circuit_metrics, msm, OutputCircuit, OutputProofPlan, ParameterSetup,
+        ProverOptions, SpendCircuit, SpendProofPlan,
+    },
+    Note, Value,
+};
+use penumbra_tct as tct;
+
+fn spend(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(1312);
+    let (pk, vk) = SpendCircuit::generate_test_parameters_with_rng(&mut rng);
+
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([b'b'; 32]), 0);
+    let (address, _dtk_d) = sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let value = Value {
+        amount: 100u64.into(),
+        asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+    };
+    let note = Note::generate(&mut rng, &address, value);
+    let note_commitment = note.commit();
+    let spend_auth_randomizer = Fr::from(2u64);
+    let v_blinding = Fr::from(3u64);
+    let nk = *sk.nullifier_key();
+    let ak: VerificationKey<SpendAuth> = sk.spend_auth_key().into();
+    let rk = RandomizedVerificationKey::try_from(VerificationKey::from(
+        sk.spend_auth_key().randomize(&spend_auth_randomizer),
+    ))
+    .unwrap();
+    let mut nct = tct::Tree::new();
+    nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+    let anchor = nct.root();
+    let note_commitment_proof = nct.witness(note_commitment).unwrap();
+    let balance_commitment = value.commit(v_blinding);
+    let nullifier = nk.derive_nullifier(0.into(), &note_commitment);
+
+    let prove_with_options = |options: &ProverOptions, rng: &mut ChaCha20Rng| {
+        SpendProofPlan {
+            note_commitment_proof: note_commitment_proof.clone(),
+            note: note.clone(),
+            v_blinding,
+            spend_auth_randomizer: spend_auth_randomizer.into(),
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk,
+        }
+        .prove_with_options(options, rng, &pk)
+        .expect("can create proof")
+    };
+    let proof = prove_with_options(&ProverOptions::default(), &mut rng);
+
+    let mut group = c.benchmark_group("spend");
+    group.sample_size(10);
+    group.bench_function("prove", |b| {
+        b.iter(|| prove_with_options(&ProverOptions::default(), &mut rng))
+    });
+    // Compare the MSM backends on the same circuit.
+    #[allow(unused_mut)]
+    let mut backends = vec![ProverOptions::default().with_msm_backend(msm::ArkworksMsm)];
+    #[cfg(feature = "parallel-msm")]
+    backends.push(ProverOptions::default().with_msm_backend(msm::ParallelMsm::default()));
+    for options in backends {
+        let name = options.msm_backend.as_ref().expect("backend is set").name();
+        group.bench_function(format!("prove/msm/{}", name), |b| {
+            b.iter(|| prove_with_options(&options, &mut rng))
+        });
+    }
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            proof
+                .verify(&vk, anchor, balance_commitment, nullifier, rk)
+                .expect("proof verifies")
+        })
+    });
+    group.finish();
+}
+
+fn output(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(1312);
+    let (pk, vk) = OutputCircuit::generate_test_parameters_with_rng(&mut rng);
+
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([b'b'; 32]), 0);
+    let (address, _dtk_d) = sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let value = Value {
+        amount: 100u64.into(),
+        asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+    };
+    let note = Note::generate(&mut rng, &address, value);
+    let note_commitment = note.commit();
+    let v_blinding = Fr::from(3u64);
+    let balance_commitment = value.commit(v_blinding);
+
+    let prove = |rng: &mut ChaCha20Rng| {
+        OutputProofPlan {
+            note: note.clone(),
+            v_blinding,
+            balance_commitment,
+            note_commitment,
+        }
+        .prove(rng, &pk)
+        .expect("can create proof")
+    };
+    let proof = prove(&mut rng);
+
+    let mut group = c.benchmark_group("output");
+    group.sample_size(10);
+    group.bench_function("prove", |b| b.iter(|| prove(&mut rng)));
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            proof
+                .verify(&vk, balance_commitment, note_commitment)
+                .expect("proof verifies")
+        })
+    });
+    group.finish();
+}
+
+/// Print the size of each circuit alongside the timings, so that changes in
+/// proving time can be traced back to the gadgets responsible.
+fn report_circuit_sizes(_c: &mut Criterion) {
+    for metrics in all_circuit_metrics().expect("can measure circuits") {
+        println!(
+            "{}: {} constraints, {} witnesses, {} instances",
+            metrics.name,
+            metrics.total.constraints,
+            metrics.total.witnesses,
+            metrics.total.instances
+        );
+        for (label, counts) in metrics.by_label() {
+            println!("  {}: {} constraints", label, counts.constraints);
+        }
+    }
+}
+
+criterion_group!(benches, report_circuit_sizes, spend, output);
+criterion_main!(benches);
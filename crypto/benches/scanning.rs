@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand_core::OsRng;
+
+use penumbra_crypto::{
+    keys::{SeedPhrase, SpendKey},
+    EncryptedNote, Note, Value,
+};
+
+fn bench(c: &mut Criterion) {
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+    let fvk = sk.full_viewing_key();
+    let other_sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+    let (address, _dtk) = other_sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let value: Value = "1upenumbra".parse().unwrap();
+
+    // A block's worth of payloads, none of which are ours, as when syncing.
+    let payloads = (0..100)
+        .map(|_| {
+            let note = Note::generate(&mut OsRng, &address, value);
+            EncryptedNote {
+                note_commitment: note.commit(),
+                ephemeral_key: note.ephemeral_public_key(),
+                encrypted_note: note.encrypt(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("trial-decryption");
+    group.bench_function("individual", |b| {
+        b.iter(|| {
+            payloads
+                .iter()
+                .map(|payload| payload.trial_decrypt(fvk))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| EncryptedNote::trial_decrypt_batch(&payloads, fvk))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
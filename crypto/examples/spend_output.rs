@@ -0,0 +1,173 @@
+//! Walk through the lifecycle of a note: from keys, to a spend of the note and
+//! an output of its value to a new address, each with its proof, to a binding
+//! signature showing that the transaction balances.
+//!
+//! Every intermediate artifact is printed as JSON. Proving and verifying keys
+//! are generated on the fly, so the proofs are only valid in this example.
+//!
+//! Run with `cargo run --release -p penumbra-crypto --example spend_output`.
+
+use anyhow::Context;
+use penumbra_crypto::{
+    balance,
+    keys::{RandomizedVerificationKey, SeedPhrase, SpendKey},
+    proofs::groth16::{
+        OutputCircuit, OutputProofPlan, ParameterSetup, SpendCircuit, SpendProofPlan,
+    },
+    rdsa::VerificationKey,
+    signing::{self, BalanceBinding, SigningPolicy},
+    transaction::Fee,
+    FieldExt, Fr, Note, Value, STAKING_TOKEN_ASSET_ID,
+};
+use penumbra_tct as tct;
+use rand_core::OsRng;
+use serde_json::json;
+
+fn main() -> anyhow::Result<()> {
+    let mut rng = OsRng;
+
+    // Keys: a spend key derived from a fresh seed phrase, and two of its
+    // addresses, one to receive a note and one to send it on to.
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut rng), 0);
+    let fvk = sk.full_viewing_key();
+    let (sender, _dtk) = fvk.incoming().payment_address(0u64.into());
+    let (recipient, _dtk) = fvk.incoming().payment_address(1u64.into());
+    print_step(
+        "keys",
+        json!({
+            "full_viewing_key": fvk.to_string(),
+            "sender": sender.to_string(),
+            "recipient": recipient.to_string(),
+        }),
+    );
+
+    // A note of 100 staking tokens, added to a small commitment tree among
+    // some unrelated commitments.
+    let value = Value {
+        amount: 100u64.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+    let note = Note::generate(&mut rng, &sender, value);
+    let mut nct = tct::Tree::new();
+    for i in 0..3u64 {
+        nct.insert(tct::Witness::Forget, tct::Commitment(i.into()))?;
+    }
+    nct.insert(tct::Witness::Keep, note.commit())?;
+    nct.end_block()?;
+    let anchor = nct.root();
+    let note_commitment_proof = nct
+        .witness(note.commit())
+        .context("note commitment is witnessed")?;
+    print_step(
+        "note",
+        json!({
+            "note": note,
+            "note_commitment": note.commit().to_string(),
+            "position": u64::from(note_commitment_proof.position()),
+            "anchor": anchor.to_string(),
+        }),
+    );
+
+    // Spend the note, committing to its value with a random blinding factor.
+    let spend_blinding = Fr::rand_uniform(&mut rng);
+    let spend_auth_randomizer = Fr::rand_uniform(&mut rng);
+    let rsk = sk.spend_auth_key().randomize(&spend_auth_randomizer);
+    let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk))?;
+    let spend_balance_commitment = value.commit(spend_blinding);
+    let nullifier = fvk.derive_nullifier(note_commitment_proof.position(), &note.commit());
+    let spend_plan = SpendProofPlan {
+        note_commitment_proof,
+        note: note.clone(),
+        v_blinding: spend_blinding,
+        spend_auth_randomizer: spend_auth_randomizer.into(),
+        ak: sk.spend_auth_key().into(),
+        nk: *sk.nullifier_key(),
+        anchor,
+        balance_commitment: spend_balance_commitment,
+        nullifier,
+        rk,
+    };
+
+    let (spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+    let spend_proof = spend_plan.prove(&mut rng, &spend_pk)?;
+    spend_proof.verify(&spend_vk, anchor, spend_balance_commitment, nullifier, rk)?;
+    print_step(
+        "spend",
+        json!({
+            "nullifier": nullifier.to_string(),
+            "rk": hex::encode(rk.to_bytes()),
+            "balance_commitment": hex::encode(spend_balance_commitment.to_bytes()),
+            "proof": hex::encode(spend_proof.to_bytes()),
+        }),
+    );
+
+    // Send 90 of the tokens on to the recipient, leaving 10 for the fee.
+    let output_value = Value {
+        amount: 90u64.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+    let output_note = Note::generate(&mut rng, &recipient, output_value);
+    let output_blinding = Fr::rand_uniform(&mut rng);
+    let output_balance_commitment = output_value.commit(output_blinding);
+    let output_plan = OutputProofPlan {
+        note_commitment: output_note.commit(),
+        note: output_note.clone(),
+        v_blinding: output_blinding,
+        balance_commitment: output_balance_commitment,
+    };
+
+    let (output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+    let output_proof = output_plan.prove(&mut rng, &output_pk)?;
+    output_proof.verify(&output_vk, output_balance_commitment, output_note.commit())?;
+    print_step(
+        "output",
+        json!({
+            "note": output_note,
+            "note_commitment": output_note.commit().to_string(),
+            "balance_commitment": hex::encode(output_balance_commitment.to_bytes()),
+            "proof": hex::encode(output_proof.to_bytes()),
+        }),
+    );
+
+    // The spend provides value and the output consumes it, so the output's
+    // commitment enters the balance negated. Together with the fee, they sum
+    // to a commitment to zero under the difference of the blinding factors,
+    // which signs for the balance.
+    let fee = Fee::from_staking_token_amount(10u64.into());
+    let commitments = [spend_balance_commitment, -output_balance_commitment];
+    let synthetic_blinding_factor = spend_blinding - output_blinding;
+    let effect_hash =
+        blake2b_simd::blake2b(&[spend_proof.to_bytes(), output_proof.to_bytes()].concat());
+    let binding_sig = signing::sign::<BalanceBinding, _>(
+        &mut rng,
+        &synthetic_blinding_factor.into(),
+        effect_hash.as_bytes(),
+    );
+    balance::Commitment::verify_balance(
+        SigningPolicy::RequireContext,
+        &commitments,
+        &fee,
+        effect_hash.as_bytes(),
+        &binding_sig,
+    )?;
+    print_step(
+        "balance",
+        json!({
+            "fee": u64::try_from(fee.amount())?,
+            "binding_verification_key": hex::encode(
+                balance::Commitment::binding_verification_key(&commitments, &fee).to_bytes()
+            ),
+            "binding_sig": hex::encode(binding_sig.to_bytes()),
+        }),
+    );
+
+    Ok(())
+}
+
+fn print_step(name: &str, artifacts: serde_json::Value) {
+    println!("== {}", name);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&artifacts).expect("artifacts serialize")
+    );
+}
@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use penumbra_crypto::{EncryptedNote, EncryptedNoteRef, ENCRYPTED_NOTE_BYTES};
+
+// Decoding arbitrary bytes must never panic, must reject anything that isn't
+// exactly one payload long, and must re-encode to the same bytes.
+fuzz_target!(|data: &[u8]| {
+    let payload_ref = match EncryptedNoteRef::new(data) {
+        Ok(payload_ref) => payload_ref,
+        Err(_) => {
+            assert_ne!(data.len(), ENCRYPTED_NOTE_BYTES);
+            return;
+        }
+    };
+    assert_eq!(data.len(), ENCRYPTED_NOTE_BYTES);
+
+    if let Ok(payload) = EncryptedNote::try_from(data) {
+        assert_eq!(&payload.to_bytes()[..], data);
+        assert_eq!(
+            payload_ref.note_commitment().unwrap(),
+            payload.note_commitment
+        );
+    } else {
+        assert!(payload_ref.note_commitment().is_err());
+    }
+});
@@ -0,0 +1,152 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use penumbra_crypto::{
+    asset, balance,
+    keys::{RandomizedVerificationKey, SeedPhrase, SpendKey},
+    proofs::groth16::{ParameterSetup, SpendCircuit, SpendProof, SpendProofPlan},
+    rdsa::{SpendAuth, VerificationKey},
+    Fr, Note, Nullifier, Value,
+};
+use penumbra_proto::core::crypto::v1alpha1 as pb;
+use penumbra_tct as tct;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+/// The encoding of an honest spend proof followed by its public inputs: the
+/// anchor, balance commitment, nullifier and randomized verification key.
+const ENCODING_LEN: usize = penumbra_crypto::proofs::groth16::SPEND_PROOF_LEN_BYTES + 4 * 32;
+
+struct Honest {
+    pvk: ark_groth16::PreparedVerifyingKey<decaf377::Bls12_377>,
+    encoding: [u8; ENCODING_LEN],
+}
+
+/// One honest proof, made once per process, since proving is far slower than
+/// verifying.
+static HONEST: Lazy<Honest> = Lazy::new(|| {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let (pk, vk) = SpendCircuit::generate_test_parameters_with_rng(&mut rng);
+
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([1; 32]), 0);
+    let (address, _dtk) = sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let value = Value {
+        amount: 10u64.into(),
+        asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+    };
+    let note = Note::generate(&mut rng, &address, value);
+    let note_commitment = note.commit();
+    let mut nct = tct::Tree::new();
+    nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+    let anchor = nct.root();
+    let note_commitment_proof = nct.witness(note_commitment).unwrap();
+
+    let v_blinding = Fr::from(3u64);
+    let spend_auth_randomizer = Fr::from(5u64);
+    let rsk = sk.spend_auth_key().randomize(&spend_auth_randomizer);
+    let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
+    let nk = *sk.nullifier_key();
+    let ak: VerificationKey<SpendAuth> = sk.spend_auth_key().into();
+    let balance_commitment = value.commit(v_blinding);
+    let nullifier = nk.derive_nullifier(0.into(), &note_commitment);
+
+    let proof = SpendProofPlan {
+        note_commitment_proof,
+        note,
+        v_blinding,
+        spend_auth_randomizer: spend_auth_randomizer.into(),
+        ak,
+        nk,
+        anchor,
+        balance_commitment,
+        nullifier,
+        rk,
+    }
+    .prove(&mut rng, &pk)
+    .unwrap();
+
+    let mut encoding = [0u8; ENCODING_LEN];
+    let parts: [&[u8]; 5] = [
+        &proof.to_bytes(),
+        &pb::MerkleRoot::from(anchor).inner,
+        &balance_commitment.to_bytes(),
+        &nullifier.to_bytes(),
+        &rk.to_bytes(),
+    ];
+    let mut offset = 0;
+    for part in parts {
+        encoding[offset..offset + part.len()].copy_from_slice(part);
+        offset += part.len();
+    }
+    assert_eq!(offset, ENCODING_LEN);
+
+    Honest {
+        pvk: ark_groth16::prepare_verifying_key(&vk),
+        encoding,
+    }
+});
+
+/// The proof and public inputs in an encoding, if they all decode.
+fn decode(
+    bytes: &[u8; ENCODING_LEN],
+) -> Option<(
+    SpendProof,
+    tct::Root,
+    balance::Commitment,
+    Nullifier,
+    RandomizedVerificationKey,
+)> {
+    let (proof, inputs) = bytes.split_at(ENCODING_LEN - 4 * 32);
+    let proof = SpendProof::try_from(proof).ok()?;
+    let anchor = tct::Root::try_from(pb::MerkleRoot {
+        inner: inputs[0..32].to_vec(),
+    })
+    .ok()?;
+    let balance_commitment = balance::Commitment::try_from(&inputs[32..64]).ok()?;
+    let nullifier = Nullifier::try_from(&inputs[64..96]).ok()?;
+    let rk =
+        RandomizedVerificationKey::try_from(<[u8; 32]>::try_from(&inputs[96..128]).ok()?).ok()?;
+    Some((proof, anchor, balance_commitment, nullifier, rk))
+}
+
+// The input is an offset into the encoding of an honest proof and its public
+// inputs, followed by bytes to XOR into the encoding from that offset.
+// Decoding and verifying the mutated encoding must never panic, and if it
+// decodes to anything other than the honest proof and inputs, it must not
+// verify.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 3 {
+        return;
+    }
+    let honest = &*HONEST;
+    let offset = u16::from_le_bytes([data[0], data[1]]) as usize % ENCODING_LEN;
+
+    let mut mutated = honest.encoding;
+    for (byte, mask) in mutated[offset..].iter_mut().zip(&data[2..]) {
+        *byte ^= mask;
+    }
+
+    let decoded = match decode(&mutated) {
+        Some(decoded) => decoded,
+        None => return,
+    };
+    let (proof, anchor, balance_commitment, nullifier, rk) = decoded;
+    let (honest_proof, honest_anchor, honest_balance_commitment, honest_nullifier, honest_rk) =
+        decode(&honest.encoding).unwrap();
+    let is_honest = proof == honest_proof
+        && anchor == honest_anchor
+        && balance_commitment == honest_balance_commitment
+        && nullifier == honest_nullifier
+        && rk.element() == honest_rk.element();
+    let verified = proof
+        .verify_prepared(&honest.pvk, anchor, balance_commitment, nullifier, rk)
+        .is_ok();
+    assert_eq!(
+        verified, is_honest,
+        "a mutated spend proof verified, or the honest one didn't"
+    );
+});
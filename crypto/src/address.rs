@@ -1,6 +1,5 @@
-use std::io::{Cursor, Read, Write};
-
 use ark_serialize::CanonicalDeserialize;
+use bech32::FromBase32;
 use f4jumble::{f4jumble, f4jumble_inv};
 use penumbra_proto::{core::crypto::v1alpha1 as pb, serializers::bech32str, Protobuf};
 use rand::{CryptoRng, Rng};
@@ -77,19 +76,39 @@ impl Address {
         &self.transmission_key_s
     }
 
-    pub fn to_vec(&self) -> Vec<u8> {
-        let mut bytes = std::io::Cursor::new(Vec::new());
-        bytes
-            .write_all(&self.diversifier().0)
-            .expect("can write diversifier into vec");
-        bytes
-            .write_all(&self.transmission_key().0)
-            .expect("can write transmission key into vec");
+    /// The compact binary encoding of this address: the diversifier, the
+    /// transmission key and the clue key, concatenated.
+    ///
+    /// This is the form hashed and encrypted by the protocol; the bytes shown
+    /// to users and used in protobufs are jumbled, see [`Address::to_vec`].
+    pub fn to_bytes(&self) -> [u8; ADDRESS_LEN_BYTES] {
+        let mut bytes = [0u8; ADDRESS_LEN_BYTES];
+        bytes[0..16].copy_from_slice(&self.d.0);
+        bytes[16..48].copy_from_slice(&self.pk_d.0);
+        bytes[48..80].copy_from_slice(&self.ck_d.0);
         bytes
-            .write_all(&self.clue_key().0)
-            .expect("can write clue key into vec");
+    }
 
-        f4jumble(bytes.get_ref()).expect("can jumble")
+    /// Parse an address from its compact binary encoding, as produced by
+    /// [`Address::to_bytes`].
+    ///
+    /// This fails if the transmission key is not the canonical encoding of an
+    /// [`Fq`] element.
+    pub fn from_bytes(bytes: &[u8; ADDRESS_LEN_BYTES]) -> anyhow::Result<Self> {
+        let (d, rest) = bytes.split_at(16);
+        let (pk_d, ck_d) = rest.split_at(32);
+        Address::from_components(
+            Diversifier(d.try_into().expect("diversifier is 16 bytes")),
+            ka::Public(pk_d.try_into().expect("transmission key is 32 bytes")),
+            fmd::ClueKey(ck_d.try_into().expect("clue key is 32 bytes")),
+        )
+        .ok_or_else(|| anyhow::anyhow!("address malformed"))
+    }
+
+    /// The jumbled binary encoding of this address, used in protobufs and in
+    /// the human-readable encoding.
+    pub fn to_vec(&self) -> Vec<u8> {
+        f4jumble(&self.to_bytes()).expect("can jumble")
     }
 
     /// A randomized dummy address.
@@ -113,7 +132,7 @@ impl Address {
     }
 
     /// Short form suitable for displaying in a UI.
-    pub fn display_short_form(&self) -> String {
+    pub fn short_form(&self) -> String {
         let full_address = format!("{}", self);
         // Fixed prefix is `penumbrav2t` plus the Bech32m separator `1`.
         let fixed_prefix = format!("{}{}", bech32str::address::BECH32_PREFIX, '1');
@@ -121,6 +140,23 @@ impl Address {
 
         format!("{}…", &full_address[0..num_chars_to_display])
     }
+
+    /// The payload to encode this address as a QR code.
+    ///
+    /// This is the Bech32m encoding of the address in upper case, which
+    /// consists only of characters in the QR alphanumeric mode, and so can be
+    /// encoded more densely than the usual lower case form.
+    pub fn to_qr_payload(&self) -> String {
+        self.to_string().to_ascii_uppercase()
+    }
+
+    /// Parse an address from a QR code payload, as produced by
+    /// [`Address::to_qr_payload`].
+    ///
+    /// The usual lower case encoding is also accepted, but not a mix of cases.
+    pub fn from_qr_payload(payload: &str) -> Result<Self, AddressParseError> {
+        payload.trim().parse()
+    }
 }
 
 impl Protobuf<pb::Address> for Address {}
@@ -156,14 +192,60 @@ impl std::fmt::Debug for Address {
     }
 }
 
+/// The reason a string could not be parsed as an [`Address`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddressParseError {
+    /// The string is Bech32m, but its checksum is wrong, as when a character
+    /// has been mistyped.
+    #[error("address checksum is invalid, check for mistyped characters")]
+    BadChecksum,
+    /// The string is not a valid Bech32m encoding.
+    #[error("address is not validly encoded: {0}")]
+    InvalidEncoding(bech32::Error),
+    /// The string uses the original Bech32 checksum, rather than Bech32m.
+    #[error("address uses a Bech32 checksum, expected Bech32m")]
+    WrongVariant,
+    /// The string encodes something other than a Penumbra address.
+    #[error("address has human-readable part {found:?}, expected {expected:?}")]
+    WrongHrp {
+        expected: &'static str,
+        found: String,
+    },
+    /// The string encodes the wrong number of bytes.
+    #[error("address encodes {found} bytes, expected {expected}")]
+    WrongLength { expected: usize, found: usize },
+    /// The string encodes the right number of bytes, but they are not the
+    /// components of an address.
+    #[error("address does not encode a valid diversifier, transmission key and clue key")]
+    InvalidComponents,
+}
+
 impl std::str::FromStr for Address {
-    type Err = anyhow::Error;
+    type Err = AddressParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        pb::Address {
-            inner: bech32str::decode(s, bech32str::address::BECH32_PREFIX, bech32str::Bech32m)?,
+        let (hrp, data, variant) = bech32::decode(s).map_err(|err| match err {
+            bech32::Error::InvalidChecksum => AddressParseError::BadChecksum,
+            err => AddressParseError::InvalidEncoding(err),
+        })?;
+        if hrp != bech32str::address::BECH32_PREFIX {
+            return Err(AddressParseError::WrongHrp {
+                expected: bech32str::address::BECH32_PREFIX,
+                found: hrp,
+            });
         }
-        .try_into()
+        if variant != bech32::Variant::Bech32m {
+            return Err(AddressParseError::WrongVariant);
+        }
+
+        let bytes = Vec::from_base32(&data).map_err(AddressParseError::InvalidEncoding)?;
+        if bytes.len() != ADDRESS_LEN_BYTES {
+            return Err(AddressParseError::WrongLength {
+                expected: ADDRESS_LEN_BYTES,
+                found: bytes.len(),
+            });
+        }
+        Address::try_from(&bytes[..]).map_err(|_| AddressParseError::InvalidComponents)
     }
 }
 
@@ -193,36 +275,11 @@ impl TryFrom<&[u8]> for Address {
 
         let unjumbled_bytes =
             f4jumble_inv(jumbled_bytes).ok_or_else(|| anyhow::anyhow!("invalid address"))?;
-        let mut bytes = Cursor::new(unjumbled_bytes);
-
-        let mut diversifier_bytes = [0u8; 16];
-        bytes
-            .read_exact(&mut diversifier_bytes)
-            .map_err(|_| anyhow::anyhow!("address malformed"))?;
-
-        let mut pk_d_bytes = [0u8; 32];
-        bytes
-            .read_exact(&mut pk_d_bytes)
-            .map_err(|_| anyhow::anyhow!("address malformed"))?;
-
-        let mut clue_key_bytes = [0; 32];
-        bytes
-            .read_exact(&mut clue_key_bytes)
+        let bytes: [u8; ADDRESS_LEN_BYTES] = unjumbled_bytes
+            .try_into()
             .map_err(|_| anyhow::anyhow!("address malformed"))?;
 
-        let diversifier = Diversifier(diversifier_bytes);
-
-        let address = Address::from_components(
-            diversifier,
-            ka::Public(pk_d_bytes),
-            fmd::ClueKey(clue_key_bytes),
-        );
-
-        if address.is_none() {
-            return Err(anyhow::anyhow!("address malformed"));
-        }
-
-        Ok(address.unwrap())
+        Address::from_bytes(&bytes)
     }
 }
 
@@ -266,6 +323,87 @@ mod tests {
         assert_eq!(addr, dest);
     }
 
+    #[test]
+    fn test_compact_bytes_roundtrip() {
+        let dest = Address::dummy(&mut OsRng);
+
+        let bytes = dest.to_bytes();
+        assert_eq!(&bytes[0..16], &dest.diversifier().0);
+        assert_eq!(Address::from_bytes(&bytes).unwrap(), dest);
+
+        // A transmission key which is not a canonical field element is rejected.
+        let mut malformed = bytes;
+        malformed[16..48].copy_from_slice(&[0xff; 32]);
+        assert!(Address::from_bytes(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_qr_payload_roundtrip() {
+        let dest = Address::dummy(&mut OsRng);
+
+        let payload = dest.to_qr_payload();
+        // Only characters in the QR alphanumeric mode are used.
+        assert!(payload
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+        assert_eq!(Address::from_qr_payload(&payload).unwrap(), dest);
+        assert_eq!(Address::from_qr_payload(&dest.to_string()).unwrap(), dest);
+
+        // Mixed case payloads are rejected, as in Bech32m.
+        let mut mixed = payload.clone();
+        mixed.replace_range(0..1, "p");
+        assert!(Address::from_qr_payload(&mixed).is_err());
+    }
+
+    #[test]
+    fn test_address_parse_errors() {
+        let dest = Address::dummy(&mut OsRng);
+        let encoded = dest.to_string();
+
+        // Change one character of the data, keeping it a valid Bech32 character.
+        let mut mistyped = encoded.clone().into_bytes();
+        let last = mistyped.len() - 1;
+        mistyped[last] = if mistyped[last] == b'q' { b'p' } else { b'q' };
+        assert_eq!(
+            String::from_utf8(mistyped).unwrap().parse::<Address>(),
+            Err(AddressParseError::BadChecksum)
+        );
+
+        let other_hrp = bech32str::encode(&dest.to_vec(), "penumbravalid", bech32str::Bech32m);
+        assert!(matches!(
+            other_hrp.parse::<Address>(),
+            Err(AddressParseError::WrongHrp { .. })
+        ));
+
+        let bech32 = bech32str::encode(
+            &dest.to_vec(),
+            bech32str::address::BECH32_PREFIX,
+            bech32str::Bech32,
+        );
+        assert_eq!(
+            bech32.parse::<Address>(),
+            Err(AddressParseError::WrongVariant)
+        );
+
+        let short = bech32str::encode(
+            &dest.to_vec()[..40],
+            bech32str::address::BECH32_PREFIX,
+            bech32str::Bech32m,
+        );
+        assert_eq!(
+            short.parse::<Address>(),
+            Err(AddressParseError::WrongLength {
+                expected: ADDRESS_LEN_BYTES,
+                found: 40
+            })
+        );
+
+        assert!(matches!(
+            "not an address".parse::<Address>(),
+            Err(AddressParseError::InvalidEncoding(_))
+        ));
+    }
+
     #[test]
     fn test_address_keys_are_diversified() {
         let rng = OsRng;
@@ -9,11 +9,11 @@ mod denom;
 mod id;
 mod registry;
 
-pub use amount::Amount;
+pub use amount::{Amount, AmountOutOfRange, FromAmount, AMOUNT_BITS};
 pub use cache::Cache;
 pub use denom::{Denom, Unit};
 pub use id::{Id, VALUE_GENERATOR_DOMAIN_SEP};
-pub use registry::{Registry, REGISTRY};
+pub use registry::{ibc_denom, Registry, REGISTRY};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(try_from = "pb::Asset", into = "pb::Asset")]
@@ -189,6 +189,40 @@ mod tests {
         assert_eq!(format!("{}", base_denom), "cube".to_string());
     }
 
+    #[test]
+    fn registered_assets_parse_and_format() {
+        let mut registry = Registry::new();
+        let atom = ibc_denom("transfer/channel-0/uatom");
+        assert_eq!(
+            atom,
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        assert_eq!(ibc_denom("uatom"), "uatom");
+
+        // Units may be registered in any order.
+        let denom = registry
+            .register(&atom, &[("matom", 3), ("atom", 6)])
+            .unwrap();
+        assert_eq!(registry.parse_denom(&atom), Some(denom.clone()));
+        assert!(registry.parse_denom("atom").is_none());
+        assert_eq!(registry.parse_unit("atom").base(), denom);
+        assert_eq!(registry.denom_by_id(&denom.id()), Some(denom.clone()));
+        assert_eq!(denom.best_unit_for(1_500_000u64.into()).to_string(), "atom");
+        assert_eq!(denom.best_unit_for(1_500u64.into()).to_string(), "matom");
+
+        // The fixed registry doesn't know about it.
+        assert_eq!(REGISTRY.parse_unit("atom").base().to_string(), "atom");
+
+        // Known denominations can't be registered again.
+        assert!(registry.register("upenumbra", &[]).is_err());
+        assert!(registry.register("uosmo", &[("penumbra", 6)]).is_err());
+        assert!(registry.register("uosmo", &[("matom", 3)]).is_err());
+        assert!(registry.register("uosmo", &[("osmo", 0)]).is_err());
+        assert!(registry
+            .register("uosmo", &[("osmo", 6), ("kosmo", 6)])
+            .is_err());
+    }
+
     proptest! {
         #[test]
         fn displaydenom_parsing_formatting_roundtrip(
@@ -1,4 +1,4 @@
-use crate::{Fq, Fr};
+use crate::{FieldExt, Fq, Fr};
 use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, iter::Sum, num::NonZeroU128, ops};
@@ -28,6 +28,59 @@ impl Amount {
             inner: u128::from_le_bytes(bytes),
         }
     }
+
+    /// Convert a field element back into an [`Amount`], failing if it lies
+    /// outside the amount domain `[0, 2^128)`.
+    pub fn try_from_fq(fq: Fq) -> Result<Amount, AmountOutOfRange> {
+        Self::try_from_le_field_bytes(fq.to_bytes())
+    }
+
+    /// Convert a scalar back into an [`Amount`], failing if it lies outside
+    /// the amount domain `[0, 2^128)`.
+    pub fn try_from_fr(fr: Fr) -> Result<Amount, AmountOutOfRange> {
+        Self::try_from_le_field_bytes(fr.to_bytes())
+    }
+
+    fn try_from_le_field_bytes(bytes: [u8; 32]) -> Result<Amount, AmountOutOfRange> {
+        if bytes[16..].iter().any(|&b| b != 0) {
+            return Err(AmountOutOfRange);
+        }
+        let mut le_bytes = [0u8; 16];
+        le_bytes.copy_from_slice(&bytes[..16]);
+        Ok(Amount::from_le_bytes(le_bytes))
+    }
+}
+
+/// The number of bits in the amount domain.
+///
+/// Both the base field and the scalar field are larger than `2^AMOUNT_BITS`,
+/// so amounts embed into either field without wrapping.
+pub const AMOUNT_BITS: usize = 128;
+
+/// Error returned when a field element does not represent a valid [`Amount`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("field element exceeds the amount domain of {} bits", AMOUNT_BITS)]
+pub struct AmountOutOfRange;
+
+/// Checked conversion of an [`Amount`] into a field element.
+///
+/// This conversion is always injective: every amount is less than `2^128`,
+/// which is smaller than the modulus of both `Fq` and `Fr`. Its inverse is
+/// [`Amount::try_from_fq`] (or [`Amount::try_from_fr`]).
+pub trait FromAmount {
+    fn from_amount(amount: Amount) -> Self;
+}
+
+impl FromAmount for Fq {
+    fn from_amount(amount: Amount) -> Fq {
+        Fq::from(amount.inner)
+    }
+}
+
+impl FromAmount for Fr {
+    fn from_amount(amount: Amount) -> Fr {
+        Fr::from(amount.inner)
+    }
 }
 
 impl From<Amount> for pb::Amount {
@@ -176,13 +229,13 @@ impl From<NonZeroU128> for Amount {
 
 impl From<Amount> for Fq {
     fn from(amount: Amount) -> Fq {
-        Fq::from(amount.inner)
+        Fq::from_amount(amount)
     }
 }
 
 impl From<Amount> for Fr {
     fn from(amount: Amount) -> Fr {
-        Fr::from(amount.inner)
+        Fr::from_amount(amount)
     }
 }
 
@@ -220,7 +273,8 @@ impl Sum for Amount {
 
 #[cfg(test)]
 mod test {
-    use crate::Amount;
+    use super::FromAmount;
+    use crate::{Amount, Fq, Fr, One};
     use penumbra_proto::core::crypto::v1alpha1 as pb;
     use rand::RngCore;
     use rand_core::OsRng;
@@ -284,4 +338,18 @@ mod test {
         let hi = (value as u128) << 64;
         assert_eq!(hi, encode_decode(hi))
     }
+
+    #[test]
+    fn field_round_trip_max() {
+        let amount = Amount { inner: u128::MAX };
+        assert_eq!(Amount::try_from_fq(Fq::from_amount(amount)), Ok(amount));
+        assert_eq!(Amount::try_from_fr(Fr::from_amount(amount)), Ok(amount));
+    }
+
+    #[test]
+    fn field_out_of_range_rejected() {
+        let just_over = Fq::from_amount(Amount { inner: u128::MAX }) + Fq::one();
+        assert!(Amount::try_from_fq(just_over).is_err());
+        assert!(Amount::try_from_fq(-Fq::one()).is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, ops::Deref};
 
 use super::{Denom, Id, REGISTRY};
+use crate::STAKING_TOKEN_DENOM;
 
 /// On-chain data structures only record a fixed-size [`Id`], so this type
 /// allows caching known [`BaseDenom`]s.
@@ -12,6 +13,14 @@ use super::{Denom, Id, REGISTRY};
 #[derive(Clone, Default, Debug)]
 pub struct Cache(BTreeMap<Id, Denom>);
 
+impl Cache {
+    /// Make a cache of the denominations known without consulting the chain, i.e. the staking
+    /// token's, to format values with when no better source of asset metadata is available.
+    pub fn with_known_assets() -> Self {
+        [STAKING_TOKEN_DENOM.clone()].into_iter().collect()
+    }
+}
+
 // Implementing Deref but not DerefMut means people get unlimited read access,
 // but can only write into the cache through approved methods.
 impl Deref for Cache {
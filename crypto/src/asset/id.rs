@@ -6,7 +6,10 @@ use once_cell::sync::Lazy;
 use penumbra_proto::{core::crypto::v1alpha1 as pb, serializers::bech32str, Protobuf};
 use serde::{Deserialize, Serialize};
 
-use crate::{Fq, Value};
+use crate::{
+    stake::{DelegationToken, IdentityKey, UnbondingToken},
+    Fq, Value,
+};
 
 /// An identifier for an IBC asset type.
 ///
@@ -122,4 +125,19 @@ impl Id {
             asset_id: self.clone(),
         }
     }
+
+    /// Compute the asset ID of the delegation token for the given validator.
+    pub fn delegation_token(validator_identity: &IdentityKey) -> Id {
+        DelegationToken::from(validator_identity).id()
+    }
+
+    /// Compute the asset ID of the unbonding token for the given validator,
+    /// unbonding from `start_epoch_index` until `end_epoch_index`.
+    pub fn unbonding_token(
+        validator_identity: &IdentityKey,
+        start_epoch_index: u64,
+        end_epoch_index: u64,
+    ) -> Id {
+        UnbondingToken::new(*validator_identity, start_epoch_index, end_epoch_index).id()
+    }
 }
@@ -1,13 +1,20 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use once_cell::sync::Lazy;
 use regex::{Regex, RegexSet};
+use sha2::{Digest, Sha256};
 
-use crate::asset::{denom, Denom, Unit};
+use crate::asset::{self, denom, Denom, Unit};
 
 /// A registry of known assets, providing metadata related to a denomination string.
 ///
-/// The [`REGISTRY`] constant provides an instance of the registry.
+/// The [`REGISTRY`] constant provides an instance of the registry with the
+/// asset families known to Penumbra. Other assets, e.g. tokens transferred in
+/// over IBC, can be added to a copy of it with [`Registry::register`].
+#[derive(Clone)]
 pub struct Registry {
     /// Individual regexes for base denominations
     base_regexes: Vec<Regex>,
@@ -35,9 +42,113 @@ pub struct Registry {
     // require working out how to write closures), we could use boxed closures
     // instead of a function.
     constructors: Vec<fn(&str) -> denom::Inner>,
+
+    /// Units of the assets added with [`Registry::register`], including their
+    /// base units, indexed by their exact denomination string.
+    registered_units: BTreeMap<String, Unit>,
+
+    /// Assets added with [`Registry::register`], indexed by asset ID.
+    registered_denoms: BTreeMap<asset::Id, Denom>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Registry {
+    /// Create a registry of the asset families known to Penumbra, the same as
+    /// [`REGISTRY`].
+    pub fn new() -> Self {
+        known_assets()
+    }
+
+    /// Register an asset with base denomination `base_denom` and the given
+    /// display units, each a denomination and its exponent relative to the base
+    /// denomination (e.g. `("penumbra", 6)` for `upenumbra`).
+    ///
+    /// The units may be given in any order. Neither the base denomination nor
+    /// any unit may already be known to the registry.
+    pub fn register(&mut self, base_denom: &str, units: &[(&str, u8)]) -> anyhow::Result<Denom> {
+        let mut names = BTreeSet::new();
+        let mut exponents = BTreeSet::new();
+        for denom in std::iter::once(base_denom).chain(units.iter().map(|(denom, _)| *denom)) {
+            if denom.is_empty() {
+                return Err(anyhow::anyhow!("denominations must be non-empty"));
+            }
+            if !names.insert(denom) {
+                return Err(anyhow::anyhow!("duplicate denomination {}", denom));
+            }
+            if self.is_known(denom) {
+                return Err(anyhow::anyhow!("denomination {} is already known", denom));
+            }
+        }
+        for (denom, exponent) in units {
+            if *exponent == 0 {
+                return Err(anyhow::anyhow!(
+                    "display unit {} must have a nonzero exponent",
+                    denom
+                ));
+            }
+            if !exponents.insert(*exponent) {
+                return Err(anyhow::anyhow!(
+                    "display unit {} has the same exponent as another unit",
+                    denom
+                ));
+            }
+            10u64.checked_pow(*exponent as u32).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "exponent {} of display unit {} is too large",
+                    exponent,
+                    denom
+                )
+            })?;
+        }
+
+        // Units are kept in priority order, largest first, so that
+        // `Denom::best_unit_for` finds the largest unit that fits.
+        let mut units = units
+            .iter()
+            .map(|(denom, exponent)| denom::UnitData {
+                exponent: *exponent,
+                denom: denom.to_string(),
+            })
+            .collect::<Vec<_>>();
+        units.sort_by(|a, b| b.exponent.cmp(&a.exponent));
+
+        let denom = Denom {
+            inner: Arc::new(denom::Inner::new(base_denom.to_string(), units)),
+        };
+        for unit in denom.units() {
+            self.registered_units.insert(unit.to_string(), unit);
+        }
+        self.registered_denoms.insert(denom.id(), denom.clone());
+
+        Ok(denom)
+    }
+
+    /// Whether `raw_denom` is a base denomination or display unit of an asset
+    /// known to the registry.
+    pub fn is_known(&self, raw_denom: &str) -> bool {
+        self.registered_units.contains_key(raw_denom)
+            || self.base_set.is_match(raw_denom)
+            || self.display_set.is_match(raw_denom)
+    }
+
+    /// Look up an asset added with [`Registry::register`] by its asset ID.
+    ///
+    /// Assets in the families known to Penumbra are matched by pattern rather
+    /// than listed, so they can only be found by denomination.
+    pub fn denom_by_id(&self, id: &asset::Id) -> Option<Denom> {
+        self.registered_denoms.get(id).cloned()
+    }
+
+    /// The assets added with [`Registry::register`].
+    pub fn registered(&self) -> impl Iterator<Item = &Denom> {
+        self.registered_denoms.values()
+    }
+
     /// Attempt to parse the provided `raw_denom` as a base denomination.
     ///
     /// If the denomination is a known base denomination, returns `Some` with
@@ -48,6 +159,16 @@ impl Registry {
     /// If the denomination is unknown, returns `Some` with the parsed base
     /// denomination and default display denomination (base = display).
     pub fn parse_denom(&self, raw_denom: &str) -> Option<Denom> {
+        // Registered assets are matched exactly, and can't overlap with the
+        // known families.
+        if let Some(unit) = self.registered_units.get(raw_denom) {
+            return if unit.exponent() == 0 {
+                Some(unit.base())
+            } else {
+                None
+            };
+        }
+
         // We hope that our regexes are disjoint (TODO: add code to test this)
         // so that there will only ever be one match from the RegexSet.
 
@@ -84,6 +205,10 @@ impl Registry {
     /// denomination. Otherwise, returns a display denomination associated with
     /// the input parsed as a base denomination.
     pub fn parse_unit(&self, raw_unit: &str) -> Unit {
+        if let Some(unit) = self.registered_units.get(raw_unit) {
+            return unit.clone();
+        }
+
         if let Some(display_index) = self.display_set.matches(raw_unit).iter().next() {
             let base_index = self.display_to_base[display_index];
             // We need to determine which unit we matched
@@ -163,12 +288,33 @@ impl Builder {
             .unwrap(),
             display_to_base,
             display_regexes,
+            registered_units: BTreeMap::new(),
+            registered_denoms: BTreeMap::new(),
         }
     }
 }
 
+/// Compute the denomination of a token transferred over IBC, from its trace:
+/// the path of ports and channels it was transferred along, followed by its
+/// base denomination on its source chain (e.g. `transfer/channel-0/uatom`).
+///
+/// As in ICS-20, this is `ibc/` followed by the uppercase hex SHA-256 hash of
+/// the trace. A trace without a path is a native denomination, and is
+/// returned unchanged.
+pub fn ibc_denom(trace: &str) -> String {
+    if !trace.contains('/') {
+        return trace.to_string();
+    }
+    format!(
+        "ibc/{}",
+        hex::encode_upper(Sha256::digest(trace.as_bytes()))
+    )
+}
+
 /// A fixed registry of known asset families.
-pub static REGISTRY: Lazy<Registry> = Lazy::new(|| {
+pub static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+fn known_assets() -> Registry {
     Builder::default()
         .add_asset(
             "^upenumbra$",
@@ -303,4 +449,4 @@ pub static REGISTRY: Lazy<Registry> = Lazy::new(|| {
             }) as for<'r> fn(&'r str) -> _,
         )
         .build()
-});
+}
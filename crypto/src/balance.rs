@@ -9,7 +9,12 @@ use std::{
 
 use crate::{asset, Value};
 
+pub mod audit;
+mod blinding;
+mod bundle;
 pub mod commitment;
+pub use blinding::{BalanceBlinding, BlindingDerivation};
+pub use bundle::{check_balance, Bundle};
 pub use commitment::Commitment;
 
 mod imbalance;
@@ -17,6 +22,7 @@ mod iter;
 use commitment::VALUE_BLINDING_GENERATOR;
 use decaf377::Fr;
 use imbalance::Imbalance;
+pub use imbalance::Sign;
 
 /// A `Balance` is a "vector of [`Value`]s", where some values may be required, while others may be
 /// provided. For a transaction to be valid, its balance must be zero.
@@ -65,6 +71,25 @@ impl Balance {
         self.iter().filter_map(Imbalance::provided)
     }
 
+    /// Use the provided [`asset::Cache`] to format this balance, listing the provided values and
+    /// then the required values, which are prefixed with a `-`.
+    ///
+    /// Each value is formatted as by [`Value::format`], so values of unknown assets are given in
+    /// base units with their asset ID.
+    pub fn format(&self, cache: &asset::Cache) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        self.provided()
+            .map(|value| value.format(cache))
+            .chain(
+                self.required()
+                    .map(|value| format!("-{}", value.format(cache))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     /// Commit to a [`Balance`] using a provided blinding factor.
     ///
     /// This is like a vectorized [`Value::commit`].
@@ -247,7 +272,7 @@ impl From<Value> for Balance {
 
 #[cfg(test)]
 mod test {
-    use crate::{Fr, Zero, STAKING_TOKEN_ASSET_ID};
+    use crate::{Fr, Zero, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM};
     use once_cell::sync::Lazy;
     use proptest::prelude::*;
 
@@ -309,6 +334,31 @@ mod test {
         assert!(balance.is_zero());
     }
 
+    #[test]
+    fn format_uses_denom_units() {
+        let mut balance = Balance::zero();
+        assert_eq!(balance.format(&asset::Cache::with_known_assets()), "0");
+
+        let gm = asset::REGISTRY.parse_denom("ugm").unwrap();
+        balance += Value {
+            amount: 10_500_000u64.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        balance -= Value {
+            amount: 3u64.into(),
+            asset_id: gm.id(),
+        };
+        let cache = asset::Cache::with_known_assets();
+        assert_eq!(
+            balance.format(&cache),
+            format!("10.5penumbra, -3{}", gm.id())
+        );
+        let cache = [STAKING_TOKEN_DENOM.clone(), gm]
+            .into_iter()
+            .collect::<asset::Cache>();
+        assert_eq!(balance.format(&cache), "10.5penumbra, -3ugm");
+    }
+
     #[derive(Debug, Clone)]
     enum Expression {
         Value(Value),
@@ -0,0 +1,188 @@
+//! Offline auditing of a wallet's balance commitments.
+//!
+//! A wallet that records the notes it spent and created, along with the blinding factors it used
+//! for each action's balance commitment, can recompute every one of those commitments and compare
+//! them against the ones included on chain. A mismatch means that the wallet's records do not
+//! describe the transactions that were actually accepted by consensus.
+
+use std::fmt::{self, Display, Formatter};
+
+use decaf377::Fr;
+
+use super::{Commitment, Sign};
+use crate::{note, Note};
+
+/// A balance commitment to audit: a note spent or created by an action, the blinding factor the
+/// wallet recorded for the action, and the balance commitment of the action on chain.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    /// The note spent or created by the action.
+    pub note: Note,
+    /// Whether the note's value is provided to the transaction (a spend) or required by it (an
+    /// output).
+    pub sign: Sign,
+    /// The blinding factor of the action's balance commitment.
+    pub blinding: Fr,
+    /// The balance commitment of the action on chain, or `None` if no action on chain spent or
+    /// created the note.
+    pub on_chain: Option<Commitment>,
+}
+
+impl Entry {
+    /// An entry for a spend of `note`.
+    pub fn spend(note: Note, blinding: Fr, on_chain: Option<Commitment>) -> Self {
+        Self {
+            note,
+            sign: Sign::Provided,
+            blinding,
+            on_chain,
+        }
+    }
+
+    /// An entry for an output creating `note`.
+    pub fn output(note: Note, blinding: Fr, on_chain: Option<Commitment>) -> Self {
+        Self {
+            note,
+            sign: Sign::Required,
+            blinding,
+            on_chain,
+        }
+    }
+
+    /// The balance commitment the action should have, according to the wallet's records.
+    pub fn expected(&self) -> Commitment {
+        self.note.value().commit_signed(self.sign, self.blinding)
+    }
+}
+
+/// An entry whose recomputed balance commitment does not match the one on chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The index of the entry in the audit.
+    pub index: usize,
+    /// The commitment to the note of the entry.
+    pub note_commitment: note::Commitment,
+    /// The balance commitment recomputed from the wallet's records.
+    pub expected: Commitment,
+    /// The balance commitment on chain, if any.
+    pub on_chain: Option<Commitment>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.on_chain {
+            Some(on_chain) => write!(
+                f,
+                "entry {} (note {:?}): expected balance commitment {}, found {} on chain",
+                self.index,
+                self.note_commitment,
+                hex::encode(self.expected.to_bytes()),
+                hex::encode(on_chain.to_bytes()),
+            ),
+            None => write!(
+                f,
+                "entry {} (note {:?}): no balance commitment found on chain",
+                self.index, self.note_commitment,
+            ),
+        }
+    }
+}
+
+/// The result of auditing a set of balance commitments.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The number of entries checked.
+    pub checked: usize,
+    /// The entries whose balance commitments did not match, in the order they were checked.
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Report {
+    /// Whether every balance commitment matched.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Return an error describing every mismatch, if there were any.
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        let mismatches = self
+            .mismatches
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(anyhow::anyhow!(
+            "{} of {} balance commitments do not match the chain:\n{}",
+            self.mismatches.len(),
+            self.checked,
+            mismatches
+        ))
+    }
+}
+
+/// Recompute the balance commitment of each entry, and report those that do not match the
+/// commitment on chain.
+pub fn audit(entries: impl IntoIterator<Item = Entry>) -> Report {
+    let mut report = Report::default();
+    for (index, entry) in entries.into_iter().enumerate() {
+        report.checked += 1;
+        let expected = entry.expected();
+        if entry.on_chain != Some(expected) {
+            report.mismatches.push(Mismatch {
+                index,
+                note_commitment: entry.note.commit(),
+                expected,
+                on_chain: entry.on_chain,
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        keys::{SeedPhrase, SpendKey},
+        Value,
+    };
+
+    #[test]
+    fn audit_reports_mismatches() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let (address, _dtk) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+        let note = Note::generate(&mut OsRng, &address, "1upenumbra".parse::<Value>().unwrap());
+        let blinding = Fr::from(7u64);
+
+        let spend = note.value().commit(blinding);
+        let output = (-crate::Balance::from(note.value())).commit(blinding);
+
+        let report = audit([
+            Entry::spend(note.clone(), blinding, Some(spend)),
+            Entry::output(note.clone(), blinding, Some(output)),
+            // The wrong blinding factor, a commitment with the wrong sign, and a missing action
+            Entry::spend(note.clone(), Fr::from(8u64), Some(spend)),
+            Entry::spend(note.clone(), blinding, Some(output)),
+            Entry::output(note, blinding, None),
+        ]);
+
+        assert_eq!(report.checked, 5);
+        assert_eq!(
+            report
+                .mismatches
+                .iter()
+                .map(|mismatch| mismatch.index)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert!(report.into_result().is_err());
+    }
+}
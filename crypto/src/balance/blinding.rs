@@ -0,0 +1,127 @@
+use decaf377::Fr;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{prf, sample::SampleUniform};
+
+/// Records how a [`BalanceBlinding`] was derived, so that it can be
+/// reconstructed and checked later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlindingDerivation {
+    /// The seed of the transaction plan the blinding was derived from.
+    pub plan_seed: [u8; 32],
+    /// The index of the action the blinding was derived for.
+    pub action_index: u32,
+}
+
+impl BlindingDerivation {
+    /// Recompute the blinding factor described by this derivation.
+    pub fn blinding_factor(&self) -> Fr {
+        prf::expand_ff(
+            b"Penumbra_BalBlnd",
+            &self.plan_seed,
+            &self.action_index.to_le_bytes(),
+        )
+    }
+}
+
+/// The blinding factor of an action's balance commitment.
+///
+/// This is a distinct type from the spend authorization randomizer (also an
+/// `Fr`), so that the two can't be accidentally swapped. Blinding factors
+/// derived from a plan seed carry their [`BlindingDerivation`], so auditing
+/// tools can reconstruct every blinding used in a transaction from the seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalanceBlinding {
+    factor: Fr,
+    derivation: Option<BlindingDerivation>,
+}
+
+impl BalanceBlinding {
+    /// Sample a fresh blinding factor using `rng`.
+    ///
+    /// The result has no derivation, since it can't be reconstructed.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            factor: Fr::rand_uniform(rng),
+            derivation: None,
+        }
+    }
+
+    /// Deterministically derive the blinding factor for the action at
+    /// `action_index` in the plan with the given `plan_seed`.
+    pub fn derive(plan_seed: [u8; 32], action_index: u32) -> Self {
+        let derivation = BlindingDerivation {
+            plan_seed,
+            action_index,
+        };
+        Self {
+            factor: derivation.blinding_factor(),
+            derivation: Some(derivation),
+        }
+    }
+
+    /// The blinding factor as a scalar.
+    pub fn to_scalar(&self) -> Fr {
+        self.factor
+    }
+
+    /// How this blinding factor was derived, if it was derived from a plan seed.
+    pub fn derivation(&self) -> Option<&BlindingDerivation> {
+        self.derivation.as_ref()
+    }
+
+    /// Check that this blinding factor is the one derived for the action at
+    /// `action_index` in the plan with the given `plan_seed`.
+    pub fn check_derivation(&self, plan_seed: [u8; 32], action_index: u32) -> anyhow::Result<()> {
+        let expected = BlindingDerivation {
+            plan_seed,
+            action_index,
+        };
+        if let Some(derivation) = &self.derivation {
+            if *derivation != expected {
+                anyhow::bail!("blinding factor is tagged with a different derivation");
+            }
+        }
+        if expected.blinding_factor() != self.factor {
+            anyhow::bail!(
+                "blinding factor was not derived for action {} of the plan",
+                action_index
+            );
+        }
+        Ok(())
+    }
+}
+
+impl From<Fr> for BalanceBlinding {
+    fn from(factor: Fr) -> Self {
+        Self {
+            factor,
+            derivation: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn derived_blindings_check() {
+        let plan_seed = [5u8; 32];
+        let blinding = BalanceBlinding::derive(plan_seed, 3);
+        assert!(blinding.check_derivation(plan_seed, 3).is_ok());
+        assert!(blinding.check_derivation(plan_seed, 4).is_err());
+        assert!(blinding.check_derivation([6u8; 32], 3).is_err());
+
+        // The derivation tag isn't needed to check the blinding factor, e.g.
+        // after it has been round-tripped through its scalar encoding.
+        let untagged = BalanceBlinding::from(blinding.to_scalar());
+        assert!(untagged.derivation().is_none());
+        assert!(untagged.check_derivation(plan_seed, 3).is_ok());
+
+        let random = BalanceBlinding::generate(&mut OsRng);
+        assert!(random.check_derivation(plan_seed, 3).is_err());
+    }
+}
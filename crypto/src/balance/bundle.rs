@@ -0,0 +1,126 @@
+use decaf377::Fr;
+use decaf377_rdsa::{Binding, Signature, SigningKey, VerificationKey, VerificationKeyBytes};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{commitment::VALUE_BLINDING_GENERATOR, Commitment};
+
+/// A set of balance commitments, from any mix of actions, together with a proof
+/// that they balance.
+///
+/// The commitments balance if their sum is a commitment to zero, i.e. a
+/// multiple of the value blinding generator. The proof is a binding signature
+/// under the synthetic blinding factor (the sum of the commitments' blinding
+/// factors), which can only be produced if no value is left over. The
+/// signature is over a hash of the commitments, so it can't be moved to a
+/// different bundle.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    commitments: Vec<Commitment>,
+    proof: Signature<Binding>,
+}
+
+impl Bundle {
+    /// Prove that the `commitments` balance, using the sum of their blinding
+    /// factors.
+    ///
+    /// Returns an error if the commitments do not sum to a commitment to zero
+    /// under `synthetic_blinding_factor`.
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        commitments: Vec<Commitment>,
+        synthetic_blinding_factor: Fr,
+    ) -> anyhow::Result<Self> {
+        if total(&commitments).0 != synthetic_blinding_factor * *VALUE_BLINDING_GENERATOR {
+            anyhow::bail!("balance commitments do not sum to a commitment to zero");
+        }
+
+        let signing_key = SigningKey::<Binding>::from(synthetic_blinding_factor);
+        let proof = signing_key.sign(rng, &transcript(&commitments));
+        Ok(Self { commitments, proof })
+    }
+
+    /// The balance commitments in this bundle.
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    /// The binding signature proving that the commitments balance.
+    pub fn proof(&self) -> &Signature<Binding> {
+        &self.proof
+    }
+
+    /// The sum of the balance commitments in this bundle.
+    pub fn total(&self) -> Commitment {
+        total(&self.commitments)
+    }
+}
+
+/// Check that the balance commitments in the `bundle` sum to a commitment to zero.
+pub fn check_balance(bundle: &Bundle) -> anyhow::Result<()> {
+    let verification_key_bytes: VerificationKeyBytes<Binding> = bundle.total().to_bytes().into();
+    let verification_key: VerificationKey<Binding> = verification_key_bytes.try_into()?;
+    verification_key
+        .verify(&transcript(&bundle.commitments), &bundle.proof)
+        .map_err(|_| anyhow::anyhow!("balance commitments do not balance"))
+}
+
+fn total(commitments: &[Commitment]) -> Commitment {
+    commitments.iter().sum()
+}
+
+/// The message signed by the balance proof, binding it to the commitments.
+fn transcript(commitments: &[Commitment]) -> Vec<u8> {
+    let mut state = blake2b_simd::Params::default()
+        .personal(b"Penumbra_BalBndl")
+        .to_state();
+    for commitment in commitments {
+        state.update(&commitment.to_bytes());
+    }
+    state.finalize().as_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{asset, balance::Sign, sample::SampleUniform, Value};
+
+    #[test]
+    fn bundle_balances() {
+        let mut rng = OsRng;
+        let upenumbra = asset::REGISTRY.parse_denom("upenumbra").unwrap().id();
+        let value = |amount: u64| Value {
+            amount: amount.into(),
+            asset_id: upenumbra,
+        };
+
+        // A spend of 10, an output of 7, and a fee of 3.
+        let blindings = [Fr::rand_uniform(&mut rng), Fr::rand_uniform(&mut rng)];
+        let commitments = vec![
+            value(10).commit_signed(Sign::Provided, blindings[0]),
+            value(7).commit_signed(Sign::Required, blindings[1]),
+            value(3).commit_signed(Sign::Required, Fr::from(0u64)),
+        ];
+        let synthetic_blinding_factor = blindings[0] + blindings[1];
+
+        let bundle =
+            Bundle::prove(&mut rng, commitments.clone(), synthetic_blinding_factor).unwrap();
+        assert!(check_balance(&bundle).is_ok());
+
+        // Dropping the fee leaves value over, so the bundle can't be proven.
+        assert!(Bundle::prove(
+            &mut rng,
+            commitments[..2].to_vec(),
+            synthetic_blinding_factor
+        )
+        .is_err());
+
+        // Nor can a valid proof be moved to a different set of commitments.
+        let tampered = Bundle {
+            commitments: commitments[..2].to_vec(),
+            proof: *bundle.proof(),
+        };
+        assert!(check_balance(&tampered).is_err());
+    }
+}
@@ -7,16 +7,35 @@ use once_cell::sync::Lazy;
 use penumbra_proto::core::crypto::v1alpha1 as pb;
 use penumbra_proto::Protobuf;
 
-use crate::Value;
+use super::Sign;
+use crate::{
+    rdsa::{Binding, Signature, VerificationKey, VerificationKeyBytes},
+    signing::{self, BalanceBinding, SigningPolicy},
+    transaction::Fee,
+    Value,
+};
 
 impl Value {
     #[allow(non_snake_case)]
     pub fn commit(&self, blinding: Fr) -> Commitment {
+        self.commit_signed(Sign::Provided, blinding)
+    }
+
+    /// Commit to this value with an explicit sign.
+    ///
+    /// A [`Sign::Provided`] value contributes positively to the balance, as
+    /// in [`Value::commit`], while a [`Sign::Required`] value is subtracted
+    /// from it, as for values consumed by fees, swaps, or outputs.
+    #[allow(non_snake_case)]
+    pub fn commit_signed(&self, sign: Sign, blinding: Fr) -> Commitment {
         let G_v = self.asset_id.value_generator();
         let H = VALUE_BLINDING_GENERATOR.deref();
 
         let v = Fr::from(self.amount);
-        let C = v * G_v + blinding * H;
+        let C = match sign {
+            Sign::Provided => v * G_v + blinding * H,
+            Sign::Required => blinding * H - v * G_v,
+        };
 
         Commitment(C)
     }
@@ -29,6 +48,50 @@ impl Commitment {
     pub fn to_bytes(&self) -> [u8; 32] {
         (*self).into()
     }
+
+    /// The sum of a transaction's balance `commitments` and its `fee`.
+    ///
+    /// The fee is public, so it is committed to with a zero blinding factor.
+    /// The transaction balances if this is a commitment to zero, i.e. a
+    /// multiple of the value blinding generator.
+    pub fn total_with_fee(commitments: &[Commitment], fee: &Fee) -> Commitment {
+        commitments.iter().sum::<Commitment>() + fee.commit(Fr::from(0u64))
+    }
+
+    /// The key that verifies a transaction's binding signature: its
+    /// [`Commitment::total_with_fee`], read as a verification key.
+    ///
+    /// Only the sum of the blinding factors is a signing key for it, and
+    /// only if no value is left over.
+    pub fn binding_verification_key(
+        commitments: &[Commitment],
+        fee: &Fee,
+    ) -> VerificationKey<Binding> {
+        let bytes: VerificationKeyBytes<Binding> =
+            Self::total_with_fee(commitments, fee).to_bytes().into();
+        bytes
+            .try_into()
+            .expect("a decaf377 encoding is a valid verification key")
+    }
+
+    /// Check that a transaction's balance `commitments` and `fee` sum to a
+    /// commitment to zero, by verifying its `binding_sig` over `effect_hash`
+    /// under the signing `policy`.
+    pub fn verify_balance(
+        policy: SigningPolicy,
+        commitments: &[Commitment],
+        fee: &Fee,
+        effect_hash: &[u8],
+        binding_sig: &Signature<Binding>,
+    ) -> Result<(), Error> {
+        signing::verify_under::<BalanceBinding>(
+            policy,
+            &Self::binding_verification_key(commitments, fee),
+            effect_hash,
+            binding_sig,
+        )
+        .map_err(|_| Error::Unbalanced)
+    }
 }
 
 pub static VALUE_BLINDING_GENERATOR: Lazy<decaf377::Element> = Lazy::new(|| {
@@ -36,10 +99,12 @@ pub static VALUE_BLINDING_GENERATOR: Lazy<decaf377::Element> = Lazy::new(|| {
     decaf377::Element::encode_to_curve(&s)
 });
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     #[error("Invalid valid commitment")]
     InvalidBalanceCommitment,
+    #[error("balance commitments do not sum to a commitment to zero")]
+    Unbalanced,
 }
 
 impl std::ops::Add<Commitment> for Commitment {
@@ -56,6 +121,32 @@ impl std::ops::Sub<Commitment> for Commitment {
     }
 }
 
+impl std::ops::AddAssign for Commitment {
+    fn add_assign(&mut self, rhs: Commitment) {
+        self.0 += rhs.0;
+    }
+}
+
+impl std::ops::SubAssign for Commitment {
+    fn sub_assign(&mut self, rhs: Commitment) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl std::iter::Sum for Commitment {
+    fn sum<I: Iterator<Item = Commitment>>(iter: I) -> Self {
+        iter.fold(Commitment::default(), |total, commitment| {
+            total + commitment
+        })
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Commitment> for Commitment {
+    fn sum<I: Iterator<Item = &'a Commitment>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
 impl std::ops::Neg for Commitment {
     type Output = Commitment;
     fn neg(self) -> Self::Output {
@@ -113,3 +204,63 @@ impl TryFrom<pb::BalanceCommitment> for Commitment {
         value.inner.as_slice().try_into().map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{asset, sample::SampleUniform, STAKING_TOKEN_ASSET_ID};
+
+    #[test]
+    fn verify_balance_includes_fee() {
+        let mut rng = OsRng;
+        let value = |amount: u64| Value {
+            amount: amount.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+
+        // A spend of 10 and an output of 7, leaving 3 for the fee.
+        let blindings = [Fr::rand_uniform(&mut rng), Fr::rand_uniform(&mut rng)];
+        let commitments = [
+            value(10).commit_signed(Sign::Provided, blindings[0]),
+            value(7).commit_signed(Sign::Required, blindings[1]),
+        ];
+        let fee = Fee::from_staking_token_amount(asset::Amount::from(3u64));
+        let synthetic_blinding_factor = blindings[0] + blindings[1];
+        assert_eq!(
+            Commitment::total_with_fee(&commitments, &fee).0,
+            synthetic_blinding_factor * *VALUE_BLINDING_GENERATOR
+        );
+
+        let effect_hash = [7u8; 64];
+        let binding_sig = signing::sign::<BalanceBinding, _>(
+            &mut rng,
+            &synthetic_blinding_factor.into(),
+            &effect_hash,
+        );
+        assert_eq!(
+            Commitment::verify_balance(
+                SigningPolicy::RequireContext,
+                &commitments,
+                &fee,
+                &effect_hash,
+                &binding_sig
+            ),
+            Ok(())
+        );
+
+        // With a smaller fee, value is left over, so the signature doesn't verify.
+        let fee = Fee::from_staking_token_amount(asset::Amount::from(2u64));
+        assert_eq!(
+            Commitment::verify_balance(
+                SigningPolicy::RequireContext,
+                &commitments,
+                &fee,
+                &effect_hash,
+                &binding_sig
+            ),
+            Err(Error::Unbalanced)
+        );
+    }
+}
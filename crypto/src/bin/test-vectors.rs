@@ -0,0 +1,25 @@
+//! Print canonical test vectors as JSON.
+//!
+//! Usage: `test-vectors [SEED] [COUNT]`, where `SEED` is 32 bytes of hex (all zero by default)
+//! and `COUNT` is the number of notes to generate vectors for (8 by default).
+
+use anyhow::Context;
+use penumbra_crypto::test_vectors::TestVectors;
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut seed = [0u8; 32];
+    if let Some(hex_seed) = args.next() {
+        hex::decode_to_slice(&hex_seed, &mut seed).context("seed must be 32 bytes of hex")?;
+    }
+    let count = match args.next() {
+        Some(count) => count.parse().context("count must be a number")?,
+        None => 8,
+    };
+
+    let vectors = TestVectors::generate(seed, count);
+    vectors.check()?;
+    println!("{}", serde_json::to_string_pretty(&vectors)?);
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use ark_ff::fields::PrimeField;
 use ark_ff::One;
-use ark_std::UniformRand;
 use decaf377::Fr;
+use rand_core::{CryptoRng, RngCore};
 use std::collections::BTreeMap;
 
+use crate::sample::SampleUniform;
+
 // a proof of correctness (r, s, t) for a given value encryption for a given public key (see the
 // threshold cryptography spec for more details.)
 #[derive(Copy, Clone)]
@@ -60,14 +62,17 @@ impl EncryptedValue {
 
 /// Encrypt the given `values` as value*decaf377::basepoint using the elgamal scheme, and compute
 /// an [`EncrytionProof`] of correctness.
-pub fn encrypt_value(value: decaf377::Fr, for_pubkey: decaf377::Element) -> EncryptedValue {
-    let mut rng = rand::thread_rng();
-    let e = Fr::rand(&mut rng);
+pub fn encrypt_value<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    value: decaf377::Fr,
+    for_pubkey: decaf377::Element,
+) -> EncryptedValue {
+    let e = Fr::rand_uniform(rng);
     let c1 = e * decaf377::basepoint();
     let c2 = e * for_pubkey + value * decaf377::basepoint();
 
-    let k1 = Fr::rand(&mut rng);
-    let k2 = Fr::rand(&mut rng);
+    let k1 = Fr::rand_uniform(rng);
+    let k2 = Fr::rand_uniform(rng);
     let alpha = decaf377::basepoint() * k1;
     let gamma = for_pubkey * k1 + decaf377::basepoint() * k2;
     let challenge_hash = blake2b_simd::Params::default()
@@ -175,17 +180,17 @@ impl DecryptionShare {
     /// `private_key`. see the
     /// [spec](https://protocol.penumbra.zone/main/crypto/flow-encryption/threshold-encryption.html)
     /// for more details.
-    pub fn new(
+    pub fn new<R: RngCore + CryptoRng>(
+        rng: &mut R,
         private_key: decaf377::Fr,
         c1: decaf377::Element,
         participant_index: u32,
         participant_commitment: decaf377::Element,
     ) -> DecryptionShare {
-        let mut rng = rand::thread_rng();
         let spi = private_key * c1;
 
         // construct the nizk proof
-        let k = Fr::rand(&mut rng);
+        let k = Fr::rand_uniform(rng);
         let alpha = k * decaf377::basepoint();
         let gamma = k * c1;
 
@@ -253,7 +258,7 @@ mod tests {
         let pubkey = privkey * decaf377::basepoint();
         let value_to_encrypt = decaf377::Fr::from(1000u64);
 
-        let encrypted = encrypt_value(value_to_encrypt, pubkey);
+        let encrypted = encrypt_value(&mut rng, value_to_encrypt, pubkey);
 
         assert!(encrypted.verify(pubkey).is_ok());
         let alt_pubkey = decaf377::Fr::rand(&mut rng) * decaf377::basepoint();
@@ -281,7 +286,7 @@ mod tests {
         ];
         let mut encrypted_values = Vec::new();
         for value in values.iter() {
-            encrypted_values.push(encrypt_value(*value, pubkey));
+            encrypted_values.push(encrypt_value(&mut rng, *value, pubkey));
         }
 
         let encrypted_aggregate = aggregate_values(&encrypted_values, pubkey).unwrap();
@@ -295,6 +300,7 @@ mod tests {
         let lut = compute_lut(1000);
         let t = 10;
         let n = 20;
+        let mut rng = rand::thread_rng();
 
         let mut participants = Vec::new();
         for i in 1..n + 1 {
@@ -333,9 +339,21 @@ mod tests {
 
         // encrypt a few values for the dkg pubkey
         let encrypted_values = [
-            encrypt_value(decaf377::Fr::from(100u64), dkg_outputs[0].group_public_key),
-            encrypt_value(decaf377::Fr::from(200u64), dkg_outputs[0].group_public_key),
-            encrypt_value(decaf377::Fr::from(300u64), dkg_outputs[0].group_public_key),
+            encrypt_value(
+                &mut rng,
+                decaf377::Fr::from(100u64),
+                dkg_outputs[0].group_public_key,
+            ),
+            encrypt_value(
+                &mut rng,
+                decaf377::Fr::from(200u64),
+                dkg_outputs[0].group_public_key,
+            ),
+            encrypt_value(
+                &mut rng,
+                decaf377::Fr::from(300u64),
+                dkg_outputs[0].group_public_key,
+            ),
         ];
         let aggregate_value =
             aggregate_values(&encrypted_values, dkg_outputs[0].group_public_key).unwrap();
@@ -345,6 +363,7 @@ mod tests {
         for i in 0..n {
             let share = &dkg_outputs[i as usize];
             decryption_shares.push(DecryptionShare::new(
+                &mut rng,
                 share.private_share,
                 aggregate_value.c1,
                 share.participant_index,
@@ -373,7 +392,7 @@ mod tests {
             let (i, random_share) = decryption_shares
                 .iter()
                 .enumerate()
-                .choose(&mut rand::thread_rng())
+                .choose(&mut rng)
                 .unwrap();
             seen_shares
                 .entry(random_share.participant_index)
@@ -5,7 +5,9 @@ use bytes::Bytes;
 use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
-use crate::{asset::Amount, ka, note, FullViewingKey, Note};
+use crate::{
+    asset::Amount, ka, keys::IncomingViewingKey, note, FieldExt, FullViewingKey, Note, PayloadKey,
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(try_from = "pb::EncryptedNote", into = "pb::EncryptedNote")]
@@ -15,53 +17,200 @@ pub struct EncryptedNote {
     pub encrypted_note: [u8; note::NOTE_CIPHERTEXT_BYTES],
 }
 
+/// Why trial decryption found no note for a key in a payload.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrialDecryptError {
+    /// The payload doesn't decrypt with the key, so it wasn't meant for it.
+    #[error("payload does not decrypt with this key")]
+    Undecryptable,
+    /// The note records zero assets, as dummy notes do.
+    #[error("decrypted note records zero assets")]
+    ZeroAmount,
+    /// The note isn't spendable by the key that decrypted it.
+    #[error("decrypted note is not spendable by this key")]
+    NotSpendable,
+    /// The note doesn't match the note commitment published with the payload,
+    /// which no honestly generated payload does.
+    #[error("decrypted note does not match its note commitment")]
+    CommitmentMismatch,
+}
+
+/// The length of the fixed-size encoding of an [`EncryptedNote`]: the note
+/// commitment, the ephemeral key, and the note ciphertext, in that order.
+pub const ENCRYPTED_NOTE_BYTES: usize = 32 + 32 + note::NOTE_CIPHERTEXT_BYTES;
+
 impl EncryptedNote {
     pub fn trial_decrypt(&self, fvk: &FullViewingKey) -> Option<Note> {
-        // Try to decrypt the encrypted note using the ephemeral key and persistent incoming
-        // viewing key -- if it doesn't decrypt, it wasn't meant for us.
-        let note = Note::decrypt(
-            self.encrypted_note.as_ref(),
-            fvk.incoming(),
+        trial_decrypt(
+            &self.note_commitment.0.to_bytes(),
             &self.ephemeral_key,
+            &self.encrypted_note,
+            fvk,
         )
-        .ok()?;
-        tracing::debug!(note_commitment = ?note.commit(), ?note, "found note while scanning");
-
-        // Verification logic (if any fails, return None & log error)
-        // Reject notes with zero amount
-        if note.amount() == Amount::zero() {
-            // This is only debug-level because it can happen honestly (e.g., swap claims, dummy spends).
-            tracing::debug!("ignoring note recording zero assets");
-            return None;
-        }
-        // Make sure spendable by keys
-        if !fvk.controls(&note) {
-            // This should be a warning, because no honestly generated note plaintext should
-            // mismatch the FVK that can detect and decrypt it.
-            tracing::warn!("decrypted note that is not spendable by provided full viewing key");
-            return None;
-        }
-        // Make sure note commitment matches
-        if note.commit() != self.note_commitment {
-            // This should be a warning, because no honestly generated note plaintext should
-            // fail to match the note commitment actually included in the chain.
-            tracing::warn!("decrypted note does not match provided note commitment");
-            return None;
-        }
+    }
+
+    /// Trial-decrypt each of the `payloads`, returning the results in the same
+    /// order.
+    ///
+    /// This is equivalent to calling [`EncryptedNote::trial_decrypt`] on each
+    /// payload, but is performed as a batch by
+    /// [`IncomingViewingKey::trial_decrypt_batch`].
+    pub fn trial_decrypt_batch(
+        payloads: &[EncryptedNote],
+        fvk: &FullViewingKey,
+    ) -> Vec<Option<Note>> {
+        fvk.incoming().trial_decrypt_batch(payloads)
+    }
+
+    /// Decrypt this payload with the shared secret of its ephemeral key and
+    /// `ivk`, checking that the note is meant for `ivk`.
+    pub(crate) fn decrypt_with_shared_secret(
+        &self,
+        shared_secret: &ka::SharedSecret,
+        ivk: &IncomingViewingKey,
+    ) -> Result<Note, TrialDecryptError> {
+        let key = PayloadKey::derive(shared_secret, &self.ephemeral_key);
+        let note = Note::decrypt_with_payload_key(&self.encrypted_note, &key, &self.ephemeral_key)
+            .map_err(|_| TrialDecryptError::Undecryptable)?;
+        check_decrypted(note, &self.note_commitment.0.to_bytes(), ivk)
+    }
+
+    /// Encode this payload in its fixed-size wire encoding.
+    pub fn to_bytes(&self) -> [u8; ENCRYPTED_NOTE_BYTES] {
+        let mut bytes = [0u8; ENCRYPTED_NOTE_BYTES];
+        bytes[..32].copy_from_slice(&self.note_commitment.0.to_bytes());
+        bytes[32..64].copy_from_slice(&self.ephemeral_key.0);
+        bytes[64..].copy_from_slice(&self.encrypted_note);
+        bytes
+    }
+}
+
+/// A borrowed, fixed-size encoding of an [`EncryptedNote`].
+///
+/// This gives access to the fields of an encoded payload without copying or
+/// allocating, so that payloads can be scanned straight out of a buffer. The
+/// note commitment is only parsed on demand.
+#[derive(Clone, Copy)]
+pub struct EncryptedNoteRef<'a>(&'a [u8; ENCRYPTED_NOTE_BYTES]);
+
+impl<'a> EncryptedNoteRef<'a> {
+    /// Borrow `bytes` as an encoded payload, checking that it has exactly
+    /// [`ENCRYPTED_NOTE_BYTES`] bytes.
+    pub fn new(bytes: &'a [u8]) -> anyhow::Result<Self> {
+        let bytes = bytes.try_into().map_err(|_| {
+            anyhow::anyhow!(
+                "encrypted note must be {} bytes, got {}",
+                ENCRYPTED_NOTE_BYTES,
+                bytes.len()
+            )
+        })?;
+        Ok(Self(bytes))
+    }
+
+    /// The encoded note commitment.
+    pub fn note_commitment_bytes(&self) -> &'a [u8; 32] {
+        self.0[..32].try_into().expect("slice has length 32")
+    }
+
+    /// The ephemeral public key.
+    pub fn ephemeral_key_bytes(&self) -> &'a [u8; 32] {
+        self.0[32..64].try_into().expect("slice has length 32")
+    }
+
+    /// The note ciphertext.
+    pub fn encrypted_note(&self) -> &'a [u8; note::NOTE_CIPHERTEXT_BYTES] {
+        self.0[64..]
+            .try_into()
+            .expect("slice has length NOTE_CIPHERTEXT_BYTES")
+    }
+
+    /// Parse the note commitment.
+    pub fn note_commitment(&self) -> anyhow::Result<note::Commitment> {
+        Ok(note::Commitment::try_from(*self.note_commitment_bytes())?)
+    }
+
+    /// Trial-decrypt the payload, as in [`EncryptedNote::trial_decrypt`].
+    pub fn trial_decrypt(&self, fvk: &FullViewingKey) -> Option<Note> {
+        trial_decrypt(
+            self.note_commitment_bytes(),
+            &ka::Public(*self.ephemeral_key_bytes()),
+            self.encrypted_note(),
+            fvk,
+        )
+    }
 
-        // NOTE: We intentionally return `Option` here instead of `Result`
-        // such that we gracefully drop malformed notes instead of returning an error
-        // that may propagate up the call stack and cause a panic.
-        // All errors in parsing notes must not cause a panic in the view service.
-        // A panic when parsing a specific note could link the fact that the malformed
-        // note can be successfully decrypted with a specific IP.
-        //
-        // See "REJECT" attack (CVE-2019-16930) for a similar attack in ZCash
-        // Section 4.1 in https://crypto.stanford.edu/timings/pingreject.pdf
-        Some(note)
+    /// Copy the payload into an owned [`EncryptedNote`].
+    pub fn to_encrypted_note(&self) -> anyhow::Result<EncryptedNote> {
+        Ok(EncryptedNote {
+            note_commitment: self.note_commitment()?,
+            ephemeral_key: ka::Public(*self.ephemeral_key_bytes()),
+            encrypted_note: *self.encrypted_note(),
+        })
     }
 }
 
+impl TryFrom<&[u8]> for EncryptedNote {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        EncryptedNoteRef::new(bytes)?.to_encrypted_note()
+    }
+}
+
+fn trial_decrypt(
+    note_commitment: &[u8; 32],
+    ephemeral_key: &ka::Public,
+    encrypted_note: &[u8],
+    fvk: &FullViewingKey,
+) -> Option<Note> {
+    // Try to decrypt the encrypted note using the ephemeral key and persistent incoming
+    // viewing key -- if it doesn't decrypt, it wasn't meant for us.
+    let note = Note::decrypt(encrypted_note, fvk.incoming(), ephemeral_key).ok()?;
+    check_decrypted(note, note_commitment, fvk.incoming()).ok()
+}
+
+/// Check that a note decrypted while scanning is valid and meant for `ivk`.
+fn check_decrypted(
+    note: Note,
+    note_commitment: &[u8; 32],
+    ivk: &IncomingViewingKey,
+) -> Result<Note, TrialDecryptError> {
+    tracing::debug!(note_commitment = ?note.commit(), ?note, "found note while scanning");
+
+    // Verification logic (if any fails, return None & log error)
+    // Reject notes with zero amount
+    if note.amount() == Amount::zero() {
+        // This is only debug-level because it can happen honestly (e.g., swap claims, dummy spends).
+        tracing::debug!("ignoring note recording zero assets");
+        return Err(TrialDecryptError::ZeroAmount);
+    }
+    // Make sure spendable by keys
+    if ivk.diversified_public(&note.diversified_generator()) != *note.transmission_key() {
+        // This should be a warning, because no honestly generated note plaintext should
+        // mismatch the FVK that can detect and decrypt it.
+        tracing::warn!("decrypted note that is not spendable by provided full viewing key");
+        return Err(TrialDecryptError::NotSpendable);
+    }
+    // Make sure note commitment matches
+    if note.commit().0.to_bytes() != *note_commitment {
+        // This should be a warning, because no honestly generated note plaintext should
+        // fail to match the note commitment actually included in the chain.
+        tracing::warn!("decrypted note does not match provided note commitment");
+        return Err(TrialDecryptError::CommitmentMismatch);
+    }
+
+    // NOTE: Callers turn the error into `None`, or only count it, so that we
+    // gracefully drop malformed notes instead of returning an error that may
+    // propagate up the call stack and cause a panic.
+    // All errors in parsing notes must not cause a panic in the view service.
+    // A panic when parsing a specific note could link the fact that the malformed
+    // note can be successfully decrypted with a specific IP.
+    //
+    // See "REJECT" attack (CVE-2019-16930) for a similar attack in ZCash
+    // Section 4.1 in https://crypto.stanford.edu/timings/pingreject.pdf
+    Ok(note)
+}
+
 impl std::fmt::Debug for EncryptedNote {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EncryptedNote")
@@ -101,3 +250,113 @@ impl TryFrom<pb::EncryptedNote> for EncryptedNote {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        keys::{SeedPhrase, SpendKey},
+        Value,
+    };
+
+    #[test]
+    fn fixed_size_encoding_roundtrip() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let fvk = sk.full_viewing_key();
+        let (address, _dtk) = fvk.incoming().payment_address(0u64.into());
+        let value: Value = "1upenumbra".parse().unwrap();
+        let note = Note::generate(&mut OsRng, &address, value);
+
+        let payload = EncryptedNote {
+            note_commitment: note.commit(),
+            ephemeral_key: note.ephemeral_public_key(),
+            encrypted_note: note.encrypt(),
+        };
+        let bytes = payload.to_bytes();
+
+        let payload_ref = EncryptedNoteRef::new(&bytes).unwrap();
+        assert_eq!(payload_ref.note_commitment().unwrap(), note.commit());
+        assert_eq!(payload_ref.trial_decrypt(fvk), Some(note.clone()));
+
+        let decoded = EncryptedNote::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.trial_decrypt(fvk), Some(note));
+
+        assert!(EncryptedNoteRef::new(&bytes[1..]).is_err());
+        assert!(EncryptedNote::try_from(&[bytes.as_ref(), &[0]].concat()[..]).is_err());
+    }
+
+    #[test]
+    fn trial_decrypt_batch_matches_individual() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let fvk = sk.full_viewing_key();
+        let other_sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let value: Value = "1upenumbra".parse().unwrap();
+
+        // Enough payloads to be split between threads.
+        let payloads = [fvk, other_sk.full_viewing_key(), fvk]
+            .into_iter()
+            .cycle()
+            .take(48)
+            .map(|recipient| {
+                let (address, _dtk) = recipient.incoming().payment_address(0u64.into());
+                let note = Note::generate(&mut OsRng, &address, value);
+                EncryptedNote {
+                    note_commitment: note.commit(),
+                    ephemeral_key: note.ephemeral_public_key(),
+                    encrypted_note: note.encrypt(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let batch = EncryptedNote::trial_decrypt_batch(&payloads, fvk);
+        let individual = payloads
+            .iter()
+            .map(|payload| payload.trial_decrypt(fvk))
+            .collect::<Vec<_>>();
+        assert_eq!(batch, individual);
+        assert_eq!(fvk.incoming().trial_decrypt_batch(&payloads), individual);
+        assert!(batch[0].is_some() && batch[1].is_none() && batch[2].is_some());
+        assert_eq!(batch.iter().flatten().count(), 32);
+    }
+
+    #[test]
+    fn trial_decrypt_batch_reports_forged_commitments() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let fvk = sk.full_viewing_key();
+        let other_sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let (address, _dtk) = fvk.incoming().payment_address(0u64.into());
+        let value: Value = "1upenumbra".parse().unwrap();
+        let note = Note::generate(&mut OsRng, &address, value);
+        let other_note = Note::generate(&mut OsRng, &address, value);
+
+        let honest = EncryptedNote {
+            note_commitment: note.commit(),
+            ephemeral_key: note.ephemeral_public_key(),
+            encrypted_note: note.encrypt(),
+        };
+        // A payload whose ciphertext decrypts to a note for us, published with
+        // the commitment of a different note.
+        let forged = EncryptedNote {
+            note_commitment: other_note.commit(),
+            ..honest.clone()
+        };
+
+        let results = fvk
+            .incoming()
+            .trial_decrypt_batch_results(&[honest.clone(), forged.clone()]);
+        assert_eq!(results[0], Ok(note));
+        assert_eq!(results[1], Err(TrialDecryptError::CommitmentMismatch));
+        assert_eq!(forged.trial_decrypt(fvk), None);
+
+        assert_eq!(
+            other_sk
+                .full_viewing_key()
+                .incoming()
+                .trial_decrypt_batch_results(&[honest]),
+            vec![Err(TrialDecryptError::Undecryptable)]
+        );
+    }
+}
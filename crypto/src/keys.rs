@@ -1,15 +1,29 @@
+mod account;
+pub use account::{AccountIndex, WalletId, WalletKey, WalletViewingKeys};
+
+mod derivation;
+pub use derivation::AddressDerivation;
+
 mod diversifier;
 pub use diversifier::{AddressIndex, Diversifier, DiversifierKey, DIVERSIFIER_LEN_BYTES};
 
 mod nullifier;
 pub use nullifier::{NullifierKey, NK_LEN_BYTES};
 
+mod randomizer;
+pub use randomizer::SpendAuthRandomizer;
+
+mod rk;
+pub use rk::RandomizedVerificationKey;
+
 mod seed_phrase;
 pub use seed_phrase::SeedPhrase;
 
 mod spend;
 pub use spend::{SpendKey, SpendKeyBytes, SPENDKEY_LEN_BYTES};
 
+pub mod threshold;
+
 mod fvk;
 mod ivk;
 mod ovk;
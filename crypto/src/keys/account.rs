@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{AccountID, AddressIndex, SeedPhrase, SpendKey, SpendKeyBytes, SPENDKEY_LEN_BYTES};
+use crate::{prf, Address, FullViewingKey};
+
+/// The index of an account within a wallet.
+///
+/// Accounts are always derived with hardened derivation: each account's spend key is derived from
+/// the wallet's secret root, so knowing the keys of one account, even its spend key, reveals
+/// nothing about any other account or about the root.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct AccountIndex(pub u32);
+
+impl From<u32> for AccountIndex {
+    fn from(index: u32) -> Self {
+        Self(index)
+    }
+}
+
+/// A fingerprint identifying a wallet, shared by all of its accounts.
+///
+/// It's computed from the full viewing key of account 0, so a view service given that key can
+/// compute it, but it can't be used to derive any key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct WalletId(pub [u8; 32]);
+
+impl From<&FullViewingKey> for WalletId {
+    fn from(fvk: &FullViewingKey) -> Self {
+        let hash_result = prf::expand(b"Penumbra_WltFngr", &[], &fvk.hash().0);
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&hash_result.as_bytes()[0..32]);
+        WalletId(id)
+    }
+}
+
+/// The root of a wallet's key hierarchy, from which every account's spend key is derived.
+///
+/// The root is the spend key that [`SpendKey::from_seed_phrase`] derives with index 0, but it is
+/// not itself an account: every account, including account 0, is derived from it in the same way.
+#[derive(Clone, Debug)]
+pub struct WalletKey {
+    root: SpendKey,
+}
+
+impl WalletKey {
+    /// Derive the key hierarchy of a wallet from its seed phrase.
+    pub fn from_seed_phrase(seed_phrase: SeedPhrase) -> Self {
+        Self {
+            root: SpendKey::from_seed_phrase(seed_phrase, 0),
+        }
+    }
+
+    /// The fingerprint of this wallet.
+    pub fn wallet_id(&self) -> WalletId {
+        WalletId::from(&self.full_viewing_key(AccountIndex(0)))
+    }
+
+    /// Derive the spend key of the given account.
+    pub fn spend_key(&self, account: AccountIndex) -> SpendKey {
+        let hash_result = prf::expand(
+            b"Penumbra_AcctDrv",
+            &self.root.to_bytes().0,
+            &account.0.to_le_bytes(),
+        );
+        let mut seed = [0u8; SPENDKEY_LEN_BYTES];
+        seed.copy_from_slice(&hash_result.as_bytes()[0..SPENDKEY_LEN_BYTES]);
+        SpendKeyBytes(seed).into()
+    }
+
+    /// Derive the full viewing key of the given account.
+    pub fn full_viewing_key(&self, account: AccountIndex) -> FullViewingKey {
+        self.spend_key(account).full_viewing_key().clone()
+    }
+
+    /// The viewing keys of the first `count` accounts, to hand to a view service.
+    pub fn viewing_keys(&self, count: u32) -> WalletViewingKeys {
+        let mut keys = WalletViewingKeys::new(self.wallet_id());
+        for index in 0..count {
+            let account = AccountIndex(index);
+            keys.insert(account, self.full_viewing_key(account));
+        }
+        keys
+    }
+}
+
+/// The full viewing keys of some of a wallet's accounts, with which a view service can index
+/// notes by account.
+#[derive(Clone)]
+pub struct WalletViewingKeys {
+    wallet_id: WalletId,
+    accounts: BTreeMap<AccountIndex, FullViewingKey>,
+}
+
+impl WalletViewingKeys {
+    /// An empty set of viewing keys for the wallet `wallet_id`.
+    pub fn new(wallet_id: WalletId) -> Self {
+        Self {
+            wallet_id,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    pub fn wallet_id(&self) -> WalletId {
+        self.wallet_id
+    }
+
+    /// Add the viewing key of an account, replacing any previous key for it.
+    pub fn insert(&mut self, account: AccountIndex, fvk: FullViewingKey) {
+        self.accounts.insert(account, fvk);
+    }
+
+    /// The viewing key of the given account, if it's known.
+    pub fn get(&self, account: AccountIndex) -> Option<&FullViewingKey> {
+        self.accounts.get(&account)
+    }
+
+    /// Iterate over the known accounts and their viewing keys, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (AccountIndex, &FullViewingKey)> {
+        self.accounts.iter().map(|(account, fvk)| (*account, fvk))
+    }
+
+    /// The account with the given [`AccountID`], if it's known.
+    pub fn account_for_id(&self, id: &AccountID) -> Option<AccountIndex> {
+        self.iter()
+            .find(|(_, fvk)| fvk.hash() == *id)
+            .map(|(account, _)| account)
+    }
+
+    /// Find the account whose incoming viewing key views `address`, and the address's index
+    /// within it.
+    ///
+    /// This trial-checks the address against each account's incoming viewing key, so it costs one
+    /// scalar multiplication per known account.
+    pub fn account_for_address(&self, address: &Address) -> Option<(AccountIndex, AddressIndex)> {
+        self.iter().find_map(|(account, fvk)| {
+            fvk.incoming()
+                .index_for_address(address)
+                .map(|index| (account, index))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_are_distinct_and_addresses_resolve() {
+        let seed_phrase = SeedPhrase::from_randomness([9u8; 32]);
+        let wallet = WalletKey::from_seed_phrase(seed_phrase.clone());
+
+        // Every account, including account 0, is derived from the root, and
+        // none of them is the root itself.
+        let root = SpendKey::from_seed_phrase(seed_phrase, 0);
+        for index in 0..3 {
+            assert_ne!(wallet.spend_key(AccountIndex(index)), root);
+        }
+        assert_ne!(
+            wallet.spend_key(AccountIndex(0)),
+            wallet.spend_key(AccountIndex(1))
+        );
+        assert_ne!(
+            wallet.spend_key(AccountIndex(1)),
+            wallet.spend_key(AccountIndex(2))
+        );
+        assert_eq!(
+            wallet.wallet_id(),
+            WalletId::from(&wallet.full_viewing_key(AccountIndex(0)))
+        );
+
+        let keys = wallet.viewing_keys(3);
+        assert_eq!(keys.wallet_id(), wallet.wallet_id());
+        let fvk = wallet.full_viewing_key(AccountIndex(2));
+        let (address, _) = fvk.payment_address(AddressIndex::from(7u32));
+        assert_eq!(
+            keys.account_for_address(&address),
+            Some((AccountIndex(2), AddressIndex::from(7u32)))
+        );
+        assert_eq!(keys.account_for_id(&fvk.hash()), Some(AccountIndex(2)));
+
+        let other = WalletKey::from_seed_phrase(SeedPhrase::from_randomness([8u8; 32]));
+        let (foreign, _) = other
+            .full_viewing_key(AccountIndex(0))
+            .payment_address(AddressIndex::from(0u32));
+        assert_eq!(keys.account_for_address(&foreign), None);
+    }
+}
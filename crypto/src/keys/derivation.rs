@@ -0,0 +1,161 @@
+use std::ops::Range;
+
+use anyhow::ensure;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use super::{AddressIndex, Diversifier, IncomingViewingKey, DIVERSIFIER_LEN_BYTES};
+use crate::{Address, FieldExt};
+
+/// A record of each step of the derivation of one address from an incoming
+/// viewing key, for auditing.
+///
+/// An address with index `i` is derived as follows:
+/// 1. the diversifier `d` is the encryption of `i` under the diversifier key;
+/// 2. `d` is hashed to a field element, which is mapped to the diversified
+///    generator `g_d`;
+/// 3. the transmission key is `pk_d = ivk * g_d`;
+/// 4. the clue key is derived from the detection key for `d`.
+///
+/// Anyone can check step 2 and the encoding of the address from the record
+/// alone, with [`verify_public`](AddressDerivation::verify_public); the whole
+/// derivation can be checked by anyone holding the incoming viewing key (e.g.
+/// from a full viewing key), with [`verify`](AddressDerivation::verify). No
+/// spending capability, and neither the incoming viewing key nor any detection
+/// key, is included in the record.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressDerivation {
+    /// The numeric index of the address.
+    pub index: u64,
+    /// The diversifier encrypting the index.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub diversifier: [u8; DIVERSIFIER_LEN_BYTES],
+    /// The hash of the diversifier to a field element, in its little-endian
+    /// encoding.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub diversifier_hash: [u8; 32],
+    /// The encoding of the diversified generator.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub diversified_generator: [u8; 32],
+    /// The transmission key.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub transmission_key: [u8; 32],
+    /// The clue key.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub clue_key: [u8; 32],
+    /// The resulting address, in its bech32m encoding.
+    pub address: String,
+}
+
+impl AddressDerivation {
+    /// Record the derivation of the address with index `index` by `ivk`.
+    pub fn new(ivk: &IncomingViewingKey, index: u64) -> Self {
+        let diversifier = ivk.dk.diversifier_for_index(&AddressIndex::Numeric(index));
+        let (address, _dtk) = ivk.payment_address(AddressIndex::Numeric(index));
+        debug_assert_eq!(*address.diversifier(), diversifier);
+
+        Self {
+            index,
+            diversifier: diversifier.0,
+            diversifier_hash: diversifier.hash_to_field().to_bytes(),
+            diversified_generator: diversifier.diversified_generator().vartime_compress().0,
+            transmission_key: address.transmission_key().0,
+            clue_key: address.clue_key().0,
+            address: address.to_string(),
+        }
+    }
+
+    /// Check the steps of the derivation that don't depend on any key: that the
+    /// diversified generator is derived from the diversifier, and that the
+    /// address encodes the recorded components.
+    pub fn verify_public(&self) -> anyhow::Result<()> {
+        let diversifier = Diversifier(self.diversifier);
+        ensure!(
+            diversifier.hash_to_field().to_bytes() == self.diversifier_hash,
+            "diversifier hash of address {} does not match its diversifier",
+            self.index
+        );
+        ensure!(
+            diversifier.diversified_generator().vartime_compress().0 == self.diversified_generator,
+            "diversified generator of address {} does not match its diversifier",
+            self.index
+        );
+
+        let address: Address = self.address.parse()?;
+        ensure!(
+            address.diversifier().0 == self.diversifier
+                && address.transmission_key().0 == self.transmission_key
+                && address.clue_key().0 == self.clue_key,
+            "address {} does not encode its recorded components",
+            self.index
+        );
+        Ok(())
+    }
+
+    /// Check every step of the derivation against `ivk`.
+    pub fn verify(&self, ivk: &IncomingViewingKey) -> anyhow::Result<()> {
+        self.verify_public()?;
+        let expected = Self::new(ivk, self.index);
+        ensure!(
+            expected.diversifier == self.diversifier,
+            "diversifier of address {} is not the encryption of its index",
+            self.index
+        );
+        ensure!(
+            expected.transmission_key == self.transmission_key,
+            "transmission key of address {} is not derived from the viewing key",
+            self.index
+        );
+        ensure!(
+            expected.clue_key == self.clue_key,
+            "clue key of address {} is not derived from the viewing key",
+            self.index
+        );
+        Ok(())
+    }
+}
+
+impl IncomingViewingKey {
+    /// Record the derivation of each address with an index in `indices`, in
+    /// order.
+    pub fn address_derivations(&self, indices: Range<u64>) -> Vec<AddressDerivation> {
+        indices
+            .map(|index| AddressDerivation::new(self, index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::{SeedPhrase, SpendKey};
+
+    #[test]
+    fn derivations_verify_only_against_their_key() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([3u8; 32]), 0);
+        let ivk = sk.full_viewing_key().incoming();
+        let derivations = ivk.address_derivations(5..8);
+        assert_eq!(derivations.len(), 3);
+
+        for derivation in &derivations {
+            derivation.verify(ivk).unwrap();
+            let (address, _dtk) = ivk.payment_address(derivation.index.into());
+            assert_eq!(derivation.address, address.to_string());
+
+            let json = serde_json::to_string(derivation).unwrap();
+            let decoded: AddressDerivation = serde_json::from_str(&json).unwrap();
+            assert_eq!(&decoded, derivation);
+        }
+
+        let other = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([4u8; 32]), 0);
+        assert!(derivations[0].verify_public().is_ok());
+        assert!(derivations[0]
+            .verify(other.full_viewing_key().incoming())
+            .is_err());
+
+        let mut tampered = derivations[1].clone();
+        tampered.diversified_generator = derivations[2].diversified_generator;
+        assert!(tampered.verify_public().is_err());
+    }
+}
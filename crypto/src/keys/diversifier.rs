@@ -23,12 +23,27 @@ pub struct Diversifier(
 
 impl Diversifier {
     /// Generate the diversified basepoint associated to this diversifier.
+    ///
+    /// This is the decaf377 encode-to-curve map (Elligator 2) applied to
+    /// [`hash_to_field`](Diversifier::hash_to_field), so that no discrete
+    /// logarithm relation between the generators of different diversifiers is
+    /// known.
     pub fn diversified_generator(&self) -> decaf377::Element {
+        decaf377::Element::encode_to_curve(&self.hash_to_field())
+    }
+
+    /// Hash the diversifier to the field element that is mapped to its
+    /// diversified basepoint.
+    ///
+    /// The hash uses BLAKE2b, which has no R1CS gadget over `Fq`, so circuits
+    /// witness this value and prove only the encode-to-curve step, with the
+    /// `diversified_generator_integrity` gadget.
+    pub fn hash_to_field(&self) -> Fq {
         let hash = blake2b_simd::Params::new()
             .personal(b"Penumbra_Divrsfy")
             .hash(&self.0);
 
-        decaf377::Element::encode_to_curve(&Fq::from_le_bytes_mod_order(hash.as_bytes()))
+        Fq::from_le_bytes_mod_order(hash.as_bytes())
     }
 }
 
@@ -294,4 +309,33 @@ mod tests {
             assert_eq!(index2, index );
         }
     }
+
+    #[test]
+    fn diversifier_hash_to_field_vectors() {
+        use decaf377::FieldExt;
+
+        let vectors = [
+            (
+                [0u8; 16],
+                "a55dfaeff2dc2d81711f15752289e99e9b0765c5204c9dfccc583fd37698c900",
+            ),
+            (
+                [1u8; 16],
+                "4e9b9d948306b231990eca3cbf4610ce36f15e02f5d063bd06a45e3876158f12",
+            ),
+            (
+                [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+                "dce67af91204f1b9d5616ca72244562b72e6d2d846da96a13db0e04165f9f503",
+            ),
+        ];
+        for (diversifier, expected) in vectors {
+            let diversifier = Diversifier(diversifier);
+            let hash = diversifier.hash_to_field();
+            assert_eq!(hex::encode(hash.to_bytes()), expected);
+            assert_eq!(
+                diversifier.diversified_generator(),
+                decaf377::Element::encode_to_curve(&hash)
+            );
+        }
+    }
 }
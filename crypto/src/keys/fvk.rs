@@ -7,10 +7,11 @@ use penumbra_proto::{core::crypto::v1alpha1 as pb, serializers::bech32str, Proto
 use poseidon377::hash_2;
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 use super::{AddressIndex, DiversifierKey, IncomingViewingKey, NullifierKey, OutgoingViewingKey};
 use crate::{
-    fmd, ka,
+    fmd, hash, ka,
     note::Commitment,
     prf,
     rdsa::{SpendAuth, VerificationKey},
@@ -62,7 +63,7 @@ impl FullViewingKey {
     /// Returns the index of the given address, if the address is viewed by this
     /// viewing key; otherwise, returns `None`.
     pub fn address_index(&self, address: &Address) -> Option<AddressIndex> {
-        self.incoming().address_index(address)
+        self.incoming().index_for_address(address)
     }
 
     /// Construct a full viewing key from its components.
@@ -84,7 +85,7 @@ impl FullViewingKey {
         let ivk = {
             let ak_s = Fq::from_bytes(*ak.as_ref())
                 .expect("verification key is valid, so its byte encoding is a decaf377 s value");
-            let ivk_mod_q = poseidon377::hash_2(&IVK_DOMAIN_SEP, (nk.0, ak_s));
+            let ivk_mod_q = hash::IVK.hash([nk.0, ak_s]);
             ka::Secret::new_from_field(Fr::from_le_bytes_mod_order(&ivk_mod_q.to_bytes()))
         };
 
@@ -185,6 +186,14 @@ impl std::fmt::Display for FullViewingKey {
     }
 }
 
+/// Zeroizes this key's copy of the [`NullifierKey`]. Copies of it handed out by
+/// [`FullViewingKey::nullifier_key`] are not affected.
+impl Drop for FullViewingKey {
+    fn drop(&mut self) {
+        self.nk.zeroize();
+    }
+}
+
 impl std::fmt::Debug for FullViewingKey {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         <Self as std::fmt::Display>::fmt(self, f)
@@ -1,19 +1,30 @@
 use ark_ff::PrimeField;
 use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
 
 use super::{AddressIndex, Diversifier, DiversifierKey};
-use crate::{fmd, ka, prf, Address, Fr};
+use crate::{fmd, ka, prf, Address, EncryptedNote, Fr, Note, TrialDecryptError};
+
+/// The fewest payloads [`IncomingViewingKey::trial_decrypt_batch`] hands to a
+/// thread at once, so that small batches aren't slowed down by scheduling.
+const TRIAL_DECRYPT_MIN_CHUNK: usize = 16;
 
 pub const IVK_LEN_BYTES: usize = 64;
 
 /// Allows viewing incoming notes, i.e., notes sent to the spending key this
 /// key is derived from.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct IncomingViewingKey {
     pub(super) ivk: ka::Secret,
     pub(super) dk: DiversifierKey,
 }
 
+impl std::fmt::Debug for IncomingViewingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncomingViewingKey").finish_non_exhaustive()
+    }
+}
+
 impl IncomingViewingKey {
     /// Derive a shielded payment address with the given [`AddressIndex`].
     pub fn payment_address(&self, index: AddressIndex) -> (Address, fmd::DetectionKey) {
@@ -51,6 +62,53 @@ impl IncomingViewingKey {
         self.ivk.key_agreement_with(pk)
     }
 
+    /// Perform key agreement with each of the given public keys, sharing the
+    /// precomputation for this key between all of them.
+    ///
+    /// The results are in the same order as the public keys, and are the same as
+    /// those of [`IncomingViewingKey::key_agreement_with`].
+    pub fn batch_key_agreement_with(
+        &self,
+        pks: &[ka::Public],
+    ) -> Vec<Result<ka::SharedSecret, ka::Error>> {
+        self.ivk.prepare().batch_key_agreement(pks)
+    }
+
+    /// Trial-decrypt each of the `payloads`, returning the notes meant for this
+    /// key in the same order, and `None` for every other payload.
+    ///
+    /// The results are the same as those of
+    /// [`EncryptedNote::trial_decrypt`], but this key is only recoded for key
+    /// agreement once for the whole batch, and the payloads are decrypted in
+    /// parallel on the rayon thread pool.
+    pub fn trial_decrypt_batch(&self, payloads: &[EncryptedNote]) -> Vec<Option<Note>> {
+        self.trial_decrypt_batch_results(payloads)
+            .into_iter()
+            .map(Result::ok)
+            .collect()
+    }
+
+    /// Trial-decrypt each of the `payloads` as in
+    /// [`IncomingViewingKey::trial_decrypt_batch`], but report why no note was
+    /// found in a payload, so that e.g. payloads forged to not match their note
+    /// commitment can be told apart from payloads meant for other keys.
+    pub fn trial_decrypt_batch_results(
+        &self,
+        payloads: &[EncryptedNote],
+    ) -> Vec<Result<Note, TrialDecryptError>> {
+        let prepared = self.ivk.prepare();
+        payloads
+            .par_iter()
+            .with_min_len(TRIAL_DECRYPT_MIN_CHUNK)
+            .map(|payload| {
+                let shared_secret = prepared
+                    .key_agreement_with(&payload.ephemeral_key)
+                    .map_err(|_| TrialDecryptError::Undecryptable)?;
+                payload.decrypt_with_shared_secret(&shared_secret, self)
+            })
+            .collect()
+    }
+
     /// Derive a transmission key from the given diversified base.
     pub fn diversified_public(&self, diversified_generator: &decaf377::Element) -> ka::Public {
         self.ivk.diversified_public(diversified_generator)
@@ -69,7 +127,7 @@ impl IncomingViewingKey {
 
     /// Returns the index of the given address, if the address is viewed by this
     /// viewing key; otherwise, returns `None`.
-    pub fn address_index(&self, address: &Address) -> Option<AddressIndex> {
+    pub fn index_for_address(&self, address: &Address) -> Option<AddressIndex> {
         if self.views_address(address) {
             Some(self.index_for_diversifier(address.diversifier()))
         } else {
@@ -1,26 +1,73 @@
-use poseidon377::hash_3;
+use rayon::prelude::*;
+use zeroize::Zeroize;
 
-use crate::{
-    note::Commitment,
-    nullifier::{Nullifier, NULLIFIER_DOMAIN_SEP},
-    Fq,
-};
+use crate::{hash, note::Commitment, nullifier::Nullifier, Fq};
 
 pub const NK_LEN_BYTES: usize = 32;
 
 /// Allows deriving the nullifier associated with a positioned piece of state.
-#[derive(Clone, Copy, Debug)]
+///
+/// The key is secret, so its `Debug` output is redacted. It is `Copy`, so that
+/// it can be handed to proof plans by value, which means it can't zeroize
+/// itself on drop. A [`FullViewingKey`](super::FullViewingKey) zeroizes its
+/// own copy when it is dropped, but every copy taken from it, e.g. by a proof
+/// plan, remains in memory until it is overwritten, and should be zeroized
+/// explicitly by whoever holds it.
+#[derive(Clone, Copy, Zeroize)]
 pub struct NullifierKey(pub Fq);
 
 impl NullifierKey {
+    /// The secret scalar of this key, e.g. to allocate it as a witness.
+    pub fn expose_secret(&self) -> &Fq {
+        &self.0
+    }
+
     pub fn derive_nullifier(
         &self,
         pos: penumbra_tct::Position,
         state_commitment: &Commitment,
     ) -> Nullifier {
-        Nullifier(hash_3(
-            &NULLIFIER_DOMAIN_SEP,
-            (self.0, state_commitment.0, (u64::from(pos)).into()),
-        ))
+        Nullifier(hash::NULLIFIER.hash([self.0, state_commitment.0, (u64::from(pos)).into()]))
+    }
+
+    /// Derive the nullifiers for many positioned pieces of state at once.
+    ///
+    /// The Poseidon evaluations are spread across threads, which makes this
+    /// much faster than repeated calls to [`NullifierKey::derive_nullifier`]
+    /// when checking a large number of witnessed commitments. The returned
+    /// nullifiers are in the same order as the `items`.
+    pub fn derive_nullifiers(
+        &self,
+        items: &[(penumbra_tct::Position, Commitment)],
+    ) -> Vec<Nullifier> {
+        items
+            .par_iter()
+            .map(|(pos, state_commitment)| self.derive_nullifier(*pos, state_commitment))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for NullifierKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NullifierKey").field(&"<redacted>").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_nullifiers_match_individual_derivation() {
+        let nk = NullifierKey(Fq::from(42u64));
+        let items: Vec<_> = (0..100u64)
+            .map(|i| (i.into(), Commitment(Fq::from(i * 7 + 1))))
+            .collect();
+
+        let batch = nk.derive_nullifiers(&items);
+        assert_eq!(batch.len(), items.len());
+        for ((pos, commitment), nullifier) in items.iter().zip(batch) {
+            assert_eq!(nk.derive_nullifier(*pos, commitment), nullifier);
+        }
     }
 }
@@ -1,6 +1,17 @@
+use zeroize::Zeroize;
+
 pub const OVK_LEN_BYTES: usize = 32;
 
 /// Allows viewing outgoing notes, i.e., notes sent from the spending key this
 /// key is derived from.
-#[derive(Clone, Debug)]
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
 pub struct OutgoingViewingKey(pub(crate) [u8; OVK_LEN_BYTES]);
+
+impl std::fmt::Debug for OutgoingViewingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OutgoingViewingKey")
+            .field(&"<redacted>")
+            .finish()
+    }
+}
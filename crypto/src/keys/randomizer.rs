@@ -0,0 +1,93 @@
+use decaf377_rdsa::{SigningKey, SpendAuth, VerificationKey};
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::{prf, sample::SampleUniform, FieldExt, Fr};
+
+/// A randomizer used to re-randomize a spend authorization key.
+///
+/// This is a distinct type from the balance commitment blinding factor (also an
+/// `Fr`), so that the two can't be accidentally swapped when building a proof.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct SpendAuthRandomizer(Fr);
+
+impl SpendAuthRandomizer {
+    /// Sample a fresh randomizer using `rng`.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(Fr::rand_uniform(rng))
+    }
+
+    /// Deterministically derive a randomizer from a 32-byte `seed`.
+    pub fn derive(seed: &[u8; 32]) -> Self {
+        Self(prf::expand_ff(b"Penumbra_SpAuRnd", seed, &[]))
+    }
+
+    /// Randomize a spend authorization signing key.
+    pub fn randomize_signing_key(&self, rsk: &SigningKey<SpendAuth>) -> SigningKey<SpendAuth> {
+        rsk.randomize(&self.0)
+    }
+
+    /// Randomize a spend authorization verification key.
+    pub fn randomize_verification_key(
+        &self,
+        ak: &VerificationKey<SpendAuth>,
+    ) -> VerificationKey<SpendAuth> {
+        ak.randomize(&self.0)
+    }
+
+    /// The randomizer as a scalar.
+    pub fn to_scalar(&self) -> Fr {
+        self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+}
+
+impl std::fmt::Debug for SpendAuthRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SpendAuthRandomizer")
+            .field(&"<redacted>")
+            .finish()
+    }
+}
+
+impl From<Fr> for SpendAuthRandomizer {
+    fn from(randomizer: Fr) -> Self {
+        Self(randomizer)
+    }
+}
+
+impl TryFrom<&[u8]> for SpendAuthRandomizer {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("spend auth randomizer must be 32 bytes"))?;
+        Ok(Self(Fr::from_bytes(bytes)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::keys::{SeedPhrase, SpendKey};
+
+    #[test]
+    fn randomized_keys_match() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let randomizer = SpendAuthRandomizer::generate(&mut OsRng);
+
+        let rsk = randomizer.randomize_signing_key(sk.spend_auth_key());
+        let rk = randomizer.randomize_verification_key(&sk.spend_auth_key().into());
+        assert_eq!(VerificationKey::from(rsk).to_bytes(), rk.to_bytes());
+
+        let round_trip = SpendAuthRandomizer::try_from(&randomizer.to_bytes()[..]).unwrap();
+        assert_eq!(round_trip.to_scalar(), randomizer.to_scalar());
+    }
+}
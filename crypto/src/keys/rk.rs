@@ -0,0 +1,86 @@
+use decaf377::{Element, Encoding};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+
+/// A randomized spend authorization key, whose encoding has been checked to
+/// be a valid group element.
+///
+/// The `rk` of a spend or delegator vote is supplied by whoever built the
+/// transaction, so it must be decoded before it can be used as a public input
+/// to a proof. Decoding it once, when this key is constructed, means proving
+/// and verifying can't fail on a malformed key, and don't repeat the
+/// decompression.
+#[derive(Clone, Copy, Debug)]
+pub struct RandomizedVerificationKey {
+    vk: VerificationKey<SpendAuth>,
+    element: Element,
+}
+
+impl RandomizedVerificationKey {
+    /// The key as a verification key, e.g. for checking a spend auth signature.
+    pub fn verification_key(&self) -> &VerificationKey<SpendAuth> {
+        &self.vk
+    }
+
+    /// The decoded group element of the key.
+    pub fn element(&self) -> Element {
+        self.element
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.vk.to_bytes()
+    }
+}
+
+impl TryFrom<VerificationKey<SpendAuth>> for RandomizedVerificationKey {
+    type Error = anyhow::Error;
+
+    fn try_from(vk: VerificationKey<SpendAuth>) -> Result<Self, Self::Error> {
+        let element = Encoding(vk.to_bytes())
+            .vartime_decompress()
+            .map_err(|_| anyhow::anyhow!("randomized verification key is not a valid element"))?;
+        Ok(Self { vk, element })
+    }
+}
+
+impl TryFrom<[u8; 32]> for RandomizedVerificationKey {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: [u8; 32]) -> Result<Self, Self::Error> {
+        let element = Encoding(bytes)
+            .vartime_decompress()
+            .map_err(|_| anyhow::anyhow!("randomized verification key is not a valid element"))?;
+        let vk = VerificationKey::try_from(bytes)?;
+        Ok(Self { vk, element })
+    }
+}
+
+impl From<RandomizedVerificationKey> for VerificationKey<SpendAuth> {
+    fn from(rk: RandomizedVerificationKey) -> Self {
+        rk.vk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::keys::{SeedPhrase, SpendAuthRandomizer, SpendKey};
+
+    #[test]
+    fn rk_decoding() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let randomizer = SpendAuthRandomizer::generate(&mut OsRng);
+        let rk = randomizer.randomize_verification_key(&sk.spend_auth_key().into());
+
+        let validated = RandomizedVerificationKey::try_from(rk).unwrap();
+        assert_eq!(validated.element().vartime_compress().0, rk.to_bytes());
+
+        // Not every 32-byte string is the encoding of an element.
+        let invalid = (0u8..=255)
+            .map(|i| [i; 32])
+            .find(|bytes| Encoding(*bytes).vartime_decompress().is_err())
+            .unwrap();
+        assert!(RandomizedVerificationKey::try_from(invalid).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use std::fmt;
 
 use rand_core::{CryptoRng, RngCore};
 use sha2::Digest;
+use zeroize::Zeroize;
 
 mod words;
 use words::BIP39_WORDS;
@@ -15,6 +16,10 @@ pub const NUM_BITS_PER_WORD: usize = 11;
 pub const NUM_BITS_PER_BYTE: usize = 8;
 
 /// A mnemonic seed phrase. Used to generate [`SpendSeed`]s.
+///
+/// The words are zeroized on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
 pub struct SeedPhrase(pub [String; NUM_WORDS]);
 
 impl SeedPhrase {
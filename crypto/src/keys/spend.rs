@@ -4,6 +4,8 @@ use hmac::Hmac;
 use pbkdf2::pbkdf2;
 use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
 
 use super::{
     seed_phrase::{SeedPhrase, NUM_PBKDF2_ROUNDS},
@@ -12,6 +14,8 @@ use super::{
 use crate::{
     prf,
     rdsa::{SigningKey, SpendAuth},
+    stake::IdentityKey,
+    Fr, GovernanceKey,
 };
 
 pub const SPENDKEY_LEN_BYTES: usize = 32;
@@ -22,11 +26,44 @@ pub const SPENDKEY_LEN_BYTES: usize = 32;
 /// TODO(hdevalence): In the future, we should hide the SpendKeyBytes
 /// and force everything to use the proto format / bech32 serialization.
 /// But we can't do this now, because we need it to support existing wallets.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+///
+/// The bytes are zeroized on drop, compared in constant time, and redacted from
+/// the `Debug` output; use [`SpendKeyBytes::expose_secret`] to read them.
+#[derive(Clone, Deserialize, Serialize, Zeroize)]
+#[zeroize(drop)]
 pub struct SpendKeyBytes(pub [u8; SPENDKEY_LEN_BYTES]);
 
+impl SpendKeyBytes {
+    /// Borrow the secret bytes of the spend key.
+    ///
+    /// Callers should avoid copying them anywhere that outlives the borrow.
+    pub fn expose_secret(&self) -> &[u8; SPENDKEY_LEN_BYTES] {
+        &self.0
+    }
+}
+
+impl PartialEq for SpendKeyBytes {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare in constant time, so that the comparison takes the same
+        // time wherever the keys differ.
+        self.0[..].ct_eq(&other.0[..]).into()
+    }
+}
+
+impl Eq for SpendKeyBytes {}
+
+impl std::fmt::Debug for SpendKeyBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SpendKeyBytes").field(&"<redacted>").finish()
+    }
+}
+
 /// A key representing a single spending authority.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+///
+/// Its `Debug` output only shows the [`AccountID`](super::AccountID) of its
+/// [`FullViewingKey`], since the viewing key's own encoding includes the
+/// nullifier key. The spend authorization key is wiped on drop.
+#[derive(Clone, Deserialize, Serialize)]
 #[serde(try_from = "pb::SpendKey", into = "pb::SpendKey")]
 pub struct SpendKey {
     seed: SpendKeyBytes,
@@ -42,6 +79,27 @@ impl PartialEq for SpendKey {
 
 impl Eq for SpendKey {}
 
+impl std::fmt::Debug for SpendKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpendKey")
+            .field("account_id", &self.fvk.hash())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SpendKey {
+    fn drop(&mut self) {
+        // `SigningKey` doesn't implement `Zeroize`, so overwrite it with the key
+        // for the zero scalar, with a volatile write so that the store isn't
+        // elided. The seed and the nullifier key are wiped by their own types.
+        let wiped = SigningKey::new_from_field(Fr::from(0u64));
+        // SAFETY: `self.ask` is a valid, aligned place, and `SigningKey` has no
+        // drop glue, so the old value doesn't need to be dropped first.
+        unsafe { std::ptr::write_volatile(&mut self.ask, wiped) };
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
 impl Protobuf<pb::SpendKey> for SpendKey {}
 
 impl TryFrom<pb::SpendKey> for SpendKey {
@@ -87,7 +145,7 @@ impl SpendKey {
     ///
     /// [`BIP39`]: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
     pub fn from_seed_phrase(seed_phrase: SeedPhrase, index: u64) -> Self {
-        let password = format!("{}", seed_phrase);
+        let mut password = format!("{}", seed_phrase);
         let salt = format!("mnemonic{}", index);
         let mut spend_seed_bytes = [0u8; 32];
         pbkdf2::<Hmac<sha2::Sha512>>(
@@ -96,7 +154,56 @@ impl SpendKey {
             NUM_PBKDF2_ROUNDS,
             &mut spend_seed_bytes,
         );
-        SpendKeyBytes(spend_seed_bytes).into()
+        password.zeroize();
+        let spend_key = SpendKeyBytes(spend_seed_bytes).into();
+        spend_seed_bytes.zeroize();
+        spend_key
+    }
+
+    /// Derive the child spend key with the given `index`.
+    ///
+    /// The result is a plain spend key, deterministically derived from this
+    /// one, with its own `ak`/`nk` pair and its own addresses. It is not
+    /// restricted in any way: it can spend any note sent to any of its own
+    /// addresses, at every address index, and nothing in the protocol or the
+    /// spend circuit distinguishes it from any other spend key. It also has no
+    /// authority over notes sent to this key's addresses.
+    ///
+    /// This can be used for a hot/cold wallet split, where the cold wallet
+    /// holds this key and hands a child key to the hot wallet. The cold wallet
+    /// can always re-derive the child key to view or sweep its funds, but the
+    /// child key reveals nothing about this key.
+    pub fn delegate(&self, index: u32) -> SpendKey {
+        let hash_result = prf::expand(b"Penumbra_DelgtSK", &self.seed.0, &index.to_le_bytes());
+        let mut seed = [0u8; SPENDKEY_LEN_BYTES];
+        seed.copy_from_slice(&hash_result.as_bytes()[0..SPENDKEY_LEN_BYTES]);
+        let spend_key = SpendKeyBytes(seed).into();
+        seed.zeroize();
+        spend_key
+    }
+
+    /// Get the validator identity key controlled by this spend key.
+    ///
+    /// As elsewhere in the wallet software, the identity key is the spend
+    /// verification key, so that validators can custody their identity using
+    /// the same processes as their funds. It is signed for by
+    /// [`SpendKey::spend_auth_key`].
+    pub fn identity_key(&self) -> IdentityKey {
+        IdentityKey(*self.fvk.spend_verification_key())
+    }
+
+    /// Derive the signing key for this spend key's validator governance key.
+    ///
+    /// Unlike the identity key, this is distinct from the spend authorization
+    /// key, so that the governance key can be handed to a hot voting setup
+    /// while the identity stays in cold storage.
+    pub fn governance_signing_key(&self) -> SigningKey<SpendAuth> {
+        SigningKey::new_from_field(prf::expand_ff(b"Penumbra_GovrnSK", &self.seed.0, &[]))
+    }
+
+    /// Get the validator governance key derived from this spend key.
+    pub fn governance_key(&self) -> GovernanceKey {
+        GovernanceKey(self.governance_signing_key().into())
     }
 
     // XXX how many of these do we need? leave them for now
@@ -162,3 +269,61 @@ impl std::str::FromStr for SpendKey {
         .try_into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldExt;
+
+    #[test]
+    fn validator_keys_round_trip_through_strings() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([4u8; 32]), 0);
+
+        let identity_key = sk.identity_key();
+        assert_eq!(
+            identity_key.to_string().parse::<IdentityKey>().unwrap(),
+            identity_key
+        );
+
+        let governance_key = sk.governance_key();
+        assert_eq!(governance_key, sk.governance_key());
+        assert_ne!(governance_key.0, identity_key.0);
+        assert_eq!(
+            governance_key.to_string().parse::<GovernanceKey>().unwrap(),
+            governance_key
+        );
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([5u8; 32]), 0);
+        let seed = sk.to_bytes();
+        let nk = sk.nullifier_key();
+
+        let debug = format!("{:?} {:?} {:?}", sk, seed, nk);
+        assert!(!debug.contains(&hex::encode(seed.expose_secret())));
+        assert!(!debug.contains(&format!("{:?}", seed.expose_secret())));
+        assert!(!debug.contains(&hex::encode(nk.expose_secret().to_bytes())));
+        // The full viewing key's encoding includes the nullifier key.
+        assert!(!debug.contains(&sk.full_viewing_key().to_string()));
+        assert!(debug.contains("<redacted>"));
+
+        assert_eq!(seed, sk.to_bytes());
+        assert_ne!(seed, sk.delegate(0).to_bytes());
+    }
+
+    #[test]
+    fn delegated_keys_are_distinct_and_deterministic() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([3u8; 32]), 0);
+
+        let hot = sk.delegate(0);
+        assert_eq!(hot, sk.delegate(0));
+        assert_ne!(hot, sk);
+        assert_ne!(hot, sk.delegate(1));
+
+        // Addresses of the delegated key are not viewed by the parent's keys.
+        let (hot_address, _dtk) = hot.full_viewing_key().payment_address(0u64.into());
+        assert!(sk.full_viewing_key().address_index(&hot_address).is_none());
+        assert!(hot.full_viewing_key().address_index(&hot_address).is_some());
+    }
+}
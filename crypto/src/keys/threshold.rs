@@ -0,0 +1,563 @@
+//! Threshold control of the spend authorization key, using FROST.
+//!
+//! [FROST](https://eprint.iacr.org/2020/852) lets any `threshold` of `max_signers` participants
+//! jointly produce a spend auth signature, without the spend authorization key ever existing in
+//! one place:
+//!
+//! 1. In the [`dkg`], run with [`frost377`], every participant deals shares of a random secret to
+//!    the others, and each ends up with a [`KeyPackage`] holding its share of the spend
+//!    authorization key `ask`. Their verifying shares make up a [`PublicKeyPackage`] with the
+//!    joint key `ak`, which is used with a nullifier key to form a
+//!    [`FullViewingKey`](crate::FullViewingKey).
+//! 2. To sign, each participant [`commit`]s to a pair of nonces, and a coordinator collects the
+//!    commitments of `threshold` participants into a [`SigningPackage`] with the effect hash and
+//!    the spend's randomizer.
+//! 3. Each of those participants [`sign`]s the package, and the coordinator [`aggregate`]s their
+//!    shares into an ordinary `decaf377-rdsa` [`Signature<SpendAuth>`], which verifies under the
+//!    spend's randomized key `rk` in the [`SpendAuthorization`] context, exactly as one made by
+//!    a single signer would.
+//!
+//! All the messages exchanged between participants are [`serde`]-serializable.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, ensure, Result};
+use ark_ff::{Field, One, PrimeField, Zero};
+use decaf377::{Element, Encoding, FieldExt, Fr};
+use decaf377_rdsa::{Signature, SpendAuth, VerificationKey};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::SpendAuthRandomizer;
+use crate::{
+    prf,
+    signing::{context_message, SpendAuthorization},
+};
+
+/// The identifier of a participant, which must be nonzero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Identifier(u16);
+
+impl Identifier {
+    /// The participant's evaluation point on the secret sharing polynomial.
+    fn scalar(&self) -> Fr {
+        Fr::from(u64::from(self.0))
+    }
+}
+
+impl TryFrom<u16> for Identifier {
+    type Error = anyhow::Error;
+
+    fn try_from(id: u16) -> Result<Self> {
+        ensure!(id != 0, "participant identifiers must be nonzero");
+        Ok(Self(id))
+    }
+}
+
+impl From<Identifier> for u16 {
+    fn from(id: Identifier) -> u16 {
+        id.0
+    }
+}
+
+/// A participant's share of the spend authorization key, from the [`dkg`].
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct KeyPackage {
+    #[zeroize(skip)]
+    identifier: Identifier,
+    signing_share: Fr,
+    #[zeroize(skip)]
+    group_key: [u8; 32],
+    #[zeroize(skip)]
+    threshold: u16,
+}
+
+impl KeyPackage {
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// The number of participants needed to sign.
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// The joint spend verification key `ak`.
+    pub fn group_key(&self) -> VerificationKey<SpendAuth> {
+        VerificationKey::try_from(self.group_key).expect("group key is a valid element")
+    }
+
+    /// This participant's share of the joint key, to be collected into the
+    /// [`PublicKeyPackage`].
+    pub fn verifying_share(&self) -> VerificationKey<SpendAuth> {
+        VerificationKey::try_from(compress(self.signing_share * decaf377::basepoint()))
+            .expect("verifying share is a valid element")
+    }
+}
+
+impl std::fmt::Debug for KeyPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPackage")
+            .field("identifier", &self.identifier)
+            .field("signing_share", &"<redacted>")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+/// The public outcome of the [`dkg`]: the joint key, and each participant's share of it, with
+/// which the coordinator can tell which participant sent an invalid signature share.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackage {
+    verifying_shares: BTreeMap<Identifier, [u8; 32]>,
+    group_key: [u8; 32],
+}
+
+impl PublicKeyPackage {
+    /// Assemble the public outcome of the DKG from every participant's
+    /// [`KeyPackage::verifying_share`].
+    ///
+    /// Errors if the shares are not all consistent with a single key shared among `threshold`
+    /// participants. Each participant should check that the resulting [`group_key`] is the one in
+    /// its own [`KeyPackage`].
+    ///
+    /// [`group_key`]: PublicKeyPackage::group_key
+    pub fn new(
+        threshold: u16,
+        verifying_shares: BTreeMap<Identifier, VerificationKey<SpendAuth>>,
+    ) -> Result<Self> {
+        let threshold = usize::from(threshold);
+        ensure!(
+            threshold >= 1 && verifying_shares.len() >= threshold,
+            "expected at least {} verifying shares",
+            threshold
+        );
+        let shares = verifying_shares
+            .iter()
+            .map(|(identifier, share)| Ok((*identifier, decompress(&share.to_bytes())?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The first `threshold` shares determine the key; every other share must interpolate to
+        // the same key with the first `threshold - 1`.
+        let (first, rest) = shares.split_at(threshold);
+        let group_key = interpolate(first)?;
+        for extra in rest {
+            let mut subset = first[..threshold - 1].to_vec();
+            subset.push(*extra);
+            ensure!(
+                interpolate(&subset)? == group_key,
+                "the verifying share of participant {} is inconsistent with the others",
+                extra.0 .0
+            );
+        }
+
+        Ok(Self {
+            verifying_shares: verifying_shares
+                .into_iter()
+                .map(|(identifier, share)| (identifier, share.to_bytes()))
+                .collect(),
+            group_key: compress(group_key),
+        })
+    }
+
+    /// The joint spend verification key `ak`.
+    pub fn group_key(&self) -> VerificationKey<SpendAuth> {
+        VerificationKey::try_from(self.group_key).expect("group key is a valid element")
+    }
+}
+
+/// Distributed generation of the spend authorization key, with the Pedersen DKG of
+/// [`frost377`].
+///
+/// Each participant runs a [`Participant`] through both rounds of the DKG, then turns it into its
+/// [`KeyPackage`] with [`key_package`]. The participants' verifying shares are collected into the
+/// [`PublicKeyPackage`].
+pub mod dkg {
+    pub use frost377::keygen::Participant;
+
+    use super::*;
+
+    /// The share of the spend authorization key of a `participant` that has completed the DKG
+    /// with the given `threshold`.
+    pub fn key_package(participant: &Participant, threshold: u16) -> Result<KeyPackage> {
+        let output = participant
+            .finalize()
+            .map_err(|error| anyhow!("could not complete the DKG: {:?}", error))?;
+        Ok(KeyPackage {
+            identifier: Identifier::try_from(u16::try_from(output.participant_index)?)?,
+            signing_share: output.private_share,
+            group_key: compress(output.group_public_key),
+            threshold,
+        })
+    }
+}
+
+/// A participant's secret nonces for one signature, which must be used only once.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct SigningNonces {
+    hiding: Fr,
+    binding: Fr,
+}
+
+/// A participant's commitments to its [`SigningNonces`], sent to the coordinator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitments {
+    pub identifier: Identifier,
+    hiding: [u8; 32],
+    binding: [u8; 32],
+}
+
+/// Everything the signers need to produce their signature shares, assembled by the coordinator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPackage {
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+    /// The message actually signed, i.e. the effect hash in the spend authorization context.
+    message: Vec<u8>,
+    randomizer: [u8; 32],
+}
+
+impl SigningPackage {
+    /// Assemble a package to authorize a spend with the given `randomizer` over `effect_hash`,
+    /// from the commitments of the participants who will sign.
+    pub fn new(
+        commitments: &[SigningCommitments],
+        effect_hash: &[u8],
+        randomizer: &SpendAuthRandomizer,
+    ) -> Result<Self> {
+        let mut by_identifier = BTreeMap::new();
+        for commitment in commitments {
+            ensure!(
+                by_identifier
+                    .insert(commitment.identifier, *commitment)
+                    .is_none(),
+                "duplicate commitments from participant {}",
+                commitment.identifier.0
+            );
+        }
+        Ok(Self {
+            commitments: by_identifier,
+            message: context_message::<SpendAuthorization>(effect_hash).to_vec(),
+            randomizer: randomizer.to_bytes(),
+        })
+    }
+
+    /// The randomized verification key `rk` the signature will verify under.
+    pub fn rk(&self, group_key: &VerificationKey<SpendAuth>) -> Result<VerificationKey<SpendAuth>> {
+        Ok(group_key.randomize(&Fr::from_bytes(self.randomizer)?))
+    }
+}
+
+/// A participant's share of a signature, sent to the coordinator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    pub identifier: Identifier,
+    share: [u8; 32],
+}
+
+/// Generate fresh nonces to sign with, returning them with the commitments to send to the
+/// coordinator.
+///
+/// The nonces are hedged with the participant's signing share, so that a weak `rng` alone does not
+/// reveal it.
+pub fn commit<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &KeyPackage,
+) -> (SigningNonces, SigningCommitments) {
+    let mut nonce = |rng: &mut R| {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        prf::expand_ff(b"Penumbra_FrstNon", &seed, &key.signing_share.to_bytes())
+    };
+    let nonces = SigningNonces {
+        hiding: nonce(rng),
+        binding: nonce(rng),
+    };
+    let commitments = SigningCommitments {
+        identifier: key.identifier,
+        hiding: compress(nonces.hiding * decaf377::basepoint()),
+        binding: compress(nonces.binding * decaf377::basepoint()),
+    };
+    (nonces, commitments)
+}
+
+/// Produce this participant's share of the signature described by `package`, consuming the nonces
+/// committed to for it.
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key: &KeyPackage,
+) -> Result<SignatureShare> {
+    let own = package
+        .commitments
+        .get(&key.identifier)
+        .ok_or_else(|| anyhow!("this participant is not a signer of the package"))?;
+    ensure!(
+        own.hiding == compress(nonces.hiding * decaf377::basepoint())
+            && own.binding == compress(nonces.binding * decaf377::basepoint()),
+        "the package does not contain this participant's commitments to these nonces"
+    );
+    ensure!(
+        package.commitments.len() >= usize::from(key.threshold),
+        "the package has fewer than {} signers",
+        key.threshold
+    );
+
+    let rk = package.rk(&key.group_key())?;
+    let (group_commitment, binding_factors) = group_commitment(package, &rk)?;
+    let challenge = challenge(&group_commitment, &rk, &package.message);
+    let lambda = lagrange_coefficient(package, key.identifier)?;
+
+    let share = nonces.hiding
+        + binding_factors[&key.identifier] * nonces.binding
+        + lambda * key.signing_share * challenge;
+    Ok(SignatureShare {
+        identifier: key.identifier,
+        share: share.to_bytes(),
+    })
+}
+
+/// Combine the signers' shares into a spend auth signature, which verifies under the package's
+/// randomized key.
+///
+/// Errors, identifying a misbehaving participant, if any share is invalid.
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &[SignatureShare],
+    public: &PublicKeyPackage,
+) -> Result<Signature<SpendAuth>> {
+    ensure!(
+        shares.len() == package.commitments.len(),
+        "expected a signature share from each of the {} signers",
+        package.commitments.len()
+    );
+
+    let rk = package.rk(&public.group_key())?;
+    let (group_commitment, binding_factors) = group_commitment(package, &rk)?;
+    let challenge = challenge(&group_commitment, &rk, &package.message);
+
+    let mut response = Fr::zero();
+    for share in shares {
+        let commitments = package
+            .commitments
+            .get(&share.identifier)
+            .ok_or_else(|| anyhow!("signature share from non-signer {}", share.identifier.0))?;
+        let verifying_share = public
+            .verifying_shares
+            .get(&share.identifier)
+            .ok_or_else(|| anyhow!("unknown participant {}", share.identifier.0))?;
+
+        let z = Fr::from_bytes(share.share)?;
+        let lambda = lagrange_coefficient(package, share.identifier)?;
+        let expected = decompress(&commitments.hiding)?
+            + binding_factors[&share.identifier] * decompress(&commitments.binding)?
+            + (lambda * challenge) * decompress(verifying_share)?;
+        ensure!(
+            z * decaf377::basepoint() == expected,
+            "participant {} sent an invalid signature share",
+            share.identifier.0
+        );
+        response += z;
+    }
+
+    // The signers signed with shares of `ask`; the randomizer turns that into a signature by
+    // `rsk = ask + randomizer`.
+    response += Fr::from_bytes(package.randomizer)? * challenge;
+
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&compress(group_commitment));
+    bytes[32..].copy_from_slice(&response.to_bytes());
+    let signature = Signature::from(bytes);
+
+    rk.verify(&package.message, &signature)
+        .map_err(|_| anyhow!("aggregated signature does not verify"))?;
+    Ok(signature)
+}
+
+/// The group commitment `R` of a signature, and each signer's binding factor.
+fn group_commitment(
+    package: &SigningPackage,
+    rk: &VerificationKey<SpendAuth>,
+) -> Result<(Element, BTreeMap<Identifier, Fr>)> {
+    let mut transcript = rk.to_bytes().to_vec();
+    transcript.extend_from_slice(&package.message);
+    for commitments in package.commitments.values() {
+        transcript.extend_from_slice(&commitments.identifier.0.to_le_bytes());
+        transcript.extend_from_slice(&commitments.hiding);
+        transcript.extend_from_slice(&commitments.binding);
+    }
+    let transcript = prf::expand(b"Penumbra_FrstTrn", &[], &transcript);
+
+    let mut group_commitment = Element::default();
+    let mut binding_factors = BTreeMap::new();
+    for (identifier, commitments) in &package.commitments {
+        let binding_factor: Fr = prf::expand_ff(
+            b"Penumbra_FrstRho",
+            transcript.as_bytes(),
+            &identifier.0.to_le_bytes(),
+        );
+        group_commitment = group_commitment
+            + decompress(&commitments.hiding)?
+            + binding_factor * decompress(&commitments.binding)?;
+        binding_factors.insert(*identifier, binding_factor);
+    }
+    Ok((group_commitment, binding_factors))
+}
+
+/// The Lagrange coefficient of `identifier` for interpolating at zero from the package's signers.
+fn lagrange_coefficient(package: &SigningPackage, identifier: Identifier) -> Result<Fr> {
+    lagrange_coefficient_among(package.commitments.keys().copied(), identifier)
+}
+
+/// The Lagrange coefficient of `identifier` for interpolating at zero from `identifiers`.
+fn lagrange_coefficient_among(
+    identifiers: impl IntoIterator<Item = Identifier>,
+    identifier: Identifier,
+) -> Result<Fr> {
+    let x = identifier.scalar();
+    let (mut numerator, mut denominator) = (Fr::one(), Fr::one());
+    for other in identifiers.into_iter().filter(|other| *other != identifier) {
+        numerator *= other.scalar();
+        denominator *= other.scalar() - x;
+    }
+    let inverse = denominator
+        .inverse()
+        .ok_or_else(|| anyhow!("duplicate participant identifiers"))?;
+    Ok(numerator * inverse)
+}
+
+/// Interpolate the shares of a secret "in the exponent" at zero.
+fn interpolate(shares: &[(Identifier, Element)]) -> Result<Element> {
+    let mut value = Element::default();
+    for (identifier, share) in shares {
+        let lambda = lagrange_coefficient_among(
+            shares.iter().map(|(identifier, _)| *identifier),
+            *identifier,
+        )?;
+        value = value + lambda * *share;
+    }
+    Ok(value)
+}
+
+/// The `decaf377-rdsa` challenge for a signature with commitment `r` by `vk` on `message`, so
+/// that aggregated signatures verify like any other.
+///
+/// `decaf377-rdsa` doesn't expose its challenge, so this mirrors it; the tests check that it
+/// agrees with signatures made by `decaf377-rdsa` itself.
+fn challenge(r: &Element, vk: &VerificationKey<SpendAuth>, message: &[u8]) -> Fr {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(64)
+        .personal(b"decaf377-rdsa---")
+        .to_state()
+        .update(&compress(*r))
+        .update(&vk.to_bytes())
+        .update(message)
+        .finalize();
+    Fr::from_le_bytes_mod_order(hash.as_bytes())
+}
+
+fn compress(element: Element) -> [u8; 32] {
+    element.vartime_compress().0
+}
+
+fn decompress(bytes: &[u8; 32]) -> Result<Element> {
+    Encoding(*bytes)
+        .vartime_decompress()
+        .map_err(|_| anyhow!("invalid group element"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::signing;
+
+    #[test]
+    fn two_of_three_signatures_verify_under_rk() {
+        let mut participants = (1..=3u32)
+            .map(|index| dkg::Participant::new(index, 2))
+            .collect::<Vec<_>>();
+        let round1 = participants
+            .iter()
+            .map(|participant| participant.round_one())
+            .collect::<Vec<_>>();
+        for participant in participants.iter_mut() {
+            participant.verify_roundone(round1.clone()).unwrap();
+        }
+        let others = participants.clone();
+        for participant in participants.iter_mut() {
+            for other in others
+                .iter()
+                .filter(|other| other.index != participant.index)
+            {
+                participant
+                    .verify_and_add_roundtwo_response(&other.round_two(participant.index))
+                    .unwrap();
+            }
+        }
+
+        let keys = participants
+            .iter()
+            .map(|participant| dkg::key_package(participant, 2).unwrap())
+            .collect::<Vec<_>>();
+        let verifying_shares = keys
+            .iter()
+            .map(|key| (key.identifier(), key.verifying_share()))
+            .collect::<BTreeMap<_, _>>();
+        let public = PublicKeyPackage::new(2, verifying_shares.clone()).unwrap();
+        assert!(keys.iter().all(|key| key.group_key() == public.group_key()));
+
+        // The public package survives a round trip through its serialization.
+        let public: PublicKeyPackage =
+            serde_json::from_str(&serde_json::to_string(&public).unwrap()).unwrap();
+        let public = &public;
+
+        // A verifying share that isn't on the same polynomial is rejected.
+        let mut bad_shares = verifying_shares;
+        let first = *bad_shares.keys().next().unwrap();
+        bad_shares.insert(first, keys[1].verifying_share());
+        assert!(PublicKeyPackage::new(2, bad_shares).is_err());
+
+        let effect_hash = [3u8; 64];
+        let randomizer = SpendAuthRandomizer::generate(&mut OsRng);
+        let signers = [&keys[0], &keys[2]];
+        let (nonces, commitments): (Vec<_>, Vec<_>) =
+            signers.iter().map(|key| commit(&mut OsRng, key)).unzip();
+        let package = SigningPackage::new(&commitments, &effect_hash, &randomizer).unwrap();
+        let shares = nonces
+            .into_iter()
+            .zip(signers)
+            .map(|(nonces, key)| sign(&package, nonces, key).unwrap())
+            .collect::<Vec<_>>();
+
+        let signature = aggregate(&package, &shares, public).unwrap();
+        let rk = randomizer.randomize_verification_key(&public.group_key());
+        assert!(signing::verify::<SpendAuthorization>(&rk, &effect_hash, &signature).is_ok());
+
+        // A corrupted share is attributed to its sender.
+        let mut bad_shares = shares.clone();
+        bad_shares[1].share = Fr::from(1u64).to_bytes();
+        let error = aggregate(&package, &bad_shares, public).unwrap_err();
+        assert!(error.to_string().contains("participant 3"));
+    }
+
+    #[test]
+    fn challenge_matches_rdsa() {
+        let sk = decaf377_rdsa::SigningKey::<SpendAuth>::new(OsRng);
+        let vk = VerificationKey::from(&sk);
+        let message = b"a message";
+        let signature: [u8; 64] = sk.sign(OsRng, message).into();
+
+        let r = decompress(signature[..32].try_into().unwrap()).unwrap();
+        let s = Fr::from_bytes(signature[32..].try_into().unwrap()).unwrap();
+        let c = challenge(&r, &vk, message);
+        assert_eq!(
+            s * decaf377::basepoint(),
+            r + c * decompress(&vk.to_bytes()).unwrap()
+        );
+    }
+}
@@ -13,23 +13,31 @@ pub mod eddy;
 mod encrypted_note;
 mod flow;
 mod governance;
+pub mod hash;
 pub mod keys;
 pub mod memo;
 pub mod note;
 mod nullifier;
+pub mod ordering;
 mod prf;
 pub mod proofs;
+pub mod proto_json;
 pub mod rseed;
+pub mod sample;
+pub mod signing;
 pub mod stake;
 pub mod symmetric;
+pub mod test_vectors;
 pub mod transaction;
 pub mod value;
 
-pub use address::Address;
+pub use address::{Address, AddressParseError};
 pub use asset::Amount;
 pub use asset::Asset;
 pub use balance::Balance;
-pub use encrypted_note::EncryptedNote;
+pub use encrypted_note::{
+    EncryptedNote, EncryptedNoteRef, TrialDecryptError, ENCRYPTED_NOTE_BYTES,
+};
 pub use flow::{MockFlowCiphertext, SwapFlow};
 pub use governance::{
     key::GovernanceKey,
@@ -37,7 +45,7 @@ pub use governance::{
 };
 pub use keys::FullViewingKey;
 pub use note::Note;
-pub use nullifier::Nullifier;
+pub use nullifier::{AlreadySpent, Nullifier, NullifierSet, Retention};
 pub use rseed::Rseed;
 pub use symmetric::PayloadKey;
 pub use value::Value;
@@ -4,6 +4,9 @@ use std::{
 };
 
 use anyhow::anyhow;
+use ark_ff::PrimeField;
+use decaf377::Fq;
+use once_cell::sync::Lazy;
 
 use crate::{
     balance, ka,
@@ -13,6 +16,10 @@ use crate::{
     Note,
 };
 
+pub(crate) static MEMO_KEY_COMMITMENT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
+    Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.memokeycommit").as_bytes())
+});
+
 pub const MEMO_CIPHERTEXT_LEN_BYTES: usize = 528;
 
 // This is the `MEMO_CIPHERTEXT_LEN_BYTES` - MAC size (16 bytes).
@@ -98,6 +105,115 @@ impl MemoCiphertext {
     }
 }
 
+/// A commitment to the memo key of an output, binding the memo to the output's
+/// note.
+///
+/// The commitment is a hash of the memo key and the note's blinding factor, so
+/// it hides the memo key, and can only be opened by someone who knows the note,
+/// i.e. its sender or recipient. The output proof can optionally prove that the
+/// commitment is to the same note it creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoKeyCommitment(pub Fq);
+
+impl MemoKeyCommitment {
+    /// Commit to `memo_key` for the output creating `note`.
+    pub fn commit(memo_key: &PayloadKey, note: &Note) -> Self {
+        let (lo, hi) = memo_key_limbs(memo_key);
+        Self(poseidon377::hash_3(
+            &MEMO_KEY_COMMITMENT_DOMAIN_SEP,
+            (note.note_blinding(), lo, hi),
+        ))
+    }
+}
+
+/// Split a memo key into two 128-bit field elements, as they are witnessed in
+/// the output circuit.
+pub(crate) fn memo_key_limbs(memo_key: &PayloadKey) -> (Fq, Fq) {
+    let bytes = memo_key.to_vec();
+    (
+        Fq::from_le_bytes_mod_order(&bytes[..16]),
+        Fq::from_le_bytes_mod_order(&bytes[16..]),
+    )
+}
+
+/// The plaintext of a memo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoPlaintext(String);
+
+impl MemoPlaintext {
+    /// Returns an error if the memo is longer than [`MEMO_LEN_BYTES`].
+    pub fn new(text: String) -> anyhow::Result<Self> {
+        if text.len() > MEMO_LEN_BYTES {
+            return Err(anyhow!(
+                "provided memo plaintext of length {} exceeds maximum memo length of {MEMO_LEN_BYTES}",
+                text.len()
+            ));
+        }
+        Ok(Self(text))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Encrypt the memo with `memo_key`.
+    pub fn seal(&self, memo_key: &PayloadKey) -> MemoCiphertext {
+        MemoCiphertext::encrypt(memo_key.clone(), &self.0).expect("memo length is checked")
+    }
+
+    /// Encrypt the memo with `memo_key`, and wrap the memo key for the
+    /// recipient of `note` with the note's shared secret.
+    ///
+    /// Returns the memo ciphertext, the wrapped memo key, and the commitment
+    /// binding the memo key to the note.
+    pub fn seal_for_note(
+        &self,
+        memo_key: &PayloadKey,
+        note: &Note,
+    ) -> (MemoCiphertext, WrappedMemoKey, MemoKeyCommitment) {
+        let wrapped_memo_key = WrappedMemoKey::encrypt(
+            memo_key,
+            note.ephemeral_secret_key(),
+            note.transmission_key(),
+            &note.diversified_generator(),
+        );
+        (
+            self.seal(memo_key),
+            wrapped_memo_key,
+            MemoKeyCommitment::commit(memo_key, note),
+        )
+    }
+
+    /// Decrypt a memo with `memo_key`.
+    pub fn open(memo_key: &PayloadKey, ciphertext: MemoCiphertext) -> anyhow::Result<Self> {
+        MemoCiphertext::decrypt(memo_key, ciphertext).map(Self)
+    }
+
+    /// Unwrap the memo key of the output creating `note` with the note's shared
+    /// secret, and decrypt the memo with it.
+    ///
+    /// If the output has a memo key commitment, the memo key is checked against
+    /// it, so that a memo not meant for this note is rejected.
+    pub fn open_with_shared_secret(
+        shared_secret: &ka::SharedSecret,
+        note: &Note,
+        wrapped_memo_key: &WrappedMemoKey,
+        memo_key_commitment: Option<MemoKeyCommitment>,
+        ciphertext: MemoCiphertext,
+    ) -> anyhow::Result<Self> {
+        let action_key = PayloadKey::derive(shared_secret, &note.ephemeral_public_key());
+        let memo_key = wrapped_memo_key
+            .decrypt_outgoing(&action_key)
+            .map_err(|_| anyhow!("could not decrypt wrapped memo key"))?;
+        if let Some(commitment) = memo_key_commitment {
+            if MemoKeyCommitment::commit(&memo_key, note) != commitment {
+                return Err(anyhow!("memo key does not match its commitment"));
+            }
+        }
+        Self::open(&memo_key, ciphertext)
+    }
+}
+
 impl TryFrom<&[u8]> for MemoCiphertext {
     type Error = anyhow::Error;
 
@@ -216,6 +332,50 @@ mod tests {
         assert_eq!(plaintext, memo);
     }
 
+    #[test]
+    fn test_memo_seal_and_open_for_note() {
+        let mut rng = OsRng;
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(rng), 0);
+        let ivk = sk.full_viewing_key().incoming();
+        let (dest, _dtk_d) = ivk.payment_address(0u64.into());
+        let value = Value {
+            amount: 10u64.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(&mut rng, &dest, value);
+        let other_note = Note::generate(&mut rng, &dest, value);
+
+        let memo = MemoPlaintext::new("Hello, friend".to_string()).unwrap();
+        let memo_key = PayloadKey::random_key(&mut rng);
+        let (ciphertext, wrapped_memo_key, commitment) = memo.seal_for_note(&memo_key, &note);
+
+        // The recipient derives the shared secret while trial-decrypting the note.
+        let shared_secret = ivk
+            .key_agreement_with(&note.ephemeral_public_key())
+            .unwrap();
+        let opened = MemoPlaintext::open_with_shared_secret(
+            &shared_secret,
+            &note,
+            &wrapped_memo_key,
+            Some(commitment),
+            ciphertext.clone(),
+        )
+        .unwrap();
+        assert_eq!(opened, memo);
+
+        // A commitment for another note is rejected.
+        assert!(MemoPlaintext::open_with_shared_secret(
+            &shared_secret,
+            &note,
+            &wrapped_memo_key,
+            Some(MemoKeyCommitment::commit(&memo_key, &other_note)),
+            ciphertext,
+        )
+        .is_err());
+
+        assert!(MemoPlaintext::new("x".repeat(MEMO_LEN_BYTES + 1)).is_err());
+    }
+
     proptest! {
         // We generate random strings, up to 10k chars long.
         // Since UTF-8 represents each char using 1 to 4 bytes,
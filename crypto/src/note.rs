@@ -12,12 +12,17 @@ use thiserror;
 pub use penumbra_tct::Commitment;
 
 use crate::{
-    asset, balance, fmd, ka,
+    asset::{self, FromAmount},
+    balance, fmd, hash, ka,
     keys::{Diversifier, IncomingViewingKey, OutgoingViewingKey},
     symmetric::{OutgoingCipherKey, OvkWrappedKey, PayloadKey, PayloadKind},
     Address, Fq, Rseed, Value,
 };
 
+mod ciphertext;
+
+pub use ciphertext::{NoteCiphertext, NOTE_CIPHERTEXT_VERSION_V0, NOTE_CIPHERTEXT_VERSION_V1};
+
 pub const NOTE_LEN_BYTES: usize = 160;
 pub const NOTE_CIPHERTEXT_BYTES: usize = 176;
 
@@ -56,6 +61,8 @@ pub enum Error {
     NoteDeserializationError,
     #[error("Decryption error")]
     DecryptionError,
+    #[error("Unsupported note ciphertext version {0}")]
+    UnsupportedCiphertextVersion(u8),
 }
 
 impl Note {
@@ -130,23 +137,12 @@ impl Note {
         self.rseed
     }
 
-    /// Encrypt a note, returning its ciphertext.
+    /// Encrypt a note, returning its unversioned ciphertext, which is read as a
+    /// [`NoteCiphertext::V0`].
     pub fn encrypt(&self) -> [u8; NOTE_CIPHERTEXT_BYTES] {
-        let esk = self.ephemeral_secret_key();
-        let epk = esk.diversified_public(&self.diversified_generator());
-        let shared_secret = esk
-            .key_agreement_with(self.transmission_key())
-            .expect("key agreement succeeded");
-
-        let key = PayloadKey::derive(&shared_secret, &epk);
-        let note_plaintext: Vec<u8> = self.into();
-        let encryption_result = key.encrypt(note_plaintext, PayloadKind::Note);
-
-        let ciphertext: [u8; NOTE_CIPHERTEXT_BYTES] = encryption_result
-            .try_into()
-            .expect("note encryption result fits in ciphertext len");
-
-        ciphertext
+        *NoteCiphertext::encrypt(self, NOTE_CIPHERTEXT_VERSION_V0)
+            .expect("version 0 is supported")
+            .ciphertext()
     }
 
     /// Generate encrypted outgoing cipher key for use with this note.
@@ -192,6 +188,8 @@ impl Note {
     }
 
     /// Decrypt a note ciphertext using the wrapped OVK to generate a plaintext `Note`.
+    ///
+    /// The ciphertext may be of any [`NoteCiphertext`] version.
     pub fn decrypt_outgoing(
         ciphertext: &[u8],
         wrapped_ovk: OvkWrappedKey,
@@ -200,36 +198,27 @@ impl Note {
         ovk: &OutgoingViewingKey,
         epk: &ka::Public,
     ) -> Result<Note, Error> {
-        if ciphertext.len() != NOTE_CIPHERTEXT_BYTES {
-            return Err(Error::DecryptionError);
-        }
+        let ciphertext = NoteCiphertext::try_from(ciphertext)?;
 
         let shared_secret =
             Note::decrypt_key(wrapped_ovk, cm, cv, ovk, epk).map_err(|_| Error::DecryptionError)?;
 
-        let key = PayloadKey::derive(&shared_secret, epk);
-        Note::decrypt_with_payload_key(ciphertext, &key, epk)
+        ciphertext.decrypt_with_shared_secret(&shared_secret, epk)
     }
 
     /// Decrypt a note ciphertext using the IVK and ephemeral public key to generate a plaintext `Note`.
+    ///
+    /// The ciphertext may be of any [`NoteCiphertext`] version.
     pub fn decrypt(
         ciphertext: &[u8],
         ivk: &IncomingViewingKey,
         epk: &ka::Public,
     ) -> Result<Note, Error> {
-        if ciphertext.len() != NOTE_CIPHERTEXT_BYTES {
-            return Err(Error::DecryptionError);
-        }
-
-        let shared_secret = ivk
-            .key_agreement_with(epk)
-            .map_err(|_| Error::DecryptionError)?;
-
-        let key = PayloadKey::derive(&shared_secret, epk);
-        Note::decrypt_with_payload_key(ciphertext, &key, epk)
+        NoteCiphertext::try_from(ciphertext)?.decrypt(ivk, epk)
     }
 
-    /// Decrypt a note ciphertext using the [`PayloadKey`].
+    /// Decrypt an unversioned note ciphertext, as produced by [`Note::encrypt`],
+    /// using the [`PayloadKey`].
     pub fn decrypt_with_payload_key(
         ciphertext: &[u8],
         payload_key: &PayloadKey,
@@ -243,6 +232,11 @@ impl Note {
             .decrypt(ciphertext.to_vec(), PayloadKind::Note)
             .map_err(|_| Error::DecryptionError)?;
 
+        Note::from_decrypted(plaintext, epk)
+    }
+
+    /// Parse a decrypted note plaintext, checking that it was encrypted to `epk`.
+    fn from_decrypted(plaintext: Vec<u8>, epk: &ka::Public) -> Result<Note, Error> {
         let plaintext_bytes: [u8; NOTE_LEN_BYTES] =
             plaintext.try_into().map_err(|_| Error::DecryptionError)?;
 
@@ -282,17 +276,14 @@ pub fn commitment(
     transmission_key_s: Fq,
     clue_key: &fmd::ClueKey,
 ) -> Commitment {
-    let commit = poseidon377::hash_6(
-        &NOTECOMMIT_DOMAIN_SEP,
-        (
-            note_blinding,
-            value.amount.into(),
-            value.asset_id.0,
-            diversified_generator.vartime_compress_to_field(),
-            transmission_key_s,
-            Fq::from_le_bytes_mod_order(&clue_key.0[..]),
-        ),
-    );
+    let commit = hash::NOTE_COMMITMENT.hash([
+        note_blinding,
+        Fq::from_amount(value.amount),
+        value.asset_id.0,
+        diversified_generator.vartime_compress_to_field(),
+        transmission_key_s,
+        Fq::from_le_bytes_mod_order(&clue_key.0[..]),
+    ]);
 
     Commitment(commit)
 }
@@ -305,17 +296,14 @@ pub fn commitment_from_address(
 ) -> Result<Commitment, Error> {
     let transmission_key_s =
         Fq::from_bytes(address.transmission_key().0).map_err(|_| Error::InvalidTransmissionKey)?;
-    let commit = poseidon377::hash_6(
-        &NOTECOMMIT_DOMAIN_SEP,
-        (
-            note_blinding,
-            value.amount.into(),
-            value.asset_id.0,
-            address.diversified_generator().vartime_compress_to_field(),
-            transmission_key_s,
-            Fq::from_le_bytes_mod_order(&address.clue_key().0[..]),
-        ),
-    );
+    let commit = hash::NOTE_COMMITMENT.hash([
+        note_blinding,
+        Fq::from_amount(value.amount),
+        value.asset_id.0,
+        address.diversified_generator().vartime_compress_to_field(),
+        transmission_key_s,
+        Fq::from_le_bytes_mod_order(&address.clue_key().0[..]),
+    ]);
 
     Ok(Commitment(commit))
 }
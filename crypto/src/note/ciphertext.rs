@@ -0,0 +1,271 @@
+use std::convert::{TryFrom, TryInto};
+
+use super::{Error, Note, NOTE_CIPHERTEXT_BYTES};
+use crate::{
+    ka,
+    keys::IncomingViewingKey,
+    symmetric::{PayloadKey, PayloadKind},
+};
+
+/// The version of the note encryption scheme used by [`Note::encrypt`].
+pub const NOTE_CIPHERTEXT_VERSION_V0: u8 = 0;
+
+/// The version of the note encryption scheme that authenticates its version
+/// and ephemeral key as associated data.
+pub const NOTE_CIPHERTEXT_VERSION_V1: u8 = 1;
+
+/// An encrypted note, tagged with the version of the scheme that encrypted it.
+///
+/// Every version encrypts the same [`NOTE_LEN_BYTES`](super::NOTE_LEN_BYTES)
+/// of note plaintext, so proofs about a note, which only ever see its
+/// commitment, don't depend on how it was encrypted.
+///
+/// A ciphertext is encoded as its version byte followed by the ciphertext
+/// itself. Notes encrypted before ciphertexts were versioned have no version
+/// byte, so an encoding of exactly [`NOTE_CIPHERTEXT_BYTES`] is read as
+/// [`NoteCiphertext::V0`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NoteCiphertext {
+    /// ChaCha20-Poly1305 under a key derived with [`PayloadKey::derive`], with
+    /// no associated data.
+    V0([u8; NOTE_CIPHERTEXT_BYTES]),
+    /// ChaCha20-Poly1305 under a key derived with [`PayloadKey::derive_v1`],
+    /// with the version byte and the ephemeral public key as associated data.
+    V1([u8; NOTE_CIPHERTEXT_BYTES]),
+}
+
+impl NoteCiphertext {
+    /// The length of an encoding with a version byte.
+    pub const ENCODED_LEN: usize = 1 + NOTE_CIPHERTEXT_BYTES;
+
+    /// Encrypt `note` with the given version of the encryption scheme.
+    pub fn encrypt(note: &Note, version: u8) -> Result<Self, Error> {
+        let esk = note.ephemeral_secret_key();
+        let epk = esk.diversified_public(&note.diversified_generator());
+        let shared_secret = esk
+            .key_agreement_with(note.transmission_key())
+            .expect("key agreement succeeded");
+        let plaintext: Vec<u8> = note.into();
+
+        let ciphertext = match version {
+            NOTE_CIPHERTEXT_VERSION_V0 => {
+                PayloadKey::derive(&shared_secret, &epk).encrypt(plaintext, PayloadKind::Note)
+            }
+            NOTE_CIPHERTEXT_VERSION_V1 => PayloadKey::derive_v1(&shared_secret, &epk)
+                .encrypt_with_associated_data(
+                    &plaintext,
+                    PayloadKind::Note,
+                    &associated_data(version, &epk),
+                ),
+            version => return Err(Error::UnsupportedCiphertextVersion(version)),
+        };
+        let ciphertext = ciphertext
+            .try_into()
+            .expect("note encryption result fits in ciphertext len");
+
+        Self::from_parts(version, ciphertext)
+    }
+
+    /// The ciphertext encrypted with the given version of the scheme.
+    pub fn from_parts(version: u8, ciphertext: [u8; NOTE_CIPHERTEXT_BYTES]) -> Result<Self, Error> {
+        match version {
+            NOTE_CIPHERTEXT_VERSION_V0 => Ok(Self::V0(ciphertext)),
+            NOTE_CIPHERTEXT_VERSION_V1 => Ok(Self::V1(ciphertext)),
+            version => Err(Error::UnsupportedCiphertextVersion(version)),
+        }
+    }
+
+    /// The version of the scheme this ciphertext was encrypted with.
+    pub fn version(&self) -> u8 {
+        match self {
+            Self::V0(_) => NOTE_CIPHERTEXT_VERSION_V0,
+            Self::V1(_) => NOTE_CIPHERTEXT_VERSION_V1,
+        }
+    }
+
+    /// The ciphertext, without its version.
+    pub fn ciphertext(&self) -> &[u8; NOTE_CIPHERTEXT_BYTES] {
+        match self {
+            Self::V0(ciphertext) | Self::V1(ciphertext) => ciphertext,
+        }
+    }
+
+    /// Encode this ciphertext with its version byte.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0] = self.version();
+        bytes[1..].copy_from_slice(self.ciphertext());
+        bytes
+    }
+
+    /// Decrypt this ciphertext using the IVK and ephemeral public key.
+    pub fn decrypt(&self, ivk: &IncomingViewingKey, epk: &ka::Public) -> Result<Note, Error> {
+        let shared_secret = ivk
+            .key_agreement_with(epk)
+            .map_err(|_| Error::DecryptionError)?;
+        self.decrypt_with_shared_secret(&shared_secret, epk)
+    }
+
+    /// Decrypt this ciphertext using the shared secret of its ephemeral key,
+    /// deriving the payload key according to its version.
+    pub fn decrypt_with_shared_secret(
+        &self,
+        shared_secret: &ka::SharedSecret,
+        epk: &ka::Public,
+    ) -> Result<Note, Error> {
+        let plaintext = match self {
+            Self::V0(ciphertext) => PayloadKey::derive(shared_secret, epk)
+                .decrypt(ciphertext.to_vec(), PayloadKind::Note),
+            Self::V1(ciphertext) => PayloadKey::derive_v1(shared_secret, epk)
+                .decrypt_with_associated_data(
+                    ciphertext,
+                    PayloadKind::Note,
+                    &associated_data(self.version(), epk),
+                ),
+        }
+        .map_err(|_| Error::DecryptionError)?;
+
+        Note::from_decrypted(plaintext, epk)
+    }
+}
+
+/// The associated data authenticated by [`NoteCiphertext::V1`].
+fn associated_data(version: u8, epk: &ka::Public) -> [u8; 33] {
+    let mut associated_data = [0u8; 33];
+    associated_data[0] = version;
+    associated_data[1..].copy_from_slice(&epk.0);
+    associated_data
+}
+
+impl std::fmt::Debug for NoteCiphertext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoteCiphertext")
+            .field("version", &self.version())
+            .field("ciphertext", &hex::encode(self.ciphertext()))
+            .finish()
+    }
+}
+
+impl TryFrom<&[u8]> for NoteCiphertext {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        match bytes.len() {
+            NOTE_CIPHERTEXT_BYTES => Ok(Self::V0(bytes.try_into().expect("length was checked"))),
+            Self::ENCODED_LEN => {
+                Self::from_parts(bytes[0], bytes[1..].try_into().expect("length was checked"))
+            }
+            _ => Err(Error::DecryptionError),
+        }
+    }
+}
+
+impl From<[u8; NOTE_CIPHERTEXT_BYTES]> for NoteCiphertext {
+    fn from(ciphertext: [u8; NOTE_CIPHERTEXT_BYTES]) -> Self {
+        Self::V0(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use decaf377::Fr;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        asset,
+        keys::{SeedPhrase, SpendKey},
+        proofs::groth16::{OutputCircuit, OutputProofPlan, ParameterSetup},
+        FieldExt, Value,
+    };
+
+    fn note_and_ivk() -> (Note, IncomingViewingKey) {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let ivk = sk.full_viewing_key().incoming().clone();
+        let (dest, _dtk_d) = ivk.payment_address(0u64.into());
+
+        let value = Value {
+            amount: 10u64.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        (Note::generate(&mut OsRng, &dest, value), ivk)
+    }
+
+    #[test]
+    fn decryption_dispatches_on_version() {
+        let (note, ivk) = note_and_ivk();
+        let epk = note.ephemeral_public_key();
+
+        let v0 = NoteCiphertext::encrypt(&note, NOTE_CIPHERTEXT_VERSION_V0).unwrap();
+        let v1 = NoteCiphertext::encrypt(&note, NOTE_CIPHERTEXT_VERSION_V1).unwrap();
+        assert_eq!(v0.ciphertext(), &note.encrypt());
+        assert_ne!(v0.ciphertext(), v1.ciphertext());
+
+        for ciphertext in [v0, v1] {
+            assert_eq!(ciphertext.decrypt(&ivk, &epk).unwrap(), note);
+
+            // Both encodings parse, and `Note::decrypt` dispatches on the version.
+            let encoded = ciphertext.to_bytes();
+            assert_eq!(NoteCiphertext::try_from(&encoded[..]).unwrap(), ciphertext);
+            assert_eq!(Note::decrypt(&encoded, &ivk, &epk).unwrap(), note);
+        }
+
+        // Unversioned ciphertexts are V0.
+        assert_eq!(Note::decrypt(&note.encrypt(), &ivk, &epk).unwrap(), note);
+
+        // A ciphertext only decrypts under the version it was encrypted with.
+        let relabeled = NoteCiphertext::V0(*v1.ciphertext());
+        assert!(relabeled.decrypt(&ivk, &epk).is_err());
+
+        let mut unknown = v1.to_bytes();
+        unknown[0] = 2;
+        assert!(matches!(
+            NoteCiphertext::try_from(&unknown[..]),
+            Err(Error::UnsupportedCiphertextVersion(2))
+        ));
+        assert!(matches!(
+            NoteCiphertext::encrypt(&note, 2),
+            Err(Error::UnsupportedCiphertextVersion(2))
+        ));
+    }
+
+    #[test]
+    fn v1_authenticates_ephemeral_key() {
+        let (note, ivk) = note_and_ivk();
+        let v1 = NoteCiphertext::encrypt(&note, NOTE_CIPHERTEXT_VERSION_V1).unwrap();
+
+        let wrong_esk = ka::Secret::new(&mut OsRng);
+        let wrong_epk = wrong_esk.diversified_public(&note.diversified_generator());
+        assert!(v1.decrypt(&ivk, &wrong_epk).is_err());
+    }
+
+    #[test]
+    fn output_proof_is_agnostic_to_ciphertext_version() {
+        let (note, ivk) = note_and_ivk();
+        let epk = note.ephemeral_public_key();
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+
+        let v_blinding = Fr::rand_uniform(&mut OsRng);
+        let balance_commitment = note.value().commit(v_blinding);
+        let proof = OutputProofPlan {
+            note_commitment: note.commit(),
+            note: note.clone(),
+            v_blinding,
+            balance_commitment,
+        }
+        .prove(&mut OsRng, &pk)
+        .unwrap();
+
+        // The proof is about the note commitment alone, so it verifies against
+        // the note recovered from a ciphertext of any version.
+        for version in [NOTE_CIPHERTEXT_VERSION_V0, NOTE_CIPHERTEXT_VERSION_V1] {
+            let decrypted = NoteCiphertext::encrypt(&note, version)
+                .unwrap()
+                .decrypt(&ivk, &epk)
+                .unwrap();
+            assert!(proof
+                .verify(&vk, balance_commitment, decrypted.commit())
+                .is_ok());
+        }
+    }
+}
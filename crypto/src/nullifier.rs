@@ -8,6 +8,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::Fq;
 
+mod set;
+
+pub use set::{AlreadySpent, NullifierSet, Retention};
+
 #[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(try_from = "pb::Nullifier", into = "pb::Nullifier")]
 pub struct Nullifier(pub Fq);
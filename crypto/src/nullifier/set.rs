@@ -0,0 +1,277 @@
+use std::collections::{btree_map, BTreeMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::Nullifier;
+
+/// How long a [`NullifierSet`] must remember a nullifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Retention {
+    /// The nullifier is kept forever, as for notes, which can be spent at any
+    /// time after they are created.
+    Forever,
+    /// The nullifier is kept through the end of the epoch with this index, and
+    /// may be pruned afterwards.
+    ///
+    /// This is for nullifiers of things that can only be claimed until then,
+    /// so that no later transaction can reveal the same nullifier.
+    UntilEpoch(u64),
+}
+
+/// The error returned when inserting a nullifier that was already spent.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("nullifier {0} was already spent")]
+pub struct AlreadySpent(pub Nullifier);
+
+/// A set of spent nullifiers, used to reject double spends.
+///
+/// Nullifiers are kept sorted, and a Bloom filter over them answers most
+/// lookups of unspent nullifiers, which are the common case when checking a
+/// transaction, without searching the set. Nullifiers inserted with
+/// [`Retention::UntilEpoch`] are indexed by epoch, so that they can be pruned
+/// in bulk with [`NullifierSet::prune`] once they can no longer be claimed.
+///
+/// The set serializes as its sorted list of nullifiers and their retention;
+/// the filter is rebuilt when it is deserialized.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "Entries", into = "Entries")]
+pub struct NullifierSet {
+    spent: BTreeMap<Nullifier, Retention>,
+    expiring: BTreeMap<u64, Vec<Nullifier>>,
+    filter: BloomFilter,
+}
+
+impl NullifierSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of nullifiers in the set.
+    pub fn len(&self) -> usize {
+        self.spent.len()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.spent.is_empty()
+    }
+
+    /// Whether `nullifier` has been spent.
+    pub fn contains(&self, nullifier: &Nullifier) -> bool {
+        self.filter.may_contain(nullifier) && self.spent.contains_key(nullifier)
+    }
+
+    /// Check that `nullifier` has not been spent.
+    pub fn check(&self, nullifier: &Nullifier) -> Result<(), AlreadySpent> {
+        if self.contains(nullifier) {
+            return Err(AlreadySpent(*nullifier));
+        }
+        Ok(())
+    }
+
+    /// Record `nullifier` as spent, to be remembered according to `retention`.
+    ///
+    /// Returns an error, leaving the set unchanged, if it was already spent.
+    pub fn insert(
+        &mut self,
+        nullifier: Nullifier,
+        retention: Retention,
+    ) -> Result<(), AlreadySpent> {
+        match self.spent.entry(nullifier) {
+            btree_map::Entry::Occupied(_) => return Err(AlreadySpent(nullifier)),
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(retention);
+            }
+        }
+        if let Retention::UntilEpoch(epoch) = retention {
+            self.expiring.entry(epoch).or_default().push(nullifier);
+        }
+
+        if self.spent.len() > self.filter.capacity() {
+            self.rebuild_filter();
+        } else {
+            self.filter.insert(&nullifier);
+        }
+        Ok(())
+    }
+
+    /// Remove every nullifier retained only until an epoch before
+    /// `current_epoch`, returning how many were removed.
+    pub fn prune(&mut self, current_epoch: u64) -> usize {
+        let retained = self.expiring.split_off(&current_epoch);
+        let expired = std::mem::replace(&mut self.expiring, retained);
+
+        let mut removed = 0;
+        for nullifier in expired.into_values().flatten() {
+            self.spent.remove(&nullifier);
+            removed += 1;
+        }
+        // Bloom filters don't support removal, so the pruned nullifiers are
+        // only dropped from the filter by rebuilding it.
+        if removed > 0 {
+            self.rebuild_filter();
+        }
+        removed
+    }
+
+    /// The spent nullifiers, in order, with their retention.
+    pub fn iter(&self) -> impl Iterator<Item = (&Nullifier, &Retention)> {
+        self.spent.iter()
+    }
+
+    fn rebuild_filter(&mut self) {
+        self.filter = BloomFilter::with_capacity(self.spent.len());
+        for nullifier in self.spent.keys() {
+            self.filter.insert(nullifier);
+        }
+    }
+}
+
+impl PartialEq for NullifierSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.spent == other.spent
+    }
+}
+
+impl Eq for NullifierSet {}
+
+#[derive(Serialize, Deserialize)]
+struct Entries(Vec<(Nullifier, Retention)>);
+
+impl From<NullifierSet> for Entries {
+    fn from(set: NullifierSet) -> Self {
+        Entries(set.spent.into_iter().collect())
+    }
+}
+
+impl From<Entries> for NullifierSet {
+    fn from(entries: Entries) -> Self {
+        let mut set = NullifierSet::new();
+        for (nullifier, retention) in entries.0 {
+            // A serialized set has no duplicates, but if it did, keeping the
+            // first entry is as good as any.
+            let _ = set.insert(nullifier, retention);
+        }
+        set
+    }
+}
+
+/// The number of filter bits per nullifier the filter is sized for, giving a
+/// false positive rate of about 0.2% with [`BLOOM_HASHES`] hashes.
+const BLOOM_BITS_PER_ITEM: usize = 16;
+
+/// The number of bits set in the filter for each nullifier.
+const BLOOM_HASHES: usize = 4;
+
+/// The smallest number of nullifiers the filter is sized for.
+const BLOOM_MIN_CAPACITY: usize = 1024;
+
+/// A Bloom filter over nullifiers.
+///
+/// Nullifiers are outputs of a hash function, so the filter indexes its bits
+/// directly with 64-bit words of their encoding, rather than hashing them
+/// again.
+#[derive(Clone, Debug)]
+struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl Default for BloomFilter {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl BloomFilter {
+    /// A filter sized for at least `capacity` nullifiers, and room to grow.
+    fn with_capacity(capacity: usize) -> Self {
+        let capacity = (capacity * 2).max(BLOOM_MIN_CAPACITY);
+        Self {
+            bits: vec![0; capacity * BLOOM_BITS_PER_ITEM / 64],
+        }
+    }
+
+    /// The number of nullifiers the filter is sized for.
+    fn capacity(&self) -> usize {
+        self.bits.len() * 64 / BLOOM_BITS_PER_ITEM
+    }
+
+    fn positions(&self, nullifier: &Nullifier) -> impl Iterator<Item = usize> {
+        let bytes = nullifier.to_bytes();
+        let num_bits = (self.bits.len() * 64) as u64;
+        (0..BLOOM_HASHES).map(move |i| {
+            let word = u64::from_le_bytes(bytes[8 * i..8 * (i + 1)].try_into().unwrap());
+            (word % num_bits) as usize
+        })
+    }
+
+    fn insert(&mut self, nullifier: &Nullifier) {
+        for position in self.positions(nullifier) {
+            self.bits[position / 64] |= 1u64 << (position % 64);
+        }
+    }
+
+    fn may_contain(&self, nullifier: &Nullifier) -> bool {
+        self.positions(nullifier)
+            .all(|position| self.bits[position / 64] & (1u64 << (position % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::PrimeField;
+
+    use super::*;
+    use crate::Fq;
+
+    fn nullifier(i: u64) -> Nullifier {
+        // Spread the nullifiers over the filter, as real ones are.
+        Nullifier(Fq::from_le_bytes_mod_order(
+            blake2b_simd::blake2b(&i.to_le_bytes()).as_bytes(),
+        ))
+    }
+
+    #[test]
+    fn insert_contains_and_prune() {
+        let mut set = NullifierSet::new();
+        for i in 0..3000 {
+            let retention = match i % 3 {
+                0 => Retention::Forever,
+                1 => Retention::UntilEpoch(1),
+                _ => Retention::UntilEpoch(2),
+            };
+            set.insert(nullifier(i), retention).unwrap();
+        }
+        assert_eq!(set.len(), 3000);
+        assert_eq!(
+            set.insert(nullifier(7), Retention::Forever),
+            Err(AlreadySpent(nullifier(7)))
+        );
+        assert!((0..3000).all(|i| set.contains(&nullifier(i))));
+        assert!((3000..4000).all(|i| set.check(&nullifier(i)).is_ok()));
+
+        // Nothing expires until the epoch after the one it is retained for.
+        assert_eq!(set.prune(1), 0);
+        assert_eq!(set.prune(2), 1000);
+        assert!(!set.contains(&nullifier(1)));
+        assert!(set.contains(&nullifier(2)));
+        assert_eq!(set.prune(10), 1000);
+        assert_eq!(set.len(), 1000);
+        assert!((0..3000).all(|i| set.contains(&nullifier(i)) == (i % 3 == 0)));
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let mut set = NullifierSet::new();
+        set.insert(nullifier(1), Retention::Forever).unwrap();
+        set.insert(nullifier(2), Retention::UntilEpoch(5)).unwrap();
+
+        let json = serde_json::to_string(&set).unwrap();
+        let mut parsed: NullifierSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, set);
+        assert!(parsed.contains(&nullifier(1)));
+        assert_eq!(parsed.prune(6), 1);
+        assert!(!parsed.contains(&nullifier(2)));
+    }
+}
@@ -0,0 +1,88 @@
+//! Canonical ordering of note commitments and nullifiers within a block.
+//!
+//! Block construction and block verification must agree on the order in which
+//! the commitments and nullifiers of a block are processed. The canonical order
+//! is ascending by the field element each one wraps, compared as integers, with
+//! no duplicates.
+
+use std::cmp::Ordering;
+
+use crate::{note, Nullifier};
+
+/// An item with a canonical ordering within a block.
+pub trait CanonicalOrder {
+    /// Compare two items according to the canonical ordering.
+    fn canonical_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl CanonicalOrder for note::Commitment {
+    fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl CanonicalOrder for Nullifier {
+    fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+/// An error indicating that a sequence is not in canonical order.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderingError {
+    #[error("item at index {index} is a duplicate of the previous item")]
+    Duplicate { index: usize },
+    #[error("item at index {index} is ordered before the previous item")]
+    OutOfOrder { index: usize },
+}
+
+/// Sort the items into canonical order, removing any duplicates.
+pub fn canonicalize<T: CanonicalOrder>(items: &mut Vec<T>) {
+    items.sort_unstable_by(CanonicalOrder::canonical_cmp);
+    items.dedup_by(|a, b| a.canonical_cmp(b) == Ordering::Equal);
+}
+
+/// Check that the items are in canonical order, with no duplicates.
+pub fn check_canonical_order<T: CanonicalOrder>(items: &[T]) -> Result<(), OrderingError> {
+    for (index, pair) in items.windows(2).enumerate() {
+        match pair[0].canonical_cmp(&pair[1]) {
+            Ordering::Less => {}
+            Ordering::Equal => return Err(OrderingError::Duplicate { index: index + 1 }),
+            Ordering::Greater => return Err(OrderingError::OutOfOrder { index: index + 1 }),
+        }
+    }
+    Ok(())
+}
+
+/// Check whether the items are in canonical order, with no duplicates.
+pub fn is_canonically_ordered<T: CanonicalOrder>(items: &[T]) -> bool {
+    check_canonical_order(items).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fq;
+
+    #[test]
+    fn canonicalize_sorts_and_dedups() {
+        let mut commitments: Vec<note::Commitment> = [5u64, 1, 3, 1, 4]
+            .iter()
+            .map(|&i| note::Commitment(Fq::from(i)))
+            .collect();
+        assert_eq!(
+            check_canonical_order(&commitments),
+            Err(OrderingError::OutOfOrder { index: 1 })
+        );
+
+        canonicalize(&mut commitments);
+        assert!(is_canonically_ordered(&commitments));
+        assert_eq!(commitments.len(), 4);
+
+        let nullifiers = [Nullifier(Fq::from(2u64)), Nullifier(Fq::from(2u64))];
+        assert_eq!(
+            check_canonical_order(&nullifiers),
+            Err(OrderingError::Duplicate { index: 1 })
+        );
+    }
+}
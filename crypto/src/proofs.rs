@@ -1,3 +1,13 @@
+#[cfg(feature = "std")]
+pub mod bench;
+mod dual;
 pub mod groth16;
+mod rng;
 pub mod transparent;
 mod transparent_gadgets;
+
+pub use dual::{
+    DisallowedProof, OutputProof, Proof, ProofPolicy, ProofSystem, SpendProof, SwapClaimProof,
+    SwapProof, UndelegateClaimProof,
+};
+pub use rng::ProofRng;
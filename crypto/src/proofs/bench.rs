@@ -0,0 +1,371 @@
+//! A self-test of the proving system, for sanity-checking parameters and hardware.
+//!
+//! [`self_test`] creates and verifies one proof for each circuit, and reports how long each step
+//! took and whether it succeeded. Operators can run it before serving traffic to check that
+//! proving is fast enough on their hardware, and [`self_test_with`] runs the same checks against
+//! deployed parameters instead of embedded test parameters.
+
+use std::time::{Duration, Instant};
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use decaf377::{Bls12_377, Fr};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_tct as tct;
+use rand_core::OsRng;
+use serde::Serialize;
+
+use crate::{
+    asset,
+    dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
+    keys::{RandomizedVerificationKey, SeedPhrase, SpendAuthRandomizer, SpendKey},
+    proofs::groth16::{
+        swap_balance_commitment, OutputCircuit, OutputProofPlan, ParameterSetup, ProverOptions,
+        SpendCircuit, SpendProofPlan, SwapCircuit, SwapClaimCircuit, SwapClaimProof, SwapProof,
+    },
+    sample::SampleUniform,
+    transaction::Fee,
+    Address, Note, Value, STAKING_TOKEN_ASSET_ID,
+};
+
+/// A proving key and its verifying key, for a single circuit.
+pub type CircuitParameters<'a> = (&'a ProvingKey<Bls12_377>, &'a VerifyingKey<Bls12_377>);
+
+/// The outcome of the self-test for a single circuit.
+#[derive(Clone, Debug, Serialize)]
+pub struct CircuitReport {
+    /// The name of the circuit.
+    pub circuit: &'static str,
+    /// How long generating the test parameters took, if they were generated.
+    pub setup: Option<Duration>,
+    /// How long creating the proof took.
+    pub prove: Duration,
+    /// How long verifying the proof took.
+    pub verify: Duration,
+    /// The reason the circuit failed the self-test, if it did.
+    pub error: Option<String>,
+}
+
+impl CircuitReport {
+    /// Whether the proof was created and verified successfully.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The outcome of the self-test for every circuit.
+#[derive(Clone, Debug, Serialize)]
+pub struct SelfTestReport {
+    pub circuits: Vec<CircuitReport>,
+}
+
+impl SelfTestReport {
+    /// Whether every circuit passed the self-test.
+    pub fn passed(&self) -> bool {
+        self.circuits.iter().all(CircuitReport::passed)
+    }
+}
+
+impl std::fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for report in &self.circuits {
+            write!(
+                f,
+                "{:<10} prove {:>10.3?}  verify {:>10.3?}",
+                report.circuit, report.prove, report.verify
+            )?;
+            if let Some(setup) = report.setup {
+                write!(f, "  setup {:>10.3?}", setup)?;
+            }
+            match &report.error {
+                None => writeln!(f, "  ok")?,
+                Some(error) => writeln!(f, "  FAILED: {}", error)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the self-test with freshly generated test parameters for each circuit.
+pub fn self_test() -> SelfTestReport {
+    let start = Instant::now();
+    let (spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+    let spend_setup = start.elapsed();
+
+    let start = Instant::now();
+    let (output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+    let output_setup = start.elapsed();
+
+    let start = Instant::now();
+    let (swap_pk, swap_vk) = SwapCircuit::generate_test_parameters();
+    let swap_setup = start.elapsed();
+
+    let start = Instant::now();
+    let (swap_claim_pk, swap_claim_vk) = SwapClaimCircuit::generate_test_parameters();
+    let swap_claim_setup = start.elapsed();
+
+    let mut report = self_test_with(
+        (&spend_pk, &spend_vk),
+        (&output_pk, &output_vk),
+        (&swap_pk, &swap_vk),
+        (&swap_claim_pk, &swap_claim_vk),
+    );
+    report.circuits[0].setup = Some(spend_setup);
+    report.circuits[1].setup = Some(output_setup);
+    report.circuits[2].setup = Some(swap_setup);
+    report.circuits[3].setup = Some(swap_claim_setup);
+    report
+}
+
+/// Run the self-test with the given parameters for each circuit, e.g. ones loaded from the
+/// parameter files used in production.
+pub fn self_test_with(
+    spend: CircuitParameters,
+    output: CircuitParameters,
+    swap: CircuitParameters,
+    swap_claim: CircuitParameters,
+) -> SelfTestReport {
+    SelfTestReport {
+        circuits: vec![
+            spend_report(spend),
+            output_report(output),
+            swap_report(swap),
+            swap_claim_report(swap_claim),
+        ],
+    }
+}
+
+/// Time `prove` and then `verify`, recording the first error encountered.
+fn timed<P>(
+    circuit: &'static str,
+    prove: impl FnOnce() -> anyhow::Result<P>,
+    verify: impl FnOnce(P) -> anyhow::Result<()>,
+) -> CircuitReport {
+    let start = Instant::now();
+    let proof = prove();
+    let prove_time = start.elapsed();
+
+    let (verify_time, result) = match proof {
+        Ok(proof) => {
+            let start = Instant::now();
+            let result = verify(proof);
+            (start.elapsed(), result)
+        }
+        Err(error) => (Duration::ZERO, Err(error.context("failed to create proof"))),
+    };
+
+    CircuitReport {
+        circuit,
+        setup: None,
+        prove: prove_time,
+        verify: verify_time,
+        error: result.err().map(|error| format!("{:#}", error)),
+    }
+}
+
+fn test_value() -> Value {
+    Value {
+        amount: 1u64.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    }
+}
+
+fn test_trading_pair() -> TradingPair {
+    let other_asset = asset::REGISTRY
+        .parse_denom("nala")
+        .expect("nala is a valid denom")
+        .id();
+    TradingPair::new(*STAKING_TOKEN_ASSET_ID, other_asset)
+}
+
+fn spend_report((pk, vk): CircuitParameters) -> CircuitReport {
+    let mut rng = OsRng;
+
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut rng), 0);
+    let (address, _dtk) = sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let note = Note::generate(&mut rng, &address, test_value());
+    let note_commitment = note.commit();
+
+    let mut nct = tct::Tree::new();
+    nct.insert(tct::Witness::Keep, note_commitment)
+        .expect("can insert into empty tree");
+    let anchor = nct.root();
+    let note_commitment_proof = nct
+        .witness(note_commitment)
+        .expect("commitment was witnessed");
+
+    let v_blinding = Fr::rand_uniform(&mut rng);
+    let balance_commitment = test_value().commit(v_blinding);
+    let ak: VerificationKey<SpendAuth> = sk.spend_auth_key().into();
+    let spend_auth_randomizer = SpendAuthRandomizer::generate(&mut rng);
+    let rk =
+        RandomizedVerificationKey::try_from(spend_auth_randomizer.randomize_verification_key(&ak))
+            .expect("a randomized spend auth key is a valid element");
+    let nk = *sk.nullifier_key();
+    let nullifier = nk.derive_nullifier(0.into(), &note_commitment);
+
+    timed(
+        "spend",
+        || {
+            SpendProofPlan {
+                note_commitment_proof,
+                note,
+                v_blinding,
+                spend_auth_randomizer,
+                ak,
+                nk,
+                anchor,
+                balance_commitment,
+                nullifier,
+                rk,
+            }
+            .prove(&mut rng, pk)
+        },
+        |proof| proof.verify(vk, anchor, balance_commitment, nullifier, rk),
+    )
+}
+
+fn output_report((pk, vk): CircuitParameters) -> CircuitReport {
+    let mut rng = OsRng;
+
+    let address = Address::dummy(&mut rng);
+    let note = Note::generate(&mut rng, &address, test_value());
+    let note_commitment = note.commit();
+    let v_blinding = Fr::rand_uniform(&mut rng);
+    let balance_commitment = test_value().commit(v_blinding);
+
+    timed(
+        "output",
+        || {
+            OutputProofPlan {
+                note,
+                v_blinding,
+                balance_commitment,
+                note_commitment,
+            }
+            .prove(&mut rng, pk)
+        },
+        |proof| proof.verify(vk, balance_commitment, note_commitment),
+    )
+}
+
+fn swap_report((pk, vk): CircuitParameters) -> CircuitReport {
+    let mut rng = OsRng;
+
+    let claim_address = Address::dummy(&mut rng);
+    let trading_pair = test_trading_pair();
+    let swap_plaintext = SwapPlaintext::new(
+        &mut rng,
+        trading_pair,
+        1u64.into(),
+        0u64.into(),
+        Fee::default(),
+        claim_address,
+    );
+    let fee_blinding = Fr::rand_uniform(&mut rng);
+    let fee_commitment = swap_plaintext.claim_fee.commit(fee_blinding);
+    let swap_commitment = swap_plaintext.swap_commitment();
+    let balance_commitment = swap_balance_commitment(&swap_plaintext, fee_commitment);
+
+    timed(
+        "swap",
+        || {
+            SwapProof::prove(
+                &ProverOptions::default(),
+                &mut rng,
+                pk,
+                swap_plaintext,
+                fee_blinding,
+                balance_commitment,
+                swap_commitment,
+                fee_commitment,
+            )
+        },
+        |proof| proof.verify(vk, balance_commitment, swap_commitment, fee_commitment),
+    )
+}
+
+fn swap_claim_report((pk, vk): CircuitParameters) -> CircuitReport {
+    let mut rng = OsRng;
+
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut rng), 0);
+    let (claim_address, _dtk) = sk
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into());
+    let swap_plaintext = SwapPlaintext::new(
+        &mut rng,
+        test_trading_pair(),
+        1u64.into(),
+        0u64.into(),
+        Fee::default(),
+        claim_address,
+    );
+
+    let mut sct = tct::Tree::new();
+    let swap_commitment = swap_plaintext.swap_commitment();
+    sct.insert(tct::Witness::Keep, swap_commitment)
+        .expect("can insert into empty tree");
+    let anchor = sct.root();
+    let swap_commitment_proof = sct
+        .witness(swap_commitment)
+        .expect("commitment was witnessed");
+    let nk = *sk.nullifier_key();
+    let nullifier = nk.derive_nullifier(swap_commitment_proof.position(), &swap_commitment);
+
+    let output_data = BatchSwapOutputData {
+        delta_1: 1,
+        delta_2: 0,
+        lambda_1: 0,
+        lambda_2: 1,
+        height: 0,
+        trading_pair: swap_plaintext.trading_pair,
+        success: true,
+    };
+    let epoch_duration = 1;
+    let (output_1, output_2) = swap_plaintext.output_notes(&output_data);
+    let fee = swap_plaintext.claim_fee.clone();
+
+    timed(
+        "swap_claim",
+        || {
+            SwapClaimProof::prove(
+                &ProverOptions::default(),
+                &mut rng,
+                pk,
+                swap_plaintext,
+                swap_commitment_proof,
+                nk,
+                anchor,
+                output_data,
+                epoch_duration,
+            )
+        },
+        |proof| {
+            proof.verify(
+                vk,
+                anchor,
+                nullifier,
+                fee,
+                output_data,
+                epoch_duration,
+                output_1.commit(),
+                output_2.commit(),
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        let report = self_test();
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.circuits.len(), 4);
+    }
+}
@@ -0,0 +1,341 @@
+//! Verification of actions proven with either the transparent proofs or
+//! Groth16 proofs, for the migration from the former to the latter.
+//!
+//! Each action's proof is a [`Proof`], which is verified with whichever proof
+//! system produced it, subject to a [`ProofPolicy`]. The policy in force is
+//! derived from the chain parameters with [`ProofPolicy::at_epoch`], so that
+//! Groth16 proofs can be required from an epoch boundary onwards by changing
+//! the parameters alone.
+
+use ark_groth16::VerifyingKey;
+use decaf377::Bls12_377;
+use penumbra_tct as tct;
+use serde::{Deserialize, Serialize};
+
+use super::{groth16, transparent};
+use crate::{
+    asset, balance, dex::BatchSwapOutputData, keys::RandomizedVerificationKey, note,
+    stake::Penalty, transaction::Fee, Nullifier,
+};
+
+/// A proof system that an action may be proven with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProofSystem {
+    /// The transparent proofs, which reveal their witnesses.
+    Transparent,
+    /// Groth16 proofs over BLS12-377.
+    Groth16,
+}
+
+impl std::fmt::Display for ProofSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProofSystem::Transparent => "transparent",
+            ProofSystem::Groth16 => "groth16",
+        })
+    }
+}
+
+/// Which proof systems verifiers accept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofPolicy {
+    /// Accept both transparent and Groth16 proofs, as during the migration.
+    AllowBoth,
+    /// Accept only Groth16 proofs.
+    RequireGroth16,
+}
+
+impl ProofPolicy {
+    /// The policy in force during `epoch`, given the epoch from which Groth16
+    /// proofs are required, if one has been scheduled.
+    pub fn at_epoch(groth16_activation_epoch: Option<u64>, epoch: u64) -> Self {
+        match groth16_activation_epoch {
+            Some(activation) if epoch >= activation => ProofPolicy::RequireGroth16,
+            _ => ProofPolicy::AllowBoth,
+        }
+    }
+
+    /// Whether proofs made with `system` are accepted under this policy.
+    pub fn allows(&self, system: ProofSystem) -> bool {
+        match self {
+            ProofPolicy::AllowBoth => true,
+            ProofPolicy::RequireGroth16 => system == ProofSystem::Groth16,
+        }
+    }
+
+    /// Check that proofs made with `system` are accepted under this policy.
+    pub fn check(&self, system: ProofSystem) -> Result<(), DisallowedProof> {
+        if self.allows(system) {
+            Ok(())
+        } else {
+            Err(DisallowedProof {
+                system,
+                policy: *self,
+            })
+        }
+    }
+}
+
+/// The error returned when verifying a proof made with a proof system that the
+/// [`ProofPolicy`] doesn't accept.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{system} proofs are not accepted under the {policy:?} proof policy")]
+pub struct DisallowedProof {
+    pub system: ProofSystem,
+    pub policy: ProofPolicy,
+}
+
+/// A proof of an action, made with either proof system.
+#[derive(Clone, Debug)]
+pub enum Proof<T, G> {
+    Transparent(T),
+    Groth16(G),
+}
+
+/// A proof of a spend.
+pub type SpendProof = Proof<transparent::SpendProof, groth16::SpendProof>;
+/// A proof of an output.
+pub type OutputProof = Proof<transparent::OutputProof, groth16::OutputProof>;
+/// A proof of a swap.
+pub type SwapProof = Proof<transparent::SwapProof, groth16::SwapProof>;
+/// A proof of a swap claim.
+pub type SwapClaimProof = Proof<transparent::SwapClaimProof, groth16::SwapClaimProof>;
+/// A proof of an undelegation claim.
+pub type UndelegateClaimProof =
+    Proof<transparent::UndelegateClaimProof, groth16::UndelegateClaimProof>;
+
+impl<T, G> Proof<T, G> {
+    /// The proof system this proof was made with.
+    pub fn system(&self) -> ProofSystem {
+        match self {
+            Proof::Transparent(_) => ProofSystem::Transparent,
+            Proof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+
+    /// Check the proof system against `policy`, then verify the proof with
+    /// whichever of the verification functions matches it.
+    fn verify_with(
+        &self,
+        policy: ProofPolicy,
+        verify_transparent: impl FnOnce(&T) -> anyhow::Result<()>,
+        verify_groth16: impl FnOnce(&G) -> Result<(), groth16::error::VerificationError>,
+    ) -> anyhow::Result<()> {
+        policy.check(self.system())?;
+        match self {
+            Proof::Transparent(proof) => verify_transparent(proof),
+            Proof::Groth16(proof) => Ok(verify_groth16(proof)?),
+        }
+    }
+}
+
+impl SpendProof {
+    /// Verify the proof under `policy`.
+    ///
+    /// The verifying key is only used for Groth16 proofs.
+    pub fn verify(
+        &self,
+        policy: ProofPolicy,
+        vk: &VerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<()> {
+        self.verify_with(
+            policy,
+            |proof| proof.verify(anchor, balance_commitment, nullifier, rk.into()),
+            |proof| proof.verify(vk, anchor, balance_commitment, nullifier, rk),
+        )
+    }
+}
+
+impl OutputProof {
+    /// Verify the proof under `policy`.
+    ///
+    /// The verifying key is only used for Groth16 proofs.
+    pub fn verify(
+        &self,
+        policy: ProofPolicy,
+        vk: &VerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> anyhow::Result<()> {
+        self.verify_with(
+            policy,
+            |proof| proof.verify(balance_commitment, note_commitment),
+            |proof| proof.verify(vk, balance_commitment, note_commitment),
+        )
+    }
+}
+
+impl SwapProof {
+    /// Verify the proof under `policy`.
+    ///
+    /// The verifying key is only used for Groth16 proofs.
+    pub fn verify(
+        &self,
+        policy: ProofPolicy,
+        vk: &VerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> anyhow::Result<()> {
+        self.verify_with(
+            policy,
+            |proof| proof.verify(fee_commitment, swap_commitment, balance_commitment),
+            |proof| proof.verify(vk, balance_commitment, swap_commitment, fee_commitment),
+        )
+    }
+}
+
+impl SwapClaimProof {
+    /// Verify the proof under `policy`.
+    ///
+    /// The verifying key is only used for Groth16 proofs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify(
+        &self,
+        policy: ProofPolicy,
+        vk: &VerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        fee: Fee,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+        note_commitment_1: note::Commitment,
+        note_commitment_2: note::Commitment,
+    ) -> anyhow::Result<()> {
+        self.verify_with(
+            policy,
+            |proof| {
+                proof.verify(
+                    anchor,
+                    nullifier,
+                    output_data,
+                    epoch_duration,
+                    note_commitment_1,
+                    note_commitment_2,
+                    fee.clone(),
+                )
+            },
+            |proof| {
+                proof.verify(
+                    vk,
+                    anchor,
+                    nullifier,
+                    fee.clone(),
+                    output_data,
+                    epoch_duration,
+                    note_commitment_1,
+                    note_commitment_2,
+                )
+            },
+        )
+    }
+}
+
+impl UndelegateClaimProof {
+    /// Verify the proof under `policy`.
+    ///
+    /// The verifying key is only used for Groth16 proofs.
+    pub fn verify(
+        &self,
+        policy: ProofPolicy,
+        vk: &VerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> anyhow::Result<()> {
+        self.verify_with(
+            policy,
+            |proof| proof.verify(balance_commitment, unbonding_id, penalty),
+            |proof| proof.verify(vk, balance_commitment, unbonding_id, penalty),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        keys::{SeedPhrase, SpendKey},
+        proofs::groth16::{OutputCircuit, OutputProofPlan, ParameterSetup},
+        Balance, FieldExt, Fr, Note, Value, STAKING_TOKEN_ASSET_ID,
+    };
+
+    #[test]
+    fn policy_follows_activation_epoch() {
+        assert_eq!(ProofPolicy::at_epoch(None, 100), ProofPolicy::AllowBoth);
+        assert_eq!(ProofPolicy::at_epoch(Some(10), 9), ProofPolicy::AllowBoth);
+        assert_eq!(
+            ProofPolicy::at_epoch(Some(10), 10),
+            ProofPolicy::RequireGroth16
+        );
+        assert!(!ProofPolicy::RequireGroth16.allows(ProofSystem::Transparent));
+        assert!(ProofPolicy::AllowBoth.allows(ProofSystem::Transparent));
+    }
+
+    #[test]
+    fn output_proofs_verify_under_policy() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let (dest, _dtk_d) = sk.full_viewing_key().payment_address(0u64.into());
+        let value = Value {
+            amount: 10u64.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(&mut OsRng, &dest, value);
+        let v_blinding = Fr::rand_uniform(&mut OsRng);
+        let note_commitment = note.commit();
+        // The transparent output proof checks a commitment to the negated
+        // value, while the output circuit checks one to the value itself.
+        let transparent_balance_commitment = (-Balance::from(value)).commit(v_blinding);
+        let groth16_balance_commitment = value.commit(v_blinding);
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let groth16: OutputProof = Proof::Groth16(
+            OutputProofPlan {
+                note: note.clone(),
+                note_commitment,
+                v_blinding,
+                balance_commitment: groth16_balance_commitment,
+            }
+            .prove(&mut OsRng, &pk)
+            .unwrap(),
+        );
+        let transparent: OutputProof =
+            Proof::Transparent(transparent::OutputProof { note, v_blinding });
+
+        transparent
+            .verify(
+                ProofPolicy::AllowBoth,
+                &vk,
+                transparent_balance_commitment,
+                note_commitment,
+            )
+            .unwrap();
+        for policy in [ProofPolicy::AllowBoth, ProofPolicy::RequireGroth16] {
+            groth16
+                .verify(policy, &vk, groth16_balance_commitment, note_commitment)
+                .unwrap();
+        }
+
+        let err = transparent
+            .verify(
+                ProofPolicy::RequireGroth16,
+                &vk,
+                transparent_balance_commitment,
+                note_commitment,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DisallowedProof>(),
+            Some(&DisallowedProof {
+                system: ProofSystem::Transparent,
+                policy: ProofPolicy::RequireGroth16,
+            })
+        );
+    }
+}
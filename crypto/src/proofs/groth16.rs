@@ -1,24 +1,85 @@
+mod batch;
+mod block;
+pub mod ceremony;
+mod delegator_vote;
+mod description;
+pub mod error;
 pub(crate) mod gadgets;
+mod key_provider;
+pub mod metrics;
+pub mod msm;
 mod output;
+mod parameters;
+pub mod params;
+#[cfg(feature = "std")]
+mod pool;
+mod prover;
+mod proving_service;
+pub mod registry;
 mod spend;
+mod swap;
+mod swap_claim;
 mod traits;
-
-pub use output::{OutputCircuit, OutputProof};
-pub use spend::{SpendCircuit, SpendProof};
-pub use traits::ParameterSetup;
+mod undelegate_claim;
+#[cfg(feature = "std")]
+pub mod verification;
+mod warm_up;
+pub mod wasm;
+mod witness;
+
+pub use batch::{BatchVerifier, InvalidProof, ProofKind};
+pub use block::{BlockVerifier, InvalidBlock, SignatureKind};
+pub use delegator_vote::{DelegatorVoteCircuit, DelegatorVoteProof, DelegatorVotePublicInputs};
+pub use description::{CircuitDescription, Enforcement, Gadget, Variable, VariableKind};
+#[cfg(feature = "std")]
+pub use key_provider::DirectoryKeys;
+pub use key_provider::{EmbeddedKeys, FetchUrl, KeyProvider, ProvingKeyCache, RemoteKeys};
+pub use output::{
+    OutputCircuit, OutputProof, OutputProofPlan, OutputPublicInputs, OUTPUT_PROOF_LEN_BYTES,
+};
+pub use parameters::ParameterStore;
+pub use penumbra_proof_verify::{
+    prepared_verifying_key_from_bytes, prepared_verifying_key_to_bytes,
+};
+#[cfg(feature = "std")]
+pub use pool::ProverPool;
+pub use prover::{ProgressCallback, ProverOptions, ProvingStage};
+#[cfg(feature = "rpc")]
+pub use proving_service::ProvingServer;
+pub use proving_service::ProvingService;
+#[cfg(feature = "std")]
+pub use proving_service::{LocalProver, RerandomizingProver};
+pub use spend::{
+    SpendCircuit, SpendProof, SpendProofPlan, SpendPublicInputs, StrictSpendCircuit,
+    SPEND_PROOF_LEN_BYTES,
+};
+pub use swap::{swap_balance_commitment, SwapCircuit, SwapProof, SwapPublicInputs};
+pub use swap_claim::{SwapClaimCircuit, SwapClaimProof, SwapClaimPublicInputs};
+pub use traits::{ParameterSetup, PublicInputs, ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS};
+pub use undelegate_claim::{
+    UndelegateClaimCircuit, UndelegateClaimProof, UndelegateClaimPublicInputs,
+};
+pub use warm_up::ProvingKeyExt;
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         asset,
-        keys::{SeedPhrase, SpendKey},
+        dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
+        keys::{RandomizedVerificationKey, SeedPhrase, SpendKey},
+        proofs::ProofRng,
+        signing::{self, SigningPolicy},
+        stake::Penalty,
+        transaction::Fee,
     };
     use decaf377::{Fq, Fr};
     use proptest::prelude::*;
 
-    use decaf377_rdsa::{SpendAuth, VerificationKey};
+    use decaf377_rdsa::{Binding, SigningKey, SpendAuth, VerificationKey};
+    use penumbra_proto::Protobuf;
     use penumbra_tct as tct;
+    use rand::{CryptoRng, Rng};
     use rand_core::OsRng;
 
     use crate::{note, Note, Value};
@@ -37,6 +98,84 @@ mod tests {
             .boxed()
     }
 
+    /// A value of `amount` upenumbra.
+    fn upenumbra(amount: u64) -> Value {
+        Value {
+            amount: amount.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        }
+    }
+
+    /// The spend key for a seed phrase made of the byte `seed`.
+    fn test_spend_key(seed: u8) -> SpendKey {
+        SpendKey::from_seed_phrase(SeedPhrase::from_randomness([seed; 32]), 0)
+    }
+
+    /// A new note of `amount` upenumbra, sent to the default address of `sk`.
+    fn test_note(rng: &mut (impl Rng + CryptoRng), sk: &SpendKey, amount: u64) -> Note {
+        let (address, _dtk_d) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+        Note::generate(rng, &address, upenumbra(amount))
+    }
+
+    /// A plan to output a new note of `amount` upenumbra, with balance blinding factor `v_blinding`.
+    fn output_plan(
+        rng: &mut (impl Rng + CryptoRng),
+        amount: u64,
+        v_blinding: Fr,
+    ) -> OutputProofPlan {
+        let address = crate::Address::dummy(rng);
+        let note = Note::generate(rng, &address, upenumbra(amount));
+        OutputProofPlan {
+            note_commitment: note.commit(),
+            balance_commitment: note.value().commit(v_blinding),
+            note,
+            v_blinding,
+        }
+    }
+
+    /// A plan for `sk` to spend `note`, which must be witnessed in `nct`, against its current root.
+    fn spend_plan_in(
+        sk: &SpendKey,
+        nct: &tct::Tree,
+        note: Note,
+        spend_auth_randomizer: Fr,
+        v_blinding: Fr,
+    ) -> SpendProofPlan {
+        let note_commitment = note.commit();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+        let nk = *sk.nullifier_key();
+        let rsk = sk.spend_auth_key().randomize(&spend_auth_randomizer);
+        SpendProofPlan {
+            nullifier: nk.derive_nullifier(note_commitment_proof.position(), &note_commitment),
+            note_commitment_proof,
+            balance_commitment: note.value().commit(v_blinding),
+            note,
+            v_blinding,
+            spend_auth_randomizer: spend_auth_randomizer.into(),
+            ak: sk.spend_auth_key().into(),
+            nk,
+            anchor: nct.root(),
+            rk: RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap(),
+        }
+    }
+
+    /// A plan for `sk` to spend a new note of `amount` upenumbra, the only note in its tree.
+    fn spend_plan(
+        rng: &mut (impl Rng + CryptoRng),
+        sk: &SpendKey,
+        amount: u64,
+        spend_auth_randomizer: Fr,
+        v_blinding: Fr,
+    ) -> SpendProofPlan {
+        let note = test_note(rng, sk, amount);
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note.commit()).unwrap();
+        spend_plan_in(sk, &nct, note, spend_auth_randomizer, v_blinding)
+    }
+
     proptest! {
     #![proptest_config(ProptestConfig::with_cases(2))]
     #[test]
@@ -60,14 +199,13 @@ mod tests {
             let note_commitment = note.commit();
             let balance_commitment = value_to_send.commit(v_blinding);
 
-            let proof = OutputProof::prove(
-                &mut rng,
-                &pk,
+            let proof = OutputProofPlan {
                 note,
                 v_blinding,
                 balance_commitment,
                 note_commitment,
-            )
+            }
+            .prove(&mut rng, &pk)
             .expect("can create proof");
 
             let proof_result = proof.verify(&vk, balance_commitment, note_commitment);
@@ -98,14 +236,13 @@ mod tests {
         let note_commitment = note.commit();
         let balance_commitment = value_to_send.commit(v_blinding);
 
-        let proof = OutputProof::prove(
-            &mut rng,
-            &pk,
-            note.clone(),
+        let proof = OutputProofPlan {
+            note: note.clone(),
             v_blinding,
             balance_commitment,
             note_commitment,
-        )
+        }
+        .prove(&mut rng, &pk)
         .expect("can create proof");
 
         let incorrect_note_commitment = note::commitment(
@@ -144,14 +281,13 @@ mod tests {
         let note_commitment = note.commit();
         let balance_commitment = value_to_send.commit(v_blinding);
 
-        let proof = OutputProof::prove(
-            &mut rng,
-            &pk,
+        let proof = OutputProofPlan {
             note,
             v_blinding,
             balance_commitment,
             note_commitment,
-        )
+        }
+        .prove(&mut rng, &pk)
         .expect("can create proof");
 
         let incorrect_balance_commitment = value_to_send.commit(incorrect_v_blinding);
@@ -191,23 +327,22 @@ mod tests {
         let anchor = nct.root();
         let note_commitment_proof = nct.witness(note_commitment).unwrap();
         let balance_commitment = value_to_send.commit(v_blinding);
-        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
         let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
-        let proof = SpendProof::prove(
-            &mut rng,
-            &pk,
+        let proof = SpendProofPlan {
             note_commitment_proof,
             note,
             v_blinding,
-            spend_auth_randomizer,
+            spend_auth_randomizer: spend_auth_randomizer.into(),
             ak,
             nk,
             anchor,
             balance_commitment,
-            nf,
+            nullifier: nf,
             rk,
-        )
+        }
+        .prove(&mut rng, &pk)
         .expect("can create proof");
 
         let proof_result = proof.verify(&vk, anchor, balance_commitment, nf, rk);
@@ -246,23 +381,22 @@ mod tests {
         let anchor = nct.root();
         let note_commitment_proof = nct.witness(note_commitment).unwrap();
         let balance_commitment = value_to_send.commit(v_blinding);
-        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
         let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
-        let proof = SpendProof::prove(
-            &mut rng,
-            &pk,
+        let proof = SpendProofPlan {
             note_commitment_proof,
             note,
             v_blinding,
-            spend_auth_randomizer,
+            spend_auth_randomizer: spend_auth_randomizer.into(),
             ak,
             nk,
             anchor,
             balance_commitment,
-            nf,
+            nullifier: nf,
             rk,
-        )
+        }
+        .prove(&mut rng, &pk)
         .expect("can create proof");
 
         let proof_result = proof.verify(&vk, incorrect_anchor, balance_commitment, nf, rk);
@@ -304,26 +438,25 @@ mod tests {
             let anchor = nct.root();
             let note_commitment_proof = nct.witness(note_commitment).unwrap();
             let balance_commitment = value_to_send.commit(v_blinding);
-            let rk: VerificationKey<SpendAuth> = rsk.into();
+            let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
             let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
             // Note that this will blow up in debug mode as the constraint
             // system is unsatisified (ark-groth16 has a debug check for this).
             // In release mode the proof will be created, but will fail to verify.
-            let proof = SpendProof::prove(
-                &mut rng,
-                &pk,
+            let proof = SpendProofPlan {
                 note_commitment_proof,
                 note,
                 v_blinding,
-                spend_auth_randomizer,
+                spend_auth_randomizer: spend_auth_randomizer.into(),
                 ak,
                 nk,
                 anchor,
                 balance_commitment,
-                nf,
+                nullifier: nf,
                 rk,
-            ).expect("can create proof in release mode");
+            }
+            .prove(&mut rng, &pk).expect("can create proof in release mode");
 
             proof.verify(&vk, anchor, balance_commitment, nf, rk).expect("boom");
         }
@@ -359,25 +492,24 @@ mod tests {
             let anchor = nct.root();
             let note_commitment_proof = nct.witness(note_commitment).unwrap();
             let balance_commitment = value_to_send.commit(v_blinding);
-            let rk: VerificationKey<SpendAuth> = rsk.into();
+            let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
             let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
             let incorrect_nf = nk.derive_nullifier(5.into(), &note_commitment);
 
-            let proof = SpendProof::prove(
-                &mut rng,
-                &pk,
+            let proof = SpendProofPlan {
                 note_commitment_proof,
                 note,
                 v_blinding,
-                spend_auth_randomizer,
+                spend_auth_randomizer: spend_auth_randomizer.into(),
                 ak,
                 nk,
                 anchor,
                 balance_commitment,
-                nf,
+                nullifier: nf,
                 rk,
-            )
+            }
+            .prove(&mut rng, &pk)
             .expect("can create proof");
 
             let proof_result = proof.verify(&vk, anchor, balance_commitment, incorrect_nf, rk);
@@ -415,23 +547,22 @@ mod tests {
         let anchor = nct.root();
         let note_commitment_proof = nct.witness(note_commitment).unwrap();
         let balance_commitment = value_to_send.commit(v_blinding);
-        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
         let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
-        let proof = SpendProof::prove(
-            &mut rng,
-            &pk,
+        let proof = SpendProofPlan {
             note_commitment_proof,
             note,
             v_blinding,
-            spend_auth_randomizer,
+            spend_auth_randomizer: spend_auth_randomizer.into(),
             ak,
             nk,
             anchor,
             balance_commitment,
-            nf,
+            nullifier: nf,
             rk,
-        )
+        }
+        .prove(&mut rng, &pk)
         .expect("can create proof");
 
         let incorrect_balance_commitment = value_to_send.commit(incorrect_blinding_factor);
@@ -441,6 +572,68 @@ mod tests {
     }
     }
 
+    #[test]
+    /// Check that the spend circuit only accepts a balance commitment to the spent note's own
+    /// value, so that spending a note of one asset can't be made to count as a spend of another.
+    ///
+    /// Value generators are derived by hashing asset IDs to the curve, so no discrete log
+    /// relation between them is known. If one were, e.g. `G_b = k * G_a`, then a commitment to
+    /// `v` of asset `a` would also be a commitment to `v / k` of asset `b`, and no circuit could
+    /// tell them apart. This checks that the generators in use aren't related by small scalars,
+    /// and that commitments built from a related generator are rejected.
+    fn spend_circuit_rejects_cross_asset_balance_forgery() {
+        let v_blinding = Fr::from(11u64);
+        let plan = spend_plan(
+            &mut OsRng,
+            &test_spend_key(b'f'),
+            100,
+            Fr::from(5u64),
+            v_blinding,
+        );
+        assert_eq!(metrics::debug_satisfaction(plan.build_circuit()), Ok(()));
+
+        let nala = asset::REGISTRY.parse_denom("nala").unwrap().id();
+        let blinding_generator = *crate::balance::commitment::VALUE_BLINDING_GENERATOR;
+        let g_a = plan.note.value().asset_id.value_generator();
+        let g_b = nala.value_generator();
+        let amount = Fr::from(100u64);
+
+        // The generators of distinct assets aren't related by any small scalar.
+        for k in 1..=1000u64 {
+            let k = Fr::from(k);
+            assert_ne!(k * g_a, g_b);
+            assert_ne!(k * g_b, g_a);
+        }
+
+        let forgeries = [
+            // The same amount of another asset.
+            Value {
+                amount: 100u64.into(),
+                asset_id: nala,
+            }
+            .commit(v_blinding),
+            // A different amount of the same asset.
+            upenumbra(101).commit(v_blinding),
+            // The same amount against a generator related to the note's by a known scalar.
+            crate::balance::Commitment(
+                amount * (Fr::from(2u64) * g_a) + v_blinding * blinding_generator,
+            ),
+        ];
+
+        for balance_commitment in forgeries {
+            let forged = SpendProofPlan {
+                balance_commitment,
+                ..plan.clone()
+            };
+            let unsatisfied = metrics::debug_satisfaction(forged.build_circuit()).unwrap_err();
+            assert!(
+                unsatisfied.gadgets.contains(&"value_commitment_integrity"),
+                "forged balance commitment failed in {:?}",
+                unsatisfied.gadgets
+            );
+        }
+    }
+
     proptest! {
             #![proptest_config(ProptestConfig::with_cases(2))]
         #[test]
@@ -470,28 +663,27 @@ mod tests {
             let anchor = nct.root();
             let note_commitment_proof = nct.witness(note_commitment).unwrap();
             let balance_commitment = value_to_send.commit(v_blinding);
-            let rk: VerificationKey<SpendAuth> = rsk.into();
+            let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
             let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
             let incorrect_rsk = sk_sender
                 .spend_auth_key()
                 .randomize(&incorrect_spend_auth_randomizer);
-            let incorrect_rk: VerificationKey<SpendAuth> = incorrect_rsk.into();
+            let incorrect_rk = RandomizedVerificationKey::try_from(VerificationKey::from(incorrect_rsk)).unwrap();
 
-            let proof = SpendProof::prove(
-                &mut rng,
-                &pk,
+            let proof = SpendProofPlan {
                 note_commitment_proof,
                 note,
                 v_blinding,
-                spend_auth_randomizer,
+                spend_auth_randomizer: spend_auth_randomizer.into(),
                 ak,
                 nk,
                 anchor,
                 balance_commitment,
-                nf,
+                nullifier: nf,
                 rk,
-            )
+            }
+            .prove(&mut rng, &pk)
             .expect("should be able to form proof");
 
             let proof_result = proof.verify(&vk, anchor, balance_commitment, nf, incorrect_rk);
@@ -531,27 +723,1564 @@ mod tests {
             // since for dummies we only check if the value is zero, and choose
             // not to enforce the other equality constraint.
             let balance_commitment = value_to_send.commit(v_blinding);
-            let rk: VerificationKey<SpendAuth> = rsk.into();
+            let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
             let nf = nk.derive_nullifier(0.into(), &note_commitment);
 
-            let proof = SpendProof::prove(
-                &mut rng,
-                &pk,
+            let proof = SpendProofPlan {
                 note_commitment_proof,
                 note,
                 v_blinding,
-                spend_auth_randomizer,
+                spend_auth_randomizer: spend_auth_randomizer.into(),
                 ak,
                 nk,
                 anchor,
                 balance_commitment,
-                nf,
+                nullifier: nf,
                 rk,
-            )
+            }
+            .prove(&mut rng, &pk)
             .expect("should be able to form proof");
 
             let proof_result = proof.verify(&vk, anchor, balance_commitment, nf, rk);
             assert!(proof_result.is_ok());
         }
     }
+
+    /// Build a tree in which `commitment` is witnessed in the first epoch, followed by
+    /// `later_epochs` epochs of unrelated commitments.
+    ///
+    /// Returns the tree along with the anchor as of the end of the first epoch.
+    fn tree_spanning_epochs(
+        commitment: note::Commitment,
+        later_epochs: u64,
+    ) -> (tct::Tree, tct::Root) {
+        let filler = |i: u64| note::Commitment(Fq::from(i));
+
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Forget, filler(0)).unwrap();
+        nct.insert(tct::Witness::Keep, commitment).unwrap();
+        nct.end_block().unwrap();
+        nct.end_epoch().unwrap();
+        let first_epoch_anchor = nct.root();
+
+        for epoch in 1..=later_epochs {
+            for block in 0..2 {
+                nct.insert(tct::Witness::Forget, filler(epoch * 10 + block))
+                    .unwrap();
+                nct.end_block().unwrap();
+            }
+            nct.end_epoch().unwrap();
+        }
+        (nct, first_epoch_anchor)
+    }
+
+    #[test]
+    /// Check that a spend of a note from an earlier epoch verifies against the latest anchor.
+    fn spend_proof_from_earlier_epoch() {
+        let (pk, vk) = SpendCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let sk = test_spend_key(b'e');
+        let note = test_note(&mut rng, &sk, 10);
+        let (nct, first_epoch_anchor) = tree_spanning_epochs(note.commit(), 3);
+        let plan = spend_plan_in(&sk, &nct, note, Fr::from(3u64), Fr::from(5u64));
+        assert_ne!(plan.anchor, first_epoch_anchor);
+        assert_eq!(plan.note_commitment_proof.position().epoch(), 0);
+        assert!(plan.note_commitment_proof.verify(plan.anchor).is_ok());
+
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+        assert!(proof
+            .verify(
+                &vk,
+                plan.anchor,
+                plan.balance_commitment,
+                plan.nullifier,
+                plan.rk
+            )
+            .is_ok());
+        // The proof is bound to the anchor it was created against.
+        assert!(proof
+            .verify(
+                &vk,
+                first_epoch_anchor,
+                plan.balance_commitment,
+                plan.nullifier,
+                plan.rk
+            )
+            .is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    /// Check that the `SwapProof` verification succeeds, and fails against an
+    /// incorrect fee commitment.
+    fn swap_proof_happy_path(seed_phrase_randomness in any::<[u8; 32]>(), fee_blinding in fr_strategy(), delta_1 in 1..200u64, delta_2 in 0..200u64, fee_amount in 0..10u64) {
+        let (pk, vk) = SwapCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::from_randomness(seed_phrase_randomness);
+        let sk_recipient = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let (claim_address, _dtk_d) = sk_recipient
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let trading_pair = TradingPair::new(
+            asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+            asset::REGISTRY.parse_denom("nala").unwrap().id(),
+        );
+        let swap_plaintext = SwapPlaintext::new(
+            &mut rng,
+            trading_pair,
+            delta_1.into(),
+            delta_2.into(),
+            Fee::from_staking_token_amount(fee_amount.into()),
+            claim_address,
+        );
+        let fee_commitment = swap_plaintext.claim_fee.commit(fee_blinding);
+        let swap_commitment = swap_plaintext.swap_commitment();
+        let balance_commitment = swap_balance_commitment(&swap_plaintext, fee_commitment);
+
+        let proof = SwapProof::prove(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            swap_plaintext.clone(),
+            fee_blinding,
+            balance_commitment,
+            swap_commitment,
+            fee_commitment,
+        )
+        .expect("can create proof");
+
+        assert!(proof
+            .verify(&vk, balance_commitment, swap_commitment, fee_commitment)
+            .is_ok());
+
+        let incorrect_fee_commitment = swap_plaintext.claim_fee.commit(fee_blinding + Fr::from(1u64));
+        assert!(proof
+            .verify(&vk, balance_commitment, swap_commitment, incorrect_fee_commitment)
+            .is_err());
+    }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    /// Check that the `SwapClaimProof` verification succeeds for the pro rata
+    /// outputs, and fails for any other outputs.
+    fn swap_claim_proof_happy_path(seed_phrase_randomness in any::<[u8; 32]>(), delta_1_i in 1..200u64, delta_1 in 200..1000u64, lambda_2 in 0..1000u64, success in any::<bool>()) {
+        let (pk, vk) = SwapClaimCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::from_randomness(seed_phrase_randomness);
+        let sk_recipient = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_recipient = sk_recipient.full_viewing_key();
+        let (claim_address, _dtk_d) = fvk_recipient.incoming().payment_address(0u64.into());
+
+        let trading_pair = TradingPair::new(
+            asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+            asset::REGISTRY.parse_denom("nala").unwrap().id(),
+        );
+        let swap_plaintext = SwapPlaintext::new(
+            &mut rng,
+            trading_pair,
+            delta_1_i.into(),
+            0u64.into(),
+            Fee::default(),
+            claim_address,
+        );
+
+        // Make the swap in the third block of the second epoch.
+        let epoch_duration = 10;
+        let mut sct = tct::Tree::new();
+        sct.end_epoch().unwrap();
+        sct.end_block().unwrap();
+        sct.end_block().unwrap();
+        let swap_commitment = swap_plaintext.swap_commitment();
+        sct.insert(tct::Witness::Keep, swap_commitment).unwrap();
+        let anchor = sct.root();
+        let swap_commitment_proof = sct.witness(swap_commitment).unwrap();
+        let nk = *sk_recipient.nullifier_key();
+        let nullifier = nk.derive_nullifier(swap_commitment_proof.position(), &swap_commitment);
+
+        let output_data = BatchSwapOutputData {
+            delta_1,
+            delta_2: 0,
+            lambda_1: 0,
+            lambda_2,
+            height: epoch_duration + 2,
+            trading_pair,
+            success,
+        };
+        let (output_1, output_2) = swap_plaintext.output_notes(&output_data);
+
+        let proof = SwapClaimProof::prove(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            swap_plaintext.clone(),
+            swap_commitment_proof,
+            nk,
+            anchor,
+            output_data,
+            epoch_duration,
+        )
+        .expect("can create proof");
+
+        assert!(proof
+            .verify(&vk, anchor, nullifier, swap_plaintext.claim_fee.clone(), output_data, epoch_duration, output_1.commit(), output_2.commit())
+            .is_ok());
+
+        // The swap commitment proof must be for the anchor being claimed against.
+        sct.insert(tct::Witness::Forget, tct::Commitment(Fq::from(1u64))).unwrap();
+        assert!(SwapClaimProof::prove(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            swap_plaintext.clone(),
+            sct.witness(swap_commitment).unwrap(),
+            nk,
+            anchor,
+            output_data,
+            epoch_duration,
+        )
+        .is_err());
+
+        // The proof is bound to the clearing prices of the batch.
+        let other_output_data = BatchSwapOutputData { lambda_2: lambda_2 + 1, ..output_data };
+        assert!(proof
+            .verify(&vk, anchor, nullifier, swap_plaintext.claim_fee.clone(), other_output_data, epoch_duration, output_1.commit(), output_2.commit())
+            .is_err());
+    }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    /// Check that a delegator vote proof verifies, and only with the voting note's value.
+    fn delegator_vote_proof_happy_path(seed_phrase_randomness in any::<[u8; 32]>(), spend_auth_randomizer in fr_strategy(), value_amount in 1..200u64) {
+        let (pk, vk) = DelegatorVoteCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::from_randomness(seed_phrase_randomness);
+        let sk_voter = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let (address, _dtk_d) = sk_voter.full_viewing_key().incoming().payment_address(0u64.into());
+        let value = Value {
+            amount: value_amount.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+
+        let note = Note::generate(&mut rng, &address, value);
+        let note_commitment = note.commit();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+        // Later insertions don't affect a vote at the snapshot anchor.
+        nct.insert(tct::Witness::Forget, note::Commitment(Fq::from(1u64))).unwrap();
+
+        let rsk = sk_voter.spend_auth_key().randomize(&spend_auth_randomizer);
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(rsk)).unwrap();
+        let nk = *sk_voter.nullifier_key();
+        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+
+        let proof = DelegatorVoteProof::prove(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            note_commitment_proof,
+            note,
+            spend_auth_randomizer.into(),
+            sk_voter.spend_auth_key().into(),
+            nk,
+            anchor,
+            nf,
+            rk,
+        )
+        .expect("can create proof");
+
+        assert!(proof.verify(&vk, anchor, nf, rk, value).is_ok());
+        let inflated = Value { amount: (value_amount + 1).into(), ..value };
+        assert!(proof.verify(&vk, anchor, nf, rk, inflated).is_err());
+        assert!(proof.verify(&vk, nct.root(), nf, rk, value).is_err());
+    }
+    }
+
+    #[test]
+    /// Check that a delegator vote can be proven against a retained snapshot anchor, after the
+    /// tree has grown and the note has been forgotten.
+    fn delegator_vote_proof_historical_anchor() {
+        let (pk, vk) = DelegatorVoteCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let sk_voter = test_spend_key(b'v');
+        let note = test_note(&mut rng, &sk_voter, 10);
+        let value = note.value();
+        let note_commitment = note.commit();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.retain_anchor();
+        nct.end_block().unwrap();
+        nct.insert(tct::Witness::Forget, note::Commitment(Fq::from(1u64)))
+            .unwrap();
+        let stale_proof = nct.witness(note_commitment).unwrap();
+        nct.forget(note_commitment);
+
+        let spend_auth_randomizer = Fr::from(5u64);
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(
+            sk_voter.spend_auth_key().randomize(&spend_auth_randomizer),
+        ))
+        .unwrap();
+        let nk = *sk_voter.nullifier_key();
+        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        let prove = |note_commitment_proof| {
+            DelegatorVoteProof::prove(
+                &ProverOptions::default(),
+                &mut OsRng,
+                &pk,
+                note_commitment_proof,
+                note.clone(),
+                spend_auth_randomizer.into(),
+                sk_voter.spend_auth_key().into(),
+                nk,
+                anchor,
+                nf,
+                rk,
+            )
+        };
+
+        // An inclusion proof against the latest root can't be used at the snapshot anchor.
+        assert!(prove(stale_proof).is_err());
+
+        let note_commitment_proof = nct
+            .witness_at(anchor, note_commitment)
+            .expect("commitment is witnessed at the retained anchor");
+        let proof = prove(note_commitment_proof).expect("can create proof");
+        assert!(proof.verify(&vk, anchor, nf, rk, value).is_ok());
+        assert!(proof.verify(&vk, nct.root(), nf, rk, value).is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    /// Check that the `UndelegateClaimProof` verification succeeds, and fails
+    /// if the claim is made at a different penalty.
+    fn undelegate_claim_proof_happy_path(balance_blinding in fr_strategy(), unbonding_amount in 1..1_000_000_000u64, penalty in 0..=1_0000_0000u64) {
+        let (pk, vk) = UndelegateClaimCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let unbonding_id = asset::REGISTRY.parse_denom("nala").unwrap().id();
+        let penalty = Penalty(penalty);
+        let balance_commitment = penalty
+            .balance_for_claim(unbonding_id, unbonding_amount.into())
+            .commit(balance_blinding);
+
+        let proof = UndelegateClaimProof::prove(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            unbonding_amount.into(),
+            balance_blinding,
+            balance_commitment,
+            unbonding_id,
+            penalty,
+        )
+        .expect("can create proof");
+
+        assert!(proof
+            .verify(&vk, balance_commitment, unbonding_id, penalty)
+            .is_ok());
+
+        // A different penalty changes the converted amount (by at least one unit, given a large
+        // enough unbonding amount), and so the balance commitment.
+        let other_penalty = Penalty(1_0000_0000 - penalty.0);
+        if penalty.apply_to(unbonding_amount.into()) != other_penalty.apply_to(unbonding_amount.into()) {
+            assert!(proof
+                .verify(&vk, balance_commitment, unbonding_id, other_penalty)
+                .is_err());
+        }
+    }
+    }
+
+    #[test]
+    /// Check that the circuit descriptions agree with the circuits' public inputs.
+    fn circuit_descriptions() {
+        let spend = SpendCircuit::describe();
+        assert_eq!(spend.num_public_inputs(), SpendCircuit::NUM_PUBLIC_INPUTS);
+        let strict_spend = StrictSpendCircuit::describe();
+        assert_eq!(
+            strict_spend.num_public_inputs(),
+            StrictSpendCircuit::NUM_PUBLIC_INPUTS
+        );
+        let output = OutputCircuit::describe();
+        assert_eq!(output.num_public_inputs(), OutputCircuit::NUM_PUBLIC_INPUTS);
+        let swap = SwapCircuit::describe();
+        assert_eq!(swap.num_public_inputs(), SwapCircuit::NUM_PUBLIC_INPUTS);
+        let swap_claim = SwapClaimCircuit::describe();
+        assert_eq!(
+            swap_claim.num_public_inputs(),
+            SwapClaimCircuit::NUM_PUBLIC_INPUTS
+        );
+        let delegator_vote = DelegatorVoteCircuit::describe();
+        assert_eq!(
+            delegator_vote.num_public_inputs(),
+            DelegatorVoteCircuit::NUM_PUBLIC_INPUTS
+        );
+        let undelegate_claim = UndelegateClaimCircuit::describe();
+        assert_eq!(
+            undelegate_claim.num_public_inputs(),
+            UndelegateClaimCircuit::NUM_PUBLIC_INPUTS
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&spend.to_json().unwrap()).unwrap();
+        assert_eq!(json["public_inputs"][0]["name"], "anchor");
+        assert_eq!(json["public_inputs"][1]["type"], "element");
+    }
+
+    #[test]
+    /// Check that an unsatisfied circuit is traced back to the gadget that failed.
+    fn debug_satisfaction_finds_failing_gadget() {
+        assert_eq!(
+            metrics::debug_satisfaction(OutputCircuit::sample_circuit()),
+            Ok(())
+        );
+
+        let mut circuit = OutputCircuit::sample_circuit();
+        circuit.note_commitment = tct::Commitment(Fq::from(1u64));
+        let unsatisfied = metrics::debug_satisfaction(circuit).unwrap_err();
+        assert_eq!(unsatisfied.gadgets, vec!["note_commitment_integrity"]);
+        assert!(unsatisfied.index.is_some());
+    }
+
+    #[test]
+    /// Check that the gadgets measured in each circuit are the gadgets in its description.
+    fn circuit_metrics_match_descriptions() {
+        let descriptions = [
+            SpendCircuit::describe(),
+            StrictSpendCircuit::describe(),
+            OutputCircuit::describe(),
+            SwapCircuit::describe(),
+            SwapClaimCircuit::describe(),
+            DelegatorVoteCircuit::describe(),
+            UndelegateClaimCircuit::describe(),
+        ];
+        let all_metrics = metrics::all_circuit_metrics().expect("can measure circuits");
+        assert_eq!(all_metrics.len(), descriptions.len());
+
+        for (metrics, description) in all_metrics.iter().zip(descriptions) {
+            assert_eq!(metrics.name, description.name);
+            let measured = metrics
+                .gadgets
+                .iter()
+                .map(|gadget| gadget.label)
+                .collect::<Vec<_>>();
+            let described = description
+                .gadgets
+                .iter()
+                .map(|gadget| gadget.name)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                measured.iter().collect::<std::collections::BTreeSet<_>>(),
+                described.iter().collect::<std::collections::BTreeSet<_>>(),
+                "{} circuit",
+                metrics.name
+            );
+
+            assert!(metrics.gadgets.iter().all(|g| g.counts.constraints > 0));
+            // Every public input is allocated outside of the gadgets.
+            assert_eq!(
+                metrics.unlabelled().instances,
+                description.num_public_inputs() + 1
+            );
+        }
+    }
+
+    #[test]
+    /// Check that a test's override of the global parameters is used, cached,
+    /// and removed again when its guard is dropped.
+    fn global_parameters_override() {
+        let _outer = params::override_for_tests(ParameterStore::new().with_test_parameters());
+        let pk = params::output_proving_key().unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            &pk,
+            &params::output_proving_key().unwrap()
+        ));
+        assert_eq!(params::output_verifying_key().unwrap(), pk.vk);
+
+        // A nested override has its own keys, and the outer keys are restored
+        // when it is dropped.
+        let inner = params::override_for_tests(ParameterStore::new().with_test_parameters());
+        assert!(!std::sync::Arc::ptr_eq(
+            &pk,
+            &params::output_proving_key().unwrap()
+        ));
+        drop(inner);
+        assert!(std::sync::Arc::ptr_eq(
+            &pk,
+            &params::output_proving_key().unwrap()
+        ));
+
+        // An empty store has no keys to give.
+        let _empty = params::override_for_tests(ParameterStore::new());
+        assert!(params::output_proving_key().is_err());
+    }
+
+    #[test]
+    /// Check that a proof verifies with any candidate key that it was made for,
+    /// as across a parameter upgrade.
+    fn output_proof_verify_any_candidate_key() {
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let (_old_pk, old_vk) =
+            OutputCircuit::generate_test_parameters_with_rng(&mut ChaCha20Rng::seed_from_u64(1));
+        let (new_pk, new_vk) =
+            OutputCircuit::generate_test_parameters_with_rng(&mut ChaCha20Rng::seed_from_u64(2));
+
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &new_pk).expect("can create proof");
+        let (balance_commitment, note_commitment) = (plan.balance_commitment, plan.note_commitment);
+
+        assert_eq!(
+            proof
+                .verify_any(&[&old_vk, &new_vk], balance_commitment, note_commitment)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            proof
+                .verify_any(&[&new_vk, &old_vk], balance_commitment, note_commitment)
+                .unwrap(),
+            0
+        );
+        assert!(proof
+            .verify_any(&[&old_vk], balance_commitment, note_commitment)
+            .is_err());
+        assert!(proof
+            .verify_any(&[], balance_commitment, note_commitment)
+            .is_err());
+    }
+
+    #[test]
+    /// Check that a batch of output proofs generated in parallel, sharing one proving key,
+    /// verify in input order.
+    fn output_proof_prove_batch() {
+        use rand_chacha::ChaCha20Rng;
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let plans = (1..=4u64)
+            .map(|amount| output_plan(&mut rng, amount, Fr::from(amount)))
+            .collect::<Vec<_>>();
+
+        let pool = ProverPool::new(ProverOptions::default().with_threads(4), 2)
+            .expect("can create prover pool");
+        assert_eq!(pool.parallelism(), 2);
+        let proofs = pool
+            .prove_batch(
+                &mut rng,
+                plans.iter().map(|plan| {
+                    let pk = &pk;
+                    move |options: &ProverOptions, rng: &mut ChaCha20Rng| {
+                        plan.prove_with_options(options, rng, pk)
+                    }
+                }),
+            )
+            .expect("can create proofs");
+
+        assert_eq!(proofs.len(), plans.len());
+        for (proof, plan) in proofs.iter().zip(&plans) {
+            assert!(proof
+                .verify(&vk, plan.balance_commitment, plan.note_commitment)
+                .is_ok());
+        }
+        // Proofs are returned in the order of their jobs.
+        assert!(proofs[0]
+            .verify(&vk, plans[1].balance_commitment, plans[1].note_commitment)
+            .is_err());
+
+        assert!(ProverPool::new(ProverOptions::default(), 0).is_err());
+    }
+
+    #[test]
+    /// Check that a deterministic seed, given in the `ProverOptions` or through a seeded
+    /// `ProofRng`, gives identical proofs, and that progress is reported.
+    fn output_proof_deterministic_seed() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let plan = output_plan(&mut ProofRng::from_seed([2u8; 32]), 10, Fr::from(7u64));
+
+        let stages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let options = {
+            let stages = stages.clone();
+            ProverOptions::default()
+                .with_threads(1)
+                .with_deterministic_seed([1u8; 32])
+                .with_progress(move |stage| stages.lock().unwrap().push(stage))
+        };
+
+        let proofs: Vec<_> = (0..2)
+            .map(|_| {
+                plan.prove_with_options(&options, &mut OsRng, &pk)
+                    .expect("can create proof")
+            })
+            .collect();
+
+        assert_eq!(proofs[0].to_bytes(), proofs[1].to_bytes());
+        assert!(proofs[0]
+            .verify(&vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+        assert_eq!(
+            stages.lock().unwrap().as_slice(),
+            &[
+                ProvingStage::Started { threads: 1 },
+                ProvingStage::Finished,
+                ProvingStage::Started { threads: 1 },
+                ProvingStage::Finished,
+            ]
+        );
+
+        let prove = |seed: [u8; 32]| {
+            plan.prove(&mut ProofRng::from_seed(seed), &pk)
+                .expect("can create proof")
+        };
+        let proof = prove([3u8; 32]);
+        assert_eq!(proof.to_bytes(), prove([3u8; 32]).to_bytes());
+        assert_ne!(proof.to_bytes(), prove([4u8; 32]).to_bytes());
+        assert!(proof
+            .verify(&vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+    }
+
+    #[test]
+    /// Check that proving with an MSM backend gives the same proof as `ark-groth16`.
+    fn msm_backends_match_arkworks_prover() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+
+        let options = ProverOptions::default().with_deterministic_seed([2u8; 32]);
+        let expected = plan
+            .prove_with_options(&options, &mut rng, &pk)
+            .expect("can create proof");
+
+        #[allow(unused_mut)]
+        let mut backends = vec![
+            options.clone().with_msm_backend(msm::ArkworksMsm),
+            options.clone().with_msm_backend(msm::ChunkedMsm::new(
+                std::sync::Arc::new(msm::ArkworksMsm),
+                3,
+            )),
+        ];
+        #[cfg(feature = "parallel-msm")]
+        {
+            backends.push(
+                options
+                    .clone()
+                    .with_msm_backend(msm::ParallelMsm::default()),
+            );
+            backends.push(
+                options
+                    .clone()
+                    .with_msm_backend(msm::ParallelMsm::with_chunk_size(3)),
+            );
+        }
+
+        for options in backends {
+            let proof = plan
+                .prove_with_options(&options, &mut rng, &pk)
+                .expect("can create proof");
+            assert_eq!(proof.to_bytes(), expected.to_bytes());
+            assert!(proof
+                .verify(&vk, plan.balance_commitment, plan.note_commitment)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    /// Check that proof plans survive their protobuf encoding, and that proving a plan gives a
+    /// proof that verifies against the plan's public inputs.
+    fn proof_plans_roundtrip_and_prove() {
+        let (spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+        let (output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = ProofRng::from_seed([5u8; 32]);
+
+        let plan = spend_plan(
+            &mut rng,
+            &test_spend_key(b'p'),
+            7,
+            Fr::from(13u64),
+            Fr::from(11u64),
+        );
+        let plan = SpendProofPlan::decode(plan.encode_to_vec().as_slice()).unwrap();
+        let public_inputs = plan.public_inputs();
+        let proof = plan.prove(&mut rng, &spend_pk).unwrap();
+        assert!(proof
+            .verify(
+                &spend_vk,
+                public_inputs.anchor,
+                public_inputs.balance_commitment,
+                public_inputs.nullifier,
+                plan.rk,
+            )
+            .is_ok());
+
+        let plan = output_plan(&mut rng, 7, Fr::from(11u64));
+        let plan = OutputProofPlan::decode(plan.encode_to_vec().as_slice()).unwrap();
+        let public_inputs = plan.public_inputs();
+        let proof = plan.prove(&mut rng, &output_pk).unwrap();
+        assert!(proof
+            .verify(
+                &output_vk,
+                public_inputs.balance_commitment,
+                public_inputs.note_commitment,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    /// Check that proofs from a proving service are rerandomized, and rejected if they don't
+    /// verify against their plan.
+    fn rerandomizing_prover_checks_proofs() {
+        use futures::executor::block_on;
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let (_, other_vk) = OutputCircuit::generate_test_parameters();
+        let pk = std::sync::Arc::new(pk);
+        // Only outputs are proven, so the output key stands in for the spend key.
+        let local = LocalProver::new(pk.clone(), pk);
+        let plan = output_plan(&mut OsRng, 3, Fr::from(4u64));
+
+        let original = block_on(local.prove_output(plan.clone())).unwrap();
+        let prover = RerandomizingProver::new(local.clone(), vk.clone(), vk.clone());
+        let proof = block_on(prover.prove_output(plan.clone())).unwrap();
+        assert_ne!(proof.to_bytes(), original.to_bytes());
+        assert!(proof
+            .verify(&vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+
+        // With a seeded RNG for each job, proving is reproducible.
+        let seeded = local.clone().with_rng(|| ProofRng::from_seed([7u8; 32]));
+        assert_eq!(
+            block_on(seeded.prove_output(plan.clone()))
+                .unwrap()
+                .to_bytes(),
+            block_on(seeded.prove_output(plan.clone()))
+                .unwrap()
+                .to_bytes()
+        );
+
+        let prover = RerandomizingProver::new(local, other_vk.clone(), other_vk);
+        assert!(block_on(prover.prove_output(plan)).is_err());
+    }
+
+    #[test]
+    /// Check that proving with locked witness memory works when the `mlock` feature is enabled,
+    /// and is refused otherwise.
+    fn output_proof_locked_witness_memory() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+
+        let result = plan.prove_with_options(
+            &ProverOptions::default().with_locked_witness_memory(),
+            &mut rng,
+            &pk,
+        );
+
+        if cfg!(all(feature = "mlock", unix)) {
+            let proof = result.expect("can create proof");
+            assert!(proof
+                .verify(&vk, plan.balance_commitment, plan.note_commitment)
+                .is_ok());
+        } else {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    /// Check that verification failures are reported with the reason they failed.
+    fn output_proof_verification_errors() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let (_spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+
+        assert_eq!(
+            proof.verify(&vk, plan.balance_commitment, plan.note_commitment),
+            Ok(())
+        );
+        assert_eq!(
+            proof.verify(
+                &vk,
+                upenumbra(10).commit(Fr::from(8u64)),
+                plan.note_commitment
+            ),
+            Err(error::VerificationError::ProofInvalid)
+        );
+        assert!(matches!(
+            proof.verify(&spend_vk, plan.balance_commitment, plan.note_commitment),
+            Err(error::VerificationError::VerifyingKeyMismatch(_))
+        ));
+    }
+
+    #[test]
+    /// Check that proving within a memory budget chunks the MSMs without changing the proof, and
+    /// fails when the proving key alone exceeds the budget.
+    fn output_proof_memory_budget() {
+        use ark_serialize::CanonicalSerialize;
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+
+        assert!(plan
+            .prove_with_options(
+                &ProverOptions::default().with_memory_budget(1024),
+                &mut rng,
+                &pk
+            )
+            .is_err());
+
+        let options = ProverOptions::default().with_deterministic_seed([3u8; 32]);
+        let expected = plan
+            .prove_with_options(&options, &mut rng, &pk)
+            .expect("can create proof");
+
+        // A few megabytes beyond the proving key leaves room for only a few thousand bases at a
+        // time, fewer than the output circuit's queries hold.
+        let proof = plan
+            .prove_with_options(
+                &options
+                    .clone()
+                    .with_memory_budget(pk.uncompressed_size() + (16 << 20)),
+                &mut rng,
+                &pk,
+            )
+            .expect("can create proof");
+        assert_eq!(proof.to_bytes(), expected.to_bytes());
+        assert!(proof
+            .verify(&vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+    }
+
+    #[test]
+    /// Check that a strict spend proof verifies with the strict verifying key,
+    /// and only with it.
+    fn strict_spend_proof_verification() {
+        let (pk, vk) = StrictSpendCircuit::generate_test_parameters();
+        let (_spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let SpendProofPlan {
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nullifier: nf,
+            rk,
+        } = spend_plan(
+            &mut rng,
+            &test_spend_key(b's'),
+            10,
+            Fr::from(3u64),
+            Fr::from(5u64),
+        );
+
+        let proof = SpendProof::prove_strict(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nf,
+            rk,
+        )
+        .expect("can create proof");
+
+        assert!(proof
+            .verify(&vk, anchor, balance_commitment, nf, rk)
+            .is_ok());
+        assert!(proof
+            .verify(&spend_vk, anchor, balance_commitment, nf, rk)
+            .is_err());
+    }
+
+    #[test]
+    /// Check that a dummy spend proof verifies against its own public inputs,
+    /// for any anchor and randomized spend authorization key.
+    fn spend_proof_dummy_verification() {
+        let (pk, vk) = SpendCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let sk = test_spend_key(b'd');
+        let rk = RandomizedVerificationKey::try_from(VerificationKey::from(
+            sk.spend_auth_key().randomize(&Fr::from(3u64)),
+        ))
+        .unwrap();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Forget, note::Commitment(Fq::from(1u64)))
+            .unwrap();
+        let anchor = nct.root();
+
+        let (proof, balance_commitment, nullifier) =
+            SpendProof::prove_dummy(&ProverOptions::default(), &mut rng, &pk, anchor, rk)
+                .expect("can create dummy proof");
+        assert!(proof
+            .verify(&vk, anchor, balance_commitment, nullifier, rk)
+            .is_ok());
+
+        let other_rk = RandomizedVerificationKey::try_from(VerificationKey::from(
+            sk.spend_auth_key().randomize(&Fr::from(4u64)),
+        ))
+        .unwrap();
+        assert!(proof
+            .verify(&vk, anchor, balance_commitment, nullifier, other_rk)
+            .is_err());
+    }
+
+    #[test]
+    /// Check that a dummy output proof verifies against its own public inputs,
+    /// but not against a balance commitment to a nonzero value.
+    fn output_proof_dummy_verification() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let (proof, balance_commitment, note_commitment) =
+            OutputProof::dummy(&ProverOptions::default(), &mut rng, &pk)
+                .expect("can create dummy proof");
+        assert!(proof
+            .verify(&vk, balance_commitment, note_commitment)
+            .is_ok());
+
+        let nonzero_commitment = upenumbra(1).commit(Fr::from(1u64));
+        assert!(proof
+            .verify(&vk, nonzero_commitment, note_commitment)
+            .is_err());
+    }
+
+    #[test]
+    /// Check the per-type public input sizes that the circuits'
+    /// `NUM_PUBLIC_INPUTS` are computed from.
+    fn public_input_sizes() {
+        use ark_ff::ToConstraintField;
+
+        assert_eq!(
+            Fq::from(1u64).to_field_elements().unwrap().len(),
+            FQ_PUBLIC_INPUTS
+        );
+        assert_eq!(
+            decaf377::basepoint().to_field_elements().unwrap().len(),
+            ELEMENT_PUBLIC_INPUTS
+        );
+    }
+
+    #[test]
+    /// Check that each circuit's public inputs are encoded for the verifier in
+    /// the order the circuit allocates them.
+    fn public_input_allocation_order() {
+        use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+        fn check<P: PublicInputs>() {
+            let circuit = P::Circuit::sample_circuit();
+            let expected = P::from_circuit(&circuit).to_field_elements().unwrap();
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            let cs = cs.borrow().unwrap();
+            // The first instance variable is the constant one.
+            assert_eq!(
+                cs.instance_assignment[1..],
+                expected[..],
+                "{}",
+                P::Circuit::NAME
+            );
+        }
+
+        check::<SpendPublicInputs>();
+        check::<OutputPublicInputs>();
+        check::<SwapPublicInputs>();
+        check::<SwapClaimPublicInputs>();
+        check::<UndelegateClaimPublicInputs>();
+        check::<DelegatorVotePublicInputs>();
+    }
+
+    #[test]
+    /// Check that an output proof binding a memo key only verifies with the
+    /// commitment to that memo key for its note.
+    fn output_proof_binds_memo_key() {
+        use crate::memo::MemoKeyCommitment;
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let pvk = ark_groth16::prepare_verifying_key(&vk);
+        let mut rng = OsRng;
+
+        let OutputProofPlan {
+            note,
+            v_blinding,
+            balance_commitment,
+            note_commitment,
+        } = output_plan(&mut rng, 10, Fr::from(7u64));
+        let memo_key = crate::PayloadKey::random_key(&mut rng);
+        let memo_key_commitment = MemoKeyCommitment::commit(&memo_key, &note);
+
+        let proof = OutputProof::prove_with_memo_key(
+            &ProverOptions::default(),
+            &mut rng,
+            &pk,
+            note.clone(),
+            v_blinding,
+            memo_key,
+            balance_commitment,
+            note_commitment,
+            memo_key_commitment,
+        )
+        .expect("can create proof");
+        let public_inputs = OutputPublicInputs {
+            balance_commitment,
+            note_commitment,
+            memo_key_commitment: Some(memo_key_commitment),
+        };
+        assert!(proof.verify_public_inputs(&pvk, &public_inputs).is_ok());
+        assert!(proof
+            .verify(&vk, balance_commitment, note_commitment)
+            .is_err());
+
+        let other_key = crate::PayloadKey::random_key(&mut rng);
+        let wrong = OutputPublicInputs {
+            memo_key_commitment: Some(MemoKeyCommitment::commit(&other_key, &note)),
+            ..public_inputs
+        };
+        assert!(proof.verify_public_inputs(&pvk, &wrong).is_err());
+    }
+
+    #[cfg(feature = "op-counts")]
+    #[test]
+    /// Check that the operations of proving and verifying an output proof are counted.
+    fn output_proof_operation_counts() {
+        use metrics::{count_operations, OperationCounts};
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+
+        let ((), counts) = count_operations(|| {
+            let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+            proof
+                .verify(&vk, plan.balance_commitment, plan.note_commitment)
+                .expect("can verify proof");
+        });
+
+        // Each MSM of the prover is over every element of its query but the
+        // first, which the constant one multiplies, except for `h` and `l`.
+        let variables = pk.a_query.len() - 1;
+        let expected = OperationCounts {
+            proofs: 1,
+            verifications: 1,
+            g1_msm_terms: pk.h_query.len() + pk.l_query.len() + 2 * variables,
+            g2_msm_terms: variables,
+            // Blinding `A`, `B` and `C` with the prover's randomness, then
+            // combining the public inputs to verify.
+            g1_scalar_muls: 6 + OutputCircuit::NUM_PUBLIC_INPUTS,
+            g2_scalar_muls: 1,
+            miller_loop_pairs: 3,
+            final_exponentiations: 1,
+            gt_exponentiations: 0,
+        };
+        assert_eq!(counts, expected);
+
+        // Nothing is counted outside of `count_operations`.
+        let ((), counts) = count_operations(|| {});
+        assert_eq!(counts, OperationCounts::default());
+    }
+
+    #[test]
+    /// Check that a spend can be proven with an auth path computed outside of
+    /// this crate's tree, and that invalid auth paths are rejected before proving.
+    fn spend_proof_with_external_auth_path() {
+        use ark_ff::UniformRand;
+
+        let (pk, vk) = SpendCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+
+        let sk = test_spend_key(b'x');
+        let note = test_note(&mut rng, &sk, 10);
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Forget, tct::Commitment(Fq::from(1u64)))
+            .unwrap();
+        nct.insert(tct::Witness::Keep, note.commit()).unwrap();
+        let plan = spend_plan_in(&sk, &nct, note, Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let position = u64::from(plan.note_commitment_proof.position());
+        let auth_path = plan
+            .note_commitment_proof
+            .auth_path()
+            .map(|siblings| siblings.map(Fq::from));
+
+        let prove = |position: u64, auth_path: [[Fq; 3]; 24]| {
+            SpendProof::prove_with_auth_path(
+                &ProverOptions::default(),
+                &mut OsRng,
+                &pk,
+                position,
+                auth_path,
+                plan.note.clone(),
+                plan.v_blinding,
+                plan.spend_auth_randomizer.clone(),
+                plan.ak,
+                plan.nk,
+                plan.anchor,
+                plan.balance_commitment,
+                plan.nullifier,
+                plan.rk,
+            )
+        };
+
+        let proof = prove(position, auth_path).expect("can create proof");
+        proof
+            .verify(
+                &vk,
+                plan.anchor,
+                plan.balance_commitment,
+                plan.nullifier,
+                plan.rk,
+            )
+            .expect("proof verifies");
+
+        let mut wrong_auth_path = auth_path;
+        wrong_auth_path[23][0] += Fq::from(1u64);
+        assert!(prove(position, wrong_auth_path).is_err());
+        assert!(prove(position + 1, auth_path).is_err());
+        assert!(prove(u64::MAX, auth_path).is_err());
+    }
+
+    #[test]
+    /// Check that a proof round-trips through its byte, protobuf and JSON encodings.
+    fn output_proof_encoding_roundtrip() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+
+        let bytes: Vec<u8> = proof.clone().into();
+        assert_eq!(bytes.len(), OUTPUT_PROOF_LEN_BYTES);
+        let decoded = OutputProof::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(OutputProof::try_from(&bytes[1..]).is_err());
+
+        let proto = penumbra_proto::core::crypto::v1alpha1::ZkOutputProof::from(proof.clone());
+        assert_eq!(OutputProof::try_from(proto).unwrap(), proof);
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: OutputProof = serde_json::from_str(&json).unwrap();
+        assert!(decoded
+            .verify(&vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+    }
+
+    #[test]
+    /// Check that a parameter store loads each circuit's key once, from the right source.
+    fn parameter_store_caches_keys() {
+        use ark_serialize::CanonicalSerialize;
+
+        let (pk, _vk) = OutputCircuit::generate_test_parameters();
+        let mut bytes = Vec::new();
+        pk.serialize(&mut bytes).unwrap();
+        let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+
+        let store = ParameterStore::new().with_embedded(OutputCircuit::NAME, bytes);
+        let output_pk = store.get::<OutputCircuit>().unwrap();
+        assert_eq!(*output_pk, pk);
+        assert!(std::sync::Arc::ptr_eq(
+            &output_pk,
+            &store.get::<OutputCircuit>().unwrap()
+        ));
+        assert!(store.get::<SpendCircuit>().is_err());
+
+        // Parameters for the wrong circuit are rejected.
+        let store = ParameterStore::new().with_embedded(SwapCircuit::NAME, bytes);
+        assert!(store.get::<SwapCircuit>().is_err());
+
+        // Keys are checked against their checksum, if one is given.
+        use sha2::{Digest, Sha256};
+        let checksum: [u8; 32] = Sha256::digest(bytes).into();
+        let store = ParameterStore::new()
+            .with_embedded(OutputCircuit::NAME, bytes)
+            .with_checksum(OutputCircuit::NAME, checksum);
+        assert_eq!(store.verifying_key::<OutputCircuit>().unwrap(), pk.vk);
+        let store = ParameterStore::new()
+            .with_embedded(OutputCircuit::NAME, bytes)
+            .with_checksum(OutputCircuit::NAME, [0; 32]);
+        assert!(store.get::<OutputCircuit>().is_err());
+    }
+
+    #[test]
+    /// Check that a proving key cache enforces checksums, and evicts the least recently used
+    /// unpinned key.
+    fn proving_key_cache_checks_and_evicts_keys() {
+        use ark_serialize::CanonicalSerialize;
+        use futures::executor::block_on;
+        use sha2::{Digest, Sha256};
+
+        let encode = |pk: &ark_groth16::ProvingKey<decaf377::Bls12_377>| {
+            let mut bytes = Vec::new();
+            pk.serialize(&mut bytes).unwrap();
+            let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+            bytes
+        };
+        let output_bytes = encode(&OutputCircuit::generate_test_parameters().0);
+        let swap_bytes = encode(&SwapCircuit::generate_test_parameters().0);
+        let keys = EmbeddedKeys::new()
+            .with_key(OutputCircuit::NAME, output_bytes)
+            .with_key(SwapCircuit::NAME, swap_bytes);
+
+        let cache = ProvingKeyCache::new(1)
+            .with_provider(EmbeddedKeys::new())
+            .with_provider(keys.clone())
+            .with_checksum(OutputCircuit::NAME, Sha256::digest(output_bytes).into())
+            .with_checksum(SwapCircuit::NAME, Sha256::digest(swap_bytes).into())
+            .require_checksums();
+
+        let output_pk = block_on(cache.pin::<OutputCircuit>()).unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            &output_pk,
+            &block_on(cache.get::<OutputCircuit>()).unwrap()
+        ));
+        // Loading another key evicts nothing while the only loaded key is pinned...
+        block_on(cache.get::<SwapCircuit>()).unwrap();
+        assert_eq!(cache.loaded(), vec![OutputCircuit::NAME, SwapCircuit::NAME]);
+        // ...but unpinning it brings the cache back to its capacity.
+        cache.unpin::<OutputCircuit>();
+        assert_eq!(cache.loaded(), vec![SwapCircuit::NAME]);
+        assert!(cache.evict::<SwapCircuit>());
+        assert!(cache.loaded().is_empty());
+
+        // Keys without a checksum, or with the wrong one, are refused.
+        assert!(block_on(cache.get::<SpendCircuit>()).is_err());
+        let cache = ProvingKeyCache::new(1)
+            .with_provider(keys)
+            .with_checksum(OutputCircuit::NAME, [0; 32]);
+        assert!(block_on(cache.get::<OutputCircuit>()).is_err());
+        assert!(cache.loaded().is_empty());
+
+        let remote = RemoteKeys::s3("params", "us-east-1", "v1/", |_url| {
+            Box::pin(async { Ok(None) })
+        });
+        assert_eq!(
+            remote.url(OutputCircuit::NAME),
+            format!(
+                "https://params.s3.us-east-1.amazonaws.com/v1/{}_pk.bin",
+                OutputCircuit::NAME
+            )
+        );
+    }
+
+    #[test]
+    /// Check that warming up a proving key through a parameter store loads it, once.
+    fn parameter_store_warms_up_keys() {
+        let store = ParameterStore::new().with_test_parameters();
+        store.warm_up::<OutputCircuit>().unwrap();
+        let pk = store.get::<OutputCircuit>().unwrap();
+        pk.warm_up();
+        assert!(std::sync::Arc::ptr_eq(
+            &pk,
+            &store.get::<OutputCircuit>().unwrap()
+        ));
+    }
+
+    #[test]
+    /// Check that the byte-oriented wrappers for browser wallets prove and verify output proofs.
+    fn byte_wrappers_prove_and_verify() {
+        use ark_serialize::CanonicalSerialize;
+        use decaf377::FieldExt;
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let (mut pk_bytes, mut vk_bytes) = (Vec::new(), Vec::new());
+        pk.serialize(&mut pk_bytes).unwrap();
+        vk.serialize(&mut vk_bytes).unwrap();
+
+        let plan = output_plan(&mut OsRng, 10, Fr::from(7u64));
+        let balance_commitment = plan.balance_commitment.to_bytes();
+        let note_commitment = plan.note_commitment.0.to_bytes();
+
+        let proof = wasm::prove_output(
+            &pk_bytes,
+            &[1u8; 32],
+            &plan.note.to_bytes(),
+            &plan.v_blinding.to_bytes(),
+            &balance_commitment,
+            &note_commitment,
+        )
+        .unwrap();
+        assert!(wasm::verify_output_proof(
+            &vk_bytes,
+            &proof,
+            &balance_commitment,
+            &note_commitment
+        )
+        .is_ok());
+
+        let other_commitment = note::Commitment(Fq::from(1u64)).0.to_bytes();
+        assert!(wasm::verify_output_proof(
+            &vk_bytes,
+            &proof,
+            &balance_commitment,
+            &other_commitment
+        )
+        .is_err());
+        assert!(
+            wasm::verify_output_proof(&vk_bytes, &proof, &balance_commitment, &[0u8; 31]).is_err()
+        );
+    }
+
+    #[test]
+    /// Check that chained ceremony contributions verify and still produce working parameters.
+    fn ceremony_contributions() {
+        use ceremony::{Contribution, Transcript};
+
+        let (initial, _vk) = OutputCircuit::generate_test_parameters();
+        let first = Contribution::make(&initial, [1u8; 32]);
+        let second = Contribution::make(&first.params, [2u8; 32]);
+        assert!(Contribution::verify(&initial, &first).is_ok());
+        assert!(Contribution::verify(&first.params, &second).is_ok());
+        assert!(Contribution::verify(&initial, &second).is_err());
+
+        let mut tampered = second.clone();
+        tampered.params.l_query[0] = first.params.l_query[0];
+        assert!(Contribution::verify(&first.params, &tampered).is_err());
+
+        let bytes = second.to_bytes();
+        assert_eq!(Contribution::from_bytes(&bytes).unwrap(), second);
+        assert!(Contribution::from_bytes(&bytes[1..]).is_err());
+        assert!(Contribution::from_bytes(&[&bytes[..], &[0u8]].concat()).is_err());
+
+        let mut transcript = Transcript::new(&initial);
+        let start = transcript.hash();
+        transcript.append(&first);
+        let after_first = transcript.hash();
+        transcript.append(&second);
+        assert_ne!(start, after_first);
+        assert_ne!(after_first, transcript.hash());
+
+        let pk = second.params;
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+        assert!(proof
+            .verify(&pk.vk, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+    }
+
+    #[test]
+    /// Check that a persisted prepared verifying key round-trips and verifies proofs.
+    fn prepared_verifying_key_roundtrip() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+
+        let pvk = ark_groth16::prepare_verifying_key(&vk);
+        let bytes = prepared_verifying_key_to_bytes(&pvk).unwrap();
+        let loaded = prepared_verifying_key_from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.vk, vk);
+        assert_eq!(loaded.alpha_g1_beta_g2, pvk.alpha_g1_beta_g2);
+        assert!(proof
+            .verify_prepared(&loaded, plan.balance_commitment, plan.note_commitment)
+            .is_ok());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(prepared_verifying_key_from_bytes(&trailing).is_err());
+        assert!(prepared_verifying_key_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    /// Check that proofs verified on a verification pool resolve to their results.
+    fn verification_pool() {
+        let (pk, vk) = OutputCircuit::generate_test_parameters();
+        let pvk = std::sync::Arc::new(ark_groth16::prepare_verifying_key(&vk));
+        let pool = verification::Pool::new(2).unwrap();
+        assert_eq!(pool.threads(), 2);
+        assert!(verification::Pool::new(0).is_err());
+
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(7u64));
+        let proof = plan.prove(&mut rng, &pk).expect("can create proof");
+        let balance_commitment = plan.balance_commitment;
+
+        let valid = pool.verify({
+            let (proof, pvk) = (proof.clone(), pvk.clone());
+            let note_commitment = plan.note_commitment;
+            move || proof.verify_prepared(&pvk, balance_commitment, note_commitment)
+        });
+        let invalid = pool.verify(move || {
+            proof.verify_prepared(&pvk, balance_commitment, note::Commitment(Fq::from(1u64)))
+        });
+        assert!(futures::executor::block_on(valid).is_ok());
+        assert_eq!(
+            futures::executor::block_on(invalid),
+            Err(error::VerificationError::ProofInvalid)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "debug-asserts")]
+    #[should_panic(expected = "output proof input mismatch: the balance commitment")]
+    /// Check that proving with a public input the witnesses don't determine panics, naming it.
+    fn debug_asserts_name_mismatched_input() {
+        let (pk, _vk) = OutputCircuit::generate_test_parameters();
+        let mut rng = OsRng;
+        let plan = output_plan(&mut rng, 10, Fr::from(1u64));
+
+        let _ = OutputProofPlan {
+            balance_commitment: upenumbra(10).commit(Fr::from(2u64)),
+            ..plan
+        }
+        .prove(&mut rng, &pk);
+    }
+
+    #[test]
+    /// Check that a batch of valid proofs verifies, and that an invalid proof is identified.
+    fn batch_verification() {
+        let (spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+        let (output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+        let spend_pvk = ark_groth16::prepare_verifying_key(&spend_vk);
+        let output_pvk = ark_groth16::prepare_verifying_key(&output_vk);
+        let mut rng = OsRng;
+
+        let spend = spend_plan(
+            &mut rng,
+            &test_spend_key(b'b'),
+            10,
+            Fr::from(3u64),
+            Fr::from(5u64),
+        );
+        let spend_proof = spend.prove(&mut rng, &spend_pk).expect("can create proof");
+
+        let outputs = (0..2u64)
+            .map(|i| {
+                let plan = output_plan(&mut rng, 10, Fr::from(i));
+                let proof = plan.prove(&mut rng, &output_pk).expect("can create proof");
+                (proof, plan.balance_commitment, plan.note_commitment)
+            })
+            .collect::<Vec<_>>();
+
+        let batch = |wrong_output: Option<usize>| {
+            let mut batch = BatchVerifier::new(&spend_pvk, &output_pvk).unwrap();
+            batch
+                .queue_spend(
+                    &spend_proof,
+                    spend.anchor,
+                    spend.balance_commitment,
+                    spend.nullifier,
+                    spend.rk,
+                )
+                .unwrap();
+            for (i, (proof, balance_commitment, note_commitment)) in outputs.iter().enumerate() {
+                let note_commitment = if wrong_output == Some(i) {
+                    outputs[(i + 1) % outputs.len()].2
+                } else {
+                    *note_commitment
+                };
+                batch
+                    .queue_output(proof, *balance_commitment, note_commitment)
+                    .unwrap();
+            }
+            batch
+        };
+
+        assert_eq!(batch(None).len(), 3);
+        assert!(batch(None).verify(&mut rng).is_ok());
+        assert_eq!(
+            batch(Some(1)).verify(&mut rng),
+            Err(InvalidProof {
+                index: 2,
+                kind: ProofKind::Output,
+            })
+        );
+    }
+
+    #[test]
+    /// Check that a block's proofs and signatures verify together, that an invalid signature is
+    /// identified, and that legacy signatures are accepted only while the signing policy allows
+    /// them.
+    fn block_verification() {
+        let (spend_pk, spend_vk) = SpendCircuit::generate_test_parameters();
+        let (_output_pk, output_vk) = OutputCircuit::generate_test_parameters();
+        let spend_pvk = ark_groth16::prepare_verifying_key(&spend_vk);
+        let output_pvk = ark_groth16::prepare_verifying_key(&output_vk);
+        let mut rng = OsRng;
+
+        let sk = test_spend_key(b'c');
+        let spend_auth_randomizer = Fr::from(3u64);
+        let v_blinding = Fr::from(5u64);
+        let spend = spend_plan(&mut rng, &sk, 10, spend_auth_randomizer, v_blinding);
+        let spend_proof = spend.prove(&mut rng, &spend_pk).expect("can create proof");
+
+        let effect_hash = [7u8; 64];
+        let rsk = sk.spend_auth_key().randomize(&spend_auth_randomizer);
+        let auth_sig = signing::sign::<signing::SpendAuthorization, _>(rng, &rsk, &effect_hash);
+        let binding_key = SigningKey::<Binding>::from(v_blinding);
+        let binding_sig =
+            signing::sign::<signing::BalanceBinding, _>(rng, &binding_key, &effect_hash);
+
+        let legacy_binding_sig = binding_key.sign(rng, &effect_hash);
+
+        let block_under = |policy, signed: &[u8], binding_sig| {
+            let mut block = BlockVerifier::new(&spend_pvk, &output_pvk, policy).unwrap();
+            block
+                .queue_spend_proof(
+                    &spend_proof,
+                    spend.anchor,
+                    spend.balance_commitment,
+                    spend.nullifier,
+                    spend.rk,
+                )
+                .unwrap();
+            block.queue_spend_auth(&spend.rk, &effect_hash, auth_sig);
+            block.queue_binding((&binding_key).into(), signed, binding_sig);
+            block
+        };
+        let block = |signed: &[u8]| block_under(SigningPolicy::RequireContext, signed, binding_sig);
+
+        assert_eq!(block(&effect_hash).signatures(), 2);
+        assert!(block(&effect_hash).verify(&mut rng).is_ok());
+        assert_eq!(
+            block(b"another effect hash").verify(&mut rng),
+            Err(InvalidBlock::Signature {
+                index: 1,
+                kind: SignatureKind::Binding,
+            })
+        );
+
+        assert!(
+            block_under(SigningPolicy::AllowLegacy, &effect_hash, legacy_binding_sig)
+                .verify(&mut rng)
+                .is_ok()
+        );
+        assert_eq!(
+            block_under(
+                SigningPolicy::RequireContext,
+                &effect_hash,
+                legacy_binding_sig
+            )
+            .verify(&mut rng),
+            Err(InvalidBlock::Signature {
+                index: 1,
+                kind: SignatureKind::Binding,
+            })
+        );
+    }
 }
@@ -0,0 +1,108 @@
+use ark_groth16::{PreparedVerifyingKey, Proof};
+use decaf377::{Bls12_377, Fq};
+use penumbra_proof_verify as verify;
+use penumbra_tct as tct;
+use rand::{CryptoRng, Rng};
+
+use crate::{
+    balance,
+    keys::RandomizedVerificationKey,
+    note,
+    proofs::groth16::{
+        error::VerificationError, OutputCircuit, OutputProof, OutputPublicInputs, ParameterSetup,
+        PublicInputs, SpendCircuit, SpendProof, SpendPublicInputs,
+    },
+    Nullifier,
+};
+
+pub use verify::{InvalidProof, ProofKind};
+
+/// Verifies many [`SpendProof`]s and [`OutputProof`]s at once.
+///
+/// This queues proofs with their typed public inputs into a
+/// [`penumbra_proof_verify::BatchVerifier`], which describes how the batch is
+/// checked.
+pub struct BatchVerifier<'a> {
+    inner: verify::BatchVerifier<'a>,
+}
+
+impl<'a> BatchVerifier<'a> {
+    /// Create an empty batch, to be verified with the given keys.
+    pub fn new(
+        spend_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+        output_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+    ) -> anyhow::Result<Self> {
+        SpendCircuit::check_verifying_key(&spend_pvk.vk)?;
+        OutputCircuit::check_verifying_key(&output_pvk.vk)?;
+        Ok(Self {
+            inner: verify::BatchVerifier::new(spend_pvk, output_pvk),
+        })
+    }
+
+    /// Queue a spend proof with its public inputs.
+    ///
+    /// Returns an error, without queueing the proof, if the public inputs are
+    /// malformed.
+    pub fn queue_spend(
+        &mut self,
+        proof: &SpendProof,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> Result<(), VerificationError> {
+        let public_inputs = SpendPublicInputs {
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk: rk.element(),
+        }
+        .to_field_elements()?;
+        self.queue(ProofKind::Spend, proof.0.clone(), public_inputs);
+        Ok(())
+    }
+
+    /// Queue an output proof with its public inputs.
+    ///
+    /// As for [`OutputProof::verify`], the proof must not bind a memo key.
+    ///
+    /// Returns an error, without queueing the proof, if the public inputs are
+    /// malformed.
+    pub fn queue_output(
+        &mut self,
+        proof: &OutputProof,
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> Result<(), VerificationError> {
+        let public_inputs = OutputPublicInputs {
+            balance_commitment,
+            note_commitment,
+            memo_key_commitment: None,
+        }
+        .to_field_elements()?;
+        self.queue(ProofKind::Output, proof.0.clone(), public_inputs);
+        Ok(())
+    }
+
+    fn queue(&mut self, kind: ProofKind, proof: Proof<Bls12_377>, inputs: Vec<Fq>) {
+        self.inner.queue(kind, proof, inputs);
+    }
+
+    /// The number of queued proofs.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether no proofs are queued.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Verify every queued proof.
+    ///
+    /// If the batch does not verify, each proof is verified individually, and
+    /// the first invalid one is returned.
+    pub fn verify<R: CryptoRng + Rng>(self, rng: &mut R) -> Result<(), InvalidProof> {
+        self.inner.verify(rng)
+    }
+}
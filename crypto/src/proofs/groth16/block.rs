@@ -0,0 +1,268 @@
+use ark_groth16::PreparedVerifyingKey;
+use decaf377::Bls12_377;
+use decaf377_rdsa::{batch, Binding, Signature, SpendAuth, VerificationKey, VerificationKeyBytes};
+use penumbra_tct as tct;
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    balance,
+    keys::RandomizedVerificationKey,
+    note,
+    proofs::groth16::{
+        error::VerificationError, BatchVerifier, InvalidProof, OutputProof, SpendProof,
+    },
+    signing::{self, BalanceBinding, SigningPolicy, SpendAuthorization},
+    Nullifier,
+};
+
+/// The kind of a signature queued in a [`BlockVerifier`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureKind {
+    SpendAuth,
+    Binding,
+}
+
+impl std::fmt::Display for SignatureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureKind::SpendAuth => f.write_str("spend auth"),
+            SignatureKind::Binding => f.write_str("binding"),
+        }
+    }
+}
+
+/// The error returned when a block fails to verify, identifying the first offending proof or
+/// signature.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidBlock {
+    #[error(transparent)]
+    Proof(#[from] InvalidProof),
+    #[error("{kind} signature at index {index} in the block did not verify")]
+    Signature {
+        /// The position of the signature in the block, in the order it was queued.
+        index: usize,
+        /// The kind of the signature.
+        kind: SignatureKind,
+    },
+}
+
+enum SignatureItem {
+    SpendAuth {
+        vk: VerificationKey<SpendAuth>,
+        signature: Signature<SpendAuth>,
+        effect_hash: Vec<u8>,
+    },
+    Binding {
+        vk: VerificationKey<Binding>,
+        signature: Signature<Binding>,
+        effect_hash: Vec<u8>,
+    },
+}
+
+impl SignatureItem {
+    fn kind(&self) -> SignatureKind {
+        match self {
+            SignatureItem::SpendAuth { .. } => SignatureKind::SpendAuth,
+            SignatureItem::Binding { .. } => SignatureKind::Binding,
+        }
+    }
+
+    fn verify(&self, policy: SigningPolicy) -> bool {
+        match self {
+            SignatureItem::SpendAuth {
+                vk,
+                signature,
+                effect_hash,
+            } => signing::verify_under::<SpendAuthorization>(policy, vk, effect_hash, signature)
+                .is_ok(),
+            SignatureItem::Binding {
+                vk,
+                signature,
+                effect_hash,
+            } => {
+                signing::verify_under::<BalanceBinding>(policy, vk, effect_hash, signature).is_ok()
+            }
+        }
+    }
+}
+
+/// Verifies every proof and signature of a block at once.
+///
+/// The proofs are checked as a [`BatchVerifier`] batch, and the spend auth and binding signatures
+/// as a single `decaf377-rdsa` batch, which combines every signature's verification equation with
+/// a random weight into one multiscalar multiplication. The two batches are verified in parallel,
+/// with weights drawn from one seed, so verifying a block costs one multi-pairing and one
+/// multiscalar multiplication, rather than one of each per transaction.
+///
+/// If either batch fails, its items are checked one by one to find the invalid one. The batch only
+/// holds signatures made in their signing context, so while the [`SigningPolicy`] still accepts
+/// legacy signatures over the raw message, a block containing one takes the individual path.
+pub struct BlockVerifier<'a> {
+    proofs: BatchVerifier<'a>,
+    signatures: Vec<SignatureItem>,
+    policy: SigningPolicy,
+}
+
+impl<'a> BlockVerifier<'a> {
+    /// Create an empty block verifier, verifying proofs with the given keys, and signatures under
+    /// `policy`.
+    pub fn new(
+        spend_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+        output_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+        policy: SigningPolicy,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            proofs: BatchVerifier::new(spend_pvk, output_pvk)?,
+            signatures: Vec::new(),
+            policy,
+        })
+    }
+
+    /// Queue a spend proof with its public inputs.
+    ///
+    /// Returns an error, without queueing the proof, if the public inputs are malformed.
+    pub fn queue_spend_proof(
+        &mut self,
+        proof: &SpendProof,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> Result<(), VerificationError> {
+        self.proofs
+            .queue_spend(proof, anchor, balance_commitment, nullifier, rk)
+    }
+
+    /// Queue an output proof with its public inputs.
+    ///
+    /// Returns an error, without queueing the proof, if the public inputs are malformed.
+    pub fn queue_output_proof(
+        &mut self,
+        proof: &OutputProof,
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> Result<(), VerificationError> {
+        self.proofs
+            .queue_output(proof, balance_commitment, note_commitment)
+    }
+
+    /// Queue a spend auth signature by `rk` over a transaction's `effect_hash`.
+    ///
+    /// This takes the same validated key as [`Self::queue_spend_proof`], so a spend's key is only
+    /// decoded once for both its proof and its signature.
+    pub fn queue_spend_auth(
+        &mut self,
+        rk: &RandomizedVerificationKey,
+        effect_hash: &[u8],
+        signature: Signature<SpendAuth>,
+    ) {
+        self.signatures.push(SignatureItem::SpendAuth {
+            vk: *rk.verification_key(),
+            signature,
+            effect_hash: effect_hash.to_vec(),
+        });
+    }
+
+    /// Queue a transaction's binding signature by `bvk` over its `effect_hash`.
+    pub fn queue_binding(
+        &mut self,
+        bvk: VerificationKey<Binding>,
+        effect_hash: &[u8],
+        signature: Signature<Binding>,
+    ) {
+        self.signatures.push(SignatureItem::Binding {
+            vk: bvk,
+            signature,
+            effect_hash: effect_hash.to_vec(),
+        });
+    }
+
+    /// The number of queued proofs.
+    pub fn proofs(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// The number of queued signatures.
+    pub fn signatures(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Verify every queued proof and signature.
+    ///
+    /// If the block does not verify, the first invalid proof, or if every proof is valid, the first
+    /// invalid signature, is returned.
+    pub fn verify<R: CryptoRng + Rng>(self, rng: &mut R) -> Result<(), InvalidBlock> {
+        let mut proof_rng = ChaCha20Rng::from_seed(rng.gen());
+        let signature_rng = ChaCha20Rng::from_seed(rng.gen());
+
+        let Self {
+            proofs,
+            signatures,
+            policy,
+        } = self;
+        let (proofs, signatures) = rayon::join(
+            move || proofs.verify(&mut proof_rng),
+            move || verify_signatures(&signatures, policy, signature_rng),
+        );
+
+        proofs?;
+        signatures
+    }
+}
+
+/// Verify `signatures` as one batch, falling back to verifying them individually under `policy` if
+/// it fails.
+fn verify_signatures(
+    signatures: &[SignatureItem],
+    policy: SigningPolicy,
+    rng: ChaCha20Rng,
+) -> Result<(), InvalidBlock> {
+    if signatures.is_empty() {
+        return Ok(());
+    }
+
+    let mut batch = batch::Verifier::new();
+    for item in signatures {
+        let queued: batch::Item = match item {
+            SignatureItem::SpendAuth {
+                vk,
+                signature,
+                effect_hash,
+            } => (
+                VerificationKeyBytes::<SpendAuth>::from(vk.to_bytes()),
+                *signature,
+                &signing::context_message::<SpendAuthorization>(effect_hash),
+            )
+                .into(),
+            SignatureItem::Binding {
+                vk,
+                signature,
+                effect_hash,
+            } => (
+                VerificationKeyBytes::<Binding>::from(vk.to_bytes()),
+                *signature,
+                &signing::context_message::<BalanceBinding>(effect_hash),
+            )
+                .into(),
+        };
+        batch.queue(queued);
+    }
+    if batch.verify(rng).is_ok() {
+        return Ok(());
+    }
+
+    tracing::debug!(
+        signatures = signatures.len(),
+        "batch signature verification failed, verifying signatures individually"
+    );
+    match signatures.iter().position(|item| !item.verify(policy)) {
+        Some(index) => Err(InvalidBlock::Signature {
+            index,
+            kind: signatures[index].kind(),
+        }),
+        // Every signature is valid under the policy, either because the batch failed spuriously,
+        // which happens with negligible probability, or because some are legacy signatures.
+        None => Ok(()),
+    }
+}
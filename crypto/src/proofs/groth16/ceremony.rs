@@ -0,0 +1,315 @@
+//! Tooling for the phase 2 multi-party ceremony generating Groth16 parameters.
+//!
+//! Each participant takes the parameters left by the previous one and
+//! multiplies their `delta` by a secret scalar, dividing the `H` and `L`
+//! queries by the same scalar. As long as a single participant destroys their
+//! secret, nobody can forge proofs with the final parameters.
+//!
+//! Every circuit (spend, output, swap, ...) has its own ceremony, starting from
+//! parameters generated for that circuit. A [`Contribution`] carries the new
+//! parameters along with a proof that its contributor knows the secret they
+//! applied, and anyone can check it against the previous parameters with
+//! [`Contribution::verify`]. The published contribution files are chained
+//! together by a [`Transcript`].
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_groth16::ProvingKey;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use decaf377::{Bls12_377, Fq};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use rayon::prelude::*;
+use zeroize::Zeroize;
+
+type G1Affine = <Bls12_377 as PairingEngine>::G1Affine;
+type G1Projective = <Bls12_377 as PairingEngine>::G1Projective;
+
+/// The header at the start of every contribution file: a magic string and a
+/// version byte.
+const HEADER: [u8; 4] = *b"PCC\x01";
+
+/// An error verifying or decoding a contribution.
+#[derive(Debug, thiserror::Error)]
+pub enum CeremonyError {
+    #[error("invalid contribution encoding: {0}")]
+    InvalidEncoding(String),
+    #[error("contribution changed the {0}, which must be left as they were")]
+    ChangedParameters(&'static str),
+    #[error("contribution sets delta to zero")]
+    ZeroDelta,
+    #[error("contributor did not prove knowledge of their secret")]
+    InvalidKnowledgeProof,
+    #[error("delta was not updated consistently in G1 and G2")]
+    InconsistentDelta,
+    #[error("H and L queries were not updated by the same secret as delta")]
+    InconsistentQueries,
+}
+
+/// A participant's contribution to the ceremony: the updated parameters, and
+/// a proof of knowledge of the secret that updated them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contribution {
+    /// The parameters after this contribution.
+    pub params: ProvingKey<Bls12_377>,
+    proof: KnowledgeProof,
+}
+
+impl Contribution {
+    /// Contribute to the ceremony, updating the `previous` parameters with a
+    /// secret derived from `entropy`.
+    ///
+    /// The `entropy` must be sampled freshly and destroyed afterwards: anyone
+    /// who learns it, along with every other participant's, can forge proofs.
+    pub fn make(previous: &ProvingKey<Bls12_377>, mut entropy: [u8; 32]) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(entropy);
+        entropy.zeroize();
+
+        let mut delta = Fq::rand(&mut rng);
+        while delta.is_zero() {
+            delta = Fq::rand(&mut rng);
+        }
+        let mut delta_inverse = delta.inverse().expect("delta is nonzero");
+
+        let mut params = previous.clone();
+        params.delta_g1 = previous.delta_g1.mul(delta.into_repr()).into_affine();
+        params.vk.delta_g2 = previous.vk.delta_g2.mul(delta.into_repr()).into_affine();
+        params.h_query = scale(&previous.h_query, delta_inverse);
+        params.l_query = scale(&previous.l_query, delta_inverse);
+
+        let proof = KnowledgeProof::new(&mut rng, &previous.delta_g1, &params.delta_g1, delta);
+        delta.zeroize();
+        delta_inverse.zeroize();
+
+        Self { params, proof }
+    }
+
+    /// Check that the `next` contribution correctly updates the `previous`
+    /// parameters.
+    ///
+    /// This checks that only `delta` and the `H` and `L` queries changed, that
+    /// they were all updated by the same secret, and that the contributor knew
+    /// that secret.
+    pub fn verify(
+        previous: &ProvingKey<Bls12_377>,
+        next: &Contribution,
+    ) -> Result<(), CeremonyError> {
+        let params = &next.params;
+
+        let unchanged = [
+            (
+                "verifying key",
+                previous.vk.alpha_g1 == params.vk.alpha_g1
+                    && previous.vk.beta_g2 == params.vk.beta_g2
+                    && previous.vk.gamma_g2 == params.vk.gamma_g2
+                    && previous.vk.gamma_abc_g1 == params.vk.gamma_abc_g1,
+            ),
+            ("beta", previous.beta_g1 == params.beta_g1),
+            ("A query", previous.a_query == params.a_query),
+            (
+                "B queries",
+                previous.b_g1_query == params.b_g1_query
+                    && previous.b_g2_query == params.b_g2_query,
+            ),
+            (
+                "query lengths",
+                previous.h_query.len() == params.h_query.len()
+                    && previous.l_query.len() == params.l_query.len(),
+            ),
+        ];
+        if let Some((name, _)) = unchanged.iter().find(|(_, unchanged)| !unchanged) {
+            return Err(CeremonyError::ChangedParameters(name));
+        }
+
+        if params.delta_g1.is_zero() || params.vk.delta_g2.is_zero() {
+            return Err(CeremonyError::ZeroDelta);
+        }
+
+        if !next.proof.verify(&previous.delta_g1, &params.delta_g1) {
+            return Err(CeremonyError::InvalidKnowledgeProof);
+        }
+
+        // The new delta is the previous one times the secret in both groups if
+        // e(delta_1', delta_2) = e(delta_1, delta_2').
+        if Bls12_377::pairing(params.delta_g1, previous.vk.delta_g2)
+            != Bls12_377::pairing(previous.delta_g1, params.vk.delta_g2)
+        {
+            return Err(CeremonyError::InconsistentDelta);
+        }
+
+        // Each query element was divided by the secret if e(q', delta_2') =
+        // e(q, delta_2). All of them are checked at once with a random linear
+        // combination, whose weights are derived from the contribution itself
+        // so that the contributor can't choose them.
+        let mut rng = ChaCha20Rng::from_seed(next.hash());
+        let weights = (0..previous.h_query.len() + previous.l_query.len())
+            .map(|_| Fq::rand(&mut rng).into_repr())
+            .collect::<Vec<_>>();
+        let combine = |params: &ProvingKey<Bls12_377>| {
+            let bases = [&params.h_query[..], &params.l_query[..]].concat();
+            VariableBaseMSM::multi_scalar_mul(&bases, &weights).into_affine()
+        };
+        if Bls12_377::pairing(combine(params), params.vk.delta_g2)
+            != Bls12_377::pairing(combine(previous), previous.vk.delta_g2)
+        {
+            return Err(CeremonyError::InconsistentQueries);
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the contribution as a contribution file.
+    ///
+    /// The encoding is deterministic: the header, then the proof of knowledge
+    /// and the parameters in their compressed `ark-serialize` encodings.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = HEADER.to_vec();
+        self.proof
+            .commitment
+            .serialize(&mut bytes)
+            .expect("can serialize to a vector");
+        self.proof
+            .response
+            .serialize(&mut bytes)
+            .expect("can serialize to a vector");
+        self.params
+            .serialize(&mut bytes)
+            .expect("can serialize to a vector");
+        bytes
+    }
+
+    /// Decode a contribution file written by [`Contribution::to_bytes`].
+    ///
+    /// This does not verify the contribution, which must be done against the
+    /// previous parameters with [`Contribution::verify`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CeremonyError> {
+        let invalid = |err: ark_serialize::SerializationError| {
+            CeremonyError::InvalidEncoding(err.to_string())
+        };
+
+        let mut reader = bytes
+            .strip_prefix(&HEADER[..])
+            .ok_or_else(|| CeremonyError::InvalidEncoding("missing header".to_string()))?;
+        let commitment = G1Affine::deserialize(&mut reader).map_err(invalid)?;
+        let response = Fq::deserialize(&mut reader).map_err(invalid)?;
+        let params = ProvingKey::deserialize(&mut reader).map_err(invalid)?;
+        if !reader.is_empty() {
+            return Err(CeremonyError::InvalidEncoding(format!(
+                "{} trailing bytes",
+                reader.len()
+            )));
+        }
+
+        Ok(Self {
+            params,
+            proof: KnowledgeProof {
+                commitment,
+                response,
+            },
+        })
+    }
+
+    /// The hash of the contribution file.
+    pub fn hash(&self) -> [u8; 32] {
+        blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(b"Penumbra_CerCont")
+            .hash(&self.to_bytes())
+            .as_bytes()
+            .try_into()
+            .expect("hash is 32 bytes")
+    }
+}
+
+/// A running hash of a ceremony's contributions, which participants publish
+/// so that everyone can check that their contribution was included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transcript {
+    hash: [u8; 32],
+}
+
+impl Transcript {
+    /// Start the transcript of a ceremony for the given initial parameters.
+    pub fn new(initial: &ProvingKey<Bls12_377>) -> Self {
+        let mut bytes = Vec::new();
+        initial
+            .serialize(&mut bytes)
+            .expect("can serialize to a vector");
+        Self {
+            hash: transcript_hash(&[0u8; 32], &bytes),
+        }
+    }
+
+    /// Add the next contribution to the transcript.
+    pub fn append(&mut self, contribution: &Contribution) {
+        self.hash = transcript_hash(&self.hash, &contribution.hash());
+    }
+
+    /// The hash of the transcript so far.
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+}
+
+fn transcript_hash(previous: &[u8; 32], next: &[u8]) -> [u8; 32] {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"Penumbra_CerTrns")
+        .to_state()
+        .update(previous)
+        .update(next)
+        .finalize()
+        .as_bytes()
+        .try_into()
+        .expect("hash is 32 bytes")
+}
+
+/// A Schnorr proof of knowledge of the secret `delta` with `next = delta * previous`.
+#[derive(Clone, Debug, PartialEq)]
+struct KnowledgeProof {
+    commitment: G1Affine,
+    response: Fq,
+}
+
+impl KnowledgeProof {
+    fn new(rng: &mut ChaCha20Rng, previous: &G1Affine, next: &G1Affine, delta: Fq) -> Self {
+        let mut nonce = Fq::rand(rng);
+        let commitment = previous.mul(nonce.into_repr()).into_affine();
+        let challenge = Self::challenge(previous, next, &commitment);
+        let response = nonce + challenge * delta;
+        nonce.zeroize();
+        Self {
+            commitment,
+            response,
+        }
+    }
+
+    fn verify(&self, previous: &G1Affine, next: &G1Affine) -> bool {
+        let challenge = Self::challenge(previous, next, &self.commitment);
+        previous.mul(self.response.into_repr())
+            == self.commitment.into_projective() + next.mul(challenge.into_repr())
+    }
+
+    fn challenge(previous: &G1Affine, next: &G1Affine, commitment: &G1Affine) -> Fq {
+        let mut bytes = Vec::new();
+        for point in [previous, next, commitment] {
+            point
+                .serialize(&mut bytes)
+                .expect("can serialize to a vector");
+        }
+        let hash = blake2b_simd::Params::new()
+            .personal(b"Penumbra_CerePoK")
+            .hash(&bytes);
+        Fq::from_le_bytes_mod_order(hash.as_bytes())
+    }
+}
+
+/// Multiply each of the `points` by `scalar`.
+fn scale(points: &[G1Affine], scalar: Fq) -> Vec<G1Affine> {
+    let scalar = scalar.into_repr();
+    let scaled = points
+        .par_iter()
+        .map(|point| point.mul(scalar))
+        .collect::<Vec<_>>();
+    G1Projective::batch_normalization_into_affine(&scaled)
+}
@@ -0,0 +1,507 @@
+use std::{borrow::Borrow, str::FromStr};
+
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    Bls12_377, Fq, Fr,
+};
+use decaf377::{Element, FieldExt};
+
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_proof_verify as verify;
+use penumbra_tct as tct;
+use rand::{CryptoRng, Rng};
+
+use crate::proofs::groth16::{
+    error::{self, VerificationError},
+    gadgets, metrics,
+    traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS},
+    witness::SecretBytes,
+    CircuitDescription, Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable,
+    VariableKind,
+};
+use crate::proofs::ProofRng;
+use crate::{
+    asset::FromAmount,
+    keys::{NullifierKey, RandomizedVerificationKey, SeedPhrase, SpendAuthRandomizer, SpendKey},
+    Note, Nullifier, Rseed, Value,
+};
+
+// Public:
+// * anchor (the note commitment tree root at the proposal's snapshot)
+// * nf (nullifier of the note voting)
+// * rk (randomized spend verification key)
+// * value (amount and asset ID of the note voting)
+//
+// Witnesses:
+// * the note, and its inclusion proof
+// * spend_auth_randomizer
+// * ak, nk
+
+/// Groth16 proof that a voter controls a note in the note commitment tree at a
+/// past anchor, without revealing which note.
+///
+/// Unlike a spend, the note's value is revealed, so that the vote can be
+/// weighted by it, and the note is not consumed: its nullifier is revealed
+/// only so that the same note cannot vote twice on a proposal.
+#[derive(Clone, Debug)]
+pub struct DelegatorVoteCircuit {
+    // Witnesses
+    /// Inclusion proof for the note commitment.
+    note_commitment_proof: tct::Proof,
+    /// The note being voted with.
+    note: Note,
+    /// The randomizer used for generating the randomized spend auth key.
+    spend_auth_randomizer: SpendAuthRandomizer,
+    /// The spend authorization key.
+    ak: VerificationKey<SpendAuth>,
+    /// The nullifier deriving key.
+    nk: NullifierKey,
+
+    // Public inputs
+    /// the merkle root of the note commitment tree at the proposal's snapshot.
+    pub anchor: tct::Root,
+    /// nullifier of the note voting.
+    pub nullifier: Nullifier,
+    /// the randomized verification spend key.
+    pub rk: Element,
+}
+
+impl ConstraintSynthesizer<Fq> for DelegatorVoteCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let note_commitment_var =
+            FqVar::new_witness(cs.clone(), || Ok(self.note_commitment_proof.commitment().0))?;
+        let position_fq: Fq = Fq::from(u64::from(self.note_commitment_proof.position()));
+        let position_var = FqVar::new_witness(cs.clone(), || Ok(position_fq))?;
+        let merkle_path_var =
+            tct::r1cs::MerkleAuthPathVar::new(cs.clone(), self.note_commitment_proof)?;
+
+        let note_blinding_var = FqVar::new_witness(cs.clone(), || Ok(self.note.note_blinding()))?;
+        let diversified_generator_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+                Ok(self.note.diversified_generator().clone())
+            })?;
+        let transmission_key_s_var =
+            FqVar::new_witness(cs.clone(), || Ok(self.note.transmission_key_s().clone()))?;
+        let element_transmission_key = decaf377::Encoding(self.note.transmission_key().0)
+            .vartime_decompress()
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let transmission_key_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || Ok(element_transmission_key))?;
+        let clue_key_var = FqVar::new_witness(cs.clone(), || {
+            Ok(Fq::from_le_bytes_mod_order(&self.note.clue_key().0[..]))
+        })?;
+        let spend_auth_randomizer_arr = SecretBytes::new(self.spend_auth_randomizer.to_bytes());
+        let spend_auth_randomizer_var: Vec<UInt8<Fq>> =
+            UInt8::new_witness_vec(cs.clone(), &spend_auth_randomizer_arr)?;
+        let ak_bytes = Fq::from_bytes(*self.ak.as_ref())
+            .expect("verification key is valid, so its byte encoding is a decaf377 s value");
+        let ak_var = FqVar::new_witness(cs.clone(), || Ok(ak_bytes))?;
+        let ak_point = decaf377::Encoding(*self.ak.as_ref())
+            .vartime_decompress()
+            .unwrap();
+        let ak_element_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || Ok(ak_point))?;
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(*self.nk.expose_secret()))?;
+
+        // Public inputs
+        let anchor_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.anchor)))?;
+        let nullifier_var = FqVar::new_input(cs.clone(), || Ok(self.nullifier.0))?;
+        let rk_var = ElementVar::new_input(cs.clone(), || Ok(self.rk))?;
+        let value_amount_var =
+            FqVar::new_input(cs.clone(), || Ok(Fq::from_amount(self.note.value().amount)))?;
+        let value_asset_id_var = FqVar::new_input(cs.clone(), || Ok(self.note.value().asset_id.0))?;
+
+        let rk_fq_var = rk_var.compress_to_field()?;
+
+        // There are no dummy votes: every constraint is always enforced.
+        let enforce = Boolean::TRUE;
+
+        metrics::gadget(&cs, "note_commitment_integrity", || {
+            gadgets::note_commitment_integrity(
+                cs.clone(),
+                &enforce,
+                note_blinding_var,
+                value_amount_var,
+                value_asset_id_var,
+                diversified_generator_var.clone(),
+                transmission_key_s_var,
+                clue_key_var,
+                note_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "merkle_auth_path", || {
+            merkle_path_var.verify(
+                cs.clone(),
+                &enforce,
+                position_var.clone(),
+                anchor_var,
+                note_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "rk_integrity", || {
+            gadgets::rk_integrity(
+                cs.clone(),
+                &enforce,
+                ak_element_var.clone(),
+                spend_auth_randomizer_var,
+                rk_fq_var,
+            )
+        })?;
+        metrics::gadget(&cs, "ak_integrity", || {
+            gadgets::ak_integrity(&enforce, &ak_var, &ak_element_var)
+        })?;
+        metrics::gadget(&cs, "ivk_integrity", || {
+            gadgets::ivk_integrity(
+                cs.clone(),
+                &enforce,
+                ak_var,
+                nk_var.clone(),
+                transmission_key_var,
+                diversified_generator_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "diversified_basepoint_not_identity", || {
+            gadgets::diversified_basepoint_not_identity(
+                cs.clone(),
+                &enforce,
+                diversified_generator_var,
+            )
+        })?;
+        metrics::gadget(&cs, "ak_not_identity", || {
+            gadgets::ak_not_identity(cs.clone(), &enforce, ak_element_var)
+        })?;
+        metrics::gadget(&cs, "nullifier_integrity", || {
+            gadgets::nullifier_integrity(
+                cs.clone(),
+                &enforce,
+                note_commitment_var,
+                nk_var,
+                position_var,
+                nullifier_var,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl DelegatorVoteCircuit {
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::Always;
+        use VariableKind::{Bytes, Element, Fq, MerkleAuthPath};
+
+        CircuitDescription {
+            name: "delegator_vote",
+            public_inputs: vec![
+                Variable::new("anchor", Fq),
+                Variable::new("nullifier", Fq),
+                Variable::new("rk", Element),
+                Variable::new("value_amount", Fq),
+                Variable::new("value_asset_id", Fq),
+            ],
+            witnesses: vec![
+                Variable::new("note_commitment", Fq),
+                Variable::new("position", Fq),
+                Variable::new("merkle_path", MerkleAuthPath),
+                Variable::new("note_blinding", Fq),
+                Variable::new("diversified_generator", Element),
+                Variable::new("transmission_key_s", Fq),
+                Variable::new("transmission_key", Element),
+                Variable::new("clue_key", Fq),
+                Variable::new("spend_auth_randomizer", Bytes { len: 32 }),
+                Variable::new("ak", Fq),
+                Variable::new("ak_element", Element),
+                Variable::new("nk", Fq),
+            ],
+            gadgets: vec![
+                Gadget::new(
+                    "note_commitment_integrity",
+                    Always,
+                    &[
+                        "note_blinding",
+                        "value_amount",
+                        "value_asset_id",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "note_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "merkle_auth_path",
+                    Always,
+                    &["position", "anchor", "note_commitment", "merkle_path"],
+                ),
+                Gadget::new(
+                    "rk_integrity",
+                    Always,
+                    &["ak_element", "spend_auth_randomizer", "rk"],
+                ),
+                Gadget::new("ak_integrity", Always, &["ak", "ak_element"]),
+                Gadget::new(
+                    "ivk_integrity",
+                    Always,
+                    &["ak", "nk", "transmission_key", "diversified_generator"],
+                ),
+                Gadget::new(
+                    "diversified_basepoint_not_identity",
+                    Always,
+                    &["diversified_generator"],
+                ),
+                Gadget::new("ak_not_identity", Always, &["ak_element"]),
+                Gadget::new(
+                    "nullifier_integrity",
+                    Always,
+                    &["note_commitment", "nk", "position", "nullifier"],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the delegator vote circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DelegatorVotePublicInputs {
+    /// the merkle root of the note commitment tree at the proposal's snapshot.
+    pub anchor: tct::Root,
+    /// nullifier of the note voting.
+    pub nullifier: Nullifier,
+    /// the randomized verification spend key.
+    pub rk: Element,
+    /// the value of the note voting, which weights the vote.
+    pub value: Value,
+}
+
+impl PublicInputs for DelegatorVotePublicInputs {
+    type Circuit = DelegatorVoteCircuit;
+
+    fn from_circuit(circuit: &DelegatorVoteCircuit) -> Self {
+        Self {
+            anchor: circuit.anchor,
+            nullifier: circuit.nullifier,
+            rk: circuit.rk,
+            value: circuit.note.value(),
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let mut public_inputs = Vec::new();
+        public_inputs.extend(error::public_input("anchor", &Fq::from(self.anchor.0))?);
+        public_inputs.extend(error::public_input("nullifier", &self.nullifier.0)?);
+        public_inputs.extend(error::public_input("rk", &self.rk)?);
+        public_inputs.push(Fq::from_amount(self.value.amount));
+        public_inputs.push(self.value.asset_id.0);
+        debug_assert_eq!(public_inputs.len(), DelegatorVoteCircuit::NUM_PUBLIC_INPUTS);
+        Ok(public_inputs)
+    }
+}
+
+impl ParameterSetup for DelegatorVoteCircuit {
+    const NAME: &'static str = "delegator_vote";
+
+    // anchor, nullifier, rk, value amount, value asset ID
+    const NUM_PUBLIC_INPUTS: usize = 4 * FQ_PUBLIC_INPUTS + ELEMENT_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
+        let seed_phrase = SeedPhrase::from_randomness([b'f'; 32]);
+        let sk_voter = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let (address, _dtk_d) = sk_voter
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let spend_auth_randomizer = SpendAuthRandomizer::from(Fr::from(1));
+        let rsk = spend_auth_randomizer.randomize_signing_key(sk_voter.spend_auth_key());
+        let nk = *sk_voter.nullifier_key();
+        let ak = sk_voter.spend_auth_key().into();
+        let note = Note::from_parts(
+            address,
+            Value::from_str("1upenumbra").expect("valid value"),
+            Rseed([1u8; 32]),
+        )
+        .expect("can make a note");
+        let rk: VerificationKey<SpendAuth> = rsk.into();
+        let element_rk = decaf377::Encoding(rk.to_bytes())
+            .vartime_decompress()
+            .expect("expect only valid element points");
+        let mut nct = tct::Tree::new();
+        let note_commitment = note.commit();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+
+        DelegatorVoteCircuit {
+            note_commitment_proof,
+            note,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            nullifier: Nullifier(Fq::from(1)),
+            rk: element_rk,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelegatorVoteProof(pub(super) Proof<Bls12_377>);
+
+impl DelegatorVoteProof {
+    #![allow(clippy::too_many_arguments)]
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The anchor is usually the root of the note commitment tree at the
+    /// proposal's snapshot, in which case the inclusion proof must be one
+    /// witnessed at that anchor, e.g. by [`tct::Tree::witness_at`]. Returns an
+    /// error if the inclusion proof does not verify against the anchor.
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        note_commitment_proof: tct::Proof,
+        note: Note,
+        spend_auth_randomizer: SpendAuthRandomizer,
+        ak: VerificationKey<SpendAuth>,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<Self> {
+        note_commitment_proof.verify(anchor).map_err(|_| {
+            anyhow::anyhow!(
+                "note commitment proof does not verify against the anchor {}",
+                anchor
+            )
+        })?;
+        let circuit = DelegatorVoteCircuit {
+            note_commitment_proof,
+            note,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            nullifier,
+            rk: rk.element(),
+        };
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok(Self(proof))
+    }
+
+    /// Generate a proof as with [`DelegatorVoteProof::prove`], drawing its randomness
+    /// from a [`ProofRng`], so that the same seed always gives the same proof.
+    pub fn prove_deterministic<P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut ProofRng,
+        pk: P,
+        note_commitment_proof: tct::Proof,
+        note: Note,
+        spend_auth_randomizer: SpendAuthRandomizer,
+        ak: VerificationKey<SpendAuth>,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<Self> {
+        Self::prove(
+            options,
+            rng,
+            pk,
+            note_commitment_proof,
+            note,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            nullifier,
+            rk,
+        )
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The `value` is the value of the note voting, which weights the vote.
+    pub fn verify(
+        &self,
+        vk: &VerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+        value: Value,
+    ) -> Result<(), VerificationError> {
+        DelegatorVoteCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(&processed_pvk, anchor, nullifier, rk, value)
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+        value: Value,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| self.verify(vk, anchor, nullifier, rk, value))
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+        value: Value,
+    ) -> Result<(), VerificationError> {
+        DelegatorVoteCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = DelegatorVotePublicInputs {
+            anchor,
+            nullifier,
+            rk: rk.element(),
+            value,
+        }
+        .to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
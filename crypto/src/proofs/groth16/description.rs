@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+use super::traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS};
+
+/// A machine-readable description of the statement a circuit proves.
+///
+/// This lists the public inputs (in the order they are passed to the verifier),
+/// the witnesses, and the gadgets constraining them, so that auditors and
+/// alternative prover implementations can consume the statement definition
+/// without reading the circuit code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CircuitDescription {
+    /// The name of the circuit.
+    pub name: &'static str,
+    /// The public inputs, in verifier order.
+    pub public_inputs: Vec<Variable>,
+    /// The private witnesses.
+    pub witnesses: Vec<Variable>,
+    /// The gadgets enforcing the statement, in the order they are applied.
+    pub gadgets: Vec<Gadget>,
+}
+
+impl CircuitDescription {
+    /// The number of field elements used to encode the public inputs.
+    pub fn num_public_inputs(&self) -> usize {
+        self.public_inputs
+            .iter()
+            .map(|input| input.kind.num_field_elements())
+            .sum()
+    }
+
+    /// Serialize the description as JSON.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// A variable allocated in a circuit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Variable {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: VariableKind,
+}
+
+impl Variable {
+    pub(crate) fn new(name: &'static str, kind: VariableKind) -> Self {
+        Self { name, kind }
+    }
+}
+
+/// The type of a circuit variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableKind {
+    /// An element of the base field `Fq`.
+    Fq,
+    /// A `decaf377` group element.
+    Element,
+    /// A little-endian byte string of the given length.
+    Bytes { len: usize },
+    /// A merkle authentication path in the tiered commitment tree.
+    MerkleAuthPath,
+}
+
+impl VariableKind {
+    /// The number of field elements used to encode this as a public input.
+    fn num_field_elements(&self) -> usize {
+        match self {
+            VariableKind::Fq => FQ_PUBLIC_INPUTS,
+            VariableKind::Element => ELEMENT_PUBLIC_INPUTS,
+            VariableKind::Bytes { len } => *len,
+            VariableKind::MerkleAuthPath => 0,
+        }
+    }
+}
+
+/// When a gadget's constraints are enforced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// The constraints are always enforced.
+    Always,
+    /// The constraints are only enforced if the value is non-zero, i.e. the
+    /// action is not a dummy.
+    UnlessDummy,
+    /// The constraints are only enforced if the optional public input they
+    /// check is provided, i.e. is non-zero.
+    IfProvided,
+}
+
+/// A gadget applied in a circuit, and the variables it constrains.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Gadget {
+    pub name: &'static str,
+    pub enforcement: Enforcement,
+    pub variables: Vec<&'static str>,
+}
+
+impl Gadget {
+    pub(crate) fn new(
+        name: &'static str,
+        enforcement: Enforcement,
+        variables: &[&'static str],
+    ) -> Self {
+        Self {
+            name,
+            enforcement,
+            variables: variables.to_vec(),
+        }
+    }
+}
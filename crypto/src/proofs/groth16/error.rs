@@ -0,0 +1,16 @@
+//! Errors returned when verifying Groth16 proofs.
+
+use ark_ff::ToConstraintField;
+use decaf377::Fq;
+
+pub use penumbra_proof_verify::VerificationError;
+
+/// Encode the named public input as field elements.
+pub(super) fn public_input<T: ToConstraintField<Fq>>(
+    name: &'static str,
+    input: &T,
+) -> Result<Vec<Fq>, VerificationError> {
+    input
+        .to_field_elements()
+        .ok_or(VerificationError::InvalidPublicInput(name))
+}
@@ -1,17 +1,17 @@
 #![allow(clippy::too_many_arguments)]
 use ark_ff::PrimeField;
-use ark_nonnative_field::NonNativeFieldVar;
-use ark_r1cs_std::{prelude::*, ToBitsGadget};
+use ark_r1cs_std::{prelude::*, uint64::UInt64, ToBitsGadget};
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 use decaf377::{
     r1cs::{ElementVar, FqVar},
-    Element, FieldExt, Fq, Fr,
+    Element, Fq,
 };
 use once_cell::sync::Lazy;
 
 use crate::{
     asset::VALUE_GENERATOR_DOMAIN_SEP, balance::commitment::VALUE_BLINDING_GENERATOR,
-    keys::IVK_DOMAIN_SEP, note::NOTECOMMIT_DOMAIN_SEP, nullifier::NULLIFIER_DOMAIN_SEP,
+    dex::swap::DOMAIN_SEPARATOR as SWAP_DOMAIN_SEP, hash, memo::MEMO_KEY_COMMITMENT_DOMAIN_SEP,
+    STAKING_TOKEN_ASSET_ID,
 };
 
 pub(crate) static SPENDAUTH_BASEPOINT: Lazy<Element> = Lazy::new(decaf377::basepoint);
@@ -28,6 +28,40 @@ pub(crate) fn diversified_basepoint_not_identity(
     Ok(())
 }
 
+/// Check that the diversified basepoint is the encode-to-curve map of the
+/// diversifier's hash to the field, as in
+/// [`Diversifier::diversified_generator`](crate::keys::Diversifier::diversified_generator).
+///
+/// The hash itself ([`Diversifier::hash_to_field`](crate::keys::Diversifier::hash_to_field))
+/// is witnessed, since BLAKE2b has no gadget over `Fq`, but `g_d` is no longer
+/// free: it must be in the image of the map.
+pub(crate) fn diversified_generator_integrity(
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    diversifier_hash: &FqVar,
+    g_d: &ElementVar,
+) -> Result<(), SynthesisError> {
+    let test_g_d = ElementVar::encode_to_curve(diversifier_hash)?;
+    g_d.conditional_enforce_equal(&test_g_d, enforce)?;
+    Ok(())
+}
+
+/// Check that the spend auth key witnessed as a field element, which the
+/// incoming viewing key is derived from, is the encoding of the spend auth key
+/// witnessed as a group element, which the randomized key is derived from.
+///
+/// Without this, the two witnesses are independent, and a prover could derive
+/// the address from one key while authorizing with another.
+pub(crate) fn ak_integrity(
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    ak: &FqVar,
+    ak_element: &ElementVar,
+) -> Result<(), SynthesisError> {
+    ak.conditional_enforce_equal(&ak_element.compress_to_field()?, enforce)?;
+    Ok(())
+}
+
 /// Check the spend auth key is not identity.
 pub(crate) fn ak_not_identity(
     cs: ConstraintSystemRef<Fq>,
@@ -41,9 +75,13 @@ pub(crate) fn ak_not_identity(
 }
 
 /// Check the integrity of the value commitment.
+///
+/// If `is_required` is set, the value is committed with a negative sign, i.e.
+/// it is consumed rather than provided by the action.
 pub(crate) fn value_commitment_integrity(
     cs: ConstraintSystemRef<Fq>,
     enforce: &Boolean<Fq>,
+    is_required: &Boolean<Fq>,
     // Witnesses
     value_amount: Vec<UInt8<Fq>>,
     value_asset_id: FqVar,
@@ -51,18 +89,83 @@ pub(crate) fn value_commitment_integrity(
     // Public inputs,
     commitment: ElementVar,
 ) -> Result<(), SynthesisError> {
-    let value_generator = FqVar::new_constant(cs.clone(), *VALUE_GENERATOR_DOMAIN_SEP)?;
-    let value_blinding_generator = ElementVar::new_constant(cs.clone(), *VALUE_BLINDING_GENERATOR)?;
+    balance_commitment_integrity(
+        cs,
+        enforce,
+        vec![BalanceTerm {
+            is_required: is_required.clone(),
+            amount: value_amount,
+            asset_id: value_asset_id,
+        }],
+        BalanceBlinding::Factor(value_blinding),
+        commitment,
+    )
+}
 
-    let hashed_asset_id = poseidon377::r1cs::hash_1(cs, &value_generator, value_asset_id)?;
-    let asset_generator = ElementVar::encode_to_curve(&hashed_asset_id)?;
-    let test_commitment = asset_generator.scalar_mul_le(value_amount.to_bits_le()?.iter())?
-        + value_blinding_generator.scalar_mul_le(value_blinding.to_bits_le()?.iter())?;
+/// A single value in a balance commitment, which is provided, or subtracted
+/// from the balance if `is_required`.
+pub(crate) struct BalanceTerm {
+    pub(crate) is_required: Boolean<Fq>,
+    pub(crate) amount: Vec<UInt8<Fq>>,
+    pub(crate) asset_id: FqVar,
+}
+
+/// What blinds a balance commitment.
+pub(crate) enum BalanceBlinding {
+    /// A blinding factor, which is multiplied by the value blinding generator.
+    Factor(Vec<UInt8<Fq>>),
+    /// Another commitment, e.g. to a fee, whose blinding factor also blinds
+    /// the terms added to it.
+    Commitment(ElementVar),
+}
+
+/// Check the integrity of a commitment to a balance of several values, as in
+/// [`Balance::commit`](crate::Balance::commit): the sum of each term's signed
+/// value, plus the blinding factor times the value blinding generator, or plus
+/// the blinding commitment.
+pub(crate) fn balance_commitment_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    terms: Vec<BalanceTerm>,
+    blinding: BalanceBlinding,
+    // Public inputs
+    commitment: ElementVar,
+) -> Result<(), SynthesisError> {
+    let mut test_commitment = match blinding {
+        BalanceBlinding::Factor(blinding) => {
+            let value_blinding_generator =
+                ElementVar::new_constant(cs.clone(), *VALUE_BLINDING_GENERATOR)?;
+            value_blinding_generator.scalar_mul_le(blinding.to_bits_le()?.iter())?
+        }
+        BalanceBlinding::Commitment(commitment) => commitment,
+    };
+    for term in terms {
+        let value_point = value_point(cs.clone(), term.amount, term.asset_id)?;
+        let signed_value_point = term
+            .is_required
+            .select(&value_point.negate()?, &value_point)?;
+        test_commitment = test_commitment + signed_value_point;
+    }
 
     commitment.conditional_enforce_equal(&test_commitment, enforce)?;
     Ok(())
 }
 
+/// Compute the unblinded commitment to a value, `value_amount` times the value generator for
+/// `value_asset_id`.
+fn value_point(
+    cs: ConstraintSystemRef<Fq>,
+    value_amount: Vec<UInt8<Fq>>,
+    value_asset_id: FqVar,
+) -> Result<ElementVar, SynthesisError> {
+    let value_generator = FqVar::new_constant(cs.clone(), *VALUE_GENERATOR_DOMAIN_SEP)?;
+
+    let hashed_asset_id = poseidon377::r1cs::hash_1(cs, &value_generator, value_asset_id)?;
+    let asset_generator = ElementVar::encode_to_curve(&hashed_asset_id)?;
+    asset_generator.scalar_mul_le(value_amount.to_bits_le()?.iter())
+}
+
 /// Check the integrity of the note commitment.
 pub(crate) fn note_commitment_integrity(
     cs: ConstraintSystemRef<Fq>,
@@ -77,19 +180,84 @@ pub(crate) fn note_commitment_integrity(
     // Public inputs
     commitment: FqVar,
 ) -> Result<(), SynthesisError> {
-    let value_blinding_generator = FqVar::new_constant(cs.clone(), *NOTECOMMIT_DOMAIN_SEP)?;
-
     let compressed_g_d = diversified_generator.compress_to_field()?;
-    let commitment_test = poseidon377::r1cs::hash_6(
+    let commitment_test = hash::NOTE_COMMITMENT.hash_var(
         cs,
-        &value_blinding_generator,
-        (
+        [
             note_blinding,
             value_amount,
             value_asset_id,
             compressed_g_d,
             transmission_key_s,
             clue_key,
+        ],
+    )?;
+
+    commitment.conditional_enforce_equal(&commitment_test, enforce)?;
+    Ok(())
+}
+
+/// Check the integrity of the memo key commitment, binding the memo key to the
+/// note with the note's blinding factor.
+pub(crate) fn memo_key_commitment_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    note_blinding: FqVar,
+    memo_key_lo: FqVar,
+    memo_key_hi: FqVar,
+    // Public input
+    commitment: FqVar,
+) -> Result<(), SynthesisError> {
+    let domain_separator = FqVar::new_constant(cs.clone(), *MEMO_KEY_COMMITMENT_DOMAIN_SEP)?;
+
+    let commitment_test = poseidon377::r1cs::hash_3(
+        cs,
+        &domain_separator,
+        (note_blinding, memo_key_lo, memo_key_hi),
+    )?;
+
+    commitment.conditional_enforce_equal(&commitment_test, enforce)?;
+    Ok(())
+}
+
+/// Check the integrity of the swap commitment.
+pub(crate) fn swap_commitment_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    rseed: FqVar,
+    claim_fee_amount: FqVar,
+    claim_fee_asset_id: FqVar,
+    diversified_generator: ElementVar,
+    transmission_key_s: FqVar,
+    clue_key: FqVar,
+    asset_1: FqVar,
+    asset_2: FqVar,
+    delta_1: FqVar,
+    delta_2: FqVar,
+    // Public input
+    commitment: FqVar,
+) -> Result<(), SynthesisError> {
+    let domain_separator = FqVar::new_constant(cs.clone(), *SWAP_DOMAIN_SEP)?;
+
+    let inner = poseidon377::r1cs::hash_4(
+        cs.clone(),
+        &domain_separator,
+        (asset_1, asset_2, delta_1, delta_2),
+    )?;
+    let compressed_g_d = diversified_generator.compress_to_field()?;
+    let commitment_test = poseidon377::r1cs::hash_7(
+        cs,
+        &domain_separator,
+        (
+            rseed,
+            claim_fee_amount,
+            claim_fee_asset_id,
+            compressed_g_d,
+            transmission_key_s,
+            clue_key,
+            inner,
         ),
     )?;
 
@@ -97,6 +265,207 @@ pub(crate) fn note_commitment_integrity(
     Ok(())
 }
 
+/// Check that the balance commitment of a swap is the fee commitment, minus the
+/// (unblinded) input amounts of the swap.
+pub(crate) fn swap_balance_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    delta_1: Vec<UInt8<Fq>>,
+    asset_1: FqVar,
+    delta_2: Vec<UInt8<Fq>>,
+    asset_2: FqVar,
+    // Public inputs
+    fee_commitment: ElementVar,
+    balance_commitment: ElementVar,
+) -> Result<(), SynthesisError> {
+    balance_commitment_integrity(
+        cs,
+        enforce,
+        vec![
+            BalanceTerm {
+                is_required: Boolean::TRUE,
+                amount: delta_1,
+                asset_id: asset_1,
+            },
+            BalanceTerm {
+                is_required: Boolean::TRUE,
+                amount: delta_2,
+                asset_id: asset_2,
+            },
+        ],
+        BalanceBlinding::Commitment(fee_commitment),
+        balance_commitment,
+    )
+}
+
+/// Witness the low 64 bits of `value`, checking (if `enforce`) that they are all
+/// of `value`, i.e. that `value` fits in a `u64`.
+///
+/// Returns the little-endian bits of `value`.
+pub(crate) fn u64_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    value: &FqVar,
+) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    let low_limb = value
+        .value()
+        .map(|value| value.into_repr().as_ref()[0])
+        .unwrap_or_default();
+    let bits = UInt64::new_witness(cs, || Ok(low_limb))?.to_bits_le();
+
+    let recomposed = Boolean::le_bits_to_fp_var(&bits)?;
+    recomposed.conditional_enforce_equal(value, enforce)?;
+    Ok(bits)
+}
+
+/// The width of an [`Amount`](crate::Amount), in bits.
+pub(crate) const AMOUNT_BITS: usize = 128;
+
+/// Check (if `enforce`) that `amount` is the little-endian value of
+/// `amount_bytes`, and so fits in [`AMOUNT_BITS`] bits.
+///
+/// Circuits use an amount both as a field element, e.g. in a note commitment,
+/// and as bytes, e.g. as the scalar of a value commitment. Without this check
+/// the two need not agree, and the field element could be any value that wraps
+/// around the field modulus.
+pub(crate) fn amount_range_check(
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    amount: &FqVar,
+    amount_bytes: &[UInt8<Fq>],
+) -> Result<(), SynthesisError> {
+    if amount_bytes.len() * 8 != AMOUNT_BITS {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let recomposed = Boolean::le_bits_to_fp_var(&amount_bytes.to_bits_le()?)?;
+    recomposed.conditional_enforce_equal(amount, enforce)?;
+    Ok(())
+}
+
+/// Check that `output` is a claimant's pro rata share of a batch swap's output.
+///
+/// If the swap succeeded, this is `floor(input * batch_output / batch_input)`
+/// (or zero, if `batch_input` is zero), and otherwise it is the `refund` of the
+/// claimant's input in the same asset.
+pub(crate) fn pro_rata_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    success: &Boolean<Fq>,
+    // Witnesses
+    input: FqVar,
+    refund: FqVar,
+    output: FqVar,
+    // Public inputs
+    batch_input: FqVar,
+    batch_output: FqVar,
+) -> Result<(), SynthesisError> {
+    u64_integrity(cs.clone(), &Boolean::TRUE, &output)?;
+
+    let batch_input_is_zero = batch_input.is_eq(&FqVar::zero())?;
+    let divides = success.and(&batch_input_is_zero.not())?;
+
+    // output * batch_input + remainder = input * batch_output,
+    // with 0 <= remainder < batch_input.
+    let remainder = FqVar::new_witness(cs.clone(), || {
+        Ok(input.value()? * batch_output.value()? - output.value()? * batch_input.value()?)
+    })?;
+    (&output * &batch_input + &remainder)
+        .conditional_enforce_equal(&(&input * &batch_output), &divides)?;
+    u64_integrity(cs.clone(), &divides, &remainder)?;
+    let slack = &batch_input - FqVar::one() - &remainder;
+    u64_integrity(cs, &divides, &slack)?;
+
+    output.conditional_enforce_equal(&FqVar::zero(), &success.and(&batch_input_is_zero)?)?;
+    output.conditional_enforce_equal(&refund, &success.not())?;
+    Ok(())
+}
+
+/// Check that `output` is the fixed-point product `floor(input * rate / scale)`.
+///
+/// The `rate` is a fixed-point number with denominator `scale`, so this is the
+/// in-circuit counterpart of multiplying an amount by a rate expressed in, e.g.,
+/// basis points. The `input` must be at most 128 bits, and the `rate` is checked
+/// to fit in a `u64`, so that no product wraps around the field modulus.
+pub(crate) fn fixed_point_mul_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    scale: u64,
+    // Witnesses
+    input: FqVar,
+    output: FqVar,
+    // Public inputs
+    rate: FqVar,
+) -> Result<(), SynthesisError> {
+    u64_integrity(cs.clone(), enforce, &rate)?;
+
+    // output * scale + remainder = input * rate, with 0 <= remainder < scale.
+    let scale = FqVar::new_constant(cs.clone(), Fq::from(scale))?;
+    let remainder = FqVar::new_witness(cs.clone(), || {
+        Ok(input.value()? * rate.value()? - output.value()? * scale.value()?)
+    })?;
+    (&output * &scale + &remainder).conditional_enforce_equal(&(&input * &rate), enforce)?;
+    u64_integrity(cs.clone(), enforce, &remainder)?;
+    let slack = &scale - FqVar::one() - &remainder;
+    u64_integrity(cs, enforce, &slack)?;
+    Ok(())
+}
+
+/// Check that the balance commitment of an undelegate claim consumes the
+/// (unblinded) unbonding amount, and provides the penalized amount of the
+/// staking token.
+pub(crate) fn undelegate_claim_balance_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    enforce: &Boolean<Fq>,
+    // Witnesses
+    unbonding_amount: Vec<UInt8<Fq>>,
+    penalized_amount: Vec<UInt8<Fq>>,
+    balance_blinding: Vec<UInt8<Fq>>,
+    // Public inputs
+    unbonding_id: FqVar,
+    balance_commitment: ElementVar,
+) -> Result<(), SynthesisError> {
+    let staking_token_id = FqVar::new_constant(cs.clone(), STAKING_TOKEN_ASSET_ID.0)?;
+
+    balance_commitment_integrity(
+        cs,
+        enforce,
+        vec![
+            BalanceTerm {
+                is_required: Boolean::TRUE,
+                amount: unbonding_amount,
+                asset_id: unbonding_id,
+            },
+            BalanceTerm {
+                is_required: Boolean::FALSE,
+                amount: penalized_amount,
+                asset_id: staking_token_id,
+            },
+        ],
+        BalanceBlinding::Factor(balance_blinding),
+        balance_commitment,
+    )
+}
+
+/// Check that the commitment at `position` was added to the tree in the block
+/// at `height`, given the number of blocks in each epoch.
+pub(crate) fn position_height_integrity(
+    cs: ConstraintSystemRef<Fq>,
+    // Witness
+    position: &FqVar,
+    // Public inputs
+    epoch_duration: FqVar,
+    height: FqVar,
+) -> Result<(), SynthesisError> {
+    // Positions are packed as 16 bits each of epoch, block and commitment index.
+    let bits = u64_integrity(cs, &Boolean::TRUE, position)?;
+    let block = Boolean::le_bits_to_fp_var(&bits[16..32])?;
+    let epoch = Boolean::le_bits_to_fp_var(&bits[32..48])?;
+
+    (epoch_duration * epoch + block).enforce_equal(&height)?;
+    Ok(())
+}
+
 /// Check integrity of nullifier derivation.
 pub(crate) fn nullifier_integrity(
     cs: ConstraintSystemRef<Fq>,
@@ -108,17 +477,35 @@ pub(crate) fn nullifier_integrity(
     // Public input
     nullifier: FqVar,
 ) -> Result<(), SynthesisError> {
-    let nullifier_constant = FqVar::new_constant(cs.clone(), *NULLIFIER_DOMAIN_SEP)?;
-
-    let computed_nullifier =
-        poseidon377::r1cs::hash_3(cs, &nullifier_constant, (nk, note_commitment, position))?;
+    let computed_nullifier = hash::NULLIFIER.hash_var(cs, [nk, note_commitment, position])?;
 
     nullifier.conditional_enforce_equal(&computed_nullifier, enforce)?;
     Ok(())
 }
 
-/// Check integrity of the diversified address.
-pub(crate) fn diversified_address_integrity(
+/// Derive the incoming viewing key `ivk = poseidon(nk, ak) mod r` from the
+/// spend authorization key `ak` and nullifier key `nk`, as in
+/// [`FullViewingKey`](crate::FullViewingKey), returning the little-endian bits
+/// of the unreduced hash.
+///
+/// The hash is in `Fq`, but `ivk` is a scalar in `Fr`. Rather than witness its
+/// reduction mod `r`, the bits of the hash are used as the scalar directly:
+/// every point in the decaf377 group has order `r`, so multiplying by the hash
+/// is the same as multiplying by `ivk`.
+pub(crate) fn ivk_derivation(
+    cs: ConstraintSystemRef<Fq>,
+    // Witnesses
+    ak: FqVar,
+    nk: FqVar,
+) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    let ivk_mod_q = hash::IVK.hash_var(cs, [nk, ak])?;
+    ivk_mod_q.to_bits_le()
+}
+
+/// Check integrity of the incoming viewing key: that the transmission key of
+/// an address is `[ivk] g_d`, for its diversified generator `g_d` and the
+/// `ivk` derived from `ak` and `nk` by [`ivk_derivation`].
+pub(crate) fn ivk_integrity(
     cs: ConstraintSystemRef<Fq>,
     enforce: &Boolean<Fq>,
     // Witnesses
@@ -127,19 +514,8 @@ pub(crate) fn diversified_address_integrity(
     transmission_key: ElementVar,
     diversified_generator: ElementVar,
 ) -> Result<(), SynthesisError> {
-    let ivk_domain_sep = FqVar::new_constant(cs.clone(), *IVK_DOMAIN_SEP)?;
-    let ivk_mod_q = poseidon377::r1cs::hash_2(cs.clone(), &ivk_domain_sep, (nk, ak))?;
-
-    // Reduce `ivk_mod_q` modulo r
-    let inner_ivk_mod_q: Fq = ivk_mod_q.value().unwrap_or_default();
-    let ivk_mod_r = Fr::from_le_bytes_mod_order(&inner_ivk_mod_q.to_bytes());
-    let ivk =
-        NonNativeFieldVar::<Fr, Fq>::new_variable(cs, || Ok(ivk_mod_r), AllocationMode::Witness)?;
-
-    // Now add constraints to demonstrate the transmission key = [ivk] g_d
-    let ivk_vars = ivk.to_bits_le()?;
-    let test_transmission_key =
-        diversified_generator.scalar_mul_le(ivk_vars.to_bits_le()?.iter())?;
+    let ivk_vars = ivk_derivation(cs, ak, nk)?;
+    let test_transmission_key = diversified_generator.scalar_mul_le(ivk_vars.iter())?;
     transmission_key.conditional_enforce_equal(&test_transmission_key, enforce)?;
     Ok(())
 }
@@ -174,10 +550,15 @@ mod tests {
 
     use super::*;
 
-    use crate::{keys::Diversifier, Address, Note, Rseed, Value};
-    use decaf377::{r1cs::CountConstraints, Bls12_377, Element};
+    use crate::{
+        asset::FromAmount,
+        keys::{Diversifier, SeedPhrase, SpendKey},
+        Address, Note, Rseed, Value,
+    };
+    use decaf377::{r1cs::CountConstraints, Bls12_377, Element, FieldExt, Fr};
     use decaf377_fmd as fmd;
     use decaf377_ka as ka;
+    use decaf377_rdsa::{SpendAuth, VerificationKey};
 
     #[derive(Clone)]
     struct TestNoteCommitmentCircuit {
@@ -198,7 +579,7 @@ mod tests {
             let note_blinding_var =
                 FqVar::new_witness(cs.clone(), || Ok(self.note.note_blinding().clone()))?;
             let value_amount_var =
-                FqVar::new_witness(cs.clone(), || Ok(Fq::from(self.note.value().amount)))?;
+                FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(self.note.value().amount)))?;
             let value_asset_id_var =
                 FqVar::new_witness(cs.clone(), || Ok(self.note.value().asset_id.0))?;
             let diversified_generator_var =
@@ -340,4 +721,390 @@ mod tests {
             assert!(!proof_result);
         }
     }
+
+    /// Allocate the witnesses and public input for a value commitment, and
+    /// check it with the given sign, returning whether the constraints hold.
+    fn value_commitment_satisfied(
+        value: Value,
+        blinding: Fr,
+        commitment: crate::balance::Commitment,
+        is_required: bool,
+    ) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let value_vars = UInt8::new_witness_vec(cs.clone(), &value.amount.to_le_bytes()).unwrap();
+        let asset_id_var = FqVar::new_witness(cs.clone(), || Ok(value.asset_id.0)).unwrap();
+        let blinding_vars = UInt8::new_witness_vec(cs.clone(), &blinding.to_bytes()).unwrap();
+        let commitment_var = ElementVar::new_input(cs.clone(), || Ok(commitment.0)).unwrap();
+        let is_required_var = Boolean::new_witness(cs.clone(), || Ok(is_required)).unwrap();
+
+        value_commitment_integrity(
+            cs.clone(),
+            &Boolean::TRUE,
+            &is_required_var,
+            value_vars,
+            asset_id_var,
+            blinding_vars,
+            commitment_var,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn value_commitment_integrity_respects_sign() {
+        use crate::balance::Sign;
+
+        let value = Value::from_str("7upenumbra").expect("valid value");
+        let blinding = Fr::from(12345u64);
+        let provided = value.commit_signed(Sign::Provided, blinding);
+        let required = value.commit_signed(Sign::Required, blinding);
+
+        assert!(value_commitment_satisfied(value, blinding, provided, false));
+        assert!(value_commitment_satisfied(value, blinding, required, true));
+        assert!(!value_commitment_satisfied(value, blinding, provided, true));
+        assert!(!value_commitment_satisfied(
+            value, blinding, required, false
+        ));
+    }
+
+    /// Allocate the signed values and blinding factor of a balance
+    /// commitment, and check it, returning whether the constraints hold.
+    fn balance_commitment_satisfied(
+        terms: &[(bool, Value)],
+        blinding: Fr,
+        commitment: crate::balance::Commitment,
+    ) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let terms = terms
+            .iter()
+            .map(|(is_required, value)| BalanceTerm {
+                is_required: Boolean::new_witness(cs.clone(), || Ok(*is_required)).unwrap(),
+                amount: UInt8::new_witness_vec(cs.clone(), &value.amount.to_le_bytes()).unwrap(),
+                asset_id: FqVar::new_witness(cs.clone(), || Ok(value.asset_id.0)).unwrap(),
+            })
+            .collect();
+        let blinding_vars = UInt8::new_witness_vec(cs.clone(), &blinding.to_bytes()).unwrap();
+        let commitment_var = ElementVar::new_input(cs.clone(), || Ok(commitment.0)).unwrap();
+
+        balance_commitment_integrity(
+            cs.clone(),
+            &Boolean::TRUE,
+            terms,
+            BalanceBlinding::Factor(blinding_vars),
+            commitment_var,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn balance_commitment_integrity_matches_native() {
+        let provided = Value::from_str("7upenumbra").expect("valid value");
+        let required = Value::from_str("3ugm").expect("valid value");
+        let blinding = Fr::from(12345u64);
+        let commitment = (crate::Balance::from(provided) - required).commit(blinding);
+
+        assert!(balance_commitment_satisfied(
+            &[(false, provided), (true, required)],
+            blinding,
+            commitment
+        ));
+        assert!(!balance_commitment_satisfied(
+            &[(false, provided), (false, required)],
+            blinding,
+            commitment
+        ));
+        assert!(!balance_commitment_satisfied(
+            &[(false, provided)],
+            blinding,
+            commitment
+        ));
+        assert!(balance_commitment_satisfied(
+            &[],
+            blinding,
+            crate::Balance::zero().commit(blinding)
+        ));
+    }
+
+    #[test]
+    fn balance_commitment_integrity_adds_to_blinding_commitment() {
+        let fee = Value::from_str("2upenumbra").expect("valid value");
+        let required = Value::from_str("3ugm").expect("valid value");
+        let blinding = Fr::from(54321u64);
+        let fee_commitment = crate::Balance::from(fee).commit(blinding);
+        let commitment = (crate::Balance::from(fee) - required).commit(blinding);
+
+        let satisfied = |commitment: crate::balance::Commitment| {
+            let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+            let term = BalanceTerm {
+                is_required: Boolean::TRUE,
+                amount: UInt8::new_witness_vec(cs.clone(), &required.amount.to_le_bytes()).unwrap(),
+                asset_id: FqVar::new_witness(cs.clone(), || Ok(required.asset_id.0)).unwrap(),
+            };
+            let fee_var = ElementVar::new_input(cs.clone(), || Ok(fee_commitment.0)).unwrap();
+            let commitment_var = ElementVar::new_input(cs.clone(), || Ok(commitment.0)).unwrap();
+            balance_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                vec![term],
+                BalanceBlinding::Commitment(fee_var),
+                commitment_var,
+            )
+            .unwrap();
+            cs.is_satisfied().unwrap()
+        };
+
+        assert!(satisfied(commitment));
+        assert!(!satisfied(fee_commitment));
+    }
+
+    /// Allocate a diversifier hash and generator as witnesses, and check that
+    /// they agree, returning whether the constraints hold.
+    fn diversified_generator_satisfied(diversifier_hash: Fq, g_d: Element) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let hash_var = FqVar::new_witness(cs.clone(), || Ok(diversifier_hash)).unwrap();
+        let g_d_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || Ok(g_d)).unwrap();
+
+        diversified_generator_integrity(&Boolean::TRUE, &hash_var, &g_d_var).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+        #[test]
+        fn diversified_generator_integrity_matches_native(
+            diversifier_bytes in any::<[u8; 16]>(),
+            other_bytes in any::<[u8; 16]>(),
+        ) {
+            let diversifier = Diversifier(diversifier_bytes);
+            let other = Diversifier(other_bytes);
+            prop_assume!(diversifier != other);
+
+            prop_assert!(diversified_generator_satisfied(
+                diversifier.hash_to_field(),
+                diversifier.diversified_generator()
+            ));
+            prop_assert!(!diversified_generator_satisfied(
+                diversifier.hash_to_field(),
+                other.diversified_generator()
+            ));
+        }
+    }
+
+    /// Allocate the keys and address as witnesses, and check the integrity of
+    /// the incoming viewing key, returning whether the constraints hold.
+    fn ivk_integrity_satisfied(ak: Fq, nk: Fq, address: &Address) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let ak_var = FqVar::new_witness(cs.clone(), || Ok(ak)).unwrap();
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(nk)).unwrap();
+        let transmission_key = decaf377::Encoding(address.transmission_key().0)
+            .vartime_decompress()
+            .unwrap();
+        let transmission_key_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || Ok(transmission_key)).unwrap();
+        let g_d_var: ElementVar = AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+            Ok(*address.diversified_generator())
+        })
+        .unwrap();
+
+        ivk_integrity(
+            cs.clone(),
+            &Boolean::TRUE,
+            ak_var,
+            nk_var,
+            transmission_key_var,
+            g_d_var,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn ivk_integrity_matches_native() {
+        let keys = |randomness| {
+            let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness(randomness), 0);
+            let fvk = sk.full_viewing_key();
+            let ak = Fq::from_bytes(*fvk.spend_verification_key().as_ref()).unwrap();
+            let (address, _dtk) = fvk.incoming().payment_address(7u64.into());
+            (ak, fvk.nullifier_key().0, address)
+        };
+        let (ak, nk, address) = keys([1u8; 32]);
+        let (other_ak, other_nk, other_address) = keys([2u8; 32]);
+
+        assert!(ivk_integrity_satisfied(ak, nk, &address));
+        assert!(ivk_integrity_satisfied(other_ak, other_nk, &other_address));
+        assert!(!ivk_integrity_satisfied(ak, nk, &other_address));
+        assert!(!ivk_integrity_satisfied(other_ak, nk, &address));
+        assert!(!ivk_integrity_satisfied(ak, other_nk, &address));
+    }
+
+    /// Allocate a spend auth key for the incoming viewing key and another for
+    /// the randomized key, as the spend and delegator vote circuits do, and
+    /// check the address and randomized key integrity along with the link
+    /// between the two keys, returning whether the constraints hold.
+    fn ak_witnesses_satisfied(
+        ivk_ak: &VerificationKey<SpendAuth>,
+        rk_ak: &VerificationKey<SpendAuth>,
+        nk: Fq,
+        address: &Address,
+        randomizer: Fr,
+        rk: &VerificationKey<SpendAuth>,
+        enforce: bool,
+    ) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let enforce = Boolean::new_witness(cs.clone(), || Ok(enforce)).unwrap();
+        let ak_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from_bytes(*ivk_ak.as_ref()).unwrap()))
+                .unwrap();
+        let ak_element_var: ElementVar = AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+            Ok(decaf377::Encoding(*rk_ak.as_ref())
+                .vartime_decompress()
+                .unwrap())
+        })
+        .unwrap();
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(nk)).unwrap();
+        let transmission_key_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+                Ok(decaf377::Encoding(address.transmission_key().0)
+                    .vartime_decompress()
+                    .unwrap())
+            })
+            .unwrap();
+        let g_d_var: ElementVar = AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+            Ok(*address.diversified_generator())
+        })
+        .unwrap();
+        let randomizer_var = UInt8::new_witness_vec(cs.clone(), &randomizer.to_bytes()).unwrap();
+        let rk_var =
+            FqVar::new_input(cs.clone(), || Ok(Fq::from_bytes(*rk.as_ref()).unwrap())).unwrap();
+
+        rk_integrity(
+            cs.clone(),
+            &enforce,
+            ak_element_var.clone(),
+            randomizer_var,
+            rk_var,
+        )
+        .unwrap();
+        ak_integrity(&enforce, &ak_var, &ak_element_var).unwrap();
+        ivk_integrity(
+            cs.clone(),
+            &enforce,
+            ak_var,
+            nk_var,
+            transmission_key_var,
+            g_d_var,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    /// Check that a prover who knows only a victim's full viewing key can't
+    /// prove the victim's address with the victim's spend auth key while
+    /// authorizing with their own.
+    fn ak_integrity_links_ak_witnesses() {
+        let victim = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([1u8; 32]), 0);
+        let attacker = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([2u8; 32]), 0);
+        let fvk = victim.full_viewing_key();
+        let (address, _dtk) = fvk.incoming().payment_address(0u64.into());
+        let nk = fvk.nullifier_key().0;
+        let randomizer = Fr::from(3u64);
+        let victim_ak = *fvk.spend_verification_key();
+        let attacker_ak = VerificationKey::from(attacker.spend_auth_key());
+        let victim_rk = VerificationKey::from(victim.spend_auth_key().randomize(&randomizer));
+        let attacker_rk = VerificationKey::from(attacker.spend_auth_key().randomize(&randomizer));
+
+        assert!(ak_witnesses_satisfied(
+            &victim_ak, &victim_ak, nk, &address, randomizer, &victim_rk, true
+        ));
+        assert!(!ak_witnesses_satisfied(
+            &victim_ak,
+            &attacker_ak,
+            nk,
+            &address,
+            randomizer,
+            &attacker_rk,
+            true
+        ));
+        // As in a dummy spend, nothing is enforced if `enforce` is false.
+        assert!(ak_witnesses_satisfied(
+            &victim_ak,
+            &attacker_ak,
+            nk,
+            &address,
+            randomizer,
+            &attacker_rk,
+            false
+        ));
+    }
+
+    #[test]
+    fn ivk_derivation_reduces_mod_r() {
+        // The unreduced hash, as a scalar, gives the same transmission key as
+        // the reduced `ivk` of the native derivation.
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let ak = Fq::from(3u64);
+        let nk = Fq::from(5u64);
+        let ak_var = FqVar::new_witness(cs.clone(), || Ok(ak)).unwrap();
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(nk)).unwrap();
+        let bits = ivk_derivation(cs.clone(), ak_var, nk_var).unwrap();
+
+        let ivk_mod_q = hash::IVK.hash([nk, ak]);
+        let ivk = Fr::from_le_bytes_mod_order(&ivk_mod_q.to_bytes());
+        let g_d = Element::encode_to_curve(&Fq::from(11u64));
+        let g_d_var: ElementVar = ElementVar::new_constant(cs.clone(), g_d).unwrap();
+        let pk_d = g_d_var.scalar_mul_le(bits.iter()).unwrap();
+
+        assert_eq!(pk_d.value().unwrap(), ivk * g_d);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    /// Allocate `amount` and `amount_bytes` as witnesses, and range check
+    /// them, returning whether the constraints hold.
+    fn amount_range_check_satisfied(amount: Fq, amount_bytes: &[u8]) -> bool {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let amount_var = FqVar::new_witness(cs.clone(), || Ok(amount)).unwrap();
+        let amount_vars = UInt8::new_witness_vec(cs.clone(), amount_bytes).unwrap();
+
+        amount_range_check(&Boolean::TRUE, &amount_var, &amount_vars).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn amount_range_check_accepts_in_range_amounts() {
+        for amount in [0u128, 1, u64::MAX as u128, u128::MAX] {
+            assert!(amount_range_check_satisfied(
+                Fq::from(amount),
+                &amount.to_le_bytes()
+            ));
+        }
+    }
+
+    #[test]
+    fn amount_range_check_rejects_out_of_range_amounts() {
+        // 2^128 has the same low 128 bits as zero.
+        let two_to_the_128 = Fq::from(u128::MAX) + Fq::one();
+        assert!(!amount_range_check_satisfied(two_to_the_128, &[0u8; 16]));
+        // -1 wraps around the modulus, so its bytes can't be any amount.
+        assert!(!amount_range_check_satisfied(
+            -Fq::one(),
+            &u128::MAX.to_le_bytes()
+        ));
+        // An in-range amount must still match its bytes.
+        assert!(!amount_range_check_satisfied(
+            Fq::from(7u64),
+            &8u128.to_le_bytes()
+        ));
+    }
+
+    #[test]
+    fn amount_range_check_requires_amount_width() {
+        let cs = ark_relations::r1cs::ConstraintSystem::<Fq>::new_ref();
+        let amount_var = FqVar::new_witness(cs.clone(), || Ok(Fq::from(7u64))).unwrap();
+        let amount_vars = UInt8::new_witness_vec(cs, &7u64.to_le_bytes()).unwrap();
+
+        assert!(amount_range_check(&Boolean::TRUE, &amount_var, &amount_vars).is_err());
+    }
 }
@@ -0,0 +1,371 @@
+//! Fetching proving keys on demand from async code.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use ark_groth16::ProvingKey;
+use decaf377::Bls12_377;
+use futures::future::{self, BoxFuture};
+
+use super::parameters::decode_proving_key;
+use crate::proofs::groth16::ParameterSetup;
+
+/// A source of encoded proving keys, such as a local directory, an object
+/// store, or keys embedded in the binary.
+///
+/// Providers only fetch bytes: checking and decoding keys, and keeping them in
+/// memory, is left to the [`ProvingKeyCache`] using them.
+pub trait KeyProvider: Send + Sync {
+    /// Fetch the compressed `ark-serialize` encoding of the proving key for the
+    /// circuit with the given [`ParameterSetup::NAME`], or `None` if this
+    /// provider doesn't have it.
+    fn fetch<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Vec<u8>>>>;
+}
+
+/// Proving keys embedded in the binary, e.g. with `include_bytes!`.
+#[derive(Clone, Debug, Default)]
+pub struct EmbeddedKeys {
+    keys: HashMap<&'static str, &'static [u8]>,
+}
+
+impl EmbeddedKeys {
+    /// Create a provider with no keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given bytes as the proving key for the circuit with the given
+    /// name.
+    pub fn with_key(mut self, name: &'static str, bytes: &'static [u8]) -> Self {
+        self.keys.insert(name, bytes);
+        self
+    }
+}
+
+impl KeyProvider for EmbeddedKeys {
+    fn fetch<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Vec<u8>>>> {
+        Box::pin(future::ready(Ok(self
+            .keys
+            .get(name)
+            .map(|bytes| bytes.to_vec()))))
+    }
+}
+
+/// Proving keys stored as files `<NAME>_pk.bin` in a local directory, as for
+/// [`ParameterStore::with_directory`](super::ParameterStore::with_directory).
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct DirectoryKeys {
+    directory: PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl DirectoryKeys {
+    /// Read proving keys from files in the given directory.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl KeyProvider for DirectoryKeys {
+    fn fetch<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Vec<u8>>>> {
+        let path = self.directory.join(format!("{}_pk.bin", name));
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        // Keys are hundreds of megabytes, so read them on their own thread
+        // rather than blocking the caller's executor.
+        std::thread::spawn(move || {
+            let result = match std::fs::read(&path) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(anyhow::anyhow!(
+                    "could not read proving key from {}: {}",
+                    path.display(),
+                    err
+                )),
+            };
+            let _ = sender.send(result);
+        });
+        Box::pin(async move { receiver.await.expect("key reads run to completion") })
+    }
+}
+
+/// A function fetching the object at a URL, returning `None` if there is no
+/// such object.
+pub type FetchUrl =
+    dyn Fn(String) -> BoxFuture<'static, anyhow::Result<Option<Vec<u8>>>> + Send + Sync;
+
+/// Proving keys stored as objects `<NAME>_pk.bin` under a base URL, e.g. in an
+/// S3 bucket.
+///
+/// This crate has no HTTP client, so objects are fetched with a function
+/// supplied by the application, which can add whatever credentials or request
+/// signing its object store needs.
+pub struct RemoteKeys {
+    base_url: String,
+    fetch: Box<FetchUrl>,
+}
+
+impl RemoteKeys {
+    /// Fetch proving keys from objects under `base_url` with `fetch`.
+    pub fn new<F>(base_url: impl Into<String>, fetch: F) -> Self
+    where
+        F: Fn(String) -> BoxFuture<'static, anyhow::Result<Option<Vec<u8>>>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        Self {
+            base_url,
+            fetch: Box::new(fetch),
+        }
+    }
+
+    /// Fetch proving keys from objects with the given key prefix in an S3
+    /// bucket, using its virtual-hosted-style URL.
+    pub fn s3<F>(bucket: &str, region: &str, prefix: &str, fetch: F) -> Self
+    where
+        F: Fn(String) -> BoxFuture<'static, anyhow::Result<Option<Vec<u8>>>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self::new(
+            format!(
+                "https://{}.s3.{}.amazonaws.com/{}",
+                bucket,
+                region,
+                prefix.trim_matches('/')
+            ),
+            fetch,
+        )
+    }
+
+    /// The URL of the proving key for the circuit with the given name.
+    pub fn url(&self, name: &str) -> String {
+        format!("{}{}_pk.bin", self.base_url, name)
+    }
+}
+
+impl std::fmt::Debug for RemoteKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteKeys")
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl KeyProvider for RemoteKeys {
+    fn fetch<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Vec<u8>>>> {
+        tracing::debug!(circuit = name, url = %self.url(name), "fetching proving key");
+        (self.fetch)(self.url(name))
+    }
+}
+
+struct Entry {
+    pk: Arc<ProvingKey<Bls12_377>>,
+    last_used: u64,
+    pinned: bool,
+}
+
+#[derive(Default)]
+struct Entries {
+    clock: u64,
+    by_name: HashMap<&'static str, Entry>,
+}
+
+impl Entries {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Evict the least recently used unpinned keys until at most `capacity`
+    /// keys are loaded, or only pinned keys remain.
+    fn evict_to(&mut self, capacity: usize) {
+        while self.by_name.len() > capacity {
+            let oldest = self
+                .by_name
+                .iter()
+                .filter(|(_, entry)| !entry.pinned)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(name, _)| *name);
+            match oldest {
+                Some(name) => {
+                    tracing::debug!(circuit = name, "evicting proving key");
+                    self.by_name.remove(name);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Keeps the proving keys a prover needs in memory, fetching them on demand
+/// from a list of [`KeyProvider`]s.
+///
+/// Each key is fetched from the first provider that has it, checked against
+/// its checksum, if one is registered, and decoded. At most
+/// [`capacity`](ProvingKeyCache::new) keys are kept loaded: when another is
+/// loaded, the least recently used key that is not
+/// [pinned](ProvingKeyCache::pin) is evicted. Evicting a key only drops the
+/// cache's reference to it, so proofs already being made with it are
+/// unaffected.
+///
+/// Unlike a [`ParameterStore`](super::ParameterStore), the cache doesn't hold a
+/// lock while fetching, so concurrent requests for a key that isn't loaded may
+/// each fetch it.
+pub struct ProvingKeyCache {
+    providers: Vec<Box<dyn KeyProvider>>,
+    checksums: HashMap<&'static str, [u8; 32]>,
+    require_checksums: bool,
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl ProvingKeyCache {
+    /// Create a cache keeping up to `capacity` unpinned keys loaded, with no
+    /// providers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            providers: Vec::new(),
+            checksums: HashMap::new(),
+            require_checksums: false,
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Fetch keys from `provider` if no provider added before it has them.
+    pub fn with_provider(mut self, provider: impl KeyProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Require the encoded proving key for the circuit with the given name to
+    /// have the given SHA-256 hash.
+    pub fn with_checksum(mut self, name: &'static str, sha256: [u8; 32]) -> Self {
+        self.checksums.insert(name, sha256);
+        self
+    }
+
+    /// Refuse to load any key without a checksum registered with
+    /// [`with_checksum`](ProvingKeyCache::with_checksum), e.g. when fetching
+    /// keys from a remote store.
+    pub fn require_checksums(mut self) -> Self {
+        self.require_checksums = true;
+        self
+    }
+
+    /// Get the proving key for the circuit `C`, fetching it if it isn't loaded.
+    pub async fn get<C: ParameterSetup>(&self) -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+        self.load::<C>(false).await
+    }
+
+    /// Get the proving key for the circuit `C`, fetching it if it isn't loaded,
+    /// and keep it loaded until it is [unpinned](ProvingKeyCache::unpin).
+    pub async fn pin<C: ParameterSetup>(&self) -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+        self.load::<C>(true).await
+    }
+
+    /// Allow the proving key for the circuit `C` to be evicted again.
+    pub fn unpin<C: ParameterSetup>(&self) {
+        let mut entries = self.lock();
+        if let Some(entry) = entries.by_name.get_mut(C::NAME) {
+            entry.pinned = false;
+        }
+        entries.evict_to(self.capacity);
+    }
+
+    /// Evict the proving key for the circuit `C`, even if it is pinned,
+    /// returning whether it was loaded.
+    pub fn evict<C: ParameterSetup>(&self) -> bool {
+        self.lock().by_name.remove(C::NAME).is_some()
+    }
+
+    /// The names of the circuits whose proving keys are loaded.
+    pub fn loaded(&self) -> Vec<&'static str> {
+        let mut names = self.lock().by_name.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries
+            .lock()
+            .expect("proving key cache is not poisoned")
+    }
+
+    async fn load<C: ParameterSetup>(
+        &self,
+        pin: bool,
+    ) -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+        if let Some(pk) = self.touch(C::NAME, pin) {
+            return Ok(pk);
+        }
+
+        let checksum = self.checksums.get(C::NAME);
+        if checksum.is_none() && self.require_checksums {
+            anyhow::bail!("no checksum registered for the {} proving key", C::NAME);
+        }
+        let bytes = self.fetch(C::NAME).await?;
+        let pk = Arc::new(decode_proving_key::<C>(&bytes, checksum)?);
+
+        let mut entries = self.lock();
+        let last_used = entries.tick();
+        // Another request may have loaded the key while this one was fetching
+        // it; keep the first, so that every caller shares one copy.
+        let entry = entries.by_name.entry(C::NAME).or_insert(Entry {
+            pk,
+            last_used,
+            pinned: false,
+        });
+        entry.last_used = last_used;
+        entry.pinned |= pin;
+        let pk = entry.pk.clone();
+        entries.evict_to(self.capacity);
+        Ok(pk)
+    }
+
+    /// Get a loaded key, marking it used, and pinned if `pin` is set.
+    fn touch(&self, name: &'static str, pin: bool) -> Option<Arc<ProvingKey<Bls12_377>>> {
+        let mut entries = self.lock();
+        let last_used = entries.tick();
+        let entry = entries.by_name.get_mut(name)?;
+        entry.last_used = last_used;
+        entry.pinned |= pin;
+        Some(entry.pk.clone())
+    }
+
+    async fn fetch(&self, name: &'static str) -> anyhow::Result<Vec<u8>> {
+        for provider in &self.providers {
+            if let Some(bytes) = provider.fetch(name).await? {
+                return Ok(bytes);
+            }
+        }
+        anyhow::bail!("no provider has a proving key for the {} circuit", name)
+    }
+}
+
+impl std::fmt::Debug for ProvingKeyCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProvingKeyCache")
+            .field("providers", &self.providers.len())
+            .field("checksums", &self.checksums.keys().collect::<Vec<_>>())
+            .field("require_checksums", &self.require_checksums)
+            .field("capacity", &self.capacity)
+            .field("loaded", &self.loaded())
+            .finish()
+    }
+}
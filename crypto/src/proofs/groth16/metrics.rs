@@ -0,0 +1,408 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{self, Display},
+    ops::{AddAssign, Range, Sub},
+};
+
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal, SynthesisError,
+};
+use decaf377::Fq;
+use penumbra_proof_verify::{self as verify, VerificationCounts};
+use serde::Serialize;
+
+use super::{
+    DelegatorVoteCircuit, OutputCircuit, ParameterSetup, SpendCircuit, StrictSpendCircuit,
+    SwapCircuit, SwapClaimCircuit, UndelegateClaimCircuit,
+};
+
+thread_local! {
+    /// The gadgets synthesized so far by the circuit being measured on this
+    /// thread, if any.
+    static RECORDED_GADGETS: RefCell<Option<Vec<GadgetMetrics>>> = RefCell::new(None);
+
+    /// The gadgets synthesized so far by the circuit being debugged on this
+    /// thread by [`debug_satisfaction`], if any.
+    static TRACED_GADGETS: RefCell<Option<GadgetTrace>> = RefCell::new(None);
+
+    /// The elliptic curve operations performed by proving so far on this
+    /// thread inside [`count_operations`], if any.
+    static RECORDED_OPERATIONS: RefCell<Option<OperationCounts>> = RefCell::new(None);
+}
+
+/// The size of a constraint system, or of the part of it added by a gadget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ConstraintCounts {
+    /// The number of R1CS constraints.
+    pub constraints: usize,
+    /// The number of witness (private) variables.
+    pub witnesses: usize,
+    /// The number of instance (public) variables, including the constant one.
+    pub instances: usize,
+}
+
+impl ConstraintCounts {
+    fn of(cs: &ConstraintSystemRef<Fq>) -> Self {
+        Self {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            instances: cs.num_instance_variables(),
+        }
+    }
+}
+
+impl Sub for ConstraintCounts {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            constraints: self.constraints - other.constraints,
+            witnesses: self.witnesses - other.witnesses,
+            instances: self.instances - other.instances,
+        }
+    }
+}
+
+/// The constraints added by a single application of a gadget.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GadgetMetrics {
+    /// The name of the gadget, as in the circuit's [`CircuitDescription`](super::CircuitDescription).
+    pub label: &'static str,
+    pub counts: ConstraintCounts,
+}
+
+/// The size of a circuit, broken down by the gadgets it applies.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CircuitMetrics {
+    /// The name of the circuit.
+    pub name: &'static str,
+    /// The size of the whole circuit.
+    pub total: ConstraintCounts,
+    /// Each application of a gadget, in the order they are synthesized.
+    pub gadgets: Vec<GadgetMetrics>,
+}
+
+impl CircuitMetrics {
+    /// Measure the circuit `C`, by synthesizing its sample circuit.
+    pub fn measure<C: ParameterSetup>() -> anyhow::Result<Self> {
+        Self::measure_circuit(C::NAME, C::sample_circuit())
+    }
+
+    /// Measure the given circuit, by synthesizing it into a fresh constraint
+    /// system.
+    ///
+    /// The constraint system is optimized for constraints, as when generating
+    /// Groth16 parameters, so the counts match those of the proving key.
+    pub fn measure_circuit<C: ConstraintSynthesizer<Fq>>(
+        name: &'static str,
+        circuit: C,
+    ) -> anyhow::Result<Self> {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+
+        let previous = RECORDED_GADGETS.with(|recorded| recorded.replace(Some(Vec::new())));
+        let result = circuit.generate_constraints(cs.clone());
+        let gadgets = RECORDED_GADGETS
+            .with(|recorded| recorded.replace(previous))
+            .unwrap_or_default();
+        result.map_err(|err| anyhow::anyhow!("could not synthesize {} circuit: {}", name, err))?;
+
+        Ok(Self {
+            name,
+            total: ConstraintCounts::of(&cs),
+            gadgets,
+        })
+    }
+
+    /// The counts for each gadget label, summed over every application of the
+    /// gadget.
+    pub fn by_label(&self) -> BTreeMap<&'static str, ConstraintCounts> {
+        let mut by_label = BTreeMap::<_, ConstraintCounts>::new();
+        for gadget in &self.gadgets {
+            let counts = by_label.entry(gadget.label).or_default();
+            counts.constraints += gadget.counts.constraints;
+            counts.witnesses += gadget.counts.witnesses;
+            counts.instances += gadget.counts.instances;
+        }
+        by_label
+    }
+
+    /// The part of the circuit not added by any gadget, i.e. the allocation of
+    /// its witnesses and public inputs.
+    pub fn unlabelled(&self) -> ConstraintCounts {
+        self.by_label()
+            .into_values()
+            .fold(self.total, |unlabelled, counts| unlabelled - counts)
+    }
+
+    /// Serialize the metrics as JSON.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Measure each of the Groth16 circuits.
+pub fn all_circuit_metrics() -> anyhow::Result<Vec<CircuitMetrics>> {
+    Ok(vec![
+        CircuitMetrics::measure::<SpendCircuit>()?,
+        CircuitMetrics::measure::<StrictSpendCircuit>()?,
+        CircuitMetrics::measure::<OutputCircuit>()?,
+        CircuitMetrics::measure::<SwapCircuit>()?,
+        CircuitMetrics::measure::<SwapClaimCircuit>()?,
+        CircuitMetrics::measure::<DelegatorVoteCircuit>()?,
+        CircuitMetrics::measure::<UndelegateClaimCircuit>()?,
+    ])
+}
+
+/// Synthesize a gadget, recording the constraints it adds under `label` if a
+/// circuit is being measured or debugged on this thread.
+///
+/// Circuits wrap each top-level gadget application in this, using the gadget
+/// names from their [`CircuitDescription`](super::CircuitDescription). Outside
+/// of [`CircuitMetrics::measure`] and [`debug_satisfaction`] it only calls
+/// `synthesize`.
+pub(crate) fn gadget<T>(
+    cs: &ConstraintSystemRef<Fq>,
+    label: &'static str,
+    synthesize: impl FnOnce() -> Result<T, SynthesisError>,
+) -> Result<T, SynthesisError> {
+    let measuring = RECORDED_GADGETS.with(|recorded| recorded.borrow().is_some());
+    let tracing = TRACED_GADGETS.with(|traced| traced.borrow().is_some());
+    if !measuring && !tracing {
+        return synthesize();
+    }
+
+    let before = ConstraintCounts::of(cs);
+    if tracing {
+        TRACED_GADGETS.with(|traced| {
+            if let Some(trace) = traced.borrow_mut().as_mut() {
+                trace.active.push(label);
+            }
+        });
+    }
+    let result = synthesize();
+    let after = ConstraintCounts::of(cs);
+    if tracing {
+        TRACED_GADGETS.with(|traced| {
+            if let Some(trace) = traced.borrow_mut().as_mut() {
+                trace.exit(before.constraints..after.constraints, result.is_err());
+            }
+        });
+    }
+    let result = result?;
+
+    if measuring {
+        RECORDED_GADGETS.with(|recorded| {
+            if let Some(gadgets) = recorded.borrow_mut().as_mut() {
+                gadgets.push(GadgetMetrics {
+                    label,
+                    counts: after - before,
+                });
+            }
+        });
+    }
+    Ok(result)
+}
+
+/// The gadgets synthesized by a circuit, for finding which one added a
+/// constraint.
+#[derive(Debug, Default)]
+struct GadgetTrace {
+    /// The labels of the gadgets being synthesized, outermost first.
+    active: Vec<&'static str>,
+    /// The constraints added by each synthesized gadget, along with the labels
+    /// of the gadget and those enclosing it, in the order they finished, so
+    /// that nested gadgets come before those enclosing them.
+    finished: Vec<(Vec<&'static str>, Range<usize>)>,
+    /// The labels of the gadgets being synthesized when synthesis first failed.
+    failed: Option<Vec<&'static str>>,
+}
+
+impl GadgetTrace {
+    fn exit(&mut self, constraints: Range<usize>, failed: bool) {
+        let labels = self.active.clone();
+        self.active.pop();
+        if failed && self.failed.is_none() {
+            self.failed = Some(labels.clone());
+        }
+        self.finished.push((labels, constraints));
+    }
+
+    /// The labels of the innermost gadget that added the constraint at `index`
+    /// and of the gadgets enclosing it.
+    fn labels_of(&self, index: usize) -> Vec<&'static str> {
+        self.finished
+            .iter()
+            .find(|(_, constraints)| constraints.contains(&index))
+            .map(|(labels, _)| labels.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// The reason a circuit's witness does not satisfy it, and the gadget
+/// responsible, as found by [`debug_satisfaction`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint {
+    /// The labels of the gadget that failed and of the gadgets enclosing it,
+    /// outermost first, or nothing if the failure happened outside of any
+    /// gadget, e.g. while allocating the witnesses.
+    pub gadgets: Vec<&'static str>,
+    /// The index of the first unsatisfied constraint, or `None` if the circuit
+    /// could not be synthesized at all.
+    pub index: Option<usize>,
+    /// A description of the failure.
+    pub reason: String,
+}
+
+impl Display for UnsatisfiedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.gadgets.is_empty() {
+            write!(f, "{} outside of any gadget", self.reason)
+        } else {
+            write!(f, "{} in {}", self.reason, self.gadgets.join("/"))
+        }
+    }
+}
+
+impl std::error::Error for UnsatisfiedConstraint {}
+
+/// Check that the given circuit's witness satisfies it, finding the gadget
+/// that failed if it does not.
+///
+/// Proving an unsatisfied circuit only fails with a [`SynthesisError`], or
+/// produces a proof that does not verify. This instead synthesizes the circuit
+/// into a fresh constraint system, finds the first unsatisfied constraint with
+/// [`ConstraintSystem::which_is_unsatisfied`], and maps it back to the labels
+/// the circuit gave its gadgets. It is much slower than proving, so should only
+/// be used to explain a failure, e.g. in tests and error reports.
+pub fn debug_satisfaction<C: ConstraintSynthesizer<Fq>>(
+    circuit: C,
+) -> Result<(), UnsatisfiedConstraint> {
+    let cs = ConstraintSystem::<Fq>::new_ref();
+
+    let previous = TRACED_GADGETS.with(|traced| traced.replace(Some(GadgetTrace::default())));
+    let result = circuit.generate_constraints(cs.clone());
+    let trace = TRACED_GADGETS
+        .with(|traced| traced.replace(previous))
+        .unwrap_or_default();
+
+    if let Err(err) = result {
+        return Err(UnsatisfiedConstraint {
+            gadgets: trace.failed.unwrap_or_default(),
+            index: None,
+            reason: format!("synthesis failed: {}", err),
+        });
+    }
+
+    match cs.which_is_unsatisfied() {
+        Ok(None) => Ok(()),
+        // Without a constraint tracing layer, the unsatisfied constraint is
+        // named by its index.
+        Ok(Some(name)) => {
+            let index = name.parse::<usize>().ok();
+            Err(UnsatisfiedConstraint {
+                gadgets: index
+                    .map(|index| trace.labels_of(index))
+                    .unwrap_or_default(),
+                index,
+                reason: format!("constraint {} is unsatisfied", name),
+            })
+        }
+        Err(err) => Err(UnsatisfiedConstraint {
+            gadgets: Vec::new(),
+            index: None,
+            reason: format!("could not check constraints: {}", err),
+        }),
+    }
+}
+
+/// The elliptic curve operations performed by proving and verifying, for
+/// modeling the cost of circuits, e.g. to calibrate their fee weights.
+///
+/// These are counted where the operations are performed: in the prover's
+/// [`msm::prove`](super::msm::prove), and in the single and batch verifiers of
+/// `penumbra-proof-verify`. A multi-scalar multiplication is counted by its
+/// number of terms, not by the curve additions its backend performs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OperationCounts {
+    /// The number of proofs generated.
+    pub proofs: usize,
+    /// The number of proofs verified, individually or as part of a batch.
+    pub verifications: usize,
+    /// The number of terms of multi-scalar multiplications in G1.
+    pub g1_msm_terms: usize,
+    /// The number of terms of multi-scalar multiplications in G2.
+    pub g2_msm_terms: usize,
+    /// The number of single scalar multiplications in G1.
+    pub g1_scalar_muls: usize,
+    /// The number of single scalar multiplications in G2.
+    pub g2_scalar_muls: usize,
+    /// The number of pairs in Miller loops.
+    pub miller_loop_pairs: usize,
+    /// The number of final exponentiations, i.e. of completed pairing checks.
+    pub final_exponentiations: usize,
+    /// The number of exponentiations in the target group.
+    pub gt_exponentiations: usize,
+}
+
+impl AddAssign for OperationCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.proofs += other.proofs;
+        self.verifications += other.verifications;
+        self.g1_msm_terms += other.g1_msm_terms;
+        self.g2_msm_terms += other.g2_msm_terms;
+        self.g1_scalar_muls += other.g1_scalar_muls;
+        self.g2_scalar_muls += other.g2_scalar_muls;
+        self.miller_loop_pairs += other.miller_loop_pairs;
+        self.final_exponentiations += other.final_exponentiations;
+        self.gt_exponentiations += other.gt_exponentiations;
+    }
+}
+
+impl From<VerificationCounts> for OperationCounts {
+    fn from(counts: VerificationCounts) -> Self {
+        Self {
+            verifications: counts.verifications,
+            g1_scalar_muls: counts.g1_scalar_muls,
+            miller_loop_pairs: counts.miller_loop_pairs,
+            final_exponentiations: counts.final_exponentiations,
+            gt_exponentiations: counts.gt_exponentiations,
+            ..Self::default()
+        }
+    }
+}
+
+/// Run `f`, counting the elliptic curve operations performed by the proofs it
+/// generates and verifies on this thread.
+///
+/// This is only available with the `op-counts` feature, without which no
+/// operations are counted.
+#[cfg(feature = "op-counts")]
+pub fn count_operations<T>(f: impl FnOnce() -> T) -> (T, OperationCounts) {
+    let previous =
+        RECORDED_OPERATIONS.with(|recorded| recorded.replace(Some(OperationCounts::default())));
+    let (result, verification_counts) = verify::count_verifications(f);
+    let mut counts = RECORDED_OPERATIONS
+        .with(|recorded| recorded.replace(previous))
+        .unwrap_or_default();
+    counts += verification_counts.into();
+    (result, counts)
+}
+
+/// Whether operations are being counted on this thread.
+pub(crate) fn counting_operations() -> bool {
+    cfg!(feature = "op-counts") && RECORDED_OPERATIONS.with(|recorded| recorded.borrow().is_some())
+}
+
+/// Record elliptic curve operations performed by proving, if they are being
+/// counted on this thread.
+pub(crate) fn record_operations(counts: OperationCounts) {
+    if cfg!(feature = "op-counts") {
+        RECORDED_OPERATIONS.with(|recorded| {
+            if let Some(recorded) = recorded.borrow_mut().as_mut() {
+                *recorded += counts;
+            }
+        });
+    }
+}
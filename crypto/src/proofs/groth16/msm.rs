@@ -0,0 +1,350 @@
+//! Pluggable multi-scalar multiplication for proving.
+//!
+//! Most of the time spent proving goes to the multi-scalar multiplications
+//! (MSMs) over the proving key's queries. An [`MsmBackend`] computes them, and
+//! can be set with [`ProverOptions::with_msm_backend`](super::ProverOptions::with_msm_backend)
+//! to use a faster implementation, e.g. one that runs on a GPU.
+
+use std::sync::Arc;
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_groth16::{Proof, ProvingKey};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError,
+};
+use decaf377::{Bls12_377, Fq};
+use rand::{CryptoRng, Rng};
+
+use super::metrics::OperationCounts;
+
+/// An affine point in G1 of BLS12-377.
+pub type G1Affine = <Bls12_377 as PairingEngine>::G1Affine;
+/// A projective point in G1 of BLS12-377.
+pub type G1Projective = <Bls12_377 as PairingEngine>::G1Projective;
+/// An affine point in G2 of BLS12-377.
+pub type G2Affine = <Bls12_377 as PairingEngine>::G2Affine;
+/// A projective point in G2 of BLS12-377.
+pub type G2Projective = <Bls12_377 as PairingEngine>::G2Projective;
+/// A scalar of BLS12-377, in the representation taken by MSMs.
+pub type Scalar = <Fq as PrimeField>::BigInt;
+
+/// A shared [`MsmBackend`], as held by [`ProverOptions`](super::ProverOptions).
+pub type SharedMsmBackend = Arc<dyn MsmBackend>;
+
+/// An implementation of the multi-scalar multiplications used by the prover.
+///
+/// Each method computes `Σ scalars[i] · bases[i]`. If the slices have
+/// different lengths, the elements past the end of the shorter one are
+/// ignored, as they are by arkworks.
+pub trait MsmBackend: Send + Sync {
+    /// A short name for this backend, e.g. for benchmark reports.
+    fn name(&self) -> &'static str;
+
+    /// Compute a multi-scalar multiplication in G1.
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[Scalar]) -> G1Projective;
+
+    /// Compute a multi-scalar multiplication in G2.
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[Scalar]) -> G2Projective;
+}
+
+/// The Pippenger implementation from `ark-ec`, which parallelizes over the
+/// windows of each MSM.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArkworksMsm;
+
+impl MsmBackend for ArkworksMsm {
+    fn name(&self) -> &'static str {
+        "arkworks"
+    }
+
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+        VariableBaseMSM::multi_scalar_mul(bases, scalars)
+    }
+
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[Scalar]) -> G2Projective {
+        VariableBaseMSM::multi_scalar_mul(bases, scalars)
+    }
+}
+
+/// An implementation that computes each MSM in chunks of at most `chunk_size`
+/// bases with an inner backend, one chunk after another, and sums the results.
+///
+/// The working memory of Pippenger's algorithm grows with the number of bases,
+/// so this bounds the memory each MSM needs, at some cost in speed. It is what
+/// [`ProverOptions::with_memory_budget`](super::ProverOptions::with_memory_budget)
+/// proves with.
+#[derive(Clone)]
+pub struct ChunkedMsm {
+    inner: SharedMsmBackend,
+    chunk_size: usize,
+}
+
+impl ChunkedMsm {
+    /// Compute MSMs with `inner`, in chunks of at most `chunk_size` bases.
+    pub fn new(inner: SharedMsmBackend, chunk_size: usize) -> Self {
+        Self {
+            inner,
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    /// The largest number of bases each MSM is computed over at once.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+}
+
+impl std::fmt::Debug for ChunkedMsm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkedMsm")
+            .field("inner", &self.inner.name())
+            .field("chunk_size", &self.chunk_size)
+            .finish()
+    }
+}
+
+impl MsmBackend for ChunkedMsm {
+    fn name(&self) -> &'static str {
+        "chunked"
+    }
+
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+        let len = bases.len().min(scalars.len());
+        bases[..len]
+            .chunks(self.chunk_size)
+            .zip(scalars[..len].chunks(self.chunk_size))
+            .fold(G1Projective::zero(), |acc, (bases, scalars)| {
+                acc + self.inner.msm_g1(bases, scalars)
+            })
+    }
+
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[Scalar]) -> G2Projective {
+        let len = bases.len().min(scalars.len());
+        bases[..len]
+            .chunks(self.chunk_size)
+            .zip(scalars[..len].chunks(self.chunk_size))
+            .fold(G2Projective::zero(), |acc, (bases, scalars)| {
+                acc + self.inner.msm_g2(bases, scalars)
+            })
+    }
+}
+
+/// A Pippenger implementation that additionally splits each MSM into chunks of
+/// bases, computed in parallel on the current rayon pool and then summed.
+///
+/// With as many chunks as threads, every thread stays busy for the whole MSM,
+/// rather than only for as many windows as the scalars have.
+#[cfg(feature = "parallel-msm")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParallelMsm {
+    chunk_size: Option<usize>,
+}
+
+#[cfg(feature = "parallel-msm")]
+impl ParallelMsm {
+    /// Split MSMs into chunks of `chunk_size` bases, rather than one chunk per
+    /// thread.
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: Some(chunk_size.max(1)),
+        }
+    }
+
+    fn msm<G: AffineCurve<ScalarField = Fq>>(
+        &self,
+        bases: &[G],
+        scalars: &[Scalar],
+    ) -> G::Projective {
+        use rayon::prelude::*;
+
+        let len = bases.len().min(scalars.len());
+        let chunk_size = self
+            .chunk_size
+            .unwrap_or_else(|| {
+                (len + rayon::current_num_threads() - 1) / rayon::current_num_threads()
+            })
+            .max(1);
+
+        bases[..len]
+            .par_chunks(chunk_size)
+            .zip(scalars[..len].par_chunks(chunk_size))
+            .map(|(bases, scalars)| VariableBaseMSM::multi_scalar_mul(bases, scalars))
+            .reduce(G::Projective::zero, |a, b| a + b)
+    }
+}
+
+#[cfg(feature = "parallel-msm")]
+impl MsmBackend for ParallelMsm {
+    fn name(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+        self.msm(bases, scalars)
+    }
+
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[Scalar]) -> G2Projective {
+        self.msm(bases, scalars)
+    }
+}
+
+/// Generate a Groth16 proof for `circuit`, computing its MSMs with `backend`.
+///
+/// This follows `ark_groth16::create_random_proof` step for step, drawing the
+/// same randomness, so for the same RNG it produces the same proof as
+/// `Groth16::prove`. It also returns the curve operations it performed.
+pub(super) fn prove<C: ConstraintSynthesizer<Fq>, R: CryptoRng + Rng>(
+    backend: &dyn MsmBackend,
+    pk: &ProvingKey<Bls12_377>,
+    circuit: C,
+    rng: &mut R,
+) -> Result<(Proof<Bls12_377>, OperationCounts), SynthesisError> {
+    let mut counts = OperationCounts {
+        proofs: 1,
+        ..OperationCounts::default()
+    };
+    // Backends ignore the elements past the end of the shorter slice.
+    let terms = |bases: usize, scalars: &[Scalar]| bases.min(scalars.len());
+
+    let r = Fq::rand(rng);
+    let s = Fq::rand(rng);
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+
+    let h = witness_map(&cs.borrow().ok_or(SynthesisError::MissingCS)?)?;
+    let h_assignment = h.into_iter().map(|h_i| h_i.into_repr()).collect::<Vec<_>>();
+    let h_acc = backend.msm_g1(&pk.h_query, &h_assignment);
+    counts.g1_msm_terms += terms(pk.h_query.len(), &h_assignment);
+    drop(h_assignment);
+
+    let cs = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+    // The constant `1` at index 0 of the instance is accounted for by adding
+    // the first element of each query directly.
+    let input_assignment = cs.instance_assignment[1..]
+        .iter()
+        .map(|x| x.into_repr())
+        .collect::<Vec<_>>();
+    let aux_assignment = cs
+        .witness_assignment
+        .iter()
+        .map(|x| x.into_repr())
+        .collect::<Vec<_>>();
+    let assignment = [&input_assignment[..], &aux_assignment[..]].concat();
+    drop(input_assignment);
+
+    let l_aux_acc = backend.msm_g1(&pk.l_query, &aux_assignment);
+    counts.g1_msm_terms += terms(pk.l_query.len(), &aux_assignment);
+    drop(aux_assignment);
+
+    let r_s_delta_g1 = pk
+        .delta_g1
+        .into_projective()
+        .mul(&r.into_repr())
+        .mul(&s.into_repr());
+    counts.g1_scalar_muls += 2;
+
+    // A = α + Σ a_i(x) + r·δ
+    let mut g_a = pk.delta_g1.mul(r);
+    g_a.add_assign_mixed(&pk.a_query[0]);
+    g_a += &backend.msm_g1(&pk.a_query[1..], &assignment);
+    g_a.add_assign_mixed(&pk.vk.alpha_g1);
+    let s_g_a = g_a.mul(&s.into_repr());
+    counts.g1_msm_terms += terms(pk.a_query.len() - 1, &assignment);
+    counts.g1_scalar_muls += 2;
+
+    // B in G1, only needed for C when r is nonzero.
+    let g1_b = if r.is_zero() {
+        G1Projective::zero()
+    } else {
+        let mut g1_b = pk.delta_g1.mul(s);
+        g1_b.add_assign_mixed(&pk.b_g1_query[0]);
+        g1_b += &backend.msm_g1(&pk.b_g1_query[1..], &assignment);
+        g1_b.add_assign_mixed(&pk.beta_g1);
+        counts.g1_msm_terms += terms(pk.b_g1_query.len() - 1, &assignment);
+        counts.g1_scalar_muls += 1;
+        g1_b
+    };
+
+    // B = β + Σ b_i(x) + s·δ
+    let mut g2_b = pk.vk.delta_g2.mul(s);
+    g2_b.add_assign_mixed(&pk.b_g2_query[0]);
+    g2_b += &backend.msm_g2(&pk.b_g2_query[1..], &assignment);
+    g2_b.add_assign_mixed(&pk.vk.beta_g2);
+    counts.g2_msm_terms += terms(pk.b_g2_query.len() - 1, &assignment);
+    counts.g2_scalar_muls += 1;
+    drop(assignment);
+
+    // C = s·A + r·B - r·s·δ + Σ l_i(x) + h(x)t(x)
+    let mut g_c = s_g_a;
+    g_c += &g1_b.mul(&r.into_repr());
+    counts.g1_scalar_muls += 1;
+    g_c -= &r_s_delta_g1;
+    g_c += &l_aux_acc;
+    g_c += &h_acc;
+
+    Ok((
+        Proof {
+            a: g_a.into_affine(),
+            b: g2_b.into_affine(),
+            c: g_c.into_affine(),
+        },
+        counts,
+    ))
+}
+
+/// Compute the coefficients of the quotient polynomial `h(x)` of the QAP that
+/// the constraint system reduces to, as `ark_groth16` does internally.
+fn witness_map(cs: &ConstraintSystem<Fq>) -> Result<Vec<Fq>, SynthesisError> {
+    let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+    let num_inputs = cs.num_instance_variables;
+    let num_constraints = cs.num_constraints;
+    let full_assignment = [&cs.instance_assignment[..], &cs.witness_assignment[..]].concat();
+
+    let domain = GeneralEvaluationDomain::<Fq>::new(num_constraints + num_inputs)
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let domain_size = domain.size();
+
+    let evaluate = |terms: &[(Fq, usize)]| {
+        terms.iter().fold(Fq::zero(), |acc, (coeff, index)| {
+            acc + *coeff * full_assignment[*index]
+        })
+    };
+
+    let mut a = vec![Fq::zero(); domain_size];
+    let mut b = vec![Fq::zero(); domain_size];
+    for i in 0..num_constraints {
+        a[i] = evaluate(&matrices.a[i]);
+        b[i] = evaluate(&matrices.b[i]);
+    }
+    a[num_constraints..num_constraints + num_inputs]
+        .clone_from_slice(&full_assignment[..num_inputs]);
+
+    domain.ifft_in_place(&mut a);
+    domain.ifft_in_place(&mut b);
+    domain.coset_fft_in_place(&mut a);
+    domain.coset_fft_in_place(&mut b);
+    let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
+    drop(a);
+    drop(b);
+
+    let mut c = vec![Fq::zero(); domain_size];
+    for i in 0..num_constraints {
+        c[i] = evaluate(&matrices.c[i]);
+    }
+    domain.ifft_in_place(&mut c);
+    domain.coset_fft_in_place(&mut c);
+
+    for (ab_i, c_i) in ab.iter_mut().zip(c) {
+        *ab_i -= &c_i;
+    }
+
+    domain.divide_by_vanishing_poly_on_coset_in_place(&mut ab);
+    domain.coset_ifft_in_place(&mut ab);
+
+    Ok(ab)
+}
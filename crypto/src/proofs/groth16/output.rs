@@ -1,5 +1,6 @@
-use std::str::FromStr;
+use std::{borrow::Borrow, str::FromStr};
 
+use anyhow::Context;
 use ark_r1cs_std::uint8::UInt8;
 use decaf377::{
     r1cs::{ElementVar, FqVar},
@@ -9,20 +10,40 @@ use decaf377::{Element, FieldExt};
 use decaf377_fmd as fmd;
 use decaf377_ka as ka;
 
-use ark_ff::{PrimeField, ToConstraintField};
-use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::CanonicalSerialize;
 use ark_snark::SNARK;
+use penumbra_proof_verify as verify;
+use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 use rand::{CryptoRng, Rng};
-use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
 
-use crate::proofs::groth16::{gadgets, ParameterSetup};
-use crate::{balance, keys::Diversifier, note, Address, Note, Rseed, Value};
+use crate::proofs::groth16::{
+    error::{self, VerificationError},
+    gadgets, metrics,
+    spend::plan_bytes,
+    traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS},
+    witness::SecretBytes,
+    CircuitDescription, Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable,
+    VariableKind,
+};
+use crate::{
+    asset::FromAmount,
+    balance,
+    keys::Diversifier,
+    memo::{memo_key_limbs, MemoKeyCommitment},
+    note,
+    sample::SampleUniform,
+    Address, Note, PayloadKey, Rseed, Value, STAKING_TOKEN_ASSET_ID,
+};
 
 // Public:
 // * vcm (value commitment)
 // * ncm (note commitment)
+// * mkcm (memo key commitment, or zero)
 //
 // Witnesses:
 // * g_d (point)
@@ -30,6 +51,7 @@ use crate::{balance, keys::Diversifier, note, Address, Note, Rseed, Value};
 // * v (u64 value plus asset ID (scalar))
 // * vblind (Fr)
 // * nblind (Fq)
+// * memo key (two 128-bit limbs)
 #[derive(Clone, Debug)]
 pub struct OutputCircuit {
     // Witnesses
@@ -37,12 +59,16 @@ pub struct OutputCircuit {
     note: Note,
     /// The blinding factor used for generating the balance commitment.
     v_blinding: Fr,
+    /// The memo key of the output, if it is bound to the note.
+    memo_key: Option<PayloadKey>,
 
     // Public inputs
     /// balance commitment of the new note,
     pub balance_commitment: balance::Commitment,
     /// note commitment of the new note,
     pub note_commitment: note::Commitment,
+    /// commitment binding the memo key to the new note, if any.
+    pub memo_key_commitment: Option<MemoKeyCommitment>,
 }
 
 impl ConstraintSynthesizer<Fq> for OutputCircuit {
@@ -51,7 +77,7 @@ impl ConstraintSynthesizer<Fq> for OutputCircuit {
         let note_blinding_var =
             FqVar::new_witness(cs.clone(), || Ok(self.note.note_blinding().clone()))?;
         let value_amount_var =
-            FqVar::new_witness(cs.clone(), || Ok(Fq::from(self.note.value().amount)))?;
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(self.note.value().amount)))?;
         let value_asset_id_var =
             FqVar::new_witness(cs.clone(), || Ok(self.note.value().asset_id.0))?;
         let diversified_generator_var: ElementVar =
@@ -60,50 +86,244 @@ impl ConstraintSynthesizer<Fq> for OutputCircuit {
             })?;
         let transmission_key_s_var =
             FqVar::new_witness(cs.clone(), || Ok(self.note.transmission_key_s().clone()))?;
+        // The clue key is only bound to the note through the note commitment:
+        // the transaction's FMD clue is not proven to be generated for it,
+        // since clue generation hashes with BLAKE2b, which has no gadget here.
         let clue_key_var = FqVar::new_witness(cs.clone(), || {
             Ok(Fq::from_le_bytes_mod_order(&self.note.clue_key().0[..]))
         })?;
-        let v_blinding_arr: [u8; 32] = self.v_blinding.to_bytes();
+        let v_blinding_arr = SecretBytes::new(self.v_blinding.to_bytes());
         let v_blinding_vars = UInt8::new_witness_vec(cs.clone(), &v_blinding_arr)?;
         let value_amount_arr = self.note.value().amount.to_le_bytes();
         let value_vars = UInt8::new_witness_vec(cs.clone(), &value_amount_arr)?;
+        metrics::gadget(&cs, "amount_range_check", || {
+            gadgets::amount_range_check(&Boolean::TRUE, &value_amount_var, &value_vars)
+        })?;
+        let (memo_key_lo, memo_key_hi) = self
+            .memo_key
+            .as_ref()
+            .map(memo_key_limbs)
+            .unwrap_or((Fq::zero(), Fq::zero()));
+        let memo_key_lo_var = FqVar::new_witness(cs.clone(), || Ok(memo_key_lo))?;
+        let memo_key_hi_var = FqVar::new_witness(cs.clone(), || Ok(memo_key_hi))?;
 
         // Public inputs
         let note_commitment_var = FqVar::new_input(cs.clone(), || Ok(self.note_commitment.0))?;
         let balance_commitment_var =
             ElementVar::new_input(cs.clone(), || Ok(self.balance_commitment.0))?;
+        let memo_key_commitment_var = FqVar::new_input(cs.clone(), || {
+            Ok(self
+                .memo_key_commitment
+                .map(|commitment| commitment.0)
+                .unwrap_or_else(Fq::zero))
+        })?;
+
+        // We short circuit to true if the value is 0. That means this is a _dummy_ output.
+        let is_dummy = value_amount_var.is_eq(&FqVar::zero())?;
+        // We use a Boolean constraint to enforce the below constraint only if this is not a
+        // dummy output.
+        let is_not_dummy = is_dummy.not();
+        // The memo key commitment is optional: a zero commitment means the
+        // output's memo key is not bound to the note.
+        let has_memo_key_commitment = memo_key_commitment_var.is_eq(&FqVar::zero())?.not();
 
-        gadgets::diversified_basepoint_not_identity(
-            cs.clone(),
-            &Boolean::TRUE,
-            diversified_generator_var.clone(),
-        )?;
-        gadgets::value_commitment_integrity(
-            cs.clone(),
-            &Boolean::TRUE,
-            value_vars,
-            value_asset_id_var.clone(),
-            v_blinding_vars,
-            balance_commitment_var,
-        )?;
-        gadgets::note_commitment_integrity(
-            cs,
-            &Boolean::TRUE,
-            note_blinding_var,
-            value_amount_var,
-            value_asset_id_var,
-            diversified_generator_var,
-            transmission_key_s_var,
-            clue_key_var,
-            note_commitment_var,
-        )?;
+        // Unlike for spends, the value and note commitment integrity checks are
+        // always enforced: a dummy output still adds its note commitment to the
+        // tree, so it must commit to the zero value it claims to create.
+        metrics::gadget(&cs, "diversified_basepoint_not_identity", || {
+            gadgets::diversified_basepoint_not_identity(
+                cs.clone(),
+                &is_not_dummy,
+                diversified_generator_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "value_commitment_integrity", || {
+            gadgets::value_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                &Boolean::FALSE,
+                value_vars,
+                value_asset_id_var.clone(),
+                v_blinding_vars,
+                balance_commitment_var,
+            )
+        })?;
+        metrics::gadget(&cs, "memo_key_commitment_integrity", || {
+            gadgets::memo_key_commitment_integrity(
+                cs.clone(),
+                &has_memo_key_commitment,
+                note_blinding_var.clone(),
+                memo_key_lo_var,
+                memo_key_hi_var,
+                memo_key_commitment_var,
+            )
+        })?;
+        metrics::gadget(&cs, "note_commitment_integrity", || {
+            gadgets::note_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                note_blinding_var,
+                value_amount_var,
+                value_asset_id_var,
+                diversified_generator_var,
+                transmission_key_s_var,
+                clue_key_var,
+                note_commitment_var,
+            )
+        })?;
 
         Ok(())
     }
 }
 
+impl OutputCircuit {
+    /// Check natively that the public inputs are the ones the witnesses determine, panicking
+    /// with the name of the first one that isn't.
+    #[cfg(feature = "debug-asserts")]
+    fn assert_public_inputs(&self) {
+        assert_eq!(
+            self.note.commit(),
+            self.note_commitment,
+            "output proof input mismatch: the note commitment does not commit to the note"
+        );
+        assert_eq!(
+            self.note.value().commit(self.v_blinding),
+            self.balance_commitment,
+            "output proof input mismatch: the balance commitment does not commit to the note's value"
+        );
+        if let Some(commitment) = self.memo_key_commitment {
+            assert_eq!(
+                self.memo_key
+                    .as_ref()
+                    .map(|memo_key| MemoKeyCommitment::commit(memo_key, &self.note)),
+                Some(commitment),
+                "output proof input mismatch: the memo key commitment does not commit to the memo key"
+            );
+        }
+    }
+
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::{Always, IfProvided, UnlessDummy};
+        use VariableKind::{Bytes, Element, Fq};
+
+        CircuitDescription {
+            name: "output",
+            public_inputs: vec![
+                Variable::new("note_commitment", Fq),
+                Variable::new("balance_commitment", Element),
+                Variable::new("memo_key_commitment", Fq),
+            ],
+            witnesses: vec![
+                Variable::new("note_blinding", Fq),
+                Variable::new("value_amount", Fq),
+                Variable::new("value_asset_id", Fq),
+                Variable::new("diversified_generator", Element),
+                Variable::new("transmission_key_s", Fq),
+                Variable::new("clue_key", Fq),
+                Variable::new("v_blinding", Bytes { len: 32 }),
+                Variable::new("value_amount_bytes", Bytes { len: 16 }),
+                Variable::new("memo_key_lo", Fq),
+                Variable::new("memo_key_hi", Fq),
+            ],
+            gadgets: vec![
+                Gadget::new(
+                    "amount_range_check",
+                    Always,
+                    &["value_amount", "value_amount_bytes"],
+                ),
+                Gadget::new(
+                    "diversified_basepoint_not_identity",
+                    UnlessDummy,
+                    &["diversified_generator"],
+                ),
+                Gadget::new(
+                    "value_commitment_integrity",
+                    Always,
+                    &[
+                        "value_amount_bytes",
+                        "value_asset_id",
+                        "v_blinding",
+                        "balance_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "memo_key_commitment_integrity",
+                    IfProvided,
+                    &[
+                        "note_blinding",
+                        "memo_key_lo",
+                        "memo_key_hi",
+                        "memo_key_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "note_commitment_integrity",
+                    Always,
+                    &[
+                        "note_blinding",
+                        "value_amount",
+                        "value_asset_id",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "note_commitment",
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the output circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputPublicInputs {
+    /// balance commitment of the new note,
+    pub balance_commitment: balance::Commitment,
+    /// note commitment of the new note,
+    pub note_commitment: note::Commitment,
+    /// commitment binding the memo key to the new note, if any.
+    pub memo_key_commitment: Option<MemoKeyCommitment>,
+}
+
+impl PublicInputs for OutputPublicInputs {
+    type Circuit = OutputCircuit;
+
+    fn from_circuit(circuit: &OutputCircuit) -> Self {
+        Self {
+            balance_commitment: circuit.balance_commitment,
+            note_commitment: circuit.note_commitment,
+            memo_key_commitment: circuit.memo_key_commitment,
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let mut public_inputs = Vec::new();
+        public_inputs.extend(error::public_input(
+            "note_commitment",
+            &self.note_commitment.0,
+        )?);
+        public_inputs.extend(error::public_input(
+            "balance_commitment",
+            &self.balance_commitment.0,
+        )?);
+        public_inputs.push(
+            self.memo_key_commitment
+                .map(|commitment| commitment.0)
+                .unwrap_or_else(Fq::zero),
+        );
+        debug_assert_eq!(public_inputs.len(), OutputCircuit::NUM_PUBLIC_INPUTS);
+        Ok(public_inputs)
+    }
+}
+
 impl ParameterSetup for OutputCircuit {
-    fn generate_test_parameters() -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+    const NAME: &'static str = "output";
+
+    // note commitment, balance commitment, memo key commitment
+    const NUM_PUBLIC_INPUTS: usize = 2 * FQ_PUBLIC_INPUTS + ELEMENT_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
         let diversifier_bytes = [1u8; 16];
         let pk_d_bytes = [1u8; 32];
         let clue_key_bytes = [1; 32];
@@ -121,61 +341,329 @@ impl ParameterSetup for OutputCircuit {
         )
         .expect("can make a note");
         let v_blinding = Fr::from(1);
-        let circuit = OutputCircuit {
+        OutputCircuit {
             note: note.clone(),
             note_commitment: note.commit(),
             v_blinding,
+            memo_key: None,
             balance_commitment: balance::Commitment(decaf377::basepoint()),
-        };
-        let (pk, vk) = Groth16::circuit_specific_setup(circuit, &mut OsRng)
-            .expect("can perform circuit specific setup");
-        (pk, vk)
+            memo_key_commitment: None,
+        }
+    }
+}
+
+/// The witnesses and public inputs of an output proof: everything needed to
+/// prove it.
+///
+/// A plan can be encoded with [`Protobuf`], e.g. to be proven on another
+/// machine. It contains the new note in the clear, so it must only be sent to
+/// a prover trusted with it.
+#[derive(Clone, Debug)]
+pub struct OutputProofPlan {
+    /// The note being created.
+    pub note: Note,
+    /// The blinding factor used for generating the balance commitment.
+    pub v_blinding: Fr,
+    /// balance commitment of the new note,
+    pub balance_commitment: balance::Commitment,
+    /// note commitment of the new note,
+    pub note_commitment: note::Commitment,
+}
+
+impl OutputProofPlan {
+    /// The circuit instance this plan proves.
+    pub fn build_circuit(&self) -> OutputCircuit {
+        OutputCircuit {
+            note: self.note.clone(),
+            note_commitment: self.note_commitment,
+            v_blinding: self.v_blinding,
+            memo_key: None,
+            balance_commitment: self.balance_commitment,
+            memo_key_commitment: None,
+        }
+    }
+
+    /// The public inputs the proof will be verified against.
+    pub fn public_inputs(&self) -> OutputPublicInputs {
+        OutputPublicInputs {
+            balance_commitment: self.balance_commitment,
+            note_commitment: self.note_commitment,
+            memo_key_commitment: None,
+        }
+    }
+
+    /// Generate the proof with the default [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        &self,
+        rng: &mut R,
+        pk: P,
+    ) -> anyhow::Result<OutputProof> {
+        self.prove_with_options(&ProverOptions::default(), rng, pk)
+    }
+
+    /// Generate the proof, according to the provided [`ProverOptions`].
+    pub fn prove_with_options<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        &self,
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+    ) -> anyhow::Result<OutputProof> {
+        OutputProof::prove_circuit(options, rng, pk, self.build_circuit())
+    }
+}
+
+impl Protobuf<pb::OutputProofPlan> for OutputProofPlan {}
+
+impl From<OutputProofPlan> for pb::OutputProofPlan {
+    fn from(plan: OutputProofPlan) -> Self {
+        pb::OutputProofPlan {
+            note: Some(plan.note.into()),
+            v_blinding: plan.v_blinding.to_bytes().to_vec(),
+            balance_commitment: Some(plan.balance_commitment.into()),
+            note_commitment: Some(plan.note_commitment.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::OutputProofPlan> for OutputProofPlan {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::OutputProofPlan) -> Result<Self, Self::Error> {
+        Ok(OutputProofPlan {
+            note: proto.note.context("missing note")?.try_into()?,
+            v_blinding: Fr::from_bytes(plan_bytes(&proto.v_blinding, "v_blinding")?)?,
+            balance_commitment: proto
+                .balance_commitment
+                .context("missing balance commitment")?
+                .try_into()?,
+            note_commitment: proto
+                .note_commitment
+                .context("missing note commitment")?
+                .try_into()?,
+        })
     }
 }
 
-pub struct OutputProof(Proof<Bls12_377>);
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "pb::ZkOutputProof", into = "pb::ZkOutputProof")]
+pub struct OutputProof(pub(super) Proof<Bls12_377>);
 
 impl OutputProof {
     #![allow(clippy::too_many_arguments)]
-    pub fn prove<R: CryptoRng + Rng>(
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    #[deprecated(note = "collect the arguments into an `OutputProofPlan` and prove that")]
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        note: Note,
+        v_blinding: Fr,
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> anyhow::Result<Self> {
+        OutputProofPlan {
+            note,
+            v_blinding,
+            balance_commitment,
+            note_commitment,
+        }
+        .prove_with_options(options, rng, pk)
+    }
+
+    /// Generate a proof that also binds the output's memo key to its note, by
+    /// proving that `memo_key_commitment` commits to `memo_key` for the note.
+    ///
+    /// The proof must be verified with the memo key commitment, with
+    /// [`OutputProof::verify_public_inputs`].
+    pub fn prove_with_memo_key<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
         rng: &mut R,
-        pk: &ProvingKey<Bls12_377>,
+        pk: P,
         note: Note,
         v_blinding: Fr,
+        memo_key: PayloadKey,
         balance_commitment: balance::Commitment,
         note_commitment: note::Commitment,
+        memo_key_commitment: MemoKeyCommitment,
     ) -> anyhow::Result<Self> {
         let circuit = OutputCircuit {
             note,
             note_commitment,
             v_blinding,
+            memo_key: Some(memo_key),
             balance_commitment,
+            memo_key_commitment: Some(memo_key_commitment),
         };
-        let proof = Groth16::prove(pk, circuit, rng).map_err(|err| anyhow::anyhow!(err))?;
+        Self::prove_circuit(options, rng, pk, circuit)
+    }
+
+    fn prove_circuit<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        circuit: OutputCircuit,
+    ) -> anyhow::Result<Self> {
+        #[cfg(feature = "debug-asserts")]
+        circuit.assert_public_inputs();
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
         Ok(Self(proof))
     }
 
+    /// Generate a proof for a _dummy_ output, creating a zero-value note to a
+    /// random address.
+    ///
+    /// Returns the proof along with its public inputs, the balance commitment
+    /// and the note commitment of the dummy note.
+    pub fn dummy<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+    ) -> anyhow::Result<(Self, balance::Commitment, note::Commitment)> {
+        let address = Address::dummy(rng);
+        let value = Value {
+            amount: 0u64.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(rng, &address, value);
+        let v_blinding = Fr::rand_uniform(rng);
+        let balance_commitment = value.commit(v_blinding);
+        let note_commitment = note.commit();
+
+        let proof = OutputProofPlan {
+            note,
+            v_blinding,
+            balance_commitment,
+            note_commitment,
+        }
+        .prove_with_options(options, rng, pk)?;
+        Ok((proof, balance_commitment, note_commitment))
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
     /// Called to verify the proof using the provided public inputs.
     ///
     /// The public inputs are:
     /// * balance commitment of the new note,
     /// * note commitment of the new note,
+    ///
+    /// Only proofs that don't bind a memo key to the note verify this way.
     pub fn verify(
         &self,
         vk: &VerifyingKey<Bls12_377>,
         balance_commitment: balance::Commitment,
         note_commitment: note::Commitment,
-    ) -> anyhow::Result<()> {
-        let processed_pvk = Groth16::process_vk(vk).map_err(|err| anyhow::anyhow!(err))?;
-        let mut public_inputs = Vec::new();
-        public_inputs.extend(note_commitment.0.to_field_elements().unwrap());
-        public_inputs.extend(balance_commitment.0.to_field_elements().unwrap());
-
-        let proof_result =
-            Groth16::verify_with_processed_vk(&processed_pvk, public_inputs.as_slice(), &self.0)
-                .map_err(|err| anyhow::anyhow!(err))?;
-        proof_result
-            .then_some(())
-            .ok_or_else(|| anyhow::anyhow!("proof did not verify"))
+    ) -> Result<(), VerificationError> {
+        OutputCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(&processed_pvk, balance_commitment, note_commitment)
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| {
+            self.verify(vk, balance_commitment, note_commitment)
+        })
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        note_commitment: note::Commitment,
+    ) -> Result<(), VerificationError> {
+        self.verify_public_inputs(
+            processed_pvk,
+            &OutputPublicInputs {
+                balance_commitment,
+                note_commitment,
+                memo_key_commitment: None,
+            },
+        )
+    }
+
+    /// Verify the proof against all of its public inputs, including the
+    /// optional memo key commitment.
+    pub fn verify_public_inputs(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        public_inputs: &OutputPublicInputs,
+    ) -> Result<(), VerificationError> {
+        OutputCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = public_inputs.to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
+
+/// The size of the compressed encoding of an [`OutputProof`].
+pub const OUTPUT_PROOF_LEN_BYTES: usize = verify::PROOF_LEN_BYTES;
+
+impl Protobuf<pb::ZkOutputProof> for OutputProof {}
+
+impl From<OutputProof> for pb::ZkOutputProof {
+    fn from(proof: OutputProof) -> Self {
+        pb::ZkOutputProof {
+            inner: proof.to_bytes(),
+        }
+    }
+}
+
+impl TryFrom<pb::ZkOutputProof> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::ZkOutputProof) -> Result<Self, Self::Error> {
+        proto.inner.as_slice().try_into()
+    }
+}
+
+impl From<OutputProof> for Vec<u8> {
+    fn from(proof: OutputProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(verify::decode_proof(bytes)?))
     }
 }
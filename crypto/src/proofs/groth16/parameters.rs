@@ -0,0 +1,209 @@
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(feature = "std")]
+use anyhow::Context;
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use decaf377::Bls12_377;
+use sha2::{Digest, Sha256};
+
+use crate::proofs::groth16::{ParameterSetup, ProvingKeyExt};
+
+/// A registry of proving keys, keyed by circuit type.
+///
+/// Each circuit's proving key is loaded the first time it is requested with
+/// [`get`](ParameterStore::get), and then cached, so that application code can
+/// hold a single store rather than passing proving keys through every layer.
+/// The returned keys can be passed directly to the prove entry points, e.g.
+/// [`SpendProofPlan::prove`](super::SpendProofPlan::prove).
+///
+/// A circuit's key is looked up, in order:
+/// * in the bytes registered with [`with_embedded`](ParameterStore::with_embedded);
+/// * in the file `<NAME>_pk.bin` in the directory set with
+///   [`with_directory`](ParameterStore::with_directory), where `NAME` is the
+///   circuit's [`ParameterSetup::NAME`] (only with the `std` feature);
+/// * by generating test parameters, if enabled with
+///   [`with_test_parameters`](ParameterStore::with_test_parameters).
+///
+/// Keys are stored in their compressed `ark-serialize` encoding, and may be
+/// checked against a known hash with [`with_checksum`](ParameterStore::with_checksum).
+#[derive(Default)]
+pub struct ParameterStore {
+    embedded: HashMap<&'static str, &'static [u8]>,
+    #[cfg(feature = "std")]
+    directory: Option<PathBuf>,
+    test_parameters: bool,
+    checksums: HashMap<&'static str, [u8; 32]>,
+    cache: Mutex<HashMap<TypeId, Arc<ProvingKey<Bls12_377>>>>,
+}
+
+impl std::fmt::Debug for ParameterStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ParameterStore");
+        debug.field("embedded", &self.embedded.keys().collect::<Vec<_>>());
+        #[cfg(feature = "std")]
+        debug.field("directory", &self.directory);
+        debug
+            .field("test_parameters", &self.test_parameters)
+            .field("checksums", &self.checksums.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ParameterStore {
+    /// Create a store with no sources of parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given bytes, e.g. from `include_bytes!`, as the proving key for
+    /// the circuit with the given name.
+    pub fn with_embedded(mut self, name: &'static str, bytes: &'static [u8]) -> Self {
+        self.embedded.insert(name, bytes);
+        self
+    }
+
+    /// Load proving keys from files in the given directory.
+    #[cfg(feature = "std")]
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Generate test parameters for any circuit without a proving key.
+    ///
+    /// This is only intended for testing, since the toxic waste of test
+    /// parameters is not destroyed.
+    pub fn with_test_parameters(mut self) -> Self {
+        self.test_parameters = true;
+        self
+    }
+
+    /// Require the encoded proving key for the circuit with the given name to
+    /// have the given SHA-256 hash, so that corrupted or substituted key files
+    /// are rejected when they are loaded.
+    pub fn with_checksum(mut self, name: &'static str, sha256: [u8; 32]) -> Self {
+        self.checksums.insert(name, sha256);
+        self
+    }
+
+    /// Get the verifying key for the circuit `C`, loading its proving key if
+    /// it has not already been loaded.
+    pub fn verifying_key<C: ParameterSetup + 'static>(
+        &self,
+    ) -> anyhow::Result<VerifyingKey<Bls12_377>> {
+        Ok(self.get::<C>()?.vk.clone())
+    }
+
+    /// Get the proving key for the circuit `C`, loading it if it has not
+    /// already been loaded.
+    pub fn get<C: ParameterSetup + 'static>(&self) -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+        // Hold the lock while loading, so that each key is only loaded once.
+        let mut cache = self.cache.lock().expect("parameter cache is not poisoned");
+        if let Some(pk) = cache.get(&TypeId::of::<C>()) {
+            return Ok(pk.clone());
+        }
+
+        let pk = Arc::new(self.load::<C>()?);
+        cache.insert(TypeId::of::<C>(), pk.clone());
+        Ok(pk)
+    }
+
+    /// Load the proving key for the circuit `C` if it has not already been
+    /// loaded, and [warm it up](ProvingKeyExt::warm_up) so that the first proof
+    /// made with it is not slowed down by page faults.
+    pub fn warm_up<C: ParameterSetup + 'static>(&self) -> anyhow::Result<()> {
+        self.get::<C>()?.warm_up();
+        Ok(())
+    }
+
+    fn load<C: ParameterSetup>(&self) -> anyhow::Result<ProvingKey<Bls12_377>> {
+        let bytes = if let Some(bytes) = self.embedded.get(C::NAME) {
+            Cow::Borrowed(*bytes)
+        } else if let Some(bytes) = self.read_from_directory(C::NAME)? {
+            Cow::Owned(bytes)
+        } else if self.test_parameters {
+            return generate_test_parameters::<C>();
+        } else {
+            anyhow::bail!("no proving key available for the {} circuit", C::NAME);
+        };
+
+        decode_proving_key::<C>(&bytes, self.checksums.get(C::NAME))
+    }
+
+    /// Read the proving key for the circuit with the given name from the
+    /// store's directory, if it has one and the key file exists.
+    #[cfg(feature = "std")]
+    fn read_from_directory(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = match &self.directory {
+            Some(directory) => directory.join(format!("{}_pk.bin", name)),
+            None => return Ok(None),
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+        tracing::debug!(circuit = name, ?path, "loading proving key");
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("could not read proving key from {}", path.display()))?;
+        Ok(Some(bytes))
+    }
+
+    /// Without the `std` feature there is no filesystem, so keys must be
+    /// embedded.
+    #[cfg(not(feature = "std"))]
+    fn read_from_directory(&self, _name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
+
+/// Decode the proving key for the circuit `C` from its compressed encoding,
+/// checking it against `checksum`, if given, and against the circuit.
+pub(super) fn decode_proving_key<C: ParameterSetup>(
+    bytes: &[u8],
+    checksum: Option<&[u8; 32]>,
+) -> anyhow::Result<ProvingKey<Bls12_377>> {
+    let pk = if let Some(expected) = checksum {
+        let actual: [u8; 32] = Sha256::digest(bytes).into();
+        if actual != *expected {
+            anyhow::bail!(
+                "{} proving key has checksum {}, expected {}",
+                C::NAME,
+                hex::encode(actual),
+                hex::encode(expected)
+            );
+        }
+        // The checksum vouches for the key, so the (slow) subgroup checks
+        // on each of its points can be skipped.
+        ProvingKey::deserialize_unchecked(bytes)
+    } else {
+        ProvingKey::deserialize(bytes)
+    }
+    .map_err(|err| anyhow::anyhow!("invalid {} proving key: {}", C::NAME, err))?;
+    C::check_proving_key(&pk)?;
+    Ok(pk)
+}
+
+#[cfg(feature = "std")]
+fn generate_test_parameters<C: ParameterSetup>() -> anyhow::Result<ProvingKey<Bls12_377>> {
+    tracing::warn!(circuit = C::NAME, "generating test parameters");
+    let pk = C::generate_test_parameters().0;
+    C::check_proving_key(&pk)?;
+    Ok(pk)
+}
+
+/// Without the `std` feature there is no default source of randomness to
+/// generate parameters with.
+#[cfg(not(feature = "std"))]
+fn generate_test_parameters<C: ParameterSetup>() -> anyhow::Result<ProvingKey<Bls12_377>> {
+    anyhow::bail!(
+        "generating test parameters for the {} circuit requires the `std` feature",
+        C::NAME
+    );
+}
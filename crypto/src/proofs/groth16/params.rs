@@ -0,0 +1,132 @@
+//! Process-wide proving and verifying keys for each circuit.
+//!
+//! The keys are held in a global [`ParameterStore`], so that each key is loaded
+//! at most once per process, the first time it is used. The store can be
+//! configured once at startup with [`init`]; otherwise, it loads keys from the
+//! directory named by the [`PARAMETERS_DIR_ENV`] environment variable, if set
+//! and the `std` feature is enabled.
+//!
+//! Tests that need different keys than the rest of the process can replace the
+//! store on their own thread with [`override_for_tests`].
+
+use std::{cell::RefCell, marker::PhantomData, sync::Arc};
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use decaf377::Bls12_377;
+use once_cell::sync::OnceCell;
+
+use crate::proofs::groth16::{OutputCircuit, ParameterSetup, ParameterStore, SpendCircuit};
+
+/// The environment variable naming the directory to load keys from, when the
+/// global store is not configured explicitly.
+pub const PARAMETERS_DIR_ENV: &str = "PENUMBRA_PARAMETERS_DIR";
+
+static STORE: OnceCell<ParameterStore> = OnceCell::new();
+
+thread_local! {
+    static OVERRIDE: RefCell<Option<Arc<ParameterStore>>> = RefCell::new(None);
+}
+
+/// Configure the global parameter store.
+///
+/// This fails if the store has already been configured, or if any key has
+/// already been requested from it.
+pub fn init(store: ParameterStore) -> anyhow::Result<()> {
+    STORE
+        .set(store)
+        .map_err(|_| anyhow::anyhow!("parameter store is already initialized"))
+}
+
+/// The global parameter store.
+///
+/// This ignores any store set with [`override_for_tests`]; use the key
+/// accessors in this module to respect it.
+pub fn store() -> &'static ParameterStore {
+    STORE.get_or_init(default_store)
+}
+
+#[cfg(feature = "std")]
+fn default_store() -> ParameterStore {
+    match std::env::var_os(PARAMETERS_DIR_ENV) {
+        Some(directory) => ParameterStore::new().with_directory(directory),
+        None => ParameterStore::new(),
+    }
+}
+
+/// Without the `std` feature there is no environment to configure the store
+/// from, so it must be configured with [`init`].
+#[cfg(not(feature = "std"))]
+fn default_store() -> ParameterStore {
+    ParameterStore::new()
+}
+
+/// Replace the parameter store used by this module's key accessors on the
+/// current thread, until the returned guard is dropped.
+///
+/// Unlike [`init`], this can be called at any time, and overrides can be
+/// nested, so each test can use its own keys without racing other tests for
+/// the global store. The override is not seen by other threads, including the
+/// threads of a [`ProverPool`](super::ProverPool).
+pub fn override_for_tests(store: ParameterStore) -> OverrideGuard {
+    let previous = OVERRIDE.with(|current| current.replace(Some(Arc::new(store))));
+    OverrideGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+/// Restores the previous parameter store of the current thread when dropped.
+///
+/// Returned by [`override_for_tests`].
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct OverrideGuard {
+    previous: Option<Arc<ParameterStore>>,
+    // The override is per-thread, so it must be dropped on the thread it was
+    // made on.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        OVERRIDE.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Run `f` with the current thread's parameter store.
+fn with_store<T>(f: impl FnOnce(&ParameterStore) -> T) -> T {
+    match OVERRIDE.with(|current| current.borrow().clone()) {
+        Some(store) => f(&store),
+        None => f(store()),
+    }
+}
+
+/// The proving key for the circuit `C`.
+pub fn proving_key<C: ParameterSetup + 'static>() -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+    with_store(|store| store.get::<C>())
+}
+
+/// The verifying key for the circuit `C`.
+pub fn verifying_key<C: ParameterSetup + 'static>() -> anyhow::Result<VerifyingKey<Bls12_377>> {
+    with_store(|store| store.verifying_key::<C>())
+}
+
+/// The proving key for spend proofs.
+pub fn spend_proving_key() -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+    proving_key::<SpendCircuit>()
+}
+
+/// The verifying key for spend proofs.
+pub fn spend_verifying_key() -> anyhow::Result<VerifyingKey<Bls12_377>> {
+    verifying_key::<SpendCircuit>()
+}
+
+/// The proving key for output proofs.
+pub fn output_proving_key() -> anyhow::Result<Arc<ProvingKey<Bls12_377>>> {
+    proving_key::<OutputCircuit>()
+}
+
+/// The verifying key for output proofs.
+pub fn output_verifying_key() -> anyhow::Result<VerifyingKey<Bls12_377>> {
+    verifying_key::<OutputCircuit>()
+}
@@ -0,0 +1,85 @@
+use rand::{CryptoRng, Rng};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use rayon::prelude::*;
+
+use super::ProverOptions;
+
+/// Generates many independent proofs in parallel, e.g. one for each action of
+/// a transaction.
+///
+/// At most `parallelism` proofs are generated at once. The thread limit and
+/// memory budget of the pool's [`ProverOptions`] apply to the pool as a whole,
+/// and are divided evenly between the proofs running concurrently.
+pub struct ProverPool {
+    pool: rayon::ThreadPool,
+    options: ProverOptions,
+}
+
+impl ProverPool {
+    /// Create a pool generating up to `parallelism` proofs at once.
+    pub fn new(options: ProverOptions, parallelism: usize) -> anyhow::Result<Self> {
+        if parallelism == 0 {
+            anyhow::bail!("prover pool parallelism must be at least 1");
+        }
+
+        let total_threads = options
+            .threads
+            .unwrap_or_else(rayon::current_num_threads)
+            .max(1);
+        let parallelism = parallelism.min(total_threads);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(parallelism)
+            .build()?;
+
+        let options = ProverOptions {
+            threads: Some((total_threads / parallelism).max(1)),
+            memory_budget: options
+                .memory_budget
+                .map(|memory_budget| memory_budget / parallelism),
+            ..options
+        };
+
+        Ok(Self { pool, options })
+    }
+
+    /// The number of proofs generated at once.
+    pub fn parallelism(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Run each of the `jobs`, returning their proofs in the order of the jobs.
+    ///
+    /// Each job is called with the options for a single proof, and its own RNG
+    /// seeded from `rng`, and should pass them to a prove entry point such as
+    /// [`SpendProofPlan::prove_with_options`](super::SpendProofPlan::prove_with_options).
+    /// Proofs of different circuits can be generated in one batch by returning
+    /// an enum.
+    ///
+    /// If any job fails, the first error in job order is returned.
+    pub fn prove_batch<T, F, R>(
+        &self,
+        rng: &mut R,
+        jobs: impl IntoIterator<Item = F>,
+    ) -> anyhow::Result<Vec<T>>
+    where
+        T: Send,
+        F: FnOnce(&ProverOptions, &mut ChaCha20Rng) -> anyhow::Result<T> + Send,
+        R: CryptoRng + Rng,
+    {
+        // Seed each job's RNG up front, so that the randomness each proof
+        // receives doesn't depend on the order the jobs are scheduled in.
+        let jobs = jobs
+            .into_iter()
+            .map(|job| (job, ChaCha20Rng::from_seed(rng.gen())))
+            .collect::<Vec<_>>();
+
+        let results = self.pool.install(|| {
+            jobs.into_par_iter()
+                .map(|(job, mut job_rng)| job(&self.options, &mut job_rng))
+                .collect::<Vec<_>>()
+        });
+
+        results.into_iter().collect()
+    }
+}
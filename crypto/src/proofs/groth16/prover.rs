@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use ark_groth16::{Groth16, Proof, ProvingKey};
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use decaf377::{Bls12_377, Fq};
+use rand::{CryptoRng, Rng};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+use super::{
+    metrics::{self, OperationCounts},
+    msm::{self, MsmBackend, SharedMsmBackend},
+    witness,
+};
+
+/// The estimated bytes of working memory each prover thread needs for each base
+/// of the multi-scalar multiplication chunk it is working on.
+///
+/// Pippenger's algorithm uses windows of about `ln(n) + 2` bits for `n` bases,
+/// so each window holds about `e² · n` bucket accumulators, as large as a
+/// projective point in G2, the larger of the two groups.
+const MSM_BYTES_PER_BASE: usize = 8 * std::mem::size_of::<msm::G2Projective>();
+
+/// The fewest bases a multi-scalar multiplication is split into chunks of.
+///
+/// Below this, Pippenger's algorithm loses most of its advantage, so a tight
+/// memory budget runs fewer threads rather than smaller chunks.
+const MIN_MSM_CHUNK_SIZE: usize = 1 << 10;
+
+/// A stage of proof generation, reported to the [`ProverOptions::progress`] callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingStage {
+    /// Proving has started, using the given number of threads.
+    Started { threads: usize },
+    /// Proving has finished.
+    Finished,
+}
+
+/// A callback reporting the progress of proof generation.
+pub type ProgressCallback = Arc<dyn Fn(ProvingStage) + Send + Sync>;
+
+/// Options controlling how proofs are generated.
+///
+/// These are passed to every prove entry point, e.g.
+/// [`SpendProofPlan::prove_with_options`](super::SpendProofPlan::prove_with_options).
+/// The default options prove with all available threads and no memory budget.
+#[derive(Clone, Default)]
+pub struct ProverOptions {
+    /// The maximum number of threads to prove with.
+    ///
+    /// When unset, the prover uses all available threads.
+    pub threads: Option<usize>,
+    /// The memory, in bytes, that proving may use, e.g. on a device with 4 GB.
+    ///
+    /// When set, proving fails up front if the proving key, the witness
+    /// assignment and the quotient polynomial alone don't fit. The multi-scalar
+    /// multiplications are then computed in chunks with [`msm::ChunkedMsm`],
+    /// and with fewer threads if need be, so that their working memory fits in
+    /// the rest, at some cost in speed.
+    ///
+    /// The sizes of the buffers allocated inside arkworks are estimates (see
+    /// [`ProverOptions::memory_plan`]), so the budget bounds the prover's peak
+    /// memory approximately, not exactly.
+    pub memory_budget: Option<usize>,
+    /// A seed for the prover's randomness.
+    ///
+    /// When set, the proof's blinding randomness is derived from this seed
+    /// instead of the provided RNG, so that the same inputs always give the
+    /// same proof. This is only intended for testing: proofs generated with
+    /// the same seed for different witnesses can leak information about them.
+    pub deterministic_seed: Option<[u8; 32]>,
+    /// A callback to report the progress of proving.
+    pub progress: Option<ProgressCallback>,
+    /// Whether to lock the byte buffers that secret witnesses, such as
+    /// blinding factors and randomizers, are decoded from into memory while
+    /// proving, so that those buffers are never written to swap.
+    ///
+    /// These buffers are always zeroized once constraint generation is done
+    /// with them. Locking them requires the `mlock` feature on a unix
+    /// platform, and proving fails if they can't be locked.
+    ///
+    /// This does not cover other copies of the witnesses: the circuit's own
+    /// fields and the constraint system's witness assignment are neither
+    /// locked nor zeroized, and may still be swapped out.
+    pub lock_witness_memory: bool,
+    /// The implementation of the multi-scalar multiplications that dominate
+    /// proving time.
+    ///
+    /// When unset, proofs are generated by `ark-groth16` directly.
+    pub msm_backend: Option<SharedMsmBackend>,
+}
+
+impl std::fmt::Debug for ProverOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProverOptions")
+            .field("threads", &self.threads)
+            .field("memory_budget", &self.memory_budget)
+            .field(
+                "deterministic_seed",
+                &self.deterministic_seed.map(hex::encode),
+            )
+            .field("progress", &self.progress.is_some())
+            .field("lock_witness_memory", &self.lock_witness_memory)
+            .field(
+                "msm_backend",
+                &self.msm_backend.as_ref().map(|backend| backend.name()),
+            )
+            .finish()
+    }
+}
+
+impl ProverOptions {
+    /// Set the maximum number of threads to prove with.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Prove within `memory_budget` bytes of memory.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Derive the prover's randomness from `seed`, making proving deterministic.
+    pub fn with_deterministic_seed(mut self, seed: [u8; 32]) -> Self {
+        self.deterministic_seed = Some(seed);
+        self
+    }
+
+    /// Lock secret witness buffers into memory while proving.
+    pub fn with_locked_witness_memory(mut self) -> Self {
+        self.lock_witness_memory = true;
+        self
+    }
+
+    /// Compute the prover's multi-scalar multiplications with `backend`.
+    pub fn with_msm_backend(mut self, backend: impl MsmBackend + 'static) -> Self {
+        self.msm_backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Report the progress of proving to `progress`.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(ProvingStage) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// The number of threads to prove with, and the size of the chunks to
+    /// compute multi-scalar multiplications in, if they must be chunked to fit
+    /// the memory budget.
+    ///
+    /// The memory left after the proving key and the witness data is divided
+    /// between the threads, each of which computes one chunk at a time. Returns
+    /// an error if not even a single thread's smallest chunk fits.
+    fn memory_plan(&self, pk: &ProvingKey<Bls12_377>) -> anyhow::Result<(usize, Option<usize>)> {
+        let threads = if cfg!(feature = "std") {
+            self.threads
+                .unwrap_or_else(rayon::current_num_threads)
+                .max(1)
+        } else {
+            1
+        };
+
+        let memory_budget = match self.memory_budget {
+            Some(memory_budget) => memory_budget,
+            None => return Ok((threads, None)),
+        };
+
+        let fixed_bytes = pk.uncompressed_size() + witness_bytes(pk);
+        let min_bytes = fixed_bytes + MIN_MSM_CHUNK_SIZE * MSM_BYTES_PER_BASE;
+        if min_bytes > memory_budget {
+            anyhow::bail!(
+                "proving requires at least {} bytes, exceeding the memory budget of {} bytes",
+                min_bytes,
+                memory_budget
+            );
+        }
+
+        let bases_in_flight = (memory_budget - fixed_bytes) / MSM_BYTES_PER_BASE;
+        let threads = threads.min(bases_in_flight / MIN_MSM_CHUNK_SIZE).max(1);
+        Ok((threads, Some(bases_in_flight / threads)))
+    }
+
+    /// Generate a proof for `circuit` according to these options.
+    pub(crate) fn prove<C: ConstraintSynthesizer<Fq> + Send, R: CryptoRng + Rng + Send>(
+        &self,
+        pk: &ProvingKey<Bls12_377>,
+        circuit: C,
+        rng: &mut R,
+    ) -> anyhow::Result<Proof<Bls12_377>> {
+        let (threads, chunk_size) = self.memory_plan(pk)?;
+        self.report(ProvingStage::Started { threads });
+
+        // The circuit is synthesized on the thread that runs `prove`, so that is
+        // where witness locking must be enabled.
+        let lock = self.lock_witness_memory;
+        let deterministic_seed = self.deterministic_seed;
+        let backend = match chunk_size {
+            Some(chunk_size) => {
+                let inner = self
+                    .msm_backend
+                    .clone()
+                    .unwrap_or_else(|| Arc::new(msm::ArkworksMsm));
+                Some(Arc::new(msm::ChunkedMsm::new(inner, chunk_size)) as SharedMsmBackend)
+            }
+            None => self.msm_backend.clone(),
+        };
+        // Operations are counted by `msm::prove`, which gives the same proofs
+        // as `ark-groth16`, so it is used whenever they are being counted.
+        let backend = backend.or_else(|| {
+            metrics::counting_operations().then(|| Arc::new(msm::ArkworksMsm) as SharedMsmBackend)
+        });
+        let prove = move || match deterministic_seed {
+            Some(seed) => {
+                let mut seeded_rng = ChaCha20Rng::from_seed(seed);
+                witness::with_locked_witnesses(lock, || {
+                    prove_with_backend(backend.as_deref(), pk, circuit, &mut seeded_rng)
+                })
+            }
+            None => witness::with_locked_witnesses(lock, || {
+                prove_with_backend(backend.as_deref(), pk, circuit, rng)
+            }),
+        };
+
+        // Proving runs inside a dedicated pool, so that the parallel
+        // multi-scalar multiplications are executed on as many threads as the
+        // thread limit and memory budget allow at once.
+        #[cfg(feature = "std")]
+        let proof = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?
+            .install(prove);
+        // Without the `std` feature, e.g. in a browser, threads can't be
+        // spawned, so proving happens on the calling thread.
+        #[cfg(not(feature = "std"))]
+        let proof = prove();
+
+        let (proof, counts) = proof?.map_err(|err| anyhow::anyhow!(err))?;
+        if let Some(counts) = counts {
+            metrics::record_operations(counts);
+        }
+
+        self.report(ProvingStage::Finished);
+        Ok(proof)
+    }
+
+    fn report(&self, stage: ProvingStage) {
+        if let Some(progress) = &self.progress {
+            progress(stage);
+        }
+    }
+}
+
+/// The estimated bytes held while proving, besides the proving key and the
+/// working memory of the multi-scalar multiplications: the witness assignment,
+/// its scalar representations, and the evaluations of the QAP polynomials.
+fn witness_bytes(pk: &ProvingKey<Bls12_377>) -> usize {
+    let variables = pk.a_query.len();
+    let domain_size = pk.h_query.len() + 1;
+    (3 * variables + 4 * domain_size) * std::mem::size_of::<Fq>()
+}
+
+/// Generate a proof, returning the curve operations performed if proving with
+/// an MSM backend.
+fn prove_with_backend<C: ConstraintSynthesizer<Fq>, R: CryptoRng + Rng>(
+    backend: Option<&dyn MsmBackend>,
+    pk: &ProvingKey<Bls12_377>,
+    circuit: C,
+    rng: &mut R,
+) -> Result<(Proof<Bls12_377>, Option<OperationCounts>), SynthesisError> {
+    match backend {
+        Some(backend) => {
+            let (proof, counts) = msm::prove(backend, pk, circuit, rng)?;
+            Ok((proof, Some(counts)))
+        }
+        None => Ok((Groth16::prove(pk, circuit, rng)?, None)),
+    }
+}
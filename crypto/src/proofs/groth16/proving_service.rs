@@ -0,0 +1,218 @@
+//! Delegating proving to another machine.
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+#[cfg(feature = "std")]
+use anyhow::Context;
+#[cfg(feature = "std")]
+use ark_groth16::{ProvingKey, VerifyingKey};
+#[cfg(feature = "std")]
+use decaf377::Bls12_377;
+use futures::future::BoxFuture;
+#[cfg(feature = "std")]
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+#[cfg(feature = "std")]
+use super::ProverOptions;
+use super::{OutputProof, OutputProofPlan, SpendProof, SpendProofPlan};
+
+#[cfg(feature = "rpc")]
+mod rpc;
+
+#[cfg(feature = "rpc")]
+pub use rpc::ProvingServer;
+
+/// A service that proves spends and outputs from their proof plans.
+///
+/// A plan contains the notes being spent or created, and the nullifier key of
+/// spends, so a service that isn't [`LocalProver`] must be trusted with them.
+/// The proofs it returns should be checked and rerandomized before they are
+/// included in a transaction, by wrapping it in a [`RerandomizingProver`].
+///
+/// With the `rpc` feature, the gRPC `ProvingServiceClient` implements this
+/// trait, sending plans in their protobuf encoding, and any implementation can
+/// be served over gRPC with a [`ProvingServer`].
+pub trait ProvingService: Send + Sync {
+    /// Prove the spend described by `plan`.
+    fn prove_spend(&self, plan: SpendProofPlan) -> BoxFuture<'_, anyhow::Result<SpendProof>>;
+
+    /// Prove the output described by `plan`.
+    fn prove_output(&self, plan: OutputProofPlan) -> BoxFuture<'_, anyhow::Result<OutputProof>>;
+}
+
+/// Proves plans on this machine, each on its own thread.
+///
+/// Each proof's randomness is drawn from a fresh RNG made by `F`, which is
+/// [`OsRng`] unless another is given with [`LocalProver::with_rng`].
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct LocalProver<F = fn() -> OsRng> {
+    spend_pk: Arc<ProvingKey<Bls12_377>>,
+    output_pk: Arc<ProvingKey<Bls12_377>>,
+    options: ProverOptions,
+    rng: F,
+}
+
+#[cfg(feature = "std")]
+impl LocalProver {
+    /// Prove with the given spend and output proving keys, and the default
+    /// [`ProverOptions`].
+    pub fn new(
+        spend_pk: Arc<ProvingKey<Bls12_377>>,
+        output_pk: Arc<ProvingKey<Bls12_377>>,
+    ) -> Self {
+        Self {
+            spend_pk,
+            output_pk,
+            options: ProverOptions::default(),
+            rng: || OsRng,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F> LocalProver<F> {
+    /// Prove according to the given [`ProverOptions`].
+    pub fn with_options(mut self, options: ProverOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Draw the randomness for each proof from a fresh RNG made by `rng`.
+    pub fn with_rng<G, R>(self, rng: G) -> LocalProver<G>
+    where
+        G: Fn() -> R + Send + Sync,
+        R: RngCore + CryptoRng + Send + 'static,
+    {
+        LocalProver {
+            spend_pk: self.spend_pk,
+            output_pk: self.output_pk,
+            options: self.options,
+            rng,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F, R> ProvingService for LocalProver<F>
+where
+    F: Fn() -> R + Send + Sync,
+    R: RngCore + CryptoRng + Send + 'static,
+{
+    fn prove_spend(&self, plan: SpendProofPlan) -> BoxFuture<'_, anyhow::Result<SpendProof>> {
+        let (options, pk, mut rng) = (self.options.clone(), self.spend_pk.clone(), (self.rng)());
+        spawn_proving(move || plan.prove_with_options(&options, &mut rng, pk))
+    }
+
+    fn prove_output(&self, plan: OutputProofPlan) -> BoxFuture<'_, anyhow::Result<OutputProof>> {
+        let (options, pk, mut rng) = (self.options.clone(), self.output_pk.clone(), (self.rng)());
+        spawn_proving(move || plan.prove_with_options(&options, &mut rng, pk))
+    }
+}
+
+/// Run `prove` on its own thread, since proving takes seconds, and shouldn't
+/// block the caller's executor.
+#[cfg(feature = "std")]
+fn spawn_proving<T, F>(prove: F) -> BoxFuture<'static, anyhow::Result<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(prove());
+    });
+    Box::pin(async move { receiver.await.expect("proving runs to completion") })
+}
+
+/// Wraps a [`ProvingService`], checking each proof it returns against the
+/// public inputs of its plan, and then rerandomizing it.
+///
+/// A rerandomized proof is a fresh proof of the same statement, so the service
+/// can't link the proofs it made to the ones that appear on chain. Checking the
+/// proofs first means a faulty service is caught before a transaction is
+/// built, rather than when it is rejected.
+///
+/// Like [`LocalProver`], the randomness for each proof is drawn from a fresh
+/// RNG made by `F`, which is [`OsRng`] unless another is given with
+/// [`RerandomizingProver::with_rng`].
+#[cfg(feature = "std")]
+pub struct RerandomizingProver<S, F = fn() -> OsRng> {
+    service: S,
+    spend_vk: VerifyingKey<Bls12_377>,
+    output_vk: VerifyingKey<Bls12_377>,
+    rng: F,
+}
+
+#[cfg(feature = "std")]
+impl<S: ProvingService> RerandomizingProver<S> {
+    /// Check and rerandomize the proofs from `service` with the given spend and
+    /// output verifying keys.
+    pub fn new(
+        service: S,
+        spend_vk: VerifyingKey<Bls12_377>,
+        output_vk: VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self {
+            service,
+            spend_vk,
+            output_vk,
+            rng: || OsRng,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: ProvingService, F> RerandomizingProver<S, F> {
+    /// Draw the randomness for rerandomizing each proof from a fresh RNG made
+    /// by `rng`.
+    pub fn with_rng<G, R>(self, rng: G) -> RerandomizingProver<S, G>
+    where
+        G: Fn() -> R + Send + Sync,
+        R: RngCore + CryptoRng,
+    {
+        RerandomizingProver {
+            service: self.service,
+            spend_vk: self.spend_vk,
+            output_vk: self.output_vk,
+            rng,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, F, R> ProvingService for RerandomizingProver<S, F>
+where
+    S: ProvingService,
+    F: Fn() -> R + Send + Sync,
+    R: RngCore + CryptoRng,
+{
+    fn prove_spend(&self, plan: SpendProofPlan) -> BoxFuture<'_, anyhow::Result<SpendProof>> {
+        Box::pin(async move {
+            let (anchor, balance_commitment, nullifier, rk) = (
+                plan.anchor,
+                plan.balance_commitment,
+                plan.nullifier,
+                plan.rk,
+            );
+            let proof = self.service.prove_spend(plan).await?;
+            proof
+                .verify(&self.spend_vk, anchor, balance_commitment, nullifier, rk)
+                .context("proving service returned an invalid spend proof")?;
+            Ok(proof.rerandomize(&mut (self.rng)(), &self.spend_vk))
+        })
+    }
+
+    fn prove_output(&self, plan: OutputProofPlan) -> BoxFuture<'_, anyhow::Result<OutputProof>> {
+        Box::pin(async move {
+            let (balance_commitment, note_commitment) =
+                (plan.balance_commitment, plan.note_commitment);
+            let proof = self.service.prove_output(plan).await?;
+            proof
+                .verify(&self.output_vk, balance_commitment, note_commitment)
+                .context("proving service returned an invalid output proof")?;
+            Ok(proof.rerandomize(&mut (self.rng)(), &self.output_vk))
+        })
+    }
+}
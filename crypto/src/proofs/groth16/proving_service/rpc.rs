@@ -0,0 +1,109 @@
+//! The [`ProvingService`] over gRPC.
+
+use anyhow::Context;
+use futures::future::BoxFuture;
+use penumbra_proto::prover::v1alpha1::{
+    self as pb, proving_service_client::ProvingServiceClient,
+    proving_service_server::ProvingService as ProvingServiceRpc,
+};
+use tonic::{async_trait, transport::Channel, Request, Response, Status};
+
+use super::ProvingService;
+use crate::proofs::groth16::{OutputProof, OutputProofPlan, SpendProof, SpendProofPlan};
+
+/// Serves a [`ProvingService`], such as a [`LocalProver`](super::LocalProver),
+/// over gRPC.
+///
+/// Wrap it in a `ProvingServiceServer` to add it to a `tonic` server.
+pub struct ProvingServer<S> {
+    service: S,
+}
+
+impl<S: ProvingService> ProvingServer<S> {
+    /// Serve proofs from `service`.
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl<S: ProvingService + 'static> ProvingServiceRpc for ProvingServer<S> {
+    async fn prove_spend(
+        &self,
+        request: Request<pb::ProveSpendRequest>,
+    ) -> Result<Response<pb::ProveSpendResponse>, Status> {
+        let plan: SpendProofPlan = request
+            .into_inner()
+            .plan
+            .ok_or_else(|| Status::invalid_argument("missing spend proof plan"))?
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+
+        let proof = self
+            .service
+            .prove_spend(plan)
+            .await
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+
+        Ok(Response::new(pb::ProveSpendResponse {
+            proof: Some(proof.into()),
+        }))
+    }
+
+    async fn prove_output(
+        &self,
+        request: Request<pb::ProveOutputRequest>,
+    ) -> Result<Response<pb::ProveOutputResponse>, Status> {
+        let plan: OutputProofPlan = request
+            .into_inner()
+            .plan
+            .ok_or_else(|| Status::invalid_argument("missing output proof plan"))?
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+
+        let proof = self
+            .service
+            .prove_output(plan)
+            .await
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+
+        Ok(Response::new(pb::ProveOutputResponse {
+            proof: Some(proof.into()),
+        }))
+    }
+}
+
+// The generated client methods take `&mut self`, but connections are cheap to
+// clone, so each request is made on its own clone of the client. They are
+// called by path, since method call syntax would pick this trait's methods.
+impl ProvingService for ProvingServiceClient<Channel> {
+    fn prove_spend(&self, plan: SpendProofPlan) -> BoxFuture<'_, anyhow::Result<SpendProof>> {
+        let mut client = self.clone();
+        Box::pin(async move {
+            let request = pb::ProveSpendRequest {
+                plan: Some(plan.into()),
+            };
+            ProvingServiceClient::prove_spend(&mut client, request)
+                .await?
+                .into_inner()
+                .proof
+                .context("missing spend proof in response")?
+                .try_into()
+        })
+    }
+
+    fn prove_output(&self, plan: OutputProofPlan) -> BoxFuture<'_, anyhow::Result<OutputProof>> {
+        let mut client = self.clone();
+        Box::pin(async move {
+            let request = pb::ProveOutputRequest {
+                plan: Some(plan.into()),
+            };
+            ProvingServiceClient::prove_output(&mut client, request)
+                .await?
+                .into_inner()
+                .proof
+                .context("missing output proof in response")?
+                .try_into()
+        })
+    }
+}
@@ -0,0 +1,317 @@
+//! Verifying keys for several versions of each circuit at once.
+//!
+//! When a circuit changes, proofs made against its old parameters remain on
+//! chain, and for a while, proofs against both the old and new parameters are
+//! submitted. Each version of a circuit's parameters is identified by a
+//! [`CircuitId`], which on-chain data can reference, and a
+//! [`ParameterRegistry`] holds the prepared verifying key for every version a
+//! node supports, so that each proof is verified with the key for the version
+//! it names.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use ark_groth16::{PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+use decaf377::Bls12_377;
+use penumbra_proof_verify as verify;
+
+use super::{
+    error::VerificationError, DelegatorVoteProof, DelegatorVotePublicInputs, OutputProof,
+    OutputPublicInputs, ParameterSetup, PublicInputs, SpendProof, SpendPublicInputs,
+    SwapClaimProof, SwapClaimPublicInputs, SwapProof, SwapPublicInputs, UndelegateClaimProof,
+    UndelegateClaimPublicInputs,
+};
+
+/// The identifier of one version of a circuit's parameters.
+///
+/// It is the hash of the circuit's version string and its verifying key, so
+/// it changes whenever either the circuit or its parameters do.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CircuitId(pub [u8; 32]);
+
+impl CircuitId {
+    /// The identifier of version `version` of a circuit, with verifying key
+    /// `vk`.
+    pub fn new(version: &str, vk: &VerifyingKey<Bls12_377>) -> Self {
+        let mut vk_bytes = Vec::new();
+        vk.serialize(&mut vk_bytes)
+            .expect("can serialize verifying key to a vector");
+
+        let hash = blake2b_simd::Params::default()
+            .personal(b"Penumbra_Circuit")
+            .hash_length(32)
+            .to_state()
+            .update(&(version.len() as u64).to_le_bytes())
+            .update(version.as_bytes())
+            .update(&vk_bytes)
+            .finalize();
+
+        let mut id = [0u8; 32];
+        id.copy_from_slice(hash.as_bytes());
+        Self(id)
+    }
+}
+
+impl fmt::Debug for CircuitId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CircuitId")
+            .field(&hex::encode(self.0))
+            .finish()
+    }
+}
+
+impl fmt::Display for CircuitId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for CircuitId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)?;
+        Ok(Self(bytes.try_into().map_err(|bytes: Vec<u8>| {
+            anyhow::anyhow!("circuit id must be 32 bytes, got {}", bytes.len())
+        })?))
+    }
+}
+
+/// An error verifying a proof against a [`ParameterRegistry`].
+#[derive(thiserror::Error, Debug)]
+pub enum RegistryError {
+    /// No verifying key is registered with the circuit id.
+    #[error("no verifying key is registered for circuit {0}")]
+    UnknownCircuit(CircuitId),
+    /// The verifying key registered with the circuit id is for another
+    /// circuit.
+    #[error("circuit {id} is a version of the {registered} circuit, not the {expected} circuit")]
+    WrongCircuit {
+        id: CircuitId,
+        registered: &'static str,
+        expected: &'static str,
+    },
+    /// The proof did not verify with the registered key.
+    #[error(transparent)]
+    Verification(#[from] VerificationError),
+}
+
+/// A Groth16 proof of one of the circuits whose verifying keys can be held in a
+/// [`ParameterRegistry`].
+pub trait RegisteredProof {
+    /// The public inputs the proof is verified against.
+    type PublicInputs: PublicInputs;
+
+    /// The underlying Groth16 proof.
+    fn groth16_proof(&self) -> &Proof<Bls12_377>;
+}
+
+macro_rules! registered_proof {
+    ($proof:ty, $public_inputs:ty) => {
+        impl RegisteredProof for $proof {
+            type PublicInputs = $public_inputs;
+
+            fn groth16_proof(&self) -> &Proof<Bls12_377> {
+                &self.0
+            }
+        }
+    };
+}
+
+registered_proof!(SpendProof, SpendPublicInputs);
+registered_proof!(OutputProof, OutputPublicInputs);
+registered_proof!(SwapProof, SwapPublicInputs);
+registered_proof!(SwapClaimProof, SwapClaimPublicInputs);
+registered_proof!(UndelegateClaimProof, UndelegateClaimPublicInputs);
+registered_proof!(DelegatorVoteProof, DelegatorVotePublicInputs);
+
+struct Entry {
+    circuit: &'static str,
+    version: String,
+    pvk: PreparedVerifyingKey<Bls12_377>,
+}
+
+/// The prepared verifying keys of every supported version of each circuit,
+/// keyed by [`CircuitId`].
+#[derive(Default)]
+pub struct ParameterRegistry {
+    keys: HashMap<CircuitId, Entry>,
+}
+
+impl fmt::Debug for ParameterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.keys
+                    .iter()
+                    .map(|(id, entry)| (id, (entry.circuit, &entry.version))),
+            )
+            .finish()
+    }
+}
+
+impl ParameterRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `vk` as the verifying key of version `version` of the circuit
+    /// `C`, returning its [`CircuitId`].
+    ///
+    /// Registering the same version of the same key again has no effect.
+    pub fn register<C: ParameterSetup>(
+        &mut self,
+        version: &str,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Result<CircuitId, VerificationError> {
+        C::check_verifying_key(vk)?;
+        let id = CircuitId::new(version, vk);
+        self.keys.entry(id).or_insert_with(|| Entry {
+            circuit: C::NAME,
+            version: version.to_owned(),
+            pvk: ark_groth16::prepare_verifying_key(vk),
+        });
+        Ok(id)
+    }
+
+    /// Stop supporting the circuit version with the given id, e.g. once an
+    /// upgrade has completed.
+    ///
+    /// Returns `true` if it was registered.
+    pub fn remove(&mut self, id: &CircuitId) -> bool {
+        self.keys.remove(id).is_some()
+    }
+
+    /// Get the prepared verifying key registered with the given id.
+    pub fn get(&self, id: &CircuitId) -> Option<&PreparedVerifyingKey<Bls12_377>> {
+        self.keys.get(id).map(|entry| &entry.pvk)
+    }
+
+    /// Get the name and version of the circuit registered with the given id.
+    pub fn circuit(&self, id: &CircuitId) -> Option<(&'static str, &str)> {
+        self.keys
+            .get(id)
+            .map(|entry| (entry.circuit, entry.version.as_str()))
+    }
+
+    /// The ids of the registered versions of the circuit `C`.
+    pub fn versions_of<C: ParameterSetup>(&self) -> impl Iterator<Item = CircuitId> + '_ {
+        self.keys
+            .iter()
+            .filter(|(_, entry)| entry.circuit == C::NAME)
+            .map(|(id, _)| *id)
+    }
+
+    /// Verify `proof` against `public_inputs`, with the verifying key
+    /// registered with the given id.
+    pub fn verify<P: RegisteredProof>(
+        &self,
+        id: &CircuitId,
+        proof: &P,
+        public_inputs: &P::PublicInputs,
+    ) -> Result<(), RegistryError> {
+        let entry = self
+            .keys
+            .get(id)
+            .ok_or(RegistryError::UnknownCircuit(*id))?;
+        let expected = <<P::PublicInputs as PublicInputs>::Circuit as ParameterSetup>::NAME;
+        if entry.circuit != expected {
+            return Err(RegistryError::WrongCircuit {
+                id: *id,
+                registered: entry.circuit,
+                expected,
+            });
+        }
+
+        let public_inputs = public_inputs.to_field_elements()?;
+        Ok(verify::verify_proof(
+            &entry.pvk,
+            &public_inputs,
+            proof.groth16_proof(),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{OsRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        keys::{SeedPhrase, SpendKey},
+        proofs::groth16::{OutputCircuit, OutputProofPlan, SpendCircuit},
+        Fr, Note, Value, STAKING_TOKEN_ASSET_ID,
+    };
+
+    #[test]
+    fn proofs_verify_against_the_version_they_name() {
+        let (old_pk, old_vk) =
+            OutputCircuit::generate_test_parameters_with_rng(&mut ChaCha20Rng::seed_from_u64(1));
+        let (_new_pk, new_vk) =
+            OutputCircuit::generate_test_parameters_with_rng(&mut ChaCha20Rng::seed_from_u64(2));
+
+        let mut registry = ParameterRegistry::new();
+        let old = registry.register::<OutputCircuit>("v1", &old_vk).unwrap();
+        let new = registry.register::<OutputCircuit>("v2", &new_vk).unwrap();
+        assert_ne!(old, new);
+        assert_ne!(old, CircuitId::new("v2", &old_vk));
+        assert_eq!(old.to_string().parse::<CircuitId>().unwrap(), old);
+        assert_eq!(registry.circuit(&new), Some((OutputCircuit::NAME, "v2")));
+        assert_eq!(registry.versions_of::<OutputCircuit>().count(), 2);
+        assert_eq!(registry.versions_of::<SpendCircuit>().count(), 0);
+
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([b'r'; 32]), 0);
+        let (dest, _dtk_d) = sk.full_viewing_key().payment_address(0u64.into());
+        let value = Value {
+            amount: 10u64.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(&mut OsRng, &dest, value);
+        let v_blinding = Fr::from(7u64);
+        let public_inputs = OutputPublicInputs {
+            balance_commitment: value.commit(v_blinding),
+            note_commitment: note.commit(),
+            memo_key_commitment: None,
+        };
+        let proof = OutputProofPlan {
+            note,
+            v_blinding,
+            balance_commitment: public_inputs.balance_commitment,
+            note_commitment: public_inputs.note_commitment,
+        }
+        .prove(&mut OsRng, &old_pk)
+        .unwrap();
+
+        registry.verify(&old, &proof, &public_inputs).unwrap();
+        assert!(matches!(
+            registry.verify(&new, &proof, &public_inputs),
+            Err(RegistryError::Verification(VerificationError::ProofInvalid))
+        ));
+
+        // A key registered for one circuit is never used for another.
+        let spend_proof = SpendProof(proof.0.clone());
+        assert!(matches!(
+            registry.verify(
+                &old,
+                &spend_proof,
+                &SpendPublicInputs {
+                    anchor: penumbra_tct::Tree::new().root(),
+                    balance_commitment: public_inputs.balance_commitment,
+                    nullifier: crate::Nullifier(Default::default()),
+                    rk: decaf377::basepoint(),
+                }
+            ),
+            Err(RegistryError::WrongCircuit { .. })
+        ));
+
+        // Once the old version is retired, its proofs are rejected.
+        assert!(registry.remove(&old));
+        assert!(registry.get(&old).is_none());
+        assert!(matches!(
+            registry.verify(&old, &proof, &public_inputs),
+            Err(RegistryError::UnknownCircuit(_))
+        ));
+    }
+}
@@ -1,7 +1,8 @@
-use std::str::FromStr;
+use std::{borrow::Borrow, str::FromStr};
 
+use anyhow::Context;
 use ark_r1cs_std::{
-    prelude::{EqGadget, FieldVar},
+    prelude::{Boolean, EqGadget, FieldVar},
     uint8::UInt8,
 };
 use decaf377::{
@@ -10,21 +11,34 @@ use decaf377::{
 };
 use decaf377::{Element, FieldExt};
 
-use ark_ff::{PrimeField, ToConstraintField};
-use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
 use ark_r1cs_std::prelude::AllocVar;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
 use ark_snark::SNARK;
 use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_proof_verify as verify;
+use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 use penumbra_tct as tct;
 use rand::{CryptoRng, Rng};
-use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
 
-use crate::proofs::groth16::{gadgets, ParameterSetup};
+use crate::proofs::groth16::{
+    error::{self, VerificationError},
+    gadgets, metrics,
+    traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS},
+    witness::SecretBytes,
+    CircuitDescription, Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable,
+    VariableKind,
+};
+use crate::proofs::ProofRng;
 use crate::{
+    asset::FromAmount,
     balance,
-    keys::{NullifierKey, SeedPhrase, SpendKey},
-    Note, Nullifier, Rseed, Value,
+    keys::{NullifierKey, RandomizedVerificationKey, SeedPhrase, SpendAuthRandomizer, SpendKey},
+    sample::SampleUniform,
+    Address, Note, Nullifier, Rseed, Value, STAKING_TOKEN_ASSET_ID,
 };
 
 /// Groth16 proof for spending existing notes.
@@ -38,7 +52,7 @@ pub struct SpendCircuit {
     /// The blinding factor used for generating the value commitment.
     v_blinding: Fr,
     /// The randomizer used for generating the randomized spend auth key.
-    spend_auth_randomizer: Fr,
+    spend_auth_randomizer: SpendAuthRandomizer,
     /// The spend authorization key.
     ak: VerificationKey<SpendAuth>,
     /// The nullifier deriving key.
@@ -57,6 +71,86 @@ pub struct SpendCircuit {
 
 impl ConstraintSynthesizer<Fq> for SpendCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        self.synthesize(cs, false)
+    }
+}
+
+impl SpendCircuit {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        note_commitment_proof: tct::Proof,
+        note: Note,
+        v_blinding: Fr,
+        spend_auth_randomizer: SpendAuthRandomizer,
+        ak: VerificationKey<SpendAuth>,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> Self {
+        SpendCircuit {
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk: rk.element(),
+        }
+    }
+
+    /// Check natively that the public inputs are the ones the witnesses determine, panicking
+    /// with the name of the first one that isn't.
+    ///
+    /// Dummy spends, of zero value, are exempt, as they are in the circuit.
+    #[cfg(feature = "debug-asserts")]
+    fn assert_public_inputs(&self) {
+        if u64::from(self.note.amount()) == 0 {
+            return;
+        }
+
+        let note_commitment = self.note.commit();
+        assert_eq!(
+            note_commitment,
+            self.note_commitment_proof.commitment(),
+            "spend proof input mismatch: the note commitment proof is not for the spent note"
+        );
+        assert!(
+            self.note_commitment_proof.verify(self.anchor).is_ok(),
+            "spend proof input mismatch: the note commitment proof does not verify against the anchor {}",
+            self.anchor
+        );
+        assert_eq!(
+            self.nk
+                .derive_nullifier(self.note_commitment_proof.position(), &note_commitment),
+            self.nullifier,
+            "spend proof input mismatch: the nullifier is not derived from the note and its position"
+        );
+        assert_eq!(
+            self.spend_auth_randomizer
+                .randomize_verification_key(&self.ak)
+                .to_bytes(),
+            self.rk.vartime_compress().0,
+            "spend proof input mismatch: rk is not ak randomized by the spend auth randomizer"
+        );
+        assert_eq!(
+            self.note.value().commit(self.v_blinding),
+            self.balance_commitment,
+            "spend proof input mismatch: the balance commitment does not commit to the note's value"
+        );
+    }
+
+    /// Synthesize the spend statement, additionally deriving the diversified
+    /// basepoint from the note's diversifier if `derive_generator` is set.
+    fn synthesize(
+        self,
+        cs: ConstraintSystemRef<Fq>,
+        derive_generator: bool,
+    ) -> ark_relations::r1cs::Result<()> {
         // Witnesses
         let note_commitment_var =
             FqVar::new_witness(cs.clone(), || Ok(self.note_commitment_proof.commitment().0))?;
@@ -65,10 +159,14 @@ impl ConstraintSynthesizer<Fq> for SpendCircuit {
         let merkle_path_var =
             tct::r1cs::MerkleAuthPathVar::new(cs.clone(), self.note_commitment_proof)?;
 
-        let note_blinding_var =
-            FqVar::new_witness(cs.clone(), || Ok(self.note.note_blinding().clone()))?;
+        // The note blinding is witnessed directly, rather than derived in-circuit
+        // from the rseed: the native derivation (`Rseed::derive_note_blinding`)
+        // uses BLAKE2b, which has no R1CS gadget over `Fq`. Migrating to an
+        // rseed witness requires first moving that derivation to a
+        // circuit-friendly hash, which changes every note commitment.
+        let note_blinding_var = FqVar::new_witness(cs.clone(), || Ok(self.note.note_blinding()))?;
         let value_amount_var =
-            FqVar::new_witness(cs.clone(), || Ok(Fq::from(self.note.value().amount)))?;
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(self.note.value().amount)))?;
         let value_asset_id_var =
             FqVar::new_witness(cs.clone(), || Ok(self.note.value().asset_id.0))?;
         let diversified_generator_var: ElementVar =
@@ -85,11 +183,14 @@ impl ConstraintSynthesizer<Fq> for SpendCircuit {
         let clue_key_var = FqVar::new_witness(cs.clone(), || {
             Ok(Fq::from_le_bytes_mod_order(&self.note.clue_key().0[..]))
         })?;
-        let v_blinding_arr: [u8; 32] = self.v_blinding.to_bytes();
+        let v_blinding_arr = SecretBytes::new(self.v_blinding.to_bytes());
         let v_blinding_vars = UInt8::new_witness_vec(cs.clone(), &v_blinding_arr)?;
         let value_amount_arr = self.note.value().amount.to_le_bytes();
         let value_vars = UInt8::new_witness_vec(cs.clone(), &value_amount_arr)?;
-        let spend_auth_randomizer_arr: [u8; 32] = self.spend_auth_randomizer.to_bytes();
+        metrics::gadget(&cs, "amount_range_check", || {
+            gadgets::amount_range_check(&Boolean::TRUE, &value_amount_var, &value_vars)
+        })?;
+        let spend_auth_randomizer_arr = SecretBytes::new(self.spend_auth_randomizer.to_bytes());
         let spend_auth_randomizer_var: Vec<UInt8<Fq>> =
             UInt8::new_witness_vec(cs.clone(), &spend_auth_randomizer_arr)?;
         let ak_bytes = Fq::from_bytes(*self.ak.as_ref())
@@ -100,7 +201,7 @@ impl ConstraintSynthesizer<Fq> for SpendCircuit {
             .unwrap();
         let ak_element_var: ElementVar =
             AllocVar::<Element, Fq>::new_witness(cs.clone(), || Ok(ak_point))?;
-        let nk_var = FqVar::new_witness(cs.clone(), || Ok(self.nk.0))?;
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(*self.nk.expose_secret()))?;
 
         // Public inputs
         let anchor_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.anchor)))?;
@@ -117,76 +218,283 @@ impl ConstraintSynthesizer<Fq> for SpendCircuit {
         // dummy spend.
         let is_not_dummy = is_dummy.not();
 
-        gadgets::note_commitment_integrity(
-            cs.clone(),
-            &is_not_dummy,
-            note_blinding_var,
-            value_amount_var,
-            value_asset_id_var.clone(),
-            diversified_generator_var.clone(),
-            transmission_key_s_var,
-            clue_key_var,
-            note_commitment_var.clone(),
-        )?;
-        merkle_path_var.verify(
-            cs.clone(),
-            &is_not_dummy,
-            position_var.clone(),
-            anchor_var,
-            note_commitment_var.clone(),
-        )?;
-        gadgets::rk_integrity(
-            cs.clone(),
-            &is_not_dummy,
-            ak_element_var.clone(),
-            spend_auth_randomizer_var,
-            rk_fq_var,
-        )?;
-        gadgets::diversified_address_integrity(
-            cs.clone(),
-            &is_not_dummy,
-            ak_var,
-            nk_var.clone(),
-            transmission_key_var,
-            diversified_generator_var.clone(),
-        )?;
-        gadgets::diversified_basepoint_not_identity(
-            cs.clone(),
-            &is_not_dummy,
-            diversified_generator_var,
-        )?;
-        gadgets::ak_not_identity(cs.clone(), &is_not_dummy, ak_element_var)?;
-        gadgets::value_commitment_integrity(
-            cs.clone(),
-            &is_not_dummy,
-            value_vars,
-            value_asset_id_var,
-            v_blinding_vars,
-            balance_commitment_var,
-        )?;
-        gadgets::nullifier_integrity(
-            cs,
-            &is_not_dummy,
-            note_commitment_var,
-            nk_var,
-            position_var,
-            nullifier_var,
-        )?;
+        metrics::gadget(&cs, "note_commitment_integrity", || {
+            gadgets::note_commitment_integrity(
+                cs.clone(),
+                &is_not_dummy,
+                note_blinding_var,
+                value_amount_var,
+                value_asset_id_var.clone(),
+                diversified_generator_var.clone(),
+                transmission_key_s_var,
+                clue_key_var,
+                note_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "merkle_auth_path", || {
+            merkle_path_var.verify(
+                cs.clone(),
+                &is_not_dummy,
+                position_var.clone(),
+                anchor_var,
+                note_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "rk_integrity", || {
+            gadgets::rk_integrity(
+                cs.clone(),
+                &is_not_dummy,
+                ak_element_var.clone(),
+                spend_auth_randomizer_var,
+                rk_fq_var,
+            )
+        })?;
+        metrics::gadget(&cs, "ak_integrity", || {
+            gadgets::ak_integrity(&is_not_dummy, &ak_var, &ak_element_var)
+        })?;
+        metrics::gadget(&cs, "ivk_integrity", || {
+            gadgets::ivk_integrity(
+                cs.clone(),
+                &is_not_dummy,
+                ak_var,
+                nk_var.clone(),
+                transmission_key_var,
+                diversified_generator_var.clone(),
+            )
+        })?;
+        if derive_generator {
+            let diversifier_hash_var =
+                FqVar::new_witness(cs.clone(), || Ok(self.note.diversifier().hash_to_field()))?;
+            metrics::gadget(&cs, "diversified_generator_integrity", || {
+                gadgets::diversified_generator_integrity(
+                    &is_not_dummy,
+                    &diversifier_hash_var,
+                    &diversified_generator_var,
+                )
+            })?;
+        }
+        metrics::gadget(&cs, "diversified_basepoint_not_identity", || {
+            gadgets::diversified_basepoint_not_identity(
+                cs.clone(),
+                &is_not_dummy,
+                diversified_generator_var,
+            )
+        })?;
+        metrics::gadget(&cs, "ak_not_identity", || {
+            gadgets::ak_not_identity(cs.clone(), &is_not_dummy, ak_element_var)
+        })?;
+        metrics::gadget(&cs, "value_commitment_integrity", || {
+            gadgets::value_commitment_integrity(
+                cs.clone(),
+                &is_not_dummy,
+                &Boolean::FALSE,
+                value_vars,
+                value_asset_id_var,
+                v_blinding_vars,
+                balance_commitment_var,
+            )
+        })?;
+        metrics::gadget(&cs, "nullifier_integrity", || {
+            gadgets::nullifier_integrity(
+                cs.clone(),
+                &is_not_dummy,
+                note_commitment_var,
+                nk_var,
+                position_var,
+                nullifier_var,
+            )
+        })?;
 
         Ok(())
     }
+
+    /// A circuit for a _dummy_ spend, of a zero-value note to a random address.
+    ///
+    /// Since the value is zero, the circuit only enforces that it is in range,
+    /// so the note need not be in the tree with root `anchor`, and `rk` need not
+    /// randomize the note's spend authorization key. The witnesses are still
+    /// consistent with each other: the balance commitment commits to zero, and
+    /// the nullifier is derived from the note.
+    pub fn dummy<R: CryptoRng + Rng>(
+        rng: &mut R,
+        anchor: tct::Root,
+        rk: RandomizedVerificationKey,
+    ) -> Self {
+        let address = Address::dummy(rng);
+        let value = Value {
+            amount: 0u64.into(),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(rng, &address, value);
+        let note_commitment = note.commit();
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment)
+            .expect("can insert into empty tree");
+        let note_commitment_proof = nct.witness(note_commitment).expect("was witnessed");
+        let nk = NullifierKey(Fq::rand_uniform(rng));
+        let nullifier = nk.derive_nullifier(note_commitment_proof.position(), &note_commitment);
+        let v_blinding = Fr::rand_uniform(rng);
+
+        SpendCircuit {
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer: SpendAuthRandomizer::from(Fr::rand_uniform(rng)),
+            ak: *rk.verification_key(),
+            nk,
+            anchor,
+            balance_commitment: value.commit(v_blinding),
+            nullifier,
+            rk: rk.element(),
+        }
+    }
+
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::{Always, UnlessDummy};
+        use VariableKind::{Bytes, Element, Fq, MerkleAuthPath};
+
+        CircuitDescription {
+            name: "spend",
+            public_inputs: vec![
+                Variable::new("anchor", Fq),
+                Variable::new("balance_commitment", Element),
+                Variable::new("nullifier", Fq),
+                Variable::new("rk", Element),
+            ],
+            witnesses: vec![
+                Variable::new("note_commitment", Fq),
+                Variable::new("position", Fq),
+                Variable::new("merkle_path", MerkleAuthPath),
+                Variable::new("note_blinding", Fq),
+                Variable::new("value_amount", Fq),
+                Variable::new("value_asset_id", Fq),
+                Variable::new("diversified_generator", Element),
+                Variable::new("transmission_key_s", Fq),
+                Variable::new("transmission_key", Element),
+                Variable::new("clue_key", Fq),
+                Variable::new("v_blinding", Bytes { len: 32 }),
+                Variable::new("value_amount_bytes", Bytes { len: 16 }),
+                Variable::new("spend_auth_randomizer", Bytes { len: 32 }),
+                Variable::new("ak", Fq),
+                Variable::new("ak_element", Element),
+                Variable::new("nk", Fq),
+            ],
+            gadgets: vec![
+                Gadget::new(
+                    "amount_range_check",
+                    Always,
+                    &["value_amount", "value_amount_bytes"],
+                ),
+                Gadget::new(
+                    "note_commitment_integrity",
+                    UnlessDummy,
+                    &[
+                        "note_blinding",
+                        "value_amount",
+                        "value_asset_id",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "note_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "merkle_auth_path",
+                    UnlessDummy,
+                    &["position", "anchor", "note_commitment", "merkle_path"],
+                ),
+                Gadget::new(
+                    "rk_integrity",
+                    UnlessDummy,
+                    &["ak_element", "spend_auth_randomizer", "rk"],
+                ),
+                Gadget::new("ak_integrity", UnlessDummy, &["ak", "ak_element"]),
+                Gadget::new(
+                    "ivk_integrity",
+                    UnlessDummy,
+                    &["ak", "nk", "transmission_key", "diversified_generator"],
+                ),
+                Gadget::new(
+                    "diversified_basepoint_not_identity",
+                    UnlessDummy,
+                    &["diversified_generator"],
+                ),
+                Gadget::new("ak_not_identity", UnlessDummy, &["ak_element"]),
+                Gadget::new(
+                    "value_commitment_integrity",
+                    UnlessDummy,
+                    &[
+                        "value_amount_bytes",
+                        "value_asset_id",
+                        "v_blinding",
+                        "balance_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "nullifier_integrity",
+                    UnlessDummy,
+                    &["note_commitment", "nk", "position", "nullifier"],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the spend circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpendPublicInputs {
+    /// the merkle root of the note commitment tree.
+    pub anchor: tct::Root,
+    /// value commitment of the note to be spent.
+    pub balance_commitment: balance::Commitment,
+    /// nullifier of the note to be spent.
+    pub nullifier: Nullifier,
+    /// the randomized verification spend key.
+    pub rk: Element,
+}
+
+impl PublicInputs for SpendPublicInputs {
+    type Circuit = SpendCircuit;
+
+    fn from_circuit(circuit: &SpendCircuit) -> Self {
+        Self {
+            anchor: circuit.anchor,
+            balance_commitment: circuit.balance_commitment,
+            nullifier: circuit.nullifier,
+            rk: circuit.rk,
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let mut public_inputs = Vec::new();
+        public_inputs.extend(error::public_input("anchor", &Fq::from(self.anchor.0))?);
+        public_inputs.extend(error::public_input(
+            "balance_commitment",
+            &self.balance_commitment.0,
+        )?);
+        public_inputs.extend(error::public_input("nullifier", &self.nullifier.0)?);
+        public_inputs.extend(error::public_input("rk", &self.rk)?);
+        debug_assert_eq!(public_inputs.len(), SpendCircuit::NUM_PUBLIC_INPUTS);
+        Ok(public_inputs)
+    }
 }
 
 impl ParameterSetup for SpendCircuit {
-    fn generate_test_parameters() -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+    const NAME: &'static str = "spend";
+
+    // anchor, balance commitment, nullifier, rk
+    const NUM_PUBLIC_INPUTS: usize = 2 * FQ_PUBLIC_INPUTS + 2 * ELEMENT_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
         let seed_phrase = SeedPhrase::from_randomness([b'f'; 32]);
         let sk_sender = SpendKey::from_seed_phrase(seed_phrase, 0);
         let fvk_sender = sk_sender.full_viewing_key();
         let ivk_sender = fvk_sender.incoming();
         let (address, _dtk_d) = ivk_sender.payment_address(0u64.into());
 
-        let spend_auth_randomizer = Fr::from(1);
-        let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
+        let spend_auth_randomizer = SpendAuthRandomizer::from(Fr::from(1));
+        let rsk = spend_auth_randomizer.randomize_signing_key(sk_sender.spend_auth_key());
         let nk = *sk_sender.nullifier_key();
         let ak = sk_sender.spend_auth_key().into();
         let note = Note::from_parts(
@@ -207,7 +515,7 @@ impl ParameterSetup for SpendCircuit {
         let anchor = nct.root();
         let note_commitment_proof = nct.witness(note_commitment).unwrap();
 
-        let circuit = SpendCircuit {
+        SpendCircuit {
             note_commitment_proof,
             note,
             v_blinding,
@@ -218,35 +526,219 @@ impl ParameterSetup for SpendCircuit {
             balance_commitment: balance::Commitment(decaf377::basepoint()),
             nullifier,
             rk: element_rk,
-        };
-        let (pk, vk) = Groth16::circuit_specific_setup(circuit, &mut OsRng)
-            .expect("can perform circuit specific setup");
-        (pk, vk)
+        }
+    }
+}
+
+/// The [`SpendCircuit`] statement, strengthened to derive the note's
+/// diversified basepoint from its diversifier, rather than only checking that
+/// it is not the identity.
+///
+/// The diversifier's hash to the field is still witnessed (see
+/// [`Diversifier::hash_to_field`](crate::keys::Diversifier::hash_to_field)), but
+/// the basepoint must be its image under the encode-to-curve map. This circuit
+/// has its own parameters, so its proofs are only valid under its own
+/// verifying key.
+#[derive(Clone, Debug)]
+pub struct StrictSpendCircuit(pub SpendCircuit);
+
+impl ConstraintSynthesizer<Fq> for StrictSpendCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        self.0.synthesize(cs, true)
+    }
+}
+
+impl StrictSpendCircuit {
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        let mut description = SpendCircuit::describe();
+        description.name = "strict_spend";
+        description
+            .witnesses
+            .push(Variable::new("diversifier_hash", VariableKind::Fq));
+        description.gadgets.push(Gadget::new(
+            "diversified_generator_integrity",
+            Enforcement::UnlessDummy,
+            &["diversifier_hash", "diversified_generator"],
+        ));
+        description
+    }
+}
+
+impl ParameterSetup for StrictSpendCircuit {
+    const NAME: &'static str = "strict_spend";
+
+    // The same public inputs as the spend circuit.
+    const NUM_PUBLIC_INPUTS: usize = SpendCircuit::NUM_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
+        StrictSpendCircuit(SpendCircuit::sample_circuit())
+    }
+}
+
+/// The witnesses and public inputs of a spend proof: everything needed to prove
+/// it.
+///
+/// A plan can be encoded with [`Protobuf`], e.g. to be proven on another
+/// machine. It contains the spent note and the nullifier key, so it must only
+/// be sent to a prover trusted with them.
+#[derive(Clone, Debug)]
+pub struct SpendProofPlan {
+    /// Inclusion proof for the note commitment.
+    pub note_commitment_proof: tct::Proof,
+    /// The note being spent.
+    pub note: Note,
+    /// The blinding factor used for generating the value commitment.
+    pub v_blinding: Fr,
+    /// The randomizer used for generating the randomized spend auth key.
+    pub spend_auth_randomizer: SpendAuthRandomizer,
+    /// The spend authorization key.
+    pub ak: VerificationKey<SpendAuth>,
+    /// The nullifier deriving key.
+    pub nk: NullifierKey,
+    /// the merkle root of the note commitment tree.
+    pub anchor: tct::Root,
+    /// value commitment of the note to be spent.
+    pub balance_commitment: balance::Commitment,
+    /// nullifier of the note to be spent.
+    pub nullifier: Nullifier,
+    /// the randomized verification spend key.
+    pub rk: RandomizedVerificationKey,
+}
+
+impl SpendProofPlan {
+    /// The circuit instance this plan proves.
+    pub fn build_circuit(&self) -> SpendCircuit {
+        SpendCircuit::new(
+            self.note_commitment_proof.clone(),
+            self.note.clone(),
+            self.v_blinding,
+            self.spend_auth_randomizer.clone(),
+            self.ak,
+            self.nk,
+            self.anchor,
+            self.balance_commitment,
+            self.nullifier,
+            self.rk,
+        )
+    }
+
+    /// The public inputs the proof will be verified against.
+    pub fn public_inputs(&self) -> SpendPublicInputs {
+        SpendPublicInputs {
+            anchor: self.anchor,
+            balance_commitment: self.balance_commitment,
+            nullifier: self.nullifier,
+            rk: self.rk.element(),
+        }
+    }
+
+    /// Generate the proof with the default [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads. To get the
+    /// same proof every time, e.g. in tests, pass a seeded [`ProofRng`].
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        &self,
+        rng: &mut R,
+        pk: P,
+    ) -> anyhow::Result<SpendProof> {
+        self.prove_with_options(&ProverOptions::default(), rng, pk)
+    }
+
+    /// Generate the proof, according to the provided [`ProverOptions`].
+    pub fn prove_with_options<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        &self,
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+    ) -> anyhow::Result<SpendProof> {
+        let circuit = self.build_circuit();
+        #[cfg(feature = "debug-asserts")]
+        circuit.assert_public_inputs();
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok(SpendProof(proof))
+    }
+}
+
+impl Protobuf<pb::SpendProofPlan> for SpendProofPlan {}
+
+impl From<SpendProofPlan> for pb::SpendProofPlan {
+    fn from(plan: SpendProofPlan) -> Self {
+        pb::SpendProofPlan {
+            note_commitment_proof: Some(plan.note_commitment_proof.into()),
+            note: Some(plan.note.into()),
+            v_blinding: plan.v_blinding.to_bytes().to_vec(),
+            spend_auth_randomizer: plan.spend_auth_randomizer.to_bytes().to_vec(),
+            ak: plan.ak.to_bytes().to_vec(),
+            nk: plan.nk.expose_secret().to_bytes().to_vec(),
+            anchor: Some(plan.anchor.into()),
+            balance_commitment: Some(plan.balance_commitment.into()),
+            nullifier: Some(plan.nullifier.into()),
+            rk: plan.rk.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::SpendProofPlan> for SpendProofPlan {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::SpendProofPlan) -> Result<Self, Self::Error> {
+        Ok(SpendProofPlan {
+            note_commitment_proof: proto
+                .note_commitment_proof
+                .context("missing note commitment proof")?
+                .try_into()?,
+            note: proto.note.context("missing note")?.try_into()?,
+            v_blinding: Fr::from_bytes(plan_bytes(&proto.v_blinding, "v_blinding")?)?,
+            spend_auth_randomizer: proto.spend_auth_randomizer.as_slice().try_into()?,
+            ak: plan_bytes(&proto.ak, "ak")?.try_into()?,
+            nk: NullifierKey(Fq::from_bytes(plan_bytes(&proto.nk, "nk")?)?),
+            anchor: proto.anchor.context("missing anchor")?.try_into()?,
+            balance_commitment: proto
+                .balance_commitment
+                .context("missing balance commitment")?
+                .try_into()?,
+            nullifier: proto.nullifier.context("missing nullifier")?.try_into()?,
+            rk: plan_bytes(&proto.rk, "rk")?.try_into()?,
+        })
     }
 }
 
-pub struct SpendProof(Proof<Bls12_377>);
+/// Read a 32-byte field of an encoded proof plan.
+pub(super) fn plan_bytes(bytes: &[u8], field: &str) -> anyhow::Result<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} must be 32 bytes, got {}", field, bytes.len()))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "pb::ZkSpendProof", into = "pb::ZkSpendProof")]
+pub struct SpendProof(pub(super) Proof<Bls12_377>);
 
 impl SpendProof {
     #![allow(clippy::too_many_arguments)]
-    pub fn prove<R: CryptoRng + Rng>(
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    #[deprecated(note = "collect the arguments into a `SpendProofPlan` and prove that")]
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
         rng: &mut R,
-        pk: &ProvingKey<Bls12_377>,
+        pk: P,
         note_commitment_proof: tct::Proof,
         note: Note,
         v_blinding: Fr,
-        spend_auth_randomizer: Fr,
+        spend_auth_randomizer: SpendAuthRandomizer,
         ak: VerificationKey<SpendAuth>,
         nk: NullifierKey,
         anchor: tct::Root,
         balance_commitment: balance::Commitment,
         nullifier: Nullifier,
-        rk: VerificationKey<SpendAuth>,
+        rk: RandomizedVerificationKey,
     ) -> anyhow::Result<Self> {
-        let element_rk = decaf377::Encoding(rk.to_bytes())
-            .vartime_decompress()
-            .expect("expect only valid element points");
-        let circuit = SpendCircuit {
+        SpendProofPlan {
             note_commitment_proof,
             note,
             v_blinding,
@@ -256,12 +748,156 @@ impl SpendProof {
             anchor,
             balance_commitment,
             nullifier,
-            rk: element_rk,
-        };
-        let proof = Groth16::prove(pk, circuit, rng).map_err(|err| anyhow::anyhow!(err))?;
+            rk,
+        }
+        .prove_with_options(options, rng, pk)
+    }
+
+    /// Generate a proof like [`SpendProofPlan::prove`], given the raw
+    /// position and authentication path of the note commitment, e.g. from an
+    /// external indexer maintaining the commitment tree.
+    ///
+    /// The authentication path is ordered from root to leaf. Since it isn't
+    /// produced by a [`tct::Tree`], it is checked against the `anchor`, and the
+    /// position against the `nullifier`, before proving: an invalid witness
+    /// would otherwise only show up as a proof that fails to verify.
+    pub fn prove_with_auth_path<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        position: u64,
+        auth_path: [[Fq; 3]; 24],
+        note: Note,
+        v_blinding: Fr,
+        spend_auth_randomizer: SpendAuthRandomizer,
+        ak: VerificationKey<SpendAuth>,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<Self> {
+        let position = tct::Position::checked_from_u64(position)
+            .context("invalid position for note commitment")?;
+        let note_commitment = note.commit();
+        let note_commitment_proof = tct::Proof::new(
+            note_commitment,
+            position,
+            auth_path.map(|siblings| siblings.map(tct::structure::Hash::new)),
+        );
+
+        if note_commitment_proof.verify(anchor).is_err() {
+            anyhow::bail!(
+                "auth path for note commitment at position {:?} does not verify against anchor {}",
+                position,
+                anchor
+            );
+        }
+        if nk.derive_nullifier(position, &note_commitment) != nullifier {
+            anyhow::bail!(
+                "nullifier does not match the note commitment at position {:?}",
+                position
+            );
+        }
+
+        SpendProofPlan {
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk,
+        }
+        .prove_with_options(options, rng, pk)
+    }
+
+    /// Generate a proof of the [`StrictSpendCircuit`] statement, which also
+    /// derives the note's diversified basepoint from its diversifier.
+    ///
+    /// The proof has the same public inputs as one made with
+    /// [`SpendProofPlan::prove`], but must be verified with the strict
+    /// circuit's verifying key.
+    pub fn prove_strict<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        note_commitment_proof: tct::Proof,
+        note: Note,
+        v_blinding: Fr,
+        spend_auth_randomizer: SpendAuthRandomizer,
+        ak: VerificationKey<SpendAuth>,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<Self> {
+        let circuit = StrictSpendCircuit(SpendCircuit::new(
+            note_commitment_proof,
+            note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk,
+        ));
+        #[cfg(feature = "debug-asserts")]
+        circuit.0.assert_public_inputs();
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
         Ok(Self(proof))
     }
 
+    /// Generate a proof for a _dummy_ spend, so that transactions can be padded
+    /// to a fixed number of spends.
+    ///
+    /// The `anchor` should be the anchor of the rest of the transaction, and
+    /// `rk` a randomized spend authorization key whose signing key will sign
+    /// the transaction. Returns the proof along with its other public inputs,
+    /// the balance commitment (to zero) and the nullifier of the dummy note.
+    pub fn prove_dummy<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        anchor: tct::Root,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<(Self, balance::Commitment, Nullifier)> {
+        let circuit = SpendCircuit::dummy(rng, anchor, rk);
+        let balance_commitment = circuit.balance_commitment;
+        let nullifier = circuit.nullifier;
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok((Self(proof), balance_commitment, nullifier))
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
     /// Called to verify the proof using the provided public inputs.
     pub fn verify(
         &self,
@@ -269,23 +905,84 @@ impl SpendProof {
         anchor: tct::Root,
         balance_commitment: balance::Commitment,
         nullifier: Nullifier,
-        rk: VerificationKey<SpendAuth>,
-    ) -> anyhow::Result<()> {
-        let processed_pvk = Groth16::process_vk(vk).map_err(|err| anyhow::anyhow!(err))?;
-        let mut public_inputs = Vec::new();
-        public_inputs.extend(Fq::from(anchor.0).to_field_elements().unwrap());
-        public_inputs.extend(balance_commitment.0.to_field_elements().unwrap());
-        public_inputs.extend(nullifier.0.to_field_elements().unwrap());
-        let element_rk = decaf377::Encoding(rk.to_bytes())
-            .vartime_decompress()
-            .expect("expect only valid element points");
-        public_inputs.extend(element_rk.to_field_elements().unwrap());
-
-        let proof_result =
-            Groth16::verify_with_processed_vk(&processed_pvk, public_inputs.as_slice(), &self.0)
-                .map_err(|err| anyhow::anyhow!(err))?;
-        proof_result
-            .then_some(())
-            .ok_or_else(|| anyhow::anyhow!("proof did not verify"))
+        rk: RandomizedVerificationKey,
+    ) -> Result<(), VerificationError> {
+        SpendCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(&processed_pvk, anchor, balance_commitment, nullifier, rk)
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| {
+            self.verify(vk, anchor, balance_commitment, nullifier, rk)
+        })
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        balance_commitment: balance::Commitment,
+        nullifier: Nullifier,
+        rk: RandomizedVerificationKey,
+    ) -> Result<(), VerificationError> {
+        SpendCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = SpendPublicInputs {
+            anchor,
+            balance_commitment,
+            nullifier,
+            rk: rk.element(),
+        }
+        .to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
+
+/// The size of the compressed encoding of a [`SpendProof`].
+pub const SPEND_PROOF_LEN_BYTES: usize = verify::PROOF_LEN_BYTES;
+
+impl Protobuf<pb::ZkSpendProof> for SpendProof {}
+
+impl From<SpendProof> for pb::ZkSpendProof {
+    fn from(proof: SpendProof) -> Self {
+        pb::ZkSpendProof {
+            inner: proof.to_bytes(),
+        }
+    }
+}
+
+impl TryFrom<pb::ZkSpendProof> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::ZkSpendProof) -> Result<Self, Self::Error> {
+        proto.inner.as_slice().try_into()
+    }
+}
+
+impl From<SpendProof> for Vec<u8> {
+    fn from(proof: SpendProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(verify::decode_proof(bytes)?))
     }
 }
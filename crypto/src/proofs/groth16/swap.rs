@@ -0,0 +1,467 @@
+use std::borrow::Borrow;
+
+use ark_r1cs_std::uint8::UInt8;
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    Bls12_377, Fq, Fr,
+};
+use decaf377::{Element, FieldExt};
+use decaf377_fmd as fmd;
+use decaf377_ka as ka;
+
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use penumbra_proof_verify as verify;
+use penumbra_tct as tct;
+use rand::{CryptoRng, Rng};
+
+use crate::proofs::groth16::{
+    error::{self, VerificationError},
+    gadgets, metrics,
+    traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS},
+    witness::SecretBytes,
+    CircuitDescription, Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable,
+    VariableKind,
+};
+use crate::proofs::ProofRng;
+use crate::{
+    asset::{self, FromAmount},
+    balance,
+    dex::swap::SwapPlaintext,
+    dex::TradingPair,
+    keys::Diversifier,
+    transaction::Fee,
+    Address, Amount, Balance, Rseed, Value, STAKING_TOKEN_ASSET_ID,
+};
+
+// Public:
+// * vcm (balance commitment of the swap)
+// * scm (swap commitment)
+// * fcm (fee commitment)
+//
+// Witnesses:
+// * swap plaintext (trading pair, input amounts, claim fee, claim address, rseed)
+// * fee blinding (Fr)
+#[derive(Clone, Debug)]
+pub struct SwapCircuit {
+    // Witnesses
+    /// The swap being created.
+    swap_plaintext: SwapPlaintext,
+    /// The blinding factor used for generating the fee commitment.
+    fee_blinding: Fr,
+
+    // Public inputs
+    /// balance commitment of the swap,
+    pub balance_commitment: balance::Commitment,
+    /// swap commitment of the swap,
+    pub swap_commitment: tct::Commitment,
+    /// commitment to the prepaid claim fee,
+    pub fee_commitment: balance::Commitment,
+}
+
+/// Allocate an amount as a witness, both as bytes and as a field element
+/// range checked to be equal to them.
+pub(super) fn amount_witness(
+    cs: ConstraintSystemRef<Fq>,
+    amount: Amount,
+) -> ark_relations::r1cs::Result<(Vec<UInt8<Fq>>, FqVar)> {
+    let bytes = UInt8::new_witness_vec(cs.clone(), &amount.to_le_bytes())?;
+    let value = FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(amount)))?;
+    metrics::gadget(&cs, "amount_range_check", || {
+        gadgets::amount_range_check(&Boolean::TRUE, &value, &bytes)
+    })?;
+    Ok((bytes, value))
+}
+
+impl ConstraintSynthesizer<Fq> for SwapCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        let swap = &self.swap_plaintext;
+
+        // Witnesses
+        let asset_1_var = FqVar::new_witness(cs.clone(), || Ok(swap.trading_pair.asset_1().0))?;
+        let asset_2_var = FqVar::new_witness(cs.clone(), || Ok(swap.trading_pair.asset_2().0))?;
+        let (delta_1_vars, delta_1_var) = amount_witness(cs.clone(), swap.delta_1_i)?;
+        let (delta_2_vars, delta_2_var) = amount_witness(cs.clone(), swap.delta_2_i)?;
+        let (fee_amount_vars, fee_amount_var) =
+            amount_witness(cs.clone(), swap.claim_fee.amount())?;
+        let fee_asset_id_var = FqVar::new_witness(cs.clone(), || Ok(swap.claim_fee.asset_id().0))?;
+        let diversified_generator_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+                Ok(*swap.claim_address.diversified_generator())
+            })?;
+        let transmission_key_s_var =
+            FqVar::new_witness(cs.clone(), || Ok(*swap.claim_address.transmission_key_s()))?;
+        let clue_key_var = FqVar::new_witness(cs.clone(), || {
+            Ok(Fq::from_le_bytes_mod_order(
+                &swap.claim_address.clue_key().0[..],
+            ))
+        })?;
+        let rseed_var = FqVar::new_witness(cs.clone(), || {
+            Ok(Fq::from_le_bytes_mod_order(&swap.rseed.to_bytes()[..]))
+        })?;
+        let fee_blinding_arr = SecretBytes::new(self.fee_blinding.to_bytes());
+        let fee_blinding_vars = UInt8::new_witness_vec(cs.clone(), &fee_blinding_arr)?;
+
+        // Public inputs
+        let balance_commitment_var =
+            ElementVar::new_input(cs.clone(), || Ok(self.balance_commitment.0))?;
+        let swap_commitment_var = FqVar::new_input(cs.clone(), || Ok(self.swap_commitment.0))?;
+        let fee_commitment_var = ElementVar::new_input(cs.clone(), || Ok(self.fee_commitment.0))?;
+
+        metrics::gadget(&cs, "swap_commitment_integrity", || {
+            gadgets::swap_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                rseed_var,
+                fee_amount_var,
+                fee_asset_id_var.clone(),
+                diversified_generator_var,
+                transmission_key_s_var,
+                clue_key_var,
+                asset_1_var.clone(),
+                asset_2_var.clone(),
+                delta_1_var,
+                delta_2_var,
+                swap_commitment_var,
+            )
+        })?;
+        // The claim fee is consumed by the swap, so it is committed to with a
+        // negative sign.
+        metrics::gadget(&cs, "value_commitment_integrity", || {
+            gadgets::value_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                &Boolean::TRUE,
+                fee_amount_vars,
+                fee_asset_id_var,
+                fee_blinding_vars,
+                fee_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "swap_balance_integrity", || {
+            gadgets::swap_balance_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                delta_1_vars,
+                asset_1_var,
+                delta_2_vars,
+                asset_2_var,
+                fee_commitment_var,
+                balance_commitment_var,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl SwapCircuit {
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::Always;
+        use VariableKind::{Bytes, Element, Fq};
+
+        CircuitDescription {
+            name: "swap",
+            public_inputs: vec![
+                Variable::new("balance_commitment", Element),
+                Variable::new("swap_commitment", Fq),
+                Variable::new("fee_commitment", Element),
+            ],
+            witnesses: vec![
+                Variable::new("asset_1", Fq),
+                Variable::new("asset_2", Fq),
+                Variable::new("delta_1", Bytes { len: 16 }),
+                Variable::new("delta_2", Bytes { len: 16 }),
+                Variable::new("claim_fee_amount", Bytes { len: 16 }),
+                Variable::new("claim_fee_asset_id", Fq),
+                Variable::new("diversified_generator", Element),
+                Variable::new("transmission_key_s", Fq),
+                Variable::new("clue_key", Fq),
+                Variable::new("rseed", Fq),
+                Variable::new("fee_blinding", Bytes { len: 32 }),
+            ],
+            gadgets: vec![
+                Gadget::new("amount_range_check", Always, &["delta_1"]),
+                Gadget::new("amount_range_check", Always, &["delta_2"]),
+                Gadget::new("amount_range_check", Always, &["claim_fee_amount"]),
+                Gadget::new(
+                    "swap_commitment_integrity",
+                    Always,
+                    &[
+                        "rseed",
+                        "claim_fee_amount",
+                        "claim_fee_asset_id",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "asset_1",
+                        "asset_2",
+                        "delta_1",
+                        "delta_2",
+                        "swap_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "value_commitment_integrity",
+                    Always,
+                    &[
+                        "claim_fee_amount",
+                        "claim_fee_asset_id",
+                        "fee_blinding",
+                        "fee_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "swap_balance_integrity",
+                    Always,
+                    &[
+                        "delta_1",
+                        "asset_1",
+                        "delta_2",
+                        "asset_2",
+                        "fee_commitment",
+                        "balance_commitment",
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the swap circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapPublicInputs {
+    /// balance commitment of the swap,
+    pub balance_commitment: balance::Commitment,
+    /// swap commitment of the swap,
+    pub swap_commitment: tct::Commitment,
+    /// commitment to the prepaid claim fee,
+    pub fee_commitment: balance::Commitment,
+}
+
+impl PublicInputs for SwapPublicInputs {
+    type Circuit = SwapCircuit;
+
+    fn from_circuit(circuit: &SwapCircuit) -> Self {
+        Self {
+            balance_commitment: circuit.balance_commitment,
+            swap_commitment: circuit.swap_commitment,
+            fee_commitment: circuit.fee_commitment,
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let mut public_inputs = Vec::new();
+        public_inputs.extend(error::public_input(
+            "balance_commitment",
+            &self.balance_commitment.0,
+        )?);
+        public_inputs.extend(error::public_input(
+            "swap_commitment",
+            &self.swap_commitment.0,
+        )?);
+        public_inputs.extend(error::public_input(
+            "fee_commitment",
+            &self.fee_commitment.0,
+        )?);
+        debug_assert_eq!(public_inputs.len(), SwapCircuit::NUM_PUBLIC_INPUTS);
+        Ok(public_inputs)
+    }
+}
+
+impl ParameterSetup for SwapCircuit {
+    const NAME: &'static str = "swap";
+
+    // balance commitment, swap commitment, fee commitment
+    const NUM_PUBLIC_INPUTS: usize =
+        ELEMENT_PUBLIC_INPUTS + FQ_PUBLIC_INPUTS + ELEMENT_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
+        let diversifier_bytes = [1u8; 16];
+        let pk_d_bytes = [1u8; 32];
+        let clue_key_bytes = [1; 32];
+        let diversifier = Diversifier(diversifier_bytes);
+        let address = Address::from_components(
+            diversifier,
+            ka::Public(pk_d_bytes),
+            fmd::ClueKey(clue_key_bytes),
+        )
+        .expect("generated 1 address");
+        let swap_plaintext = SwapPlaintext {
+            trading_pair: TradingPair::new(*STAKING_TOKEN_ASSET_ID, asset::Id(Fq::from(1u64))),
+            delta_1_i: 1u64.into(),
+            delta_2_i: 0u64.into(),
+            claim_fee: Fee::default(),
+            claim_address: address,
+            rseed: Rseed([1u8; 32]),
+        };
+        let fee_blinding = Fr::from(1);
+        let fee_commitment = swap_plaintext.claim_fee.commit(fee_blinding);
+        SwapCircuit {
+            swap_commitment: swap_plaintext.swap_commitment(),
+            balance_commitment: swap_balance_commitment(&swap_plaintext, fee_commitment),
+            fee_commitment,
+            swap_plaintext,
+            fee_blinding,
+        }
+    }
+}
+
+/// The balance commitment of a swap with the given fee commitment: the fee
+/// commitment, minus the unblinded input amounts of the swap.
+pub fn swap_balance_commitment(
+    swap_plaintext: &SwapPlaintext,
+    fee_commitment: balance::Commitment,
+) -> balance::Commitment {
+    let input_1 = Value {
+        amount: swap_plaintext.delta_1_i,
+        asset_id: swap_plaintext.trading_pair.asset_1(),
+    };
+    let input_2 = Value {
+        amount: swap_plaintext.delta_2_i,
+        asset_id: swap_plaintext.trading_pair.asset_2(),
+    };
+    let transparent_balance = Balance::default() - input_1 - input_2;
+    transparent_balance.commit(Fr::from(0u64)) + fee_commitment
+}
+
+pub struct SwapProof(pub(super) Proof<Bls12_377>);
+
+impl SwapProof {
+    #![allow(clippy::too_many_arguments)]
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        swap_plaintext: SwapPlaintext,
+        fee_blinding: Fr,
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> anyhow::Result<Self> {
+        let circuit = SwapCircuit {
+            swap_plaintext,
+            fee_blinding,
+            balance_commitment,
+            swap_commitment,
+            fee_commitment,
+        };
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok(Self(proof))
+    }
+
+    /// Generate a proof as with [`SwapProof::prove`], drawing its randomness
+    /// from a [`ProofRng`], so that the same seed always gives the same proof.
+    pub fn prove_deterministic<P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut ProofRng,
+        pk: P,
+        swap_plaintext: SwapPlaintext,
+        fee_blinding: Fr,
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> anyhow::Result<Self> {
+        Self::prove(
+            options,
+            rng,
+            pk,
+            swap_plaintext,
+            fee_blinding,
+            balance_commitment,
+            swap_commitment,
+            fee_commitment,
+        )
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The public inputs are:
+    /// * balance commitment of the swap,
+    /// * swap commitment of the swap,
+    /// * commitment to the prepaid claim fee,
+    pub fn verify(
+        &self,
+        vk: &VerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> Result<(), VerificationError> {
+        SwapCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(
+            &processed_pvk,
+            balance_commitment,
+            swap_commitment,
+            fee_commitment,
+        )
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| {
+            self.verify(vk, balance_commitment, swap_commitment, fee_commitment)
+        })
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        swap_commitment: tct::Commitment,
+        fee_commitment: balance::Commitment,
+    ) -> Result<(), VerificationError> {
+        SwapCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = SwapPublicInputs {
+            balance_commitment,
+            swap_commitment,
+            fee_commitment,
+        }
+        .to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
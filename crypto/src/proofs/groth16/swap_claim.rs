@@ -0,0 +1,687 @@
+use std::borrow::Borrow;
+
+use decaf377::Element;
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    Bls12_377, Fq,
+};
+use decaf377_fmd as fmd;
+use decaf377_ka as ka;
+
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use penumbra_proof_verify as verify;
+use penumbra_tct as tct;
+use rand::{CryptoRng, Rng};
+
+use crate::proofs::groth16::{
+    error::VerificationError, gadgets, metrics, traits::FQ_PUBLIC_INPUTS, CircuitDescription,
+    Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable, VariableKind,
+};
+use crate::proofs::ProofRng;
+use crate::{
+    asset::{self, FromAmount},
+    dex::{swap::SwapPlaintext, BatchSwapOutputData, TradingPair},
+    keys::{Diversifier, NullifierKey},
+    note,
+    transaction::Fee,
+    Address, Nullifier, Rseed, STAKING_TOKEN_ASSET_ID,
+};
+
+// Public:
+// * anchor
+// * nf (nullifier of the swap NFT)
+// * claim fee (amount and asset ID)
+// * batch swap output data (input and output amounts, success flag, trading pair, height)
+// * epoch duration
+// * ncm_1, ncm_2 (note commitments of the two outputs)
+//
+// Witnesses:
+// * swap plaintext (input amounts, claim address, rseed)
+// * swap commitment and its merkle path
+// * nk (nullifier key)
+// * lambda_1_i, lambda_2_i (output amounts)
+// * nblind_1, nblind_2 (output note blindings)
+#[derive(Clone, Debug)]
+pub struct SwapClaimCircuit {
+    // Witnesses
+    /// The swap being claimed.
+    swap_plaintext: SwapPlaintext,
+    /// Inclusion proof for the swap commitment.
+    swap_commitment_proof: tct::Proof,
+    /// The nullifier deriving key for the swap NFT.
+    nk: NullifierKey,
+    /// The output notes of the claim.
+    output_notes: (note::Note, note::Note),
+
+    // Public inputs
+    /// the merkle root of the state commitment tree,
+    pub anchor: tct::Root,
+    /// nullifier of the swap NFT being claimed,
+    pub nullifier: Nullifier,
+    /// the prepaid claim fee,
+    pub fee: Fee,
+    /// the result of the batch swap the swap was part of,
+    pub output_data: BatchSwapOutputData,
+    /// the number of blocks in each epoch,
+    pub epoch_duration: u64,
+    /// note commitment of the first output note,
+    pub note_commitment_1: note::Commitment,
+    /// note commitment of the second output note,
+    pub note_commitment_2: note::Commitment,
+}
+
+impl ConstraintSynthesizer<Fq> for SwapClaimCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        let swap = &self.swap_plaintext;
+        let (output_1, output_2) = &self.output_notes;
+
+        // Witnesses
+        let swap_commitment_var =
+            FqVar::new_witness(cs.clone(), || Ok(self.swap_commitment_proof.commitment().0))?;
+        let position_fq = Fq::from(u64::from(self.swap_commitment_proof.position()));
+        let position_var = FqVar::new_witness(cs.clone(), || Ok(position_fq))?;
+        let merkle_path_var =
+            tct::r1cs::MerkleAuthPathVar::new(cs.clone(), self.swap_commitment_proof)?;
+        let delta_1_i_var = FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(swap.delta_1_i)))?;
+        let delta_2_i_var = FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(swap.delta_2_i)))?;
+        let diversified_generator_var: ElementVar =
+            AllocVar::<Element, Fq>::new_witness(cs.clone(), || {
+                Ok(*swap.claim_address.diversified_generator())
+            })?;
+        let transmission_key_s_var =
+            FqVar::new_witness(cs.clone(), || Ok(*swap.claim_address.transmission_key_s()))?;
+        let clue_key_var = FqVar::new_witness(cs.clone(), || {
+            Ok(Fq::from_le_bytes_mod_order(
+                &swap.claim_address.clue_key().0[..],
+            ))
+        })?;
+        let rseed_var = FqVar::new_witness(cs.clone(), || {
+            Ok(Fq::from_le_bytes_mod_order(&swap.rseed.to_bytes()[..]))
+        })?;
+        let nk_var = FqVar::new_witness(cs.clone(), || Ok(*self.nk.expose_secret()))?;
+        let lambda_1_i_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(output_1.value().amount)))?;
+        let lambda_2_i_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from_amount(output_2.value().amount)))?;
+        // As in the spend circuit, the note blindings are witnessed directly,
+        // since deriving them from the swap's rseed uses BLAKE2b.
+        let note_blinding_1_var = FqVar::new_witness(cs.clone(), || Ok(output_1.note_blinding()))?;
+        let note_blinding_2_var = FqVar::new_witness(cs.clone(), || Ok(output_2.note_blinding()))?;
+
+        // Public inputs
+        let output_data = &self.output_data;
+        let anchor_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.anchor)))?;
+        let nullifier_var = FqVar::new_input(cs.clone(), || Ok(self.nullifier.0))?;
+        let fee_amount_var =
+            FqVar::new_input(cs.clone(), || Ok(Fq::from_amount(self.fee.amount())))?;
+        let fee_asset_id_var = FqVar::new_input(cs.clone(), || Ok(self.fee.asset_id().0))?;
+        let delta_1_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(output_data.delta_1)))?;
+        let delta_2_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(output_data.delta_2)))?;
+        let lambda_1_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(output_data.lambda_1)))?;
+        let lambda_2_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(output_data.lambda_2)))?;
+        let success_var = Boolean::new_input(cs.clone(), || Ok(output_data.success))?;
+        let asset_1_var =
+            FqVar::new_input(cs.clone(), || Ok(output_data.trading_pair.asset_1().0))?;
+        let asset_2_var =
+            FqVar::new_input(cs.clone(), || Ok(output_data.trading_pair.asset_2().0))?;
+        let height_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(output_data.height)))?;
+        let epoch_duration_var =
+            FqVar::new_input(cs.clone(), || Ok(Fq::from(self.epoch_duration)))?;
+        let note_commitment_1_var = FqVar::new_input(cs.clone(), || Ok(self.note_commitment_1.0))?;
+        let note_commitment_2_var = FqVar::new_input(cs.clone(), || Ok(self.note_commitment_2.0))?;
+
+        // The swap commitment is recomputed from the public claim fee and
+        // trading pair, so the swap must have been made for both of them.
+        metrics::gadget(&cs, "swap_commitment_integrity", || {
+            gadgets::swap_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                rseed_var,
+                fee_amount_var,
+                fee_asset_id_var,
+                diversified_generator_var.clone(),
+                transmission_key_s_var.clone(),
+                clue_key_var.clone(),
+                asset_1_var.clone(),
+                asset_2_var.clone(),
+                delta_1_i_var.clone(),
+                delta_2_i_var.clone(),
+                swap_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "merkle_path_integrity", || {
+            merkle_path_var.verify(
+                cs.clone(),
+                &Boolean::TRUE,
+                position_var.clone(),
+                anchor_var,
+                swap_commitment_var.clone(),
+            )
+        })?;
+        metrics::gadget(&cs, "nullifier_integrity", || {
+            gadgets::nullifier_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                swap_commitment_var,
+                nk_var,
+                position_var.clone(),
+                nullifier_var,
+            )
+        })?;
+        // The clearing prices are only valid for the block the swap was made in.
+        metrics::gadget(&cs, "position_height_integrity", || {
+            gadgets::position_height_integrity(
+                cs.clone(),
+                &position_var,
+                epoch_duration_var,
+                height_var,
+            )
+        })?;
+        metrics::gadget(&cs, "pro_rata_integrity", || {
+            gadgets::pro_rata_integrity(
+                cs.clone(),
+                &success_var,
+                delta_2_i_var.clone(),
+                delta_1_i_var.clone(),
+                lambda_1_i_var.clone(),
+                delta_2_var,
+                lambda_1_var,
+            )
+        })?;
+        metrics::gadget(&cs, "pro_rata_integrity", || {
+            gadgets::pro_rata_integrity(
+                cs.clone(),
+                &success_var,
+                delta_1_i_var,
+                delta_2_i_var,
+                lambda_2_i_var.clone(),
+                delta_1_var,
+                lambda_2_var,
+            )
+        })?;
+        metrics::gadget(&cs, "note_commitment_integrity", || {
+            gadgets::note_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                note_blinding_1_var,
+                lambda_1_i_var,
+                asset_1_var,
+                diversified_generator_var.clone(),
+                transmission_key_s_var.clone(),
+                clue_key_var.clone(),
+                note_commitment_1_var,
+            )
+        })?;
+        metrics::gadget(&cs, "note_commitment_integrity", || {
+            gadgets::note_commitment_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                note_blinding_2_var,
+                lambda_2_i_var,
+                asset_2_var,
+                diversified_generator_var,
+                transmission_key_s_var,
+                clue_key_var,
+                note_commitment_2_var,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl SwapClaimCircuit {
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::Always;
+        use VariableKind::{Element, Fq, MerkleAuthPath};
+
+        CircuitDescription {
+            name: "swap_claim",
+            public_inputs: vec![
+                Variable::new("anchor", Fq),
+                Variable::new("nullifier", Fq),
+                Variable::new("claim_fee_amount", Fq),
+                Variable::new("claim_fee_asset_id", Fq),
+                Variable::new("delta_1", Fq),
+                Variable::new("delta_2", Fq),
+                Variable::new("lambda_1", Fq),
+                Variable::new("lambda_2", Fq),
+                Variable::new("success", Fq),
+                Variable::new("asset_1", Fq),
+                Variable::new("asset_2", Fq),
+                Variable::new("height", Fq),
+                Variable::new("epoch_duration", Fq),
+                Variable::new("note_commitment_1", Fq),
+                Variable::new("note_commitment_2", Fq),
+            ],
+            witnesses: vec![
+                Variable::new("swap_commitment", Fq),
+                Variable::new("position", Fq),
+                Variable::new("merkle_path", MerkleAuthPath),
+                Variable::new("delta_1_i", Fq),
+                Variable::new("delta_2_i", Fq),
+                Variable::new("diversified_generator", Element),
+                Variable::new("transmission_key_s", Fq),
+                Variable::new("clue_key", Fq),
+                Variable::new("rseed", Fq),
+                Variable::new("nk", Fq),
+                Variable::new("lambda_1_i", Fq),
+                Variable::new("lambda_2_i", Fq),
+                Variable::new("note_blinding_1", Fq),
+                Variable::new("note_blinding_2", Fq),
+            ],
+            gadgets: vec![
+                Gadget::new(
+                    "swap_commitment_integrity",
+                    Always,
+                    &[
+                        "rseed",
+                        "claim_fee_amount",
+                        "claim_fee_asset_id",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "asset_1",
+                        "asset_2",
+                        "delta_1_i",
+                        "delta_2_i",
+                        "swap_commitment",
+                    ],
+                ),
+                Gadget::new(
+                    "merkle_path_integrity",
+                    Always,
+                    &["merkle_path", "position", "anchor", "swap_commitment"],
+                ),
+                Gadget::new(
+                    "nullifier_integrity",
+                    Always,
+                    &["swap_commitment", "nk", "position", "nullifier"],
+                ),
+                Gadget::new(
+                    "position_height_integrity",
+                    Always,
+                    &["position", "epoch_duration", "height"],
+                ),
+                Gadget::new(
+                    "pro_rata_integrity",
+                    Always,
+                    &[
+                        "success",
+                        "delta_2_i",
+                        "delta_1_i",
+                        "lambda_1_i",
+                        "delta_2",
+                        "lambda_1",
+                    ],
+                ),
+                Gadget::new(
+                    "pro_rata_integrity",
+                    Always,
+                    &[
+                        "success",
+                        "delta_1_i",
+                        "delta_2_i",
+                        "lambda_2_i",
+                        "delta_1",
+                        "lambda_2",
+                    ],
+                ),
+                Gadget::new(
+                    "note_commitment_integrity",
+                    Always,
+                    &[
+                        "note_blinding_1",
+                        "lambda_1_i",
+                        "asset_1",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "note_commitment_1",
+                    ],
+                ),
+                Gadget::new(
+                    "note_commitment_integrity",
+                    Always,
+                    &[
+                        "note_blinding_2",
+                        "lambda_2_i",
+                        "asset_2",
+                        "diversified_generator",
+                        "transmission_key_s",
+                        "clue_key",
+                        "note_commitment_2",
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the swap claim circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapClaimPublicInputs {
+    /// the merkle root of the state commitment tree,
+    pub anchor: tct::Root,
+    /// nullifier of the swap NFT being claimed,
+    pub nullifier: Nullifier,
+    /// the prepaid claim fee,
+    pub fee: Fee,
+    /// the result of the batch swap the swap was part of,
+    pub output_data: BatchSwapOutputData,
+    /// the number of blocks in each epoch,
+    pub epoch_duration: u64,
+    /// note commitment of the first output note,
+    pub note_commitment_1: note::Commitment,
+    /// note commitment of the second output note,
+    pub note_commitment_2: note::Commitment,
+}
+
+impl PublicInputs for SwapClaimPublicInputs {
+    type Circuit = SwapClaimCircuit;
+
+    fn from_circuit(circuit: &SwapClaimCircuit) -> Self {
+        Self {
+            anchor: circuit.anchor,
+            nullifier: circuit.nullifier,
+            fee: circuit.fee.clone(),
+            output_data: circuit.output_data,
+            epoch_duration: circuit.epoch_duration,
+            note_commitment_1: circuit.note_commitment_1,
+            note_commitment_2: circuit.note_commitment_2,
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let output_data = &self.output_data;
+        let public_inputs = vec![
+            Fq::from(self.anchor),
+            self.nullifier.0,
+            Fq::from_amount(self.fee.amount()),
+            self.fee.asset_id().0,
+            Fq::from(output_data.delta_1),
+            Fq::from(output_data.delta_2),
+            Fq::from(output_data.lambda_1),
+            Fq::from(output_data.lambda_2),
+            Fq::from(output_data.success as u64),
+            output_data.trading_pair.asset_1().0,
+            output_data.trading_pair.asset_2().0,
+            Fq::from(output_data.height),
+            Fq::from(self.epoch_duration),
+            self.note_commitment_1.0,
+            self.note_commitment_2.0,
+        ];
+        debug_assert_eq!(public_inputs.len(), SwapClaimCircuit::NUM_PUBLIC_INPUTS);
+        Ok(public_inputs)
+    }
+}
+
+impl ParameterSetup for SwapClaimCircuit {
+    const NAME: &'static str = "swap_claim";
+
+    // anchor, nullifier, claim fee (2), output data (8), epoch duration, note commitments (2)
+    const NUM_PUBLIC_INPUTS: usize = 15 * FQ_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
+        let diversifier_bytes = [1u8; 16];
+        let pk_d_bytes = [1u8; 32];
+        let clue_key_bytes = [1; 32];
+        let diversifier = Diversifier(diversifier_bytes);
+        let address = Address::from_components(
+            diversifier,
+            ka::Public(pk_d_bytes),
+            fmd::ClueKey(clue_key_bytes),
+        )
+        .expect("generated 1 address");
+        let trading_pair = TradingPair::new(*STAKING_TOKEN_ASSET_ID, asset::Id(Fq::from(1u64)));
+        let swap_plaintext = SwapPlaintext {
+            trading_pair,
+            delta_1_i: 1u64.into(),
+            delta_2_i: 0u64.into(),
+            claim_fee: Fee::default(),
+            claim_address: address,
+            rseed: Rseed([1u8; 32]),
+        };
+        let output_data = BatchSwapOutputData {
+            delta_1: 1,
+            delta_2: 0,
+            lambda_1: 0,
+            lambda_2: 1,
+            height: 0,
+            trading_pair,
+            success: true,
+        };
+
+        let swap_commitment = swap_plaintext.swap_commitment();
+        let mut sct = tct::Tree::new();
+        sct.insert(tct::Witness::Keep, swap_commitment)
+            .expect("can insert into empty tree");
+        let swap_commitment_proof = sct.witness(swap_commitment).expect("was witnessed");
+        let nk = NullifierKey(Fq::from(1u64));
+
+        SwapClaimCircuit::new(
+            swap_plaintext,
+            swap_commitment_proof,
+            nk,
+            sct.root(),
+            output_data,
+            1,
+        )
+    }
+}
+
+impl SwapClaimCircuit {
+    /// Construct the circuit for claiming the given swap, computing its
+    /// nullifier and output notes from the batch swap output data.
+    fn new(
+        swap_plaintext: SwapPlaintext,
+        swap_commitment_proof: tct::Proof,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+    ) -> Self {
+        let nullifier = nk.derive_nullifier(
+            swap_commitment_proof.position(),
+            &swap_commitment_proof.commitment(),
+        );
+        let output_notes = swap_plaintext.output_notes(&output_data);
+        Self {
+            fee: swap_plaintext.claim_fee.clone(),
+            note_commitment_1: output_notes.0.commit(),
+            note_commitment_2: output_notes.1.commit(),
+            swap_plaintext,
+            swap_commitment_proof,
+            nk,
+            output_notes,
+            anchor,
+            nullifier,
+            output_data,
+            epoch_duration,
+        }
+    }
+}
+
+pub struct SwapClaimProof(pub(super) Proof<Bls12_377>);
+
+impl SwapClaimProof {
+    #![allow(clippy::too_many_arguments)]
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The nullifier, claim fee and output note commitments are all determined
+    /// by the swap and the batch swap output data, so they are computed rather
+    /// than passed in.
+    ///
+    /// The swap commitment proof must verify against the anchor: to claim
+    /// against a historical anchor, witness it with [`tct::Tree::witness_at`].
+    /// Returns an error if it does not.
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        swap_plaintext: SwapPlaintext,
+        swap_commitment_proof: tct::Proof,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+    ) -> anyhow::Result<Self> {
+        swap_commitment_proof.verify(anchor).map_err(|_| {
+            anyhow::anyhow!(
+                "swap commitment proof does not verify against the anchor {}",
+                anchor
+            )
+        })?;
+        let circuit = SwapClaimCircuit::new(
+            swap_plaintext,
+            swap_commitment_proof,
+            nk,
+            anchor,
+            output_data,
+            epoch_duration,
+        );
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok(Self(proof))
+    }
+
+    /// Generate a proof as with [`SwapClaimProof::prove`], drawing its randomness
+    /// from a [`ProofRng`], so that the same seed always gives the same proof.
+    pub fn prove_deterministic<P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut ProofRng,
+        pk: P,
+        swap_plaintext: SwapPlaintext,
+        swap_commitment_proof: tct::Proof,
+        nk: NullifierKey,
+        anchor: tct::Root,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+    ) -> anyhow::Result<Self> {
+        Self::prove(
+            options,
+            rng,
+            pk,
+            swap_plaintext,
+            swap_commitment_proof,
+            nk,
+            anchor,
+            output_data,
+            epoch_duration,
+        )
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        vk: &VerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        fee: Fee,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+        note_commitment_1: note::Commitment,
+        note_commitment_2: note::Commitment,
+    ) -> Result<(), VerificationError> {
+        SwapClaimCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(
+            &processed_pvk,
+            anchor,
+            nullifier,
+            fee,
+            output_data,
+            epoch_duration,
+            note_commitment_1,
+            note_commitment_2,
+        )
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        fee: Fee,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+        note_commitment_1: note::Commitment,
+        note_commitment_2: note::Commitment,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| {
+            self.verify(
+                vk,
+                anchor,
+                nullifier,
+                fee.clone(),
+                output_data,
+                epoch_duration,
+                note_commitment_1,
+                note_commitment_2,
+            )
+        })
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        nullifier: Nullifier,
+        fee: Fee,
+        output_data: BatchSwapOutputData,
+        epoch_duration: u64,
+        note_commitment_1: note::Commitment,
+        note_commitment_2: note::Commitment,
+    ) -> Result<(), VerificationError> {
+        SwapClaimCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = SwapClaimPublicInputs {
+            anchor,
+            nullifier,
+            fee,
+            output_data,
+            epoch_duration,
+            note_commitment_1,
+            note_commitment_2,
+        }
+        .to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
@@ -1,7 +1,77 @@
-use ark_groth16::{ProvingKey, VerifyingKey};
-use decaf377::Bls12_377;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_snark::SNARK;
+use decaf377::{Bls12_377, Fq};
+use rand::{CryptoRng, Rng};
+
+use super::error::VerificationError;
+#[cfg(feature = "std")]
+use crate::sample::DefaultRng;
+
+/// The number of field elements used to encode an `Fq` public input.
+pub const FQ_PUBLIC_INPUTS: usize = 1;
+
+/// The number of field elements used to encode a `decaf377::Element` public input.
+pub const ELEMENT_PUBLIC_INPUTS: usize = 1;
 
 /// Must be implemented to generate proving and verification keys for a circuit.
-pub trait ParameterSetup {
-    fn generate_test_parameters() -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>);
+pub trait ParameterSetup: ConstraintSynthesizer<Fq> + Sized {
+    /// The name of the circuit, used to look up its parameters.
+    const NAME: &'static str;
+
+    /// The number of public inputs (as field elements) this circuit expects.
+    const NUM_PUBLIC_INPUTS: usize;
+
+    /// An instance of the circuit with fixed, consistent witnesses, used to
+    /// generate test parameters and to measure the size of the circuit.
+    fn sample_circuit() -> Self;
+
+    /// Generate test parameters for the circuit using the provided randomness.
+    ///
+    /// A seeded `rng` gives reproducible parameters, for regression tests.
+    fn generate_test_parameters_with_rng<R: CryptoRng + Rng>(
+        rng: &mut R,
+    ) -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+        let (pk, vk) = Groth16::circuit_specific_setup(Self::sample_circuit(), rng)
+            .expect("can perform circuit specific setup");
+        Self::check_verifying_key(&vk).expect("circuit has expected number of public inputs");
+        (pk, vk)
+    }
+
+    #[cfg(feature = "std")]
+    fn generate_test_parameters() -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+        Self::generate_test_parameters_with_rng(&mut DefaultRng)
+    }
+
+    /// Check that a verifying key was generated for a circuit with this
+    /// circuit's number of public inputs.
+    ///
+    /// This should be called whenever parameters are loaded, so that a change
+    /// to the circuit's public inputs can't be paired with stale parameters.
+    fn check_verifying_key(vk: &VerifyingKey<Bls12_377>) -> Result<(), VerificationError> {
+        penumbra_proof_verify::check_verifying_key(vk, Self::NUM_PUBLIC_INPUTS)
+    }
+
+    /// Check that a proving key was generated for a circuit with this
+    /// circuit's number of public inputs.
+    fn check_proving_key(pk: &ProvingKey<Bls12_377>) -> anyhow::Result<()> {
+        Ok(Self::check_verifying_key(&pk.vk)?)
+    }
+}
+
+/// The public inputs of a circuit, as known to its verifier.
+///
+/// The field elements the verifier checks a proof against must be in exactly
+/// the order the circuit allocates its inputs in, so each circuit encodes them
+/// in one place, here, which the tests check against its allocation order.
+pub trait PublicInputs: Sized {
+    /// The circuit these are the public inputs of.
+    type Circuit: ParameterSetup;
+
+    /// The public inputs of an instance of the circuit.
+    fn from_circuit(circuit: &Self::Circuit) -> Self;
+
+    /// Encode the public inputs as field elements, in the order the circuit
+    /// allocates them.
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError>;
 }
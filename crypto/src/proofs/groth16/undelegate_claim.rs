@@ -0,0 +1,341 @@
+use std::borrow::Borrow;
+
+use ark_r1cs_std::uint8::UInt8;
+use decaf377::FieldExt;
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    Bls12_377, Fq, Fr,
+};
+
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use penumbra_proof_verify as verify;
+use rand::{CryptoRng, Rng};
+
+use super::swap::amount_witness;
+use crate::proofs::groth16::{
+    error::{self, VerificationError},
+    gadgets, metrics,
+    traits::{ELEMENT_PUBLIC_INPUTS, FQ_PUBLIC_INPUTS},
+    witness::SecretBytes,
+    CircuitDescription, Enforcement, Gadget, ParameterSetup, ProverOptions, PublicInputs, Variable,
+    VariableKind,
+};
+use crate::proofs::ProofRng;
+use crate::{asset, balance, stake::Penalty, Amount};
+
+/// Penalties are fixed-point numbers with this denominator, i.e. in units of
+/// `bps^2`.
+const PENALTY_SCALE: u64 = 1_0000_0000;
+
+// Public:
+// * vcm (balance commitment of the claim)
+// * unbonding id (asset ID of the unbonding token)
+// * penalty
+//
+// Witnesses:
+// * unbonding amount
+// * penalized amount (the unbonding amount with the penalty applied)
+// * balance blinding (Fr)
+
+/// Groth16 proof that an undelegate claim's balance commitment converts some
+/// amount of an unbonding token to the staking token at the penalty-adjusted
+/// rate, without revealing the amount.
+#[derive(Clone, Debug)]
+pub struct UndelegateClaimCircuit {
+    // Witnesses
+    /// The amount of the unbonding token being claimed.
+    unbonding_amount: Amount,
+    /// The blinding factor used for generating the balance commitment.
+    balance_blinding: Fr,
+
+    // Public inputs
+    /// balance commitment of the claim,
+    pub balance_commitment: balance::Commitment,
+    /// asset ID of the unbonding token,
+    pub unbonding_id: asset::Id,
+    /// penalty applied to the unbonding amount.
+    pub penalty: Penalty,
+}
+
+impl ConstraintSynthesizer<Fq> for UndelegateClaimCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let (unbonding_amount_vars, unbonding_amount_var) =
+            amount_witness(cs.clone(), self.unbonding_amount)?;
+        let (penalized_amount_vars, penalized_amount_var) =
+            amount_witness(cs.clone(), self.penalty.apply_to(self.unbonding_amount))?;
+        let balance_blinding_arr = SecretBytes::new(self.balance_blinding.to_bytes());
+        let balance_blinding_vars = UInt8::new_witness_vec(cs.clone(), &balance_blinding_arr)?;
+
+        // Public inputs
+        let balance_commitment_var =
+            ElementVar::new_input(cs.clone(), || Ok(self.balance_commitment.0))?;
+        let unbonding_id_var = FqVar::new_input(cs.clone(), || Ok(self.unbonding_id.0))?;
+        let penalty_var = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.penalty.0)))?;
+
+        // The penalized amount is the unbonding amount times `1 - penalty`.
+        let rate_var = FqVar::new_constant(cs.clone(), Fq::from(PENALTY_SCALE))? - penalty_var;
+        metrics::gadget(&cs, "fixed_point_mul_integrity", || {
+            gadgets::fixed_point_mul_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                PENALTY_SCALE,
+                unbonding_amount_var,
+                penalized_amount_var,
+                rate_var,
+            )
+        })?;
+        metrics::gadget(&cs, "undelegate_claim_balance_integrity", || {
+            gadgets::undelegate_claim_balance_integrity(
+                cs.clone(),
+                &Boolean::TRUE,
+                unbonding_amount_vars,
+                penalized_amount_vars,
+                balance_blinding_vars,
+                unbonding_id_var,
+                balance_commitment_var,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl UndelegateClaimCircuit {
+    /// Describe the statement proven by this circuit.
+    pub fn describe() -> CircuitDescription {
+        use Enforcement::Always;
+        use VariableKind::{Bytes, Element, Fq};
+
+        CircuitDescription {
+            name: "undelegate_claim",
+            public_inputs: vec![
+                Variable::new("balance_commitment", Element),
+                Variable::new("unbonding_id", Fq),
+                Variable::new("penalty", Fq),
+            ],
+            witnesses: vec![
+                Variable::new("unbonding_amount", Bytes { len: 16 }),
+                Variable::new("penalized_amount", Bytes { len: 16 }),
+                Variable::new("balance_blinding", Bytes { len: 32 }),
+            ],
+            gadgets: vec![
+                Gadget::new("amount_range_check", Always, &["unbonding_amount"]),
+                Gadget::new("amount_range_check", Always, &["penalized_amount"]),
+                Gadget::new(
+                    "fixed_point_mul_integrity",
+                    Always,
+                    &["unbonding_amount", "penalized_amount", "penalty"],
+                ),
+                Gadget::new(
+                    "undelegate_claim_balance_integrity",
+                    Always,
+                    &[
+                        "unbonding_amount",
+                        "penalized_amount",
+                        "balance_blinding",
+                        "unbonding_id",
+                        "balance_commitment",
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+/// The public inputs to the undelegate claim circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UndelegateClaimPublicInputs {
+    /// balance commitment of the claim,
+    pub balance_commitment: balance::Commitment,
+    /// asset ID of the unbonding token,
+    pub unbonding_id: asset::Id,
+    /// penalty applied to the unbonding amount.
+    pub penalty: Penalty,
+}
+
+impl PublicInputs for UndelegateClaimPublicInputs {
+    type Circuit = UndelegateClaimCircuit;
+
+    fn from_circuit(circuit: &UndelegateClaimCircuit) -> Self {
+        Self {
+            balance_commitment: circuit.balance_commitment,
+            unbonding_id: circuit.unbonding_id,
+            penalty: circuit.penalty,
+        }
+    }
+
+    fn to_field_elements(&self) -> Result<Vec<Fq>, VerificationError> {
+        let mut public_inputs = Vec::new();
+        public_inputs.extend(error::public_input(
+            "balance_commitment",
+            &self.balance_commitment.0,
+        )?);
+        public_inputs.extend(error::public_input("unbonding_id", &self.unbonding_id.0)?);
+        public_inputs.push(Fq::from(self.penalty.0));
+        debug_assert_eq!(
+            public_inputs.len(),
+            UndelegateClaimCircuit::NUM_PUBLIC_INPUTS
+        );
+        Ok(public_inputs)
+    }
+}
+
+impl ParameterSetup for UndelegateClaimCircuit {
+    const NAME: &'static str = "undelegate_claim";
+
+    // balance commitment, unbonding id, penalty
+    const NUM_PUBLIC_INPUTS: usize = ELEMENT_PUBLIC_INPUTS + 2 * FQ_PUBLIC_INPUTS;
+
+    fn sample_circuit() -> Self {
+        let unbonding_amount = Amount::from(1u64);
+        let balance_blinding = Fr::from(1);
+        let unbonding_id = asset::Id(Fq::from(1u64));
+        let penalty = Penalty(0);
+        UndelegateClaimCircuit {
+            unbonding_amount,
+            balance_blinding,
+            balance_commitment: penalty
+                .balance_for_claim(unbonding_id, unbonding_amount)
+                .commit(balance_blinding),
+            unbonding_id,
+            penalty,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndelegateClaimProof(pub(super) Proof<Bls12_377>);
+
+impl UndelegateClaimProof {
+    #![allow(clippy::too_many_arguments)]
+    /// Generate a proof, according to the provided [`ProverOptions`].
+    ///
+    /// The proving key can be passed by reference, or as an `Arc` so that a
+    /// single loaded key can be shared between many prover threads.
+    pub fn prove<R: CryptoRng + Rng + Send, P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut R,
+        pk: P,
+        unbonding_amount: Amount,
+        balance_blinding: Fr,
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> anyhow::Result<Self> {
+        let circuit = UndelegateClaimCircuit {
+            unbonding_amount,
+            balance_blinding,
+            balance_commitment,
+            unbonding_id,
+            penalty,
+        };
+        let proof = options.prove(pk.borrow(), circuit, rng)?;
+        Ok(Self(proof))
+    }
+
+    /// Generate a proof as with [`UndelegateClaimProof::prove`], drawing its randomness
+    /// from a [`ProofRng`], so that the same seed always gives the same proof.
+    pub fn prove_deterministic<P: Borrow<ProvingKey<Bls12_377>>>(
+        options: &ProverOptions,
+        rng: &mut ProofRng,
+        pk: P,
+        unbonding_amount: Amount,
+        balance_blinding: Fr,
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> anyhow::Result<Self> {
+        Self::prove(
+            options,
+            rng,
+            pk,
+            unbonding_amount,
+            balance_blinding,
+            balance_commitment,
+            unbonding_id,
+            penalty,
+        )
+    }
+
+    /// Serialize the proof in its compressed encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize(&mut bytes)
+            .expect("can serialize proof to a vector");
+        bytes
+    }
+
+    /// Rerandomize the proof, so that it can't be linked to the proof it was
+    /// made from, e.g. by the party that proving was outsourced to.
+    ///
+    /// The result is a fresh proof of the same statement, which verifies with
+    /// the same public inputs as this proof, against the verifying key `vk`
+    /// that this proof verifies against.
+    pub fn rerandomize<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        vk: &VerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self(ark_groth16::rerandomize_proof(rng, vk, &self.0))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The public inputs are:
+    /// * balance commitment of the claim,
+    /// * asset ID of the unbonding token,
+    /// * penalty applied to the unbonding amount,
+    pub fn verify(
+        &self,
+        vk: &VerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> Result<(), VerificationError> {
+        UndelegateClaimCircuit::check_verifying_key(vk)?;
+        let processed_pvk = Groth16::process_vk(vk)
+            .map_err(|err| VerificationError::VerifyingKeyMismatch(err.to_string()))?;
+        self.verify_prepared(&processed_pvk, balance_commitment, unbonding_id, penalty)
+    }
+
+    /// Verify the proof with each of the candidate verifying keys in order,
+    /// e.g. the keys from before and after a parameter upgrade, returning the
+    /// index of the first key the proof verifies with.
+    pub fn verify_any(
+        &self,
+        vks: &[&VerifyingKey<Bls12_377>],
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> anyhow::Result<usize> {
+        verify::verify_any(vks, |vk| {
+            self.verify(vk, balance_commitment, unbonding_id, penalty)
+        })
+    }
+
+    /// Verify the proof against an already prepared verifying key, e.g. one
+    /// loaded with [`prepared_verifying_key_from_bytes`](super::prepared_verifying_key_from_bytes).
+    pub fn verify_prepared(
+        &self,
+        processed_pvk: &PreparedVerifyingKey<Bls12_377>,
+        balance_commitment: balance::Commitment,
+        unbonding_id: asset::Id,
+        penalty: Penalty,
+    ) -> Result<(), VerificationError> {
+        UndelegateClaimCircuit::check_verifying_key(&processed_pvk.vk)?;
+        let public_inputs = UndelegateClaimPublicInputs {
+            balance_commitment,
+            unbonding_id,
+            penalty,
+        }
+        .to_field_elements()?;
+
+        verify::verify_proof(processed_pvk, &public_inputs, &self.0)
+    }
+}
@@ -0,0 +1,69 @@
+//! Verifying proofs from async code without blocking its executor.
+
+use std::{future::Future, sync::Arc};
+
+use futures::channel::oneshot;
+
+/// Offloads proof verification to a dedicated thread pool, so that async
+/// code, e.g. consensus code running on tokio, doesn't block its executor's
+/// threads on pairing computations.
+///
+/// Cloning a pool is cheap, and the clones share its threads.
+#[derive(Clone)]
+pub struct Pool {
+    pool: Arc<rayon::ThreadPool>,
+}
+
+impl Pool {
+    /// Create a pool verifying up to `threads` proofs at once.
+    pub fn new(threads: usize) -> anyhow::Result<Self> {
+        if threads == 0 {
+            anyhow::bail!("verification pool must have at least 1 thread");
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("groth16-verify-{}", index))
+            .build()?;
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    /// The number of proofs verified at once.
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Run `verify` on the pool, returning a future that resolves to its
+    /// result.
+    ///
+    /// The job should verify a proof, e.g. by calling
+    /// [`SpendProof::verify_prepared`](super::SpendProof::verify_prepared)
+    /// with a prepared verifying key shared through an `Arc`. It is started
+    /// immediately, whether or not the future is polled, and if the future is
+    /// dropped its result is discarded.
+    ///
+    /// As for any job on a rayon pool, a panic in `verify` aborts the process.
+    pub fn verify<T, F>(&self, verify: F) -> impl Future<Output = T> + Send + 'static
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.pool.spawn(move || {
+            // The caller may have stopped waiting for the result.
+            let _ = sender.send(verify());
+        });
+
+        async move { receiver.await.expect("verification jobs run to completion") }
+    }
+}
+
+impl std::fmt::Debug for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pool")
+            .field("threads", &self.threads())
+            .finish()
+    }
+}
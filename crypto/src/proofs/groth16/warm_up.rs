@@ -0,0 +1,45 @@
+use ark_groth16::ProvingKey;
+use decaf377::Bls12_377;
+
+/// The granularity at which memory is touched when warming up a key.
+const PAGE_SIZE: usize = 4096;
+
+/// Extension methods for Groth16 proving keys.
+pub trait ProvingKeyExt {
+    /// Read every page of the key's queries, so that the first proof made with
+    /// it doesn't pay for faulting them in.
+    ///
+    /// A proving key is several hundred megabytes of curve points, which after
+    /// a long idle period may have been swapped out or compressed by the OS.
+    /// Latency-sensitive callers, such as a wallet about to build a
+    /// transaction, can call this in the background ahead of time. The pages
+    /// of each query are touched in parallel.
+    ///
+    /// The Groth16 prover doesn't precompute any tables from the key, so
+    /// touching its memory is all the warm-up there is to do.
+    fn warm_up(&self);
+}
+
+impl ProvingKeyExt for ProvingKey<Bls12_377> {
+    fn warm_up(&self) {
+        rayon::scope(|scope| {
+            scope.spawn(|_| touch(&self.a_query));
+            scope.spawn(|_| touch(&self.b_g1_query));
+            scope.spawn(|_| touch(&self.b_g2_query));
+            scope.spawn(|_| touch(&self.h_query));
+            scope.spawn(|_| touch(&self.l_query));
+        });
+    }
+}
+
+/// Read one element from each page spanned by `elements`.
+fn touch<T: Copy>(elements: &[T]) {
+    let stride = (PAGE_SIZE / std::mem::size_of::<T>().max(1)).max(1);
+    for element in elements.iter().step_by(stride) {
+        // SAFETY: `element` is a valid reference. The read is volatile so that
+        // it isn't optimized away.
+        unsafe {
+            std::ptr::read_volatile(element);
+        }
+    }
+}
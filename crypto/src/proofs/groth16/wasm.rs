@@ -0,0 +1,117 @@
+//! Byte-oriented wrappers around proving and verification, for browser
+//! wallets.
+//!
+//! Every argument is an encoded byte slice and every error is a string, so with
+//! the `wasm` feature these functions are exported to JavaScript by
+//! `wasm-bindgen` as they are, without any glue types.
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use decaf377::{Bls12_377, FieldExt, Fr};
+use penumbra_proto::core::crypto::v1alpha1 as pb;
+use penumbra_tct as tct;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::{OutputProof, OutputProofPlan, SpendProof};
+use crate::{balance, keys::RandomizedVerificationKey, note, Note, Nullifier};
+
+/// Generate an output proof, returning its encoding.
+///
+/// The proof's randomness is drawn from a generator seeded with the 32 bytes
+/// of `entropy`, which must be freshly sampled for each proof, e.g. with
+/// `crypto.getRandomValues`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn prove_output(
+    pk: &[u8],
+    entropy: &[u8],
+    note: &[u8],
+    v_blinding: &[u8],
+    balance_commitment: &[u8],
+    note_commitment: &[u8],
+) -> Result<Vec<u8>, String> {
+    let pk = ProvingKey::<Bls12_377>::deserialize(pk).map_err(|err| err.to_string())?;
+    let mut rng = ChaCha20Rng::from_seed(decode("entropy", entropy, |bytes| Ok(bytes))?);
+    let note = Note::try_from(note).map_err(|err| err.to_string())?;
+    let v_blinding = decode("v_blinding", v_blinding, |bytes| Ok(Fr::from_bytes(bytes)?))?;
+    let balance_commitment = decode("balance_commitment", balance_commitment, |bytes| {
+        Ok(balance::Commitment::try_from(bytes)?)
+    })?;
+    let note_commitment = decode("note_commitment", note_commitment, |bytes| {
+        Ok(note::Commitment::try_from(bytes)?)
+    })?;
+
+    let proof = OutputProofPlan {
+        note,
+        v_blinding,
+        balance_commitment,
+        note_commitment,
+    }
+    .prove(&mut rng, &pk)
+    .map_err(|err| err.to_string())?;
+    Ok(proof.to_bytes())
+}
+
+/// Verify an output proof against its public inputs.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn verify_output_proof(
+    vk: &[u8],
+    proof: &[u8],
+    balance_commitment: &[u8],
+    note_commitment: &[u8],
+) -> Result<(), String> {
+    let vk = VerifyingKey::<Bls12_377>::deserialize(vk).map_err(|err| err.to_string())?;
+    let proof = OutputProof::try_from(proof).map_err(|err| err.to_string())?;
+    let balance_commitment = decode("balance_commitment", balance_commitment, |bytes| {
+        Ok(balance::Commitment::try_from(bytes)?)
+    })?;
+    let note_commitment = decode("note_commitment", note_commitment, |bytes| {
+        Ok(note::Commitment::try_from(bytes)?)
+    })?;
+
+    proof
+        .verify(&vk, balance_commitment, note_commitment)
+        .map_err(|err| err.to_string())
+}
+
+/// Verify a spend proof against its public inputs.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn verify_spend_proof(
+    vk: &[u8],
+    proof: &[u8],
+    anchor: &[u8],
+    balance_commitment: &[u8],
+    nullifier: &[u8],
+    rk: &[u8],
+) -> Result<(), String> {
+    let vk = VerifyingKey::<Bls12_377>::deserialize(vk).map_err(|err| err.to_string())?;
+    let proof = SpendProof::try_from(proof).map_err(|err| err.to_string())?;
+    let anchor = tct::Root::try_from(pb::MerkleRoot {
+        inner: anchor.to_vec(),
+    })
+    .map_err(|err| format!("invalid anchor: {}", err))?;
+    let balance_commitment = decode("balance_commitment", balance_commitment, |bytes| {
+        Ok(balance::Commitment::try_from(bytes)?)
+    })?;
+    let nullifier =
+        Nullifier::try_from(nullifier).map_err(|err| format!("invalid nullifier: {}", err))?;
+    let rk = decode("rk", rk, RandomizedVerificationKey::try_from)?;
+
+    proof
+        .verify(&vk, anchor, balance_commitment, nullifier, rk)
+        .map_err(|err| err.to_string())
+}
+
+/// Decode a 32-byte argument, naming it in the error if it is invalid.
+fn decode<T>(
+    name: &str,
+    bytes: &[u8],
+    decode: impl FnOnce([u8; 32]) -> anyhow::Result<T>,
+) -> Result<T, String> {
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| format!("{} must be 32 bytes, got {}", name, bytes.len()))?;
+    decode(bytes).map_err(|err| format!("invalid {}: {}", name, err))
+}
@@ -0,0 +1,120 @@
+use std::{cell::RefCell, ops::Deref};
+
+use zeroize::Zeroize;
+
+thread_local! {
+    /// Whether the circuit being synthesized on this thread should lock its
+    /// secret witness buffers into memory, and if so, the first error locking
+    /// one of them.
+    static LOCKING: RefCell<Option<Result<(), String>>> = RefCell::new(None);
+}
+
+/// Run `prove`, locking the secret witness buffers it allocates into memory if
+/// `lock` is set.
+///
+/// Returns an error if any buffer could not be locked, e.g. because the
+/// process's locked memory limit was reached, or because the crate was built
+/// without the `mlock` feature.
+pub(super) fn with_locked_witnesses<T>(lock: bool, prove: impl FnOnce() -> T) -> anyhow::Result<T> {
+    if !lock {
+        return Ok(prove());
+    }
+    if !cfg!(all(feature = "mlock", unix)) {
+        anyhow::bail!("locking witness memory requires the `mlock` feature on a unix platform");
+    }
+
+    let previous = LOCKING.with(|locking| locking.replace(Some(Ok(()))));
+    let result = prove();
+    let locked = LOCKING
+        .with(|locking| locking.replace(previous))
+        .unwrap_or(Ok(()));
+    locked.map_err(|err| anyhow::anyhow!("could not lock witness memory: {}", err))?;
+    Ok(result)
+}
+
+/// The bytes of a secret witness, such as a blinding factor or randomizer,
+/// while a circuit is synthesized.
+///
+/// The bytes are zeroized when the buffer is dropped, i.e. once constraint
+/// generation is done with them. When proving with
+/// [`ProverOptions::lock_witness_memory`](super::ProverOptions::lock_witness_memory),
+/// they are also locked into memory, so that this buffer is never written to
+/// swap. Only this buffer is protected: the field elements allocated from it
+/// in the constraint system, and the circuit it was taken from, are not.
+pub(crate) struct SecretBytes<const N: usize> {
+    bytes: Box<[u8; N]>,
+    locked: bool,
+}
+
+impl<const N: usize> SecretBytes<N> {
+    /// Move `bytes` into a secret buffer, zeroizing the original.
+    pub(crate) fn new(mut bytes: [u8; N]) -> Self {
+        let mut secret = Self {
+            bytes: Box::new([0u8; N]),
+            locked: false,
+        };
+        secret.lock();
+        secret.bytes.copy_from_slice(&bytes);
+        bytes.zeroize();
+        secret
+    }
+
+    /// Lock the buffer into memory, if the circuit is being synthesized with
+    /// locking enabled.
+    fn lock(&mut self) {
+        let should_lock = LOCKING.with(|locking| matches!(*locking.borrow(), Some(Ok(()))));
+        if !should_lock {
+            return;
+        }
+
+        match mlock(self.bytes.as_ptr(), N) {
+            Ok(()) => self.locked = true,
+            Err(err) => LOCKING.with(|locking| *locking.borrow_mut() = Some(Err(err))),
+        }
+    }
+}
+
+impl<const N: usize> Deref for SecretBytes<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+}
+
+impl<const N: usize> Drop for SecretBytes<N> {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            munlock(self.bytes.as_ptr(), N);
+        }
+    }
+}
+
+#[cfg(all(feature = "mlock", unix))]
+fn mlock(ptr: *const u8, len: usize) -> Result<(), String> {
+    // SAFETY: `ptr` points to an allocation of at least `len` bytes, which is
+    // only unlocked when it is dropped.
+    if unsafe { libc::mlock(ptr as *const libc::c_void, len) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(all(feature = "mlock", unix)))]
+fn mlock(_ptr: *const u8, _len: usize) -> Result<(), String> {
+    Err("memory locking is not supported in this build".to_string())
+}
+
+#[cfg(all(feature = "mlock", unix))]
+fn munlock(ptr: *const u8, len: usize) {
+    // SAFETY: `ptr` points to an allocation of at least `len` bytes, which
+    // was locked by `mlock`.
+    unsafe {
+        libc::munlock(ptr as *const libc::c_void, len);
+    }
+}
+
+#[cfg(not(all(feature = "mlock", unix)))]
+fn munlock(_ptr: *const u8, _len: usize) {}
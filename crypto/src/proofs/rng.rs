@@ -0,0 +1,52 @@
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+
+/// The randomness used to build proofs, and the transactions containing them.
+///
+/// This is always a ChaCha20 stream, seeded either from another generator, to
+/// build real transactions, or from a fixed seed, so that tests can build
+/// exactly the same proofs and transaction bytes on every run. Using the same
+/// seed for different witnesses can leak information about them, so fixed
+/// seeds are only for testing.
+#[derive(Clone, Debug)]
+pub struct ProofRng(ChaCha20Rng);
+
+impl ProofRng {
+    /// A generator producing the same stream for the same `seed`.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self(ChaCha20Rng::from_seed(seed))
+    }
+
+    /// A generator seeded from `rng`, e.g. `OsRng`.
+    pub fn from_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        Self::from_seed(seed)
+    }
+
+    /// Split off an independent generator, so that the randomness drawn from
+    /// it doesn't depend on how much is drawn from this one afterwards.
+    pub fn fork(&mut self) -> Self {
+        Self::from_rng(self)
+    }
+}
+
+impl RngCore for ProofRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for ProofRng {}
@@ -139,7 +139,7 @@ impl Protobuf<transparent_proofs::SpendProof> for SpendProof {}
 impl From<SpendProof> for transparent_proofs::SpendProof {
     fn from(msg: SpendProof) -> Self {
         let ak_bytes: [u8; 32] = msg.ak.into();
-        let nk_bytes: [u8; 32] = msg.nk.0.to_bytes();
+        let nk_bytes: [u8; 32] = msg.nk.expose_secret().to_bytes();
         transparent_proofs::SpendProof {
             note_commitment_proof: Some(msg.note_commitment_proof.into()),
             note: Some(msg.note.into()),
@@ -407,7 +407,7 @@ impl From<SwapClaimProof> for transparent_proofs::SwapClaimProof {
         Self {
             swap_commitment_proof: Some(msg.swap_commitment_proof.into()),
             swap_plaintext: Some(msg.swap_plaintext.into()),
-            nk: msg.nk.0.to_bytes().to_vec(),
+            nk: msg.nk.expose_secret().to_bytes().to_vec(),
             lambda_1_i: msg.lambda_1_i,
             lambda_2_i: msg.lambda_2_i,
         }
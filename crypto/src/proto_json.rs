@@ -0,0 +1,251 @@
+//! Serializers following the [proto3 JSON mapping] of the protobuf types.
+//!
+//! The `Serialize` implementations of domain types are meant to be read by
+//! people: addresses and asset IDs are Bech32 strings, and commitments are hex.
+//! Tools that work from the protobuf definitions, like REST gateways and block
+//! explorers, expect the canonical proto3 JSON instead, with field names in
+//! lowerCamelCase, bytes in base64, and 64-bit integers as decimal strings.
+//! Fields with default values are omitted when serializing, and accepted as
+//! missing when deserializing.
+//!
+//! Each module here can be used with `#[serde(with = "...")]` on a field of
+//! the corresponding domain type:
+//!
+//! ```
+//! use penumbra_crypto::{proto_json, Address, Value};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Transfer {
+//!     #[serde(with = "proto_json::address")]
+//!     to: Address,
+//!     #[serde(with = "proto_json::value")]
+//!     value: Value,
+//! }
+//! ```
+//!
+//! [proto3 JSON mapping]: https://protobuf.dev/programming-guides/proto3/#json
+
+use penumbra_proto::{core::crypto::v1alpha1 as pb, serializers::base64str};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A message whose only field is `bytes inner = 1`.
+#[derive(Serialize, Deserialize)]
+struct Inner {
+    #[serde(default, with = "base64str", skip_serializing_if = "Vec::is_empty")]
+    inner: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AmountJson {
+    #[serde(default, with = "uint64", skip_serializing_if = "is_zero")]
+    lo: u64,
+    #[serde(default, with = "uint64", skip_serializing_if = "is_zero")]
+    hi: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ValueJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    amount: Option<AmountJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    asset_id: Option<Inner>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Serialize `value` as its proto3 JSON, by way of its protobuf type `P`.
+fn serialize_via<T, P, J, S>(
+    value: &T,
+    to_json: fn(P) -> J,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Clone + Into<P>,
+    J: Serialize,
+    S: Serializer,
+{
+    to_json(value.clone().into()).serialize(serializer)
+}
+
+/// Deserialize a `T` from its proto3 JSON, by way of its protobuf type `P`.
+fn deserialize_via<'de, T, P, J, D>(from_json: fn(J) -> P, deserializer: D) -> Result<T, D::Error>
+where
+    T: TryFrom<P>,
+    T::Error: Into<anyhow::Error>,
+    J: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::try_from(from_json(J::deserialize(deserializer)?))
+        .map_err(|e| D::Error::custom(format!("{:#}", e.into())))
+}
+
+/// 64-bit unsigned integers, written as decimal strings, and read from either
+/// strings or numbers.
+mod uint64 {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    pub fn serialize<S: Serializer>(n: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(n)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(s) => s.parse().map_err(D::Error::custom),
+            StringOrNumber::Number(n) => Ok(n),
+        }
+    }
+}
+
+macro_rules! inner_bytes_module {
+    ($(#[$doc:meta])* $name:ident, $domain:ty, $proto:ident) => {
+        $(#[$doc])*
+        pub mod $name {
+            use super::*;
+
+            pub fn serialize<S>(value: &$domain, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serialize_via(value, |p: pb::$proto| Inner { inner: p.inner }, serializer)
+            }
+
+            pub fn deserialize<'de, D>(deserializer: D) -> Result<$domain, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserialize_via(|j: Inner| pb::$proto { inner: j.inner }, deserializer)
+            }
+        }
+    };
+}
+
+inner_bytes_module!(
+    /// [`Address`](crate::Address)es, as `{"inner": "<base64>"}`.
+    address,
+    crate::Address,
+    Address
+);
+inner_bytes_module!(
+    /// [`asset::Id`](crate::asset::Id)s, as `{"inner": "<base64>"}`.
+    asset_id,
+    crate::asset::Id,
+    AssetId
+);
+inner_bytes_module!(
+    /// [`balance::Commitment`](crate::balance::Commitment)s, as `{"inner": "<base64>"}`.
+    balance_commitment,
+    crate::balance::Commitment,
+    BalanceCommitment
+);
+inner_bytes_module!(
+    /// Note and swap [`Commitment`](crate::note::Commitment)s, as `{"inner": "<base64>"}`.
+    state_commitment,
+    crate::note::Commitment,
+    StateCommitment
+);
+
+/// [`Amount`](crate::asset::Amount)s, as `{"lo": "<u64>", "hi": "<u64>"}`.
+pub mod amount {
+    use super::*;
+    use crate::asset::Amount;
+
+    pub fn serialize<S: Serializer>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_via(
+            amount,
+            |p: pb::Amount| AmountJson { lo: p.lo, hi: p.hi },
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Amount, D::Error> {
+        deserialize_via(
+            |j: AmountJson| pb::Amount { lo: j.lo, hi: j.hi },
+            deserializer,
+        )
+    }
+}
+
+/// [`Value`](crate::Value)s, as `{"amount": {...}, "assetId": {...}}`.
+pub mod value {
+    use super::*;
+    use crate::Value;
+
+    pub fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_via(
+            value,
+            |p: pb::Value| ValueJson {
+                amount: p.amount.map(|a| AmountJson { lo: a.lo, hi: a.hi }),
+                asset_id: p.asset_id.map(|id| Inner { inner: id.inner }),
+            },
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserialize_via(
+            |j: ValueJson| pb::Value {
+                amount: j.amount.map(|a| pb::Amount { lo: a.lo, hi: a.hi }),
+                asset_id: j.asset_id.map(|id| pb::AssetId { inner: id.inner }),
+            },
+            deserializer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{asset, balance, Address, Fr, Value, STAKING_TOKEN_ASSET_ID};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Example {
+        #[serde(with = "address")]
+        address: Address,
+        #[serde(with = "value")]
+        value: Value,
+        #[serde(with = "balance_commitment")]
+        balance_commitment: balance::Commitment,
+    }
+
+    #[test]
+    fn proto3_json_roundtrip() {
+        let value = Value {
+            amount: asset::Amount::from(1u64 << 40),
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let example = Example {
+            address: Address::dummy(&mut OsRng),
+            value,
+            balance_commitment: value.commit(Fr::from(7u64)),
+        };
+
+        let json = serde_json::to_value(&example).unwrap();
+        assert_eq!(json["value"]["amount"]["lo"], "1099511627776");
+        assert!(json["value"]["amount"].get("hi").is_none());
+        assert!(json["value"]["assetId"]["inner"].is_string());
+        assert!(json["address"]["inner"].is_string());
+        assert!(json["balanceCommitment"]["inner"].is_string());
+
+        let parsed: Example = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, example);
+
+        // Parsers accept 64-bit integers as numbers too.
+        let mut json = serde_json::to_value(&example).unwrap();
+        json["value"]["amount"]["lo"] = serde_json::json!(1u64 << 40);
+        assert_eq!(serde_json::from_value::<Example>(json).unwrap(), example);
+    }
+}
@@ -0,0 +1,74 @@
+//! Uniform sampling of field elements.
+//!
+//! Blinding factors and randomizers must be (statistically) uniform in their
+//! field, or commitments and re-randomized keys can leak information about the
+//! values they hide. The helpers here always sample 64 bytes of randomness
+//! and reduce them modulo the field order. Since both `Fq` and `Fr` have
+//! moduli of fewer than 256 bits, the bias of this wide reduction is at most
+//! `2^-256`, which is negligible. No rejection sampling is needed, so the
+//! running time does not depend on the sampled value.
+
+use ark_ff::PrimeField;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{prf, Fq, Fr};
+
+/// The default source of randomness for callers without their own RNG policy.
+///
+/// Library code in this crate never samples randomness on its own: every
+/// randomized operation takes a caller-provided [`CryptoRng`], so that
+/// applications can choose their randomness source, and tests can be made
+/// deterministic. Callers without special requirements should pass this one.
+///
+/// This requires the `std` feature; without it, e.g. in a browser, callers
+/// should seed a generator from the platform's own randomness source.
+#[cfg(feature = "std")]
+pub use rand_core::OsRng as DefaultRng;
+
+/// The number of random bytes reduced into a single field element.
+pub const WIDE_SAMPLE_BYTES: usize = 64;
+
+/// A prime field which can be sampled uniformly at random.
+pub trait SampleUniform: PrimeField {
+    /// Sample a uniformly random element of the field from `rng`.
+    fn rand_uniform<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; WIDE_SAMPLE_BYTES];
+        rng.fill_bytes(&mut bytes);
+        Self::from_le_bytes_mod_order(&bytes)
+    }
+
+    /// Deterministically derive a uniformly distributed element of the field
+    /// from a 32-byte `seed`.
+    ///
+    /// Distinct seeds give independent-looking elements, so this can be used
+    /// to derive blinding factors from a uniformly random seed.
+    fn rand_from_seed(seed: &[u8; 32]) -> Self {
+        prf::expand_ff(b"Penumbra_SmplUni", seed, &[])
+    }
+}
+
+impl SampleUniform for Fq {}
+impl SampleUniform for Fr {}
+
+/// Sample a fresh blinding factor for a balance commitment.
+pub fn blinding_factor<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    Fr::rand_uniform(rng)
+}
+
+/// Sample a fresh randomizer for a spend authorization key.
+pub fn spend_auth_randomizer<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    Fr::rand_uniform(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rand_from_seed_is_deterministic() {
+        let seed = [7u8; 32];
+        assert_eq!(Fr::rand_from_seed(&seed), Fr::rand_from_seed(&seed));
+        assert_eq!(Fq::rand_from_seed(&seed), Fq::rand_from_seed(&seed));
+        assert_ne!(Fr::rand_from_seed(&seed), Fr::rand_from_seed(&[8u8; 32]));
+    }
+}
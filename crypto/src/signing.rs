@@ -0,0 +1,176 @@
+//! Typed signing contexts for the signatures used in the protocol.
+//!
+//! Every signature is made in a [`SigningContext`], which fixes both the signature domain and a
+//! label that the message is hashed under before signing. Since the label differs between
+//! contexts, a signature produced for one purpose (e.g. authorizing a spend) never verifies for
+//! another (e.g. a validator definition), even when the same key is used for both.
+//!
+//! Signatures made before signing contexts were introduced are over the raw message. Verifiers
+//! keep accepting them until the chain switches to signing contexts, as decided by a
+//! [`SigningPolicy`], so that existing clients keep working until then.
+
+use decaf377_rdsa::{Binding, Domain, Signature, SigningKey, SpendAuth, VerificationKey};
+use rand_core::{CryptoRng, RngCore};
+
+mod aggregate;
+
+pub use aggregate::AggregateAuthSignature;
+
+/// A purpose for which signatures are produced.
+pub trait SigningContext {
+    /// The signature domain used in this context.
+    type Domain: Domain;
+
+    /// The label under which messages are hashed in this context, unique to the context.
+    const LABEL: &'static [u8; 16];
+}
+
+/// Signatures authorizing a spend, over the transaction's effect hash.
+#[derive(Clone, Copy, Debug)]
+pub struct SpendAuthorization;
+
+impl SigningContext for SpendAuthorization {
+    type Domain = SpendAuth;
+    const LABEL: &'static [u8; 16] = b"Penumbra_SigSpAu";
+}
+
+/// Binding signatures proving that a transaction's balance commitments balance.
+#[derive(Clone, Copy, Debug)]
+pub struct BalanceBinding;
+
+impl SigningContext for BalanceBinding {
+    type Domain = Binding;
+    const LABEL: &'static [u8; 16] = b"Penumbra_SigBind";
+}
+
+/// Signatures by a validator's identity key over its validator definition.
+#[derive(Clone, Copy, Debug)]
+pub struct ValidatorDefinition;
+
+impl SigningContext for ValidatorDefinition {
+    type Domain = SpendAuth;
+    const LABEL: &'static [u8; 16] = b"Penumbra_SigVDef";
+}
+
+/// Signatures by a validator's governance key over its vote on a proposal.
+#[derive(Clone, Copy, Debug)]
+pub struct GovernanceVote;
+
+impl SigningContext for GovernanceVote {
+    type Domain = SpendAuth;
+    const LABEL: &'static [u8; 16] = b"Penumbra_SigGovV";
+}
+
+/// Sign `message` with `key` in the signing context `C`.
+pub fn sign<C: SigningContext, R: RngCore + CryptoRng>(
+    rng: R,
+    key: &SigningKey<C::Domain>,
+    message: &[u8],
+) -> Signature<C::Domain> {
+    key.sign(rng, &context_message::<C>(message))
+}
+
+/// Verify a `signature` on `message` by `key`, made in the signing context `C`.
+pub fn verify<C: SigningContext>(
+    key: &VerificationKey<C::Domain>,
+    message: &[u8],
+    signature: &Signature<C::Domain>,
+) -> Result<(), decaf377_rdsa::Error> {
+    key.verify(&context_message::<C>(message), signature)
+}
+
+/// Which signatures verifiers accept, during the migration to signing contexts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigningPolicy {
+    /// Accept signatures made in the signing context, as well as signatures over the raw message
+    /// made before signing contexts were introduced.
+    AllowLegacy,
+    /// Accept only signatures made in the signing context.
+    RequireContext,
+}
+
+impl SigningPolicy {
+    /// The policy in force at `height`, given the height from which signatures must be made in
+    /// their signing context, if one has been scheduled.
+    pub fn at_height(activation_height: Option<u64>, height: u64) -> Self {
+        match activation_height {
+            Some(activation) if height >= activation => SigningPolicy::RequireContext,
+            _ => SigningPolicy::AllowLegacy,
+        }
+    }
+}
+
+/// Verify a `signature` on `message` by `key` under `policy`: made in the signing context `C`, or,
+/// if the policy allows it, over the raw message.
+pub fn verify_under<C: SigningContext>(
+    policy: SigningPolicy,
+    key: &VerificationKey<C::Domain>,
+    message: &[u8],
+    signature: &Signature<C::Domain>,
+) -> Result<(), decaf377_rdsa::Error> {
+    match (verify::<C>(key, message, signature), policy) {
+        (Ok(()), _) => Ok(()),
+        (Err(_), SigningPolicy::AllowLegacy) => key.verify(message, signature),
+        (Err(error), SigningPolicy::RequireContext) => Err(error),
+    }
+}
+
+/// The bytes actually signed for `message` in the context `C`.
+pub(crate) fn context_message<C: SigningContext>(message: &[u8]) -> [u8; 64] {
+    *blake2b_simd::Params::default()
+        .personal(C::LABEL)
+        .hash(message)
+        .as_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn signatures_do_not_transplant() {
+        let sk = SigningKey::<SpendAuth>::new(OsRng);
+        let vk = VerificationKey::from(&sk);
+        let message = b"a message";
+
+        let signature = sign::<ValidatorDefinition, _>(OsRng, &sk, message);
+        assert!(verify::<ValidatorDefinition>(&vk, message, &signature).is_ok());
+        assert!(verify::<GovernanceVote>(&vk, message, &signature).is_err());
+        assert!(verify::<SpendAuthorization>(&vk, message, &signature).is_err());
+
+        // Nor does a signature over the raw message verify in any context.
+        let raw = sk.sign(OsRng, message);
+        assert!(verify::<ValidatorDefinition>(&vk, message, &raw).is_err());
+    }
+
+    #[test]
+    fn legacy_signatures_verify_until_activation() {
+        let sk = SigningKey::<SpendAuth>::new(OsRng);
+        let vk = VerificationKey::from(&sk);
+        let message = b"a message";
+
+        // A signature made before signing contexts were introduced.
+        let legacy = sk.sign(OsRng, message);
+        let contextual = sign::<SpendAuthorization, _>(OsRng, &sk, message);
+
+        let before = SigningPolicy::at_height(Some(10), 9);
+        let after = SigningPolicy::at_height(Some(10), 10);
+        assert_eq!(before, SigningPolicy::AllowLegacy);
+        assert_eq!(after, SigningPolicy::RequireContext);
+        assert_eq!(
+            SigningPolicy::at_height(None, u64::MAX),
+            SigningPolicy::AllowLegacy
+        );
+
+        assert!(verify_under::<SpendAuthorization>(before, &vk, message, &legacy).is_ok());
+        assert!(verify_under::<SpendAuthorization>(before, &vk, message, &contextual).is_ok());
+        assert!(verify_under::<SpendAuthorization>(after, &vk, message, &legacy).is_err());
+        assert!(verify_under::<SpendAuthorization>(after, &vk, message, &contextual).is_ok());
+
+        // Allowing legacy signatures doesn't allow signatures from another context.
+        let other = sign::<GovernanceVote, _>(OsRng, &sk, message);
+        assert!(verify_under::<SpendAuthorization>(before, &vk, message, &other).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Half-aggregated spend authorization signatures.
+//!
+//! A transaction with `n` spends carries `n` spend auth signatures, each 64 bytes, all over the
+//! same effect hash. An [`AggregateAuthSignature`] replaces them with `n` 32-byte nonce
+//! commitments and a single 32-byte response, roughly halving their size, by the Schnorr
+//! half-aggregation of [Chalkias et al.](https://eprint.iacr.org/2021/350).
+//!
+//! Each spend is signed by its randomized signing key `rsk`, and verified against the
+//! corresponding [`RandomizedVerificationKey`] `rk`, the same already-decoded key that the spend
+//! proof takes as a public input, so that verifying a spend's proof and its authorization decode
+//! `rk` only once.
+//!
+//! Signing for every key at once is possible because all the spends of a transaction are
+//! authorized by the same party; the individual responses are never revealed, so an aggregate
+//! can't be split apart into signatures on a subset of the spends.
+
+use anyhow::{ensure, Result};
+use decaf377::{Element, Encoding, FieldExt, Fr};
+use decaf377_rdsa::{SigningKey, SpendAuth};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{context_message, SpendAuthorization};
+use crate::{keys::RandomizedVerificationKey, prf};
+
+/// A single signature authorizing every spend of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateAuthSignature {
+    /// The nonce commitment of each spend, in order.
+    nonce_commitments: Vec<[u8; 32]>,
+    /// The aggregated response.
+    response: Fr,
+}
+
+impl AggregateAuthSignature {
+    /// Sign `message` (typically a transaction's effect hash) with each of the randomized spend
+    /// authorization keys `rsks`, aggregating the signatures into one.
+    pub fn sign_all<R: RngCore + CryptoRng>(
+        mut rng: R,
+        rsks: &[SigningKey<SpendAuth>],
+        message: &[u8],
+    ) -> Self {
+        let message = context_message::<SpendAuthorization>(message);
+
+        let mut nonces = Vec::with_capacity(rsks.len());
+        let mut nonce_commitments = Vec::with_capacity(rsks.len());
+        let mut rks = Vec::with_capacity(rsks.len());
+        for rsk in rsks {
+            // Derive the nonce from the key and message as well as fresh randomness, so that a
+            // bad rng alone can't cause a nonce to be reused across messages.
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            let mut input = rsk.to_bytes().to_vec();
+            input.extend_from_slice(&message);
+            let nonce: Fr = prf::expand_ff(b"Penumbra_AgSpNon", &seed, &input);
+
+            nonces.push(nonce);
+            nonce_commitments.push((nonce * decaf377::basepoint()).vartime_compress().0);
+            rks.push(decaf377_rdsa::VerificationKey::from(rsk).to_bytes());
+        }
+
+        let response = rsks
+            .iter()
+            .zip(nonces)
+            .enumerate()
+            .map(|(i, (rsk, nonce))| {
+                let rsk = Fr::from_bytes(rsk.to_bytes()).expect("signing keys are scalars");
+                let challenge = challenge(&nonce_commitments[i], &rks[i], &message);
+                let coefficient = coefficient(&nonce_commitments, &rks, &message, i);
+                coefficient * (nonce + challenge * rsk)
+            })
+            .fold(Fr::from(0u64), |sum, term| sum + term);
+
+        Self {
+            nonce_commitments,
+            response,
+        }
+    }
+
+    /// Verify that this signature authorizes `message` by every one of `rks`, in order.
+    pub fn verify_all(&self, rks: &[RandomizedVerificationKey], message: &[u8]) -> Result<()> {
+        ensure!(
+            rks.len() == self.nonce_commitments.len(),
+            "aggregate signature is for {} spends, but {} keys were given",
+            self.nonce_commitments.len(),
+            rks.len()
+        );
+
+        let message = context_message::<SpendAuthorization>(message);
+        let rk_bytes = rks.iter().map(|rk| rk.to_bytes()).collect::<Vec<_>>();
+
+        let mut expected = Element::default();
+        for (i, (commitment, rk)) in self.nonce_commitments.iter().zip(rks).enumerate() {
+            let nonce_commitment = Encoding(*commitment)
+                .vartime_decompress()
+                .map_err(|_| anyhow::anyhow!("nonce commitment is not a valid element"))?;
+            let challenge = challenge(commitment, &rk_bytes[i], &message);
+            let coefficient = coefficient(&self.nonce_commitments, &rk_bytes, &message, i);
+            expected = expected + coefficient * (nonce_commitment + challenge * rk.element());
+        }
+
+        ensure!(
+            self.response * decaf377::basepoint() == expected,
+            "invalid aggregate spend authorization signature"
+        );
+        Ok(())
+    }
+
+    /// The number of spends this signature authorizes.
+    pub fn len(&self) -> usize {
+        self.nonce_commitments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nonce_commitments.is_empty()
+    }
+
+    /// Encode the signature as the response followed by each nonce commitment.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 * (self.nonce_commitments.len() + 1));
+        bytes.extend_from_slice(&self.response.to_bytes());
+        for commitment in &self.nonce_commitments {
+            bytes.extend_from_slice(commitment);
+        }
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for AggregateAuthSignature {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        ensure!(
+            !bytes.is_empty() && bytes.len() % 32 == 0,
+            "aggregate signature must be a non-empty multiple of 32 bytes"
+        );
+        let mut chunks = bytes
+            .chunks_exact(32)
+            .map(|chunk| <[u8; 32]>::try_from(chunk).expect("chunks are 32 bytes"));
+        let response = Fr::from_bytes(chunks.next().expect("bytes are non-empty"))?;
+        Ok(Self {
+            nonce_commitments: chunks.collect(),
+            response,
+        })
+    }
+}
+
+/// The Schnorr challenge for a single spend's signature.
+fn challenge(nonce_commitment: &[u8; 32], rk: &[u8; 32], message: &[u8; 64]) -> Fr {
+    let mut input = nonce_commitment.to_vec();
+    input.extend_from_slice(rk);
+    input.extend_from_slice(message);
+    prf::expand_ff(b"Penumbra_AgSpChl", &[], &input)
+}
+
+/// The coefficient of the `index`th spend's response in the aggregate, which binds it to every
+/// other spend, so that the responses can't be rearranged between them.
+fn coefficient(
+    nonce_commitments: &[[u8; 32]],
+    rks: &[[u8; 32]],
+    message: &[u8; 64],
+    index: usize,
+) -> Fr {
+    let mut input = Vec::with_capacity(64 * nonce_commitments.len() + 72);
+    for (commitment, rk) in nonce_commitments.iter().zip(rks) {
+        input.extend_from_slice(commitment);
+        input.extend_from_slice(rk);
+    }
+    input.extend_from_slice(message);
+    input.extend_from_slice(&(index as u64).to_le_bytes());
+    prf::expand_ff(b"Penumbra_AgSpCof", &[], &input)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::keys::{SeedPhrase, SpendAuthRandomizer, SpendKey};
+
+    #[test]
+    fn aggregate_signature_verifies_only_for_its_keys_and_message() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let randomizers = (0..4)
+            .map(|_| SpendAuthRandomizer::generate(&mut OsRng))
+            .collect::<Vec<_>>();
+        let rsks = randomizers
+            .iter()
+            .map(|r| r.randomize_signing_key(sk.spend_auth_key()))
+            .collect::<Vec<_>>();
+        let rks = randomizers
+            .iter()
+            .map(|r| {
+                r.randomize_verification_key(&sk.spend_auth_key().into())
+                    .try_into()
+                    .unwrap()
+            })
+            .collect::<Vec<RandomizedVerificationKey>>();
+        let message = b"effect hash";
+
+        let signature = AggregateAuthSignature::sign_all(OsRng, &rsks, message);
+        assert_eq!(signature.to_bytes().len(), 32 * 5);
+        signature.verify_all(&rks, message).unwrap();
+
+        let decoded = AggregateAuthSignature::try_from(&signature.to_bytes()[..]).unwrap();
+        assert_eq!(decoded, signature);
+
+        assert!(signature.verify_all(&rks, b"another message").is_err());
+        assert!(signature.verify_all(&rks[..3], message).is_err());
+        let mut swapped = rks.clone();
+        swapped.swap(0, 1);
+        assert!(signature.verify_all(&swapped, message).is_err());
+
+        // An aggregate of a single spend only verifies for that spend's key.
+        let single = AggregateAuthSignature::sign_all(OsRng, &rsks[..1], message);
+        single.verify_all(&rks[..1], message).unwrap();
+        assert!(single.verify_all(&rks[1..2], message).is_err());
+    }
+}
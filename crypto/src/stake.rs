@@ -1,9 +1,11 @@
 mod delegation_token;
+mod exchange_rate;
 mod identity_key;
 mod penalty;
 mod unbonding_token;
 
 pub use delegation_token::DelegationToken;
+pub use exchange_rate::ExchangeRate;
 pub use identity_key::IdentityKey;
 pub use penalty::Penalty;
 pub use unbonding_token::UnbondingToken;
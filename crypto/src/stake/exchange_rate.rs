@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::Penalty;
+use crate::Amount;
+
+/// The rate at which a validator's delegation tokens can be exchanged for the
+/// staking token.
+///
+/// The rate is represented as a fixed-point integer in bps^2 (denominator
+/// 10^8), like [`Penalty`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExchangeRate(pub u64);
+
+impl Default for ExchangeRate {
+    fn default() -> Self {
+        ExchangeRate::ONE
+    }
+}
+
+impl ExchangeRate {
+    /// The exchange rate of a newly created validator, 1.
+    pub const ONE: ExchangeRate = ExchangeRate(1_0000_0000);
+
+    /// Computes the amount of delegation tokens corresponding to the given
+    /// amount of unbonded stake.
+    ///
+    /// This rounds down, so in general converting back with
+    /// [`ExchangeRate::unbonded_amount`] does not give the original amount.
+    ///
+    /// Returns `None` if the rate is zero, as it is for a validator slashed by
+    /// a full penalty, since its delegation tokens are then worth nothing, or if
+    /// the amount is too large to convert.
+    pub fn delegation_amount(&self, unbonded_amount: Amount) -> Option<Amount> {
+        u128::from(unbonded_amount)
+            .checked_mul(1_0000_0000)?
+            .checked_div(self.0 as u128)
+            .map(Amount::from)
+    }
+
+    /// Computes the amount of unbonded stake corresponding to the given amount
+    /// of delegation tokens.
+    ///
+    /// This rounds down, so in general converting back with
+    /// [`ExchangeRate::delegation_amount`] does not give the original amount.
+    ///
+    /// Returns `None` if the amount is too large to convert.
+    pub fn unbonded_amount(&self, delegation_amount: Amount) -> Option<Amount> {
+        u128::from(delegation_amount)
+            .checked_mul(self.0 as u128)
+            .map(|product| Amount::from(product / 1_0000_0000))
+    }
+
+    /// Apply a slashing `penalty` to this exchange rate.
+    pub fn slash(&self, penalty: Penalty) -> ExchangeRate {
+        // (1 - penalty) * exchange_rate, with the penalty in bps^2
+        let slashed = (self.0 as u128 * penalty.0 as u128) / 1_0000_0000;
+        ExchangeRate(
+            self.0
+                .saturating_sub(u64::try_from(slashed).unwrap_or(u64::MAX)),
+        )
+    }
+}
+
+impl std::fmt::Display for ExchangeRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exchange_rate_conversions() {
+        let rate = ExchangeRate(1_5000_0000);
+        assert_eq!(rate.delegation_amount(300u64.into()), Some(200u64.into()));
+        assert_eq!(rate.unbonded_amount(200u64.into()), Some(300u64.into()));
+
+        // A 10% penalty is 1000 bps, i.e. 1000_0000 bps^2.
+        assert_eq!(rate.slash(Penalty(1000_0000)), ExchangeRate(1_3500_0000));
+        assert_eq!(
+            ExchangeRate::ONE.slash(Penalty(1_0000_0000)),
+            ExchangeRate(0)
+        );
+    }
+
+    #[test]
+    fn exchange_rate_conversions_are_checked() {
+        // Nothing can be delegated to a validator slashed down to a zero rate.
+        let slashed = ExchangeRate::ONE.slash(Penalty(1_0000_0000));
+        assert_eq!(slashed.delegation_amount(300u64.into()), None);
+        assert_eq!(slashed.unbonded_amount(300u64.into()), Some(0u64.into()));
+
+        let huge = Amount::from(u128::MAX / 2);
+        assert_eq!(ExchangeRate::ONE.delegation_amount(huge), None);
+        assert_eq!(ExchangeRate::ONE.unbonded_amount(huge), None);
+    }
+}
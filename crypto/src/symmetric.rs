@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
 use chacha20poly1305::{
-    aead::{Aead, NewAead},
+    aead::{Aead, NewAead, Payload},
     ChaCha20Poly1305, Key, Nonce,
 };
 use rand::{CryptoRng, RngCore};
@@ -8,7 +8,7 @@ use rand::{CryptoRng, RngCore};
 use crate::{
     balance, ka,
     keys::{IncomingViewingKey, OutgoingViewingKey},
-    note,
+    note, prf, FieldExt, FullViewingKey,
 };
 
 pub const PAYLOAD_KEY_LEN_BYTES: usize = 32;
@@ -57,6 +57,21 @@ impl PayloadKey {
         Self(*Key::from_slice(key.as_bytes()))
     }
 
+    /// Use Blake2b-256 to derive a `PayloadKey` for version 1 note ciphertexts.
+    ///
+    /// This is domain-separated from [`PayloadKey::derive`], so that the same
+    /// shared secret never yields the same key for two ciphertext versions.
+    pub fn derive_v1(shared_secret: &ka::SharedSecret, epk: &ka::Public) -> Self {
+        let key = blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(b"Penumbra_PayKey1")
+            .to_state()
+            .update(&shared_secret.0)
+            .update(&epk.0)
+            .finalize();
+        Self(*Key::from_slice(key.as_bytes()))
+    }
+
     /// Derive a random `PayloadKey`. Used for memo key wrapping.
     pub fn random_key<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
         let mut key_bytes = [0u8; 32];
@@ -89,6 +104,52 @@ impl PayloadKey {
             .decrypt(nonce, ciphertext.as_ref())
             .map_err(|_| anyhow::anyhow!("decryption error"))
     }
+
+    /// Encrypt a payload using the `PayloadKey`, authenticating `associated_data`
+    /// along with it.
+    pub fn encrypt_with_associated_data(
+        &self,
+        plaintext: &[u8],
+        kind: PayloadKind,
+        associated_data: &[u8],
+    ) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        let nonce_bytes = kind.nonce();
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        cipher
+            .encrypt(
+                nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: associated_data,
+                },
+            )
+            .expect("encryption succeeded")
+    }
+
+    /// Decrypt a payload using the `PayloadKey`, checking that it was encrypted
+    /// with the same `associated_data`.
+    pub fn decrypt_with_associated_data(
+        &self,
+        ciphertext: &[u8],
+        kind: PayloadKind,
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>> {
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        let nonce_bytes = kind.nonce();
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        cipher
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("decryption error"))
+    }
 }
 
 impl TryFrom<&[u8]> for PayloadKey {
@@ -289,3 +350,70 @@ impl TryFrom<&[u8]> for WrappedMemoKey {
         Ok(Self(bytes))
     }
 }
+
+/// Represents a symmetric `ChaCha20Poly1305` key used to encrypt local wallet
+/// data, such as address labels, under a [`FullViewingKey`].
+///
+/// Encryption with this key is _deterministic_: the nonce is derived from the
+/// key and the plaintext, so the same plaintext always encrypts to the same
+/// ciphertext. This lets encrypted entries be looked up by their plaintext
+/// without decrypting everything, at the cost of revealing which entries are
+/// equal.
+#[derive(Clone)]
+pub struct LabelKey(Key);
+
+/// The length of the synthetic nonce prepended to [`LabelKey`] ciphertexts.
+const LABEL_NONCE_LEN_BYTES: usize = 12;
+
+impl LabelKey {
+    /// Derive the label key for the given full viewing key.
+    pub fn derive(fvk: &FullViewingKey) -> Self {
+        let hash_result = prf::expand(
+            b"Penumbra_LabelKy",
+            &fvk.outgoing().0,
+            &fvk.nullifier_key().0.to_bytes(),
+        );
+        Self(*Key::from_slice(&hash_result.as_bytes()[0..32]))
+    }
+
+    fn synthetic_nonce(&self, plaintext: &[u8]) -> [u8; LABEL_NONCE_LEN_BYTES] {
+        let hash_result = prf::expand(b"Penumbra_LabelNc", self.0.as_slice(), plaintext);
+        let mut nonce = [0u8; LABEL_NONCE_LEN_BYTES];
+        nonce.copy_from_slice(&hash_result.as_bytes()[0..LABEL_NONCE_LEN_BYTES]);
+        nonce
+    }
+
+    /// Deterministically encrypt the given plaintext.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        let nonce_bytes = self.synthetic_nonce(plaintext);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let mut ciphertext = nonce_bytes.to_vec();
+        ciphertext.extend(
+            cipher
+                .encrypt(nonce, plaintext)
+                .expect("encryption succeeded"),
+        );
+        ciphertext
+    }
+
+    /// Decrypt a ciphertext produced by [`LabelKey::encrypt`].
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if ciphertext.len() < LABEL_NONCE_LEN_BYTES {
+            return Err(anyhow!("label ciphertext too short"));
+        }
+        let (nonce_bytes, ciphertext) = ciphertext.split_at(LABEL_NONCE_LEN_BYTES);
+
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+            .map_err(|_| anyhow!("decryption error"))?;
+
+        // The nonce is synthetic, so check it was derived from this plaintext.
+        if self.synthetic_nonce(&plaintext) != nonce_bytes {
+            return Err(anyhow!("decryption error"));
+        }
+        Ok(plaintext)
+    }
+}
@@ -0,0 +1,388 @@
+//! Canonical test vectors, for checking other implementations of Penumbra's cryptography, such as
+//! mobile wallets or auditing tools, against this one.
+//!
+//! [`TestVectors::generate`] derives a set of vectors from a seed using exactly the code that
+//! nodes and wallets run, and [`TestVectors::check`] recomputes every output of a set of vectors
+//! from its inputs. The vectors serialize to JSON, with every 32-byte value in its canonical
+//! encoding as hex and every amount as a decimal string; run the `test-vectors` binary of this
+//! crate to print them.
+
+use anyhow::ensure;
+use ark_ff::UniformRand;
+use decaf377::{Element, Encoding, FieldExt, Fq, Fr};
+use penumbra_tct::{self as tct, structure::Hash};
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as, DisplayFromStr};
+
+use crate::{
+    asset,
+    keys::{NullifierKey, RandomizedVerificationKey, SeedPhrase, SpendAuthRandomizer, SpendKey},
+    proofs::groth16::{OutputPublicInputs, PublicInputs, SpendPublicInputs},
+    rdsa::{SpendAuth, VerificationKey},
+    Address, Note, Nullifier, Rseed, Value,
+};
+
+/// A complete set of test vectors.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// The seed the vectors were generated from.
+    #[serde_as(as = "Hex")]
+    pub seed: [u8; 32],
+    pub note_commitments: Vec<NoteCommitmentVector>,
+    pub nullifiers: Vec<NullifierVector>,
+    pub balance_commitments: Vec<BalanceCommitmentVector>,
+    pub merkle_paths: Vec<MerklePathVector>,
+    pub spend_public_inputs: Vec<SpendPublicInputsVector>,
+    pub output_public_inputs: Vec<OutputPublicInputsVector>,
+}
+
+/// The commitment to a note.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteCommitmentVector {
+    /// The recipient of the note, in its bech32m encoding.
+    pub address: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub amount: u64,
+    #[serde_as(as = "Hex")]
+    pub asset_id: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub rseed: [u8; 32],
+    /// The blinding factor derived from the `rseed`.
+    #[serde_as(as = "Hex")]
+    pub note_blinding: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub note_commitment: [u8; 32],
+}
+
+/// The nullifier of a note at a position in the note commitment tree.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NullifierVector {
+    #[serde_as(as = "Hex")]
+    pub nullifier_key: [u8; 32],
+    pub position: u64,
+    #[serde_as(as = "Hex")]
+    pub note_commitment: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub nullifier: [u8; 32],
+}
+
+/// The commitment to a value with a blinding factor.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceCommitmentVector {
+    #[serde_as(as = "DisplayFromStr")]
+    pub amount: u64,
+    #[serde_as(as = "Hex")]
+    pub asset_id: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub blinding: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub balance_commitment: [u8; 32],
+}
+
+/// The authentication path of a note commitment in the note commitment tree.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerklePathVector {
+    #[serde_as(as = "Hex")]
+    pub note_commitment: [u8; 32],
+    pub position: u64,
+    /// The three siblings of the path at each of the 24 levels of the tree, from the root down to
+    /// the leaf.
+    #[serde_as(as = "Vec<[Hex; 3]>")]
+    pub auth_path: Vec<[[u8; 32]; 3]>,
+    #[serde_as(as = "Hex")]
+    pub root: [u8; 32],
+}
+
+/// The public inputs of a spend proof, and their encoding as field elements.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendPublicInputsVector {
+    #[serde_as(as = "Hex")]
+    pub anchor: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub balance_commitment: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub nullifier: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub rk: [u8; 32],
+    /// The field elements the verifier checks the proof against, in order.
+    #[serde_as(as = "Vec<Hex>")]
+    pub field_elements: Vec<[u8; 32]>,
+}
+
+/// The public inputs of an output proof, and their encoding as field elements.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputPublicInputsVector {
+    #[serde_as(as = "Hex")]
+    pub balance_commitment: [u8; 32],
+    #[serde_as(as = "Hex")]
+    pub note_commitment: [u8; 32],
+    /// The field elements the verifier checks the proof against, in order.
+    #[serde_as(as = "Vec<Hex>")]
+    pub field_elements: Vec<[u8; 32]>,
+}
+
+impl TestVectors {
+    /// Generate vectors for `count` notes from `seed`.
+    ///
+    /// The notes are sent to addresses of one spend key, with random amounts and asset IDs, and
+    /// inserted into a note commitment tree in blocks of three, so the Merkle paths cross block
+    /// boundaries.
+    pub fn generate(seed: [u8; 32], count: u32) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness(rng.gen()), 0);
+        let fvk = sk.full_viewing_key();
+        let nk = *sk.nullifier_key();
+        let ak: VerificationKey<SpendAuth> = sk.spend_auth_key().into();
+
+        let mut notes = Vec::new();
+        let mut nct = tct::Tree::new();
+        for index in 0..count {
+            let (address, _dtk) = fvk.payment_address(index.into());
+            let value = Value {
+                amount: rng.gen::<u64>().into(),
+                asset_id: asset::Id(Fq::rand(&mut rng)),
+            };
+            let note = Note::from_parts(address, value, Rseed(rng.gen()))
+                .expect("can make a note with a valid address");
+            nct.insert(tct::Witness::Keep, note.commit())
+                .expect("tree is not full");
+            if index % 3 == 2 {
+                nct.end_block().expect("tree is not full");
+            }
+            notes.push(note);
+        }
+        let anchor = nct.root();
+
+        let mut vectors = Self {
+            seed,
+            note_commitments: Vec::new(),
+            nullifiers: Vec::new(),
+            balance_commitments: Vec::new(),
+            merkle_paths: Vec::new(),
+            spend_public_inputs: Vec::new(),
+            output_public_inputs: Vec::new(),
+        };
+        for note in notes {
+            let note_commitment = note.commit();
+            let proof = nct
+                .witness(note_commitment)
+                .expect("every note is witnessed");
+            let position = proof.position();
+            let nullifier = nk.derive_nullifier(position, &note_commitment);
+            let blinding = Fr::rand(&mut rng);
+            let balance_commitment = note.value().commit(blinding);
+            let rk = RandomizedVerificationKey::try_from(
+                SpendAuthRandomizer::derive(&rng.gen()).randomize_verification_key(&ak),
+            )
+            .expect("randomized key is a valid element");
+
+            vectors.note_commitments.push(NoteCommitmentVector {
+                address: note.address().to_string(),
+                amount: note.amount().into(),
+                asset_id: note.asset_id().0.to_bytes(),
+                rseed: note.rseed().0,
+                note_blinding: note.note_blinding().to_bytes(),
+                note_commitment: note_commitment.0.to_bytes(),
+            });
+            vectors.nullifiers.push(NullifierVector {
+                nullifier_key: nk.0.to_bytes(),
+                position: position.into(),
+                note_commitment: note_commitment.0.to_bytes(),
+                nullifier: nullifier.to_bytes(),
+            });
+            vectors.balance_commitments.push(BalanceCommitmentVector {
+                amount: note.amount().into(),
+                asset_id: note.asset_id().0.to_bytes(),
+                blinding: blinding.to_bytes(),
+                balance_commitment: balance_commitment.to_bytes(),
+            });
+            vectors.merkle_paths.push(MerklePathVector {
+                note_commitment: note_commitment.0.to_bytes(),
+                position: position.into(),
+                auth_path: proof
+                    .auth_path()
+                    .iter()
+                    .map(|siblings| (**siblings).map(Hash::to_bytes))
+                    .collect(),
+                root: Fq::from(anchor).to_bytes(),
+            });
+
+            let spend = SpendPublicInputs {
+                anchor,
+                balance_commitment,
+                nullifier,
+                rk: rk.element(),
+            };
+            vectors.spend_public_inputs.push(SpendPublicInputsVector {
+                anchor: Fq::from(anchor).to_bytes(),
+                balance_commitment: balance_commitment.to_bytes(),
+                nullifier: nullifier.to_bytes(),
+                rk: rk.to_bytes(),
+                field_elements: encode_public_inputs(&spend),
+            });
+            let output = OutputPublicInputs {
+                balance_commitment,
+                note_commitment,
+                memo_key_commitment: None,
+            };
+            vectors.output_public_inputs.push(OutputPublicInputsVector {
+                balance_commitment: balance_commitment.to_bytes(),
+                note_commitment: note_commitment.0.to_bytes(),
+                field_elements: encode_public_inputs(&output),
+            });
+        }
+        vectors
+    }
+
+    /// Recompute every output of these vectors from their inputs, returning an error describing
+    /// the first that doesn't match.
+    pub fn check(&self) -> anyhow::Result<()> {
+        for (i, vector) in self.note_commitments.iter().enumerate() {
+            let address: Address = vector.address.parse()?;
+            let value = Value {
+                amount: vector.amount.into(),
+                asset_id: asset::Id(Fq::from_bytes(vector.asset_id)?),
+            };
+            let note = Note::from_parts(address, value, Rseed(vector.rseed))?;
+            ensure!(
+                note.note_blinding().to_bytes() == vector.note_blinding,
+                "note blinding {} does not match",
+                i
+            );
+            ensure!(
+                note.commit().0.to_bytes() == vector.note_commitment,
+                "note commitment {} does not match",
+                i
+            );
+        }
+
+        for (i, vector) in self.nullifiers.iter().enumerate() {
+            let nk = NullifierKey(Fq::from_bytes(vector.nullifier_key)?);
+            let note_commitment = tct::Commitment(Fq::from_bytes(vector.note_commitment)?);
+            let nullifier = nk.derive_nullifier(vector.position.into(), &note_commitment);
+            ensure!(
+                nullifier.to_bytes() == vector.nullifier,
+                "nullifier {} does not match",
+                i
+            );
+        }
+
+        for (i, vector) in self.balance_commitments.iter().enumerate() {
+            let value = Value {
+                amount: vector.amount.into(),
+                asset_id: asset::Id(Fq::from_bytes(vector.asset_id)?),
+            };
+            let balance_commitment = value.commit(Fr::from_bytes(vector.blinding)?);
+            ensure!(
+                balance_commitment.to_bytes() == vector.balance_commitment,
+                "balance commitment {} does not match",
+                i
+            );
+        }
+
+        for (i, vector) in self.merkle_paths.iter().enumerate() {
+            ensure!(
+                vector.auth_path.len() == 24,
+                "merkle path {} does not have 24 levels",
+                i
+            );
+            let mut auth_path = [[Hash::zero(); 3]; 24];
+            for (level, siblings) in auth_path.iter_mut().zip(&vector.auth_path) {
+                for (sibling, bytes) in level.iter_mut().zip(siblings) {
+                    *sibling = Hash::from_bytes(*bytes)?;
+                }
+            }
+            let proof = tct::Proof::new(
+                tct::Commitment(Fq::from_bytes(vector.note_commitment)?),
+                vector.position.into(),
+                auth_path,
+            );
+            let root = tct::Root(Hash::from_bytes(vector.root)?);
+            ensure!(
+                proof.verify(root).is_ok(),
+                "merkle path {} does not lead to its root",
+                i
+            );
+        }
+
+        for (i, vector) in self.spend_public_inputs.iter().enumerate() {
+            let spend = SpendPublicInputs {
+                anchor: tct::Root(Hash::from_bytes(vector.anchor)?),
+                balance_commitment: vector.balance_commitment.try_into()?,
+                nullifier: Nullifier(Fq::from_bytes(vector.nullifier)?),
+                rk: decode_element(vector.rk)?,
+            };
+            ensure!(
+                encode_public_inputs(&spend) == vector.field_elements,
+                "spend public inputs {} do not match",
+                i
+            );
+        }
+
+        for (i, vector) in self.output_public_inputs.iter().enumerate() {
+            let output = OutputPublicInputs {
+                balance_commitment: vector.balance_commitment.try_into()?,
+                note_commitment: tct::Commitment(Fq::from_bytes(vector.note_commitment)?),
+                memo_key_commitment: None,
+            };
+            ensure!(
+                encode_public_inputs(&output) == vector.field_elements,
+                "output public inputs {} do not match",
+                i
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_public_inputs(inputs: &impl PublicInputs) -> Vec<[u8; 32]> {
+    inputs
+        .to_field_elements()
+        .expect("public inputs of valid values can be encoded")
+        .iter()
+        .map(FieldExt::to_bytes)
+        .collect()
+}
+
+fn decode_element(bytes: [u8; 32]) -> anyhow::Result<Element> {
+    Encoding(bytes)
+        .vartime_decompress()
+        .map_err(|_| anyhow::anyhow!("invalid element encoding"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors_round_trip_and_check() {
+        let vectors = TestVectors::generate([5u8; 32], 7);
+        assert_eq!(vectors.note_commitments.len(), 7);
+        assert_eq!(vectors, TestVectors::generate([5u8; 32], 7));
+        vectors.check().unwrap();
+
+        let json = serde_json::to_string_pretty(&vectors).unwrap();
+        let decoded: TestVectors = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, vectors);
+        decoded.check().unwrap();
+
+        let mut tampered = vectors.clone();
+        tampered.merkle_paths[4].auth_path[23][1] = tampered.merkle_paths[4].auth_path[23][0];
+        assert!(tampered.check().is_err());
+
+        let mut tampered = vectors;
+        tampered.balance_commitments[0].amount += 1;
+        assert!(tampered.check().is_err());
+    }
+}
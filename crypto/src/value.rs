@@ -51,8 +51,20 @@ impl Value {
     ///
     /// Returns the amount in terms of the asset ID if the denomination is not known.
     pub fn format(&self, cache: &asset::Cache) -> String {
-        cache
-            .get(&self.asset_id)
+        self.format_with_denom(cache.get(&self.asset_id))
+    }
+
+    /// Use the assets added to the provided [`asset::Registry`] to format this
+    /// value in its best unit.
+    ///
+    /// Returns the amount in terms of the asset ID if the denomination was not
+    /// registered.
+    pub fn format_with_registry(&self, registry: &asset::Registry) -> String {
+        self.format_with_denom(registry.denom_by_id(&self.asset_id).as_ref())
+    }
+
+    fn format_with_denom(&self, denom: Option<&asset::Denom>) -> String {
+        denom
             .map(|base_denom| {
                 let display_denom = base_denom.best_unit_for(self.amount);
                 format!(
@@ -63,12 +75,12 @@ impl Value {
             })
             .unwrap_or_else(|| format!("{}{}", self.amount, self.asset_id))
     }
-}
-
-impl FromStr for Value {
-    type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    /// Parse a value such as `1.5penumbra`, looking up its unit in the provided
+    /// [`asset::Registry`].
+    ///
+    /// [`Value::from_str`] does the same with the fixed [`asset::REGISTRY`].
+    pub fn parse_with_registry(s: &str, registry: &asset::Registry) -> anyhow::Result<Self> {
         let asset_id_re = Regex::new(r"^([0-9.]+)(passet[0-9].*)$").unwrap();
         let denom_re = Regex::new(r"^([0-9.]+)([^0-9.].*)$").unwrap();
 
@@ -87,7 +99,7 @@ impl FromStr for Value {
             let numeric_str = captures.get(1).expect("matched regex").as_str();
             let denom_str = captures.get(2).expect("matched regex").as_str();
 
-            let display_denom = asset::REGISTRY.parse_unit(denom_str);
+            let display_denom = registry.parse_unit(denom_str);
             let amount = display_denom.parse_value(numeric_str)?;
             let asset_id = display_denom.base().id();
 
@@ -101,6 +113,14 @@ impl FromStr for Value {
     }
 }
 
+impl FromStr for Value {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Value::parse_with_registry(s, &asset::REGISTRY)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use decaf377::Fr;
@@ -244,6 +264,38 @@ mod tests {
         assert!(Value::from_str("nala").is_err());
     }
 
+    #[test]
+    fn parse_with_registry_roundtrips() {
+        let mut registry = asset::Registry::new();
+        let denom = registry.register("uosmo", &[("osmo", 6)]).unwrap();
+
+        let v1 = Value::parse_with_registry("2.5osmo", &registry).unwrap();
+        assert_eq!(v1.amount, 2_500_000u64.into());
+        assert_eq!(v1.asset_id, denom.id());
+        assert_eq!(v1.format_with_registry(&registry), "2.5osmo");
+        assert_eq!(
+            Value::parse_with_registry(&v1.format_with_registry(&registry), &registry).unwrap(),
+            v1
+        );
+
+        let v2 = Value::parse_with_registry("12uosmo", &registry).unwrap();
+        assert_eq!(v2.format_with_registry(&registry), "12uosmo");
+
+        // Without the registration, `osmo` is its own base denomination.
+        let v3: Value = "2osmo".parse().unwrap();
+        assert_ne!(v3.asset_id, denom.id());
+        assert_eq!(
+            v3.format_with_registry(&registry),
+            format!("2{}", v3.asset_id)
+        );
+
+        // The registry still knows the built-in assets.
+        assert_eq!(
+            Value::parse_with_registry("1penumbra", &registry).unwrap(),
+            "1penumbra".parse().unwrap()
+        );
+    }
+
     #[test]
     fn format_picks_best_unit() {
         let upenumbra_base_denom = asset::REGISTRY.parse_denom("upenumbra").unwrap();
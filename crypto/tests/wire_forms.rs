@@ -0,0 +1,145 @@
+//! Checks that the primitives crossing this crate's boundary each have exactly one wire form.
+//!
+//! Every value must round-trip through its protobuf encoding, anything of the wrong length or not
+//! encoding a valid value must be rejected, and anything that is accepted must re-encode to exactly
+//! the bytes it was decoded from, so that no two encodings decode to the same value.
+
+use penumbra_crypto::{
+    asset, balance,
+    keys::{AddressIndex, SeedPhrase, SpendKey},
+    note, Address, Fq, Fr, Nullifier, Value,
+};
+use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
+use penumbra_tct as tct;
+use proptest::prelude::*;
+
+/// Check that a value round-trips through its protobuf message and its encoded bytes.
+macro_rules! round_trip {
+    ($value:expr, $ty:ty, $proto:ty) => {{
+        let value: $ty = $value;
+        let proto: $proto = value.to_proto();
+        assert_eq!(<$ty>::try_from(proto).unwrap(), value);
+        assert_eq!(<$ty>::decode(&*value.encode_to_vec()).unwrap(), value);
+    }};
+}
+
+/// Check that `inner` is rejected if it's not valid, and otherwise decodes to a value which
+/// re-encodes to exactly the same bytes.
+fn canonical<T, P>(inner: Vec<u8>, wrap: impl Fn(Vec<u8>) -> P, unwrap: impl Fn(P) -> Vec<u8>)
+where
+    T: TryFrom<P> + Into<P>,
+{
+    if let Ok(value) = T::try_from(wrap(inner.clone())) {
+        assert_eq!(unwrap(value.into()), inner);
+    }
+}
+
+fn address(index: u32) -> Address {
+    let sk = SpendKey::from_seed_phrase(SeedPhrase::from_randomness([7u8; 32]), 0);
+    sk.full_viewing_key()
+        .incoming()
+        .payment_address(AddressIndex::from(index))
+        .0
+}
+
+fn balance_commitment(amount: u64, blinding: u64) -> balance::Commitment {
+    let value = Value {
+        amount: amount.into(),
+        asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+    };
+    value.commit(Fr::from(blinding))
+}
+
+fn root(commitments: u64) -> tct::Root {
+    let mut tree = tct::Tree::new();
+    for i in 0..commitments {
+        tree.insert(tct::Witness::Forget, tct::Commitment(Fq::from(i)))
+            .unwrap();
+    }
+    tree.root()
+}
+
+/// Byte strings that are never valid encodings of a 32-byte primitive.
+fn invalid_lengths() -> Vec<Vec<u8>> {
+    vec![vec![], vec![0; 31], vec![0; 33], vec![0; 64]]
+}
+
+#[test]
+fn wrong_lengths_are_rejected() {
+    for inner in invalid_lengths() {
+        assert!(Nullifier::try_from(pb::Nullifier {
+            inner: inner.clone()
+        })
+        .is_err());
+        assert!(balance::Commitment::try_from(pb::BalanceCommitment {
+            inner: inner.clone()
+        })
+        .is_err());
+        assert!(note::Commitment::try_from(pb::StateCommitment {
+            inner: inner.clone()
+        })
+        .is_err());
+        assert!(tct::Root::try_from(pb::MerkleRoot {
+            inner: inner.clone()
+        })
+        .is_err());
+        assert!(Address::try_from(pb::Address { inner }).is_err());
+    }
+    let mut long_address = address(0).to_vec();
+    long_address.push(0);
+    assert!(Address::try_from(pb::Address {
+        inner: long_address
+    })
+    .is_err());
+}
+
+#[test]
+fn out_of_range_field_elements_are_rejected() {
+    // All ones is larger than the modulus of the field
+    let inner = vec![0xff; 32];
+    assert!(Nullifier::try_from(pb::Nullifier {
+        inner: inner.clone()
+    })
+    .is_err());
+    assert!(note::Commitment::try_from(pb::StateCommitment {
+        inner: inner.clone()
+    })
+    .is_err());
+    assert!(tct::Root::try_from(pb::MerkleRoot {
+        inner: inner.clone()
+    })
+    .is_err());
+    assert!(balance::Commitment::try_from(pb::BalanceCommitment { inner }).is_err());
+}
+
+proptest! {
+    #[test]
+    fn primitives_round_trip(n in any::<u64>(), blinding in any::<u64>(), index in 0u32..1024) {
+        round_trip!(Nullifier(Fq::from(n)), Nullifier, pb::Nullifier);
+        round_trip!(balance_commitment(n, blinding), balance::Commitment, pb::BalanceCommitment);
+        round_trip!(note::Commitment(Fq::from(n)), note::Commitment, pb::StateCommitment);
+        round_trip!(address(index), Address, pb::Address);
+        round_trip!(root(n % 16), tct::Root, pb::MerkleRoot);
+    }
+
+    #[test]
+    fn accepted_encodings_are_canonical(inner in prop::collection::vec(any::<u8>(), 32)) {
+        canonical::<Nullifier, _>(inner.clone(), |inner| pb::Nullifier { inner }, |p| p.inner);
+        canonical::<balance::Commitment, _>(
+            inner.clone(),
+            |inner| pb::BalanceCommitment { inner },
+            |p| p.inner,
+        );
+        canonical::<note::Commitment, _>(
+            inner.clone(),
+            |inner| pb::StateCommitment { inner },
+            |p| p.inner,
+        );
+        canonical::<tct::Root, _>(inner, |inner| pb::MerkleRoot { inner }, |p| p.inner);
+    }
+
+    #[test]
+    fn accepted_addresses_are_canonical(inner in prop::collection::vec(any::<u8>(), 80)) {
+        canonical::<Address, _>(inner, |inner| pb::Address { inner }, |p| p.inner);
+    }
+}
@@ -77,8 +77,19 @@ impl DetectionKey {
     ///
     /// This function executes in constant time with respect to the detection
     /// key material, but short-circuits to return early on a false detection.
-    #[allow(non_snake_case)]
     pub fn examine(&self, clue: &Clue) -> bool {
+        self.examine_with_max_precision(clue, MAX_PRECISION)
+    }
+
+    /// Like [`examine`](DetectionKey::examine), but only checks at most
+    /// `max_precision` bits of the clue.
+    ///
+    /// This bounds the false positive rate of the test below by
+    /// `2^-max_precision`, regardless of the precision the clue was created
+    /// with, which lets a detection server limit how much it learns about the
+    /// messages sent to the key.
+    #[allow(non_snake_case)]
+    pub fn examine_with_max_precision(&self, clue: &Clue, max_precision: usize) -> bool {
         let P_encoding = decaf377::Encoding::try_from(&clue.0[0..32]).expect("slice is right len");
 
         let P = if let Ok(P) = P_encoding.vartime_decompress() {
@@ -109,7 +120,7 @@ impl DetectionKey {
         let m = hash::to_scalar(&P_encoding.0, precision_bits, &clue.0[65..68]);
         let Q_bytes = ((y * P) + (m * decaf377::basepoint())).vartime_compress();
 
-        for i in 0..(precision_bits as usize) {
+        for i in 0..(precision_bits as usize).min(max_precision) {
             let Px_i = (P * self.xs[i]).vartime_compress();
             let key_i = hash::to_bit(&P_encoding.0, &Px_i.0, &Q_bytes.0);
             let msg_i = (ciphertexts[i] as u8) ^ key_i;
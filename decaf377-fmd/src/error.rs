@@ -15,4 +15,10 @@ pub enum Error {
     /// A clue key encoding was invalid.
     #[error("Invalid clue key.")]
     InvalidClueKey,
+    /// A detection request was made for a client that isn't registered.
+    #[error("Unknown detection client.")]
+    UnknownClient,
+    /// A detection request would exceed the client's rate limit.
+    #[error("Rate limit exceeded: {remaining} clues remaining in the current period.")]
+    RateLimited { remaining: u64 },
 }
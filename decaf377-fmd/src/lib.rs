@@ -8,11 +8,13 @@ mod detection;
 mod error;
 mod hash;
 mod hkd;
+mod server;
 
 pub use clue::Clue;
 pub use clue_key::{ClueKey, ExpandedClueKey};
 pub use detection::DetectionKey;
 pub use error::Error;
+pub use server::{Budget, DetectionServer, Usage};
 
 /// The maximum detection precision, chosen so that the message bits fit in 3 bytes.
 pub const MAX_PRECISION: usize = 24;
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use crate::{Clue, DetectionKey, Error, MAX_PRECISION};
+
+/// The limits placed on a single client of a [`DetectionServer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    /// The maximum number of clue bits checked on behalf of the client.
+    ///
+    /// The server's detections for the client have a false positive rate of
+    /// at least `2^-max_precision`, whatever precision clues were created
+    /// with, which bounds what the server can learn about the client's
+    /// messages.
+    pub max_precision: usize,
+    /// The maximum number of clues examined for the client in each period.
+    pub clues_per_period: u64,
+    /// The length of a rate-limiting period.
+    pub period: Duration,
+}
+
+/// The lifetime totals of a client's use of a [`DetectionServer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The number of batches examined.
+    pub batches: u64,
+    /// The number of clues examined.
+    pub clues: u64,
+    /// The number of clues detected.
+    pub detections: u64,
+    /// The number of batches rejected for exceeding the rate limit.
+    pub rejected: u64,
+}
+
+struct Client {
+    key: DetectionKey,
+    budget: Budget,
+    usage: Usage,
+    period_start: Instant,
+    period_clues: u64,
+}
+
+/// A helper for running shared detection infrastructure.
+///
+/// The server holds the [`DetectionKey`]s of its registered clients and
+/// examines batches of clues on their behalf, returning the payloads whose
+/// clues were detected. Each client has a [`Budget`], which limits both the
+/// precision of detection and the number of clues examined per period, and
+/// the server keeps [`Usage`] accounting for each client.
+pub struct DetectionServer<C> {
+    clients: HashMap<C, Client>,
+}
+
+impl<C: Eq + Hash> Default for DetectionServer<C> {
+    fn default() -> Self {
+        Self {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl<C: Eq + Hash> DetectionServer<C> {
+    /// Create a server with no registered clients.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a client's detection key with the given budget, replacing any
+    /// previous registration for the client.
+    pub fn register(&mut self, client: C, key: DetectionKey, budget: Budget) -> Result<(), Error> {
+        if budget.max_precision > MAX_PRECISION {
+            return Err(Error::PrecisionTooLarge(budget.max_precision));
+        }
+
+        self.clients.insert(
+            client,
+            Client {
+                key,
+                budget,
+                usage: Usage::default(),
+                period_start: Instant::now(),
+                period_clues: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a client, returning whether it was registered.
+    pub fn unregister(&mut self, client: &C) -> bool {
+        self.clients.remove(client).is_some()
+    }
+
+    /// The usage accounting for a client, if it is registered.
+    pub fn usage(&self, client: &C) -> Option<Usage> {
+        self.clients.get(client).map(|client| client.usage)
+    }
+
+    /// Examine a batch of `(clue, payload)` pairs for a client, returning the
+    /// payloads whose clues were detected.
+    ///
+    /// A batch that would take the client over its budget for the current
+    /// period is rejected as a whole.
+    pub fn detect<'a, P>(
+        &mut self,
+        client: &C,
+        batch: &'a [(Clue, P)],
+    ) -> Result<Vec<&'a P>, Error> {
+        self.detect_at(client, batch, Instant::now())
+    }
+
+    /// Like [`detect`](DetectionServer::detect), but with an explicit current
+    /// time.
+    pub fn detect_at<'a, P>(
+        &mut self,
+        client: &C,
+        batch: &'a [(Clue, P)],
+        now: Instant,
+    ) -> Result<Vec<&'a P>, Error> {
+        let client = self.clients.get_mut(client).ok_or(Error::UnknownClient)?;
+
+        if now.saturating_duration_since(client.period_start) >= client.budget.period {
+            client.period_start = now;
+            client.period_clues = 0;
+        }
+
+        let remaining = client
+            .budget
+            .clues_per_period
+            .saturating_sub(client.period_clues);
+        if batch.len() as u64 > remaining {
+            client.usage.rejected += 1;
+            return Err(Error::RateLimited { remaining });
+        }
+
+        let max_precision = client.budget.max_precision;
+        let detected = batch
+            .iter()
+            .filter(|(clue, _)| client.key.examine_with_max_precision(clue, max_precision))
+            .map(|(_, payload)| payload)
+            .collect::<Vec<_>>();
+
+        client.period_clues += batch.len() as u64;
+        client.usage.batches += 1;
+        client.usage.clues += batch.len() as u64;
+        client.usage.detections += detected.len() as u64;
+
+        Ok(detected)
+    }
+}
@@ -50,3 +50,50 @@ fn fails_to_generate_clue() {
         .create_clue(fmd::MAX_PRECISION + 1, OsRng)
         .expect_err("fails to generate clue with excessive precision");
 }
+
+#[test]
+fn detection_server_enforces_budget() {
+    use std::time::{Duration, Instant};
+
+    let alice_dk = fmd::DetectionKey::new(OsRng);
+    let alice_clue_key = alice_dk.clue_key().expand().unwrap();
+    let bobce_clue_key = fmd::DetectionKey::new(OsRng).clue_key().expand().unwrap();
+
+    let budget = fmd::Budget {
+        max_precision: 4,
+        clues_per_period: 8,
+        period: Duration::from_secs(60),
+    };
+    let mut server = fmd::DetectionServer::new();
+    server.register("alice", alice_dk, budget).unwrap();
+
+    // Clues for alice are always detected, but precise clues for bobce are
+    // still detected at the budgeted rate rather than at their own precision.
+    let batch = (0..4)
+        .map(|i| (alice_clue_key.create_clue(20, OsRng).unwrap(), i))
+        .chain((4..8).map(|i| (bobce_clue_key.create_clue(20, OsRng).unwrap(), i)))
+        .collect::<Vec<_>>();
+
+    let now = Instant::now();
+    let detected = server.detect_at(&"alice", &batch, now).unwrap();
+    assert!(detected.starts_with(&[&0, &1, &2, &3]));
+
+    // The period's budget is spent, until the next period starts.
+    assert!(matches!(
+        server.detect_at(&"alice", &batch[..1], now),
+        Err(fmd::Error::RateLimited { remaining: 0 })
+    ));
+    assert!(server
+        .detect_at(&"alice", &batch[..1], now + budget.period)
+        .is_ok());
+
+    let usage = server.usage(&"alice").unwrap();
+    assert_eq!(usage.batches, 2);
+    assert_eq!(usage.clues, 9);
+    assert_eq!(usage.rejected, 1);
+
+    assert!(matches!(
+        server.detect(&"bobce", &batch),
+        Err(fmd::Error::UnknownClient)
+    ));
+}
@@ -77,6 +77,12 @@ impl Secret {
         Ok(SharedSecret((self.0 * pk).vartime_compress().into()))
     }
 
+    /// Precompute the recoding of this secret key, for performing key agreement
+    /// with many public keys.
+    pub fn prepare(&self) -> PreparedSecret {
+        PreparedSecret::new(&self.0)
+    }
+
     /// Convert this shared secret to bytes.
     ///
     /// Convenience wrapper around an [`Into`] impl.
@@ -85,6 +91,128 @@ impl Secret {
     }
 }
 
+/// The width of the signed windows [`PreparedSecret`] recodes a secret key into.
+const WINDOW_WIDTH: usize = 5;
+
+/// A secret key, recoded for fast key agreement with many public keys, as when
+/// scanning for notes sent to an incoming viewing key.
+///
+/// The secret key is fixed while the public keys vary, so the key is recoded
+/// into its width-5 non-adjacent form once, and each key agreement only needs a
+/// small table of odd multiples of the public key. This replaces about half of
+/// the additions of a plain double-and-add with a handful of table entries.
+///
+/// The recoding is the only work shared between key agreements: the table of
+/// multiples depends on the public key, so it is rebuilt for each one, and
+/// there are no fixed-base tables to precompute, since the base is the varying
+/// public key rather than the secret.
+///
+/// Like [`Secret::key_agreement_with`], the multiplication is variable-time.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct PreparedSecret {
+    /// The signed digits of the secret key, least significant first: each is
+    /// zero or odd with absolute value less than `2^(WINDOW_WIDTH - 1)`, and
+    /// any two non-zero digits are at least `WINDOW_WIDTH` positions apart.
+    digits: Vec<i8>,
+}
+
+impl PreparedSecret {
+    fn new(sk: &decaf377::Fr) -> Self {
+        let mut bytes = sk.to_bytes();
+        // One limb of padding, so that a window may read past the top bit.
+        let mut limbs = [0u64; 5];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().expect("chunk has length 8"));
+        }
+        bytes.zeroize();
+
+        let width = 1u64 << WINDOW_WIDTH;
+        let window_mask = width - 1;
+        let mut digits = vec![0i8; 256];
+        let mut position = 0;
+        let mut carry = 0;
+        while position < 256 {
+            let (limb, bit) = (position / 64, position % 64);
+            let bit_buf = if bit < 64 - WINDOW_WIDTH {
+                limbs[limb] >> bit
+            } else {
+                (limbs[limb] >> bit) | (limbs[limb + 1] << (64 - bit))
+            };
+            let window = carry + (bit_buf & window_mask);
+
+            if window & 1 == 0 {
+                // The window starts with a zero bit: move along one bit.
+                position += 1;
+                continue;
+            }
+            if window < width / 2 {
+                carry = 0;
+                digits[position] = window as i8;
+            } else {
+                carry = 1;
+                digits[position] = (window as i8).wrapping_sub(width as i8);
+            }
+            position += WINDOW_WIDTH;
+        }
+        limbs.zeroize();
+        // The secret key is less than 2^253, so the last window never carries.
+        debug_assert_eq!(carry, 0);
+
+        Self { digits }
+    }
+
+    /// Perform key agreement with the provided public key, as in
+    /// [`Secret::key_agreement_with`].
+    pub fn key_agreement_with(&self, other: &Public) -> Result<SharedSecret, Error> {
+        let pk = decaf377::Encoding(other.0)
+            .vartime_decompress()
+            .map_err(|_| Error::InvalidPublic(*other))?;
+
+        Ok(SharedSecret(self.mul(pk).vartime_compress().into()))
+    }
+
+    /// Perform key agreement with each of the provided public keys, returning
+    /// the results in the same order.
+    ///
+    /// This shares the recoding of the secret key between all the public keys,
+    /// but each multiplication is otherwise performed separately, as with
+    /// [`PreparedSecret::key_agreement_with`]. An invalid public key only fails
+    /// its own key agreement.
+    pub fn batch_key_agreement(&self, others: &[Public]) -> Vec<Result<SharedSecret, Error>> {
+        others
+            .iter()
+            .map(|other| self.key_agreement_with(other))
+            .collect()
+    }
+
+    fn mul(&self, point: decaf377::Element) -> decaf377::Element {
+        // The odd multiples `point, 3 * point, ..., 15 * point`.
+        let double = point + point;
+        let mut table = [point; 1 << (WINDOW_WIDTH - 2)];
+        for i in 1..table.len() {
+            table[i] = table[i - 1] + double;
+        }
+
+        let mut result = decaf377::Element::default();
+        for &digit in self.digits.iter().rev() {
+            result = result + result;
+            if digit > 0 {
+                result += table[(digit / 2) as usize];
+            } else if digit < 0 {
+                result += -table[(-digit / 2) as usize];
+            }
+        }
+        result
+    }
+}
+
+impl std::fmt::Debug for PreparedSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("decaf377_ka::PreparedSecret(..)")
+    }
+}
+
 impl std::fmt::Debug for Public {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!(
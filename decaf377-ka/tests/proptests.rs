@@ -60,4 +60,29 @@ proptest! {
         assert_eq!(alice_ss1, bob_ss1);
         assert_eq!(alice_ss2, bob_ss2);
     }
+
+    #[test]
+    fn prepared_key_agreement_matches(
+        alice_sk in fr_strategy(),
+        bob_sks in prop::collection::vec(fr_strategy(), 1..8),
+    ) {
+        let alice_sk = ka::Secret::new_from_field(alice_sk);
+        let prepared = alice_sk.prepare();
+
+        let mut bob_pks = bob_sks
+            .into_iter()
+            .map(|bob_sk| ka::Secret::new_from_field(bob_sk).public())
+            .collect::<Vec<_>>();
+        // An invalid public key only fails its own key agreement.
+        bob_pks.push(ka::Public([0xff; 32]));
+
+        let batch = prepared.batch_key_agreement(&bob_pks);
+        assert_eq!(batch.len(), bob_pks.len());
+        for (bob_pk, ss) in bob_pks.iter().zip(batch) {
+            match alice_sk.key_agreement_with(bob_pk) {
+                Ok(expected) => assert_eq!(ss.unwrap(), expected),
+                Err(_) => assert!(ss.is_err()),
+            }
+        }
+    }
 }
@@ -1,12 +1,10 @@
 use std::{fs::File, io::Write};
 
 use anyhow::{anyhow, Context, Result};
-use ark_ff::UniformRand;
-use decaf377::Fr;
 use penumbra_chain::Epoch;
 use penumbra_component::stake::rate::RateData;
 use penumbra_crypto::{
-    asset,
+    asset, sample,
     stake::{DelegationToken, IdentityKey, Penalty, UnbondingToken},
     transaction::Fee,
     Amount, Value, STAKING_TOKEN_ASSET_ID,
@@ -458,7 +456,7 @@ impl TxCmd {
                                 end_epoch_index,
                                 penalty,
                                 unbonding_amount,
-                                balance_blinding: Fr::rand(&mut OsRng),
+                                balance_blinding: sample::blinding_factor(&mut OsRng),
                             })
                             .fee(fee.clone())
                             .plan(app.view.as_mut().unwrap(), &app.fvk, Some(address_index))
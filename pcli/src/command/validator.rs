@@ -2,7 +2,12 @@ use std::{fs::File, io::Write};
 
 use anyhow::{Context, Result};
 use penumbra_component::stake::{validator, validator::Validator, FundingStream, FundingStreams};
-use penumbra_crypto::{stake::IdentityKey, transaction::Fee, GovernanceKey};
+use penumbra_crypto::{
+    signing::{self, GovernanceVote, ValidatorDefinition},
+    stake::IdentityKey,
+    transaction::Fee,
+    GovernanceKey,
+};
 use penumbra_proto::{core::stake::v1alpha1::Validator as ProtoValidator, Message, Protobuf};
 use penumbra_transaction::action::{ValidatorVote, ValidatorVoteBody, Vote};
 use penumbra_wallet::plan;
@@ -108,7 +113,8 @@ impl ValidatorCmd {
                 // Sign the validator definition with the wallet's spend key.
                 let protobuf_serialized: ProtoValidator = new_validator.clone().into();
                 let v_bytes = protobuf_serialized.encode_to_vec();
-                let auth_sig = sk.spend_auth_key().sign(OsRng, &v_bytes);
+                let auth_sig =
+                    signing::sign::<ValidatorDefinition, _>(OsRng, sk.spend_auth_key(), &v_bytes);
                 let vd = validator::Definition {
                     validator: new_validator,
                     auth_sig,
@@ -136,7 +142,7 @@ impl ValidatorCmd {
                 vote,
             } => {
                 // TODO: support submitting a separate governance key.
-                let identity_key = IdentityKey(*sk.full_viewing_key().spend_verification_key());
+                let identity_key = sk.identity_key();
                 // Currently this is always just copied from the identity key
                 let governance_key = GovernanceKey(identity_key.0);
 
@@ -153,7 +159,8 @@ impl ValidatorCmd {
 
                 // Generate an authorizing signature with the governance key for the vote body
                 let body_bytes = body.encode_to_vec();
-                let auth_sig = governance_auth_key.sign(OsRng, &body_bytes);
+                let auth_sig =
+                    signing::sign::<GovernanceVote, _>(OsRng, governance_auth_key, &body_bytes);
 
                 let vote = ValidatorVote { body, auth_sig };
 
@@ -10,6 +10,10 @@ pub struct AddressCmd {
     /// Generate an ephemeral address instead of an indexed one.
     #[clap(short, long)]
     ephemeral: bool,
+    /// Export, as JSON, how each of this many addresses starting at the index
+    /// is derived, so that a third party can audit the derivation.
+    #[clap(long, value_name = "COUNT", conflicts_with = "ephemeral")]
+    audit: Option<u64>,
 }
 
 impl AddressCmd {
@@ -19,6 +23,16 @@ impl AddressCmd {
     }
 
     pub fn exec(&self, fvk: &FullViewingKey) -> Result<()> {
+        if let Some(count) = self.audit {
+            let start = self.index.unwrap_or(0);
+            let end = start
+                .checked_add(count)
+                .ok_or_else(|| anyhow::anyhow!("address index range overflows"))?;
+            let derivations = fvk.incoming().address_derivations(start..end);
+            println!("{}", serde_json::to_string_pretty(&derivations)?);
+            return Ok(());
+        }
+
         match self.ephemeral {
             false => {
                 let (address, _dtk) = fvk
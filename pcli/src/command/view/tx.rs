@@ -177,7 +177,7 @@ fn format_address(ivk: &IncomingViewingKey, address: &Address) -> String {
             AddressIndex::Random(_) => "[self: ephemeral]".to_string(),
         }
     } else {
-        address.display_short_form()
+        address.short_form()
     }
 }
 
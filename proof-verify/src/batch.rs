@@ -0,0 +1,214 @@
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_groth16::{PreparedVerifyingKey, Proof};
+use ark_relations::r1cs::SynthesisError;
+use decaf377::{Bls12_377, Fq};
+use rand::{CryptoRng, Rng};
+
+use crate::{
+    counts::{self, VerificationCounts},
+    proof::check_pairing,
+};
+
+/// The kind of a proof queued in a [`BatchVerifier`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofKind {
+    Spend,
+    Output,
+}
+
+impl std::fmt::Display for ProofKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProofKind::Spend => f.write_str("spend"),
+            ProofKind::Output => f.write_str("output"),
+        }
+    }
+}
+
+/// The error returned when a batch fails to verify, identifying the first
+/// offending proof.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{kind} proof at index {index} in the batch did not verify")]
+pub struct InvalidProof {
+    /// The position of the proof in the batch, in the order it was queued.
+    pub index: usize,
+    /// The kind of the proof.
+    pub kind: ProofKind,
+}
+
+struct Item {
+    kind: ProofKind,
+    proof: Proof<Bls12_377>,
+    public_inputs: Vec<Fq>,
+}
+
+/// Verifies many spend and output proofs at once.
+///
+/// Each proof `i` is checked by the pairing equation
+/// `e(A_i, B_i) = e(alpha, beta) e(L_i, gamma) e(C_i, delta)`, where `L_i` is
+/// the linear combination of its public inputs. The batch checks a random
+/// linear combination of these equations with a single multi-pairing, sharing
+/// the `gamma`, `delta` and `e(alpha, beta)` terms between all proofs made with
+/// the same verifying key. If the batch check fails, the proofs are checked one
+/// by one to find the invalid one.
+///
+/// Proofs are queued with their public inputs already encoded as field
+/// elements; callers are responsible for checking that the verifying keys
+/// belong to the spend and output circuits.
+pub struct BatchVerifier<'a> {
+    spend_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+    output_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+    items: Vec<Item>,
+}
+
+impl<'a> BatchVerifier<'a> {
+    /// Create an empty batch, to be verified with the given keys.
+    pub fn new(
+        spend_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+        output_pvk: &'a PreparedVerifyingKey<Bls12_377>,
+    ) -> Self {
+        Self {
+            spend_pvk,
+            output_pvk,
+            items: Vec::new(),
+        }
+    }
+
+    /// Queue a proof of the given kind with its encoded public inputs.
+    pub fn queue(&mut self, kind: ProofKind, proof: Proof<Bls12_377>, public_inputs: Vec<Fq>) {
+        self.items.push(Item {
+            kind,
+            proof,
+            public_inputs,
+        });
+    }
+
+    /// The number of queued proofs.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether no proofs are queued.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Verify every queued proof.
+    ///
+    /// If the batch does not verify, each proof is verified individually, and
+    /// the first invalid one is returned.
+    pub fn verify<R: CryptoRng + Rng>(self, rng: &mut R) -> Result<(), InvalidProof> {
+        if self.items.is_empty() || self.verify_batch(rng).unwrap_or(false) {
+            return Ok(());
+        }
+
+        tracing::debug!(
+            proofs = self.items.len(),
+            "batch verification failed, verifying proofs individually"
+        );
+        for (index, item) in self.items.iter().enumerate() {
+            let valid = check_pairing(self.pvk(item.kind), &item.public_inputs, &item.proof)
+                .unwrap_or(false);
+            if !valid {
+                return Err(InvalidProof {
+                    index,
+                    kind: item.kind,
+                });
+            }
+        }
+
+        // If every proof is valid individually, so is any linear combination.
+        Ok(())
+    }
+
+    fn pvk(&self, kind: ProofKind) -> &'a PreparedVerifyingKey<Bls12_377> {
+        match kind {
+            ProofKind::Spend => self.spend_pvk,
+            ProofKind::Output => self.output_pvk,
+        }
+    }
+
+    /// Check a random linear combination of every proof's pairing equation.
+    fn verify_batch<R: CryptoRng + Rng>(&self, rng: &mut R) -> Result<bool, SynthesisError> {
+        let mut groups = [ProofKind::Spend, ProofKind::Output].map(Group::new);
+
+        let mut pairs = Vec::with_capacity(self.items.len() + 2 * groups.len());
+        for item in &self.items {
+            let r = Fq::rand(rng);
+            let prepared_inputs =
+                ark_groth16::prepare_inputs(self.pvk(item.kind), &item.public_inputs)?;
+
+            pairs.push((
+                item.proof.a.mul(r.into_repr()).into_affine().into(),
+                item.proof.b.into(),
+            ));
+
+            let group = groups
+                .iter_mut()
+                .find(|group| group.kind == item.kind)
+                .expect("every proof kind has a group");
+            group.proofs += 1;
+            group.weight += r;
+            group.inputs += prepared_inputs.mul(r.into_repr());
+            group.c += item.proof.c.mul(r.into_repr());
+        }
+
+        let mut alpha_beta = <Bls12_377 as PairingEngine>::Fqk::one();
+        let mut gt_exponentiations = 0;
+        for group in groups.iter().filter(|group| group.proofs > 0) {
+            let pvk = self.pvk(group.kind);
+            // The prepared `gamma` and `delta` are already negated.
+            pairs.push((
+                group.inputs.into_affine().into(),
+                pvk.gamma_g2_neg_pc.clone(),
+            ));
+            pairs.push((group.c.into_affine().into(), pvk.delta_g2_neg_pc.clone()));
+            alpha_beta *= pvk.alpha_g1_beta_g2.pow(group.weight.into_repr());
+            gt_exponentiations += 1;
+        }
+
+        let qap = Bls12_377::miller_loop(pairs.iter());
+        let test =
+            Bls12_377::final_exponentiation(&qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        counts::record(VerificationCounts {
+            verifications: self.items.len(),
+            // Each proof's public inputs are combined by `prepare_inputs`, and
+            // its combined inputs, `A` and `C` are weighted.
+            g1_scalar_muls: self
+                .items
+                .iter()
+                .map(|item| item.public_inputs.len() + 3)
+                .sum(),
+            miller_loop_pairs: pairs.len(),
+            final_exponentiations: 1,
+            gt_exponentiations,
+        });
+        Ok(test == alpha_beta)
+    }
+}
+
+/// The terms of a batch shared by all the proofs made with one verifying key.
+struct Group {
+    kind: ProofKind,
+    proofs: usize,
+    /// The sum of the proofs' random weights.
+    weight: Fq,
+    /// The weighted sum of the proofs' public input combinations.
+    inputs: <Bls12_377 as PairingEngine>::G1Projective,
+    /// The weighted sum of the proofs' `C` elements.
+    c: <Bls12_377 as PairingEngine>::G1Projective,
+}
+
+impl Group {
+    fn new(kind: ProofKind) -> Self {
+        Self {
+            kind,
+            proofs: 0,
+            weight: Fq::zero(),
+            inputs: Default::default(),
+            c: Default::default(),
+        }
+    }
+}
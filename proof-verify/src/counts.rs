@@ -0,0 +1,55 @@
+//! Counting the elliptic curve operations performed by verification.
+
+use std::{cell::RefCell, ops::AddAssign};
+
+thread_local! {
+    /// The operations performed so far on this thread inside
+    /// [`count_verifications`], if any.
+    static RECORDED: RefCell<Option<VerificationCounts>> = RefCell::new(None);
+}
+
+/// The elliptic curve operations performed by verifying proofs, counted where
+/// they are performed, e.g. to calibrate the fee weights of circuits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VerificationCounts {
+    /// The number of proofs verified, individually or as part of a batch.
+    pub verifications: usize,
+    /// The number of single scalar multiplications in G1.
+    pub g1_scalar_muls: usize,
+    /// The number of pairs in Miller loops.
+    pub miller_loop_pairs: usize,
+    /// The number of final exponentiations, i.e. of completed pairing checks.
+    pub final_exponentiations: usize,
+    /// The number of exponentiations in the target group.
+    pub gt_exponentiations: usize,
+}
+
+impl AddAssign for VerificationCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.verifications += other.verifications;
+        self.g1_scalar_muls += other.g1_scalar_muls;
+        self.miller_loop_pairs += other.miller_loop_pairs;
+        self.final_exponentiations += other.final_exponentiations;
+        self.gt_exponentiations += other.gt_exponentiations;
+    }
+}
+
+/// Run `f`, counting the elliptic curve operations performed by the proofs it
+/// verifies on this thread.
+pub fn count_verifications<T>(f: impl FnOnce() -> T) -> (T, VerificationCounts) {
+    let previous = RECORDED.with(|recorded| recorded.replace(Some(VerificationCounts::default())));
+    let result = f();
+    let counts = RECORDED
+        .with(|recorded| recorded.replace(previous))
+        .unwrap_or_default();
+    (result, counts)
+}
+
+/// Record operations just performed, if they are being counted on this thread.
+pub(crate) fn record(counts: VerificationCounts) {
+    RECORDED.with(|recorded| {
+        if let Some(recorded) = recorded.borrow_mut().as_mut() {
+            *recorded += counts;
+        }
+    });
+}
@@ -0,0 +1,32 @@
+//! Errors returned when verifying Groth16 proofs.
+
+use ark_relations::r1cs::SynthesisError;
+
+/// The reason a proof failed to verify.
+///
+/// This distinguishes proofs that are genuinely invalid from public inputs or
+/// verifying keys that could never have been used to verify any proof, so that
+/// callers can e.g. penalize the former differently from the latter.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The named public input could not be encoded as field elements.
+    #[error("invalid public input `{0}`")]
+    InvalidPublicInput(&'static str),
+    /// The public inputs were well-formed, but the proof does not verify.
+    #[error("proof did not verify")]
+    ProofInvalid,
+    /// The verifying key was not generated for the circuit being verified.
+    #[error("verifying key does not match the circuit: {0}")]
+    VerifyingKeyMismatch(String),
+}
+
+/// Interpret the result of checking a proof's pairing equation.
+pub(crate) fn check(result: Result<bool, SynthesisError>) -> Result<(), VerificationError> {
+    match result {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(VerificationError::ProofInvalid),
+        // The number of public inputs was already checked against the
+        // verifying key, so any remaining error is due to the key itself.
+        Err(err) => Err(VerificationError::VerifyingKeyMismatch(err.to_string())),
+    }
+}
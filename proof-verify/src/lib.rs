@@ -0,0 +1,26 @@
+//! Verification of Penumbra's Groth16 proofs.
+//!
+//! This crate holds everything needed to check a proof, and nothing needed to
+//! make one: verifying key checks and preparation, proof decoding, and single
+//! and batch verification against public inputs already encoded as field
+//! elements. Consensus nodes and light clients that only verify proofs can
+//! depend on it without building the circuits, and it is the part of the proof
+//! system to audit for soundness of verification.
+//!
+//! The `penumbra-crypto` crate re-exports these items from
+//! `proofs::groth16`, alongside the circuits and the typed public inputs of
+//! each proof.
+
+mod batch;
+mod counts;
+mod error;
+mod prepared;
+mod proof;
+mod upgrade;
+
+pub use batch::{BatchVerifier, InvalidProof, ProofKind};
+pub use counts::{count_verifications, VerificationCounts};
+pub use error::VerificationError;
+pub use prepared::{prepared_verifying_key_from_bytes, prepared_verifying_key_to_bytes};
+pub use proof::{check_verifying_key, decode_proof, verify_proof, PROOF_LEN_BYTES};
+pub use upgrade::verify_any;
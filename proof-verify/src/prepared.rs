@@ -0,0 +1,44 @@
+use ark_groth16::{PreparedVerifyingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use decaf377::Bls12_377;
+
+/// Serialize a prepared verifying key, so that it can be persisted and loaded
+/// with [`prepared_verifying_key_from_bytes`] instead of being re-prepared.
+///
+/// The encoding is the verifying key followed by the precomputed pairing
+/// `e(alpha, beta)`, which is the expensive part of preparing a key. The
+/// prepared `G2` line coefficients are not stored, since their layout is an
+/// implementation detail of the pairing library; they are cheap to recompute.
+pub fn prepared_verifying_key_to_bytes(
+    pvk: &PreparedVerifyingKey<Bls12_377>,
+) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    pvk.vk.serialize(&mut bytes)?;
+    pvk.alpha_g1_beta_g2.serialize(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Load a prepared verifying key written by [`prepared_verifying_key_to_bytes`].
+///
+/// The stored pairing is not recomputed, so the bytes must come from trusted
+/// storage, e.g. a cache written by this node.
+pub fn prepared_verifying_key_from_bytes(
+    bytes: &[u8],
+) -> anyhow::Result<PreparedVerifyingKey<Bls12_377>> {
+    let mut reader = bytes;
+    let vk = VerifyingKey::<Bls12_377>::deserialize(&mut reader)?;
+    let alpha_g1_beta_g2 = CanonicalDeserialize::deserialize(&mut reader)?;
+    if !reader.is_empty() {
+        anyhow::bail!(
+            "unexpected {} trailing bytes after prepared verifying key",
+            reader.len()
+        );
+    }
+
+    Ok(PreparedVerifyingKey {
+        gamma_g2_neg_pc: (-vk.gamma_g2).into(),
+        delta_g2_neg_pc: (-vk.delta_g2).into(),
+        alpha_g1_beta_g2,
+        vk,
+    })
+}
@@ -0,0 +1,89 @@
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_groth16::{PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::CanonicalDeserialize;
+use decaf377::{Bls12_377, Fq};
+
+use crate::{
+    counts::{self, VerificationCounts},
+    error, VerificationError,
+};
+
+/// The size of the compressed encoding of a Groth16 proof over BLS12-377.
+pub const PROOF_LEN_BYTES: usize = 192;
+
+/// Decode a proof from its compressed encoding.
+pub fn decode_proof(bytes: &[u8]) -> anyhow::Result<Proof<Bls12_377>> {
+    if bytes.len() != PROOF_LEN_BYTES {
+        anyhow::bail!(
+            "proof must be {} bytes, got {}",
+            PROOF_LEN_BYTES,
+            bytes.len()
+        );
+    }
+    Proof::deserialize(bytes).map_err(|err| anyhow::anyhow!("invalid proof encoding: {}", err))
+}
+
+/// Check that a verifying key was generated for a circuit with
+/// `num_public_inputs` public inputs.
+///
+/// This should be called whenever parameters are loaded, so that a change
+/// to a circuit's public inputs can't be paired with stale parameters.
+pub fn check_verifying_key(
+    vk: &VerifyingKey<Bls12_377>,
+    num_public_inputs: usize,
+) -> Result<(), VerificationError> {
+    // The first element of `gamma_abc_g1` corresponds to the constant
+    // "one" variable, not to a public input.
+    let vk_inputs = vk.gamma_abc_g1.len().saturating_sub(1);
+    if vk_inputs != num_public_inputs {
+        return Err(VerificationError::VerifyingKeyMismatch(format!(
+            "verifying key expects {} public inputs, but circuit has {}",
+            vk_inputs, num_public_inputs
+        )));
+    }
+    Ok(())
+}
+
+/// Verify `proof` against its public inputs, encoded as field elements in the
+/// order its circuit allocates them.
+pub fn verify_proof(
+    pvk: &PreparedVerifyingKey<Bls12_377>,
+    public_inputs: &[Fq],
+    proof: &Proof<Bls12_377>,
+) -> Result<(), VerificationError> {
+    error::check(check_pairing(pvk, public_inputs, proof))
+}
+
+/// Check a proof's pairing equation, as `Groth16::verify_with_processed_vk`
+/// does, counting the operations performed.
+pub(crate) fn check_pairing(
+    pvk: &PreparedVerifyingKey<Bls12_377>,
+    public_inputs: &[Fq],
+    proof: &Proof<Bls12_377>,
+) -> Result<bool, SynthesisError> {
+    let prepared_inputs = ark_groth16::prepare_inputs(pvk, public_inputs)?;
+
+    // The prepared `gamma` and `delta` are already negated.
+    let pairs = [
+        (proof.a.into(), proof.b.into()),
+        (
+            prepared_inputs.into_affine().into(),
+            pvk.gamma_g2_neg_pc.clone(),
+        ),
+        (proof.c.into(), pvk.delta_g2_neg_pc.clone()),
+    ];
+    let qap = Bls12_377::miller_loop(pairs.iter());
+    let test = Bls12_377::final_exponentiation(&qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+    counts::record(VerificationCounts {
+        verifications: 1,
+        // `prepare_inputs` multiplies one element of `gamma_abc_g1` by each
+        // public input.
+        g1_scalar_muls: public_inputs.len(),
+        miller_loop_pairs: pairs.len(),
+        final_exponentiations: 1,
+        ..VerificationCounts::default()
+    });
+    Ok(test == pvk.alpha_g1_beta_g2)
+}
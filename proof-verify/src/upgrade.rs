@@ -0,0 +1,32 @@
+use ark_groth16::VerifyingKey;
+use decaf377::Bls12_377;
+
+use crate::VerificationError;
+
+/// Verify a proof with each of the candidate verifying keys in turn, returning
+/// the index of the first key it verifies with.
+///
+/// Across a parameter upgrade, transactions may carry proofs made with either
+/// the old or the new proving key, so nodes need to accept both until the old
+/// parameters are retired. Keys should be listed in order of preference, since
+/// every key before the matching one costs a failed verification.
+pub fn verify_any(
+    vks: &[&VerifyingKey<Bls12_377>],
+    verify: impl Fn(&VerifyingKey<Bls12_377>) -> Result<(), VerificationError>,
+) -> anyhow::Result<usize> {
+    let mut last_error = None;
+    for (index, vk) in vks.iter().enumerate() {
+        match verify(vk) {
+            Ok(()) => return Ok(index),
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(match last_error {
+        Some(err) => anyhow::Error::from(err).context(format!(
+            "proof did not verify with any of the {} candidate verifying keys",
+            vks.len()
+        )),
+        None => anyhow::anyhow!("no candidate verifying keys to verify the proof with"),
+    })
+}
@@ -20,14 +20,11 @@ pub struct ChainParameters {
     pub base_reward_rate: u64,
     /// The penalty for slashing due to misbehavior.
     #[prost(message, optional, tag = "5")]
-    pub slashing_penalty_misbehavior: ::core::option::Option<
-        super::super::stake::v1alpha1::Penalty,
-    >,
+    pub slashing_penalty_misbehavior:
+        ::core::option::Option<super::super::stake::v1alpha1::Penalty>,
     /// The penalty for slashing due to downtime.
     #[prost(message, optional, tag = "10")]
-    pub slashing_penalty_downtime: ::core::option::Option<
-        super::super::stake::v1alpha1::Penalty,
-    >,
+    pub slashing_penalty_downtime: ::core::option::Option<super::super::stake::v1alpha1::Penalty>,
     /// The number of blocks in the window to check for downtime.
     #[prost(uint64, tag = "11")]
     pub signed_blocks_window_len: u64,
@@ -48,9 +45,7 @@ pub struct ChainParameters {
     pub proposal_voting_blocks: u64,
     /// The deposit required to create a proposal.
     #[prost(message, optional, tag = "21")]
-    pub proposal_deposit_amount: ::core::option::Option<
-        super::super::crypto::v1alpha1::Amount,
-    >,
+    pub proposal_deposit_amount: ::core::option::Option<super::super::crypto::v1alpha1::Amount>,
     /// The quorum required for a proposal to be considered valid, as a fraction of the total stake
     /// weight of the network.
     #[prost(message, optional, tag = "22")]
@@ -62,6 +57,14 @@ pub struct ChainParameters {
     /// have passed it, as a ratio of "no with veto" votes over all total votes.
     #[prost(message, optional, tag = "24")]
     pub proposal_veto_threshold: ::core::option::Option<Ratio>,
+    /// The epoch from which only Groth16 proofs are accepted, if one has been scheduled. Until then,
+    /// transparent proofs are accepted as well.
+    #[prost(uint64, optional, tag = "25")]
+    pub groth16_activation_epoch: ::core::option::Option<u64>,
+    /// The height from which signatures must be made in their signing context, if one has been
+    /// scheduled. Until then, signatures over the raw message are accepted as well.
+    #[prost(uint64, optional, tag = "26")]
+    pub signing_context_activation_height: ::core::option::Option<u64>,
 }
 /// The ratio between two numbers, used in governance to describe vote thresholds and quorums.
 #[derive(::serde::Deserialize, ::serde::Serialize)]
@@ -127,9 +130,7 @@ pub struct CompactBlock {
     pub fmd_parameters: ::core::option::Option<FmdParameters>,
     /// Price data for swaps executed in this block.
     #[prost(message, repeated, tag = "5")]
-    pub swap_outputs: ::prost::alloc::vec::Vec<
-        super::super::dex::v1alpha1::BatchSwapOutputData,
-    >,
+    pub swap_outputs: ::prost::alloc::vec::Vec<super::super::dex::v1alpha1::BatchSwapOutputData>,
 }
 #[derive(::serde::Deserialize, ::serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -145,9 +146,8 @@ pub mod state_payload {
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct RolledUp {
         #[prost(message, optional, tag = "1")]
-        pub commitment: ::core::option::Option<
-            super::super::super::crypto::v1alpha1::StateCommitment,
-        >,
+        pub commitment:
+            ::core::option::Option<super::super::super::crypto::v1alpha1::StateCommitment>,
     }
     #[derive(::serde::Deserialize, ::serde::Serialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -156,9 +156,7 @@ pub mod state_payload {
         #[prost(message, optional, tag = "1")]
         pub source: ::core::option::Option<super::NoteSource>,
         #[prost(message, optional, tag = "2")]
-        pub note: ::core::option::Option<
-            super::super::super::crypto::v1alpha1::EncryptedNote,
-        >,
+        pub note: ::core::option::Option<super::super::super::crypto::v1alpha1::EncryptedNote>,
     }
     #[derive(::serde::Deserialize, ::serde::Serialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -167,9 +165,7 @@ pub mod state_payload {
         #[prost(message, optional, tag = "1")]
         pub source: ::core::option::Option<super::NoteSource>,
         #[prost(message, optional, tag = "2")]
-        pub swap: ::core::option::Option<
-            super::super::super::dex::v1alpha1::SwapPayload,
-        >,
+        pub swap: ::core::option::Option<super::super::super::dex::v1alpha1::SwapPayload>,
     }
     #[derive(::serde::Deserialize, ::serde::Serialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -223,8 +219,6 @@ pub mod genesis_app_state {
         #[prost(string, tag = "2")]
         pub denom: ::prost::alloc::string::String,
         #[prost(message, optional, tag = "3")]
-        pub address: ::core::option::Option<
-            super::super::super::crypto::v1alpha1::Address,
-        >,
+        pub address: ::core::option::Option<super::super::super::crypto::v1alpha1::Address>,
     }
 }
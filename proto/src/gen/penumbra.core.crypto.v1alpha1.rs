@@ -266,3 +266,78 @@ pub struct EffectHash {
     #[prost(bytes = "vec", tag = "1")]
     pub inner: ::prost::alloc::vec::Vec<u8>,
 }
+/// A Groth16 proof of a spend, in its compressed encoding.
+#[derive(::serde::Deserialize, ::serde::Serialize)]
+#[serde(transparent)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZkSpendProof {
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "crate::serializers::base64str")]
+    pub inner: ::prost::alloc::vec::Vec<u8>,
+}
+/// A Groth16 proof of an output, in its compressed encoding.
+#[derive(::serde::Deserialize, ::serde::Serialize)]
+#[serde(transparent)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZkOutputProof {
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "crate::serializers::base64str")]
+    pub inner: ::prost::alloc::vec::Vec<u8>,
+}
+/// Opaque, client-defined metadata about a note.
+///
+/// Note metadata is carried through transaction plans and recorded by the view
+/// service when the note is detected, so that wallets can correlate planned and
+/// received notes. It is never included in a transaction.
+#[derive(::serde::Deserialize, ::serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NoteMetadata {
+    #[prost(btree_map = "string, bytes", tag = "1")]
+    pub entries: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::vec::Vec<u8>,
+    >,
+}
+/// The witnesses and public inputs of a spend proof: everything needed to prove
+/// it, e.g. on another machine.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpendProofPlan {
+    #[prost(message, optional, tag = "1")]
+    pub note_commitment_proof: ::core::option::Option<NoteCommitmentProof>,
+    #[prost(message, optional, tag = "2")]
+    pub note: ::core::option::Option<Note>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub v_blinding: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub spend_auth_randomizer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub ak: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub nk: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "7")]
+    pub anchor: ::core::option::Option<MerkleRoot>,
+    #[prost(message, optional, tag = "8")]
+    pub balance_commitment: ::core::option::Option<BalanceCommitment>,
+    #[prost(message, optional, tag = "9")]
+    pub nullifier: ::core::option::Option<Nullifier>,
+    #[prost(bytes = "vec", tag = "10")]
+    pub rk: ::prost::alloc::vec::Vec<u8>,
+}
+/// The witnesses and public inputs of an output proof: everything needed to
+/// prove it, e.g. on another machine.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutputProofPlan {
+    #[prost(message, optional, tag = "1")]
+    pub note: ::core::option::Option<Note>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub v_blinding: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub balance_commitment: ::core::option::Option<BalanceCommitment>,
+    #[prost(message, optional, tag = "4")]
+    pub note_commitment: ::core::option::Option<StateCommitment>,
+}
@@ -551,4 +551,9 @@ pub struct OutputPlan {
     #[prost(bytes = "bytes", tag = "4")]
     #[serde(with = "crate::serializers::hexstr_bytes")]
     pub value_blinding: ::prost::bytes::Bytes,
+    /// Opaque metadata about the new note, which is not included in the transaction.
+    #[prost(message, optional, tag = "5")]
+    pub note_metadata: ::core::option::Option<
+        super::super::crypto::v1alpha1::NoteMetadata,
+    >,
 }
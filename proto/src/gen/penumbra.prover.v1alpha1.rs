@@ -0,0 +1,359 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProveSpendRequest {
+    /// The spend to prove.
+    #[prost(message, optional, tag = "1")]
+    pub plan: ::core::option::Option<
+        super::super::core::crypto::v1alpha1::SpendProofPlan,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProveSpendResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<
+        super::super::core::crypto::v1alpha1::ZkSpendProof,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProveOutputRequest {
+    /// The output to prove.
+    #[prost(message, optional, tag = "1")]
+    pub plan: ::core::option::Option<
+        super::super::core::crypto::v1alpha1::OutputProofPlan,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProveOutputResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<
+        super::super::core::crypto::v1alpha1::ZkOutputProof,
+    >,
+}
+/// Generated client implementations.
+#[cfg(feature = "rpc")]
+pub mod proving_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// The proving protocol lets a client, such as a mobile wallet, delegate
+    /// generating proofs to a faster machine.
+    ///
+    /// Proof plans contain the notes being spent and created and the keys needed to
+    /// prove with them, so the proving service must be trusted with them. Clients
+    /// should rerandomize the proofs they receive before including them in a
+    /// transaction, so that the service can't link the proofs it made to the ones
+    /// that appear on chain.
+    #[derive(Debug, Clone)]
+    pub struct ProvingServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ProvingServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ProvingServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ProvingServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            ProvingServiceClient::new(
+                InterceptedService::new(inner, interceptor),
+            )
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Proves the spend described by the given plan.
+        pub async fn prove_spend(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ProveSpendRequest>,
+        ) -> Result<tonic::Response<super::ProveSpendResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/penumbra.prover.v1alpha1.ProvingService/ProveSpend",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Proves the output described by the given plan.
+        pub async fn prove_output(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ProveOutputRequest>,
+        ) -> Result<tonic::Response<super::ProveOutputResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/penumbra.prover.v1alpha1.ProvingService/ProveOutput",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+#[cfg(feature = "rpc")]
+pub mod proving_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    ///Generated trait containing gRPC methods that should be implemented for use with ProvingServiceServer.
+    #[async_trait]
+    pub trait ProvingService: Send + Sync + 'static {
+        /// Proves the spend described by the given plan.
+        async fn prove_spend(
+            &self,
+            request: tonic::Request<super::ProveSpendRequest>,
+        ) -> Result<tonic::Response<super::ProveSpendResponse>, tonic::Status>;
+        /// Proves the output described by the given plan.
+        async fn prove_output(
+            &self,
+            request: tonic::Request<super::ProveOutputRequest>,
+        ) -> Result<tonic::Response<super::ProveOutputResponse>, tonic::Status>;
+    }
+    /// The proving protocol lets a client, such as a mobile wallet, delegate
+    /// generating proofs to a faster machine.
+    ///
+    /// Proof plans contain the notes being spent and created and the keys needed to
+    /// prove with them, so the proving service must be trusted with them. Clients
+    /// should rerandomize the proofs they receive before including them in a
+    /// transaction, so that the service can't link the proofs it made to the ones
+    /// that appear on chain.
+    #[derive(Debug)]
+    pub struct ProvingServiceServer<T: ProvingService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: ProvingService> ProvingServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>>
+    for ProvingServiceServer<T>
+    where
+        T: ProvingService,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/penumbra.prover.v1alpha1.ProvingService/ProveSpend" => {
+                    #[allow(non_camel_case_types)]
+                    struct ProveSpendSvc<T: ProvingService>(pub Arc<T>);
+                    impl<
+                        T: ProvingService,
+                    > tonic::server::UnaryService<super::ProveSpendRequest>
+                    for ProveSpendSvc<T> {
+                        type Response = super::ProveSpendResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ProveSpendRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).prove_spend(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ProveSpendSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/penumbra.prover.v1alpha1.ProvingService/ProveOutput" => {
+                    #[allow(non_camel_case_types)]
+                    struct ProveOutputSvc<T: ProvingService>(pub Arc<T>);
+                    impl<
+                        T: ProvingService,
+                    > tonic::server::UnaryService<super::ProveOutputRequest>
+                    for ProveOutputSvc<T> {
+                        type Response = super::ProveOutputResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ProveOutputRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).prove_output(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ProveOutputSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: ProvingService> Clone for ProvingServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: ProvingService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: ProvingService> tonic::server::NamedService
+    for ProvingServiceServer<T> {
+        const NAME: &'static str = "penumbra.prover.v1alpha1.ProvingService";
+    }
+}
@@ -496,6 +496,11 @@ pub struct SpendableNoteRecord {
     /// The source of the note (a tx hash or otherwise)
     #[prost(message, optional, tag = "8")]
     pub source: ::core::option::Option<super::super::core::chain::v1alpha1::NoteSource>,
+    /// Opaque metadata about the note, if it was planned with any.
+    #[prost(message, optional, tag = "9")]
+    pub note_metadata: ::core::option::Option<
+        super::super::core::crypto::v1alpha1::NoteMetadata,
+    >,
 }
 #[derive(::serde::Deserialize, ::serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -219,6 +219,13 @@ pub mod penumbra {
             include!("gen/penumbra.custody.v1alpha1.rs");
         }
     }
+
+    /// Remote proving protocol structures.
+    pub mod prover {
+        pub mod v1alpha1 {
+            include!("gen/penumbra.prover.v1alpha1.rs");
+        }
+    }
 }
 
 // TODO(erwan): figure out path to upstream those. maybe they should be in their own crate or repo?
@@ -0,0 +1,169 @@
+#[macro_use]
+extern crate proptest_derive;
+
+use proptest::{arbitrary::*, prelude::*};
+
+use penumbra_tct::{internal::hash::Hash, Commitment, Tree, Witness};
+
+const MAX_USED_COMMITMENTS: usize = 4;
+const MAX_TIER_ACTIONS: usize = 64;
+
+#[derive(Debug, Copy, Clone, Arbitrary)]
+#[proptest(params("Vec<Commitment>"))]
+enum Action {
+    EndBlock,
+    EndEpoch,
+    Forget(Commitment),
+    Insert(Witness, Commitment),
+}
+
+/// A naive reference implementation of the tree's hashing, which stores every leaf hash and
+/// recomputes every interior hash from scratch, without any of the real tree's caching or nesting.
+#[derive(Debug, Default)]
+struct Reference {
+    epochs: Vec<Tier<Tier<Hash>>>,
+}
+
+/// A block or epoch in the reference tree.
+#[derive(Debug, Default)]
+struct Tier<T> {
+    items: Vec<T>,
+    finalized: bool,
+}
+
+impl<T> Tier<T> {
+    fn finalized() -> Self {
+        Self {
+            items: Vec::new(),
+            finalized: true,
+        }
+    }
+}
+
+impl Reference {
+    /// The current epoch, starting a new one if the last one is finalized.
+    fn epoch(&mut self) -> &mut Tier<Tier<Hash>> {
+        if self.epochs.last().map_or(true, |epoch| epoch.finalized) {
+            self.epochs.push(Tier::default());
+        }
+        self.epochs.last_mut().unwrap()
+    }
+
+    fn insert(&mut self, commitment: Commitment) {
+        let epoch = self.epoch();
+        if epoch.items.last().map_or(true, |block| block.finalized) {
+            epoch.items.push(Tier::default());
+        }
+        let block = epoch.items.last_mut().unwrap();
+        block.items.push(Hash::of(commitment));
+    }
+
+    /// End the current block, returning its root.
+    fn end_block(&mut self) -> Hash {
+        let epoch = self.epoch();
+        match epoch.items.last_mut() {
+            Some(block) if !block.finalized => block.finalized = true,
+            _ => epoch.items.push(Tier::finalized()),
+        }
+        block_hash(epoch.items.last().unwrap())
+    }
+
+    /// End the current epoch, returning its root.
+    fn end_epoch(&mut self) -> Hash {
+        match self.epochs.last_mut() {
+            Some(epoch) if !epoch.finalized => {
+                epoch.finalized = true;
+                if let Some(block) = epoch.items.last_mut() {
+                    block.finalized = true;
+                }
+            }
+            _ => self.epochs.push(Tier::finalized()),
+        }
+        epoch_hash(self.epochs.last().unwrap())
+    }
+
+    fn root(&self) -> Hash {
+        let epochs = self.epochs.iter().map(epoch_hash).collect::<Vec<_>>();
+        if epochs.is_empty() {
+            Hash::zero()
+        } else {
+            subtree(&epochs, 24, 16, Hash::zero())
+        }
+    }
+}
+
+fn block_hash(block: &Tier<Hash>) -> Hash {
+    tier_hash(&block.items, block.finalized, 8, 0)
+}
+
+fn epoch_hash(epoch: &Tier<Tier<Hash>>) -> Hash {
+    let blocks = epoch.items.iter().map(block_hash).collect::<Vec<_>>();
+    tier_hash(&blocks, epoch.finalized, 16, 8)
+}
+
+/// The hash of a tier of height `height` whose items have height `base`.
+///
+/// Unfinalized tiers pad missing items with zero, and finalized tiers pad them with one, so that a
+/// tier's hash changes when it is finalized. A finalized empty tier is represented by one.
+fn tier_hash(items: &[Hash], finalized: bool, height: u8, base: u8) -> Hash {
+    match (items.is_empty(), finalized) {
+        (true, true) => Hash::one(),
+        (true, false) => Hash::zero(),
+        (false, true) => subtree(items, height, base, Hash::one()),
+        (false, false) => subtree(items, height, base, Hash::zero()),
+    }
+}
+
+/// The hash of a complete quaternary subtree of height `height`, whose leftmost leaves at height
+/// `base` are `items`, and whose missing children are `padding`.
+fn subtree(items: &[Hash], height: u8, base: u8, padding: Hash) -> Hash {
+    if height == base {
+        return items[0];
+    }
+
+    let width = 4usize.pow(u32::from(height - base - 1));
+    let mut children = [padding; 4];
+    for (child, items) in children.iter_mut().zip(items.chunks(width)) {
+        *child = subtree(items, height - 1, base, padding);
+    }
+    let [a, b, c, d] = children;
+    Hash::node(height, a, b, c, d)
+}
+
+proptest! {
+    #[test]
+    fn root_matches_reference(
+        actions in
+            prop::collection::vec(any::<Commitment>(), 1..MAX_USED_COMMITMENTS)
+                .prop_flat_map(|commitments| {
+                    prop::collection::vec(any_with::<Action>(commitments), 1..MAX_TIER_ACTIONS)
+                })
+    ) {
+        let mut tree = Tree::new();
+        let mut reference = Reference::default();
+        assert_eq!(tree.root().0, reference.root());
+
+        for action in actions {
+            match action {
+                Action::Insert(witness, commitment) => {
+                    tree.insert(witness, commitment).unwrap();
+                    reference.insert(commitment);
+                }
+                Action::EndBlock => {
+                    let block_root = tree.end_block().unwrap();
+                    assert_eq!(block_root.0, reference.end_block(), "block root mismatch");
+                }
+                Action::EndEpoch => {
+                    let epoch_root = tree.end_epoch().unwrap();
+                    assert_eq!(epoch_root.0, reference.end_epoch(), "epoch root mismatch");
+                }
+                Action::Forget(commitment) => {
+                    // Forgetting a commitment never changes any hash
+                    tree.forget(commitment);
+                }
+            }
+
+            assert_eq!(tree.root().0, reference.root(), "root mismatch after {:?}", action);
+        }
+    }
+}
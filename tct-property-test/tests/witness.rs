@@ -153,6 +153,72 @@ proptest! {
         validate::all_proofs(&tree).unwrap();
     }
 
+    #[test]
+    fn refresh_stale_proofs(
+        actions in
+            prop::collection::vec(any::<Commitment>(), 1..MAX_USED_COMMITMENTS)
+                .prop_flat_map(|commitments| {
+                    prop::collection::vec(any_with::<Action>(commitments), 1..MAX_TIER_ACTIONS)
+                })
+    ) {
+        let mut tree = Tree::new();
+        let mut proofs = Vec::new();
+        for action in actions {
+            action.apply(&mut tree).unwrap();
+            if let Action::Insert(Witness::Keep, commitment) = action {
+                proofs.push(tree.witness(commitment).unwrap());
+            }
+        }
+
+        // Every proof can be refreshed to the current root, unless its commitment is no longer
+        // witnessed at the same position
+        for proof in proofs {
+            let still_witnessed = tree.position_of(proof.commitment()) == Some(proof.position());
+            match tree.refresh_proof(&proof) {
+                Some(refreshed) => {
+                    assert!(still_witnessed);
+                    assert_eq!(refreshed.position(), proof.position());
+                    assert!(refreshed.verify(tree.root()).is_ok());
+                }
+                None => assert!(!still_witnessed),
+            }
+        }
+    }
+
+    #[test]
+    fn witness_at_retained_anchor(
+        (before, after) in
+            prop::collection::vec(any::<Commitment>(), 1..MAX_USED_COMMITMENTS)
+                .prop_flat_map(|commitments| {
+                    (
+                        prop::collection::vec(any_with::<Action>(commitments.clone()), 1..MAX_TIER_ACTIONS),
+                        prop::collection::vec(any_with::<Action>(commitments), 1..MAX_TIER_ACTIONS),
+                    )
+                })
+    ) {
+        let mut tree = Tree::new();
+        for action in before {
+            action.apply(&mut tree).unwrap();
+        }
+
+        let anchor = tree.retain_anchor();
+        let snapshot = tree.clone();
+        for action in after {
+            action.apply(&mut tree).unwrap();
+        }
+
+        // Everything witnessed when the anchor was retained can still be witnessed against it,
+        // regardless of what happened to the tree afterwards
+        for (_, commitment) in snapshot.commitments() {
+            let proof = tree.witness_at(anchor, commitment).unwrap();
+            assert_eq!(proof, snapshot.witness(commitment).unwrap());
+            assert!(proof.verify(anchor).is_ok());
+        }
+
+        assert!(tree.release_anchor(anchor));
+        assert_eq!(tree.retained_anchors().count(), 0);
+    }
+
     #[test]
     fn validate_cached_hashes(
         actions in
@@ -0,0 +1,144 @@
+//! Benchmarks for the core operations on a [`Tree`], at a range of tree sizes and witness
+//! densities.
+//!
+//! Witnessing and forgetting are also benchmarked with [`HashRetention::TierRoots`], and the number
+//! of hashes each retention policy writes to storage is printed alongside, to show the tradeoff.
+//!
+//! Building the largest trees takes a while; set `TCT_BENCH_MAX_LEAVES` to skip tree sizes above
+//! a given number of leaves.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use decaf377::Fq;
+use penumbra_tct::{storage::InMemory, Commitment, HashRetention, Tree, Witness};
+
+/// The tree sizes to benchmark, in leaves.
+const SIZES: [usize; 5] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+/// The witness densities to benchmark, as the fraction of leaves that are kept, in percent.
+const DENSITIES: [usize; 2] = [1, 100];
+
+/// The number of commitments in a block.
+const BLOCK_CAPACITY: usize = 1 << 16;
+
+fn commitment(i: usize) -> Commitment {
+    Commitment(Fq::from(i as u64))
+}
+
+/// Build a tree with `size` leaves, keeping `density` percent of them, and return it along with
+/// the commitments that were kept.
+fn build(size: usize, density: usize) -> (Tree, Vec<Commitment>) {
+    let mut tree = Tree::new();
+    let mut kept = Vec::new();
+    for i in 0..size {
+        let witness = if i * density / 100 != (i + 1) * density / 100 {
+            kept.push(commitment(i));
+            Witness::Keep
+        } else {
+            Witness::Forget
+        };
+        tree.insert(witness, commitment(i)).unwrap();
+        if (i + 1) % BLOCK_CAPACITY == 0 {
+            tree.end_block().unwrap();
+        }
+    }
+    // Make sure all the hashes in the tree are computed before benchmarking.
+    tree.root();
+    (tree, kept)
+}
+
+/// Count the hashes written to storage when serializing the tree.
+fn stored_hashes(tree: &Tree) -> usize {
+    let mut storage = InMemory::new();
+    tree.to_writer(&mut storage).unwrap();
+    storage.hashes().count()
+}
+
+fn sizes() -> impl Iterator<Item = usize> {
+    let max = std::env::var("TCT_BENCH_MAX_LEAVES")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(usize::MAX);
+    SIZES.into_iter().filter(move |&size| size <= max)
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tct");
+    group.sample_size(10);
+
+    for size in sizes() {
+        for density in DENSITIES {
+            let (tree, kept) = build(size, density);
+            let parameter = format!("{}_leaves/{}%_witnessed", size, density);
+            let new = commitment(size);
+
+            group.bench_with_input(BenchmarkId::new("insert", &parameter), &tree, |b, tree| {
+                b.iter_batched(
+                    || tree.clone(),
+                    |mut tree| tree.insert(Witness::Keep, new).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            });
+
+            group.bench_with_input(BenchmarkId::new("root", &parameter), &tree, |b, tree| {
+                b.iter_batched(
+                    || {
+                        let mut tree = tree.clone();
+                        tree.insert(Witness::Keep, new).unwrap();
+                        tree
+                    },
+                    |tree| tree.root(),
+                    BatchSize::SmallInput,
+                )
+            });
+
+            group.bench_with_input(BenchmarkId::new("witness", &parameter), &tree, |b, tree| {
+                let mut kept = kept.iter().cycle();
+                b.iter(|| tree.witness(*kept.next().unwrap()).unwrap())
+            });
+
+            group.bench_with_input(BenchmarkId::new("forget", &parameter), &tree, |b, tree| {
+                let mut kept = kept.iter().cycle();
+                b.iter_batched(
+                    || (tree.clone(), *kept.next().unwrap()),
+                    |(mut tree, commitment)| assert!(tree.forget(commitment)),
+                    BatchSize::SmallInput,
+                )
+            });
+
+            let mut evicted = tree.clone();
+            evicted.set_hash_retention(HashRetention::TierRoots);
+            eprintln!(
+                "{}: {} hashes stored retaining all, {} retaining tier roots",
+                parameter,
+                stored_hashes(&tree),
+                stored_hashes(&evicted),
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new("witness_tier_roots", &parameter),
+                &evicted,
+                |b, tree| {
+                    let mut kept = kept.iter().cycle();
+                    b.iter(|| tree.witness(*kept.next().unwrap()).unwrap())
+                },
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new("forget_tier_roots", &parameter),
+                &evicted,
+                |b, tree| {
+                    let mut kept = kept.iter().cycle();
+                    b.iter_batched(
+                        || (tree.clone(), *kept.next().unwrap()),
+                        |(mut tree, commitment)| assert!(tree.forget(commitment)),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
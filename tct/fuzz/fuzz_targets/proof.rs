@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use penumbra_proto::Protobuf;
+use penumbra_tct::Proof;
+
+// Decoding arbitrary bytes as a proof must never panic, and anything that decodes must re-encode
+// to a proof that decodes identically.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = Proof::decode(data) {
+        let encoded = proof.encode_to_vec();
+        assert_eq!(Proof::decode(&*encoded).unwrap(), proof);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use penumbra_tct::{storage::InMemory, Tree};
+
+// Loading a tree from arbitrary storage contents must never panic: corrupt storage should only
+// ever produce an error.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut storage) = bincode::deserialize::<InMemory>(data) {
+        let _ = Tree::from_reader(&mut storage);
+    }
+});
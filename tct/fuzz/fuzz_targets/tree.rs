@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use penumbra_tct::Tree;
+
+// Deserializing arbitrary bytes as a tree must never panic, and anything that deserializes must
+// round-trip through its serialization.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tree) = bincode::deserialize::<Tree>(data) {
+        let serialized = bincode::serialize(&tree).unwrap();
+        let reloaded = bincode::deserialize::<Tree>(&serialized).unwrap();
+        assert_eq!(reloaded.root(), tree.root());
+        assert_eq!(reloaded.position(), tree.position());
+    }
+});
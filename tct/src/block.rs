@@ -132,11 +132,35 @@ impl Builder {
     ///
     /// Returns [`InsertError`] if the block is full.
     pub fn insert(&mut self, witness: Witness, commitment: Commitment) -> Result<(), InsertError> {
-        let item = match witness {
-            Witness::Keep => commitment.into(),
-            Witness::Forget => Hash::of(commitment).into(),
-        };
+        self.insert_item(witness, commitment, witness.item(commitment))
+    }
+
+    /// Add a batch of [`Commitment`]s to this [`block::Builder`](Builder), in order.
+    ///
+    /// This is equivalent to calling [`insert`](Builder::insert) on each commitment, but hashes the
+    /// commitments in parallel. As with [`root`](Builder::root), the interior hashes of the block
+    /// are only computed once they are needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError`] if the block becomes full, in which case the commitments before the
+    /// one that did not fit remain inserted.
+    pub fn insert_batch(
+        &mut self,
+        batch: impl IntoIterator<Item = (Witness, Commitment)>,
+    ) -> Result<(), InsertError> {
+        for (witness, commitment, item) in Witness::items(batch) {
+            self.insert_item(witness, commitment, item)?;
+        }
+        Ok(())
+    }
 
+    fn insert_item(
+        &mut self,
+        witness: Witness,
+        commitment: Commitment,
+        item: Item,
+    ) -> Result<(), InsertError> {
         // Get the position of the insertion, if it would succeed
         let position = u16::try_from(self.inner.position().ok_or(InsertError)?)
             .expect("position of block is never greater than `u16::MAX`")
@@ -0,0 +1,93 @@
+//! The changes made to a fork of a [`Tree`], which can be merged back into the tree it was forked
+//! from.
+
+use crate::builder::{block, epoch};
+use crate::error::ApplyError;
+use crate::{Commitment, Position, Root, Tree, Witness};
+
+/// A change made to a fork of a [`Tree`].
+#[derive(Debug, Clone)]
+pub(crate) enum Change {
+    /// A commitment was inserted with [`Tree::insert`].
+    Insert(Witness, Commitment),
+    /// A commitment was forgotten with [`Tree::forget`].
+    Forget(Commitment),
+    /// The current block was ended with [`Tree::end_block`].
+    EndBlock,
+    /// The current epoch was ended with [`Tree::end_epoch`].
+    EndEpoch,
+    /// A block was inserted with [`Tree::insert_block`].
+    InsertBlock(block::Finalized),
+    /// An epoch was inserted with [`Tree::insert_epoch`].
+    InsertEpoch(epoch::Finalized),
+}
+
+/// The changes made to a [`Tree`] since it was [forked](Tree::fork), in order, as merged back into
+/// the tree it was forked from by [`Tree::apply`].
+#[derive(Debug, Clone)]
+pub struct Delta {
+    base_root: Root,
+    base_position: Option<Position>,
+    changes: Vec<Change>,
+}
+
+impl Delta {
+    pub(crate) fn new(base_root: Root, base_position: Option<Position>) -> Self {
+        Self {
+            base_root,
+            base_position,
+            changes: Vec::new(),
+        }
+    }
+
+    /// The root of the tree that the fork was made from.
+    pub fn base_root(&self) -> Root {
+        self.base_root
+    }
+
+    /// The position of the tree that the fork was made from.
+    pub fn base_position(&self) -> Option<Position> {
+        self.base_position
+    }
+
+    /// The number of changes made to the fork.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Whether the fork is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    // Make each change to the tree, in order.
+    pub(crate) fn replay(&self, tree: &mut Tree) -> Result<(), ApplyError> {
+        for change in &self.changes {
+            match change {
+                Change::Insert(witness, commitment) => {
+                    tree.insert(*witness, *commitment)?;
+                }
+                Change::Forget(commitment) => {
+                    tree.forget(*commitment);
+                }
+                Change::EndBlock => {
+                    tree.end_block()?;
+                }
+                Change::EndEpoch => {
+                    tree.end_epoch()?;
+                }
+                Change::InsertBlock(block) => {
+                    tree.insert_block(block.clone())?;
+                }
+                Change::InsertEpoch(epoch) => {
+                    tree.insert_epoch(epoch.clone())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -144,11 +144,35 @@ impl Builder {
     /// - the [`epoch::Builder`](Builder) is full, or
     /// - the most recent block is full.
     pub fn insert(&mut self, witness: Witness, commitment: Commitment) -> Result<(), InsertError> {
-        let item = match witness {
-            Witness::Keep => commitment.into(),
-            Witness::Forget => Hash::of(commitment).into(),
-        };
+        self.insert_item(witness, commitment, witness.item(commitment))
+    }
+
+    /// Add a batch of [`Commitment`]s to the most recent block of this
+    /// [`epoch::Builder`](Builder), in order.
+    ///
+    /// This is equivalent to calling [`insert`](Builder::insert) on each commitment, but hashes the
+    /// commitments in parallel.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError`] if the epoch or the most recent block becomes full, in which case
+    /// the commitments before the one that did not fit remain inserted.
+    pub fn insert_batch(
+        &mut self,
+        batch: impl IntoIterator<Item = (Witness, Commitment)>,
+    ) -> Result<(), InsertError> {
+        for (witness, commitment, item) in Witness::items(batch) {
+            self.insert_item(witness, commitment, item)?;
+        }
+        Ok(())
+    }
 
+    fn insert_item(
+        &mut self,
+        witness: Witness,
+        commitment: Commitment,
+        item: Item,
+    ) -> Result<(), InsertError> {
         // Get the position of the insertion, if it would succeed
         let position = u32::try_from(self.inner.position().ok_or(InsertError::Full)?)
             .expect("position of epoch is never greater than `u32::MAX`")
@@ -6,7 +6,7 @@ use crate::builder;
 use crate::prelude::*;
 
 #[doc(inline)]
-pub use crate::tree::RootDecodeError;
+pub use crate::tree::{PositionDecodeError, RootDecodeError};
 
 pub mod proof {
     //! Errors from deserializing or verifying inclusion proofs.
@@ -110,6 +110,40 @@ impl From<InsertEpochError> for builder::epoch::Finalized {
     }
 }
 
+/// An error occurred when applying the [`Delta`](crate::Delta) of a fork to a [`Tree`] with
+/// [`Tree::apply`].
+#[derive(Debug, Clone, Error)]
+pub enum ApplyError {
+    /// The delta was not forked from the [`Tree`] in its current state.
+    #[error("delta was not forked from the current state of the tree")]
+    BaseMismatch,
+    /// A commitment could not be inserted.
+    #[error(transparent)]
+    Insert(#[from] InsertError),
+    /// A block could not be inserted or ended.
+    #[error(transparent)]
+    InsertBlock(#[from] InsertBlockError),
+    /// An epoch could not be inserted or ended.
+    #[error(transparent)]
+    InsertEpoch(#[from] InsertEpochError),
+}
+
+/// An error occurred when extending a [`Tree`] from a stream with
+/// [`Tree::extend_from_stream`].
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Error)]
+pub enum ExtendError {
+    /// A commitment could not be inserted.
+    #[error(transparent)]
+    Insert(#[from] InsertError),
+    /// The current block could not be ended.
+    #[error(transparent)]
+    EndBlock(#[from] InsertBlockError),
+    /// The current epoch could not be ended.
+    #[error(transparent)]
+    EndEpoch(#[from] InsertEpochError),
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -119,5 +153,6 @@ mod test {
         static_assertions::assert_impl_all!(InsertError: Sync, Send);
         static_assertions::assert_impl_all!(InsertBlockError: Sync, Send);
         static_assertions::assert_impl_all!(InsertEpochError: Sync, Send);
+        static_assertions::assert_impl_all!(ApplyError: Sync, Send);
     }
 }
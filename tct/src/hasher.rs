@@ -0,0 +1,127 @@
+//! The hash function used to compute the hashes of commitments and internal nodes of the tree.
+//!
+//! By default, the tree is hashed with [`Poseidon`]. Experimental deployments can evaluate other
+//! arithmetization-friendly hash functions by implementing [`TreeHasher`] and calling [`install`]
+//! before any tree is constructed, without otherwise changing the tree implementation.
+//!
+//! Note that the hasher determines every root and authentication path in the tree, so trees (and
+//! stored trees) built with different hashers are incompatible, and the [`r1cs`](crate::r1cs)
+//! gadgets only support [`Poseidon`].
+
+use ark_ff::PrimeField;
+use once_cell::sync::OnceCell;
+use poseidon377::Fq;
+
+/// A hash function for the tree.
+///
+/// Each hasher has its own domain separator, derived from its [`label`](TreeHasher::label), which
+/// is passed to [`hash_leaf`](TreeHasher::hash_leaf) directly, and to
+/// [`hash_node`](TreeHasher::hash_node) offset by the height of the node.
+pub trait TreeHasher: Send + Sync + 'static {
+    /// A label identifying this hash function, from which its domain separator is derived.
+    ///
+    /// Every hasher must use a distinct label, so that hashes computed by different hashers are
+    /// domain-separated from one another.
+    fn label(&self) -> &'static [u8];
+
+    /// Hash a commitment to be inserted into the tree, using the given domain separator.
+    fn hash_leaf(&self, domain_separator: &Fq, commitment: Fq) -> Fq;
+
+    /// Hash the four children of an internal node, using the given domain separator.
+    fn hash_node(&self, domain_separator: &Fq, children: (Fq, Fq, Fq, Fq)) -> Fq;
+}
+
+/// The default [`TreeHasher`], using the Poseidon hash over `Fq`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Poseidon;
+
+impl TreeHasher for Poseidon {
+    fn label(&self) -> &'static [u8] {
+        b"penumbra.tct"
+    }
+
+    fn hash_leaf(&self, domain_separator: &Fq, commitment: Fq) -> Fq {
+        poseidon377::hash_1(domain_separator, commitment)
+    }
+
+    fn hash_node(&self, domain_separator: &Fq, children: (Fq, Fq, Fq, Fq)) -> Fq {
+        poseidon377::hash_4(domain_separator, children)
+    }
+}
+
+/// The domain separator for a hasher with the given label.
+pub fn domain_separator(label: &[u8]) -> Fq {
+    Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(label).as_bytes())
+}
+
+/// An error when installing a [`TreeHasher`] after the tree has already been hashed.
+#[derive(Debug, Clone, Copy, Error)]
+#[error("a tree hasher is already in use")]
+pub struct HasherInUse;
+
+/// Install the [`TreeHasher`] used for all trees in this process.
+///
+/// This must be called before anything in the tree is hashed, since hashes computed by different
+/// hashers can't be mixed; otherwise, it returns an error and the hasher already in use is kept.
+pub fn install(hasher: impl TreeHasher) -> Result<(), HasherInUse> {
+    ACTIVE
+        .set(Active::new(Box::new(hasher)))
+        .map_err(|_| HasherInUse)
+}
+
+/// The installed hasher, along with its precomputed domain separator.
+pub(crate) struct Active {
+    hasher: Box<dyn TreeHasher>,
+    domain_separator: Fq,
+}
+
+static ACTIVE: OnceCell<Active> = OnceCell::new();
+
+impl Active {
+    fn new(hasher: Box<dyn TreeHasher>) -> Self {
+        Self {
+            domain_separator: domain_separator(hasher.label()),
+            hasher,
+        }
+    }
+
+    /// Get the installed hasher, installing [`Poseidon`] if none has been installed yet.
+    #[inline]
+    pub(crate) fn get() -> &'static Active {
+        ACTIVE.get_or_init(|| Active::new(Box::new(Poseidon)))
+    }
+
+    /// Hash a commitment to be inserted into the tree.
+    #[inline]
+    pub(crate) fn hash_leaf(&self, commitment: Fq) -> Fq {
+        self.hasher.hash_leaf(&self.domain_separator, commitment)
+    }
+
+    /// Hash the children of an internal node at the given height.
+    #[inline]
+    pub(crate) fn hash_node(&self, height: u8, children: (Fq, Fq, Fq, Fq)) -> Fq {
+        let height = Fq::from_le_bytes_mod_order(&height.to_le_bytes());
+        self.hasher
+            .hash_node(&(self.domain_separator + height), children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{internal::hash::Hash, Commitment, DOMAIN_SEPARATOR};
+
+    #[test]
+    fn poseidon_is_default() {
+        assert_eq!(domain_separator(Poseidon.label()), *DOMAIN_SEPARATOR);
+
+        let commitment = Commitment(Fq::from(1u64));
+        assert_eq!(
+            Fq::from(Hash::of(commitment)),
+            poseidon377::hash_1(&DOMAIN_SEPARATOR, commitment.0)
+        );
+
+        // Once the tree has been hashed, the hasher can't be changed.
+        assert!(install(Poseidon).is_err());
+    }
+}
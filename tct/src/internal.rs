@@ -158,4 +158,4 @@ pub mod complete {
     };
 }
 
-pub(crate) use interface::UncheckedSetHash;
+pub(crate) use interface::{EvictHashes, UncheckedSetHash};
@@ -109,3 +109,9 @@ impl UncheckedSetHash for Item {
         }
     }
 }
+
+impl EvictHashes for Item {
+    fn evict_hashes_below(&mut self) {
+        // The hash of a commitment is kept, since it is needed at every level above it
+    }
+}
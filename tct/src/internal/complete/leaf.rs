@@ -93,3 +93,13 @@ impl<Item: UncheckedSetHash> UncheckedSetHash for Leaf<Item> {
         self.0.finish_initialize()
     }
 }
+
+impl<Item: EvictHashes> EvictHashes for Leaf<Item> {
+    fn evict_hashes_below(&mut self) {
+        self.0.evict_hashes_below()
+    }
+
+    fn evict_hashes(&mut self) {
+        self.0.evict_hashes()
+    }
+}
@@ -10,7 +10,8 @@ pub use children::Children;
 /// A complete sparse node in a tree, storing only the witnessed subtrees.
 #[derive(Clone, Debug)]
 pub struct Node<Child: Clone> {
-    hash: Hash,
+    // `None` if the hash has been evicted, in which case it is recomputed from the children
+    hash: OptionHash,
     forgotten: [Forgotten; 4],
     children: Children<Child>,
 }
@@ -31,7 +32,7 @@ impl<'de, Child: Height + GetHash + Deserialize<'de> + Clone> Deserialize<'de> f
     {
         let children = Children::deserialize(deserializer)?;
         Ok(Self {
-            hash: children.hash(),
+            hash: Some(children.hash()).into(),
             forgotten: Default::default(),
             children,
         })
@@ -48,7 +49,7 @@ impl<Child: Height + Clone> Node<Child> {
     {
         match Children::try_from(children) {
             Ok(children) => Insert::Keep(Self {
-                hash: children.hash(),
+                hash: Some(children.hash()).into(),
                 forgotten,
                 children,
             }),
@@ -85,12 +86,13 @@ where
 impl<Child: Height + GetHash + Clone> GetHash for Node<Child> {
     #[inline]
     fn hash(&self) -> Hash {
-        self.hash
+        // An evicted hash is recomputed every time, rather than being cached again
+        self.cached_hash().unwrap_or_else(|| self.children.hash())
     }
 
     #[inline]
     fn cached_hash(&self) -> Option<Hash> {
-        Some(self.hash)
+        self.hash.into()
     }
 }
 
@@ -165,6 +167,11 @@ impl<Child: GetHash + ForgetOwned + Clone> ForgetOwned for Node<Child> {
         // those hashes into a single node hash
         let reconstructed = match Children::try_from(children) {
             Ok(children) => {
+                debug_assert!(
+                    self.cached_hash()
+                        .map_or(true, |hash| hash == children.hash()),
+                    "forgetting must not change the hash of a node"
+                );
                 let mut reconstructed = Self {
                     children,
                     hash: self.hash,
@@ -178,7 +185,16 @@ impl<Child: GetHash + ForgetOwned + Clone> ForgetOwned for Node<Child> {
                 }
                 Insert::Keep(reconstructed)
             }
-            Err(_) => Insert::Hash(self.hash),
+            // If every child was forgotten, the whole node is pruned to its cached hash, freeing
+            // the memory for the subtree beneath it
+            Err([a, b, c, d]) => {
+                let hash = Hash::node(<Self as Height>::Height::HEIGHT, a, b, c, d);
+                debug_assert!(
+                    self.cached_hash().map_or(true, |cached| cached == hash),
+                    "forgetting must not change the hash of a node"
+                );
+                Insert::Hash(hash)
+            }
         };
 
         (reconstructed, was_forgotten)
@@ -236,7 +252,7 @@ impl<Child: Height + OutOfOrderOwned + Clone> OutOfOrderOwned for Node<Child> {
             }) => (hash, forgotten, children.into()),
             // If there's no node here yet, grab the hash and make up the contents of a new node,
             // into which we will insert the commitment
-            Insert::Hash(hash) => (hash, [Forgotten::default(); 4], {
+            Insert::Hash(hash) => (Some(hash).into(), [Forgotten::default(); 4], {
                 // Initially, all the children are the uninitialized hash; these will be filled in
                 // over time, and then those that aren't filled in will be set to the appropriate
                 // finalized hash
@@ -279,7 +295,7 @@ impl<Child: GetHash + UncheckedSetHash + Clone> UncheckedSetHash for Node<Child>
         match height.cmp(&Self::Height::HEIGHT) {
             Greater => panic!("height too large when setting hash: {}", height),
             // Set the hash here
-            Equal => self.hash = hash,
+            Equal => self.hash = Some(hash).into(),
             // Set the hash below
             Less => {
                 let (which_way, index) = WhichWay::at(Self::Height::HEIGHT, index);
@@ -315,17 +331,59 @@ impl<Child: GetHash + UncheckedSetHash + Clone> UncheckedSetHash for Node<Child>
         // the children which haven't been resolved yet!
 
         // Then, compute the hash at this node, if necessary
-        if self.hash.is_uninitialized() {
-            self.hash = self.children.hash();
+        if self
+            .cached_hash()
+            .map_or(true, |hash| hash.is_uninitialized())
+        {
+            self.hash = Some(self.children.hash()).into();
         }
     }
 }
 
+impl<Child: Height + GetHash + EvictHashes + Clone> EvictHashes for Node<Child> {
+    fn evict_hashes_below(&mut self) {
+        for child in self.children.children_mut() {
+            if let InsertMut::Keep(child) = child {
+                child.evict_hashes();
+            }
+        }
+    }
+
+    fn evict_hashes(&mut self) {
+        self.hash = None.into();
+        self.evict_hashes_below();
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::{Commitment, Tree, Witness};
+
     #[test]
     fn check_node_size() {
         // Disabled due to spurious test failure.
         // static_assertions::assert_eq_size!(Node<()>, [u8; 72]);
     }
+
+    #[test]
+    fn forgetting_prunes_complete_subtrees_to_their_hash() {
+        let mut tree = Tree::new();
+        let commitments = (0..20u64).map(|i| Commitment(i.into())).collect::<Vec<_>>();
+        for commitment in &commitments {
+            tree.insert(Witness::Keep, *commitment).unwrap();
+        }
+        // Ending the epoch makes its tiers complete.
+        tree.end_epoch().unwrap();
+        let root = tree.root();
+
+        for commitment in &commitments {
+            assert!(tree.forget(*commitment));
+            assert_eq!(tree.root(), root);
+        }
+
+        // Nothing is left beneath the forgotten epoch but its hash.
+        let epochs = tree.structure().children();
+        assert!(epochs[0].children().is_empty());
+        assert_eq!(epochs[0].cached_hash(), Some(epochs[0].hash()));
+    }
 }
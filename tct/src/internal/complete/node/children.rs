@@ -278,52 +278,80 @@ impl<Child: Clone> Children<Child> {
 }
 
 impl<Child: Clone> From<Children<Child>> for [Insert<Child>; 4] {
-    /// Get an array of references to the children or hashes stored in this [`Children`].
+    /// Take the children or hashes stored in this [`Children`].
+    ///
+    /// If these children are not shared with any other tree, they are moved out rather than
+    /// cloned, so that a child dropped by the caller (e.g. when it is forgotten) is freed at once.
     fn from(children: Children<Child>) -> [Insert<Child>; 4] {
         use Children::*;
         use Insert::*;
 
         match children {
-            ___C(c) => [Hash(c.0), Hash(c.1), Hash(c.2), Keep(c.3.clone())],
-            __C_(c) => [Hash(c.0), Hash(c.1), Keep(c.2.clone()), Hash(c.3)],
-            __CC(c) => [Hash(c.0), Hash(c.1), Keep(c.2.clone()), Keep(c.3.clone())],
-            _C__(c) => [Hash(c.0), Keep(c.1.clone()), Hash(c.2), Hash(c.3)],
-            _C_C(c) => [Hash(c.0), Keep(c.1.clone()), Hash(c.2), Keep(c.3.clone())],
-            _CC_(c) => [Hash(c.0), Keep(c.1.clone()), Keep(c.2.clone()), Hash(c.3)],
-            _CCC(c) => [
-                Hash(c.0),
-                Keep(c.1.clone()),
-                Keep(c.2.clone()),
-                Keep(c.3.clone()),
-            ],
-            C___(c) => [Keep(c.0.clone()), Hash(c.1), Hash(c.2), Hash(c.3)],
-            C__C(c) => [Keep(c.0.clone()), Hash(c.1), Hash(c.2), Keep(c.3.clone())],
-            C_C_(c) => [Keep(c.0.clone()), Hash(c.1), Keep(c.2.clone()), Hash(c.3)],
-            C_CC(c) => [
-                Keep(c.0.clone()),
-                Hash(c.1),
-                Keep(c.2.clone()),
-                Keep(c.3.clone()),
-            ],
-            CC__(c) => [Keep(c.0.clone()), Keep(c.1.clone()), Hash(c.2), Hash(c.3)],
-            CC_C(c) => [
-                Keep(c.0.clone()),
-                Keep(c.1.clone()),
-                Hash(c.2),
-                Keep(c.3.clone()),
-            ],
-            CCC_(c) => [
-                Keep(c.0.clone()),
-                Keep(c.1.clone()),
-                Keep(c.2.clone()),
-                Hash(c.3),
-            ],
-            CCCC(c) => [
-                Keep(c.0.clone()),
-                Keep(c.1.clone()),
-                Keep(c.2.clone()),
-                Keep(c.3.clone()),
-            ],
+            ___C(c) => {
+                let ___C(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Hash(b), Hash(c), Keep(d)]
+            }
+            __C_(c) => {
+                let __C_(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Hash(b), Keep(c), Hash(d)]
+            }
+            __CC(c) => {
+                let __CC(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Hash(b), Keep(c), Keep(d)]
+            }
+            _C__(c) => {
+                let _C__(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Keep(b), Hash(c), Hash(d)]
+            }
+            _C_C(c) => {
+                let _C_C(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Keep(b), Hash(c), Keep(d)]
+            }
+            _CC_(c) => {
+                let _CC_(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Keep(b), Keep(c), Hash(d)]
+            }
+            _CCC(c) => {
+                let _CCC(a, b, c, d) = unwrap_or_clone(c);
+                [Hash(a), Keep(b), Keep(c), Keep(d)]
+            }
+            C___(c) => {
+                let C___(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Hash(b), Hash(c), Hash(d)]
+            }
+            C__C(c) => {
+                let C__C(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Hash(b), Hash(c), Keep(d)]
+            }
+            C_C_(c) => {
+                let C_C_(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Hash(b), Keep(c), Hash(d)]
+            }
+            C_CC(c) => {
+                let C_CC(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Hash(b), Keep(c), Keep(d)]
+            }
+            CC__(c) => {
+                let CC__(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Keep(b), Hash(c), Hash(d)]
+            }
+            CC_C(c) => {
+                let CC_C(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Keep(b), Hash(c), Keep(d)]
+            }
+            CCC_(c) => {
+                let CCC_(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Keep(b), Keep(c), Hash(d)]
+            }
+            CCCC(c) => {
+                let CCCC(a, b, c, d) = unwrap_or_clone(c);
+                [Keep(a), Keep(b), Keep(c), Keep(d)]
+            }
         }
     }
 }
+
+/// Take the contents of an [`Arc`], cloning them only if they are shared.
+fn unwrap_or_clone<T: Clone>(arc: Arc<T>) -> T {
+    Arc::try_unwrap(arc).unwrap_or_else(|arc| (*arc).clone())
+}
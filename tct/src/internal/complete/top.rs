@@ -75,3 +75,10 @@ impl<Item: GetHash + UncheckedSetHash + Clone> UncheckedSetHash for Top<Item> {
         self.inner.finish_initialize()
     }
 }
+
+impl<Item: GetHash + Height + EvictHashes + Clone> EvictHashes for Top<Item> {
+    // The root of the tier is always kept, so that it never needs recomputing from scratch
+    fn evict_hashes_below(&mut self) {
+        self.inner.evict_hashes_below()
+    }
+}
@@ -14,6 +14,15 @@ impl From<Commitment> for Item {
     }
 }
 
+impl Item {
+    /// Create an item witnessing `commitment`, whose `hash` has already been computed.
+    pub(crate) fn with_hash(commitment: Commitment, hash: Hash) -> Self {
+        Self {
+            item: Insert::Keep((commitment, hash)),
+        }
+    }
+}
+
 impl From<Hash> for Item {
     fn from(hash: Hash) -> Self {
         Self {
@@ -149,3 +158,9 @@ impl UncheckedSetHash for Item {
         }
     }
 }
+
+impl EvictHashes for Item {
+    fn evict_hashes_below(&mut self) {
+        // The hash of a commitment is kept, since it is needed at every level above it
+    }
+}
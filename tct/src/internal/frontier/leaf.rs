@@ -128,3 +128,9 @@ impl<Item: UncheckedSetHash> UncheckedSetHash for Leaf<Item> {
         self.item.finish_initialize()
     }
 }
+
+impl<Item: EvictHashes> EvictHashes for Leaf<Item> {
+    fn evict_hashes_below(&mut self) {
+        self.item.evict_hashes_below()
+    }
+}
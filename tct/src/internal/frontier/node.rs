@@ -622,3 +622,22 @@ where
         // if we've set it to a real hash
     }
 }
+
+impl<Child: Focus + EvictHashes + Clone> EvictHashes for Node<Child>
+where
+    Child::Complete: EvictHashes + Clone,
+{
+    fn evict_hashes_below(&mut self) {
+        // The hashes of the siblings themselves are kept, because they are needed to recompute the
+        // hash of this node each time the frontier changes
+        for sibling in self.siblings.iter_mut() {
+            // Avoid cloning hashed siblings which are shared with another tree
+            if let Insert::Keep(_) = **sibling {
+                if let Insert::Keep(sibling) = Arc::make_mut(sibling) {
+                    sibling.evict_hashes_below();
+                }
+            }
+        }
+        Arc::make_mut(&mut self.focus).evict_hashes_below();
+    }
+}
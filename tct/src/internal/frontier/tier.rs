@@ -442,6 +442,19 @@ where
     }
 }
 
+impl<Item: Focus + EvictHashes + Clone> EvictHashes for Tier<Item>
+where
+    Item::Complete: EvictHashes + Clone,
+{
+    fn evict_hashes_below(&mut self) {
+        match &mut self.inner {
+            Inner::Frontier(frontier) => frontier.evict_hashes_below(),
+            Inner::Complete(complete) => complete.evict_hashes_below(),
+            Inner::Hash(_) => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
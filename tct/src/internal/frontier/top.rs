@@ -296,6 +296,17 @@ where
     }
 }
 
+impl<Item: Focus + Height + EvictHashes + Clone> EvictHashes for Top<Item>
+where
+    Item::Complete: EvictHashes + Clone,
+{
+    fn evict_hashes_below(&mut self) {
+        if let Some(ref mut inner) = self.inner {
+            inner.evict_hashes_below();
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
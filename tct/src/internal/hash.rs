@@ -7,13 +7,16 @@ use std::{
     ops::RangeInclusive,
 };
 
-use ark_ff::{fields::PrimeField, BigInteger256, Fp256, One, Zero};
+use ark_ff::{BigInteger256, Fp256, One, Zero};
 use decaf377::FieldExt;
 use once_cell::sync::Lazy;
-use poseidon377::{hash_1, hash_4, Fq};
+use poseidon377::Fq;
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::*;
+use crate::{
+    hasher::{self, Active, TreeHasher},
+    prelude::*,
+};
 
 mod cache;
 mod option;
@@ -96,11 +99,11 @@ impl Debug for Hash {
     }
 }
 
-/// The domain separator used for leaves in the tree, and used as a base index for the domain
-/// separators of nodes in the tree (nodes get a domain separator of the form `DOMAIN_SEPARATOR +
-/// HEIGHT`).
+/// The domain separator used for leaves in the tree by the default [`Poseidon`](hasher::Poseidon)
+/// hasher, and used as a base index for the domain separators of nodes in the tree (nodes get a
+/// domain separator of the form `DOMAIN_SEPARATOR + HEIGHT`).
 pub static DOMAIN_SEPARATOR: Lazy<Fq> =
-    Lazy::new(|| Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.tct").as_bytes()));
+    Lazy::new(|| hasher::domain_separator(hasher::Poseidon.label()));
 
 #[allow(unused)]
 impl Hash {
@@ -153,7 +156,7 @@ impl Hash {
     /// Hash an individual commitment to be inserted into the tree.
     #[inline]
     pub fn of(item: Commitment) -> Hash {
-        Self(hash_1(&DOMAIN_SEPARATOR, item.0))
+        Self(Active::get().hash_leaf(item.0))
     }
 
     /// Construct a hash for an internal node of the tree, given its height and the hashes of its
@@ -162,8 +165,7 @@ impl Hash {
     pub fn node(height: u8, a: Hash, b: Hash, c: Hash, d: Hash) -> Hash {
         // Definition of hash of node without cache optimization
         fn hash_node(height: u8, a: Hash, b: Hash, c: Hash, d: Hash) -> Hash {
-            let height = Fq::from_le_bytes_mod_order(&height.to_le_bytes());
-            Self(hash_4(&(*DOMAIN_SEPARATOR + height), (a.0, b.0, c.0, d.0)))
+            Self(Active::get().hash_node(height, (a.0, b.0, c.0, d.0)))
         }
 
         // The range of hashes to precompute: this captures hashes starting at the first internal node
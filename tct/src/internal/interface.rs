@@ -158,3 +158,20 @@ pub(crate) trait UncheckedSetHash: Height {
     /// longer contains any `Hash::uninitialized()` anywhere.
     fn finish_initialize(&mut self);
 }
+
+/// The hashes cached in complete nodes beneath the root of their tier can be evicted, to be
+/// recomputed from the nodes' children whenever they are needed.
+///
+/// Hashes that can't be recomputed, such as those of pruned subtrees and of leaves, are always
+/// kept, as are the hashes cached on the frontier.
+pub(crate) trait EvictHashes {
+    /// Evict the cached hashes of complete nodes beneath this one, but not its own, except where
+    /// they are the roots of complete tiers.
+    fn evict_hashes_below(&mut self);
+
+    /// Evict the cached hash of this, if it's a complete node but not the root of a complete tier,
+    /// as well as those beneath it.
+    fn evict_hashes(&mut self) {
+        self.evict_hashes_below()
+    }
+}
@@ -53,6 +53,11 @@ pub struct VerifyError {
 }
 
 impl VerifyError {
+    /// A proof did not verify against the given root hash.
+    pub(crate) fn new(root: Hash) -> Self {
+        Self { root }
+    }
+
     /// Get the root hash against which the proof failed to verify.
     pub fn root(&self) -> Hash {
         self.root
@@ -51,27 +51,39 @@ extern crate thiserror;
 extern crate async_trait;
 
 mod commitment;
+mod delta;
 mod index;
 mod proof;
 mod random;
+#[cfg(feature = "tokio")]
+mod stream;
 mod tree;
 mod witness;
 
 pub mod error;
+pub mod hasher;
 pub mod storage;
 pub mod structure;
 pub mod validate;
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 #[doc(inline)]
 pub use {
     commitment::Commitment,
+    delta::Delta,
     internal::hash::Forgotten,
     internal::hash::DOMAIN_SEPARATOR,
-    proof::Proof,
-    tree::{Position, Root, Tree},
+    proof::{BlockProof, EpochProof, FrontierProof, Proof},
+    tree::{HashRetention, Position, Root, Tree},
     witness::Witness,
 };
 
+#[cfg(feature = "tokio")]
+#[doc(inline)]
+pub use stream::{Progress, StreamItem};
+
 #[cfg(any(doc, feature = "internal"))]
 pub mod internal;
 #[cfg(not(any(doc, feature = "internal")))]
@@ -112,7 +124,7 @@ mod prelude {
             interface::Witness,
             path::{self, AuthPath, Path, WhichWay},
             three::{Elems, ElemsMut, IntoElems, Three},
-            UncheckedSetHash,
+            EvictHashes, UncheckedSetHash,
         },
         storage::{
             self, AsyncRead, AsyncWrite, DeleteRange, Read, StoreCommitment, StoreHash,
@@ -0,0 +1,127 @@
+//! Gauges describing a [`Tree`], exported via the [`metrics`](::metrics) facade.
+//!
+//! Services which maintain a [`Tree`] (such as a view service) can call [`register_metrics`] once
+//! at startup, and then [`Exporter::record`] whenever their tree changes, to expose the size and
+//! freshness of the tree on their metrics dashboards.
+
+use std::time::Instant;
+
+use ::metrics::{describe_gauge, gauge, Unit};
+
+use crate::{structure, Root, Tree};
+
+/// The total number of commitments ever inserted into the tree, including forgotten ones.
+pub const TCT_COMMITMENTS_TOTAL: &str = "penumbra_tct_commitments_total";
+/// The number of commitments currently witnessed in the tree.
+pub const TCT_WITNESSED_COMMITMENTS: &str = "penumbra_tct_witnessed_commitments";
+/// The estimated memory used by the tree, in bytes.
+pub const TCT_MEMORY_BYTES: &str = "penumbra_tct_memory_bytes";
+/// The time since the root of the tree last changed, in seconds.
+pub const TCT_ROOT_AGE_SECONDS: &str = "penumbra_tct_root_age_seconds";
+
+/// Registers all the metrics recorded by an [`Exporter`].
+pub fn register_metrics() {
+    describe_gauge!(
+        TCT_COMMITMENTS_TOTAL,
+        Unit::Count,
+        "The total number of commitments ever inserted into the tree, including forgotten ones"
+    );
+    describe_gauge!(
+        TCT_WITNESSED_COMMITMENTS,
+        Unit::Count,
+        "The number of commitments currently witnessed in the tree"
+    );
+    describe_gauge!(
+        TCT_MEMORY_BYTES,
+        Unit::Bytes,
+        "The estimated memory used by the tree"
+    );
+    describe_gauge!(
+        TCT_ROOT_AGE_SECONDS,
+        Unit::Seconds,
+        "The time since the root of the tree last changed"
+    );
+}
+
+/// Records the gauges for a single [`Tree`].
+///
+/// The exporter remembers the last root it saw, so that it can report how long ago the root
+/// changed; use one exporter per tree.
+#[derive(Debug, Clone, Default)]
+pub struct Exporter {
+    last_root: Option<(Root, Instant)>,
+}
+
+impl Exporter {
+    /// Create a new exporter, which has not yet seen any tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current state of the tree.
+    pub fn record(&mut self, tree: &Tree) {
+        let now = Instant::now();
+        let root = tree.root();
+        let changed_at = match self.last_root {
+            Some((last_root, changed_at)) if last_root == root => changed_at,
+            _ => {
+                self.last_root = Some((root, now));
+                now
+            }
+        };
+
+        // A full tree has no next position, but has had every possible commitment inserted.
+        let commitments = tree.position().map(u64::from).unwrap_or(u64::MAX);
+
+        gauge!(TCT_COMMITMENTS_TOTAL, commitments as f64);
+        gauge!(TCT_WITNESSED_COMMITMENTS, tree.witnessed_count() as f64);
+        gauge!(TCT_MEMORY_BYTES, estimated_memory_bytes(tree) as f64);
+        gauge!(
+            TCT_ROOT_AGE_SECONDS,
+            now.duration_since(changed_at).as_secs_f64()
+        );
+    }
+}
+
+/// Estimate the memory used by the tree, in bytes.
+///
+/// This counts every node of the tree which is held in memory, each of which stores a hash and
+/// pointers to up to four children, plus an entry in the index for every witnessed commitment.
+/// Forgotten subtrees are represented by a single hash, so this is proportional to the number of
+/// witnessed commitments rather than the number ever inserted. The estimate ignores allocator
+/// overhead, so the true figure will be somewhat higher.
+pub fn estimated_memory_bytes(tree: &Tree) -> usize {
+    const NODE_BYTES: usize =
+        std::mem::size_of::<structure::Hash>() + 4 * std::mem::size_of::<usize>();
+    const INDEX_ENTRY_BYTES: usize =
+        std::mem::size_of::<crate::Commitment>() + std::mem::size_of::<u64>();
+
+    let nodes = structure::traverse(tree.structure()).count();
+
+    std::mem::size_of::<Tree>() + nodes * NODE_BYTES + tree.witnessed_count() * INDEX_ENTRY_BYTES
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Commitment, Witness};
+
+    #[test]
+    fn forgetting_reduces_memory_estimate() {
+        let mut tree = Tree::new();
+        let empty = estimated_memory_bytes(&tree);
+
+        let commitments = (0..16u64).map(|i| Commitment(i.into())).collect::<Vec<_>>();
+        for commitment in &commitments {
+            tree.insert(Witness::Keep, *commitment).unwrap();
+        }
+        let full = estimated_memory_bytes(&tree);
+        assert!(full > empty);
+
+        for commitment in &commitments[1..] {
+            tree.forget(*commitment);
+        }
+        let forgotten = estimated_memory_bytes(&tree);
+        assert!(forgotten < full);
+    }
+}
@@ -1,8 +1,19 @@
 use ark_ff::UniformRand;
 use poseidon377::Fq;
+use serde::{Deserialize, Serialize};
 
+use crate::builder::{block, epoch};
 use crate::prelude::*;
 
+/// The height of the root of a block above its commitments.
+const BLOCK_HEIGHT: u8 = 8;
+
+/// The height of the root of an epoch above its commitments.
+const EPOCH_HEIGHT: u8 = 16;
+
+/// The height of the root of the whole tree above its commitments.
+const TREE_HEIGHT: u8 = 24;
+
 /// A proof of the inclusion of some [`Commitment`] in a [`Tree`] with a particular [`Root`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Proof(
@@ -68,6 +79,70 @@ impl Proof {
         self.0.verify(root.0)
     }
 
+    /// Verify a [`Proof`] of inclusion against the root of the epoch containing its commitment,
+    /// such as one returned by [`Tree::end_epoch`].
+    ///
+    /// Once an epoch has ended its root never changes, so unlike [`Proof::verify`], this succeeds
+    /// for a proof produced at any later point in the tree's history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for that [`epoch::Root`].
+    pub fn verify_epoch(&self, root: epoch::Root) -> Result<(), VerifyError> {
+        self.verify_at_height(EPOCH_HEIGHT, root.0)
+    }
+
+    /// Verify a [`Proof`] of inclusion against the root of the block containing its commitment,
+    /// such as one returned by [`Tree::end_block`].
+    ///
+    /// Once a block has ended its root never changes, so unlike [`Proof::verify`], this succeeds
+    /// for a proof produced at any later point in the tree's history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for that [`block::Root`].
+    pub fn verify_block(&self, root: block::Root) -> Result<(), VerifyError> {
+        self.verify_at_height(BLOCK_HEIGHT, root.0)
+    }
+
+    /// Verify a [`Proof`] of inclusion in a particular block of a particular epoch, against the
+    /// [`Root`] of a [`Tree`].
+    ///
+    /// This checks that the proof's commitment is in the block proven by `block` to be at the
+    /// same position as the commitment's, and that `block` is valid for `root`. Since the root of a
+    /// block never changes once it has ended, the proof can have been produced at any later point
+    /// in the tree's history than `block`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for the root of that block, if the block is
+    /// not the one containing the commitment, or if the block proof is invalid for that [`Root`].
+    pub fn verify_in_block(&self, block: &BlockProof, root: Root) -> Result<(), VerifyError> {
+        let position = self.position();
+        if (position.epoch(), position.block()) != (block.epoch(), block.block()) {
+            return Err(VerifyError::new(block.root().0));
+        }
+        self.verify_block(block.root())?;
+        block.verify(root)
+    }
+
+    /// Verify the proof against the hash of the node at the given height above its commitment.
+    fn verify_at_height(&self, height: u8, root: Hash) -> Result<(), VerifyError> {
+        // The auth path is ordered from root to leaf, so the siblings at height 1 are last.
+        let node = hash_path(
+            Hash::of(self.commitment()),
+            u64::from(self.position()),
+            0,
+            self.auth_path().into_iter().rev().take(height as usize),
+        );
+
+        if node == root {
+            Ok(())
+        } else {
+            Err(VerifyError::new(root))
+        }
+    }
+
     /// Get the commitment whose inclusion is witnessed by the proof.
     pub fn commitment(&self) -> Commitment {
         self.0.leaf
@@ -113,6 +188,247 @@ impl Proof {
     }
 }
 
+/// Hash `node`, which is at `height` above the commitment at `position`, up through each of the
+/// `siblings`, ordered from leaf to root, returning the hash of the last node reached.
+fn hash_path<'a>(
+    mut node: Hash,
+    position: u64,
+    height: u8,
+    siblings: impl IntoIterator<Item = &'a [Hash; 3]>,
+) -> Hash {
+    for (height, siblings) in (height + 1..=TREE_HEIGHT).zip(siblings) {
+        let which_way = WhichWay::at(height, position).0;
+        let [leftmost, left, right, rightmost] = which_way.insert(node, *siblings);
+        node = Hash::node(height, leftmost, left, right, rightmost);
+    }
+    node
+}
+
+/// A proof of the inclusion of the root of some block in a [`Tree`] with a particular [`Root`], at
+/// a particular index in a particular epoch.
+///
+/// Together with [`Proof::verify_block`], this proves that a [`Commitment`] is in that block of
+/// that epoch; see [`Proof::verify_in_block`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockProof {
+    epoch: u16,
+    block: u16,
+    root: block::Root,
+    auth_path: [[Hash; 3]; 16],
+}
+
+impl BlockProof {
+    /// Construct a new [`BlockProof`] of inclusion for the root of the block with the given index
+    /// in the epoch with the given index, and its authentication path from the root of the tree to
+    /// the block.
+    pub fn new(epoch: u16, block: u16, root: block::Root, auth_path: [[Hash; 3]; 16]) -> Self {
+        Self {
+            epoch,
+            block,
+            root,
+            auth_path,
+        }
+    }
+
+    /// Verify a [`BlockProof`] against the [`Root`] of a [`Tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for that [`Root`].
+    pub fn verify(&self, root: Root) -> Result<(), VerifyError> {
+        let position = u64::from(Position::from((self.epoch, self.block, 0)));
+        let node = hash_path(
+            self.root.0,
+            position,
+            BLOCK_HEIGHT,
+            self.auth_path.iter().rev(),
+        );
+
+        if node == root.0 {
+            Ok(())
+        } else {
+            Err(VerifyError::new(root.0))
+        }
+    }
+
+    /// The index of the epoch containing the block.
+    pub fn epoch(&self) -> u16 {
+        self.epoch
+    }
+
+    /// The index of the block within its epoch.
+    pub fn block(&self) -> u16 {
+        self.block
+    }
+
+    /// The root of the block whose inclusion is witnessed by the proof.
+    pub fn root(&self) -> block::Root {
+        self.root
+    }
+
+    /// Get the authentication path for this proof, ordered from the root of the tree to the block.
+    pub fn auth_path(&self) -> &[[Hash; 3]; 16] {
+        &self.auth_path
+    }
+}
+
+/// A proof of the inclusion of the root of some epoch in a [`Tree`] with a particular [`Root`], at
+/// a particular index.
+///
+/// Together with [`Proof::verify_epoch`], this proves that a [`Commitment`] is in that epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochProof {
+    epoch: u16,
+    root: epoch::Root,
+    auth_path: [[Hash; 3]; 8],
+}
+
+impl EpochProof {
+    /// Construct a new [`EpochProof`] of inclusion for the root of the epoch with the given index,
+    /// and its authentication path from the root of the tree to the epoch.
+    pub fn new(epoch: u16, root: epoch::Root, auth_path: [[Hash; 3]; 8]) -> Self {
+        Self {
+            epoch,
+            root,
+            auth_path,
+        }
+    }
+
+    /// Verify an [`EpochProof`] against the [`Root`] of a [`Tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for that [`Root`].
+    pub fn verify(&self, root: Root) -> Result<(), VerifyError> {
+        let position = u64::from(Position::from((self.epoch, 0, 0)));
+        let node = hash_path(
+            self.root.0,
+            position,
+            EPOCH_HEIGHT,
+            self.auth_path.iter().rev(),
+        );
+
+        if node == root.0 {
+            Ok(())
+        } else {
+            Err(VerifyError::new(root.0))
+        }
+    }
+
+    /// The index of the epoch.
+    pub fn epoch(&self) -> u16 {
+        self.epoch
+    }
+
+    /// The root of the epoch whose inclusion is witnessed by the proof.
+    pub fn root(&self) -> epoch::Root {
+        self.root
+    }
+
+    /// Get the authentication path for this proof, ordered from the root of the tree to the epoch.
+    pub fn auth_path(&self) -> &[[Hash; 3]; 8] {
+        &self.auth_path
+    }
+}
+
+/// A proof of the frontier of a [`Tree`] with a particular [`Root`]: the path from the root down to
+/// the most recently inserted [`Commitment`], along with the hashes of the nodes to its left.
+///
+/// Everything to the right of the frontier is empty, so this proves how many commitments the tree
+/// holds: no commitment can be included at or after its [`position`](FrontierProof::position).
+///
+/// The path stops early where the frontier passes through a block or epoch whose contents have all
+/// been forgotten, since only its hash is kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrontierProof {
+    position: Option<Position>,
+    siblings: Vec<Vec<Hash>>,
+    focus: Hash,
+}
+
+impl FrontierProof {
+    /// Construct a [`FrontierProof`] from the position of a tree, the hashes of the children to the
+    /// left of the frontier in each frontier node, ordered from root to leaf, and the hash of the
+    /// lowest node on the frontier.
+    pub fn new(position: Option<Position>, siblings: Vec<Vec<Hash>>, focus: Hash) -> Self {
+        Self {
+            position,
+            siblings,
+            focus,
+        }
+    }
+
+    /// Verify a [`FrontierProof`] against the [`Root`] of a [`Tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError`] if the proof is invalid for that [`Root`], including if the
+    /// number of children to the left of the frontier at any height doesn't match its position.
+    pub fn verify(&self, root: Root) -> Result<(), VerifyError> {
+        if self.node_at(TREE_HEIGHT) == Some(root.0) {
+            Ok(())
+        } else {
+            Err(VerifyError::new(root.0))
+        }
+    }
+
+    /// The position of the tree at which the next [`Commitment`] would be inserted, or `None` if
+    /// the tree is full.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// The hashes of the children to the left of the frontier in each frontier node, ordered from
+    /// root to leaf.
+    pub fn siblings(&self) -> &[Vec<Hash>] {
+        &self.siblings
+    }
+
+    /// The hash of the lowest node on the frontier: the most recently inserted commitment, or the
+    /// root of a block or epoch whose contents were forgotten.
+    pub fn focus(&self) -> Hash {
+        self.focus
+    }
+
+    /// The height of the [`focus`](FrontierProof::focus) above the commitments of the tree.
+    pub fn focus_height(&self) -> u8 {
+        TREE_HEIGHT.saturating_sub(self.siblings.len() as u8)
+    }
+
+    /// The hash of the node on the frontier at the given height, or `None` if the proof doesn't
+    /// reach down that far, or is inconsistent with its position.
+    pub fn node_at(&self, height: u8) -> Option<Hash> {
+        if self.siblings.len() > TREE_HEIGHT as usize || height < self.focus_height() {
+            return None;
+        }
+
+        // The most recently inserted position, to whose left lies every child on the frontier
+        let tip = match self.position {
+            Some(position) => u64::from(position).checked_sub(1),
+            None => Some(4u64.pow(TREE_HEIGHT as u32) - 1),
+        };
+
+        let mut node = self.focus;
+        // The siblings are ordered from root to leaf, so the siblings at the focus are last.
+        for (height, siblings) in (self.focus_height() + 1..=height).zip(self.siblings.iter().rev())
+        {
+            // An empty tree has no frontier below its root.
+            let tip = tip?;
+            if siblings.len() as u64 != (tip >> (2 * (height - 1))) & 0b11 {
+                return None;
+            }
+            // The frontier is padded with zero hashes on its right.
+            let mut children = [Hash::zero(); 4];
+            children[..siblings.len()].copy_from_slice(siblings);
+            children[siblings.len()] = node;
+            let [a, b, c, d] = children;
+            node = Hash::node(height, a, b, c, d);
+        }
+
+        Some(node)
+    }
+}
+
 use penumbra_proto::core::crypto::v1alpha1 as pb;
 use rand::Rng;
 
@@ -7,10 +7,22 @@ use decaf377::FieldExt;
 use decaf377::{r1cs::FqVar, Fq};
 
 use crate::{
-    internal::{hash::DOMAIN_SEPARATOR, path::WhichWay},
-    Proof,
+    internal::{
+        hash::{Hash, DOMAIN_SEPARATOR},
+        path::WhichWay,
+    },
+    FrontierProof, Proof,
 };
 
+/// The height of the root of the whole tree above its commitments.
+const TREE_HEIGHT: u8 = 24;
+
+/// The height of the root of an epoch above its commitments.
+const EPOCH_HEIGHT: u8 = 16;
+
+/// The height of the root of a block above its commitments.
+const BLOCK_HEIGHT: u8 = 8;
+
 /// This represents the TCT's auth path in R1CS.
 pub struct MerkleAuthPathVar {
     inner: [[FqVar; 3]; 24],
@@ -51,6 +63,9 @@ impl MerkleAuthPathVar {
     }
 
     /// Certify an auth path given a provided anchor, position, and leaf.
+    ///
+    /// The anchor is the root of the whole tree at the time the proof was produced: to prove
+    /// against a historical anchor, witness a proof produced by [`Tree::witness_at`](crate::Tree::witness_at).
     pub fn verify(
         &self,
         cs: ConstraintSystemRef<Fq>,
@@ -59,6 +74,52 @@ impl MerkleAuthPathVar {
         anchor_var: FqVar,
         leaf_var: FqVar,
     ) -> Result<(), SynthesisError> {
+        let root_var = self.root_at_height(cs, TREE_HEIGHT, position_var, leaf_var)?;
+        anchor_var.conditional_enforce_equal(&root_var, enforce)
+    }
+
+    /// Certify an auth path given the root of the epoch containing the leaf, its position, and
+    /// the leaf.
+    ///
+    /// Epoch roots never change once the epoch has ended, so this accepts an auth path produced at
+    /// any later point in the tree's history.
+    pub fn verify_epoch(
+        &self,
+        cs: ConstraintSystemRef<Fq>,
+        enforce: &Boolean<Fq>,
+        position_var: FqVar,
+        epoch_root_var: FqVar,
+        leaf_var: FqVar,
+    ) -> Result<(), SynthesisError> {
+        let root_var = self.root_at_height(cs, EPOCH_HEIGHT, position_var, leaf_var)?;
+        epoch_root_var.conditional_enforce_equal(&root_var, enforce)
+    }
+
+    /// Certify an auth path given the root of the block containing the leaf, its position, and
+    /// the leaf.
+    ///
+    /// Block roots never change once the block has ended, so this accepts an auth path produced at
+    /// any later point in the tree's history.
+    pub fn verify_block(
+        &self,
+        cs: ConstraintSystemRef<Fq>,
+        enforce: &Boolean<Fq>,
+        position_var: FqVar,
+        block_root_var: FqVar,
+        leaf_var: FqVar,
+    ) -> Result<(), SynthesisError> {
+        let root_var = self.root_at_height(cs, BLOCK_HEIGHT, position_var, leaf_var)?;
+        block_root_var.conditional_enforce_equal(&root_var, enforce)
+    }
+
+    /// Compute the hash of the node at the given height above the leaf, using the auth path.
+    fn root_at_height(
+        &self,
+        cs: ConstraintSystemRef<Fq>,
+        height: u8,
+        position_var: FqVar,
+        leaf_var: FqVar,
+    ) -> Result<FqVar, SynthesisError> {
         // We need to compute the root using the provided auth path, position,
         // and leaf.
         let domain_separator = FqVar::new_constant(cs.clone(), *DOMAIN_SEPARATOR)?;
@@ -75,10 +136,10 @@ impl MerkleAuthPathVar {
         let mut previous_level = leaf_var;
 
         // Start hashing from height 1, first hashing the leaf and its three siblings together,
-        // then the next level and so on, until we reach the root of the quadtree.
-        for height_value in 1..=24 {
+        // then the next level and so on, until we reach the requested height.
+        for height_value in 1..=height {
             let which_way = WhichWay::at(height_value, index_value).0;
-            let siblings = &self.inner[(24 - height_value) as usize];
+            let siblings = &self.inner[(TREE_HEIGHT - height_value) as usize];
             let [leftmost, left, right, rightmost] =
                 which_way.insert(previous_level.clone(), siblings.clone());
 
@@ -95,8 +156,339 @@ impl MerkleAuthPathVar {
             previous_level = parent;
         }
 
-        anchor_var.conditional_enforce_equal(&previous_level, enforce)?;
+        Ok(previous_level)
+    }
+}
+
+/// This represents the frontier of a TCT in R1CS, above some height.
+pub struct FrontierProofVar {
+    /// The height of `focus` above the commitments of the tree.
+    height: u8,
+    /// The hash of the node on the frontier at `height`.
+    focus: FqVar,
+    /// The children of each node on the frontier other than the next node on the frontier, ordered
+    /// from root to leaf: the children to its left, followed by zero padding.
+    others: Vec<[FqVar; 3]>,
+}
+
+impl FrontierProofVar {
+    /// Witness the frontier of a TCT above the given height.
+    ///
+    /// The height determines the shape of the circuit, so must be fixed by it: for instance, a
+    /// circuit binding to the block currently being built would use a height of 8. The proof must
+    /// reach down to the given height, which it does not if the frontier at that height passes
+    /// through a block or epoch whose contents were forgotten.
+    pub fn new(
+        cs: ConstraintSystemRef<Fq>,
+        frontier_proof: &FrontierProof,
+        height: u8,
+    ) -> Result<Self, SynthesisError> {
+        let focus = frontier_proof
+            .node_at(height)
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        let focus = FqVar::new_witness(cs.clone(), || Ok(Fq::from(focus)))?;
+
+        let mut others = Vec::new();
+        for siblings in &frontier_proof.siblings()[..(TREE_HEIGHT - height) as usize] {
+            let mut nodes = [FqVar::zero(), FqVar::zero(), FqVar::zero()];
+            for (i, node) in nodes.iter_mut().enumerate() {
+                let hash = siblings.get(i).copied().unwrap_or_else(Hash::zero);
+                *node = FqVar::new_witness(cs.clone(), || Ok(Fq::from(hash)))?;
+            }
+            others.push(nodes);
+        }
+
+        Ok(Self {
+            height,
+            focus,
+            others,
+        })
+    }
+
+    /// The hash of the node on the frontier at the height it was witnessed above.
+    pub fn focus(&self) -> &FqVar {
+        &self.focus
+    }
+
+    /// Certify the frontier given the root of the tree and the position of the tree, at which the
+    /// next commitment would be inserted.
+    ///
+    /// The tree must not be empty, since then it has no frontier.
+    pub fn verify(
+        &self,
+        cs: ConstraintSystemRef<Fq>,
+        enforce: &Boolean<Fq>,
+        position_var: FqVar,
+        anchor_var: FqVar,
+    ) -> Result<(), SynthesisError> {
+        // The position of the most recently inserted commitment, whose base-4 digits say how many
+        // children lie to the left of the frontier at each height.
+        let tip_bits = (position_var - FqVar::one()).to_bits_le()?;
+        let zero = FqVar::zero();
+
+        let mut node = self.focus.clone();
+        // The other children are ordered from root to leaf, so those at the focus are last.
+        for (height_value, [o0, o1, o2]) in
+            (self.height + 1..=TREE_HEIGHT).zip(self.others.iter().rev())
+        {
+            let low = &tip_bits[2 * (height_value as usize - 1)];
+            let high = &tip_bits[2 * (height_value as usize - 1) + 1];
+            let is_0 = low.not().and(&high.not())?;
+            let is_1 = low.and(&high.not())?;
+            let is_2 = low.not().and(high)?;
+            let is_3 = low.and(high)?;
+
+            // Each child to the right of the frontier must be zero padding.
+            o0.conditional_enforce_equal(&zero, &enforce.and(&is_0)?)?;
+            o1.conditional_enforce_equal(&zero, &enforce.and(&high.not())?)?;
+            o2.conditional_enforce_equal(&zero, &enforce.and(&is_3.not())?)?;
+
+            // Place the frontier node among the other children, according to the digit.
+            let a = FqVar::conditionally_select(&is_0, &node, o0)?;
+            let b = FqVar::conditionally_select(
+                &is_0,
+                o0,
+                &FqVar::conditionally_select(&is_1, &node, o1)?,
+            )?;
+            let c = FqVar::conditionally_select(
+                &is_3,
+                o2,
+                &FqVar::conditionally_select(&is_2, &node, o1)?,
+            )?;
+            let d = FqVar::conditionally_select(&is_3, &node, o2)?;
+
+            let height_var = FqVar::new_constant(cs.clone(), Fq::from(height_value))?;
+            node = MerkleAuthPathVar::hash_node(cs.clone(), height_var, a, b, c, d)?;
+        }
+
+        anchor_var.conditional_enforce_equal(&node, enforce)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::{Commitment, Position, Tree, Witness};
+
+    /// Build a tree in which `commitment` is witnessed at exactly `position`,
+    /// filling the positions before it with forgotten commitments.
+    fn tree_with_commitment_at(position: Position, commitment: Commitment) -> Tree {
+        let mut tree = Tree::new();
+        for _ in 0..position.epoch() {
+            tree.end_epoch().unwrap();
+        }
+        for _ in 0..position.block() {
+            tree.end_block().unwrap();
+        }
+        for i in 0..position.commitment() {
+            let filler = Commitment(Fq::from(u64::from(i) + 1));
+            tree.insert(Witness::Forget, filler).unwrap();
+        }
+        tree.insert(Witness::Keep, commitment).unwrap();
+        assert_eq!(tree.position_of(commitment), Some(position));
+        tree
+    }
+
+    /// Check whether the in-circuit auth path verification is satisfied for
+    /// the given proof and anchor.
+    fn auth_path_satisfied(proof: Proof, anchor: Fq) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let leaf_var = FqVar::new_witness(cs.clone(), || Ok(proof.commitment().0)).unwrap();
+        let position_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from(u64::from(proof.position())))).unwrap();
+        let anchor_var = FqVar::new_input(cs.clone(), || Ok(anchor)).unwrap();
+        let path_var = MerkleAuthPathVar::new(cs.clone(), proof).unwrap();
+
+        path_var
+            .verify(
+                cs.clone(),
+                &Boolean::TRUE,
+                position_var,
+                anchor_var,
+                leaf_var,
+            )
+            .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    /// Which root an auth path is verified against in-circuit.
+    #[derive(Clone, Copy)]
+    enum RootKind {
+        Tree,
+        Epoch,
+        Block,
+    }
+
+    /// Check whether the in-circuit verification against the given kind of root is satisfied
+    /// for the given proof and root.
+    fn auth_path_satisfied_at(proof: Proof, kind: RootKind, root: Fq) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let leaf_var = FqVar::new_witness(cs.clone(), || Ok(proof.commitment().0)).unwrap();
+        let position_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from(u64::from(proof.position())))).unwrap();
+        let root_var = FqVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let path_var = MerkleAuthPathVar::new(cs.clone(), proof).unwrap();
+
+        let verify = match kind {
+            RootKind::Tree => MerkleAuthPathVar::verify,
+            RootKind::Epoch => MerkleAuthPathVar::verify_epoch,
+            RootKind::Block => MerkleAuthPathVar::verify_block,
+        };
+        verify(
+            &path_var,
+            cs.clone(),
+            &Boolean::TRUE,
+            position_var,
+            root_var,
+            leaf_var,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn historical_anchors() {
+        let commitment = Commitment(Fq::from(u64::MAX));
+        let mut tree = tree_with_commitment_at((1, 2, 3).into(), commitment);
+        let block_root = tree.end_block().unwrap();
+        let anchor = tree.retain_anchor();
+        let epoch_root = tree.end_epoch().unwrap();
+
+        // Grow the tree, so that the current root no longer matches the retained anchor.
+        tree.insert(Witness::Keep, Commitment(Fq::from(7u64)))
+            .unwrap();
+        assert_ne!(tree.root(), anchor);
+
+        // A proof against the latest root still verifies against the roots of its block and
+        // epoch, which don't change once they have ended.
+        let proof = tree.witness(commitment).unwrap();
+        proof.verify_block(block_root).unwrap();
+        proof.verify_epoch(epoch_root).unwrap();
+        assert!(proof.verify(anchor).is_err());
+        assert!(auth_path_satisfied_at(
+            proof.clone(),
+            RootKind::Block,
+            Fq::from(block_root)
+        ));
+        assert!(auth_path_satisfied_at(
+            proof.clone(),
+            RootKind::Epoch,
+            Fq::from(epoch_root)
+        ));
+        assert!(!auth_path_satisfied_at(
+            proof.clone(),
+            RootKind::Epoch,
+            Fq::from(block_root)
+        ));
+        assert!(!auth_path_satisfied_at(
+            proof,
+            RootKind::Tree,
+            Fq::from(anchor)
+        ));
+
+        // A proof against the retained anchor verifies against it.
+        let historical_proof = tree.witness_at(anchor, commitment).unwrap();
+        historical_proof.verify(anchor).unwrap();
+        assert!(auth_path_satisfied_at(
+            historical_proof,
+            RootKind::Tree,
+            Fq::from(anchor)
+        ));
+    }
+
+    /// Check whether the in-circuit frontier verification is satisfied for the given proof above
+    /// the given height, and the given position and anchor.
+    fn frontier_satisfied(
+        proof: &FrontierProof,
+        height: u8,
+        position: Position,
+        anchor: Fq,
+    ) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let position_var =
+            FqVar::new_witness(cs.clone(), || Ok(Fq::from(u64::from(position)))).unwrap();
+        let anchor_var = FqVar::new_input(cs.clone(), || Ok(anchor)).unwrap();
+        let frontier_var = FrontierProofVar::new(cs.clone(), proof, height).unwrap();
+
+        frontier_var
+            .verify(cs.clone(), &Boolean::TRUE, position_var, anchor_var)
+            .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn frontier() {
+        let commitment = Commitment(Fq::from(u64::MAX));
+        let tree = tree_with_commitment_at((1, 2, 3).into(), commitment);
+        let anchor = Fq::from(tree.root());
+        let position = tree.position().unwrap();
+        let proof = tree.frontier_proof();
+        proof.verify(tree.root()).unwrap();
+
+        for height in [0, 8, 16] {
+            assert!(frontier_satisfied(&proof, height, position, anchor));
+            assert!(!frontier_satisfied(
+                &proof,
+                height,
+                position,
+                anchor + Fq::from(1u64)
+            ));
+        }
+
+        // The frontier of a tree of a different size doesn't verify.
+        let other_position = (1, 2, 5).into();
+        assert!(!frontier_satisfied(&proof, 0, other_position, anchor));
+    }
+
+    fn check_position(position: Position) {
+        let commitment = Commitment(Fq::from(u64::MAX));
+        let tree = tree_with_commitment_at(position, commitment);
+        let anchor = tree.root();
+        let proof = tree.witness(commitment).unwrap();
+
+        // The out-of-circuit and in-circuit verification must agree.
+        proof.verify(anchor).unwrap();
+        assert!(
+            auth_path_satisfied(proof.clone(), Fq::from(anchor)),
+            "auth path at {:?} should verify in-circuit",
+            position
+        );
+        assert!(
+            !auth_path_satisfied(proof, Fq::from(anchor) + Fq::from(1u64)),
+            "auth path at {:?} should not verify against the wrong anchor",
+            position
+        );
+    }
+
+    #[test]
+    fn first_position() {
+        check_position((0, 0, 0).into());
+    }
+
+    #[test]
+    fn last_position_of_first_node() {
+        check_position((0, 0, 3).into());
+    }
+
+    #[test]
+    fn last_position_of_first_block() {
+        check_position((0, 0, u16::MAX).into());
+    }
+
+    #[test]
+    fn first_position_of_second_block() {
+        check_position((0, 1, 0).into());
+    }
+
+    #[test]
+    fn last_position_of_first_epoch() {
+        check_position((0, u16::MAX, u16::MAX).into());
+    }
 
-        Ok(())
+    #[test]
+    fn first_position_of_second_epoch() {
+        check_position((1, 0, 0).into());
     }
 }
@@ -14,6 +14,7 @@ use crate::prelude::*;
 pub(crate) mod deserialize;
 pub(crate) mod serialize;
 
+pub mod binary;
 pub mod in_memory;
 pub use deserialize::{LoadCommitments, LoadHashes};
 pub use in_memory::InMemory;
@@ -225,6 +226,30 @@ pub enum Update {
     DeleteRange(DeleteRange),
 }
 
+impl Update {
+    /// Perform this update on a synchronous storage backend.
+    pub fn apply_to<W: Write>(self, writer: &mut W) -> Result<(), W::Error> {
+        match self {
+            Update::SetPosition(position) => writer.set_position(position),
+            Update::SetForgotten(forgotten) => writer.set_forgotten(forgotten),
+            Update::StoreHash(StoreHash {
+                position,
+                height,
+                hash,
+                essential,
+            }) => writer.add_hash(position, height, hash, essential),
+            Update::StoreCommitment(StoreCommitment {
+                position,
+                commitment,
+            }) => writer.add_commitment(position, commitment),
+            Update::DeleteRange(DeleteRange {
+                below_height,
+                positions,
+            }) => writer.delete_range(below_height, positions),
+        }
+    }
+}
+
 /// An update to the underlying storage that constitutes storing a single hash.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoreHash {
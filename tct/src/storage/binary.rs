@@ -0,0 +1,259 @@
+//! A compact binary encoding of [`Update`]s, for persisting a [`Tree`](crate::Tree) as a byte
+//! stream, or sending a syncing client the changes to a tree since the position it last stored.
+//!
+//! The stream starts with a header identifying the format, followed by one record per update. Each
+//! record is a tag byte followed by the update's fields, with positions and forgotten versions as
+//! little-endian `u64`s and hashes and commitments as their 32-byte encodings.
+//!
+//! To persist a tree, write the [`Tree::updates`](crate::Tree::updates) since the position and
+//! forgotten version last written, and to load it, [`apply`] every stored update to a [`Write`]
+//! storage backend such as [`InMemory`](super::InMemory), then use
+//! [`Tree::from_reader`](crate::Tree::from_reader).
+
+use std::io;
+
+use decaf377::FieldExt;
+
+use super::*;
+
+/// The header at the start of every stream of updates: a magic string and a version byte.
+const HEADER: [u8; 4] = *b"TCT\x01";
+
+const SET_POSITION: u8 = 0;
+const SET_POSITION_FULL: u8 = 1;
+const SET_FORGOTTEN: u8 = 2;
+const STORE_COMMITMENT: u8 = 3;
+const STORE_HASH: u8 = 4;
+const STORE_ESSENTIAL_HASH: u8 = 5;
+const DELETE_RANGE: u8 = 6;
+
+/// An error decoding a stream of updates.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    /// The underlying reader failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The stream did not start with the expected header.
+    #[error("stream does not start with a tree update header")]
+    InvalidHeader,
+    /// A record had an unknown tag.
+    #[error("unknown update tag {0}")]
+    UnknownTag(u8),
+    /// A stored hash was not a valid field element.
+    #[error("invalid hash at position {position:?}, height {height}")]
+    InvalidHash {
+        /// The position of the hash.
+        position: Position,
+        /// The height of the hash.
+        height: u8,
+    },
+    /// A stored commitment was not a valid field element.
+    #[error("invalid commitment at position {position:?}")]
+    InvalidCommitment {
+        /// The position of the commitment.
+        position: Position,
+    },
+}
+
+/// Write the `updates` to `writer` as a binary stream.
+pub fn write<W: io::Write>(
+    writer: &mut W,
+    updates: impl IntoIterator<Item = Update>,
+) -> io::Result<()> {
+    writer.write_all(&HEADER)?;
+    for update in updates {
+        match update {
+            Update::SetPosition(StoredPosition::Position(position)) => {
+                writer.write_all(&[SET_POSITION])?;
+                writer.write_all(&u64::from(position).to_le_bytes())?;
+            }
+            Update::SetPosition(StoredPosition::Full) => writer.write_all(&[SET_POSITION_FULL])?,
+            Update::SetForgotten(forgotten) => {
+                writer.write_all(&[SET_FORGOTTEN])?;
+                writer.write_all(&u64::from(forgotten).to_le_bytes())?;
+            }
+            Update::StoreCommitment(StoreCommitment {
+                position,
+                commitment,
+            }) => {
+                writer.write_all(&[STORE_COMMITMENT])?;
+                writer.write_all(&u64::from(position).to_le_bytes())?;
+                writer.write_all(&commitment.0.to_bytes())?;
+            }
+            Update::StoreHash(StoreHash {
+                position,
+                height,
+                hash,
+                essential,
+            }) => {
+                let tag = if essential {
+                    STORE_ESSENTIAL_HASH
+                } else {
+                    STORE_HASH
+                };
+                writer.write_all(&[tag, height])?;
+                writer.write_all(&u64::from(position).to_le_bytes())?;
+                writer.write_all(&hash.to_bytes())?;
+            }
+            Update::DeleteRange(DeleteRange {
+                below_height,
+                positions,
+            }) => {
+                writer.write_all(&[DELETE_RANGE, below_height])?;
+                writer.write_all(&u64::from(positions.start).to_le_bytes())?;
+                writer.write_all(&u64::from(positions.end).to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a binary stream of updates, as written by [`write`], from `reader` until it ends.
+pub fn read<R: io::Read>(reader: &mut R) -> Result<Vec<Update>, DecodeError> {
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header)?;
+    if header != HEADER {
+        return Err(DecodeError::InvalidHeader);
+    }
+
+    let mut updates = Vec::new();
+    loop {
+        let mut tag = [0u8; 1];
+        if reader.read(&mut tag)? == 0 {
+            return Ok(updates);
+        }
+
+        let update = match tag[0] {
+            SET_POSITION => Update::SetPosition(StoredPosition::Position(read_u64(reader)?.into())),
+            SET_POSITION_FULL => Update::SetPosition(StoredPosition::Full),
+            SET_FORGOTTEN => Update::SetForgotten(read_u64(reader)?.into()),
+            STORE_COMMITMENT => {
+                let position = read_u64(reader)?.into();
+                let commitment = Commitment::try_from(read_bytes(reader)?)
+                    .map_err(|_| DecodeError::InvalidCommitment { position })?;
+                Update::StoreCommitment(StoreCommitment {
+                    position,
+                    commitment,
+                })
+            }
+            tag @ (STORE_HASH | STORE_ESSENTIAL_HASH) => {
+                let height = read_u8(reader)?;
+                let position = read_u64(reader)?.into();
+                let hash = Hash::from_bytes(read_bytes(reader)?)
+                    .map_err(|_| DecodeError::InvalidHash { position, height })?;
+                Update::StoreHash(StoreHash {
+                    position,
+                    height,
+                    hash,
+                    essential: tag == STORE_ESSENTIAL_HASH,
+                })
+            }
+            DELETE_RANGE => {
+                let below_height = read_u8(reader)?;
+                let start = read_u64(reader)?.into();
+                let end = read_u64(reader)?.into();
+                Update::DeleteRange(DeleteRange {
+                    below_height,
+                    positions: start..end,
+                })
+            }
+            tag => return Err(DecodeError::UnknownTag(tag)),
+        };
+        updates.push(update);
+    }
+}
+
+/// Apply each of the `updates` to the `writer`, in order.
+pub fn apply<W: Write>(
+    writer: &mut W,
+    updates: impl IntoIterator<Item = Update>,
+) -> Result<(), W::Error> {
+    for update in updates {
+        update.apply_to(writer)?;
+    }
+    Ok(())
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u64<R: io::Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_bytes<R: io::Read>(reader: &mut R) -> io::Result<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use decaf377::Fq;
+
+    use super::*;
+    use crate::{Tree, Witness};
+
+    fn load(bytes: &[u8], storage: &mut InMemory) -> Tree {
+        let updates = read(&mut &bytes[..]).unwrap();
+        apply(storage, updates).unwrap();
+        Tree::from_reader(storage).unwrap()
+    }
+
+    #[test]
+    fn full_and_incremental_roundtrip() {
+        let mut tree = Tree::new();
+        for i in 0..10u64 {
+            let witness = if i % 3 == 0 {
+                Witness::Keep
+            } else {
+                Witness::Forget
+            };
+            tree.insert(witness, Commitment(Fq::from(i))).unwrap();
+        }
+        tree.end_block().unwrap();
+
+        let mut full = Vec::new();
+        write(
+            &mut full,
+            tree.updates(StoredPosition::default(), Forgotten::default()),
+        )
+        .unwrap();
+        let mut storage = InMemory::new();
+        assert_eq!(load(&full, &mut storage), tree);
+
+        // The client has stored the tree up to this point, and only needs the changes since.
+        let (last_position, last_forgotten) = (storage.position(), storage.forgotten());
+        tree.insert(Witness::Keep, Commitment(Fq::from(100u64)))
+            .unwrap();
+        tree.forget(Commitment(Fq::from(3u64)));
+        tree.end_epoch().unwrap();
+
+        let mut delta = Vec::new();
+        write(&mut delta, tree.updates(last_position, last_forgotten)).unwrap();
+        assert!(delta.len() < full.len());
+        assert_eq!(load(&delta, &mut storage), tree);
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        assert!(matches!(
+            read(&mut &b"TCT\x02"[..]),
+            Err(DecodeError::InvalidHeader)
+        ));
+        assert!(matches!(
+            read(&mut &b"TCT\x01\xff"[..]),
+            Err(DecodeError::UnknownTag(0xff))
+        ));
+        // A truncated record is an error, not the end of the stream.
+        assert!(matches!(
+            read(&mut &b"TCT\x01\x00\x01"[..]),
+            Err(DecodeError::Io(_))
+        ));
+    }
+}
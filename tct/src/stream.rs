@@ -0,0 +1,179 @@
+//! Building a [`Tree`] from an asynchronous stream of commitments, as they arrive.
+
+use futures::{Stream, StreamExt};
+
+use crate::error::ExtendError;
+use crate::prelude::*;
+
+/// How many commitments to insert before yielding to the async runtime, so that a source that is
+/// always ready doesn't block other tasks for the whole of a large block.
+const YIELD_INTERVAL: usize = 1024;
+
+/// An item in a stream of insertions into a [`Tree`], for [`Tree::extend_from_stream`].
+///
+/// A stream of `(Commitment, Witness)` pairs can be used directly, but then its blocks and epochs
+/// are never ended, so progress is only reported at its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamItem {
+    /// Insert a commitment into the current block.
+    Commitment(Commitment, Witness),
+    /// End the current block, as with [`Tree::end_block`].
+    EndBlock,
+    /// End the current epoch, as with [`Tree::end_epoch`].
+    EndEpoch,
+}
+
+impl From<(Commitment, Witness)> for StreamItem {
+    fn from((commitment, witness): (Commitment, Witness)) -> Self {
+        StreamItem::Commitment(commitment, witness)
+    }
+}
+
+/// A snapshot of a [`Tree`] being extended by [`Tree::extend_from_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The position of the tree, or `None` if it is full.
+    pub position: Option<Position>,
+    /// The root of the tree.
+    pub root: Root,
+}
+
+impl Tree {
+    /// Insert the commitments from a stream into this [`Tree`] as they arrive, returning a stream
+    /// of [`Progress`] snapshots of the tree at the end of each block and epoch, and when the
+    /// source ends.
+    ///
+    /// The returned stream must be polled to make progress. Dropping it cancels the insertion, at
+    /// which point the tree contains every commitment inserted so far: each insertion is completed
+    /// before the source is polled again, so the tree is never left partway through one. A
+    /// cancelled block is not ended, so more commitments can be inserted into it later.
+    ///
+    /// # Errors
+    ///
+    /// The returned stream ends after yielding an [`ExtendError`] if the tree, or its current
+    /// epoch or block, becomes full.
+    pub fn extend_from_stream<'a, S>(
+        &'a mut self,
+        source: S,
+    ) -> impl Stream<Item = Result<Progress, ExtendError>> + 'a
+    where
+        S: Stream + Unpin + 'a,
+        S::Item: Into<StreamItem>,
+    {
+        futures::stream::unfold(Some((self, source)), |state| async move {
+            let (tree, mut source) = state?;
+            let mut inserted = 0;
+
+            while let Some(item) = source.next().await {
+                let result = match item.into() {
+                    StreamItem::Commitment(commitment, witness) => {
+                        if let Err(error) = tree.insert(witness, commitment) {
+                            return Some((Err(error.into()), None));
+                        }
+                        inserted += 1;
+                        if inserted % YIELD_INTERVAL == 0 {
+                            tokio::task::yield_now().await;
+                        }
+                        continue;
+                    }
+                    StreamItem::EndBlock => tree.end_block().map(|_| ()).map_err(Into::into),
+                    StreamItem::EndEpoch => tree.end_epoch().map(|_| ()).map_err(Into::into),
+                };
+
+                return Some(match result {
+                    Ok(()) => (Ok(tree.progress()), Some((tree, source))),
+                    Err(error) => (Err(error), None),
+                });
+            }
+
+            // The source has ended, so report the final state of the tree.
+            Some((Ok(tree.progress()), None))
+        })
+    }
+
+    fn progress(&self) -> Progress {
+        Progress {
+            position: self.position(),
+            root: self.root(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use decaf377::Fq;
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn items() -> Vec<StreamItem> {
+        let mut items = Vec::new();
+        for block in 0..3u64 {
+            for i in 0..10 {
+                let commitment = Commitment(Fq::from(block * 10 + i));
+                items.push((commitment, Witness::Keep).into());
+            }
+            items.push(StreamItem::EndBlock);
+        }
+        items.push(StreamItem::EndEpoch);
+        items.push((Commitment(Fq::from(100u64)), Witness::Forget).into());
+        items
+    }
+
+    #[test]
+    fn extend_from_stream_matches_insertion() {
+        let mut expected = Tree::new();
+        let mut snapshots = Vec::new();
+        for item in items() {
+            match item {
+                StreamItem::Commitment(commitment, witness) => {
+                    expected.insert(witness, commitment).unwrap();
+                    continue;
+                }
+                StreamItem::EndBlock => {
+                    expected.end_block().unwrap();
+                }
+                StreamItem::EndEpoch => {
+                    expected.end_epoch().unwrap();
+                }
+            }
+            snapshots.push(expected.progress());
+        }
+        snapshots.push(expected.progress());
+
+        let mut tree = Tree::new();
+        let progress = block_on(
+            tree.extend_from_stream(futures::stream::iter(items()))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            progress.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            snapshots
+        );
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn cancelled_extension_keeps_inserted_commitments() {
+        let mut tree = Tree::new();
+        {
+            let progress = tree.extend_from_stream(futures::stream::iter(items()));
+            futures::pin_mut!(progress);
+            // Stop after the first block
+            block_on(progress.next()).unwrap().unwrap();
+        }
+
+        let mut expected = Tree::new();
+        for item in items().into_iter().take(10) {
+            if let StreamItem::Commitment(commitment, witness) = item {
+                expected.insert(witness, commitment).unwrap();
+            }
+        }
+        expected.end_block().unwrap();
+        assert_eq!(tree, expected);
+
+        // The tree can still be extended after cancellation.
+        tree.insert(Witness::Keep, Commitment(Fq::from(1000u64)))
+            .unwrap();
+    }
+}
@@ -319,6 +319,92 @@ impl<'tree> Node<'tree> {
             Place::Complete
         }
     }
+
+    /// Whether this node's hash is held in memory, either because it's cached or because the
+    /// node's contents were pruned down to its hash, rather than having to be recomputed from its
+    /// children.
+    pub fn is_hash_cached(&self) -> bool {
+        self.cached_hash().is_some()
+    }
+}
+
+/// Traverse `node` and every node beneath it, in pre-order from left to right.
+pub fn traverse(node: Node<'_>) -> Traverse<'_> {
+    Traverse { stack: vec![node] }
+}
+
+/// The internal nodes at or beneath `node` which are in the given [`Place`], in pre-order.
+///
+/// Complete nodes never change, so their hashes are computed at most once, while the hashes of
+/// nodes on the frontier are recomputed whenever something is inserted beneath them. Use
+/// [`Node::height`] and [`Node::is_hash_cached`] on each node to account for its cost.
+pub fn internal_nodes(node: Node<'_>, place: Place) -> impl Iterator<Item = Node<'_>> {
+    traverse(node)
+        .filter(move |node| matches!(node.kind(), Kind::Internal { .. }) && node.place() == place)
+}
+
+/// The nodes beneath `node` at the given height, from left to right, without descending beneath
+/// them.
+///
+/// Each of these is the root of an independent subtree, so a storage engine can use them to shard
+/// the tree, e.g. by block with height 8, or by epoch with height 16. Subtrees which were entirely
+/// forgotten are represented only by their hash, with no children, and those whose positions have
+/// not been filled yet are absent.
+pub fn subtrees(node: Node<'_>, height: u8) -> Subtrees<'_> {
+    Subtrees {
+        height,
+        traverse: traverse(node),
+    }
+}
+
+/// An iterator over a node and every node beneath it, in pre-order from left to right.
+///
+/// Returned by [`traverse`].
+#[derive(Clone)]
+pub struct Traverse<'tree> {
+    stack: Vec<Node<'tree>>,
+}
+
+impl<'tree> Iterator for Traverse<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
+}
+
+/// An iterator over the nodes at some height beneath a node.
+///
+/// Returned by [`subtrees`].
+#[derive(Clone)]
+pub struct Subtrees<'tree> {
+    height: u8,
+    traverse: Traverse<'tree>,
+}
+
+impl<'tree> Iterator for Subtrees<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.traverse.stack.last() {
+                None => return None,
+                Some(node) if node.height() <= self.height => {
+                    // Don't descend beneath the nodes we're looking for, since none of the nodes
+                    // beneath them can be at the right height.
+                    let node = self.traverse.stack.pop()?;
+                    if node.height() == self.height {
+                        return Some(node);
+                    }
+                }
+                Some(_) => {
+                    self.traverse.next();
+                }
+            }
+        }
+    }
 }
 
 mod sealed {
@@ -412,6 +498,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn traversals_correct() {
+        let mut tree = crate::Tree::new();
+        for i in 0..100u16 {
+            tree.insert(crate::Witness::Keep, Commitment(i.into()))
+                .unwrap();
+        }
+
+        // A pre-order traversal visits parents before children, from left to right.
+        let nodes = traverse(tree.structure()).collect::<Vec<_>>();
+        assert_eq!(nodes[0].height(), 24);
+        let leaves = nodes
+            .iter()
+            .filter(|node| node.height() == 0)
+            .map(|node| u64::from(node.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, (0..100).collect::<Vec<_>>());
+
+        // Every internal node is either complete or on the frontier, and any cached hash is the
+        // node's hash.
+        let complete = internal_nodes(tree.structure(), Place::Complete).count();
+        let frontier = internal_nodes(tree.structure(), Place::Frontier).count();
+        assert_eq!(complete + frontier, nodes.len() - leaves.len());
+        assert_eq!(frontier, 24);
+        for node in internal_nodes(tree.structure(), Place::Complete) {
+            assert!(node
+                .cached_hash()
+                .map_or(true, |cached| cached == node.hash()));
+        }
+
+        // The 100 leaves fill 25 subtrees of height 1, and 2 of height 3.
+        let subtrees_at = |height| subtrees(tree.structure(), height).collect::<Vec<_>>();
+        assert_eq!(subtrees_at(1).len(), 25);
+        assert_eq!(subtrees_at(3).len(), 2);
+        assert!(subtrees_at(3).iter().all(|node| node.height() == 3));
+        assert_eq!(subtrees_at(24).len(), 1);
+    }
+
     #[test]
     fn height_correct() {
         const MAX_SIZE_TO_TEST: u16 = 100;
@@ -8,7 +8,10 @@ use penumbra_proto::{core::crypto::v1alpha1 as pb, Protobuf};
 
 use crate::error::*;
 use crate::prelude::{Witness as _, *};
-use crate::Witness;
+use crate::{
+    delta::{Change, Delta},
+    BlockProof, EpochProof, FrontierProof, Witness,
+};
 
 #[path = "epoch.rs"]
 pub(crate) mod epoch;
@@ -20,6 +23,15 @@ pub(crate) use epoch::block;
 pub struct Tree {
     index: HashedMap<Commitment, index::within::Tree>,
     inner: Arc<frontier::Top<frontier::Tier<frontier::Tier<frontier::Item>>>>,
+    /// Snapshots of the tree at retained anchors, which are not serialized.
+    #[serde(skip)]
+    retained: HashedMap<Root, Tree>,
+    /// Which hashes to keep cached in complete tiers, which is not serialized.
+    #[serde(skip)]
+    hash_retention: HashRetention,
+    /// The changes made since this tree was forked, if it is a fork, which are not serialized.
+    #[serde(skip)]
+    delta: Option<Box<Delta>>,
 }
 
 impl Default for Tree {
@@ -27,10 +39,38 @@ impl Default for Tree {
         Self {
             index: HashedMap::default(),
             inner: Arc::new(frontier::Top::new(frontier::TrackForgotten::Yes)),
+            retained: HashedMap::default(),
+            hash_retention: HashRetention::default(),
+            delta: None,
         }
     }
 }
 
+/// Which of the hashes of the complete (finalized) parts of a [`Tree`] are kept cached, as set by
+/// [`Tree::set_hash_retention`].
+///
+/// Whatever the policy, the hashes of pruned subtrees and of witnessed commitments are always kept,
+/// since they can't be recomputed, as are the hashes on the frontier of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashRetention {
+    /// Keep the hash of every complete node cached.
+    All,
+    /// Keep only the hashes at the roots of complete blocks and epochs cached, along with those of
+    /// the complete nodes adjacent to the frontier, recomputing the hashes of the nodes beneath
+    /// them whenever they are needed.
+    ///
+    /// This makes witnessing and forgetting commitments slower, since they need the hashes of the
+    /// nodes beneath the roots, and means that incremental serialization doesn't write those hashes
+    /// to storage, to be recomputed when the tree is loaded instead.
+    TierRoots,
+}
+
+impl Default for HashRetention {
+    fn default() -> Self {
+        HashRetention::All
+    }
+}
+
 impl PartialEq for Tree {
     fn eq(&self, other: &Tree) -> bool {
         self.position() == other.position() // two trees could have identical contents but different positions
@@ -92,12 +132,35 @@ impl Display for Root {
 }
 
 /// The index of a [`Commitment`] within a [`Tree`].
-#[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
-)]
+///
+/// A position is serialized as its epoch, block and commitment indices. Human-readable formats
+/// also accept the `u64` the indices pack into, as written by older versions, and both forms are
+/// checked to lie within a [`Tree`] when deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 #[cfg_attr(any(test, feature = "arbitrary"), derive(proptest_derive::Arbitrary))]
 pub struct Position(index::within::Tree);
 
+/// The number of epochs in a [`Tree`], of blocks in an epoch, and of commitments in a block: each
+/// is a quadtree of height 8.
+const TIER_CAPACITY: u64 = 4u64.pow(8);
+
+/// An error occurred when converting indices or a `u64` to a [`Position`], because it does not lie
+/// within a [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PositionDecodeError {
+    /// One of the indices was too large.
+    #[error("{kind} index {index} is out of range, must be less than 65536")]
+    IndexOutOfRange {
+        /// Which index was too large: the epoch, block or commitment index.
+        kind: &'static str,
+        /// The index.
+        index: u64,
+    },
+    /// The `u64` position was past the end of the tree.
+    #[error("position {0} is past the end of the tree")]
+    PastEnd(u64),
+}
+
 impl Position {
     /// The index of the [`Commitment`] to which this [`Position`] refers within its own block.
     pub fn commitment(&self) -> u16 {
@@ -115,6 +178,19 @@ impl Position {
     }
 }
 
+impl Position {
+    /// Convert a `u64` to a [`Position`], checking that it lies within a [`Tree`].
+    ///
+    /// Unlike `From<u64>`, which ignores any bits past the 48 used by a position, this should be
+    /// used for positions read from storage, which may not have been written by this crate.
+    pub fn checked_from_u64(position: u64) -> Result<Self, PositionDecodeError> {
+        if position >= TIER_CAPACITY.pow(3) {
+            return Err(PositionDecodeError::PastEnd(position));
+        }
+        Ok(position.into())
+    }
+}
+
 impl From<Position> for u64 {
     fn from(position: Position) -> Self {
         position.0.into()
@@ -143,6 +219,74 @@ impl From<Position> for (u16, u16, u16) {
     }
 }
 
+impl TryFrom<(u64, u64, u64)> for Position {
+    type Error = PositionDecodeError;
+
+    fn try_from((epoch, block, commitment): (u64, u64, u64)) -> Result<Self, Self::Error> {
+        let check = |kind, index: u64| {
+            u16::try_from(index).map_err(|_| PositionDecodeError::IndexOutOfRange { kind, index })
+        };
+        Ok((
+            check("epoch", epoch)?,
+            check("block", block)?,
+            check("commitment", commitment)?,
+        )
+            .into())
+    }
+}
+
+/// The indices of a serialized [`Position`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Position")]
+struct PositionIndices<T> {
+    epoch: T,
+    block: T,
+    commitment: T,
+}
+
+/// A [`Position`] deserialized from a human-readable format.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HumanReadablePosition {
+    Indices(PositionIndices<u64>),
+    Packed(u64),
+}
+
+impl Serialize for Position {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PositionIndices {
+            epoch: self.epoch(),
+            block: self.block(),
+            commitment: self.commitment(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            // Each index is a `u16`, so is always in range.
+            let PositionIndices {
+                epoch,
+                block,
+                commitment,
+            } = PositionIndices::<u16>::deserialize(deserializer)?;
+            return Ok((epoch, block, commitment).into());
+        }
+
+        match HumanReadablePosition::deserialize(deserializer)? {
+            HumanReadablePosition::Indices(PositionIndices {
+                epoch,
+                block,
+                commitment,
+            }) => Position::try_from((epoch, block, commitment)),
+            HumanReadablePosition::Packed(position) => Position::checked_from_u64(position),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
 impl Tree {
     /// Create a new empty [`Tree`] for storing all commitments to the end of time.
     pub fn new() -> Self {
@@ -157,6 +301,40 @@ impl Tree {
         Self {
             index,
             inner: Arc::new(inner),
+            retained: HashedMap::default(),
+            hash_retention: HashRetention::default(),
+            delta: None,
+        }
+    }
+
+    /// Set which of the hashes of the finalized blocks and epochs of this [`Tree`] are kept cached.
+    ///
+    /// Changing the policy to [`HashRetention::TierRoots`] evicts the hashes throughout the tree,
+    /// and from then on, each block and epoch is evicted as it ends. Changing it back to
+    /// [`HashRetention::All`] recomputes every evicted hash. Either way, this copies any structure
+    /// the tree shares with its clones, including its retained anchors.
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_hash_retention(&mut self, policy: HashRetention) {
+        if policy != self.hash_retention {
+            let inner = Arc::make_mut(&mut self.inner);
+            match policy {
+                HashRetention::All => inner.finish_initialize(),
+                HashRetention::TierRoots => inner.evict_hashes_below(),
+            }
+            self.hash_retention = policy;
+        }
+    }
+
+    /// Get which of the hashes of the finalized blocks and epochs of this [`Tree`] are kept cached.
+    pub fn hash_retention(&self) -> HashRetention {
+        self.hash_retention
+    }
+
+    // Apply the hash retention policy to the latest epoch, the only place where blocks and epochs
+    // can have been finalized since it was last applied.
+    fn retain_hashes(&mut self) {
+        if self.hash_retention == HashRetention::TierRoots {
+            Arc::make_mut(&mut self.inner).update(|epoch| epoch.evict_hashes_below());
         }
     }
 
@@ -192,11 +370,40 @@ impl Tree {
         witness: Witness,
         commitment: Commitment,
     ) -> Result<Position, InsertError> {
-        let item = match witness {
-            Witness::Keep => commitment.into(),
-            Witness::Forget => Hash::of(commitment).into(),
-        };
+        self.insert_item(witness, commitment, witness.item(commitment))
+    }
+
+    /// Add a batch of [`Commitment`]s to the most recent block of the most recent epoch of this
+    /// [`Tree`], in order.
+    ///
+    /// This is equivalent to calling [`insert`](Tree::insert) on each commitment, but is much
+    /// faster when syncing many commitments: the commitments are hashed in parallel up front, and
+    /// as with every insertion, the hashes along the frontier of the tree are not recomputed until
+    /// the next call to [`root`](Tree::root).
+    ///
+    /// If successful, returns the [`Position`] at which each commitment was inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError`] under the same conditions as [`insert`](Tree::insert), in which case
+    /// the commitments before the one that could not be inserted remain inserted.
+    #[instrument(level = "trace", skip(self, batch))]
+    pub fn insert_batch(
+        &mut self,
+        batch: impl IntoIterator<Item = (Witness, Commitment)>,
+    ) -> Result<Vec<Position>, InsertError> {
+        Witness::items(batch)
+            .into_iter()
+            .map(|(witness, commitment, item)| self.insert_item(witness, commitment, item))
+            .collect()
+    }
 
+    fn insert_item(
+        &mut self,
+        witness: Witness,
+        commitment: Commitment,
+        item: Item,
+    ) -> Result<Position, InsertError> {
         // Get the position of the insertion, if it would succeed
         let position = (self.inner.position().ok_or(InsertError::Full)?).into();
 
@@ -254,6 +461,8 @@ impl Tree {
             }
         }
 
+        self.record(|| Change::Insert(witness, commitment));
+
         let position = Position(position);
         trace!(?position);
         Ok(position)
@@ -291,6 +500,164 @@ impl Tree {
         Some(proof)
     }
 
+    /// Get a [`Proof`] of inclusion for the commitment, first inserting it with [`Witness::Keep`]
+    /// into the most recent block of the most recent epoch if it is not already witnessed.
+    ///
+    /// This combines [`insert`](Tree::insert) and [`witness`](Tree::witness) into a single step,
+    /// so the returned [`Proof`] is always for the position returned alongside it, and verifies
+    /// against the [`root`](Tree::root) of the tree immediately afterwards. If the commitment was
+    /// already witnessed, nothing is inserted and its existing position is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError`] if the commitment is not witnessed and could not be inserted, under
+    /// the same conditions as [`insert`](Tree::insert).
+    #[instrument(level = "trace", skip(self))]
+    pub fn witness_or_insert(
+        &mut self,
+        commitment: Commitment,
+    ) -> Result<(Position, Proof), InsertError> {
+        let position = match self.position_of(commitment) {
+            Some(position) => position,
+            None => self.insert(Witness::Keep, commitment)?,
+        };
+        let proof = self
+            .witness(commitment)
+            .expect("commitment must be witnessed because it was indexed or just inserted");
+        debug_assert_eq!(proof.position(), position);
+        Ok((position, proof))
+    }
+
+    /// Refresh a [`Proof`] produced by this tree at an earlier point, so that it verifies against
+    /// the current root of the tree.
+    ///
+    /// Proofs become stale as the tree grows, since the authentication path changes as new
+    /// commitments are inserted. As long as the commitment is still witnessed at the same position,
+    /// the current authentication path is read from the hashes the tree retains for it, without
+    /// re-inserting or re-hashing anything.
+    ///
+    /// Returns `None` if the proof's commitment has since been forgotten, or if it is witnessed at a
+    /// different position than the one in the proof.
+    #[instrument(level = "trace", skip(self))]
+    pub fn refresh_proof(&self, proof: &Proof) -> Option<Proof> {
+        let commitment = proof.commitment();
+        if self.position_of(commitment)? != proof.position() {
+            trace!("witnessed at a different position");
+            return None;
+        }
+        self.witness(commitment)
+    }
+
+    /// Retain a snapshot of the tree at its current root, so that proofs against that root can
+    /// later be produced by [`Tree::witness_at`], even after the tree has grown or commitments have
+    /// been forgotten.
+    ///
+    /// Snapshots share all their structure with the tree, so retaining one is cheap, but the hashes
+    /// needed to witness the commitments witnessed at the time of the snapshot are kept in memory
+    /// until it is released by [`Tree::release_anchor`]. Retained snapshots are not serialized.
+    ///
+    /// Returns the retained anchor.
+    #[instrument(level = "trace", skip(self))]
+    pub fn retain_anchor(&mut self) -> Root {
+        let anchor = self.root();
+        let mut snapshot = self.clone();
+        snapshot.retained = HashedMap::default();
+        snapshot.delta = None;
+        self.retained.insert(anchor, snapshot);
+        trace!(?anchor);
+        anchor
+    }
+
+    /// Release the snapshot retained at the given anchor.
+    ///
+    /// Returns `true` if the anchor was retained, and `false` if it was not.
+    #[instrument(level = "trace", skip(self))]
+    pub fn release_anchor(&mut self, anchor: Root) -> bool {
+        self.retained.remove(&anchor).is_some()
+    }
+
+    /// Get the anchors at which snapshots are currently retained, in no particular order.
+    pub fn retained_anchors(&self) -> impl Iterator<Item = Root> + '_ {
+        self.retained.keys().copied()
+    }
+
+    /// Get a [`Proof`] of inclusion for the commitment that verifies against the given `anchor`,
+    /// which must be either the current root of the tree or an anchor retained by
+    /// [`Tree::retain_anchor`].
+    ///
+    /// If the anchor is not retained, or the commitment was not witnessed when the anchor was
+    /// retained, return `None`.
+    #[instrument(level = "trace", skip(self))]
+    pub fn witness_at(&self, anchor: Root, commitment: Commitment) -> Option<Proof> {
+        if let Some(snapshot) = self.retained.get(&anchor) {
+            snapshot.witness(commitment)
+        } else if anchor == self.root() {
+            self.witness(commitment)
+        } else {
+            trace!("anchor not retained");
+            None
+        }
+    }
+
+    /// Fork this [`Tree`], e.g. to speculatively extend it with a candidate block.
+    ///
+    /// The fork shares all its structure with this tree, and each of them copies only the parts of
+    /// the frontier that it goes on to change, so forking is cheap. Every change made to the fork is
+    /// recorded in its [`Delta`], which can be merged back into this tree with [`Tree::apply`].
+    /// Retained anchors are not carried over to the fork.
+    #[instrument(level = "trace", skip(self))]
+    pub fn fork(&self) -> Tree {
+        Tree {
+            index: self.index.clone(),
+            inner: self.inner.clone(),
+            retained: HashedMap::default(),
+            hash_retention: self.hash_retention,
+            delta: Some(Box::new(Delta::new(self.root(), self.position()))),
+        }
+    }
+
+    /// Get the changes made to this [`Tree`] since it was [forked](Tree::fork), or `None` if it is
+    /// not a fork.
+    pub fn delta(&self) -> Option<&Delta> {
+        self.delta.as_deref()
+    }
+
+    /// Merge the changes made to a fork of this [`Tree`] back into it, as if they had been made to
+    /// this tree directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApplyError`] if the delta was not forked from this tree in its current state, e.g.
+    /// because the delta of another fork has been applied since, or if any of its changes could
+    /// not be made. Either way, this tree is left unchanged.
+    #[instrument(level = "trace", skip(self, delta))]
+    pub fn apply(&mut self, delta: &Delta) -> Result<(), ApplyError> {
+        if delta.base_root() != self.root() || delta.base_position() != self.position() {
+            let error = ApplyError::BaseMismatch;
+            error!(%error);
+            return Err(error);
+        }
+
+        // Make the changes to a copy, sharing all its structure with this tree, so that this tree
+        // is untouched if any of them fail
+        let mut tree = self.clone();
+        delta.replay(&mut tree).map_err(|error| {
+            error!(%error);
+            error
+        })?;
+        *self = tree;
+
+        trace!(changes = delta.len());
+        Ok(())
+    }
+
+    // Record a change in the delta of this tree, if it is a fork.
+    fn record(&mut self, change: impl FnOnce() -> Change) {
+        if let Some(delta) = &mut self.delta {
+            delta.push(change());
+        }
+    }
+
     /// Forget about the witness for the given [`Commitment`].
     ///
     /// Returns `true` if the commitment was previously witnessed (and now is forgotten), and `false` if
@@ -307,6 +674,7 @@ impl Tree {
             debug_assert!(forgotten);
             // Remove this entry from the index
             self.index.remove(&commitment);
+            self.record(|| Change::Forget(commitment));
         }
 
         trace!(?forgotten);
@@ -351,12 +719,19 @@ impl Tree {
         &mut self,
         block: impl Into<block::Finalized>,
     ) -> Result<block::Root, InsertBlockError> {
+        let block = block.into();
+        let recorded = self.delta.as_ref().map(|_| block.clone());
+
         // We split apart the inside so that we get the right instrumention when this is called as
         // an inner function in `end_block`
         let block_root = self.insert_block_uninstrumented(block).map_err(|error| {
             error!(%error);
             error
         })?;
+        if let Some(block) = recorded {
+            self.record(|| Change::InsertBlock(block));
+        }
+        self.retain_hashes();
         trace!(?block_root);
         Ok(block_root)
     }
@@ -492,6 +867,8 @@ impl Tree {
                 })?;
         };
 
+        self.record(|| Change::EndBlock);
+        self.retain_hashes();
         trace!(finalized_block_root = ?finalized_root);
         Ok(finalized_root)
     }
@@ -546,12 +923,19 @@ impl Tree {
         &mut self,
         epoch: impl Into<epoch::Finalized>,
     ) -> Result<epoch::Root, InsertEpochError> {
+        let epoch = epoch.into();
+        let recorded = self.delta.as_ref().map(|_| epoch.clone());
+
         // We split apart the inside so that we get the right instrumention when this is called as
         // an inner function in `end_epoch`
         let epoch_root = self.insert_epoch_uninstrumented(epoch).map_err(|error| {
             error!(%error);
             error
         })?;
+        if let Some(epoch) = recorded {
+            self.record(|| Change::InsertEpoch(epoch));
+        }
+        self.retain_hashes();
         trace!(?epoch_root);
         Ok(epoch_root)
     }
@@ -639,6 +1023,8 @@ impl Tree {
                 })?;
         };
 
+        self.record(|| Change::EndEpoch);
+        self.retain_hashes();
         trace!(finalized_epoch_root = ?finalized_root);
         Ok(finalized_root)
     }
@@ -663,6 +1049,109 @@ impl Tree {
         root
     }
 
+    /// Get the root hash of the block with the given index in the epoch with the given index.
+    ///
+    /// If the block is the most recent one and has not ended, this is its
+    /// [`current_block_root`](Tree::current_block_root), which changes as commitments are inserted
+    /// into it.
+    ///
+    /// Returns `None` if the block has not begun, or if every commitment in its epoch was
+    /// forgotten, so that only the root of the epoch is known.
+    #[instrument(level = "trace", skip(self))]
+    pub fn block_root(&self, epoch: u16, block: u16) -> Option<block::Root> {
+        let root = self.block_proof(epoch, block).map(|proof| proof.root());
+        trace!(?root);
+        root
+    }
+
+    /// Get a [`BlockProof`] of the inclusion of the root of the block with the given index in the
+    /// epoch with the given index, against the current [`root`](Tree::root) of this [`Tree`].
+    ///
+    /// Returns `None` under the same conditions as [`block_root`](Tree::block_root).
+    #[instrument(level = "trace", skip(self))]
+    pub fn block_proof(&self, epoch: u16, block: u16) -> Option<BlockProof> {
+        let position = u64::from(Position::from((epoch, block, 0)));
+        let (root, auth_path) = self.path_to(8, position)?;
+        let auth_path = auth_path
+            .try_into()
+            .expect("path from the root to a block has 16 levels");
+        Some(BlockProof::new(epoch, block, block::Root(root), auth_path))
+    }
+
+    /// Get the root hash of the epoch with the given index.
+    ///
+    /// If the epoch is the most recent one and has not ended, this is its
+    /// [`current_epoch_root`](Tree::current_epoch_root), which changes as commitments are inserted
+    /// into it.
+    ///
+    /// Returns `None` if the epoch has not begun.
+    #[instrument(level = "trace", skip(self))]
+    pub fn epoch_root(&self, epoch: u16) -> Option<epoch::Root> {
+        let root = self.epoch_proof(epoch).map(|proof| proof.root());
+        trace!(?root);
+        root
+    }
+
+    /// Get an [`EpochProof`] of the inclusion of the root of the epoch with the given index, against
+    /// the current [`root`](Tree::root) of this [`Tree`].
+    ///
+    /// Returns `None` under the same conditions as [`epoch_root`](Tree::epoch_root).
+    #[instrument(level = "trace", skip(self))]
+    pub fn epoch_proof(&self, epoch: u16) -> Option<EpochProof> {
+        let position = u64::from(Position::from((epoch, 0, 0)));
+        let (root, auth_path) = self.path_to(16, position)?;
+        let auth_path = auth_path
+            .try_into()
+            .expect("path from the root to an epoch has 8 levels");
+        Some(EpochProof::new(epoch, epoch::Root(root), auth_path))
+    }
+
+    /// Walk down from the root of the tree to the node at `height` above the commitment at
+    /// `position`, returning its hash and the hashes of its siblings at each level, ordered from
+    /// root to leaf.
+    ///
+    /// Returns `None` if there is no such node, either because the position has not been reached,
+    /// or because the node is beneath one whose contents were forgotten.
+    fn path_to(&self, height: u8, position: u64) -> Option<(Hash, Vec<[Hash; 3]>)> {
+        let mut node = self.structure();
+        let mut auth_path = Vec::new();
+        while node.height() > height {
+            let children = node.children();
+            // Nodes on the frontier are padded with zero hashes on their right.
+            let mut hashes = [Hash::zero(); 4];
+            for (hash, child) in hashes.iter_mut().zip(&children) {
+                *hash = child.hash();
+            }
+            let (which_way, _) = WhichWay::at(node.height(), position);
+            auth_path.push(which_way.pick(hashes).1);
+
+            let index = (position >> (2 * (node.height() - 1))) & 0b11;
+            node = children.into_iter().nth(index as usize)?;
+        }
+        Some((node.hash(), auth_path))
+    }
+
+    /// Get a [`FrontierProof`] of the frontier of this [`Tree`] against its current
+    /// [`root`](Tree::root), which proves its [`position`](Tree::position).
+    #[instrument(level = "trace", skip(self))]
+    pub fn frontier_proof(&self) -> FrontierProof {
+        let mut node = self.structure();
+        let mut siblings = Vec::new();
+        // The last child of each node on the frontier is the next node on the frontier, and the
+        // children of a node on the frontier whose contents were forgotten are not known.
+        loop {
+            let children = node.children();
+            match children.split_last() {
+                Some((focus, left)) => {
+                    siblings.push(left.iter().map(|child| child.hash()).collect());
+                    node = *focus;
+                }
+                None => break,
+            }
+        }
+        FrontierProof::new(self.position(), siblings, node.hash())
+    }
+
     /// The position in this [`Tree`] at which the next [`Commitment`] would be inserted.
     ///
     /// If the [`Tree`] is full, returns `None`.
@@ -724,6 +1213,22 @@ impl Tree {
         crate::storage::serialize::Serializer::default().commitments(self)
     }
 
+    /// Get an iterator over all commitments currently witnessed in the tree, paired with their
+    /// positions, **ordered by position**.
+    ///
+    /// This yields the same commitments as [`commitments`](Tree::commitments), by traversing the
+    /// complete and frontier parts of the tree, but in the same `(Commitment, Position)` form as
+    /// [`commitments_unordered`](Tree::commitments_unordered). This allows a client to audit its
+    /// set of witnessed commitments, e.g. to find ones it no longer needs to witness and can
+    /// [`forget`](Tree::forget).
+    #[instrument(level = "trace", skip(self))]
+    pub fn witnessed_commitments(
+        &self,
+    ) -> impl Iterator<Item = (Commitment, Position)> + Send + Sync + '_ {
+        self.commitments()
+            .map(|(position, commitment)| (commitment, position))
+    }
+
     /// Get an iterator over all commitments currently witnessed in the tree.
     ///
     /// Unlike [`commitments`](Tree::commitments), this **does not** guarantee that commitments will
@@ -851,10 +1356,7 @@ impl From<frontier::Top<frontier::Tier<frontier::Tier<frontier::Item>>>> for Tre
         let mut index = HashedMap::default();
 
         // Traverse the tree to reconstruct the index
-        let mut stack = vec![Node::root(&inner)];
-        while let Some(node) = stack.pop() {
-            stack.extend(node.children());
-
+        for node in structure::traverse(Node::root(&inner)) {
             if let structure::Kind::Leaf {
                 commitment: Some(commitment),
             } = node.kind()
@@ -866,6 +1368,335 @@ impl From<frontier::Top<frontier::Tier<frontier::Tier<frontier::Item>>>> for Tre
         Self {
             inner: Arc::new(inner),
             index,
+            retained: HashedMap::default(),
+            hash_retention: HashRetention::default(),
+            delta: None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn batch(range: std::ops::Range<u64>) -> Vec<(Witness, Commitment)> {
+        range
+            .map(|i| {
+                let witness = if i % 2 == 0 {
+                    Witness::Keep
+                } else {
+                    Witness::Forget
+                };
+                (witness, Commitment(Fq::from(i)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hash_retention_preserves_roots_and_proofs() {
+        let mut tree = Tree::new();
+        for (witness, commitment) in batch(0..50) {
+            tree.insert(witness, commitment).unwrap();
+        }
+        tree.end_block().unwrap();
+        tree.end_epoch().unwrap();
+        for (witness, commitment) in batch(50..100) {
+            tree.insert(witness, commitment).unwrap();
+        }
+
+        let expected = tree.clone();
+        let proofs = |tree: &Tree| {
+            batch(0..100)
+                .into_iter()
+                .filter(|(witness, _)| *witness == Witness::Keep)
+                .map(|(_, commitment)| tree.witness(commitment).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let expected_proofs = proofs(&tree);
+
+        tree.set_hash_retention(HashRetention::TierRoots);
+        assert_eq!(tree.root(), expected.root());
+        assert_eq!(proofs(&tree), expected_proofs);
+
+        // Only the roots of the finalized epoch and its block are still cached, not the nodes
+        // beneath them
+        let first_at = |tree: &Tree, height| {
+            let mut node = tree.structure();
+            while node.height() > height {
+                node = node.children()[0];
+            }
+            node.cached_hash()
+        };
+        assert!(first_at(&tree, 16).is_some());
+        assert!(first_at(&tree, 15).is_none());
+        assert!(first_at(&tree, 8).is_some());
+        assert!(first_at(&tree, 7).is_none());
+
+        // Blocks and epochs are evicted as they end, and forgetting still works once evicted
+        tree.end_block().unwrap();
+        tree.end_epoch().unwrap();
+        assert!(tree.forget(Commitment(Fq::from(0u64))));
+        assert!(tree.forget(Commitment(Fq::from(50u64))));
+        let mut expected = expected;
+        expected.end_block().unwrap();
+        expected.end_epoch().unwrap();
+        expected.forget(Commitment(Fq::from(0u64)));
+        expected.forget(Commitment(Fq::from(50u64)));
+        assert_eq!(tree, expected);
+
+        tree.set_hash_retention(HashRetention::All);
+        assert!(first_at(&tree, 7).is_some());
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn frontier_proof_verifies() {
+        let mut tree = Tree::new();
+        tree.frontier_proof().verify(tree.root()).unwrap();
+
+        for range in [0..10, 10..11, 11..100] {
+            for (witness, commitment) in batch(range) {
+                tree.insert(witness, commitment).unwrap();
+                let proof = tree.frontier_proof();
+                assert_eq!(proof.position(), tree.position());
+                proof.verify(tree.root()).unwrap();
+            }
+            tree.end_block().unwrap();
+            tree.frontier_proof().verify(tree.root()).unwrap();
+        }
+        let previous_root = tree.root();
+        tree.end_epoch().unwrap();
+        tree.end_block().unwrap();
+
+        let proof = tree.frontier_proof();
+        proof.verify(tree.root()).unwrap();
+        assert!(proof.verify(previous_root).is_err());
+
+        // A proof claiming a different size for the tree doesn't verify.
+        let position = tree
+            .position()
+            .map(|position| (u64::from(position) + 1).into());
+        let forged = FrontierProof::new(position, proof.siblings().to_vec(), proof.focus());
+        assert!(forged.verify(tree.root()).is_err());
+    }
+
+    #[test]
+    fn block_and_epoch_roots_are_proven() {
+        let mut tree = Tree::new();
+        assert_eq!(tree.block_root(0, 0), None);
+        assert_eq!(tree.epoch_root(0), None);
+
+        let mut block_roots = Vec::new();
+        for range in [0..10, 10..20] {
+            for (witness, commitment) in batch(range) {
+                tree.insert(witness, commitment).unwrap();
+            }
+            block_roots.push(tree.end_block().unwrap());
+        }
+        let epoch_root = tree.end_epoch().unwrap();
+        for (witness, commitment) in batch(20..30) {
+            tree.insert(witness, commitment).unwrap();
+        }
+
+        assert_eq!(tree.block_root(0, 0), Some(block_roots[0]));
+        assert_eq!(tree.block_root(0, 1), Some(block_roots[1]));
+        assert_eq!(tree.block_root(0, 2), None);
+        assert_eq!(tree.epoch_root(0), Some(epoch_root));
+        assert_eq!(tree.block_root(1, 0), Some(tree.current_block_root()));
+        assert_eq!(tree.epoch_root(1), Some(tree.current_epoch_root()));
+        assert_eq!(tree.epoch_root(2), None);
+
+        let root = tree.root();
+        for (epoch, block) in [(0, 0), (0, 1), (1, 0)] {
+            tree.block_proof(epoch, block)
+                .unwrap()
+                .verify(root)
+                .unwrap();
+        }
+        for epoch in [0, 1] {
+            tree.epoch_proof(epoch).unwrap().verify(root).unwrap();
+        }
+
+        // A commitment is proven to be in its own block, and not in any other.
+        let proof = tree.witness(Commitment(Fq::from(12u64))).unwrap();
+        let block = tree.block_proof(0, 1).unwrap();
+        proof.verify_in_block(&block, root).unwrap();
+        let other = tree.block_proof(0, 0).unwrap();
+        assert!(proof.verify_in_block(&other, root).is_err());
+
+        // A proof claiming the block is somewhere else doesn't verify.
+        let moved = BlockProof::new(0, 2, block.root(), *block.auth_path());
+        assert!(moved.verify(root).is_err());
+
+        // Roots of ended blocks and epochs are still proven once everything in them is forgotten.
+        for i in (0..20u64).step_by(2) {
+            assert!(tree.forget(Commitment(Fq::from(i))));
+        }
+        assert_eq!(tree.epoch_root(0), Some(epoch_root));
+        tree.epoch_proof(0).unwrap().verify(tree.root()).unwrap();
+    }
+
+    #[test]
+    fn forks_apply_their_changes_to_the_base() {
+        let mut tree = Tree::new();
+        tree.insert_batch(batch(0..10)).unwrap();
+        let base_root = tree.root();
+
+        // Two candidate blocks are built speculatively without disturbing the tree.
+        let mut first = tree.fork();
+        first.insert_batch(batch(10..20)).unwrap();
+        assert!(first.forget(Commitment(Fq::from(0u64))));
+        first.end_block().unwrap();
+        first.insert_block(block::Root(Hash::zero())).unwrap();
+        first.end_epoch().unwrap();
+
+        let mut second = tree.fork();
+        second.insert_batch(batch(30..35)).unwrap();
+
+        assert_eq!(tree.root(), base_root);
+        assert!(tree.witness(Commitment(Fq::from(0u64))).is_some());
+        assert!(tree.witness(Commitment(Fq::from(10u64))).is_none());
+        assert!(tree.delta().is_none());
+        assert_eq!(first.delta().unwrap().len(), 14);
+
+        // Applying the first fork's changes makes the tree equal to the fork.
+        tree.apply(first.delta().unwrap()).unwrap();
+        assert_eq!(tree, first);
+        assert_eq!(tree.root(), first.root());
+        assert!(tree.witness(Commitment(Fq::from(0u64))).is_none());
+        assert!(tree.witness(Commitment(Fq::from(10u64))).is_some());
+
+        // The second fork was made from a state the tree is no longer in.
+        assert!(matches!(
+            tree.apply(second.delta().unwrap()),
+            Err(ApplyError::BaseMismatch)
+        ));
+        assert_eq!(tree, first);
+    }
+
+    #[test]
+    fn position_bounds_are_checked() {
+        let last = Position::from((u16::MAX, u16::MAX, u16::MAX));
+        assert_eq!(Position::checked_from_u64(u64::from(last)), Ok(last));
+        assert_eq!(
+            Position::checked_from_u64(u64::from(last) + 1),
+            Err(PositionDecodeError::PastEnd(1 << 48))
+        );
+        assert_eq!(
+            Position::try_from((1u64, 1 << 16, 0)),
+            Err(PositionDecodeError::IndexOutOfRange {
+                kind: "block",
+                index: 1 << 16
+            })
+        );
+    }
+
+    #[test]
+    fn position_serializes_as_indices() {
+        let position = Position::from((1, 2, 3));
+        let json = serde_json::to_string(&position).unwrap();
+        assert_eq!(json, r#"{"epoch":1,"block":2,"commitment":3}"#);
+        assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), position);
+
+        // Packed positions written by older versions are still accepted, if in range.
+        let packed = u64::from(position).to_string();
+        assert_eq!(serde_json::from_str::<Position>(&packed).unwrap(), position);
+        assert!(serde_json::from_str::<Position>(&u64::MAX.to_string()).is_err());
+        assert!(
+            serde_json::from_str::<Position>(r#"{"epoch":1,"block":65536,"commitment":3}"#)
+                .is_err()
+        );
+
+        let bytes = bincode::serialize(&position).unwrap();
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(bincode::deserialize::<Position>(&bytes).unwrap(), position);
+    }
+
+    #[test]
+    fn insert_batch_matches_sequential_insertion() {
+        let mut sequential = Tree::new();
+        let mut batched = Tree::new();
+
+        for range in [0..10, 10..11, 11..100] {
+            let mut positions = Vec::new();
+            for (witness, commitment) in batch(range.clone()) {
+                positions.push(sequential.insert(witness, commitment).unwrap());
+            }
+            assert_eq!(batched.insert_batch(batch(range)).unwrap(), positions);
+            sequential.end_block().unwrap();
+            batched.end_block().unwrap();
+        }
+
+        assert_eq!(batched.root(), sequential.root());
+        assert_eq!(batched, sequential);
+        for (witness, commitment) in batch(0..100) {
+            assert_eq!(
+                batched.witness(commitment).is_some(),
+                witness == Witness::Keep
+            );
+        }
+    }
+
+    #[test]
+    fn witnessed_commitments_are_ordered_by_position() {
+        let mut tree = Tree::new();
+        let mut expected = Vec::new();
+        for (i, (witness, commitment)) in batch(0..40).into_iter().enumerate() {
+            let position = tree.insert(witness, commitment).unwrap();
+            if witness == Witness::Keep {
+                expected.push((commitment, position));
+            }
+            if i % 7 == 6 {
+                tree.end_block().unwrap();
+            }
+            if i % 17 == 16 {
+                tree.end_epoch().unwrap();
+            }
+        }
+
+        // Forgotten commitments are no longer witnessed
+        let (forgotten, _) = expected.remove(3);
+        assert!(tree.forget(forgotten));
+
+        assert_eq!(tree.witnessed_commitments().collect::<Vec<_>>(), expected);
+        let mut unordered = tree.commitments_unordered().collect::<Vec<_>>();
+        unordered.sort_by_key(|(_, position)| *position);
+        assert_eq!(unordered, expected);
+    }
+
+    #[test]
+    fn witness_or_insert_inserts_once() {
+        let mut tree = Tree::new();
+        tree.insert(Witness::Forget, Commitment(Fq::from(0u64)))
+            .unwrap();
+        let commitment = Commitment(Fq::from(1u64));
+
+        let (position, proof) = tree.witness_or_insert(commitment).unwrap();
+        assert_eq!(tree.position_of(commitment), Some(position));
+        assert_eq!(proof.position(), position);
+        proof.verify(tree.root()).unwrap();
+
+        // A second call doesn't insert the commitment again
+        let before = tree.position();
+        assert_eq!(tree.witness_or_insert(commitment).unwrap().0, position);
+        assert_eq!(tree.position(), before);
+    }
+
+    #[test]
+    fn builder_insert_batch_matches_sequential_insertion() {
+        let mut block = block::Builder::new();
+        let mut block_batched = block::Builder::new();
+        let mut epoch = epoch::Builder::new();
+        let mut epoch_batched = epoch::Builder::new();
+
+        for (witness, commitment) in batch(0..50) {
+            block.insert(witness, commitment).unwrap();
+            epoch.insert(witness, commitment).unwrap();
+        }
+        block_batched.insert_batch(batch(0..50)).unwrap();
+        epoch_batched.insert_batch(batch(0..50)).unwrap();
+
+        assert_eq!(block_batched.root(), block.root());
+        assert_eq!(epoch_batched.root(), epoch.root());
+    }
+}
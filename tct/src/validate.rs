@@ -25,10 +25,7 @@ pub fn index(tree: &Tree) -> Result<(), IndexMalformed> {
 
     let mut errors = vec![];
 
-    let mut stack = vec![tree.structure()];
-    while let Some(node) = stack.pop() {
-        stack.extend(node.children());
-
+    for node in structure::traverse(tree.structure()) {
         if let Kind::Leaf {
             commitment: Some(actual_commitment),
         } = node.kind()
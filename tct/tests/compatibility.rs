@@ -0,0 +1,154 @@
+//! Storage compatibility tests for the tree.
+//!
+//! The fixtures in `tests/fixtures/` were generated from a fixed tree by an earlier version of this
+//! crate. These tests check that the current version computes the same roots for that tree, and
+//! can still load both its serde serialization and its incremental storage serialization, with
+//! the loaded trees having identical roots and producing valid witnesses.
+//!
+//! Fixtures must only be regenerated (by running the tests with `UPDATE_FIXTURES=1`) when a
+//! breaking change to the tree's storage format or hashing is intended. A missing fixture is an
+//! error otherwise, since a test that writes its own fixture only compares the tree with itself.
+
+use std::path::PathBuf;
+
+use decaf377::Fq;
+use penumbra_proto::Protobuf;
+use penumbra_tct::{storage::InMemory, Commitment, Proof, Tree, Witness};
+
+/// The number of epochs in the fixture tree.
+const EPOCHS: u64 = 3;
+/// The number of blocks in each epoch of the fixture tree.
+const BLOCKS: u64 = 4;
+/// The number of commitments in each non-empty block of the fixture tree.
+const COMMITMENTS: u64 = 5;
+
+/// Build the fixture tree, returning it along with the commitments it witnesses.
+fn fixture_tree() -> (Tree, Vec<Commitment>) {
+    let mut tree = Tree::new();
+    let mut witnessed = Vec::new();
+    let mut next = 0u64;
+
+    for _ in 0..EPOCHS {
+        for block in 0..BLOCKS {
+            // Leave one block in each epoch empty.
+            if block != 1 {
+                for _ in 0..COMMITMENTS {
+                    let commitment = Commitment(Fq::from(next));
+                    let witness = if next % 2 == 0 {
+                        witnessed.push(commitment);
+                        Witness::Keep
+                    } else {
+                        Witness::Forget
+                    };
+                    tree.insert(witness, commitment).unwrap();
+                    next += 1;
+                }
+            }
+            tree.end_block().unwrap();
+        }
+        tree.end_epoch().unwrap();
+    }
+
+    // Leave the last epoch and block open, and forget one of the witnessed commitments.
+    for _ in 0..COMMITMENTS {
+        let commitment = Commitment(Fq::from(next));
+        tree.insert(Witness::Keep, commitment).unwrap();
+        witnessed.push(commitment);
+        next += 1;
+    }
+    let forgotten = witnessed.remove(0);
+    assert!(tree.forget(forgotten));
+
+    (tree, witnessed)
+}
+
+/// Return the contents of the fixture `name`, writing `actual` to it first if `UPDATE_FIXTURES=1`
+/// is set.
+fn fixture(name: &str, actual: impl FnOnce() -> String) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+
+    if std::env::var("UPDATE_FIXTURES").as_deref() == Ok("1") {
+        std::fs::create_dir_all(path.parent().unwrap()).expect("can create fixture directory");
+        std::fs::write(&path, actual()).expect("can write fixture");
+        eprintln!("wrote fixture {}", path.display());
+    }
+
+    std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!(
+            "can't read fixture {} ({}); it must be generated from a released version of the tree",
+            path.display(),
+            error
+        )
+    })
+}
+
+/// Check that `tree` has the fixture root, and witnesses each of `witnessed` with a valid proof.
+fn check_loaded(tree: &Tree, witnessed: &[Commitment]) {
+    let (expected, _) = fixture_tree();
+    let root = fixture("root.txt", || format!("{}\n", expected.root()));
+    assert_eq!(tree.root().to_string(), root.trim());
+    assert_eq!(tree.root(), expected.root());
+    assert_eq!(tree.position(), expected.position());
+    assert_eq!(tree.forgotten(), expected.forgotten());
+
+    for commitment in witnessed {
+        let proof = tree.witness(*commitment).expect("commitment is witnessed");
+        proof.verify(tree.root()).expect("proof verifies");
+    }
+}
+
+#[test]
+fn roots_are_stable() {
+    let (tree, _) = fixture_tree();
+
+    let actual = format!(
+        "root: {}\nepoch_root: {}\nblock_root: {}\n",
+        tree.root(),
+        tree.current_epoch_root(),
+        tree.current_block_root(),
+    );
+    let expected = fixture("roots.txt", || actual.clone());
+    assert_eq!(
+        actual, expected,
+        "tree roots do not match fixture (rerun with UPDATE_FIXTURES=1 only if this is an intentional breaking change)"
+    );
+}
+
+#[test]
+fn proofs_are_stable() {
+    let (tree, witnessed) = fixture_tree();
+    let proof = tree.witness(witnessed[0]).unwrap();
+
+    let expected = fixture("proof.txt", || {
+        format!("{}\n", hex::encode(proof.encode_to_vec()))
+    });
+    let stored = Proof::decode(&*hex::decode(expected.trim()).unwrap()).unwrap();
+    assert_eq!(stored, proof);
+    stored.verify(tree.root()).expect("stored proof verifies");
+}
+
+#[test]
+fn serialized_tree_loads() {
+    let (tree, witnessed) = fixture_tree();
+
+    let serialized = fixture("tree.json", || serde_json::to_string(&tree).unwrap());
+    let loaded: Tree = serde_json::from_str(&serialized).expect("can deserialize tree");
+    check_loaded(&loaded, &witnessed);
+}
+
+#[test]
+fn stored_tree_loads() {
+    let (tree, witnessed) = fixture_tree();
+
+    let stored = fixture("storage.json", || {
+        let mut storage = InMemory::new();
+        tree.to_writer(&mut storage).unwrap();
+        serde_json::to_string(&storage).unwrap()
+    });
+    let mut storage: InMemory = serde_json::from_str(&stored).expect("can deserialize storage");
+    let loaded = Tree::from_reader(&mut storage).expect("can load tree from storage");
+    check_loaded(&loaded, &witnessed);
+}
@@ -39,6 +39,10 @@ fn main() -> Result<()> {
         ".penumbra.core.chain.v1alpha1.CompactBlock",
     ]);
 
+    // Note metadata is keyed by string, and should be encoded in a
+    // deterministic order.
+    config.btree_map(&[".penumbra.core.crypto.v1alpha1.NoteMetadata"]);
+
     for (path, attribute) in TYPE_ATTRIBUTES.iter() {
         config.type_attribute(path, attribute);
     }
@@ -88,6 +92,8 @@ fn main() -> Result<()> {
         .client_mod_attribute("penumbra.view.v1alpha1", "#[cfg(feature = \"rpc\")]")
         .server_mod_attribute("penumbra.custody.v1alpha1", "#[cfg(feature = \"rpc\")]")
         .client_mod_attribute("penumbra.custody.v1alpha1", "#[cfg(feature = \"rpc\")]")
+        .server_mod_attribute("penumbra.prover.v1alpha1", "#[cfg(feature = \"rpc\")]")
+        .client_mod_attribute("penumbra.prover.v1alpha1", "#[cfg(feature = \"rpc\")]")
         .server_mod_attribute(
             "cosmos.base.tendermint.v1beta1",
             "#[cfg(feature = \"rpc\")]",
@@ -102,6 +108,7 @@ fn main() -> Result<()> {
                 "../../proto/proto/penumbra/client/v1alpha1/client.proto",
                 "../../proto/proto/penumbra/view/v1alpha1/view.proto",
                 "../../proto/proto/penumbra/custody/v1alpha1/custody.proto",
+                "../../proto/proto/penumbra/prover/v1alpha1/prover.proto",
                 "../../proto/ibc-go-vendor/cosmos/base/tendermint/v1beta1/query.proto",
                 "../../proto/ibc-go-vendor/tendermint/types/validator.proto",
                 "../../proto/ibc-go-vendor/tendermint/p2p/types.proto",
@@ -261,6 +268,17 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.core.crypto.v1alpha1.Fee", SERIALIZE),
     (".penumbra.core.crypto.v1alpha1.Clue", SERIALIZE),
     (".penumbra.core.crypto.v1alpha1.Clue", SERDE_TRANSPARENT),
+    (".penumbra.core.crypto.v1alpha1.ZKSpendProof", SERIALIZE),
+    (
+        ".penumbra.core.crypto.v1alpha1.ZKSpendProof",
+        SERDE_TRANSPARENT,
+    ),
+    (".penumbra.core.crypto.v1alpha1.ZKOutputProof", SERIALIZE),
+    (
+        ".penumbra.core.crypto.v1alpha1.ZKOutputProof",
+        SERDE_TRANSPARENT,
+    ),
+    (".penumbra.core.crypto.v1alpha1.NoteMetadata", SERIALIZE),
     (".penumbra.core.chain.v1alpha1.ChainParameters", SERIALIZE),
     (".penumbra.core.chain.v1alpha1.FmdParameters", SERIALIZE),
     (".penumbra.core.chain.v1alpha1.CompactBlock", SERIALIZE),
@@ -427,6 +445,14 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
     ),
     (".penumbra.core.stake.v1alpha1.Uptime.bitvec", AS_BASE64),
     (".penumbra.core.crypto.v1alpha1.Clue.inner", AS_HEX),
+    (
+        ".penumbra.core.crypto.v1alpha1.ZKSpendProof.inner",
+        AS_BASE64,
+    ),
+    (
+        ".penumbra.core.crypto.v1alpha1.ZKOutputProof.inner",
+        AS_BASE64,
+    ),
     (
         ".penumbra.core.crypto.v1alpha1.Address.inner",
         AS_BECH32_ADDRESS,
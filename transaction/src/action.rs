@@ -12,6 +12,7 @@ pub mod output;
 mod position;
 pub mod proposal;
 pub mod spend;
+pub mod stateless_checks;
 pub mod swap;
 pub mod swap_claim;
 mod undelegate;
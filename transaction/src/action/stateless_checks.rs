@@ -0,0 +1,116 @@
+//! Cheap checks of decoded action bodies, to be performed before verifying their proofs.
+//!
+//! Proof verification is by far the most expensive part of checking an action, so a mempool should
+//! reject actions whose bodies could never be valid before verifying any proofs. These checks only
+//! look at the body itself, so they can be performed without access to any chain state.
+
+use penumbra_crypto::{balance, ka, rdsa};
+
+use super::{output, spend, swap};
+
+/// A reason to reject an action body without verifying its proof.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    #[error("randomized verification key is not a canonical encoding")]
+    NonCanonicalRk,
+    #[error("randomized verification key is the identity")]
+    IdentityRk,
+    #[error("ephemeral key {0:?} is not a valid encoding")]
+    InvalidEphemeralKey(ka::Public),
+    #[error("ephemeral key is the identity")]
+    IdentityEphemeralKey,
+    #[error("balance commitment is not a canonical encoding")]
+    NonCanonicalBalanceCommitment,
+}
+
+/// Check a spend body: its randomized verification key must be a canonical encoding of a
+/// non-identity element.
+pub fn check_spend_body(body: &spend::Body) -> Result<(), Rejection> {
+    check_rk(&body.rk)?;
+    check_balance_commitment(&body.balance_commitment)
+}
+
+/// Check an output body: its ephemeral key must decode to a non-identity element.
+pub fn check_output_body(body: &output::Body) -> Result<(), Rejection> {
+    check_ephemeral_key(&body.note_payload.ephemeral_key)?;
+    check_balance_commitment(&body.balance_commitment)
+}
+
+/// Check a swap body: its ephemeral key must decode to a non-identity element.
+pub fn check_swap_body(body: &swap::Body) -> Result<(), Rejection> {
+    check_ephemeral_key(&body.payload.ephemeral_key)?;
+    check_balance_commitment(&body.fee_commitment)
+}
+
+fn check_rk(rk: &rdsa::VerificationKey<rdsa::SpendAuth>) -> Result<(), Rejection> {
+    let bytes = rk.to_bytes();
+    let element = decaf377::Encoding(bytes)
+        .vartime_decompress()
+        .map_err(|_| Rejection::NonCanonicalRk)?;
+    if element.vartime_compress().0 != bytes {
+        return Err(Rejection::NonCanonicalRk);
+    }
+    if element == decaf377::Element::default() {
+        return Err(Rejection::IdentityRk);
+    }
+    Ok(())
+}
+
+fn check_ephemeral_key(epk: &ka::Public) -> Result<(), Rejection> {
+    let element = decaf377::Encoding(epk.0)
+        .vartime_decompress()
+        .map_err(|_| Rejection::InvalidEphemeralKey(*epk))?;
+    if element == decaf377::Element::default() {
+        return Err(Rejection::IdentityEphemeralKey);
+    }
+    Ok(())
+}
+
+fn check_balance_commitment(commitment: &balance::Commitment) -> Result<(), Rejection> {
+    // A decoded commitment always re-encodes canonically, but a body assembled in memory may not
+    // have been decoded.
+    let bytes = commitment.to_bytes();
+    match decaf377::Encoding(bytes).vartime_decompress() {
+        Ok(element) if element == commitment.0 => Ok(()),
+        _ => Err(Rejection::NonCanonicalBalanceCommitment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_ephemeral_keys() {
+        let valid = ka::Secret::new(&mut OsRng).public();
+        assert_eq!(check_ephemeral_key(&valid), Ok(()));
+
+        let identity = ka::Public(decaf377::Element::default().vartime_compress().0);
+        assert_eq!(
+            check_ephemeral_key(&identity),
+            Err(Rejection::IdentityEphemeralKey)
+        );
+
+        let invalid = ka::Public([0xff; 32]);
+        assert_eq!(
+            check_ephemeral_key(&invalid),
+            Err(Rejection::InvalidEphemeralKey(invalid))
+        );
+    }
+
+    #[test]
+    fn rejects_identity_rk() {
+        let sk = rdsa::SigningKey::<rdsa::SpendAuth>::new(OsRng);
+        let rk: rdsa::VerificationKey<rdsa::SpendAuth> = sk.into();
+        assert_eq!(check_rk(&rk), Ok(()));
+
+        let identity: rdsa::VerificationKey<rdsa::SpendAuth> = decaf377::Element::default()
+            .vartime_compress()
+            .0
+            .try_into()
+            .unwrap();
+        assert_eq!(check_rk(&identity), Err(Rejection::IdentityRk));
+    }
+}
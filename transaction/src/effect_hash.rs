@@ -683,6 +683,7 @@ mod tests {
         asset,
         dex::{swap::SwapPlaintext, TradingPair},
         keys::{SeedPhrase, SpendKey},
+        proofs::ProofRng,
         transaction::Fee,
         Note, Value, STAKING_TOKEN_ASSET_ID,
     };
@@ -788,8 +789,24 @@ mod tests {
                 .collect(),
         };
         let transaction = plan
-            .build(&mut OsRng, fvk, auth_data, witness_data)
+            .clone()
+            .build(
+                &mut ProofRng::from_seed([7u8; 32]),
+                fvk,
+                auth_data.clone(),
+                witness_data.clone(),
+            )
             .unwrap();
+        // Building from the same seed gives exactly the same transaction.
+        let rebuilt = plan
+            .build(
+                &mut ProofRng::from_seed([7u8; 32]),
+                fvk,
+                auth_data,
+                witness_data,
+            )
+            .unwrap();
+        assert_eq!(Vec::<u8>::from(&transaction), Vec::<u8>::from(&rebuilt));
 
         let transaction_effect_hash = transaction.effect_hash();
 
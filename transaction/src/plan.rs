@@ -1,13 +1,21 @@
 //! Declarative transaction plans, used for transaction authorization and
 //! creation.
 
-use anyhow::Result;
-use penumbra_crypto::{transaction::Fee, Address};
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use ark_ff::Zero;
+use penumbra_crypto::{
+    balance::{audit, BalanceBlinding},
+    transaction::Fee,
+    Address, FieldExt, Fr,
+};
 use penumbra_proto::{
     core::ibc::v1alpha1 as pb_ibc, core::stake::v1alpha1 as pb_stake,
     core::transaction::v1alpha1 as pb, Protobuf,
 };
-use rand::{CryptoRng, Rng};
+use rand::{seq::SliceRandom, CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::action::{
@@ -21,8 +29,8 @@ mod clue;
 mod memo;
 
 pub use action::{
-    ActionPlan, DelegatorVotePlan, OutputPlan, SpendPlan, SwapClaimPlan, SwapPlan,
-    UndelegateClaimPlan,
+    ActionPlan, DelegatorVotePlan, OutputPlan, SpendPlan, SpendValidationError, SwapClaimPlan,
+    SwapPlan, UndelegateClaimPlan,
 };
 pub use clue::CluePlan;
 pub use memo::MemoPlan;
@@ -216,6 +224,193 @@ impl TransactionPlan {
 
         self.clue_plans = clue_plans;
     }
+
+    /// Replace the balance commitment blinding factors of this plan's spends,
+    /// outputs and swaps with ones derived from `plan_seed` and each action's
+    /// index.
+    ///
+    /// This lets auditing tools reconstruct every blinding factor used in the
+    /// transaction from the seed, using [`TransactionPlan::check_blinding_derivations`].
+    /// Since each action has a distinct index, the derived blinding factors are
+    /// distinct, as [`TransactionPlan::check_blindings`] requires.
+    pub fn derive_blindings(&mut self, plan_seed: [u8; 32]) {
+        for (index, action) in self.actions.iter_mut().enumerate() {
+            let derived = BalanceBlinding::derive(plan_seed, index as u32);
+            match action {
+                ActionPlan::Spend(spend) => spend.value_blinding = derived,
+                ActionPlan::Output(output) => output.value_blinding = derived,
+                ActionPlan::Swap(swap) => swap.fee_blinding = derived.to_scalar(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Check that the balance commitment blinding factors of this plan's spends,
+    /// outputs and swaps were all derived from `plan_seed`.
+    pub fn check_blinding_derivations(&self, plan_seed: [u8; 32]) -> Result<()> {
+        for (index, blinding) in self.balance_blindings() {
+            blinding
+                .check_derivation(plan_seed, index as u32)
+                .with_context(|| format!("invalid blinding factor for action {}", index))?;
+        }
+        Ok(())
+    }
+
+    /// Shuffle this plan's spends among themselves, and its outputs among
+    /// themselves, using randomness derived from `plan_seed`.
+    ///
+    /// Spends and outputs are first put in a canonical order, by the commitment
+    /// of the note each one spends or creates, and then shuffled, so the result
+    /// depends only on the seed and on which actions the plan has, not on the
+    /// order they were planned in. This keeps the position of an action from
+    /// revealing its role (e.g. that change is always the first output), while
+    /// letting anyone with the seed, such as an offline signer, reproduce the
+    /// order, using [`TransactionPlan::check_action_order`].
+    ///
+    /// All other actions keep their positions. Since blinding factors are
+    /// derived from action indices, shuffle before calling
+    /// [`TransactionPlan::derive_blindings`].
+    pub fn shuffle_actions(&mut self, plan_seed: [u8; 32]) {
+        let mut rng = ChaCha20Rng::from_seed(shuffle_seed(plan_seed));
+        for kind in [ShuffleKind::Spend, ShuffleKind::Output] {
+            let indices = self
+                .actions
+                .iter()
+                .enumerate()
+                .filter(|(_, action)| matches!(shuffle_key(action), Some((k, _)) if k == kind))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+
+            let mut shuffled = indices
+                .iter()
+                .map(|&index| self.actions[index].clone())
+                .collect::<Vec<_>>();
+            shuffled.sort_by_cached_key(shuffle_key);
+            shuffled.shuffle(&mut rng);
+
+            for (index, action) in indices.into_iter().zip(shuffled) {
+                self.actions[index] = action;
+            }
+        }
+    }
+
+    /// Check that this plan's spends and outputs are in the order that
+    /// [`TransactionPlan::shuffle_actions`] puts them in for `plan_seed`.
+    pub fn check_action_order(&self, plan_seed: [u8; 32]) -> Result<()> {
+        let mut expected = self.clone();
+        expected.shuffle_actions(plan_seed);
+        for (index, (action, expected)) in self.actions.iter().zip(&expected.actions).enumerate() {
+            if shuffle_key(action) != shuffle_key(expected) {
+                anyhow::bail!(
+                    "action {} is not in the order derived from the plan seed",
+                    index
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the balance commitment blinding factors of this plan's spends,
+    /// outputs and swaps are all nonzero and distinct.
+    ///
+    /// A zero blinding factor leaves the committed value unhidden, and two
+    /// actions sharing a blinding factor let anyone cancel it out by subtracting
+    /// their commitments. Neither happens with honestly sampled or derived
+    /// blinding factors, so this is a sanity check on a plan before its
+    /// transaction is built and broadcast.
+    pub fn check_blindings(&self) -> Result<()> {
+        let mut seen = BTreeMap::new();
+        for (index, blinding) in self.balance_blindings() {
+            let factor = blinding.to_scalar();
+            if factor == Fr::zero() {
+                anyhow::bail!("action {} has a zero balance blinding factor", index);
+            }
+            if let Some(previous) = seen.insert(factor.to_bytes(), index) {
+                anyhow::bail!(
+                    "actions {} and {} reuse the same balance blinding factor",
+                    previous,
+                    index
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The balance commitment blinding factor of each spend, output and swap in
+    /// this plan, with the index of its action.
+    fn balance_blindings(&self) -> impl Iterator<Item = (usize, BalanceBlinding)> + '_ {
+        self.actions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| {
+                let blinding = match action {
+                    ActionPlan::Spend(spend) => spend.value_blinding,
+                    ActionPlan::Output(output) => output.value_blinding,
+                    ActionPlan::Swap(swap) => BalanceBlinding::from(swap.fee_blinding),
+                    _ => return None,
+                };
+                Some((index, blinding))
+            })
+    }
+
+    /// Audit the balance commitments of the `transaction` built from this plan, recomputing the
+    /// commitment of each spend and output from the note and blinding factor in the plan.
+    ///
+    /// Actions are built with all spends first and then all outputs, each in the order of the
+    /// plan, so the spends and outputs of the plan are matched with those of the transaction in
+    /// order.
+    pub fn audit_balance_commitments(&self, transaction: &crate::Transaction) -> audit::Report {
+        let mut spends = transaction.spends();
+        let mut outputs = transaction.outputs();
+
+        let spend_entries = self.spend_plans().map(|spend| {
+            audit::Entry::spend(
+                spend.note.clone(),
+                spend.value_blinding.to_scalar(),
+                spends.next().map(|spend| spend.body.balance_commitment),
+            )
+        });
+        let output_entries = self.output_plans().map(|output| {
+            audit::Entry::output(
+                output.output_note(),
+                output.value_blinding.to_scalar(),
+                outputs.next().map(|output| output.body.balance_commitment),
+            )
+        });
+
+        audit::audit(spend_entries.chain(output_entries))
+    }
+}
+
+/// The kinds of action that [`TransactionPlan::shuffle_actions`] shuffles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ShuffleKind {
+    Spend,
+    Output,
+}
+
+/// The kind of a shuffled action, and the note commitment that puts it in
+/// canonical order before shuffling.
+fn shuffle_key(action: &ActionPlan) -> Option<(ShuffleKind, [u8; 32])> {
+    match action {
+        ActionPlan::Spend(spend) => Some((ShuffleKind::Spend, spend.note.commit().0.to_bytes())),
+        ActionPlan::Output(output) => Some((
+            ShuffleKind::Output,
+            output.output_note().commit().0.to_bytes(),
+        )),
+        _ => None,
+    }
+}
+
+/// Derive the seed of the action shuffle from the plan seed.
+fn shuffle_seed(plan_seed: [u8; 32]) -> [u8; 32] {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"Penumbra_ActShuf")
+        .hash(&plan_seed)
+        .as_bytes()
+        .try_into()
+        .expect("hash is 32 bytes")
 }
 
 impl Protobuf<pb::TransactionPlan> for TransactionPlan {}
@@ -262,3 +457,91 @@ impl TryFrom<pb::TransactionPlan> for TransactionPlan {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn check_blindings_flags_zero_and_reused_blindings() {
+        let mut plan = TransactionPlan {
+            actions: vec![
+                SpendPlan::dummy(&mut OsRng).into(),
+                OutputPlan::dummy(&mut OsRng).into(),
+            ],
+            ..Default::default()
+        };
+        assert!(plan.check_blindings().is_ok());
+
+        if let ActionPlan::Output(output) = &mut plan.actions[1] {
+            output.value_blinding = BalanceBlinding::from(Fr::zero());
+        }
+        assert!(plan.check_blindings().is_err());
+
+        let plan_seed = [1u8; 32];
+        plan.derive_blindings(plan_seed);
+        assert!(plan.check_blindings().is_ok());
+        assert!(plan.check_blinding_derivations(plan_seed).is_ok());
+
+        let spend_blinding = plan.spend_plans().next().unwrap().value_blinding;
+        if let ActionPlan::Output(output) = &mut plan.actions[1] {
+            output.value_blinding = spend_blinding;
+        }
+        assert!(plan.check_blindings().is_err());
+    }
+
+    #[test]
+    fn shuffle_actions_is_canonical_and_checkable() {
+        let spends = (0..3)
+            .map(|_| ActionPlan::from(SpendPlan::dummy(&mut OsRng)))
+            .collect::<Vec<_>>();
+        let outputs = (0..4)
+            .map(|_| ActionPlan::from(OutputPlan::dummy(&mut OsRng)))
+            .collect::<Vec<_>>();
+        let plan_seed = [3u8; 32];
+
+        let mut plan = TransactionPlan {
+            actions: spends.iter().chain(&outputs).cloned().collect(),
+            ..Default::default()
+        };
+        plan.shuffle_actions(plan_seed);
+        assert!(plan.check_action_order(plan_seed).is_ok());
+
+        // Spends stay in the spend positions, and outputs in the output positions.
+        assert!(plan.actions[..3]
+            .iter()
+            .all(|action| matches!(action, ActionPlan::Spend(_))));
+        assert!(plan.actions[3..]
+            .iter()
+            .all(|action| matches!(action, ActionPlan::Output(_))));
+
+        // The order doesn't depend on the order the actions were planned in.
+        let mut reordered = TransactionPlan {
+            actions: spends
+                .iter()
+                .rev()
+                .chain(outputs.iter().rev())
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        reordered.shuffle_actions(plan_seed);
+        assert_eq!(
+            plan.actions.iter().map(shuffle_key).collect::<Vec<_>>(),
+            reordered
+                .actions
+                .iter()
+                .map(shuffle_key)
+                .collect::<Vec<_>>(),
+        );
+
+        // Deriving blinding factors afterwards doesn't change the order.
+        plan.derive_blindings(plan_seed);
+        assert!(plan.check_action_order(plan_seed).is_ok());
+
+        plan.actions.swap(3, 4);
+        assert!(plan.check_action_order(plan_seed).is_err());
+    }
+}
@@ -14,7 +14,7 @@ mod undelegate_claim;
 
 pub use delegator_vote::DelegatorVotePlan;
 pub use output::OutputPlan;
-pub use spend::SpendPlan;
+pub use spend::{SpendPlan, SpendValidationError};
 pub use swap::SwapPlan;
 pub use swap_claim::SwapClaimPlan;
 pub use undelegate_claim::UndelegateClaimPlan;
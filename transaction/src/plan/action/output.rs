@@ -1,12 +1,16 @@
-use ark_ff::UniformRand;
+use std::collections::BTreeMap;
+
 use penumbra_crypto::{
+    balance::BalanceBlinding,
     ka,
     keys::{IncomingViewingKey, OutgoingViewingKey},
     proofs::transparent::OutputProof,
     symmetric::WrappedMemoKey,
     Address, EncryptedNote, FieldExt, Fr, Note, PayloadKey, Rseed, Value, STAKING_TOKEN_ASSET_ID,
 };
-use penumbra_proto::{core::transaction::v1alpha1 as pb, Protobuf};
+use penumbra_proto::{
+    core::crypto::v1alpha1 as pb_crypto, core::transaction::v1alpha1 as pb, Protobuf,
+};
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
@@ -19,7 +23,10 @@ pub struct OutputPlan {
     pub value: Value,
     pub dest_address: Address,
     pub rseed: Rseed,
-    pub value_blinding: Fr,
+    pub value_blinding: BalanceBlinding,
+    /// Opaque metadata about the new note, which the view service records when
+    /// the note is detected, but which is never included in the transaction.
+    pub note_metadata: BTreeMap<String, Vec<u8>>,
 }
 
 impl OutputPlan {
@@ -30,15 +37,22 @@ impl OutputPlan {
         dest_address: Address,
     ) -> OutputPlan {
         let rseed = Rseed::generate(rng);
-        let value_blinding = Fr::rand(rng);
+        let value_blinding = BalanceBlinding::generate(rng);
         Self {
             value,
             dest_address,
             rseed,
             value_blinding,
+            note_metadata: BTreeMap::new(),
         }
     }
 
+    /// Attach the given metadata to the new note.
+    pub fn with_note_metadata(mut self, note_metadata: BTreeMap<String, Vec<u8>>) -> Self {
+        self.note_metadata = note_metadata;
+        self
+    }
+
     /// Create a dummy [`OutputPlan`].
     pub fn dummy<R: CryptoRng + RngCore>(rng: &mut R) -> OutputPlan {
         let dummy_address = Address::dummy(rng);
@@ -71,7 +85,7 @@ impl OutputPlan {
     pub fn output_proof(&self) -> OutputProof {
         OutputProof {
             note: self.output_note(),
-            v_blinding: self.value_blinding,
+            v_blinding: self.value_blinding.to_scalar(),
         }
     }
 
@@ -81,7 +95,7 @@ impl OutputPlan {
         let note = self.output_note();
         let note_commitment = note.commit();
 
-        let balance_commitment = self.balance().commit(self.value_blinding);
+        let balance_commitment = self.balance().commit(self.value_blinding.to_scalar());
 
         // Encrypt the note to the recipient...
         let diversified_generator = note.diversified_generator();
@@ -128,7 +142,10 @@ impl From<OutputPlan> for pb::OutputPlan {
             value: Some(msg.value.into()),
             dest_address: Some(msg.dest_address.into()),
             rseed: msg.rseed.to_bytes().to_vec().into(),
-            value_blinding: msg.value_blinding.to_bytes().to_vec().into(),
+            value_blinding: msg.value_blinding.to_scalar().to_bytes().to_vec().into(),
+            note_metadata: (!msg.note_metadata.is_empty()).then(|| pb_crypto::NoteMetadata {
+                entries: msg.note_metadata,
+            }),
         }
     }
 }
@@ -146,7 +163,11 @@ impl TryFrom<pb::OutputPlan> for OutputPlan {
                 .ok_or_else(|| anyhow::anyhow!("missing address"))?
                 .try_into()?,
             rseed: Rseed(msg.rseed.as_ref().try_into()?),
-            value_blinding: Fr::from_bytes(msg.value_blinding.as_ref().try_into()?)?,
+            value_blinding: Fr::from_bytes(msg.value_blinding.as_ref().try_into()?)?.into(),
+            note_metadata: msg
+                .note_metadata
+                .map(|metadata| metadata.entries)
+                .unwrap_or_default(),
         })
     }
 }
@@ -156,6 +177,7 @@ mod test {
     use super::OutputPlan;
     use penumbra_crypto::keys::{SeedPhrase, SpendKey};
     use penumbra_crypto::{PayloadKey, Value};
+    use penumbra_proto::Protobuf;
     use rand_core::OsRng;
 
     #[test]
@@ -173,7 +195,7 @@ mod test {
         let dest_address = "penumbrav2t1f5h060qspaga3vvwf2mwak2dj6ugymxd2et5h6l3n0u2y57lcv4t7j2m8n75nm7qmhg4v3csexl5slm6tm5hg5wyw39fv2q0jnpwdjn3llduzgmg5d3efuqq6ymn76t0hvgage".parse().unwrap();
 
         let output_plan = OutputPlan::new(&mut rng, value, dest_address);
-        let blinding_factor = output_plan.value_blinding;
+        let blinding_factor = output_plan.value_blinding.to_scalar();
 
         let _body = output_plan.output_body(ovk, &dummy_memo_key);
 
@@ -185,4 +207,27 @@ mod test {
             .verify(balance_commitment, note_commitment)
             .unwrap();
     }
+
+    #[test]
+    /// Check that note metadata survives encoding the plan, but does not affect the output.
+    fn note_metadata_roundtrip() {
+        let mut rng = OsRng;
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(rng), 0);
+        let (dest_address, _dtk) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+        let value: Value = "1upenumbra".parse().unwrap();
+
+        let plain = OutputPlan::new(&mut rng, value, dest_address);
+        let with_metadata = plain.clone().with_note_metadata(
+            [("invoice".to_string(), b"1312".to_vec())]
+                .into_iter()
+                .collect(),
+        );
+
+        let decoded = OutputPlan::decode(with_metadata.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.note_metadata, with_metadata.note_metadata);
+        assert_eq!(decoded.output_note().commit(), plain.output_note().commit());
+    }
 }
@@ -1,8 +1,7 @@
-use ark_ff::UniformRand;
 use decaf377_rdsa::{Signature, SpendAuth};
 use penumbra_crypto::{
-    proofs::transparent::SpendProof, Address, FieldExt, Fr, FullViewingKey, Note, Rseed, Value,
-    STAKING_TOKEN_ASSET_ID,
+    balance::BalanceBlinding, proofs::transparent::SpendProof, sample, Address, Amount, FieldExt,
+    Fr, FullViewingKey, Note, Rseed, Value, STAKING_TOKEN_ASSET_ID,
 };
 use penumbra_proto::{core::transaction::v1alpha1 as pb, Protobuf};
 use penumbra_tct as tct;
@@ -11,6 +10,22 @@ use serde::{Deserialize, Serialize};
 
 use crate::action::{spend, Spend};
 
+/// An error encountered while checking a [`SpendPlan`] before proving.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum SpendValidationError {
+    #[error("note is not decryptable by the provided full viewing key")]
+    NotViewable,
+    #[error("note commitment {0:?} is not witnessed in the note commitment tree")]
+    NotWitnessed(penumbra_crypto::note::Commitment),
+    #[error("note commitment proof does not verify against the current anchor {0:?}")]
+    AnchorMismatch(tct::Root),
+    #[error("planned position {planned:?} does not match witnessed position {witnessed:?}")]
+    PositionMismatch {
+        planned: tct::Position,
+        witnessed: tct::Position,
+    },
+}
+
 /// A planned [`Spend`](Spend).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(try_from = "pb::SpendPlan", into = "pb::SpendPlan")]
@@ -18,7 +33,7 @@ pub struct SpendPlan {
     pub note: Note,
     pub position: tct::Position,
     pub randomizer: Fr,
-    pub value_blinding: Fr,
+    pub value_blinding: BalanceBlinding,
 }
 
 impl SpendPlan {
@@ -31,8 +46,8 @@ impl SpendPlan {
         SpendPlan {
             note,
             position,
-            randomizer: Fr::rand(rng),
-            value_blinding: Fr::rand(rng),
+            randomizer: sample::spend_auth_randomizer(rng),
+            value_blinding: BalanceBlinding::generate(rng),
         }
     }
 
@@ -53,6 +68,54 @@ impl SpendPlan {
         Self::new(rng, dummy_note, 0u64.into())
     }
 
+    /// Check that this plan can be turned into a valid [`Spend`] against the
+    /// given `tree`, without doing any of the expensive proving work.
+    ///
+    /// This checks that:
+    ///
+    /// - the planned note decrypts under the incoming viewing key of `fvk`;
+    /// - the note commitment is witnessed in `tree`, and its inclusion proof
+    ///   verifies against the tree's current anchor;
+    /// - the witnessed position matches the position recorded in the plan.
+    ///
+    /// Dummy spends (with zero value) are always valid, since the proof
+    /// short-circuits for them.
+    pub fn validate(
+        &self,
+        fvk: &FullViewingKey,
+        tree: &tct::Tree,
+    ) -> Result<(), SpendValidationError> {
+        if self.note.amount() == Amount::zero() {
+            return Ok(());
+        }
+
+        let ciphertext = self.note.encrypt();
+        let epk = self.note.ephemeral_public_key();
+        match Note::decrypt(&ciphertext, fvk.incoming(), &epk) {
+            Ok(decrypted) if decrypted == self.note => {}
+            _ => return Err(SpendValidationError::NotViewable),
+        }
+
+        let note_commitment = self.note.commit();
+        let proof = tree
+            .witness(note_commitment)
+            .ok_or(SpendValidationError::NotWitnessed(note_commitment))?;
+
+        let anchor = tree.root();
+        proof
+            .verify(anchor)
+            .map_err(|_| SpendValidationError::AnchorMismatch(anchor))?;
+
+        if proof.position() != self.position {
+            return Err(SpendValidationError::PositionMismatch {
+                planned: self.position,
+                witnessed: proof.position(),
+            });
+        }
+
+        Ok(())
+    }
+
     /// Convenience method to construct the [`Spend`] described by this [`SpendPlan`].
     pub fn spend(
         &self,
@@ -70,7 +133,7 @@ impl SpendPlan {
     /// Construct the [`spend::Body`] described by this [`SpendPlan`].
     pub fn spend_body(&self, fvk: &FullViewingKey) -> spend::Body {
         spend::Body {
-            balance_commitment: self.balance().commit(self.value_blinding),
+            balance_commitment: self.balance().commit(self.value_blinding.to_scalar()),
             nullifier: fvk.derive_nullifier(self.position, &self.note.commit()),
             rk: fvk.spend_verification_key().randomize(&self.randomizer),
         }
@@ -85,7 +148,7 @@ impl SpendPlan {
         SpendProof {
             note_commitment_proof,
             note: self.note.clone(),
-            v_blinding: self.value_blinding,
+            v_blinding: self.value_blinding.to_scalar(),
             spend_auth_randomizer: self.randomizer,
             ak: *fvk.spend_verification_key(),
             nk: *fvk.nullifier_key(),
@@ -109,7 +172,7 @@ impl From<SpendPlan> for pb::SpendPlan {
             note: Some(msg.note.into()),
             position: u64::from(msg.position),
             randomizer: msg.randomizer.to_bytes().to_vec().into(),
-            value_blinding: msg.value_blinding.to_bytes().to_vec().into(),
+            value_blinding: msg.value_blinding.to_scalar().to_bytes().to_vec().into(),
         }
     }
 }
@@ -124,7 +187,52 @@ impl TryFrom<pb::SpendPlan> for SpendPlan {
                 .try_into()?,
             position: msg.position.into(),
             randomizer: Fr::from_bytes(msg.randomizer.as_ref().try_into()?)?,
-            value_blinding: Fr::from_bytes(msg.value_blinding.as_ref().try_into()?)?,
+            value_blinding: Fr::from_bytes(msg.value_blinding.as_ref().try_into()?)?.into(),
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SpendPlan, SpendValidationError};
+    use penumbra_crypto::keys::{SeedPhrase, SpendKey};
+    use penumbra_crypto::{Note, Value};
+    use penumbra_tct as tct;
+    use rand_core::OsRng;
+
+    #[test]
+    /// Check that `SpendPlan::validate` accepts a well-formed plan and rejects
+    /// plans whose note or position does not match the tree.
+    fn spend_plan_validation() {
+        let mut rng = OsRng;
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(rng), 0);
+        let fvk = sk.full_viewing_key();
+        let (address, _dtk) = fvk.payment_address(0u64.into());
+
+        let value: Value = "10upenumbra".parse().unwrap();
+        let note = Note::generate(&mut rng, &address, value);
+        let mut tree = tct::Tree::new();
+        tree.insert(tct::Witness::Keep, note.commit()).unwrap();
+        let position = tree.position_of(note.commit()).unwrap();
+
+        let plan = SpendPlan::new(&mut rng, note.clone(), position);
+        assert_eq!(plan.validate(fvk, &tree), Ok(()));
+
+        let wrong_position = SpendPlan::new(&mut rng, note, 1u64.into());
+        assert_eq!(
+            wrong_position.validate(fvk, &tree),
+            Err(SpendValidationError::PositionMismatch {
+                planned: 1u64.into(),
+                witnessed: position,
+            })
+        );
+
+        let other_fvk = SpendKey::from_seed_phrase(SeedPhrase::generate(rng), 0)
+            .full_viewing_key()
+            .clone();
+        assert_eq!(
+            plan.validate(&other_fvk, &tree),
+            Err(SpendValidationError::NotViewable)
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::{anyhow, Result};
-use ark_ff::UniformRand;
 
 use penumbra_crypto::dex::swap::SwapPlaintext;
 use penumbra_crypto::Balance;
-use penumbra_crypto::{ka, proofs::transparent::SwapProof, FieldExt, Fr, FullViewingKey, Value};
+use penumbra_crypto::{
+    ka, proofs::transparent::SwapProof, sample, FieldExt, Fr, FullViewingKey, Value,
+};
 use penumbra_proto::{core::dex::v1alpha1 as pb, Protobuf};
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
@@ -22,7 +23,7 @@ pub struct SwapPlan {
 impl SwapPlan {
     /// Create a new [`SwapPlan`] that requests a swap between the given assets and input amounts.
     pub fn new<R: CryptoRng + RngCore>(rng: &mut R, swap_plaintext: SwapPlaintext) -> SwapPlan {
-        let fee_blinding = Fr::rand(rng);
+        let fee_blinding = sample::blinding_factor(rng);
         let esk = ka::Secret::new(rng);
 
         SwapPlan {
@@ -1,4 +1,7 @@
-use penumbra_crypto::keys::SpendKey;
+use penumbra_crypto::{
+    keys::SpendKey,
+    signing::{self, SpendAuthorization},
+};
 use rand::{CryptoRng, RngCore};
 
 use crate::{plan::TransactionPlan, AuthorizationData};
@@ -16,7 +19,8 @@ impl TransactionPlan {
         let mut spend_auths = Vec::new();
         for spend_plan in self.spend_plans() {
             let rsk = sk.spend_auth_key().randomize(&spend_plan.randomizer);
-            let auth_sig = rsk.sign(&mut rng, effect_hash.as_ref());
+            let auth_sig =
+                signing::sign::<SpendAuthorization, _>(&mut rng, &rsk, effect_hash.as_ref());
             spend_auths.push(auth_sig);
         }
         AuthorizationData {
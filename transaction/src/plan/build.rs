@@ -1,8 +1,12 @@
 use anyhow::{Context, Result};
 use penumbra_crypto::{
-    memo::MemoCiphertext, rdsa, symmetric::PayloadKey, Fr, FullViewingKey, Zero,
+    memo::MemoCiphertext,
+    proofs::ProofRng,
+    rdsa,
+    signing::{self, BalanceBinding},
+    symmetric::PayloadKey,
+    Fr, FullViewingKey, Zero,
 };
-use rand_core::{CryptoRng, RngCore};
 
 use super::TransactionPlan;
 use crate::{action::Action, AuthorizationData, Transaction, TransactionBody, WitnessData};
@@ -16,9 +20,12 @@ impl TransactionPlan {
     /// - `auth_data`, the [`AuthorizationData`] authorizing the transaction;
     /// - `witness_data`, the [`WitnessData`] used for proving;
     ///
-    pub fn build<R: CryptoRng + RngCore>(
+    /// All the randomness used in building is drawn from `rng`, so building the same plan with the
+    /// same authorization and witness data from a [`ProofRng`] with a fixed seed always gives the
+    /// same transaction.
+    pub fn build(
         self,
-        rng: &mut R,
+        rng: &mut ProofRng,
         fvk: &FullViewingKey,
         auth_data: AuthorizationData,
         witness_data: WitnessData,
@@ -59,7 +66,7 @@ impl TransactionPlan {
                 .get(&note_commitment)
                 .context(format!("could not get proof for {:?}", note_commitment))?;
 
-            synthetic_blinding_factor += spend_plan.value_blinding;
+            synthetic_blinding_factor += spend_plan.value_blinding.to_scalar();
             actions.push(Action::Spend(spend_plan.spend(
                 fvk,
                 auth_sig,
@@ -73,7 +80,7 @@ impl TransactionPlan {
         // field with a dummy key.
         for output_plan in self.output_plans() {
             // Outputs subtract from the transaction's value balance.
-            synthetic_blinding_factor += output_plan.value_blinding;
+            synthetic_blinding_factor += output_plan.value_blinding.to_scalar();
             actions.push(Action::Output(output_plan.output(
                 fvk.outgoing(),
                 memo_key.as_ref().unwrap_or(&dummy_payload_key),
@@ -145,7 +152,11 @@ impl TransactionPlan {
 
         // Finally, compute the binding signature and assemble the transaction.
         let binding_signing_key = rdsa::SigningKey::from(synthetic_blinding_factor);
-        let binding_sig = binding_signing_key.sign(rng, auth_data.effect_hash.as_ref());
+        let binding_sig = signing::sign::<BalanceBinding, _>(
+            rng,
+            &binding_signing_key,
+            auth_data.effect_hash.as_ref(),
+        );
         tracing::debug!(bvk = ?rdsa::VerificationKey::from(&binding_signing_key), effect_hash = ?auth_data.effect_hash);
 
         // TODO: add consistency checks?
@@ -4,15 +4,16 @@ use std::{
 };
 
 use anyhow::Error;
-use ark_ff::Zero;
 use bytes::Bytes;
 use decaf377_fmd::Clue;
 use penumbra_crypto::{
+    balance,
     memo::MemoCiphertext,
     note::Commitment,
-    rdsa::{Binding, Signature, VerificationKey, VerificationKeyBytes},
+    rdsa::{Binding, Signature, VerificationKey},
+    signing::SigningPolicy,
     transaction::Fee,
-    Fr, FullViewingKey, Note, Nullifier, PayloadKey,
+    FullViewingKey, Note, Nullifier, PayloadKey,
 };
 use penumbra_proto::{
     core::ibc::v1alpha1 as pb_ibc, core::stake::v1alpha1 as pbs,
@@ -22,7 +23,9 @@ use penumbra_tct as tct;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    action::{Delegate, Output, ProposalSubmit, ProposalWithdraw, Swap, Undelegate, ValidatorVote},
+    action::{
+        Delegate, Output, ProposalSubmit, ProposalWithdraw, Spend, Swap, Undelegate, ValidatorVote,
+    },
     view::action_view::OutputView,
     Action, ActionView, IsAction, TransactionPerspective, TransactionView,
 };
@@ -244,6 +247,16 @@ impl Transaction {
         })
     }
 
+    pub fn spends(&self) -> impl Iterator<Item = &Spend> {
+        self.actions().filter_map(|action| {
+            if let Action::Spend(s) = action {
+                Some(s)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn outputs(&self) -> impl Iterator<Item = &Output> {
         self.actions().filter_map(|action| {
             if let Action::Output(d) = action {
@@ -294,24 +307,33 @@ impl Transaction {
         id_bytes
     }
 
+    /// The balance commitments of the transaction's actions.
+    pub fn balance_commitments(&self) -> Vec<balance::Commitment> {
+        self.transaction_body
+            .actions
+            .iter()
+            .map(|action| action.balance_commitment())
+            .collect()
+    }
+
     /// Compute the binding verification key from the transaction data.
     pub fn binding_verification_key(&self) -> VerificationKey<Binding> {
-        let mut balance_commitments = decaf377::Element::default();
-        for action in &self.transaction_body.actions {
-            balance_commitments += action.balance_commitment().0;
-        }
-
-        // Add fee into binding verification key computation.
-        let fee_v_blinding = Fr::zero();
-        let fee_value_commitment = self.transaction_body.fee.commit(fee_v_blinding);
-        balance_commitments += fee_value_commitment.0;
-
-        let binding_verification_key_bytes: VerificationKeyBytes<Binding> =
-            balance_commitments.vartime_compress().0.into();
+        balance::Commitment::binding_verification_key(
+            &self.balance_commitments(),
+            &self.transaction_body.fee,
+        )
+    }
 
-        binding_verification_key_bytes
-            .try_into()
-            .expect("verification key is valid")
+    /// Check that the transaction's actions and fee balance, by verifying its
+    /// binding signature under the signing `policy`.
+    pub fn verify_balance(&self, policy: SigningPolicy) -> Result<(), balance::commitment::Error> {
+        balance::Commitment::verify_balance(
+            policy,
+            &self.balance_commitments(),
+            &self.transaction_body.fee,
+            self.transaction_body.effect_hash().as_ref(),
+            &self.binding_sig,
+        )
     }
 }
 
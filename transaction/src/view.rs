@@ -1,5 +1,5 @@
 use decaf377_fmd::Clue;
-use penumbra_crypto::transaction::Fee;
+use penumbra_crypto::{transaction::Fee, Balance, FullViewingKey, Note};
 use penumbra_proto::{core::transaction::v1alpha1 as pbt, Protobuf};
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +20,51 @@ pub struct TransactionView {
     pub memo: Option<String>,
 }
 
+impl TransactionView {
+    /// The notes created by this transaction that are controlled by `fvk`.
+    pub fn owned_outputs<'a>(
+        &'a self,
+        fvk: &'a FullViewingKey,
+    ) -> impl Iterator<Item = &'a Note> + 'a {
+        self.action_views.iter().filter_map(move |action_view| {
+            if let ActionView::Output(action_view::OutputView::Visible { note, .. }) = action_view {
+                fvk.controls(note).then_some(note)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The notes consumed by this transaction that are controlled by `fvk`.
+    pub fn owned_spends<'a>(
+        &'a self,
+        fvk: &'a FullViewingKey,
+    ) -> impl Iterator<Item = &'a Note> + 'a {
+        self.action_views.iter().filter_map(move |action_view| {
+            if let ActionView::Spend(action_view::SpendView::Visible { note, .. }) = action_view {
+                fvk.controls(note).then_some(note)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The net change to the balance of `fvk` caused by this transaction.
+    ///
+    /// Values received are provided, and values spent are required, so any
+    /// fee paid by the holder of `fvk` shows up as a required value.
+    pub fn balance_change(&self, fvk: &FullViewingKey) -> Balance {
+        let mut balance = Balance::zero();
+        for note in self.owned_outputs(fvk) {
+            balance += note.value();
+        }
+        for note in self.owned_spends(fvk) {
+            balance -= note.value();
+        }
+        balance
+    }
+}
+
 impl Protobuf<pbt::TransactionView> for TransactionView {}
 
 impl TryFrom<pbt::TransactionView> for TransactionView {
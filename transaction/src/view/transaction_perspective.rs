@@ -1,9 +1,11 @@
-use penumbra_crypto::{note, Note, Nullifier, PayloadKey};
+use penumbra_crypto::{note, FullViewingKey, Note, Nullifier, PayloadKey};
 use penumbra_proto::core::transaction::v1alpha1::{
     self as pb, NullifierWithNote, PayloadKeyWithCommitment,
 };
 use std::collections::BTreeMap;
 
+use crate::Transaction;
+
 /// This represents the data to understand an individual transaction without
 /// disclosing viewing keys.
 pub struct TransactionPerspective {
@@ -28,7 +30,35 @@ pub struct TransactionPerspective {
     pub advice_notes: BTreeMap<note::Commitment, Note>,
 }
 
-impl TransactionPerspective {}
+impl TransactionPerspective {
+    /// Build the perspective of the holder of `fvk` on the given transaction.
+    ///
+    /// Payload keys are derived for every output and swap the `fvk` can
+    /// decrypt, whether incoming or outgoing. Since spends only reveal
+    /// nullifiers, the notes they consume cannot be recovered from the
+    /// transaction alone: callers should supply the `spent_notes` they know
+    /// about, and only those whose nullifiers appear in the transaction are
+    /// retained.
+    pub fn from_fvk(
+        fvk: &FullViewingKey,
+        transaction: &Transaction,
+        spent_notes: impl IntoIterator<Item = (Nullifier, Note)>,
+    ) -> anyhow::Result<Self> {
+        let payload_keys = transaction.payload_keys(fvk)?;
+
+        let nullifiers = transaction.spent_nullifiers().collect::<Vec<_>>();
+        let spend_nullifiers = spent_notes
+            .into_iter()
+            .filter(|(nullifier, _)| nullifiers.contains(nullifier))
+            .collect();
+
+        Ok(Self {
+            payload_keys,
+            spend_nullifiers,
+            advice_notes: BTreeMap::new(),
+        })
+    }
+}
 
 impl From<TransactionPerspective> for pb::TransactionPerspective {
     fn from(msg: TransactionPerspective) -> Self {
@@ -0,0 +1,219 @@
+//! Transaction-level checks that spends cannot be malleated.
+//!
+//! A spend's auth signature is made over the effect hash of the whole
+//! transaction body, which commits to every field of every action body. So
+//! changing any field of a signed spend, or replacing it outright, must change
+//! the effect hash and invalidate the signature, even though the spend proof
+//! itself is not bound to the body.
+
+use penumbra_crypto::{
+    asset,
+    keys::{SeedPhrase, SpendKey},
+    proofs::transparent::SpendProof,
+    rdsa::{SpendAuth, VerificationKey},
+    signing::{self, SpendAuthorization},
+    transaction::Fee,
+    Fr, Note, Value,
+};
+use penumbra_tct as tct;
+use penumbra_transaction::{
+    action::{spend, Spend},
+    Action, TransactionBody,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+/// A transaction body containing a single spend of a note controlled by `sk`.
+struct SignedSpend {
+    body: TransactionBody,
+    sk: SpendKey,
+}
+
+impl SignedSpend {
+    fn new(rng: &mut ChaCha20Rng) -> Self {
+        let sk = spend_key();
+        let address = body_address();
+        let value = Value {
+            amount: 10u64.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(rng, &address, value);
+        let note_commitment = note.commit();
+
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+
+        let v_blinding = Fr::from(3u64);
+        let spend_auth_randomizer = Fr::from(5u64);
+        let nk = *sk.nullifier_key();
+        let rsk = sk.spend_auth_key().randomize(&spend_auth_randomizer);
+        // Replaced by a signature over the effect hash below.
+        let placeholder_sig = rsk.sign(&mut *rng, b"");
+        let spend = Spend {
+            body: spend::Body {
+                balance_commitment: value.commit(v_blinding),
+                nullifier: nk.derive_nullifier(0.into(), &note_commitment),
+                rk: rsk.into(),
+            },
+            auth_sig: placeholder_sig,
+            proof: SpendProof {
+                note_commitment_proof,
+                note,
+                v_blinding,
+                spend_auth_randomizer,
+                ak: sk.spend_auth_key().into(),
+                nk,
+            },
+        };
+
+        let mut signed = Self {
+            body: TransactionBody {
+                actions: vec![Action::Spend(spend)],
+                expiry_height: 0,
+                chain_id: "penumbra-test".to_string(),
+                fee: Fee::default(),
+                fmd_clues: Vec::new(),
+                memo: None,
+            },
+            sk,
+        };
+        signed.sign(rng, spend_auth_randomizer);
+        signed
+    }
+
+    /// Sign the current body with the key randomized by `spend_auth_randomizer`.
+    fn sign(&mut self, rng: &mut ChaCha20Rng, spend_auth_randomizer: Fr) {
+        let rsk = self.sk.spend_auth_key().randomize(&spend_auth_randomizer);
+        let effect_hash = self.body.effect_hash();
+        let auth_sig = signing::sign::<SpendAuthorization, _>(rng, &rsk, effect_hash.as_ref());
+        self.spend_mut().auth_sig = auth_sig;
+    }
+
+    fn spend(&self) -> &Spend {
+        match &self.body.actions[0] {
+            Action::Spend(spend) => spend,
+            _ => unreachable!("the body contains a single spend"),
+        }
+    }
+
+    fn spend_mut(&mut self) -> &mut Spend {
+        match &mut self.body.actions[0] {
+            Action::Spend(spend) => spend,
+            _ => unreachable!("the body contains a single spend"),
+        }
+    }
+
+    /// Check the spend's auth signature as the chain does, against the
+    /// effect hash of the body as it is now.
+    fn is_authorized(&self) -> bool {
+        let spend = self.spend();
+        signing::verify::<SpendAuthorization>(
+            &spend.body.rk,
+            self.body.effect_hash().as_ref(),
+            &spend.auth_sig,
+        )
+        .is_ok()
+    }
+}
+
+/// Apply `malleate` to the body of a signed spend, and check that the effect
+/// hash changes and the auth signature no longer verifies.
+fn assert_malleation_rejected(malleate: impl FnOnce(&mut spend::Body)) {
+    let mut rng = ChaCha20Rng::seed_from_u64(1312);
+    let mut signed = SignedSpend::new(&mut rng);
+    assert!(signed.is_authorized());
+
+    let original_effect_hash = signed.body.effect_hash();
+    malleate(&mut signed.spend_mut().body);
+
+    assert_ne!(signed.body.effect_hash(), original_effect_hash);
+    assert!(!signed.is_authorized());
+}
+
+#[test]
+fn resigning_after_changes_authorizes_spend() {
+    let mut rng = ChaCha20Rng::seed_from_u64(1312);
+    let mut signed = SignedSpend::new(&mut rng);
+    signed.spend_mut().body.balance_commitment = Value {
+        amount: 11u64.into(),
+        asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+    }
+    .commit(Fr::from(3u64));
+    assert!(!signed.is_authorized());
+
+    // The signature binds the body, not the key: re-signing restores it.
+    signed.sign(&mut rng, Fr::from(5u64));
+    assert!(signed.is_authorized());
+}
+
+#[test]
+fn changing_balance_commitment_invalidates_auth_sig() {
+    assert_malleation_rejected(|body| {
+        body.balance_commitment = Value {
+            amount: 1_000_000u64.into(),
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        }
+        .commit(Fr::from(3u64));
+    });
+}
+
+#[test]
+fn changing_nullifier_invalidates_auth_sig() {
+    assert_malleation_rejected(|body| {
+        let other = Note::generate(
+            &mut ChaCha20Rng::seed_from_u64(7),
+            &body_address(),
+            Value {
+                amount: 10u64.into(),
+                asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+            },
+        );
+        body.nullifier = spend_key()
+            .nullifier_key()
+            .derive_nullifier(0.into(), &other.commit());
+    });
+}
+
+#[test]
+fn rerandomizing_rk_invalidates_auth_sig() {
+    // Even a different randomization of the same spend authorization key
+    // is a different effect, which the existing signature does not cover.
+    assert_malleation_rejected(|body| {
+        let rk: VerificationKey<SpendAuth> = spend_key()
+            .spend_auth_key()
+            .randomize(&Fr::from(6u64))
+            .into();
+        body.rk = rk;
+    });
+}
+
+#[test]
+fn replacing_spend_body_invalidates_auth_sig() {
+    let mut rng = ChaCha20Rng::seed_from_u64(1312);
+    let mut signed = SignedSpend::new(&mut rng);
+    let mut other = SignedSpend::new(&mut ChaCha20Rng::seed_from_u64(1313));
+    assert!(other.is_authorized());
+
+    // Our signature doesn't authorize another spend of the same key.
+    let auth_sig = signed.spend().auth_sig;
+    other.spend_mut().auth_sig = auth_sig;
+    assert!(!other.is_authorized());
+
+    // Moving the signature to an otherwise identical transaction with a
+    // different chain ID is also rejected.
+    signed.body.chain_id = "penumbra-other".to_string();
+    assert!(!signed.is_authorized());
+}
+
+fn spend_key() -> SpendKey {
+    SpendKey::from_seed_phrase(SeedPhrase::from_randomness([7u8; 32]), 0)
+}
+
+fn body_address() -> penumbra_crypto::Address {
+    spend_key()
+        .full_viewing_key()
+        .incoming()
+        .payment_address(0u64.into())
+        .0
+}
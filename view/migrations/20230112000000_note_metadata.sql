@@ -0,0 +1,13 @@
+-- Opaque, client-defined metadata about notes, which is never included on-chain.
+--
+-- Metadata about planned outputs is recorded when their transaction is
+-- witnessed, and moved onto the spendable note record when the note is
+-- detected.
+CREATE TABLE planned_note_metadata (
+    note_commitment         BLOB PRIMARY KEY NOT NULL,
+    -- the encoded NoteMetadata protobuf message
+    note_metadata           BLOB NOT NULL
+);
+
+-- null if the note was not planned with any metadata
+ALTER TABLE spendable_notes ADD COLUMN note_metadata BLOB;
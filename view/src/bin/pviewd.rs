@@ -6,7 +6,7 @@ use penumbra_crypto::FullViewingKey;
 use penumbra_proto::client::v1alpha1::oblivious_query_service_client::ObliviousQueryServiceClient;
 use penumbra_proto::client::v1alpha1::ChainParametersRequest;
 use penumbra_proto::view::v1alpha1::view_protocol_service_server::ViewProtocolServiceServer;
-use penumbra_view::ViewService;
+use penumbra_view::{ScanOptions, ViewService};
 use std::env;
 use std::str::FromStr;
 use tonic::transport::Server;
@@ -47,6 +47,10 @@ enum Command {
         /// Bind the view gRPC server to this port.
         #[clap(long, default_value = "8081")]
         view_port: u16,
+        /// Recompute the note commitment of every detected note while scanning, rejecting notes
+        /// that don't match the commitment on chain.
+        #[clap(long)]
+        verify_note_commitments: bool,
     },
 }
 #[tokio::main]
@@ -79,12 +83,25 @@ async fn main() -> Result<()> {
             .await?;
             Ok(())
         }
-        Command::Start { host, view_port } => {
+        Command::Start {
+            host,
+            view_port,
+            verify_note_commitments,
+        } => {
             tracing::info!(?opt.sqlite_path, ?host, ?view_port, ?opt.node, ?opt.pd_port, "starting pviewd");
 
             let storage = penumbra_view::Storage::load(opt.sqlite_path).await?;
 
-            let service = ViewService::new(storage, opt.node, opt.pd_port).await?;
+            let service = ViewService::new_with_options(
+                storage,
+                opt.node,
+                opt.pd_port,
+                ScanOptions {
+                    verify_note_commitments,
+                    ..Default::default()
+                },
+            )
+            .await?;
 
             tokio::spawn(
                 Server::builder()
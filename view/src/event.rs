@@ -0,0 +1,52 @@
+use futures::{Stream, StreamExt};
+use penumbra_crypto::Nullifier;
+use penumbra_tct as tct;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+use crate::SpendableNoteRecord;
+
+/// The number of events buffered for each subscriber before it starts missing
+/// events.
+pub(crate) const WALLET_EVENT_CAPACITY: usize = 1024;
+
+/// A change to the wallet's state, found while syncing.
+///
+/// Events are only published once the change they describe has been committed
+/// to [`Storage`](crate::Storage), so a subscriber can always query the
+/// storage for more detail about an event it receives.
+#[derive(Clone, Debug)]
+pub enum WalletEvent {
+    /// A note spendable by the wallet was detected, as recorded in storage,
+    /// including any metadata planned for it.
+    NoteReceived(SpendableNoteRecord),
+    /// One of the wallet's notes was spent, revealing the given nullifier.
+    NoteSpent(Nullifier),
+    /// The wallet has been synced up to and including the given height.
+    SyncProgress { height: u64 },
+    /// The epoch with the given index ended, with the given root.
+    EpochRoot {
+        epoch: u64,
+        height: u64,
+        root: tct::builder::epoch::Root,
+    },
+}
+
+/// Turn a subscription to the wallet event channel into a stream.
+///
+/// A subscriber that falls more than [`WALLET_EVENT_CAPACITY`] events behind
+/// misses the oldest events; this is logged, and the subscriber should
+/// re-read any state it needs from storage.
+pub(crate) fn stream(
+    rx: broadcast::Receiver<WalletEvent>,
+) -> impl Stream<Item = WalletEvent> + Send + 'static {
+    BroadcastStream::new(rx).filter_map(|event| async move {
+        match event {
+            Ok(event) => Some(event),
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                tracing::warn!(missed, "wallet event subscriber lagged, dropping events");
+                None
+            }
+        }
+    })
+}
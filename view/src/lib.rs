@@ -1,7 +1,9 @@
 #![recursion_limit = "256"]
 mod client;
+mod event;
 mod metrics;
 mod note_record;
+mod retention;
 mod service;
 mod status;
 mod storage;
@@ -13,8 +15,11 @@ use worker::Worker;
 
 pub use crate::metrics::register_metrics;
 pub use client::ViewClient;
+pub use event::WalletEvent;
 pub use note_record::SpendableNoteRecord;
+pub use retention::{Prune, RetentionPolicy};
 pub use service::ViewService;
 pub use status::StatusStreamResponse;
 pub use storage::Storage;
 pub use swap_record::SwapRecord;
+pub use sync::ScanOptions;
@@ -15,15 +15,14 @@ pub use metrics::*;
 
 /// Registers all metrics used by this crate.
 pub fn register_metrics() {
-    /*
-    // Sample code for reference -- delete when adding the first metric
-    register_counter!(MEMPOOL_CHECKTX_TOTAL);
+    penumbra_tct::metrics::register_metrics();
+
+    register_counter!(SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL);
     describe_counter!(
-        MEMPOOL_CHECKTX_TOTAL,
-        "The total number of checktx requests made to the mempool"
+        SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL,
+        "The total number of detected notes rejected while scanning because they do not match their on-chain note commitment"
     );
-     */
 }
 
-// Sample code for reference -- delete when adding the first metric
-// pub const MEMPOOL_CHECKTX_TOTAL: &str = "penumbra_pd_mempool_checktx_total";
+pub const SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL: &str =
+    "penumbra_view_scan_note_commitment_mismatches_total";
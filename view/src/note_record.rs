@@ -1,8 +1,12 @@
+use std::collections::BTreeMap;
+
 use penumbra_chain::NoteSource;
 use penumbra_crypto::{
     asset, keys::AddressIndex, note, Address, FieldExt, Fq, Note, Nullifier, Rseed, Value,
 };
-use penumbra_proto::{view::v1alpha1 as pb, Protobuf};
+use penumbra_proto::{
+    core::crypto::v1alpha1 as pb_crypto, view::v1alpha1 as pb, Message, Protobuf,
+};
 use penumbra_tct as tct;
 
 use serde::{Deserialize, Serialize};
@@ -20,6 +24,8 @@ pub struct SpendableNoteRecord {
     pub height_spent: Option<u64>,
     pub position: tct::Position,
     pub source: NoteSource,
+    /// Opaque metadata about the note, recorded from the plan that created it.
+    pub note_metadata: BTreeMap<String, Vec<u8>>,
 }
 
 impl Protobuf<pb::SpendableNoteRecord> for SpendableNoteRecord {}
@@ -34,6 +40,9 @@ impl From<SpendableNoteRecord> for pb::SpendableNoteRecord {
             height_spent: v.height_spent,
             position: v.position.into(),
             source: Some(v.source.into()),
+            note_metadata: (!v.note_metadata.is_empty()).then(|| pb_crypto::NoteMetadata {
+                entries: v.note_metadata,
+            }),
         }
     }
 }
@@ -65,6 +74,10 @@ impl TryFrom<pb::SpendableNoteRecord> for SpendableNoteRecord {
                 .source
                 .ok_or_else(|| anyhow::anyhow!("missing note source"))?
                 .try_into()?,
+            note_metadata: v
+                .note_metadata
+                .map(|metadata| metadata.entries)
+                .unwrap_or_default(),
         })
     }
 }
@@ -132,7 +145,11 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for SpendableNoteRecord {
         let height_spent = row
             .get::<'r, Option<i64>, _>("height_spent")
             .map(|v| v as u64);
-        let position = (row.get::<'r, i64, _>("position") as u64).into();
+        let position = tct::Position::checked_from_u64(row.get::<'r, i64, _>("position") as u64)
+            .map_err(|e| sqlx::Error::ColumnDecode {
+                index: "position".to_string(),
+                source: e.into(),
+            })?;
 
         let value = Value {
             amount: amount.into(),
@@ -151,6 +168,19 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for SpendableNoteRecord {
             }
         })?;
 
+        // Notes planned without metadata, or detected before it could be recorded, have none.
+        let note_metadata = match row.try_get::<'r, Option<&[u8]>, _>("note_metadata") {
+            Ok(Some(bytes)) => {
+                pb_crypto::NoteMetadata::decode(bytes)
+                    .map_err(|e| sqlx::Error::ColumnDecode {
+                        index: "note_metadata".to_string(),
+                        source: e.into(),
+                    })?
+                    .entries
+            }
+            _ => BTreeMap::new(),
+        };
+
         Ok(SpendableNoteRecord {
             note_commitment,
             note,
@@ -160,6 +190,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for SpendableNoteRecord {
             height_created,
             height_spent,
             source,
+            note_metadata,
         })
     }
 }
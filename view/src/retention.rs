@@ -0,0 +1,57 @@
+use std::ops::Range;
+
+use penumbra_chain::Epoch;
+
+/// Which witnesses the view service keeps in its note commitment tree once notes are spent.
+///
+/// The witness of an unspent note is always kept, since it's needed to spend the note. The policy
+/// is evaluated by the sync worker whenever it records a block that needed scanning, so nothing
+/// else needs to forget spent notes. Empty blocks skip the database, so notes due to be forgotten
+/// at one are forgotten at the next block that is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionPolicy {
+    /// Keep the witness of every note, even after it's spent.
+    KeepAll,
+    /// Forget the witness of each note in the block it's spent in.
+    KeepUnspent,
+    /// Keep the witnesses of notes spent in the current epoch or in this many epochs before it,
+    /// forgetting the witnesses of notes spent earlier.
+    KeepRecentEpochs(u64),
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy::KeepUnspent
+    }
+}
+
+/// The spent notes to forget when recording a block, as decided by a [`RetentionPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prune {
+    /// Forget nothing.
+    Nothing,
+    /// Forget the notes spent in the block being recorded.
+    SpentInBlock,
+    /// Forget the notes spent at any height in the range.
+    SpentBetween(Range<u64>),
+}
+
+impl RetentionPolicy {
+    /// Decide what to forget when recording the block at `height`, given that every note spent
+    /// below the height `swept` has already been forgotten.
+    pub fn prune(&self, height: u64, epoch_duration: u64, swept: u64) -> Prune {
+        match *self {
+            RetentionPolicy::KeepAll => Prune::Nothing,
+            RetentionPolicy::KeepUnspent => Prune::SpentInBlock,
+            RetentionPolicy::KeepRecentEpochs(epochs) => {
+                let epoch = Epoch::from_height(height, epoch_duration);
+                let cutoff = epoch.index.saturating_sub(epochs) * epoch_duration;
+                if cutoff > swept {
+                    Prune::SpentBetween(swept..cutoff)
+                } else {
+                    Prune::Nothing
+                }
+            }
+        }
+    }
+}
@@ -32,7 +32,7 @@ use tokio_stream::wrappers::WatchStream;
 use tonic::async_trait;
 use tracing::instrument;
 
-use crate::{Storage, Worker};
+use crate::{ScanOptions, Storage, WalletEvent, Worker};
 
 /// A service that synchronizes private chain state and responds to queries
 /// about it.
@@ -80,8 +80,19 @@ impl ViewService {
     /// by this method, rather than calling it multiple times.  That way, each clone
     /// will be backed by the same scanning task, rather than each spawning its own.
     pub async fn new(storage: Storage, node: String, pd_port: u16) -> Result<Self, anyhow::Error> {
+        Self::new_with_options(storage, node, pd_port, ScanOptions::default()).await
+    }
+
+    /// Constructs a new [`ViewService`] like [`Self::new`], scanning blocks with the given
+    /// options.
+    pub async fn new_with_options(
+        storage: Storage,
+        node: String,
+        pd_port: u16,
+        scan_options: ScanOptions,
+    ) -> Result<Self, anyhow::Error> {
         let (worker, nct, error_slot, sync_height_rx) =
-            Worker::new(storage.clone(), node.clone(), pd_port).await?;
+            Worker::new(storage.clone(), node.clone(), pd_port, scan_options).await?;
 
         tokio::spawn(worker.run());
 
@@ -99,6 +110,12 @@ impl ViewService {
         })
     }
 
+    /// Subscribe to the [`WalletEvent`]s found by this service's sync worker,
+    /// as an alternative to polling its storage for changes.
+    pub fn wallet_events(&self) -> impl futures::Stream<Item = WalletEvent> + Send + 'static {
+        self.storage.wallet_events()
+    }
+
     async fn check_fvk(&self, fvk: Option<&pbc::AccountId>) -> Result<(), tonic::Status> {
         // Takes an Option to avoid making the caller handle missing fields,
         // should error on None or wrong account ID
@@ -758,6 +775,23 @@ impl ViewProtocolService for ViewService {
             witness_data.add_proof(nc, Proof::dummy(&mut OsRng, nc));
         }
 
+        // Remember any metadata about the planned outputs, so that it can be
+        // attached to the notes when they are detected.
+        for output_plan in tx_plan
+            .output_plans()
+            .filter(|plan| !plan.note_metadata.is_empty())
+        {
+            self.storage
+                .record_planned_note_metadata(
+                    output_plan.output_note().commit(),
+                    output_plan.note_metadata.clone(),
+                )
+                .await
+                .map_err(|e| {
+                    tonic::Status::internal(format!("error recording note metadata: {}", e))
+                })?;
+        }
+
         let witness_response = WitnessResponse {
             witness_data: Some(witness_data.into()),
         };
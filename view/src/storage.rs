@@ -11,7 +11,8 @@ use penumbra_proto::{
     client::v1alpha1::{
         oblivious_query_service_client::ObliviousQueryServiceClient, ChainParametersRequest,
     },
-    Protobuf,
+    core::crypto::v1alpha1 as pb_crypto,
+    Message, Protobuf,
 };
 use penumbra_tct as tct;
 use penumbra_transaction::Transaction;
@@ -21,7 +22,11 @@ use std::{collections::BTreeMap, num::NonZeroU64, sync::Arc};
 use tct::Commitment;
 use tokio::sync::broadcast::{self, error::RecvError};
 
-use crate::{sync::FilteredBlock, SpendableNoteRecord, SwapRecord};
+use crate::{
+    event::{self, WalletEvent},
+    sync::FilteredBlock,
+    Prune, SpendableNoteRecord, SwapRecord,
+};
 
 mod nct;
 use nct::TreeStore;
@@ -41,6 +46,7 @@ pub struct Storage {
 
     scanned_notes_tx: tokio::sync::broadcast::Sender<SpendableNoteRecord>,
     scanned_nullifiers_tx: tokio::sync::broadcast::Sender<Nullifier>,
+    wallet_events_tx: tokio::sync::broadcast::Sender<WalletEvent>,
 }
 
 impl Storage {
@@ -102,6 +108,7 @@ impl Storage {
             uncommitted_height: Arc::new(Mutex::new(None)),
             scanned_notes_tx: broadcast::channel(10).0,
             scanned_nullifiers_tx: broadcast::channel(10).0,
+            wallet_events_tx: broadcast::channel(event::WALLET_EVENT_CAPACITY).0,
         })
     }
 
@@ -158,9 +165,23 @@ impl Storage {
             uncommitted_height: Arc::new(Mutex::new(None)),
             scanned_notes_tx: broadcast::channel(10).0,
             scanned_nullifiers_tx: broadcast::channel(10).0,
+            wallet_events_tx: broadcast::channel(event::WALLET_EVENT_CAPACITY).0,
         })
     }
 
+    /// Subscribe to the [`WalletEvent`]s published as the wallet syncs.
+    ///
+    /// The stream only contains events published after subscribing.
+    pub fn wallet_events(&self) -> impl futures::Stream<Item = WalletEvent> + Send + 'static {
+        event::stream(self.wallet_events_tx.subscribe())
+    }
+
+    /// Publish a [`WalletEvent`] to all current subscribers.
+    pub(crate) fn publish_event(&self, event: WalletEvent) {
+        // This fails if there are no subscribers, in which case there is nothing to do.
+        let _ = self.wallet_events_tx.send(event);
+    }
+
     /// Query for account balance by address
     pub async fn balance_by_address(&self, address: Address) -> anyhow::Result<BTreeMap<Id, u64>> {
         let address = address.to_vec();
@@ -216,7 +237,8 @@ impl Storage {
                         spendable_notes.source,
                         spendable_notes.height_spent,
                         spendable_notes.nullifier,
-                        spendable_notes.position
+                        spendable_notes.position,
+                        spendable_notes.note_metadata
                     FROM notes
                     JOIN spendable_notes ON notes.note_commitment = spendable_notes.note_commitment
                     WHERE notes.note_commitment = x'{}'",
@@ -521,7 +543,8 @@ impl Storage {
                         spendable_notes.source,
                         spendable_notes.height_spent,
                         spendable_notes.nullifier,
-                        spendable_notes.position
+                        spendable_notes.position,
+                        spendable_notes.note_metadata
                     FROM notes
                     JOIN spendable_notes ON notes.note_commitment = spendable_notes.note_commitment
                     WHERE hex(spendable_notes.nullifier) = \"{}\"",
@@ -632,7 +655,8 @@ impl Storage {
                         spendable_notes.source,
                         spendable_notes.height_spent,
                         spendable_notes.nullifier,
-                        spendable_notes.position
+                        spendable_notes.position,
+                        spendable_notes.note_metadata
             FROM notes
             JOIN spendable_notes ON notes.note_commitment = spendable_notes.note_commitment
             WHERE spendable_notes.height_spent IS {}
@@ -759,6 +783,40 @@ impl Storage {
         Ok(())
     }
 
+    /// Record opaque metadata about a planned note, to be attached to its
+    /// [`SpendableNoteRecord`] when the note is detected.
+    pub async fn record_planned_note_metadata(
+        &self,
+        note_commitment: note::Commitment,
+        note_metadata: BTreeMap<String, Vec<u8>>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let note_commitment = note_commitment.0.to_bytes().to_vec();
+        let note_metadata = pb_crypto::NoteMetadata {
+            entries: note_metadata,
+        }
+        .encode_to_vec();
+
+        sqlx::query!(
+            "INSERT OR REPLACE INTO planned_note_metadata
+                    (
+                        note_commitment,
+                        note_metadata
+                    )
+                    VALUES
+                    (?, ?)",
+            note_commitment,
+            note_metadata,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     /// Return advice about note contents for use in scanning.
     ///
     /// Given a list of note commitments, this method checks whether any of them
@@ -831,7 +889,8 @@ impl Storage {
                         spendable_notes.source,
                         spendable_notes.height_spent,
                         spendable_notes.nullifier,
-                        spendable_notes.position
+                        spendable_notes.position,
+                        spendable_notes.note_metadata
                 FROM notes
                 JOIN spendable_notes ON notes.note_commitment = spendable_notes.note_commitment
                 WHERE spendable_notes.nullifier IN ({})",
@@ -850,12 +909,15 @@ impl Storage {
         .collect())
     }
 
+    /// Record a scanned block, returning its new notes as they were stored, i.e. with any
+    /// metadata planned for them attached.
     pub async fn record_block(
         &self,
         filtered_block: FilteredBlock,
         transactions: Vec<Transaction>,
         nct: &mut tct::Tree,
-    ) -> anyhow::Result<()> {
+        prune: &Prune,
+    ) -> anyhow::Result<Vec<SpendableNoteRecord>> {
         //Check that the incoming block height follows the latest recorded height
         let last_sync_height = self.last_sync_height().await?;
 
@@ -876,6 +938,7 @@ impl Storage {
         let mut dbtx = self.pool.begin().await?;
 
         // Insert new note records into storage
+        let mut new_notes = Vec::with_capacity(filtered_block.new_notes.len());
         for note_record in &filtered_block.new_notes {
             // https://github.com/launchbadge/sqlx/issues/1430
             // https://github.com/launchbadge/sqlx/issues/1151
@@ -925,10 +988,13 @@ impl Storage {
                         height_created,
                         address_index,
                         source,
-                        height_spent
+                        height_spent,
+                        note_metadata
                     )
                     VALUES
-                    (?, ?, ?, ?, ?, ?, NULL)",
+                    (?, ?, ?, ?, ?, ?, NULL, (
+                        SELECT note_metadata FROM planned_note_metadata WHERE note_commitment = ?
+                    ))",
                 note_commitment,
                 nullifier,
                 position,
@@ -936,6 +1002,28 @@ impl Storage {
                 address_index,
                 source,
                 // height_spent is NULL
+                // note_metadata is copied from any planned metadata
+                note_commitment,
+            )
+            .execute(&mut dbtx)
+            .await?;
+
+            // Attach the same planned metadata to the record we return.
+            let mut note_record = note_record.clone();
+            if let Some(row) =
+                query("SELECT note_metadata FROM planned_note_metadata WHERE note_commitment = ?")
+                    .bind(&note_commitment)
+                    .fetch_optional(&mut dbtx)
+                    .await?
+            {
+                note_record.note_metadata =
+                    pb_crypto::NoteMetadata::decode(row.get::<&[u8], _>("note_metadata"))?.entries;
+            }
+            new_notes.push(note_record);
+
+            sqlx::query!(
+                "DELETE FROM planned_note_metadata WHERE note_commitment = ?",
+                note_commitment,
             )
             .execute(&mut dbtx)
             .await?;
@@ -981,8 +1069,27 @@ impl Storage {
             // TODO: mark spent swaps as spent
 
             if let Some(bytes) = spent_commitment_bytes {
-                // Forget spent note commitments from the NCT
-                let spent_commitment = Commitment::try_from(bytes.note_commitment.as_slice())?;
+                if *prune == Prune::SpentInBlock {
+                    // Forget spent note commitments from the NCT
+                    let spent_commitment = Commitment::try_from(bytes.note_commitment.as_slice())?;
+                    nct.forget(spent_commitment);
+                }
+            }
+        }
+
+        // Forget notes spent long enough ago, according to the retention policy
+        if let Prune::SpentBetween(heights) = prune {
+            let rows = sqlx::query(
+                "SELECT note_commitment FROM spendable_notes
+                WHERE height_spent >= ? AND height_spent < ?",
+            )
+            .bind(heights.start as i64)
+            .bind(heights.end as i64)
+            .fetch_all(&mut dbtx)
+            .await?;
+            for row in rows {
+                let spent_commitment =
+                    Commitment::try_from(row.get::<&[u8], _>("note_commitment"))?;
                 nct.forget(spent_commitment);
             }
         }
@@ -1050,7 +1157,7 @@ impl Storage {
         // Broadcast all committed note records to channel
         // Done following tx.commit() to avoid notifying of a new SpendableNoteRecord before it is actually committed to the database
 
-        for note_record in &filtered_block.new_notes {
+        for note_record in &new_notes {
             // This will fail to be broadcast if there is no active receiver (such as on initial sync)
             // The error is ignored, as this isn't a problem, because if there is no active receiver there is nothing to do
             let _ = self.scanned_notes_tx.send(note_record.clone());
@@ -1062,6 +1169,6 @@ impl Storage {
             let _ = self.scanned_nullifiers_tx.send(*nullifier);
         }
 
-        Ok(())
+        Ok(new_notes)
     }
 }
@@ -21,7 +21,9 @@ impl AsyncRead for TreeStore<'_, '_> {
             .fetch_one(&mut *self.0)
             .await?
             .position
-            .map(|p| Position::from(p as u64))
+            .map(|p| Position::checked_from_u64(p as u64))
+            .transpose()
+            .context("could not decode tree position from local database")?
             .into())
     }
 
@@ -64,7 +66,7 @@ impl AsyncRead for TreeStore<'_, '_> {
                     let row = row?;
                     if let Either::Right(row) = row {
                         Ok::<_, Self::Error>(Some((
-                            Position::from(row.position as u64),
+                            Position::checked_from_u64(row.position as u64)?,
                             row.height as u8,
                             Hash::from_bytes(
                                 row.hash
@@ -111,7 +113,7 @@ impl AsyncRead for TreeStore<'_, '_> {
                     let row = row?;
                     if let Either::Right(row) = row {
                         Ok::<_, Self::Error>(Some((
-                            Position::from(row.position as u64),
+                            Position::checked_from_u64(row.position as u64)?,
                             Commitment::try_from(<[u8; 32]>::try_from(row.commitment).map_err(
                                 |_| anyhow::anyhow!("commitment was of incorrect length"),
                             )?)?,
@@ -96,7 +96,11 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for SwapRecord {
         let height_claimed = row
             .get::<'r, Option<i64>, _>("height_claimed")
             .map(|v| v as u64);
-        let position = (row.get::<'r, i64, _>("position") as u64).into();
+        let position = tct::Position::checked_from_u64(row.get::<'r, i64, _>("position") as u64)
+            .map_err(|e| sqlx::Error::ColumnDecode {
+                index: "position".to_string(),
+                source: e.into(),
+            })?;
         let source = NoteSource::try_from(row.get::<'r, &[u8], _>("source")).map_err(|e| {
             sqlx::Error::ColumnDecode {
                 index: "source".to_string(),
@@ -3,11 +3,27 @@ use std::collections::{BTreeMap, BTreeSet};
 use penumbra_chain::{params::FmdParameters, CompactBlock, Epoch, NoteSource, StatePayload};
 use penumbra_crypto::{
     dex::swap::{SwapPayload, SwapPlaintext},
-    EncryptedNote, FullViewingKey, Note, Nullifier,
+    EncryptedNote, FullViewingKey, Note, Nullifier, TrialDecryptError,
 };
 use penumbra_tct as tct;
 
-use crate::{SpendableNoteRecord, Storage, SwapRecord};
+use crate::{metrics, RetentionPolicy, SpendableNoteRecord, Storage, SwapRecord};
+
+/// Options for scanning blocks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanOptions {
+    /// Recompute the note commitment of each note found through scanning advice (e.g. the outputs
+    /// of our swaps), and reject the note if it differs from the commitment published on chain.
+    ///
+    /// Trial decryption always rejects decrypted notes that don't match the commitment published
+    /// with their payload, counting them in
+    /// [`SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL`](metrics::SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL),
+    /// but advice is trusted as stored. This re-checks advice too, counting its rejections in the
+    /// same metric.
+    pub verify_note_commitments: bool,
+    /// Which witnesses to keep once their notes are spent.
+    pub retention: RetentionPolicy,
+}
 
 /// Contains the results of scanning a single block.
 #[derive(Debug, Clone)]
@@ -17,6 +33,8 @@ pub struct FilteredBlock {
     pub spent_nullifiers: Vec<Nullifier>,
     pub height: u64,
     pub fmd_parameters: Option<FmdParameters>,
+    /// The root of the epoch ended by this block, if any.
+    pub epoch_root: Option<tct::builder::epoch::Root>,
 }
 
 impl FilteredBlock {
@@ -32,7 +50,14 @@ impl FilteredBlock {
     }
 }
 
-#[tracing::instrument(skip(fvk, note_commitment_tree, state_payloads, nullifiers, storage))]
+#[tracing::instrument(skip(
+    fvk,
+    note_commitment_tree,
+    state_payloads,
+    nullifiers,
+    storage,
+    options
+))]
 pub async fn scan_block(
     fvk: &FullViewingKey,
     note_commitment_tree: &mut tct::Tree,
@@ -48,14 +73,17 @@ pub async fn scan_block(
     }: CompactBlock,
     epoch_duration: u64,
     storage: &Storage,
+    options: ScanOptions,
 ) -> anyhow::Result<FilteredBlock> {
-    // Trial-decrypt a note with our own specific viewing key
-    let trial_decrypt_note =
-        |note_payload: EncryptedNote| -> tokio::task::JoinHandle<Option<Note>> {
+    // Trial-decrypt a batch of notes with our own specific viewing key
+    let trial_decrypt_notes =
+        |note_payloads: Vec<EncryptedNote>| -> tokio::task::JoinHandle<Vec<Result<Note, TrialDecryptError>>> {
             // TODO: change fvk to Arc<FVK> in Worker and pass to scan_block as Arc
             // need this so the task is 'static and not dependent on key lifetime
             let fvk2 = fvk.clone();
-            tokio::spawn(async move { note_payload.trial_decrypt(&fvk2) })
+            tokio::task::spawn_blocking(move || {
+                fvk2.incoming().trial_decrypt_batch_results(&note_payloads)
+            })
         };
     // Trial-decrypt a swap with our own specific viewing key
     let trial_decrypt_swap =
@@ -70,26 +98,37 @@ pub async fn scan_block(
     let spent_nullifiers: Vec<Nullifier> = nullifiers;
 
     // Trial-decrypt the notes in this block, keeping track of the ones that were meant for us
-    let mut note_decryptions = Vec::new();
+    let mut note_payloads = Vec::new();
     let mut swap_decryptions = Vec::new();
     let mut unknown_commitments = Vec::new();
 
     for payload in state_payloads.iter() {
         match payload {
-            StatePayload::Note { note, .. } => {
-                note_decryptions.push(trial_decrypt_note(note.clone()));
-            }
+            StatePayload::Note { note, .. } => note_payloads.push(note.clone()),
             StatePayload::Swap { swap, .. } => {
                 swap_decryptions.push(trial_decrypt_swap(swap.clone()));
             }
             StatePayload::RolledUp(commitment) => unknown_commitments.push(commitment.clone()),
         }
     }
+    // Notes are decrypted as one batch, so the key agreements can share the
+    // precomputation for our incoming viewing key, and are spread across the
+    // rayon thread pool.
+    let note_decryptions = trial_decrypt_notes(note_payloads);
     // Having started trial decryption in the background, ask the Storage for scanning advice:
     let mut note_advice = storage.scan_advice(unknown_commitments).await?;
-    for decryption in note_decryptions {
-        if let Some(note) = decryption.await.unwrap() {
-            note_advice.insert(note.commit(), note);
+    for decryption in note_decryptions.await.unwrap() {
+        match decryption {
+            Ok(note) => {
+                note_advice.insert(note.commit(), note);
+            }
+            Err(TrialDecryptError::CommitmentMismatch) => {
+                // Trial decryption has already warned about the note. As for an invalid swap
+                // output below, a forged payload is only visible to us, so keep going as though
+                // the note wasn't ours.
+                metrics::increment_counter!(metrics::SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL);
+            }
+            Err(_) => {}
         }
     }
     let mut swap_advice = BTreeMap::new();
@@ -116,10 +155,22 @@ pub async fn scan_block(
         for payload in state_payloads.into_iter() {
             // We need to insert each commitment, so use a match statement to ensure we
             // exhaustively cover all possible cases.
-            match (
-                note_advice.get(payload.commitment()),
-                swap_advice.get(payload.commitment()),
-            ) {
+            let mut detected_note = note_advice.get(payload.commitment());
+            if options.verify_note_commitments {
+                if let Some(note) = detected_note {
+                    if note.commit() != *payload.commitment() {
+                        tracing::warn!(
+                            ?height,
+                            note_commitment = ?payload.commitment(),
+                            "stored advice does not match its note commitment, ignoring it"
+                        );
+                        metrics::increment_counter!(metrics::SCAN_NOTE_COMMITMENT_MISMATCHES_TOTAL);
+                        detected_note = None;
+                    }
+                }
+            }
+
+            match (detected_note, swap_advice.get(payload.commitment())) {
                 (Some(note), None) => {
                     // Keep track of this commitment for later witnessing
                     let position = note_commitment_tree
@@ -139,6 +190,8 @@ pub async fn scan_block(
                         nullifier,
                         position,
                         source,
+                        // Filled in from any planned metadata when the note is recorded.
+                        note_metadata: Default::default(),
                     });
                 }
                 (None, Some(swap)) => {
@@ -188,17 +241,44 @@ pub async fn scan_block(
         }
 
         // End the block in the commitment tree
-        note_commitment_tree
+        let computed_block_root = note_commitment_tree
             .end_block()
             .expect("ending the block must succed");
+
+        // When we hash the block ourselves, it must agree with the published block root
+        if computed_block_root != block_root {
+            anyhow::bail!(
+                "block root mismatch at height {}: computed {:?}, but block claims {:?}",
+                height,
+                computed_block_root,
+                block_root
+            );
+        }
     }
 
     // If we've also reached the end of the epoch, end the epoch in the commitment tree
+    let mut ended_epoch_root = None;
     if Epoch::from_height(height, epoch_duration).is_epoch_end(height) {
         tracing::debug!(?height, "end of epoch");
-        note_commitment_tree
+        let computed_epoch_root = note_commitment_tree
             .end_epoch()
             .expect("ending the epoch must succeed");
+
+        // Blocks with nothing for us were inserted using only their published block roots,
+        // without hashing their contents. The epoch root commits to all of those block roots,
+        // so checking it against the published epoch root verifies every block root we
+        // skipped hashing during this epoch.
+        if let Some(epoch_root) = epoch_root {
+            if computed_epoch_root != epoch_root {
+                anyhow::bail!(
+                    "epoch root mismatch at height {}: computed {:?}, but block claims {:?}",
+                    height,
+                    computed_epoch_root,
+                    epoch_root
+                );
+            }
+        }
+        ended_epoch_root = Some(computed_epoch_root);
     }
 
     // Print the TCT root for debugging
@@ -215,6 +295,7 @@ pub async fn scan_block(
         spent_nullifiers: filtered_nullifiers,
         height,
         fmd_parameters,
+        epoch_root: ended_epoch_root,
     };
 
     Ok(result)
@@ -23,15 +23,17 @@ use tonic::transport::Channel;
 use penumbra_proto::client::v1alpha1::specific_query_service_client::SpecificQueryServiceClient;
 
 use crate::{
-    sync::{scan_block, FilteredBlock},
-    Storage,
+    sync::{scan_block, FilteredBlock, ScanOptions},
+    Prune, SpendableNoteRecord, Storage, WalletEvent,
 };
 
 pub struct Worker {
     storage: Storage,
     client: ObliviousQueryServiceClient<Channel>,
     nct: Arc<RwLock<penumbra_tct::Tree>>,
+    nct_metrics: penumbra_tct::metrics::Exporter,
     fvk: FullViewingKey, // TODO: notifications (see TODOs on ViewService)
+    scan_options: ScanOptions,
     error_slot: Arc<Mutex<Option<anyhow::Error>>>,
     sync_height_tx: watch::Sender<u64>,
     tm_client: TendermintProxyServiceClient<Channel>,
@@ -50,6 +52,7 @@ impl Worker {
         storage: Storage,
         node: String,
         pd_port: u16,
+        scan_options: ScanOptions,
     ) -> Result<
         (
             Self,
@@ -85,7 +88,9 @@ impl Worker {
                 storage,
                 client,
                 nct: nct.clone(),
+                nct_metrics: penumbra_tct::metrics::Exporter::new(),
                 fvk,
+                scan_options,
                 error_slot: error_slot.clone(),
                 sync_height_tx,
                 tm_client,
@@ -201,6 +206,10 @@ impl Worker {
 
         let epoch_duration = self.storage.chain_params().await?.epoch_duration;
 
+        // Every note spent below this height has been forgotten according to the retention
+        // policy. Starting from zero re-forgets notes forgotten by a previous run, which is a no-op.
+        let mut swept = 0;
+
         let mut stream = self
             .client
             .compact_block_range(tonic::Request::new(CompactBlockRangeRequest {
@@ -241,14 +250,38 @@ impl Worker {
                 nct_guard.end_block().unwrap();
                 // We also need to end the epoch, since if there are no funding streams, then an
                 // epoch boundary won't necessarily require scanning:
-                if Epoch::from_height(height, epoch_duration).is_epoch_end(height) {
-                    nct_guard
+                let epoch = Epoch::from_height(height, epoch_duration);
+                let mut ended_epoch_root = None;
+                if epoch.is_epoch_end(height) {
+                    let epoch_root = nct_guard
                         .end_epoch()
                         .expect("ending the epoch must succeed");
+                    // Check the epoch, including any blocks inserted by their roots alone,
+                    // against the published epoch root:
+                    if let Some(published_epoch_root) = block.epoch_root {
+                        if epoch_root != published_epoch_root {
+                            anyhow::bail!(
+                                "epoch root mismatch at height {}: computed {:?}, but block claims {:?}",
+                                height,
+                                epoch_root,
+                                published_epoch_root
+                            );
+                        }
+                    }
+                    ended_epoch_root = Some(epoch_root);
                 }
                 self.storage.record_empty_block(height).await?;
                 // Notify all watchers of the new height we just recorded.
                 self.sync_height_tx.send(height)?;
+                if let Some(root) = ended_epoch_root {
+                    self.storage.publish_event(WalletEvent::EpochRoot {
+                        epoch: epoch.index,
+                        height,
+                        root,
+                    });
+                }
+                self.storage
+                    .publish_event(WalletEvent::SyncProgress { height });
             } else {
                 // Otherwise, scan the block and commit its changes:
                 let filtered_block = scan_block(
@@ -257,21 +290,33 @@ impl Worker {
                     block,
                     epoch_duration,
                     &self.storage,
+                    self.scan_options,
                 )
                 .await?;
 
                 // Download any transactions we detected.
                 let transactions = self.fetch_transactions(&filtered_block).await?;
 
-                self.storage
-                    .record_block(filtered_block.clone(), transactions, &mut nct_guard)
+                let prune = self
+                    .scan_options
+                    .retention
+                    .prune(height, epoch_duration, swept);
+                let new_notes = self
+                    .storage
+                    .record_block(filtered_block.clone(), transactions, &mut nct_guard, &prune)
                     .await?;
+                if let Prune::SpentBetween(heights) = prune {
+                    swept = heights.end;
+                }
                 // Notify all watchers of the new height we just recorded.
                 self.sync_height_tx.send(filtered_block.height)?;
+                self.publish_block_events(filtered_block, new_notes, epoch_duration);
             }
             #[cfg(feature = "nct-divergence-check")]
             nct_divergence_check(&mut self.specific_client, height, nct_guard.root()).await?;
 
+            self.nct_metrics.record(&nct_guard);
+
             // Release the NCT RwLock
             drop(nct_guard);
 
@@ -285,6 +330,34 @@ impl Worker {
         Ok(())
     }
 
+    /// Publish the [`WalletEvent`]s for a scanned block, once it has been recorded, with its
+    /// `new_notes` as they were stored.
+    fn publish_block_events(
+        &self,
+        filtered_block: FilteredBlock,
+        new_notes: Vec<SpendableNoteRecord>,
+        epoch_duration: u64,
+    ) {
+        let height = filtered_block.height;
+        for note_record in new_notes {
+            self.storage
+                .publish_event(WalletEvent::NoteReceived(note_record));
+        }
+        for nullifier in filtered_block.spent_nullifiers {
+            self.storage
+                .publish_event(WalletEvent::NoteSpent(nullifier));
+        }
+        if let Some(root) = filtered_block.epoch_root {
+            self.storage.publish_event(WalletEvent::EpochRoot {
+                epoch: Epoch::from_height(height, epoch_duration).index,
+                height,
+                root,
+            });
+        }
+        self.storage
+            .publish_event(WalletEvent::SyncProgress { height });
+    }
+
     pub async fn run(mut self) -> Result<(), anyhow::Error> {
         self.run_inner().await.map_err(|e| {
             tracing::info!(?e, "view worker error");
@@ -0,0 +1,322 @@
+use anyhow::{anyhow, Context};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use penumbra_crypto::keys::{SeedPhrase, SpendKey};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::{KeyStore, LabelStore};
+
+/// The magic bytes at the start of every wallet backup.
+const MAGIC: &[u8; 8] = b"PENUMBAK";
+/// The current version of the wallet backup format.
+const VERSION: u8 = 1;
+const SALT_LEN_BYTES: usize = 16;
+const NONCE_LEN_BYTES: usize = 12;
+/// The length of the header: magic, version, KDF parameters, salt and nonce.
+const HEADER_LEN_BYTES: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN_BYTES + NONCE_LEN_BYTES;
+/// The largest memory cost accepted for a backup, in KiB.
+///
+/// The parameters are read from the backup's header before it can be
+/// authenticated, so they must be bounded, or a crafted backup could make
+/// importing it allocate any amount of memory.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+/// The largest number of passes accepted for a backup.
+const MAX_ITERATIONS: u32 = 64;
+/// The largest degree of parallelism accepted for a backup.
+const MAX_PARALLELISM: u32 = 16;
+
+/// The cost parameters of the Argon2id key derivation used to encrypt a backup.
+///
+/// These are stored in the backup's header, so that they can be raised for
+/// new backups without breaking old ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// The memory cost, in KiB.
+    pub memory_kib: u32,
+    /// The number of passes over the memory.
+    pub iterations: u32,
+    /// The degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Derive the backup encryption key from a password.
+    ///
+    /// Parameters above the maximum costs are rejected before deriving anything.
+    fn derive_key(&self, password: &str, salt: &[u8]) -> anyhow::Result<Key> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            return Err(anyhow!(
+                "key derivation parameters {:?} exceed the maximum costs",
+                self
+            ));
+        }
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| anyhow!("invalid key derivation parameters: {}", e))?;
+        let mut key = Key::default();
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), salt, key.as_mut_slice())
+            .map_err(|e| anyhow!("key derivation failed: {}", e))?;
+        Ok(key)
+    }
+}
+
+/// An encrypted backup of a wallet's spend authority and address labels.
+///
+/// A backup is exported as a versioned header (holding the key derivation
+/// parameters, salt and nonce) followed by the contents, encrypted with
+/// `ChaCha20Poly1305` under a key derived from a password with Argon2id. The
+/// header is authenticated along with the contents, so it can't be tampered
+/// with either.
+#[derive(Clone)]
+pub struct Backup {
+    /// The seed phrase the spend key was derived from, if it is known.
+    pub seed_phrase: Option<SeedPhrase>,
+    pub spend_key: SpendKey,
+    pub labels: LabelStore,
+}
+
+/// The encrypted contents of a [`Backup`].
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    seed_phrase: Option<String>,
+    spend_key: SpendKey,
+    labels: LabelStore,
+}
+
+impl Backup {
+    /// Back up a wallet created from the given seed phrase.
+    pub fn from_seed_phrase(seed_phrase: SeedPhrase, labels: LabelStore) -> Self {
+        let spend_key = KeyStore::from_seed_phrase(seed_phrase.clone()).spend_key;
+        Self {
+            seed_phrase: Some(seed_phrase),
+            spend_key,
+            labels,
+        }
+    }
+
+    /// Back up the wallet in the given key store.
+    pub fn from_key_store(key_store: &KeyStore, labels: LabelStore) -> Self {
+        Self {
+            seed_phrase: None,
+            spend_key: key_store.spend_key.clone(),
+            labels,
+        }
+    }
+
+    /// The key store for the backed-up wallet.
+    pub fn key_store(&self) -> KeyStore {
+        KeyStore {
+            spend_key: self.spend_key.clone(),
+        }
+    }
+
+    /// Export the backup, encrypted with the given password.
+    pub fn export(&self, password: &str) -> anyhow::Result<Vec<u8>> {
+        self.export_with_params(OsRng, password, KdfParams::default())
+    }
+
+    /// Export the backup, encrypted with the given password using a key derived
+    /// with the given parameters.
+    pub fn export_with_params<R: RngCore + CryptoRng>(
+        &self,
+        mut rng: R,
+        password: &str,
+        params: KdfParams,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut salt = [0u8; SALT_LEN_BYTES];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN_BYTES];
+        rng.fill_bytes(&mut nonce);
+
+        let mut backup = Vec::with_capacity(HEADER_LEN_BYTES);
+        backup.extend_from_slice(MAGIC);
+        backup.push(VERSION);
+        backup.extend_from_slice(&params.memory_kib.to_le_bytes());
+        backup.extend_from_slice(&params.iterations.to_le_bytes());
+        backup.extend_from_slice(&params.parallelism.to_le_bytes());
+        backup.extend_from_slice(&salt);
+        backup.extend_from_slice(&nonce);
+
+        let contents = Zeroizing::new(serde_json::to_vec(&Contents {
+            seed_phrase: self.seed_phrase.as_ref().map(ToString::to_string),
+            spend_key: self.spend_key.clone(),
+            labels: self.labels.clone(),
+        })?);
+        let key = params.derive_key(password, &salt)?;
+        let ciphertext = ChaCha20Poly1305::new(&key)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: contents.as_slice(),
+                    aad: &backup,
+                },
+            )
+            .map_err(|_| anyhow!("backup encryption failed"))?;
+
+        backup.extend_from_slice(&ciphertext);
+        Ok(backup)
+    }
+
+    /// Import a backup, decrypting it with the given password.
+    pub fn import(backup: &[u8], password: &str) -> anyhow::Result<Self> {
+        if backup.len() < HEADER_LEN_BYTES {
+            return Err(anyhow!("backup is truncated"));
+        }
+        let (header, ciphertext) = backup.split_at(HEADER_LEN_BYTES);
+        let (magic, rest) = header.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(anyhow!("not a wallet backup"));
+        }
+        let (version, rest) = rest.split_at(1);
+        if version[0] != VERSION {
+            return Err(anyhow!("unsupported backup version {}", version[0]));
+        }
+        let (params, rest) = rest.split_at(3 * 4);
+        let (salt, nonce) = rest.split_at(SALT_LEN_BYTES);
+
+        let param = |i: usize| u32::from_le_bytes(params[4 * i..4 * (i + 1)].try_into().unwrap());
+        let params = KdfParams {
+            memory_kib: param(0),
+            iterations: param(1),
+            parallelism: param(2),
+        };
+
+        let key = params.derive_key(password, salt)?;
+        let contents = Zeroizing::new(
+            ChaCha20Poly1305::new(&key)
+                .decrypt(
+                    Nonce::from_slice(nonce),
+                    Payload {
+                        msg: ciphertext,
+                        aad: header,
+                    },
+                )
+                .map_err(|_| anyhow!("incorrect password or corrupted backup"))?,
+        );
+        let contents: Contents =
+            serde_json::from_slice(&contents).context("malformed backup contents")?;
+
+        let seed_phrase = contents
+            .seed_phrase
+            .map(Zeroizing::new)
+            .map(|phrase| phrase.parse::<SeedPhrase>())
+            .transpose()
+            .context("malformed seed phrase in backup")?;
+        if let Some(seed_phrase) = &seed_phrase {
+            if KeyStore::from_seed_phrase(seed_phrase.clone()).spend_key != contents.spend_key {
+                return Err(anyhow!(
+                    "the spend key in the backup is not derived from its seed phrase"
+                ));
+            }
+        }
+
+        Ok(Self {
+            seed_phrase,
+            spend_key: contents.spend_key,
+            labels: contents.labels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::keys::AddressIndex;
+
+    use super::*;
+
+    /// Cheap parameters, so that tests run quickly.
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn backup_roundtrip() {
+        let seed_phrase = SeedPhrase::generate(OsRng);
+        let backup = Backup::from_seed_phrase(seed_phrase.clone(), LabelStore::default());
+        let fvk = backup.spend_key.full_viewing_key().clone();
+
+        let mut labels = LabelStore::default();
+        labels.label_index(&fvk, AddressIndex::from(1u64), "savings");
+        let backup = Backup { labels, ..backup };
+
+        let exported = backup
+            .export_with_params(OsRng, "hunter2", TEST_PARAMS)
+            .unwrap();
+        let imported = Backup::import(&exported, "hunter2").unwrap();
+
+        assert_eq!(
+            imported.seed_phrase.map(|phrase| phrase.to_string()),
+            Some(seed_phrase.to_string())
+        );
+        assert_eq!(
+            imported.spend_key.full_viewing_key().to_string(),
+            fvk.to_string()
+        );
+        assert_eq!(
+            imported
+                .labels
+                .index_label(&fvk, AddressIndex::from(1u64))
+                .unwrap()
+                .as_deref(),
+            Some("savings")
+        );
+
+        // The wrong password, or any tampering with the header, is rejected.
+        assert!(Backup::import(&exported, "hunter3").is_err());
+        let mut tampered = exported.clone();
+        tampered[MAGIC.len() + 1] ^= 1;
+        assert!(Backup::import(&tampered, "hunter2").is_err());
+    }
+
+    #[test]
+    fn import_rejects_excessive_kdf_params() {
+        let backup = Backup::from_seed_phrase(SeedPhrase::generate(OsRng), LabelStore::default());
+        let exported = backup
+            .export_with_params(OsRng, "hunter2", TEST_PARAMS)
+            .unwrap();
+
+        // Each parameter is checked against its maximum before any key is
+        // derived, so this fails quickly rather than allocating 4 TiB.
+        for i in 0..3 {
+            let mut crafted = exported.clone();
+            let offset = MAGIC.len() + 1 + 4 * i;
+            crafted[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert!(Backup::import(&crafted, "hunter2").is_err());
+        }
+    }
+
+    #[test]
+    fn import_rejects_mismatched_spend_key() {
+        let backup = Backup::from_seed_phrase(SeedPhrase::generate(OsRng), LabelStore::default());
+        let other = KeyStore::from_seed_phrase(SeedPhrase::generate(OsRng));
+        let mismatched = Backup {
+            spend_key: other.spend_key,
+            ..backup
+        };
+
+        let exported = mismatched
+            .export_with_params(OsRng, "hunter2", TEST_PARAMS)
+            .unwrap();
+        assert!(Backup::import(&exported, "hunter2").is_err());
+    }
+}
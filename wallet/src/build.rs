@@ -1,5 +1,5 @@
 use anyhow::Result;
-use penumbra_crypto::FullViewingKey;
+use penumbra_crypto::{proofs::ProofRng, FullViewingKey};
 use penumbra_custody::{AuthorizeRequest, CustodyClient};
 use penumbra_transaction::{plan::TransactionPlan, Transaction};
 use penumbra_view::ViewClient;
@@ -17,6 +17,9 @@ where
     C: CustodyClient,
     R: RngCore + CryptoRng,
 {
+    // Make sure no balance commitment blinding factor is zero or reused, before anything is signed
+    plan.check_blindings()?;
+
     // Get the authorization data from the custody service...
     let auth_data = custody
         .authorize(AuthorizeRequest {
@@ -33,5 +36,10 @@ where
     let witness_data = view.witness(fvk.hash(), &plan).await?;
 
     // ... and then build the transaction:
-    plan.build(&mut rng, fvk, auth_data, witness_data)
+    plan.build(
+        &mut ProofRng::from_rng(&mut rng),
+        fvk,
+        auth_data,
+        witness_data,
+    )
 }
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use penumbra_crypto::{keys::AddressIndex, symmetric::LabelKey, Address, FullViewingKey};
+use serde::{Deserialize, Serialize};
+
+/// What a label in the [`LabelStore`] refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Labeled {
+    /// One of the wallet's own addresses, by its index.
+    Index(AddressIndex),
+    /// An external address, such as a contact.
+    External(Address),
+}
+
+/// A local store of labels for address indices and external addresses.
+///
+/// Both the labels and the external addresses are encrypted with the
+/// [`LabelKey`] derived from the wallet's full viewing key, so that a backup
+/// of the label store doesn't leak the user's contacts. Since the encryption
+/// is deterministic, labels can be looked up without decrypting every entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelStore {
+    /// Encrypted labels, keyed by the hex-encoded address index they label.
+    #[serde(default)]
+    indices: BTreeMap<String, String>,
+    /// Encrypted labels, keyed by the hex-encoded encrypted address they label.
+    #[serde(default)]
+    external: BTreeMap<String, String>,
+}
+
+impl LabelStore {
+    /// Write the label store to the provided path, overwriting any existing one.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Read the label store from the provided path.
+    pub fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        serde_json::from_slice(std::fs::read(path)?.as_slice()).map_err(Into::into)
+    }
+
+    /// Attach a label to one of the wallet's own address indices.
+    pub fn label_index(&mut self, fvk: &FullViewingKey, index: AddressIndex, label: &str) {
+        let key = LabelKey::derive(fvk);
+        self.indices.insert(
+            hex::encode(index.to_bytes()),
+            hex::encode(key.encrypt(label.as_bytes())),
+        );
+    }
+
+    /// Attach a label to an address.
+    ///
+    /// If the address belongs to the wallet, the label is attached to its index.
+    pub fn label_address(&mut self, fvk: &FullViewingKey, address: &Address, label: &str) {
+        if let Some(index) = fvk.address_index(address) {
+            return self.label_index(fvk, index, label);
+        }
+        let key = LabelKey::derive(fvk);
+        self.external.insert(
+            hex::encode(key.encrypt(&address.to_vec())),
+            hex::encode(key.encrypt(label.as_bytes())),
+        );
+    }
+
+    /// Get the label attached to the given address index, if any.
+    pub fn index_label(
+        &self,
+        fvk: &FullViewingKey,
+        index: AddressIndex,
+    ) -> anyhow::Result<Option<String>> {
+        self.indices
+            .get(&hex::encode(index.to_bytes()))
+            .map(|ciphertext| decrypt_label(&LabelKey::derive(fvk), ciphertext))
+            .transpose()
+    }
+
+    /// Get the label attached to the given address, if any.
+    pub fn address_label(
+        &self,
+        fvk: &FullViewingKey,
+        address: &Address,
+    ) -> anyhow::Result<Option<String>> {
+        if let Some(index) = fvk.address_index(address) {
+            return self.index_label(fvk, index);
+        }
+        let key = LabelKey::derive(fvk);
+        self.external
+            .get(&hex::encode(key.encrypt(&address.to_vec())))
+            .map(|ciphertext| decrypt_label(&key, ciphertext))
+            .transpose()
+    }
+
+    /// Find everything carrying the given label.
+    pub fn lookup(&self, fvk: &FullViewingKey, label: &str) -> anyhow::Result<Vec<Labeled>> {
+        let key = LabelKey::derive(fvk);
+        let encrypted_label = hex::encode(key.encrypt(label.as_bytes()));
+
+        let mut found = Vec::new();
+        for (index, _) in self
+            .indices
+            .iter()
+            .filter(|(_, ciphertext)| **ciphertext == encrypted_label)
+        {
+            found.push(Labeled::Index(hex::decode(index)?.as_slice().try_into()?));
+        }
+        for (address, _) in self
+            .external
+            .iter()
+            .filter(|(_, ciphertext)| **ciphertext == encrypted_label)
+        {
+            let address_bytes = key.decrypt(&hex::decode(address)?)?;
+            found.push(Labeled::External(address_bytes.try_into()?));
+        }
+        Ok(found)
+    }
+
+    /// Resolve a label to a single address, for use when planning a transaction.
+    ///
+    /// Returns an error if the label is unknown or ambiguous.
+    pub fn resolve(&self, fvk: &FullViewingKey, label: &str) -> anyhow::Result<Address> {
+        match self.lookup(fvk, label)?.as_slice() {
+            [Labeled::Index(index)] => Ok(fvk.payment_address(*index).0),
+            [Labeled::External(address)] => Ok(*address),
+            [] => Err(anyhow::anyhow!("no address labeled {:?}", label)),
+            _ => Err(anyhow::anyhow!("label {:?} is ambiguous", label)),
+        }
+    }
+}
+
+fn decrypt_label(key: &LabelKey, ciphertext: &str) -> anyhow::Result<String> {
+    let plaintext = key.decrypt(&hex::decode(ciphertext)?)?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::keys::{SeedPhrase, SpendKey};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn label_round_trip() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0);
+        let fvk = sk.full_viewing_key();
+        let (contact, _dtk) = SpendKey::from_seed_phrase(SeedPhrase::generate(OsRng), 0)
+            .full_viewing_key()
+            .payment_address(0u64.into());
+
+        let mut store = LabelStore::default();
+        store.label_index(fvk, 1u64.into(), "savings");
+        store.label_address(fvk, &contact, "alice");
+
+        assert_eq!(
+            store.index_label(fvk, 1u64.into()).unwrap(),
+            Some("savings".to_string())
+        );
+        assert_eq!(
+            store.address_label(fvk, &contact).unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(store.resolve(fvk, "alice").unwrap(), contact);
+        assert_eq!(
+            store.resolve(fvk, "savings").unwrap(),
+            fvk.payment_address(1u64.into()).0
+        );
+        assert!(store.resolve(fvk, "bob").is_err());
+
+        // Neither the label nor the contact's address appear in the serialized store.
+        let serialized = serde_json::to_string(&store).unwrap();
+        assert!(!serialized.contains("alice"));
+        assert!(!serialized.contains(&hex::encode(contact.to_vec())));
+    }
+}
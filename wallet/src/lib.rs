@@ -1,6 +1,11 @@
+mod backup;
 mod build;
 mod key_store;
+mod label_store;
+pub use backup::{Backup, KdfParams};
 pub use build::build_transaction;
 pub use key_store::KeyStore;
+pub use label_store::{LabelStore, Labeled};
 
+pub mod migration;
 pub mod plan;
@@ -0,0 +1,212 @@
+//! Moving funds from one key to another, e.g. when rotating a seed phrase.
+//!
+//! Every transaction that spends several notes reveals (to anyone who later learns any of them)
+//! that those notes had a common owner, and a burst of transactions of the same unusual shape is
+//! easy to pick out of the chain. A migration [`Plan`] therefore moves the notes of the old key in
+//! batches of a randomly chosen size, each padded with dummy spends to the same number of spends,
+//! sent to a fresh address of the new key, and submitted in a random order after a random delay.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use penumbra_crypto::{
+    asset, keys::AddressIndex, transaction::Fee, Amount, FullViewingKey, Note, Value,
+};
+use penumbra_proto::view::v1alpha1::NotesRequest;
+use penumbra_tct as tct;
+use penumbra_transaction::plan::TransactionPlan;
+use penumbra_view::ViewClient;
+use rand::{seq::SliceRandom, CryptoRng, Rng, RngCore};
+use tracing::instrument;
+
+use crate::plan::{spend_all_chunks, Planner};
+
+/// Options for the shape and schedule of a migration [`Plan`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// The number of spends in every migration transaction, including dummy spends.
+    pub spends_per_transaction: usize,
+    /// The longest delay, in blocks, between submitting one migration transaction and the next.
+    pub max_delay_blocks: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            spends_per_transaction: 4,
+            max_delay_blocks: 720,
+        }
+    }
+}
+
+/// A single transaction of a migration [`Plan`].
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// How many blocks to wait after the previous step (or the start of the migration) before
+    /// submitting this transaction.
+    pub delay_blocks: u64,
+    /// The transaction to submit.
+    pub transaction: TransactionPlan,
+}
+
+/// A schedule of transactions moving every unspent note of one key to addresses of another.
+///
+/// The steps spend distinct notes, so they can be submitted independently, but submitting them at
+/// the scheduled times, rather than all at once, makes them harder to link to each other.
+#[derive(Clone, Debug, Default)]
+pub struct Plan {
+    /// The transactions of the migration, in the order they should be submitted.
+    pub steps: Vec<Step>,
+}
+
+impl Plan {
+    /// Plan the migration of every unspent note of `old_fvk` to fresh addresses of `new_fvk`,
+    /// paying `fee` for each transaction out of the notes it spends.
+    ///
+    /// Errors if [`Options::spends_per_transaction`] is less than 2, leaving no room for a fee note
+    /// and another note, or if there isn't enough of the fee's asset to pay every transaction's
+    /// fee.
+    #[instrument(skip(view, old_fvk, new_fvk, rng))]
+    pub async fn generate<V, R>(
+        view: &mut V,
+        old_fvk: &FullViewingKey,
+        new_fvk: &FullViewingKey,
+        mut rng: R,
+        fee: Fee,
+        options: Options,
+    ) -> Result<Plan>
+    where
+        V: ViewClient,
+        R: RngCore + CryptoRng,
+    {
+        let chain_params = view.chain_params().await?;
+        let fmd_params = view.fmd_parameters().await?;
+        let notes = view
+            .notes(NotesRequest {
+                account_id: Some(old_fvk.hash().into()),
+                include_spent: false,
+                ..Default::default()
+            })
+            .await?;
+
+        let batches = batches(
+            &mut rng,
+            notes
+                .into_iter()
+                .map(|record| (record.note, record.position))
+                .collect(),
+            &fee,
+            options.spends_per_transaction,
+        )
+        .context("can't split notes into migration transactions")?;
+
+        let mut steps = Vec::with_capacity(batches.len());
+        for batch in batches {
+            let padding = options.spends_per_transaction - batch.len();
+            let to = new_fvk
+                .incoming()
+                .payment_address(AddressIndex::Random(rng.gen()))
+                .0;
+
+            let mut planner = Planner::new(&mut rng);
+            planner.fee(fee.clone());
+
+            let mut totals = BTreeMap::<asset::Id, Amount>::new();
+            for (note, position) in batch {
+                let total = totals.entry(note.asset_id()).or_insert_with(Amount::zero);
+                *total = *total + note.amount();
+                planner.spend(note, position);
+            }
+            for (asset_id, mut amount) in totals {
+                if asset_id == fee.asset_id() {
+                    amount = amount - fee.amount();
+                }
+                if amount > Amount::zero() {
+                    planner.output(Value { amount, asset_id }, to);
+                }
+            }
+            planner.dummy_spends(padding);
+
+            let transaction = planner
+                .plan_with_spendable_notes(&chain_params, &fmd_params, old_fvk, None, Vec::new())
+                .context("can't build migration transaction")?;
+            let delay_blocks = rng.gen_range(0..=options.max_delay_blocks);
+
+            steps.push(Step {
+                delay_blocks,
+                transaction,
+            });
+        }
+
+        Ok(Plan { steps })
+    }
+
+    /// The number of blocks from the start of the migration until its last step is submitted.
+    pub fn total_delay_blocks(&self) -> u64 {
+        self.steps.iter().map(|step| step.delay_blocks).sum()
+    }
+}
+
+/// Split `notes` into batches of at most `max_spends` notes each, so that each batch has enough of
+/// the fee's asset to pay `fee`, in a random order.
+///
+/// The batches hold at most a randomly chosen number of notes, so that the number of dummy spends
+/// padding them, and so the number of migration transactions, varies from one migration to the
+/// next. If the notes of the fee's asset can't pay for batches that small, they are as big as
+/// `max_spends` allows.
+fn batches<R: Rng>(
+    rng: &mut R,
+    notes: Vec<(Note, tct::Position)>,
+    fee: &Fee,
+    max_spends: usize,
+) -> Result<Vec<Vec<(Note, tct::Position)>>> {
+    let size = rng.gen_range(2..=max_spends.max(2));
+    let mut batches = match spend_all_chunks(notes.clone(), fee, size) {
+        Ok(batches) if size <= max_spends => batches,
+        _ => spend_all_chunks(notes, fee, max_spends)?,
+    };
+    batches.shuffle(rng);
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{Address, STAKING_TOKEN_ASSET_ID};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::plan::test_note;
+
+    #[test]
+    fn batches_spend_every_note_and_pay_every_fee() {
+        let mut rng = ChaCha20Rng::from_seed([5u8; 32]);
+        let address = Address::dummy(&mut rng);
+        let gm = asset::REGISTRY.parse_denom("ugm").unwrap().id();
+        let mut note = |amount, asset_id| test_note(&mut rng, &address, amount, asset_id);
+
+        let fee = Fee::from_staking_token_amount(10u64.into());
+        let mut notes = (0..6).map(|_| note(1, gm)).collect::<Vec<_>>();
+        notes.extend((0..6).map(|_| note(10, *STAKING_TOKEN_ASSET_ID)));
+
+        let mut rng = ChaCha20Rng::from_seed([6u8; 32]);
+        let planned = batches(&mut rng, notes.clone(), &fee, 3).unwrap();
+        assert_eq!(planned.iter().map(Vec::len).sum::<usize>(), notes.len());
+        for batch in &planned {
+            assert!(!batch.is_empty() && batch.len() <= 3);
+            let funding = batch
+                .iter()
+                .filter(|(note, _)| note.asset_id() == fee.asset_id())
+                .map(|(note, _)| u128::from(note.amount()))
+                .sum::<u128>();
+            assert!(funding >= 10);
+        }
+
+        // Six other notes can't be moved with only one note to pay a fee.
+        notes.truncate(7);
+        assert!(batches(&mut rng, notes.clone(), &fee, 3).is_err());
+
+        // A single spend per transaction leaves no room for the fee note and another note.
+        assert!(batches(&mut rng, notes, &fee, 1).is_err());
+    }
+}
@@ -20,7 +20,10 @@ use rand_core::{CryptoRng, RngCore};
 use tracing::instrument;
 
 mod planner;
-pub use planner::Planner;
+pub(crate) use planner::spend_all_chunks;
+#[cfg(test)]
+pub(crate) use planner::test_note;
+pub use planner::{ChangeSplitting, Planner, SPEND_ALL_MAX_SPENDS};
 
 pub async fn validator_definition<V, R>(
     fvk: &FullViewingKey,
@@ -79,7 +82,7 @@ where
 {
     Planner::new(rng)
         .fee(fee)
-        .delegate(unbonded_amount, rate_data)
+        .delegate(unbonded_amount, rate_data)?
         .plan(view, fvk, source_address.map(Into::into))
         .await
         .context("can't build delegate plan")
@@ -114,6 +117,29 @@ where
         .context("can't build send transaction")
 }
 
+/// Plan transactions moving every note of the account (or of the `source_address`, if given) to
+/// `dest_address`, each paying `fee`.
+#[instrument(skip(fvk, view, rng, fee, dest_address, source_address))]
+pub async fn spend_all<V, R>(
+    fvk: &FullViewingKey,
+    view: &mut V,
+    rng: R,
+    fee: Fee,
+    dest_address: Address,
+    source_address: Option<u64>,
+) -> Result<Vec<TransactionPlan>, anyhow::Error>
+where
+    V: ViewClient,
+    R: RngCore + CryptoRng,
+{
+    let mut planner = Planner::new(rng);
+    planner.fee(fee);
+    planner
+        .spend_all(view, fvk, source_address.map(Into::into), dest_address)
+        .await
+        .context("can't build transactions spending all notes")
+}
+
 #[instrument(skip(fvk, view, rng))]
 pub async fn sweep<V, R>(
     fvk: &FullViewingKey,
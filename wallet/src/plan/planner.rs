@@ -1,4 +1,5 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     mem,
 };
@@ -11,12 +12,11 @@ use penumbra_component::{
     stake::{rate::RateData, validator},
 };
 use penumbra_crypto::{
-    asset::Amount,
-    asset::Denom,
+    asset::{self, Amount, Denom},
     dex::{swap::SwapPlaintext, TradingPair},
     keys::AddressIndex,
     transaction::Fee,
-    Address, FullViewingKey, Note, Value,
+    Address, FullViewingKey, Note, Value, STAKING_TOKEN_ASSET_ID,
 };
 use penumbra_proto::view::v1alpha1::NotesRequest;
 use penumbra_tct as tct;
@@ -28,17 +28,54 @@ use penumbra_transaction::{
     },
 };
 use penumbra_view::{SpendableNoteRecord, ViewClient};
-use rand::{CryptoRng, RngCore};
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use tracing::instrument;
 
 use penumbra_crypto::Balance;
 
+/// Options for splitting a transaction's change across several outputs.
+///
+/// By default, the planner makes a single change output for each asset, whose amount reveals
+/// (to anyone who later learns the note) exactly how much was left over. With change splitting,
+/// each change value is instead divided into several outputs of random amounts, each to a
+/// distinct fresh address, so that change amounts are harder to correlate with other activity.
+///
+/// Each extra output costs `fee_per_output` of the staking token, which is paid out of the
+/// staking token change, and at most `fee_budget` is spent on extra outputs in total.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangeSplitting {
+    /// The maximum number of outputs a single change value is split into.
+    pub max_outputs: usize,
+    /// The additional fee paid for each extra change output.
+    pub fee_per_output: Amount,
+    /// The maximum additional fee paid for extra change outputs.
+    pub fee_budget: Amount,
+}
+
+impl Default for ChangeSplitting {
+    fn default() -> Self {
+        Self {
+            max_outputs: 4,
+            fee_per_output: Amount::zero(),
+            fee_budget: Amount::zero(),
+        }
+    }
+}
+
+/// The most spends [`Planner::spend_all`] puts in a single transaction.
+pub const SPEND_ALL_MAX_SPENDS: usize = 32;
+
 /// A planner for a [`TransactionPlan`] that can fill in the required spends and change outputs upon
 /// finalization to make a transaction balance.
 pub struct Planner<R: RngCore + CryptoRng> {
     rng: R,
     balance: Balance,
     plan: TransactionPlan,
+    change_splitting: Option<(ChangeSplitting, [u8; 32])>,
+    random_blindings: bool,
+    // Unlike the other fields, this is not cleared, since it describes assets rather than the plan
+    asset_cache: asset::Cache,
     // IMPORTANT: if you add more fields here, make sure to clear them when the planner is finished
 }
 
@@ -47,6 +84,11 @@ impl<R: RngCore + CryptoRng> Debug for Planner<R> {
         f.debug_struct("Builder")
             .field("balance", &self.balance)
             .field("plan", &self.plan)
+            .field(
+                "change_splitting",
+                &self.change_splitting.map(|(options, _seed)| options),
+            )
+            .field("random_blindings", &self.random_blindings)
             .finish()
     }
 }
@@ -58,9 +100,19 @@ impl<R: RngCore + CryptoRng> Planner<R> {
             rng,
             balance: Balance::default(),
             plan: TransactionPlan::default(),
+            change_splitting: None,
+            random_blindings: false,
+            asset_cache: asset::Cache::with_known_assets(),
         }
     }
 
+    /// Add the provided asset denominations to those used to format values in errors and logs.
+    #[instrument(skip(self, cache))]
+    pub fn asset_cache(&mut self, cache: asset::Cache) -> &mut Self {
+        self.asset_cache.extend(cache.values().cloned());
+        self
+    }
+
     /// Get the current transaction balance of the planner.
     pub fn balance(&self) -> &Balance {
         &self.balance
@@ -111,6 +163,31 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         self
     }
 
+    /// Split the change of this transaction plan across several outputs, according to the given
+    /// [`ChangeSplitting`] options.
+    ///
+    /// The split amounts and change addresses are derived deterministically from a plan seed,
+    /// which is drawn from the planner's random number generator when this is called.
+    #[instrument(skip(self))]
+    pub fn split_change(&mut self, options: ChangeSplitting) -> &mut Self {
+        let seed = self.rng.gen();
+        self.change_splitting = Some((options, seed));
+        self
+    }
+
+    /// Keep the independently sampled balance commitment blinding factor of each action, rather
+    /// than deriving them all from a plan seed.
+    ///
+    /// By default, once the plan is balanced, the blinding factors of its spends, outputs and
+    /// swaps are replaced by ones derived from a plan seed drawn from the planner's random number
+    /// generator (see [`TransactionPlan::derive_blindings`]), which guarantees they are distinct.
+    /// Either way, the finished plan is checked with [`TransactionPlan::check_blindings`].
+    #[instrument(skip(self))]
+    pub fn random_blindings(&mut self) -> &mut Self {
+        self.random_blindings = true;
+        self
+    }
+
     /// Spend a specific positioned note in the transaction.
     ///
     /// If you don't use this method to specify spends, they will be filled in automatically from
@@ -122,6 +199,17 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         self
     }
 
+    /// Add `count` dummy spends to the transaction, which spend nothing but are indistinguishable
+    /// on chain from real spends, e.g. to pad transactions to a common number of spends.
+    #[instrument(skip(self))]
+    pub fn dummy_spends(&mut self, count: usize) -> &mut Self {
+        for _ in 0..count {
+            let spend = SpendPlan::dummy(&mut self.rng).into();
+            self.action(spend);
+        }
+        self
+    }
+
     /// Perform a swap claim based on an input swap NFT with a pre-paid fee.
     #[instrument(skip(self))]
     pub fn swap_claim(&mut self, plan: SwapClaimPlan) -> &mut Self {
@@ -189,14 +277,36 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         self
     }
 
+    /// Add an output note from this transaction, with opaque metadata about the note.
+    ///
+    /// The metadata is not included in the transaction, but the view service attaches it to the
+    /// note's record when it detects the note, so it can be used to correlate planned and received
+    /// notes.
+    #[instrument(skip(self))]
+    pub fn output_with_metadata(
+        &mut self,
+        value: Value,
+        address: Address,
+        note_metadata: BTreeMap<String, Vec<u8>>,
+    ) -> &mut Self {
+        let output = OutputPlan::new(&mut self.rng, value, address)
+            .with_note_metadata(note_metadata)
+            .into();
+        self.action(output);
+        self
+    }
+
     /// Add a delegation to this transaction.
     ///
     /// If you don't specify spends or outputs as well, they will be filled in automatically.
+    ///
+    /// Errors if nothing can be delegated at the validator's exchange rate, e.g. because it was
+    /// slashed down to zero.
     #[instrument(skip(self))]
-    pub fn delegate(&mut self, unbonded_amount: u64, rate_data: RateData) -> &mut Self {
-        let delegation = rate_data.build_delegate(unbonded_amount).into();
+    pub fn delegate(&mut self, unbonded_amount: u64, rate_data: RateData) -> Result<&mut Self> {
+        let delegation = rate_data.build_delegate(unbonded_amount)?.into();
         self.action(delegation);
-        self
+        Ok(self)
     }
 
     /// Add an undelegation to this transaction.
@@ -295,6 +405,10 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         // Gather all the information needed from the view service
         let chain_params = view.chain_params().await?;
         let fmd_params = view.fmd_parameters().await?;
+        // The asset metadata is only used for formatting, so planning doesn't depend on it
+        if let Ok(assets) = view.assets().await {
+            self.asset_cache(assets);
+        }
         let mut spendable_notes = Vec::new();
         for request in self.notes_requests(fvk, source) {
             let notes = view.notes(request).await?;
@@ -305,6 +419,82 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         self.plan_with_spendable_notes(&chain_params, &fmd_params, fvk, source, spendable_notes)
     }
 
+    /// Plan transactions spending every unspent note of the account (from the `source` address
+    /// only, if given) to the address `to`, e.g. to move all of a wallet's funds to a new one.
+    ///
+    /// The notes are split across as many transactions as needed to spend at most
+    /// [`SPEND_ALL_MAX_SPENDS`] notes in each, and each transaction pays the fee set with
+    /// [`Planner::fee`] out of the notes it spends. The plans are returned in the order they should
+    /// be submitted, and can be submitted independently, since each spends distinct notes.
+    ///
+    /// Errors if anything other than the fee has been added to the planner, or if there isn't
+    /// enough of the fee's asset to pay every transaction's fee. Clears the contents of the
+    /// planner, which can be re-used.
+    pub async fn spend_all<V: ViewClient>(
+        &mut self,
+        view: &mut V,
+        fvk: &FullViewingKey,
+        source: Option<AddressIndex>,
+        to: Address,
+    ) -> anyhow::Result<Vec<TransactionPlan>> {
+        if !self.plan.actions.is_empty() {
+            anyhow::bail!("spending all notes can only be combined with a fee");
+        }
+        let fee = self.plan.fee.clone();
+
+        let chain_params = view.chain_params().await?;
+        let fmd_params = view.fmd_parameters().await?;
+        let notes = view
+            .notes(NotesRequest {
+                account_id: Some(fvk.hash().into()),
+                address_index: source.map(Into::into),
+                include_spent: false,
+                ..Default::default()
+            })
+            .await?;
+
+        let chunks = spend_all_chunks(
+            notes
+                .into_iter()
+                .map(|record| (record.note, record.position))
+                .collect(),
+            &fee,
+            SPEND_ALL_MAX_SPENDS,
+        )?;
+
+        let mut plans = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            // Re-apply the fee, which is cleared along with the rest of the planner by each plan
+            self.balance = Balance::zero();
+            self.fee(fee.clone());
+
+            let mut totals = BTreeMap::<asset::Id, Amount>::new();
+            for (note, position) in chunk {
+                let total = totals.entry(note.asset_id()).or_insert_with(Amount::zero);
+                *total = *total + note.amount();
+                self.spend(note, position);
+            }
+            for (asset_id, mut amount) in totals {
+                if asset_id == fee.asset_id() {
+                    amount = amount - fee.amount();
+                }
+                if amount > Amount::zero() {
+                    self.output(Value { amount, asset_id }, to);
+                }
+            }
+
+            plans.push(self.plan_with_spendable_notes(
+                &chain_params,
+                &fmd_params,
+                fvk,
+                source,
+                Vec::new(),
+            )?);
+        }
+
+        Ok(plans)
+    }
+
     /// Add spends and change outputs as required to balance the transaction, using the spendable
     /// notes provided. It is the caller's responsibility to ensure that the notes are the result of
     /// collected responses to the requests generated by an immediately preceding call to
@@ -320,7 +510,11 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         source: Option<AddressIndex>,
         spendable_notes: Vec<SpendableNoteRecord>,
     ) -> anyhow::Result<TransactionPlan> {
-        tracing::debug!(plan = ?self.plan, balance = ?self.balance, "finalizing transaction");
+        tracing::debug!(
+            plan = ?self.plan,
+            balance = %self.balance.format(&self.asset_cache),
+            "finalizing transaction"
+        );
 
         // Fill in the chain id based on the view service
         self.plan.chain_id = chain_params.chain_id.clone();
@@ -330,14 +524,20 @@ impl<R: RngCore + CryptoRng> Planner<R> {
             self.spend(record.note, record.position);
         }
 
-        // For any remaining provided balance, make a single change note for each
-        let self_address = fvk
-            .incoming()
-            .payment_address(source.unwrap_or(AddressIndex::Numeric(0)))
-            .0;
-
-        for value in self.balance.provided().collect::<Vec<_>>() {
-            self.output(value, self_address);
+        // For any remaining provided balance, make change notes: either a single change note for
+        // each, or several, if change splitting is enabled
+        let change = self.balance.provided().collect::<Vec<_>>();
+        if let Some((options, seed)) = self.change_splitting.take() {
+            self.split_change_outputs(fvk, change, options, seed);
+        } else {
+            let self_address = fvk
+                .incoming()
+                .payment_address(source.unwrap_or(AddressIndex::Numeric(0)))
+                .0;
+
+            for value in change {
+                self.output(value, self_address);
+            }
         }
 
         // If there are outputs, we check that a memo has been added. If not, we add a default memo.
@@ -356,17 +556,297 @@ impl<R: RngCore + CryptoRng> Planner<R> {
         // Now the transaction should be fully balanced, unless we didn't have enough to spend
         if !self.balance.is_zero() {
             anyhow::bail!(
-                "balance is non-zero after attempting to balance transaction: {:?}",
-                self.balance
+                "balance is non-zero after attempting to balance transaction: {}",
+                self.balance.format(&self.asset_cache)
             );
         }
 
+        // Now that every action has been added, shuffle the spends and outputs with a plan seed,
+        // so their positions don't reveal which is change, then derive their blinding factors
+        // from the same seed, unless asked not to, and check that none is zero or reused
+        let plan_seed = self.rng.gen();
+        self.plan.shuffle_actions(plan_seed);
+        if !self.random_blindings {
+            self.plan.derive_blindings(plan_seed);
+        }
+        self.plan.check_blindings()?;
+
         tracing::debug!(plan = ?self.plan, "finished balancing transaction");
 
         // Clear the planner and pull out the plan to return
         self.balance = Balance::zero();
+        self.change_splitting = None;
+        self.random_blindings = false;
         let plan = mem::take(&mut self.plan);
 
         Ok(plan)
     }
+
+    /// Make change outputs for each of the `change` values, split according to `options`.
+    fn split_change_outputs(
+        &mut self,
+        fvk: &FullViewingKey,
+        mut change: Vec<Value>,
+        options: ChangeSplitting,
+        seed: [u8; 32],
+    ) {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let staking_change = change
+            .iter()
+            .position(|value| value.asset_id == *STAKING_TOKEN_ASSET_ID);
+
+        // Work out how many extra outputs the fee budget (and the staking token change, which pays
+        // for them) allows, and set aside the fee for them.
+        let mut extra_outputs = usize::MAX;
+        let mut reserved_fee = Amount::zero();
+        if options.fee_per_output > Amount::zero() {
+            let available = staking_change
+                .map(|index| change[index].amount)
+                .unwrap_or_else(Amount::zero);
+            let budget = if options.fee_budget < available {
+                options.fee_budget
+            } else {
+                available
+            };
+            extra_outputs = u128::from(budget / options.fee_per_output)
+                .try_into()
+                .unwrap_or(usize::MAX);
+            reserved_fee = options.fee_per_output * Amount::from(extra_outputs as u128);
+            if let Some(index) = staking_change {
+                change[index].amount = change[index].amount - reserved_fee;
+            }
+        }
+
+        // Decide how many parts to split each value into.
+        let mut parts = Vec::with_capacity(change.len());
+        for value in &change {
+            let count = split_count(value.amount, options.max_outputs, extra_outputs);
+            extra_outputs -= count.saturating_sub(1);
+            parts.push(count);
+        }
+
+        // Only pay for the extra outputs actually made, and return the rest of the reserved fee
+        // to the staking token change.
+        let extra_fee = options.fee_per_output
+            * Amount::from(
+                parts
+                    .iter()
+                    .map(|count| count.saturating_sub(1) as u128)
+                    .sum::<u128>(),
+            );
+        if let Some(index) = staking_change {
+            change[index].amount = change[index].amount + (reserved_fee - extra_fee);
+            parts[index] = parts[index].max(1);
+        }
+        if extra_fee > Amount::zero() {
+            let fee = Fee::from_staking_token_amount(self.plan.fee.amount() + extra_fee);
+            self.balance += Fee::from_staking_token_amount(extra_fee).balance();
+            self.plan.fee = fee;
+        }
+
+        for (value, count) in change.into_iter().zip(parts) {
+            if value.amount == Amount::zero() {
+                continue;
+            }
+            for amount in split_amount(&mut rng, value.amount, count) {
+                let (address, _dtk) = fvk
+                    .incoming()
+                    .payment_address(AddressIndex::Random(rng.gen()));
+                self.output(
+                    Value {
+                        amount,
+                        asset_id: value.asset_id,
+                    },
+                    address,
+                );
+            }
+        }
+    }
+}
+
+/// The number of parts to split `amount` into, given the maximum number of parts and the number of
+/// extra (beyond the first) parts remaining in the budget.
+fn split_count(amount: Amount, max_outputs: usize, extra_outputs: usize) -> usize {
+    if amount == Amount::zero() {
+        return 0;
+    }
+    let max_by_amount = usize::try_from(u128::from(amount)).unwrap_or(usize::MAX);
+    max_outputs
+        .max(1)
+        .min(max_by_amount)
+        .min(extra_outputs.saturating_add(1))
+}
+
+/// Split `notes` into groups of at most `max_spends`, each holding enough of the fee's asset to pay
+/// `fee`.
+///
+/// The notes of the fee's asset are spread across the groups, largest first, as needed to cover
+/// each group's fee, and the remaining notes fill the groups in order of asset and amount.
+///
+/// Errors if `max_spends` leaves no room for a fee note and another note, or if there isn't enough
+/// of the fee's asset to pay every group's fee.
+pub(crate) fn spend_all_chunks(
+    notes: Vec<(Note, tct::Position)>,
+    fee: &Fee,
+    max_spends: usize,
+) -> anyhow::Result<Vec<Vec<(Note, tct::Position)>>> {
+    if max_spends < 2 {
+        anyhow::bail!(
+            "spending all notes needs room for at least 2 spends per transaction, not {}",
+            max_spends
+        );
+    }
+
+    let (mut fee_notes, mut other_notes): (Vec<_>, Vec<_>) = notes
+        .into_iter()
+        .partition(|(note, _)| note.asset_id() == fee.asset_id());
+    fee_notes.sort_by_key(|(note, _)| std::cmp::Reverse(u128::from(note.amount())));
+    other_notes.sort_by_key(|(note, _)| (note.asset_id(), u128::from(note.amount())));
+    let mut fee_notes = fee_notes.into_iter().peekable();
+    let mut other_notes = other_notes.into_iter().peekable();
+
+    let mut chunks = Vec::new();
+    while fee_notes.peek().is_some() || other_notes.peek().is_some() {
+        let mut chunk = Vec::new();
+
+        // First take enough of the fee's asset to pay this transaction's fee...
+        let mut funding = Amount::zero();
+        while funding < fee.amount() && chunk.len() < max_spends {
+            match fee_notes.next() {
+                Some((note, position)) => {
+                    funding = funding + note.amount();
+                    chunk.push((note, position));
+                }
+                None => break,
+            }
+        }
+        if funding < fee.amount() {
+            anyhow::bail!(
+                "not enough {} to pay the fee of every transaction spending all notes",
+                fee.asset_id()
+            );
+        }
+
+        // ... then fill the rest of the transaction with other notes, and only once those are
+        // spent, with the remaining notes of the fee's asset.
+        while chunk.len() < max_spends {
+            match other_notes.next().or_else(|| fee_notes.next()) {
+                Some(note) => chunk.push(note),
+                None => break,
+            }
+        }
+
+        chunks.push(chunk);
+    }
+
+    Ok(chunks)
+}
+
+/// Split `amount` into `count` non-zero parts of random size, which sum to `amount`.
+///
+/// The parts are the gaps between `count - 1` distinct cut points, chosen uniformly at random.
+fn split_amount<R: Rng>(rng: &mut R, amount: Amount, count: usize) -> Vec<Amount> {
+    let total = u128::from(amount);
+    debug_assert!(count >= 1 && count as u128 <= total);
+
+    let mut cuts = BTreeSet::new();
+    while cuts.len() + 1 < count {
+        cuts.insert(rng.gen_range(1..total));
+    }
+    cuts.insert(total);
+
+    let mut last = 0;
+    cuts.into_iter()
+        .map(|cut| {
+            let part = Amount::from(cut - last);
+            last = cut;
+            part
+        })
+        .collect()
+}
+
+/// Generate a note of `amount` of `asset_id` to `address`, at position 0, for tests.
+#[cfg(test)]
+pub(crate) fn test_note<R: Rng + CryptoRng>(
+    rng: &mut R,
+    address: &Address,
+    amount: u64,
+    asset_id: asset::Id,
+) -> (Note, tct::Position) {
+    let value = Value {
+        amount: amount.into(),
+        asset_id,
+    };
+    (
+        Note::generate(rng, address, value),
+        tct::Position::from(0u64),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_amount_sums_to_total() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        for (total, count) in [(1u64, 1), (2, 2), (5, 5), (1000, 4), (u64::MAX, 3)] {
+            let parts = split_amount(&mut rng, total.into(), count);
+            assert_eq!(parts.len(), count);
+            assert!(parts.iter().all(|part| *part > Amount::zero()));
+            assert_eq!(
+                parts.into_iter().map(u128::from).sum::<u128>(),
+                total as u128
+            );
+        }
+    }
+
+    #[test]
+    fn split_amount_is_deterministic() {
+        let split = |seed| split_amount(&mut ChaCha20Rng::from_seed(seed), 1000u64.into(), 4);
+        assert_eq!(split([2u8; 32]), split([2u8; 32]));
+        assert_ne!(split([2u8; 32]), split([3u8; 32]));
+    }
+
+    #[test]
+    fn spend_all_chunks_pay_every_fee() {
+        let mut rng = ChaCha20Rng::from_seed([4u8; 32]);
+        let address = Address::dummy(&mut rng);
+        let gm = asset::REGISTRY.parse_denom("ugm").unwrap().id();
+        let mut note = |amount, asset_id| test_note(&mut rng, &address, amount, asset_id);
+
+        let fee = Fee::from_staking_token_amount(10u64.into());
+        let mut notes = (0..5).map(|_| note(1, gm)).collect::<Vec<_>>();
+        notes.push(note(15, *STAKING_TOKEN_ASSET_ID));
+        notes.push(note(10, *STAKING_TOKEN_ASSET_ID));
+
+        let chunks = spend_all_chunks(notes.clone(), &fee, 4).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.iter().map(Vec::len).sum::<usize>(), notes.len());
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 4));
+        for chunk in &chunks {
+            let funding = chunk
+                .iter()
+                .filter(|(note, _)| note.asset_id() == fee.asset_id())
+                .map(|(note, _)| u128::from(note.amount()))
+                .sum::<u128>();
+            assert!(funding >= 10);
+        }
+
+        // With two spends per transaction, the five other notes need five transactions, but there
+        // are only two notes to pay their fees.
+        assert!(spend_all_chunks(notes.clone(), &fee, 2).is_err());
+
+        // A single spend leaves no room for the fee note and another note.
+        assert!(spend_all_chunks(notes, &fee, 1).is_err());
+    }
+
+    #[test]
+    fn split_count_respects_limits() {
+        assert_eq!(split_count(Amount::zero(), 4, 10), 0);
+        assert_eq!(split_count(2u64.into(), 4, 10), 2);
+        assert_eq!(split_count(1000u64.into(), 4, 10), 4);
+        assert_eq!(split_count(1000u64.into(), 4, 1), 2);
+        assert_eq!(split_count(1000u64.into(), 4, 0), 1);
+    }
 }